@@ -0,0 +1,79 @@
+//! Streams a 10 million triangle surface from a chunk cache while orbiting
+//! low over it, and reports the memory held and the longest frames:
+//!
+//!     cargo run --release --example stream
+
+use std::time::{Duration, Instant};
+
+use engine::chunked::{ChunkCache, TRIANGLES_PER_CHUNK};
+use engine::mesh::Color;
+use engine::mesh::quad::QuadMesh;
+use engine::{Camera, Light, LightKind, World};
+use nalgebra::{Perspective3, Point3, UnitQuaternion, Vector3};
+
+const WIDTH: u32 = 500;
+const HEIGHT: u32 = 500;
+/// 2 * 2237^2, just over 10 million triangles.
+const DIVISIONS: usize = 2237;
+const BUDGET: usize = 64 << 20;
+const FRAMES: u32 = 600;
+
+fn main() {
+    let path = std::env::temp_dir().join("shapes-stream-example.chunks");
+    let start = Instant::now();
+    let cache = {
+        let surface = QuadMesh::grid(200.0, 200.0, DIVISIONS, Color::rgb(120, 150, 110));
+        ChunkCache::build(&surface, TRIANGLES_PER_CHUNK, &path).expect("cache written")
+    };
+    println!(
+        "{} triangles in {} chunks, cached in {:.1} s",
+        cache.triangles(),
+        cache.chunks().len(),
+        start.elapsed().as_secs_f64()
+    );
+
+    let mut world = World::new(
+        Camera::looking_at(Point3::new(0.0, 0.0, -10.0), Point3::origin()),
+        Light {
+            position: Point3::new(-1.0, 1.0, -1.0),
+            target: Point3::origin(),
+            kind: LightKind::Directional,
+            intensity: 1.0,
+            shadow_resolution: 0,
+            shadow_bias: 0.05,
+        },
+        Perspective3::new(WIDTH as f32 / HEIGHT as f32, 1.0, 0.1, 30.0).to_homogeneous(),
+        Vec::new(),
+    );
+    world.threads = std::thread::available_parallelism().map_or(1, |n| n.get());
+    // Lay the surface down as ground.
+    let flat = UnitQuaternion::from_axis_angle(&Vector3::x_axis(), std::f32::consts::FRAC_PI_2);
+    world.load_chunked(cache, flat.to_homogeneous(), BUDGET);
+
+    let mut frame = vec![0; (WIDTH * HEIGHT * 4) as usize];
+    let mut frame_times = Vec::with_capacity(FRAMES as usize);
+    let (mut peak, mut approximate) = (0, 0);
+    for i in 0..FRAMES {
+        let angle = i as f32 / FRAMES as f32 * std::f32::consts::TAU;
+        let focus = Point3::new(60.0 * angle.cos(), 0.0, 60.0 * angle.sin());
+        world.camera = Camera::looking_at(focus + Vector3::new(0.0, 8.0, -12.0), focus);
+        let started = Instant::now();
+        world.stream_chunks();
+        world.draw(world.camera.generate_view_mat(), &mut frame, WIDTH, HEIGHT);
+        frame_times.push(started.elapsed());
+        peak = peak.max(world.streamed_bytes());
+        approximate += world.stats().approximate as u32;
+    }
+    frame_times.sort();
+    let median = frame_times[frame_times.len() / 2];
+    let longest = frame_times.last().copied().unwrap_or(Duration::ZERO);
+    println!(
+        "at most {:.1} of {:.1} MiB held; frames {:.2} ms median, {:.2} ms longest; \
+         {approximate} of {FRAMES} frames still had chunks coming in",
+        peak as f64 / (1 << 20) as f64,
+        BUDGET as f64 / (1 << 20) as f64,
+        median.as_secs_f64() * 1000.0,
+        longest.as_secs_f64() * 1000.0,
+    );
+    let _ = std::fs::remove_file(&path);
+}
@@ -0,0 +1,67 @@
+//! Renders a 128x128 noise terrain headless and reports the time per frame.
+//! A stress test for the rasterizer and triangle sorting:
+//!
+//!     cargo run --release --example terrain
+
+use std::time::Instant;
+
+use engine::mesh::Color;
+use engine::mesh::terrain::{TerrainMesh, noise_heightmap};
+use engine::{Camera, Light, LightKind, Object, World};
+use nalgebra::{Perspective3, Point3, Vector3};
+
+const WIDTH: u32 = 500;
+const HEIGHT: u32 = 500;
+const SIZE: usize = 128;
+const FRAMES: u32 = 20;
+
+/// Water, grass and rock bands by altitude.
+fn altitude_color(height: f32) -> Color {
+    let (r, g, b) = match height {
+        h if h < 0.35 => (40, 90, 200),
+        h if h < 0.65 => (70, 150, 60),
+        _ => (130, 120, 110),
+    };
+    Color { r, g, b, a: 255 }
+}
+
+fn main() {
+    let heights = noise_heightmap(SIZE, SIZE, 0.05, 4, 7);
+    let terrain = TerrainMesh::from_heightmap(
+        SIZE,
+        SIZE,
+        &heights,
+        Vector3::new(0.1, 3.0, 0.1),
+        altitude_color,
+    )
+    .expect("terrain is at least 2x2");
+
+    let mut world = World::new(
+        Camera::looking_at(Point3::new(0.0, 6.0, -9.0), Point3::new(0.0, 0.0, 0.0)),
+        Light {
+            position: Point3::new(-1.0, 1.0, -1.0),
+            target: Point3::new(0.0, 0.0, 0.0),
+            kind: LightKind::Directional,
+            intensity: 1.0,
+            shadow_resolution: 0,
+            shadow_bias: 0.05,
+        },
+        Perspective3::new(WIDTH as f32 / HEIGHT as f32, 1.0, 0.1, 200.0).to_homogeneous(),
+        vec![Object::new(terrain, 0.0, 0.0, 0.0)],
+    );
+    world.threads = std::thread::available_parallelism().map_or(1, |n| n.get());
+
+    let view = world.camera.generate_view_mat();
+    let mut frame = vec![0; (WIDTH * HEIGHT * 4) as usize];
+    let start = Instant::now();
+    for _ in 0..FRAMES {
+        world.draw(view, &mut frame, WIDTH, HEIGHT);
+    }
+    let stats = world.stats();
+    println!(
+        "{:.2} ms per frame, {} of {} triangles drawn",
+        start.elapsed().as_secs_f64() * 1000.0 / FRAMES as f64,
+        stats.triangles_rasterized,
+        stats.triangles_submitted
+    );
+}
@@ -0,0 +1,55 @@
+//! Simple procedural motion for objects, advanced by [`World::update`].
+//!
+//! [`World::update`]: crate::World::update
+
+use nalgebra::{Point3, Unit, UnitQuaternion, Vector3};
+
+use crate::world::Object;
+
+/// How an object moves over time. Speeds are in radians per second.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Animation {
+    /// Rotate in place around `axis`, through the object's origin.
+    Spin {
+        axis: Unit<Vector3<f32>>,
+        speed: f32,
+    },
+    /// Circle `center` at `radius` in the horizontal plane, starting on the
+    /// +x side when the world clock is zero.
+    Orbit {
+        center: Point3<f32>,
+        radius: f32,
+        speed: f32,
+    },
+    /// Move up and down by up to `amplitude` around the starting height.
+    Bob { amplitude: f32, speed: f32 },
+}
+
+impl Animation {
+    /// Move `object` from world time `time` to `time + dt`.
+    pub(crate) fn step(&self, object: &mut Object, time: f32, dt: f32) {
+        match *self {
+            Animation::Spin { axis, speed } => {
+                object.rotation =
+                    UnitQuaternion::from_axis_angle(&axis, speed * dt) * object.rotation;
+            }
+            Animation::Orbit {
+                center,
+                radius,
+                speed,
+            } => {
+                let angle = speed * (time + dt);
+                object.offset_x = center.x + radius * angle.cos();
+                object.offset_y = center.y;
+                object.offset_z = center.z + radius * angle.sin();
+            }
+            Animation::Bob { amplitude, speed } => {
+                // Apply the change in height so it composes with other edits
+                // to the offset.
+                let before = (speed * time).sin();
+                let after = (speed * (time + dt)).sin();
+                object.offset_y += amplitude * (after - before);
+            }
+        }
+    }
+}
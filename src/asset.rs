@@ -0,0 +1,63 @@
+//! Where loaders get the files a model refers to (an OBJ's `mtllib`, ...).
+//!
+//! Nothing in the library path touches the filesystem unless it is handed a
+//! [`DirResolver`], so assets can just as well come from `include_bytes!`.
+
+use std::collections::HashMap;
+use std::io;
+use std::path::PathBuf;
+
+/// Resolves a path referenced from inside an asset to its bytes.
+pub trait AssetResolver {
+    fn load(&self, path: &str) -> io::Result<Vec<u8>>;
+}
+
+impl<F: Fn(&str) -> io::Result<Vec<u8>>> AssetResolver for F {
+    fn load(&self, path: &str) -> io::Result<Vec<u8>> {
+        self(path)
+    }
+}
+
+/// Resolves paths relative to a directory on disk.
+pub struct DirResolver(pub PathBuf);
+
+impl AssetResolver for DirResolver {
+    fn load(&self, path: &str) -> io::Result<Vec<u8>> {
+        std::fs::read(self.0.join(path))
+    }
+}
+
+/// Resolves paths from an in-memory table, e.g. filled with `include_bytes!`.
+#[derive(Default)]
+pub struct MemoryResolver {
+    files: HashMap<String, &'static [u8]>,
+}
+
+impl MemoryResolver {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with(mut self, path: &str, bytes: &'static [u8]) -> Self {
+        self.files.insert(path.to_string(), bytes);
+        self
+    }
+}
+
+impl AssetResolver for MemoryResolver {
+    fn load(&self, path: &str) -> io::Result<Vec<u8>> {
+        self.files
+            .get(path)
+            .map(|bytes| bytes.to_vec())
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, path.to_string()))
+    }
+}
+
+/// A resolver that has nothing, for self-contained assets.
+pub struct NoAssets;
+
+impl AssetResolver for NoAssets {
+    fn load(&self, path: &str) -> io::Result<Vec<u8>> {
+        Err(io::Error::new(io::ErrorKind::NotFound, path.to_string()))
+    }
+}
@@ -0,0 +1,26 @@
+//! Flat squares that always face the camera, for markers and particles
+//! that need no modelled geometry.
+
+use nalgebra::Point3;
+
+use crate::mesh::Color;
+
+/// A camera-facing square of side `size` world units centred on `position`.
+/// Billboards are unlit: they show `color` as is, apart from fog, and are
+/// painted in depth order with everything else.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Billboard {
+    pub position: Point3<f32>,
+    pub size: f32,
+    pub color: Color,
+}
+
+impl Billboard {
+    pub fn new(position: Point3<f32>, size: f32, color: Color) -> Self {
+        Self {
+            position,
+            size,
+            color,
+        }
+    }
+}
@@ -0,0 +1,217 @@
+//! Saved camera viewpoints, flown back to smoothly, for setting up the
+//! same shot again and again.
+//!
+//! Bookmarks save as plain text, one per line: the slot counting from 1,
+//! the position, the orientation as a quaternion and the vertical field of
+//! view in radians.
+//!
+//! ```text
+//! # slot  position  orientation (i j k w)  fovy
+//! 1 0 0 -5 0 0 0 1 1
+//! ```
+
+use std::fmt::{self, Write as _};
+use std::io;
+use std::path::Path;
+
+use nalgebra::{Point3, Quaternion, UnitQuaternion};
+
+use crate::camera::Camera;
+
+/// How many bookmarks there are slots for.
+pub const BOOKMARK_SLOTS: usize = 9;
+
+/// Why saved bookmarks could not be loaded.
+#[derive(Debug)]
+pub enum BookmarkError {
+    Io(io::Error),
+    Parse { line: usize, message: String },
+}
+
+impl fmt::Display for BookmarkError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BookmarkError::Io(err) => write!(f, "{err}"),
+            BookmarkError::Parse { line, message } => write!(f, "line {line}: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for BookmarkError {}
+
+impl From<io::Error> for BookmarkError {
+    fn from(err: io::Error) -> Self {
+        BookmarkError::Io(err)
+    }
+}
+
+/// Everything about the view a bookmark brings back: where the camera is,
+/// which way it faces, and how wide it sees.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CameraPose {
+    pub position: Point3<f32>,
+    pub orientation: UnitQuaternion<f32>,
+    /// Vertical field of view in radians.
+    pub fovy: f32,
+}
+
+impl CameraPose {
+    /// `camera`'s pose, seen with a vertical field of view of `fovy`.
+    pub fn of(camera: &Camera, fovy: f32) -> Self {
+        Self {
+            position: camera.position,
+            orientation: camera.orientation,
+            fovy,
+        }
+    }
+
+    /// The pose `t` of the way from `self` to `other`: positions and
+    /// fields of view blended straight, orientations along the shorter
+    /// arc between them.
+    pub fn lerp(&self, other: &CameraPose, t: f32) -> CameraPose {
+        let t = t.clamp(0.0, 1.0);
+        // Facing exactly opposite ways, no arc is shorter: turn at the end.
+        let orientation = self
+            .orientation
+            .try_slerp(&other.orientation, t, 1e-6)
+            .unwrap_or(if t < 1.0 {
+                self.orientation
+            } else {
+                other.orientation
+            });
+        CameraPose {
+            position: self.position + (other.position - self.position) * t,
+            orientation,
+            fovy: self.fovy + (other.fovy - self.fovy) * t,
+        }
+    }
+}
+
+/// Slow out, slow in: `t` from 0 to 1 mapped onto a curve with no speed
+/// at either end.
+pub fn ease_in_out(t: f32) -> f32 {
+    let t = t.clamp(0.0, 1.0);
+    t * t * (3.0 - 2.0 * t)
+}
+
+/// A camera on its way from one pose to another, eased at both ends.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CameraTransition {
+    from: CameraPose,
+    to: CameraPose,
+    duration: f32,
+    elapsed: f32,
+}
+
+impl CameraTransition {
+    /// Go from `from` to `to` in `duration` seconds.
+    pub fn new(from: CameraPose, to: CameraPose, duration: f32) -> Self {
+        Self {
+            from,
+            to,
+            duration: duration.max(0.0),
+            elapsed: 0.0,
+        }
+    }
+
+    /// Where the camera is headed.
+    pub fn target(&self) -> &CameraPose {
+        &self.to
+    }
+
+    /// Move `dt` seconds further along, returning the pose there.
+    pub fn advance(&mut self, dt: f32) -> CameraPose {
+        self.elapsed = (self.elapsed + dt).min(self.duration);
+        self.pose()
+    }
+
+    /// The pose at the time reached so far.
+    pub fn pose(&self) -> CameraPose {
+        if self.is_finished() {
+            return self.to;
+        }
+        self.from
+            .lerp(&self.to, ease_in_out(self.elapsed / self.duration))
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.elapsed >= self.duration
+    }
+}
+
+/// Parse saved bookmarks, one `slot px py pz qi qj qk qw fovy` line per
+/// bookmark, into [`BOOKMARK_SLOTS`] slots. Blank lines and lines starting
+/// with `#` are skipped.
+pub fn parse_bookmarks(text: &str) -> Result<Vec<Option<CameraPose>>, BookmarkError> {
+    let mut bookmarks = vec![None; BOOKMARK_SLOTS];
+    for (number, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let parse_error = |message: String| BookmarkError::Parse {
+            line: number + 1,
+            message,
+        };
+        let values: Vec<f32> = line
+            .split_whitespace()
+            .map(str::parse)
+            .collect::<Result<_, _>>()
+            .map_err(|err| parse_error(format!("bad number: {err}")))?;
+        let &[slot, px, py, pz, qi, qj, qk, qw, fovy] = values.as_slice() else {
+            return Err(parse_error(format!(
+                "expected 9 numbers, found {}",
+                values.len()
+            )));
+        };
+        if slot.fract() != 0.0 || !(1.0..=BOOKMARK_SLOTS as f32).contains(&slot) {
+            return Err(parse_error(format!(
+                "slot {slot} is not from 1 to {BOOKMARK_SLOTS}"
+            )));
+        }
+        let quaternion = Quaternion::new(qw, qi, qj, qk);
+        if !quaternion.norm().is_normal() {
+            return Err(parse_error("orientation has no length".to_string()));
+        }
+        bookmarks[slot as usize - 1] = Some(CameraPose {
+            position: Point3::new(px, py, pz),
+            orientation: UnitQuaternion::from_quaternion(quaternion),
+            fovy,
+        });
+    }
+    Ok(bookmarks)
+}
+
+/// `bookmarks` in the format [`parse_bookmarks`] reads, slot 1 first.
+pub fn format_bookmarks(bookmarks: &[Option<CameraPose>]) -> String {
+    let mut text = "# slot  position  orientation (i j k w)  fovy\n".to_string();
+    for (slot, pose) in bookmarks.iter().enumerate() {
+        let Some(pose) = pose else {
+            continue;
+        };
+        let (p, q) = (pose.position, pose.orientation.coords);
+        let _ = writeln!(
+            text,
+            "{} {} {} {} {} {} {} {} {}",
+            slot + 1,
+            p.x,
+            p.y,
+            p.z,
+            q.x,
+            q.y,
+            q.z,
+            q.w,
+            pose.fovy
+        );
+    }
+    text
+}
+
+/// Load bookmarks saved by [`save_bookmarks`].
+pub fn load_bookmarks(path: impl AsRef<Path>) -> Result<Vec<Option<CameraPose>>, BookmarkError> {
+    parse_bookmarks(&std::fs::read_to_string(path)?)
+}
+
+pub fn save_bookmarks(path: impl AsRef<Path>, bookmarks: &[Option<CameraPose>]) -> io::Result<()> {
+    std::fs::write(path, format_bookmarks(bookmarks))
+}
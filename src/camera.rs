@@ -0,0 +1,150 @@
+use nalgebra::{Matrix4, Point3, Unit, UnitQuaternion, Vector3};
+
+/// Closest an orbiting camera gets to its focus.
+pub const MIN_ORBIT_DISTANCE: f32 = 0.5;
+
+/// Pitch stays this far short of straight up or down so yaw, which turns
+/// around the world's up axis, never spins the view in place.
+const MAX_PITCH: f32 = std::f32::consts::FRAC_PI_2 - 0.01;
+
+/// How mouse and movement input steer the [`Camera`].
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum CameraMode {
+    /// Turn in place and walk around.
+    #[default]
+    Fly,
+    /// Circle `focus` at `distance`, always looking at it.
+    Orbit { focus: Point3<f32>, distance: f32 },
+}
+
+/// A camera at `position`, turned by `orientation` and steered according to
+/// its [`CameraMode`].
+///
+/// Unrotated, the camera looks along +z with +y up. Turning is applied to
+/// the quaternion directly, so repeated small turns don't drift the way
+/// rebuilding the view from accumulated angles does.
+pub struct Camera {
+    pub position: Point3<f32>,
+    pub orientation: UnitQuaternion<f32>,
+    pub mode: CameraMode,
+}
+
+impl Camera {
+    /// A flying camera at `position` looking at `target`, level with the
+    /// horizon.
+    pub fn looking_at(position: Point3<f32>, target: Point3<f32>) -> Self {
+        let mut camera = Self {
+            position,
+            orientation: UnitQuaternion::identity(),
+            mode: CameraMode::Fly,
+        };
+        camera.look_at(target);
+        camera
+    }
+
+    /// Turn to face `target` without rolling. Looking straight up or down is
+    /// clamped short of the pole; a target at the camera is ignored.
+    pub fn look_at(&mut self, target: Point3<f32>) {
+        let Some(direction) = (target - self.position).try_normalize(f32::EPSILON) else {
+            return;
+        };
+        let yaw = direction.x.atan2(direction.z);
+        let pitch = direction.y.asin().clamp(-MAX_PITCH, MAX_PITCH);
+        self.orientation = UnitQuaternion::from_axis_angle(&Vector3::y_axis(), yaw)
+            * UnitQuaternion::from_axis_angle(&Vector3::x_axis(), -pitch);
+    }
+
+    /// Right-handed view matrix for the current position and orientation.
+    pub fn generate_view_mat(&self) -> Matrix4<f32> {
+        Matrix4::look_at_rh(&self.position, &self.target(), &self.up())
+    }
+
+    /// Unit vector the camera looks along.
+    pub fn forward(&self) -> Vector3<f32> {
+        self.orientation * Vector3::z()
+    }
+
+    /// Unit vector towards the right edge of the screen.
+    pub fn right(&self) -> Vector3<f32> {
+        self.forward().cross(&self.up())
+    }
+
+    /// Unit vector towards the top edge of the screen.
+    pub fn up(&self) -> Vector3<f32> {
+        self.orientation * Vector3::y()
+    }
+
+    /// A point one unit in front of the camera, or the focus when orbiting.
+    pub fn target(&self) -> Point3<f32> {
+        match self.mode {
+            CameraMode::Fly => self.position + self.forward(),
+            CameraMode::Orbit { focus, .. } => focus,
+        }
+    }
+
+    /// Heading around the world's up axis and elevation above the horizon,
+    /// in radians. Zero yaw looks along +z.
+    pub fn yaw_pitch(&self) -> (f32, f32) {
+        let forward = self.forward();
+        (
+            forward.x.atan2(forward.z),
+            forward.y.clamp(-1.0, 1.0).asin(),
+        )
+    }
+
+    /// Turn by `yaw` radians around the world's up axis and `pitch` radians
+    /// around the camera's right axis, positive pitch looking up. A flying
+    /// camera turns in place; an orbiting one moves around its focus.
+    pub fn rotate(&mut self, yaw: f32, pitch: f32) {
+        let (_, current) = self.yaw_pitch();
+        let pitch = (current + pitch).clamp(-MAX_PITCH, MAX_PITCH) - current;
+        let right = Unit::new_normalize(self.right());
+        self.orientation = UnitQuaternion::from_axis_angle(&Vector3::y_axis(), yaw)
+            * UnitQuaternion::from_axis_angle(&right, pitch)
+            * self.orientation;
+        self.orientation.renormalize_fast();
+        self.sync_orbit();
+    }
+
+    /// Start orbiting `focus` from where the camera is now, turning to face
+    /// it.
+    pub fn orbit_around(&mut self, focus: Point3<f32>) {
+        self.look_at(focus);
+        self.mode = CameraMode::Orbit {
+            focus,
+            distance: (focus - self.position).norm().max(MIN_ORBIT_DISTANCE),
+        };
+        self.sync_orbit();
+    }
+
+    /// Go back to flying from the current position and view.
+    pub fn fly(&mut self) {
+        self.mode = CameraMode::Fly;
+    }
+
+    /// Move an orbiting camera `amount` closer to its focus, or away for a
+    /// negative amount. Does nothing while flying.
+    pub fn dolly(&mut self, amount: f32) {
+        if let CameraMode::Orbit { distance, .. } = &mut self.mode {
+            *distance = (*distance - amount).max(MIN_ORBIT_DISTANCE);
+            self.sync_orbit();
+        }
+    }
+
+    /// Slide an orbiting camera and its focus `right` and `up` in the view
+    /// plane. Does nothing while flying.
+    pub fn pan(&mut self, right: f32, up: f32) {
+        let offset = self.right() * right + self.up() * up;
+        if let CameraMode::Orbit { focus, .. } = &mut self.mode {
+            *focus += offset;
+            self.sync_orbit();
+        }
+    }
+
+    /// Place an orbiting camera on its sphere, behind the focus.
+    fn sync_orbit(&mut self) {
+        if let CameraMode::Orbit { focus, distance } = self.mode {
+            self.position = focus - self.forward() * distance;
+        }
+    }
+}
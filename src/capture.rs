@@ -0,0 +1,132 @@
+//! Recording rendered frames for turning into image sequences.
+
+use std::collections::VecDeque;
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::thread::JoinHandle;
+
+use log::warn;
+
+use crate::output::ImageFormat;
+
+/// Why a frame could not be added to a [`Capture`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CaptureError {
+    /// The frame is not the size the capture started at.
+    SizeChanged {
+        expected: (u32, u32),
+        found: (u32, u32),
+    },
+}
+
+impl fmt::Display for CaptureError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CaptureError::SizeChanged { expected, found } => write!(
+                f,
+                "frame is {}x{}, capture started at {}x{}",
+                found.0, found.1, expected.0, expected.1
+            ),
+        }
+    }
+}
+
+impl std::error::Error for CaptureError {}
+
+/// The most recent frames of a recording, held in memory until saved.
+///
+/// At most `max_frames` frames are kept; once full, each new frame drops
+/// the oldest one, so memory stays bounded however long the capture runs.
+pub struct Capture {
+    width: u32,
+    height: u32,
+    max_frames: usize,
+    frames: VecDeque<Vec<u8>>,
+    dropped: usize,
+}
+
+impl Capture {
+    /// Start capturing `width * height` RGBA frames, keeping at most
+    /// `max_frames` (at least one).
+    pub fn new(width: u32, height: u32, max_frames: usize) -> Self {
+        let max_frames = max_frames.max(1);
+        Self {
+            width,
+            height,
+            max_frames,
+            frames: VecDeque::with_capacity(max_frames.min(1024)),
+            dropped: 0,
+        }
+    }
+
+    /// Copy one frame into the capture. Frames of any other size than the
+    /// capture started at are refused.
+    pub fn push(&mut self, frame: &[u8], width: u32, height: u32) -> Result<(), CaptureError> {
+        if (width, height) != (self.width, self.height)
+            || frame.len() != (width * height * 4) as usize
+        {
+            return Err(CaptureError::SizeChanged {
+                expected: (self.width, self.height),
+                found: (width, height),
+            });
+        }
+        if self.frames.len() == self.max_frames {
+            if self.dropped == 0 {
+                warn!(
+                    "capture is full at {} frames, dropping the oldest",
+                    self.max_frames
+                );
+            }
+            self.frames.pop_front();
+            self.dropped += 1;
+        }
+        self.frames.push_back(frame.to_vec());
+        Ok(())
+    }
+
+    /// Frames currently held.
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    /// Frames dropped to stay within `max_frames`.
+    pub fn dropped(&self) -> usize {
+        self.dropped
+    }
+
+    /// Write every `skip`th frame into `dir` in `format`, as
+    /// `frame_00000.png`, `frame_00001.png`, ... for PNG, creating the
+    /// directory if needed. Returns the number of files written.
+    pub fn write_sequence(
+        &self,
+        dir: impl AsRef<Path>,
+        skip: usize,
+        format: ImageFormat,
+    ) -> io::Result<usize> {
+        let dir = dir.as_ref();
+        std::fs::create_dir_all(dir)?;
+        let mut written = 0;
+        for frame in self.frames.iter().step_by(skip.max(1)) {
+            let path = dir.join(format!("frame_{written:05}.{}", format.extension()));
+            std::fs::write(path, format.encode(frame, self.width, self.height))?;
+            written += 1;
+        }
+        Ok(written)
+    }
+
+    /// [`Capture::write_sequence`] on its own thread, so encoding and disk
+    /// writes don't hold up rendering.
+    pub fn save_in_background(
+        self,
+        dir: PathBuf,
+        skip: usize,
+        format: ImageFormat,
+    ) -> JoinHandle<io::Result<usize>> {
+        std::thread::spawn(move || self.write_sequence(dir, skip, format))
+    }
+}
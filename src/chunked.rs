@@ -0,0 +1,589 @@
+//! Meshes too big to keep in memory whole, cut into chunks kept in a cache
+//! file and streamed in as the camera comes near them.
+//!
+//! [`ChunkCache::build`] splits a mesh into chunks of neighbouring
+//! triangles, halving the longest side of their bounds until each chunk is
+//! small enough, and writes them to a file. [`World::load_chunked`] then
+//! adds an object per chunk, drawn as a see-through box of its bounds
+//! until [`World::stream_chunks`] has it read on a thread of its own. Only
+//! the bounds of every chunk stay in memory, plus as many chunks as fit in
+//! the stream's budget; the least recently seen go first to make room.
+//!
+//! The cache file is little-endian: [`MAGIC`], the chunk count as `u32`,
+//! then per chunk its bounds as six `f32`, the byte offset of its geometry
+//! as `u64` and its vertex and triangle counts as `u32`. The geometry of
+//! each chunk follows: vertices as three `f32`, then triangles as three
+//! `u32` indices into the chunk's vertices and four bytes of RGBA.
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+use std::mem::size_of;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+
+use log::error;
+use nalgebra::{Matrix4, Point3};
+
+use crate::mesh::cube::CubeMesh;
+use crate::mesh::{Color, Mesh, Triangle};
+use crate::pipeline::{aabb_intersects_frustum, extract_frustum_planes};
+use crate::world::{Object, PLACEHOLDER_COLOR, World};
+
+/// First bytes of a chunk cache file, with the format's version.
+pub const MAGIC: &[u8; 8] = b"SHCHUNK1";
+
+/// Triangles per chunk that keep a chunk quick to read while leaving few
+/// enough chunks that culling them costs little.
+pub const TRIANGLES_PER_CHUNK: usize = 16384;
+
+/// Chunk reads a stream has waiting on its thread at most, so requests
+/// don't pile up behind a camera that has moved on.
+pub const MAX_IN_FLIGHT: usize = 4;
+
+/// Bytes of the table entry for one chunk.
+const ENTRY_SIZE: usize = 6 * 4 + 8 + 4 + 4;
+/// Bytes of one vertex and one triangle of chunk geometry.
+const VERTEX_SIZE: usize = 3 * 4;
+const TRIANGLE_SIZE: usize = 3 * 4 + 4;
+
+/// Where one chunk's geometry is and how big it is.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChunkInfo {
+    /// Corners of the box around the chunk's vertices, in the mesh's
+    /// space.
+    pub min: Point3<f32>,
+    pub max: Point3<f32>,
+    pub vertices: usize,
+    pub triangles: usize,
+    /// Byte offset of the geometry in the cache file.
+    offset: u64,
+}
+
+impl ChunkInfo {
+    /// Memory the chunk takes once read, counted against a stream's
+    /// budget.
+    pub fn bytes(&self) -> usize {
+        self.vertices * size_of::<Point3<f32>>() + self.triangles * size_of::<Triangle>()
+    }
+}
+
+/// The chunk table of a cache file, enough to place every chunk without
+/// reading any.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChunkCache {
+    path: PathBuf,
+    chunks: Vec<ChunkInfo>,
+}
+
+impl ChunkCache {
+    /// Split `mesh` into chunks of at most `triangles_per_chunk` triangles
+    /// and write them to `path`. Every triangle goes to the chunk its
+    /// centroid falls in, so chunks keep whole triangles and their bounds
+    /// may overlap a little.
+    pub fn build(
+        mesh: &dyn Mesh,
+        triangles_per_chunk: usize,
+        path: impl AsRef<Path>,
+    ) -> io::Result<Self> {
+        let path = path.as_ref();
+        let verts = mesh.verts();
+        let tris: Vec<Triangle> = mesh.primitives().triangles().collect();
+        let centroids: Vec<Point3<f32>> = tris
+            .iter()
+            .map(|tri| {
+                Point3::from(
+                    (verts[tri.v1].coords + verts[tri.v2].coords + verts[tri.v3].coords) / 3.0,
+                )
+            })
+            .collect();
+        let mut leaves = Vec::new();
+        split(
+            (0..tris.len()).collect(),
+            &centroids,
+            triangles_per_chunk.max(1),
+            &mut leaves,
+        );
+
+        let mut file = BufWriter::new(File::create(path)?);
+        file.write_all(MAGIC)?;
+        file.write_all(&(leaves.len() as u32).to_le_bytes())?;
+        let table_start = (MAGIC.len() + 4) as u64;
+        let mut offset = table_start + (leaves.len() * ENTRY_SIZE) as u64;
+        // The table goes first, so every chunk is laid out before any is
+        // written. Each chunk's geometry is gathered once for the table and
+        // again for writing rather than keeping a second copy of the mesh.
+        let mut chunks = Vec::with_capacity(leaves.len());
+        for leaf in &leaves {
+            let (chunk_verts, chunk_tris) = chunk_geometry(leaf, &tris, verts);
+            let (min, max) = bounds(&chunk_verts);
+            let info = ChunkInfo {
+                min,
+                max,
+                vertices: chunk_verts.len(),
+                triangles: chunk_tris.len(),
+                offset,
+            };
+            offset += (info.vertices * VERTEX_SIZE + info.triangles * TRIANGLE_SIZE) as u64;
+            write_entry(&mut file, &info)?;
+            chunks.push(info);
+        }
+        for leaf in &leaves {
+            let (chunk_verts, chunk_tris) = chunk_geometry(leaf, &tris, verts);
+            for v in chunk_verts {
+                for c in [v.x, v.y, v.z] {
+                    file.write_all(&c.to_le_bytes())?;
+                }
+            }
+            for (indices, color) in chunk_tris {
+                for i in indices {
+                    file.write_all(&i.to_le_bytes())?;
+                }
+                file.write_all(&[color.r, color.g, color.b, color.a])?;
+            }
+        }
+        file.flush()?;
+        Ok(Self {
+            path: path.to_path_buf(),
+            chunks,
+        })
+    }
+
+    /// Read the chunk table of the cache file at `path`.
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        let path = path.as_ref();
+        let mut file = BufReader::new(File::open(path)?);
+        let mut magic = [0; 8];
+        file.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "not a chunk cache file",
+            ));
+        }
+        let count = read_u32(&mut file)? as usize;
+        let chunks = (0..count)
+            .map(|_| read_entry(&mut file))
+            .collect::<io::Result<_>>()?;
+        Ok(Self {
+            path: path.to_path_buf(),
+            chunks,
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn chunks(&self) -> &[ChunkInfo] {
+        &self.chunks
+    }
+
+    /// Triangles in every chunk together.
+    pub fn triangles(&self) -> usize {
+        self.chunks.iter().map(|chunk| chunk.triangles).sum()
+    }
+
+    /// Read the geometry of chunk `index` from the cache file.
+    pub fn read_chunk(&self, index: usize) -> io::Result<ChunkMesh> {
+        let info = self
+            .chunks
+            .get(index)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, format!("no chunk {index}")))?;
+        read_geometry(&mut File::open(&self.path)?, info)
+    }
+}
+
+/// Halve `triangles` across the longest side of their centroids' bounds
+/// until each part has at most `limit`, pushing the parts onto `leaves`.
+fn split(
+    mut triangles: Vec<usize>,
+    centroids: &[Point3<f32>],
+    limit: usize,
+    leaves: &mut Vec<Vec<usize>>,
+) {
+    if triangles.len() <= limit {
+        if !triangles.is_empty() {
+            leaves.push(triangles);
+        }
+        return;
+    }
+    let points: Vec<Point3<f32>> = triangles.iter().map(|&i| centroids[i]).collect();
+    let (min, max) = bounds(&points);
+    let axis = (max - min).imax();
+    let middle = triangles.len() / 2;
+    triangles.select_nth_unstable_by(middle, |&a, &b| {
+        centroids[a][axis].total_cmp(&centroids[b][axis])
+    });
+    let upper = triangles.split_off(middle);
+    split(triangles, centroids, limit, leaves);
+    split(upper, centroids, limit, leaves);
+}
+
+/// A chunk's triangle as written: its corners in the chunk's vertices, and
+/// its color.
+type ChunkTriangle = ([u32; 3], Color);
+
+/// The triangles of `tris` at `leaf`, with the vertices they use renumbered
+/// from zero in the order they are first used.
+fn chunk_geometry(
+    leaf: &[usize],
+    tris: &[Triangle],
+    verts: &[Point3<f32>],
+) -> (Vec<Point3<f32>>, Vec<ChunkTriangle>) {
+    let mut local: HashMap<usize, u32> = HashMap::new();
+    let mut chunk_verts = Vec::new();
+    let mut chunk_tris = Vec::with_capacity(leaf.len());
+    for &index in leaf {
+        let tri = tris[index];
+        let corners = [tri.v1, tri.v2, tri.v3].map(|v| {
+            *local.entry(v).or_insert_with(|| {
+                chunk_verts.push(verts[v]);
+                (chunk_verts.len() - 1) as u32
+            })
+        });
+        chunk_tris.push((corners, tri.color));
+    }
+    (chunk_verts, chunk_tris)
+}
+
+fn bounds(points: &[Point3<f32>]) -> (Point3<f32>, Point3<f32>) {
+    let Some((first, rest)) = points.split_first() else {
+        return (Point3::origin(), Point3::origin());
+    };
+    rest.iter()
+        .fold((*first, *first), |(min, max), p| (min.inf(p), max.sup(p)))
+}
+
+fn write_entry(out: &mut impl Write, info: &ChunkInfo) -> io::Result<()> {
+    for c in [
+        info.min.x, info.min.y, info.min.z, info.max.x, info.max.y, info.max.z,
+    ] {
+        out.write_all(&c.to_le_bytes())?;
+    }
+    out.write_all(&info.offset.to_le_bytes())?;
+    out.write_all(&(info.vertices as u32).to_le_bytes())?;
+    out.write_all(&(info.triangles as u32).to_le_bytes())
+}
+
+fn read_entry(input: &mut impl Read) -> io::Result<ChunkInfo> {
+    let mut entry = [0; ENTRY_SIZE];
+    input.read_exact(&mut entry)?;
+    let f = |i: usize| f32::from_le_bytes(entry[i * 4..i * 4 + 4].try_into().unwrap());
+    let u = |at: usize| u32::from_le_bytes(entry[at..at + 4].try_into().unwrap()) as usize;
+    Ok(ChunkInfo {
+        min: Point3::new(f(0), f(1), f(2)),
+        max: Point3::new(f(3), f(4), f(5)),
+        offset: u64::from_le_bytes(entry[24..32].try_into().unwrap()),
+        vertices: u(32),
+        triangles: u(36),
+    })
+}
+
+fn read_u32(input: &mut impl Read) -> io::Result<u32> {
+    let mut bytes = [0; 4];
+    input.read_exact(&mut bytes)?;
+    Ok(u32::from_le_bytes(bytes))
+}
+
+fn read_geometry(file: &mut File, info: &ChunkInfo) -> io::Result<ChunkMesh> {
+    file.seek(SeekFrom::Start(info.offset))?;
+    let mut bytes = vec![0; info.vertices * VERTEX_SIZE + info.triangles * TRIANGLE_SIZE];
+    file.read_exact(&mut bytes)?;
+    let (vertex_bytes, triangle_bytes) = bytes.split_at(info.vertices * VERTEX_SIZE);
+    let f = |b: &[u8]| f32::from_le_bytes(b.try_into().unwrap());
+    let verts = vertex_bytes
+        .chunks_exact(VERTEX_SIZE)
+        .map(|v| Point3::new(f(&v[0..4]), f(&v[4..8]), f(&v[8..12])))
+        .collect();
+    let u = |b: &[u8]| u32::from_le_bytes(b.try_into().unwrap()) as usize;
+    let tris = triangle_bytes
+        .chunks_exact(TRIANGLE_SIZE)
+        .map(|t| Triangle {
+            v1: u(&t[0..4]),
+            v2: u(&t[4..8]),
+            v3: u(&t[8..12]),
+            color: Color {
+                r: t[12],
+                g: t[13],
+                b: t[14],
+                a: t[15],
+            },
+        })
+        .collect();
+    let mesh = ChunkMesh { verts, tris };
+    mesh.validate()
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err.to_string()))?;
+    Ok(mesh)
+}
+
+/// The geometry of one chunk, read from a cache file.
+#[derive(Debug)]
+pub struct ChunkMesh {
+    verts: Vec<Point3<f32>>,
+    tris: Vec<Triangle>,
+}
+
+impl Mesh for ChunkMesh {
+    fn verts(&self) -> &[Point3<f32>] {
+        &self.verts
+    }
+
+    fn tris(&self) -> &Vec<Triangle> {
+        &self.tris
+    }
+}
+
+/// The see-through box a chunk is drawn as until it is read.
+#[derive(Debug)]
+struct ChunkBounds {
+    verts: Vec<Point3<f32>>,
+    tris: Vec<Triangle>,
+}
+
+impl ChunkBounds {
+    fn new(info: &ChunkInfo) -> Self {
+        let cube = CubeMesh::spanning(info.min, info.max, PLACEHOLDER_COLOR);
+        Self {
+            verts: cube.verts().to_vec(),
+            tris: cube.tris().clone(),
+        }
+    }
+}
+
+impl Mesh for ChunkBounds {
+    fn verts(&self) -> &[Point3<f32>] {
+        &self.verts
+    }
+
+    fn tris(&self) -> &Vec<Triangle> {
+        &self.tris
+    }
+
+    fn placeholder(&self) -> bool {
+        true
+    }
+}
+
+/// Where a chunk of a stream is.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Residency {
+    /// Only its bounds are known.
+    Bounds,
+    /// Being read.
+    Loading,
+    /// Read, and last in view on this frame of the stream.
+    Resident { last_seen: u64 },
+    /// Its read failed; it stays as bounds.
+    Failed,
+}
+
+/// A chunked mesh being streamed into a [`World`], see
+/// [`World::load_chunked`].
+pub(crate) struct ChunkStream {
+    chunks: Vec<ChunkInfo>,
+    /// Index in [`World::models`] of each chunk's object, `None` once it
+    /// has been removed.
+    objects: Vec<Option<usize>>,
+    residency: Vec<Residency>,
+    /// Most bytes of chunk geometry read or being read at once.
+    budget: usize,
+    /// Bytes of the chunks read or being read.
+    used: usize,
+    frame: u64,
+    requests: Sender<usize>,
+    arrivals: Receiver<(usize, io::Result<ChunkMesh>)>,
+}
+
+impl ChunkStream {
+    fn spawn(cache: ChunkCache, objects: Vec<Option<usize>>, budget: usize) -> Self {
+        let (requests, thread_requests) = mpsc::channel::<usize>();
+        let (thread_arrivals, arrivals) = mpsc::channel();
+        let thread_cache = cache.clone();
+        thread::spawn(move || {
+            let mut file = File::open(thread_cache.path());
+            // Runs until the stream, and with it the sender, is dropped.
+            for index in thread_requests {
+                let mesh = match &mut file {
+                    Ok(file) => read_geometry(file, &thread_cache.chunks[index]),
+                    Err(err) => Err(io::Error::new(err.kind(), err.to_string())),
+                };
+                if thread_arrivals.send((index, mesh)).is_err() {
+                    break;
+                }
+            }
+        });
+        Self {
+            residency: vec![Residency::Bounds; cache.chunks.len()],
+            chunks: cache.chunks,
+            objects,
+            budget,
+            used: 0,
+            frame: 0,
+            requests,
+            arrivals,
+        }
+    }
+
+    fn in_flight(&self) -> usize {
+        self.residency
+            .iter()
+            .filter(|&&state| state == Residency::Loading)
+            .count()
+    }
+
+    /// Put chunk `index` back to its bounds, freeing its memory.
+    fn evict(&mut self, index: usize, models: &mut [Object]) {
+        if let Some(model) = self.objects[index].and_then(|object| models.get_mut(object)) {
+            model.mesh = Rc::new(ChunkBounds::new(&self.chunks[index]));
+        }
+        self.used -= self.chunks[index].bytes();
+        self.residency[index] = Residency::Bounds;
+    }
+
+    /// The object of a chunk at `object` in [`World::models`] was removed.
+    pub(crate) fn object_removed(&mut self, object: usize) {
+        for (index, slot) in self.objects.iter_mut().enumerate() {
+            match *slot {
+                Some(i) if i == object => {
+                    *slot = None;
+                    if let Residency::Resident { .. } = self.residency[index] {
+                        self.used -= self.chunks[index].bytes();
+                        self.residency[index] = Residency::Failed;
+                    }
+                }
+                Some(i) if i > object => *slot = Some(i - 1),
+                _ => {}
+            }
+        }
+    }
+}
+
+impl World {
+    /// Add the mesh in `cache` as one object per chunk, placed by
+    /// `transform` like [`World::load_object_async`], and stream chunk
+    /// geometry in with [`World::stream_chunks`] while keeping at most
+    /// `budget` bytes of it, as counted by [`ChunkInfo::bytes`]. Returns
+    /// the chunks' object indices, in the cache's order.
+    pub fn load_chunked(
+        &mut self,
+        cache: ChunkCache,
+        transform: Matrix4<f32>,
+        budget: usize,
+    ) -> Vec<usize> {
+        let objects: Vec<usize> = cache
+            .chunks()
+            .iter()
+            .map(|info| {
+                let mut object = Object::new(ChunkBounds::new(info), 0.0, 0.0, 0.0);
+                crate::world::set_transform(&mut object, &transform);
+                self.models.push(object);
+                self.models.len() - 1
+            })
+            .collect();
+        let slots = objects.iter().copied().map(Some).collect();
+        self.streams.push(ChunkStream::spawn(cache, slots, budget));
+        objects
+    }
+
+    /// Swap in the chunks read since the last call, then ask for the ones
+    /// in view that are still bounds, nearest first, making room by
+    /// putting back the chunks out of view longest. Chunks in view that
+    /// don't fit the budget stay bounds. Never waits for a read; call it
+    /// once a frame before drawing. Returns how many chunks arrived.
+    pub fn stream_chunks(&mut self) -> usize {
+        let view_proj = self.proj_mat * self.camera.generate_view_mat();
+        let matrices = self.world_matrices();
+        let eye = self.camera.position;
+        let mut arrived = 0;
+        let mut streams = std::mem::take(&mut self.streams);
+        for stream in &mut streams {
+            stream.frame += 1;
+            while let Ok((index, mesh)) = stream.arrivals.try_recv() {
+                let model = stream.objects[index].and_then(|object| self.models.get_mut(object));
+                match (mesh, model) {
+                    (Ok(mesh), Some(model)) => {
+                        model.mesh = Rc::new(mesh);
+                        stream.residency[index] = Residency::Resident {
+                            last_seen: stream.frame,
+                        };
+                        arrived += 1;
+                    }
+                    // Removed while it was being read.
+                    (Ok(_), None) => {
+                        stream.used -= stream.chunks[index].bytes();
+                        stream.residency[index] = Residency::Failed;
+                    }
+                    (Err(err), _) => {
+                        error!("chunk {index}: {err}");
+                        stream.used -= stream.chunks[index].bytes();
+                        stream.residency[index] = Residency::Failed;
+                    }
+                }
+            }
+
+            let mut wanted = Vec::new();
+            for (index, info) in stream.chunks.iter().enumerate() {
+                let Some(model_mat) = stream.objects[index].and_then(|object| matrices.get(object))
+                else {
+                    continue;
+                };
+                let planes = extract_frustum_planes(&(view_proj * model_mat));
+                if !aabb_intersects_frustum(&planes, &info.min, &info.max) {
+                    continue;
+                }
+                match &mut stream.residency[index] {
+                    Residency::Resident { last_seen } => *last_seen = stream.frame,
+                    Residency::Bounds => {
+                        let centre =
+                            model_mat.transform_point(&(info.min + (info.max - info.min) / 2.0));
+                        wanted.push((index, (centre - eye).norm_squared()));
+                    }
+                    Residency::Loading | Residency::Failed => {}
+                }
+            }
+            wanted.sort_by(|a, b| a.1.total_cmp(&b.1));
+
+            let room = MAX_IN_FLIGHT.saturating_sub(stream.in_flight());
+            for (index, _) in wanted.into_iter().take(room) {
+                let bytes = stream.chunks[index].bytes();
+                while stream.used + bytes > stream.budget {
+                    let oldest = stream
+                        .residency
+                        .iter()
+                        .enumerate()
+                        .filter_map(|(i, state)| match *state {
+                            Residency::Resident { last_seen } if last_seen < stream.frame => {
+                                Some((i, last_seen))
+                            }
+                            _ => None,
+                        })
+                        .min_by_key(|&(_, last_seen)| last_seen);
+                    let Some((oldest, _)) = oldest else {
+                        break;
+                    };
+                    stream.evict(oldest, &mut self.models);
+                }
+                if stream.used + bytes > stream.budget || stream.requests.send(index).is_err() {
+                    break;
+                }
+                stream.used += bytes;
+                stream.residency[index] = Residency::Loading;
+            }
+        }
+        self.streams = streams;
+        arrived
+    }
+
+    /// Bytes of chunk geometry read or being read, over every stream.
+    pub fn streamed_bytes(&self) -> usize {
+        self.streams.iter().map(|stream| stream.used).sum()
+    }
+
+    /// How many chunk reads are under way, over every stream.
+    pub fn chunks_pending(&self) -> usize {
+        self.streams.iter().map(ChunkStream::in_flight).sum()
+    }
+}
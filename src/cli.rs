@@ -0,0 +1,805 @@
+//! Command line of the `engine` binary, parsed by hand.
+//!
+//! ```text
+//! shapes view [file.scene] [model.obj...] [--width N] [--height N]
+//!             [--scale F] [--watch] [--keep-camera] [--max-fps N]
+//!             [--input path] [--bookmarks path]
+//!             [--bookmark-seconds F] [--eye-height F] [--export path]
+//!             [--camera-track path] [--target-ms F] [--profile]
+//!             [--capture-dir path] [--capture-frames N] [--capture-skip N]
+//!             [--paint-size N] [--brush-radius F] [--brush-hardness F]
+//!             [--brush-color COLOR] [--paint-out path]
+//!             [--stream model.obj] [--stream-budget MB] [scene flags]
+//! shapes render [file.scene] [model.obj...] -o out.png [--width N]
+//!               [--height N] [--frames N] [--camera-track path] [scene flags]
+//! shapes info model.obj
+//! shapes simplify model.obj --target N -o out.obj
+//! shapes reduce repro.scene --check-panic [-o out.scene] [--width N]
+//!               [--height N] [scene flags]
+//! ```
+//!
+//! With no arguments at all the window opens as `view` would.
+
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use nalgebra::Point3;
+
+use crate::depth::DepthConvention;
+use crate::mesh::obj::{ObjError, ObjMesh};
+use crate::mesh::{Color, ImportOptions, Mesh};
+use crate::paint::Brush;
+use crate::texture::FilterMode;
+
+pub const USAGE: &str = "\
+usage:
+  shapes view [file.scene] [model.obj...] [--width N] [--height N]
+              [--scale F] [--watch] [--keep-camera] [--max-fps N]
+              [--input path] [--bookmarks path]
+              [--bookmark-seconds F] [--eye-height F] [--export path]
+              [--camera-track path] [--target-ms F] [--profile]
+              [--capture-dir path] [--capture-frames N] [--capture-skip N]
+              [--paint-size N] [--brush-radius F] [--brush-hardness F]
+              [--brush-color COLOR] [--paint-out path]
+              [--stream model.obj] [--stream-budget MB] [scene flags]
+      open the interactive window, set up as the scene file says and with
+      the models loaded into the scene, --watch reloading them and the
+      scene file when they change (leaving the camera be with
+      --keep-camera), drawing at most N frames a second, with key
+      bindings from --input and camera bookmarks kept in --bookmarks
+      (bookmarks.txt) and flown to over --bookmark-seconds (1), keeping the camera at least --eye-height above the ground and
+      X writing the scene to --export (scene.obj); F6 and F7 record and
+      play --camera-track (camera.track), --target-ms starts the render
+      scale following that frame time, --profile times each stage, and F9
+      captures frames into --capture-dir (capture), holding the last
+      --capture-frames (600) and keeping every --capture-skip'th (1); Y
+      paints the selected object, on a blank --paint-size (256) texture
+      if it has none, with a brush of --brush-radius texels (4),
+      --brush-hardness (0.5) and --brush-color (#ff0000), and \\ saves
+      the texture to --paint-out (paint.png); --stream models are cut
+      into chunks cached beside them and read as they come into view,
+      holding at most --stream-budget (256) megabytes of each
+  shapes render [file.scene] [model.obj...] -o out.png [--width N]
+                [--height N] [--frames N] [--camera-track path]
+                [scene flags]
+      draw the scene, set up as the scene file says, without a window and write it as PNG, PPM or BMP by
+      extension; several frames are numbered out-0000.png, out-0001.png, ...
+  shapes info model.obj
+      print a model's vertex and triangle counts, bounds and warnings
+  shapes simplify model.obj --target N -o out.obj
+      collapse edges until the model has at most N triangles and write it,
+      with its colors in out.mtl beside it
+  shapes reduce repro.scene --check-panic [-o out.scene] [--width N]
+                [--height N] [scene flags]
+      cut the scene down to the fewest models, lights and backgrounds,
+      with models swapped for boxes, that drawing it still panics with,
+      and write that to out.scene (repro.reduced.scene)
+  shapes help
+      print this message
+
+scene flags, for view and render:
+  --clip near,far | --clip auto
+      move the clip planes from 0.1 and 200, or fit them to the scene
+  --depth-buffer, --reversed-depth
+      depth test every pixel, with reversed depth for precision far away
+  --back-to-front
+      under the depth buffer, still draw solid geometry farthest first
+  --splitting-sort
+      cut up surfaces that pass through each other instead
+  --simplify N
+      simplify models with more than N triangles as they load
+  --planar-shadows
+      flatten shadows onto the floor instead of using a shadow map
+  --small-triangles PIXELS | --small-triangles points
+      skip triangles smaller than that on screen, or draw ones under half
+      a pixel as points
+  --particles
+      add a fountain of particles
+  --sky
+      draw a sky behind the scene instead of a flat color
+  --auto-lod
+      give loaded models coarser levels of detail for the distance
+  --colorblind-ids
+      color the ID views from a palette readable with color blindness
+  --instances N
+      fill the floor with an N by N grid of cubes sharing one mesh
+  --lod-spheres N
+      line up N spheres that get coarser with distance
+  --billboards N
+      scatter N camera-facing squares over the floor
+  --checker-floor nearest | --checker-floor bilinear
+      cover the floor in a checkerboard texture, sampled either way
+  --monitor
+      stand a screen in the scene showing it live from another camera";
+
+/// Where camera bookmarks are kept when no path is given.
+pub const DEFAULT_BOOKMARKS: &str = "bookmarks.txt";
+
+/// Where the scene is exported when no path is given.
+pub const DEFAULT_EXPORT: &str = "scene.obj";
+
+/// Where the camera track is recorded to and played from when no path is
+/// given.
+pub const DEFAULT_CAMERA_TRACK: &str = "camera.track";
+
+/// Where captured frames are saved when no folder is given.
+pub const DEFAULT_CAPTURE_DIR: &str = "capture";
+
+/// Megabytes of chunks each streamed model holds when no budget is given.
+pub const DEFAULT_STREAM_BUDGET: usize = 256;
+
+/// Where a painted texture is saved when no path is given.
+pub const DEFAULT_PAINT_OUT: &str = "paint.png";
+
+/// Texels to a side of the blank texture made for painting an object that
+/// has none.
+pub const DEFAULT_PAINT_SIZE: u32 = 256;
+
+/// Window or image size used when none is given. The window never gets
+/// smaller than it starts, in logical pixels.
+pub const DEFAULT_WIDTH: u32 = 500;
+pub const DEFAULT_HEIGHT: u32 = 500;
+
+/// Where the clip planes go.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ClipPlanes {
+    /// Fit them around the scene every frame.
+    Auto,
+    /// Near and far distances.
+    Fixed(f32, f32),
+}
+
+impl std::str::FromStr for ClipPlanes {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, ()> {
+        if s == "auto" {
+            return Ok(ClipPlanes::Auto);
+        }
+        let (near, far) = s.split_once(',').ok_or(())?;
+        let parse = |d: &str| d.trim().parse().map_err(|_| ());
+        Ok(ClipPlanes::Fixed(parse(near)?, parse(far)?))
+    }
+}
+
+/// What happens to triangles that come out tiny on screen.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SmallTriangles {
+    /// Skip those narrower or shorter than this many pixels.
+    Cull(f32),
+    /// Gather those under half a pixel into points.
+    Points,
+}
+
+impl std::str::FromStr for SmallTriangles {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, ()> {
+        if s == "points" {
+            return Ok(SmallTriangles::Points);
+        }
+        s.parse().map(SmallTriangles::Cull).map_err(|_| ())
+    }
+}
+
+impl std::str::FromStr for FilterMode {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, ()> {
+        match s {
+            "nearest" => Ok(FilterMode::Nearest),
+            "bilinear" => Ok(FilterMode::Bilinear),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Settings for the scene that `view` and `render` both take.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SceneArgs {
+    /// The demo's own clip planes when unset.
+    pub clip: Option<ClipPlanes>,
+    /// Test pixels against a depth buffer of this convention.
+    pub depth_buffer: Option<DepthConvention>,
+    /// Draw solid geometry back to front under the depth buffer too,
+    /// instead of nearest first.
+    pub back_to_front: bool,
+    /// Cut up triangles that pass through each other.
+    pub splitting_sort: bool,
+    /// Simplify models with more triangles than this as they load.
+    pub simplify: Option<usize>,
+    /// Project shadows flat onto the floor instead of using the light's
+    /// shadow map.
+    pub planar_shadows: bool,
+    pub small_triangles: Option<SmallTriangles>,
+    /// Add a particle fountain.
+    pub particles: bool,
+    /// Draw a sky behind the scene.
+    pub sky: bool,
+    /// Give loaded models levels of detail.
+    pub auto_lod: bool,
+    /// Color the ID render modes from the color blind safe palette.
+    pub colorblind_ids: bool,
+    /// Fill the floor with this many cubes a side, for stress testing.
+    pub instances: Option<usize>,
+    /// Line up this many spheres with levels of detail.
+    pub lod_spheres: Option<usize>,
+    /// Scatter this many billboards over the floor.
+    pub billboards: Option<usize>,
+    /// Texture the floor with a checkerboard sampled this way.
+    pub checker_floor: Option<FilterMode>,
+    /// Stand a screen showing the scene from another camera in it.
+    pub monitor: bool,
+}
+
+impl SceneArgs {
+    /// Take `flag`, and its value from `args`, if it is a scene flag.
+    /// Returns whether it was.
+    fn parse_flag(
+        &mut self,
+        flag: &str,
+        args: &mut impl Iterator<Item = String>,
+    ) -> Result<bool, CliError> {
+        match flag {
+            "--clip" => self.clip = Some(value(flag, args)?),
+            "--depth-buffer" => self.depth_buffer = Some(DepthConvention::Standard),
+            "--reversed-depth" => self.depth_buffer = Some(DepthConvention::Reversed),
+            "--back-to-front" => self.back_to_front = true,
+            "--splitting-sort" => self.splitting_sort = true,
+            "--simplify" => self.simplify = Some(value(flag, args)?),
+            "--planar-shadows" => self.planar_shadows = true,
+            "--small-triangles" => self.small_triangles = Some(value(flag, args)?),
+            "--particles" => self.particles = true,
+            "--sky" => self.sky = true,
+            "--auto-lod" => self.auto_lod = true,
+            "--colorblind-ids" => self.colorblind_ids = true,
+            "--instances" => self.instances = Some(value(flag, args)?),
+            "--lod-spheres" => self.lod_spheres = Some(value(flag, args)?),
+            "--billboards" => self.billboards = Some(value(flag, args)?),
+            "--checker-floor" => self.checker_floor = Some(value(flag, args)?),
+            "--monitor" => self.monitor = true,
+            _ => return Ok(false),
+        }
+        Ok(true)
+    }
+}
+
+/// What the binary was asked to do.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+    View(ViewArgs),
+    Render(RenderArgs),
+    Info { model: PathBuf },
+    Simplify(SimplifyArgs),
+    Reduce(ReduceArgs),
+    Help,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ViewArgs {
+    /// Scene file setting up the camera and light and naming models, see
+    /// [`crate::scene_file`].
+    pub scene_file: Option<PathBuf>,
+    /// OBJ files loaded into the scene in the background.
+    pub models: Vec<PathBuf>,
+    pub width: u32,
+    pub height: u32,
+    /// Render scale to start at; the demo's own default when unset.
+    pub scale: Option<f32>,
+    /// Reload `models` and the scene file whenever their files change.
+    pub watch: bool,
+    /// Leave the camera where it is when the scene file is reloaded.
+    pub keep_camera: bool,
+    /// Most frames drawn a second; as many as fit when unset.
+    pub max_fps: Option<f32>,
+    /// Key bindings file, see [`crate::input::InputConfig::parse`]. The
+    /// default keys when unset.
+    pub input: Option<PathBuf>,
+    /// Camera bookmarks are read from here and saved back to it.
+    pub bookmarks: PathBuf,
+    /// How long the camera takes to fly to a bookmark.
+    pub bookmark_seconds: f32,
+    /// Keep the camera at least this high above the ground under it.
+    pub eye_height: Option<f32>,
+    /// The scene is baked into this OBJ file on request.
+    pub export: PathBuf,
+    /// Camera tracks are recorded to and played from here.
+    pub camera_track: PathBuf,
+    /// Start with the render scale following this frame time, in
+    /// milliseconds.
+    pub target_ms: Option<f32>,
+    /// Time each stage of drawing.
+    pub profile: bool,
+    /// Captured frames are saved in this folder.
+    pub capture_dir: PathBuf,
+    /// Most recent frames a capture holds on to.
+    pub capture_frames: usize,
+    /// Keep every this many captured frames.
+    pub capture_skip: usize,
+    /// Side of the blank texture an object gets when painted without one.
+    pub paint_size: u32,
+    pub brush: Brush,
+    /// A painted texture is saved here on request.
+    pub paint_out: PathBuf,
+    /// OBJ files streamed in chunks, see [`crate::chunked`].
+    pub streamed: Vec<PathBuf>,
+    /// Megabytes of chunk geometry each streamed model holds at most.
+    pub stream_budget: usize,
+    pub scene: SceneArgs,
+}
+
+impl Default for ViewArgs {
+    fn default() -> Self {
+        Self {
+            scene_file: None,
+            models: Vec::new(),
+            width: DEFAULT_WIDTH,
+            height: DEFAULT_HEIGHT,
+            scale: None,
+            watch: false,
+            keep_camera: false,
+            max_fps: None,
+            input: None,
+            bookmarks: PathBuf::from(DEFAULT_BOOKMARKS),
+            bookmark_seconds: 1.0,
+            eye_height: None,
+            export: PathBuf::from(DEFAULT_EXPORT),
+            camera_track: PathBuf::from(DEFAULT_CAMERA_TRACK),
+            target_ms: None,
+            profile: false,
+            capture_dir: PathBuf::from(DEFAULT_CAPTURE_DIR),
+            capture_frames: 600,
+            capture_skip: 1,
+            paint_size: DEFAULT_PAINT_SIZE,
+            brush: Brush::default(),
+            paint_out: PathBuf::from(DEFAULT_PAINT_OUT),
+            streamed: Vec::new(),
+            stream_budget: DEFAULT_STREAM_BUDGET,
+            scene: SceneArgs::default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RenderArgs {
+    pub scene_file: Option<PathBuf>,
+    pub models: Vec<PathBuf>,
+    /// Where the image goes. With more than one frame, the frame number is
+    /// added to the file name.
+    pub output: PathBuf,
+    pub width: u32,
+    pub height: u32,
+    pub frames: usize,
+    /// Camera track played over the frames, start to end.
+    pub camera_track: Option<PathBuf>,
+    pub scene: SceneArgs,
+}
+
+impl RenderArgs {
+    /// Where frame `index` is written.
+    pub fn frame_path(&self, index: usize) -> PathBuf {
+        if self.frames <= 1 {
+            return self.output.clone();
+        }
+        let stem = self
+            .output
+            .file_stem()
+            .map_or_else(|| "frame".into(), |stem| stem.to_string_lossy());
+        let extension = self
+            .output
+            .extension()
+            .map_or_else(|| "png".into(), |ext| ext.to_string_lossy());
+        self.output
+            .with_file_name(format!("{stem}-{index:04}.{extension}"))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SimplifyArgs {
+    pub model: PathBuf,
+    /// Most triangles the written model may have.
+    pub target: usize,
+    pub output: PathBuf,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReduceArgs {
+    pub scene_file: PathBuf,
+    /// Where the cut down scene is written; beside the scene file, with
+    /// `reduced` added to its name, when not given.
+    pub output: PathBuf,
+    pub width: u32,
+    pub height: u32,
+    /// Keep cuts that still panic when drawn.
+    pub check_panic: bool,
+    /// Scene flags the scene is drawn with, as for `render`.
+    pub scene: SceneArgs,
+}
+
+/// Why the command line could not be understood.
+#[derive(Debug, Clone, PartialEq)]
+pub enum CliError {
+    UnknownCommand(String),
+    /// A flag the command doesn't take.
+    UnknownFlag {
+        command: &'static str,
+        flag: String,
+    },
+    MissingValue(String),
+    BadValue {
+        flag: String,
+        value: String,
+    },
+    /// A required argument was not given.
+    Missing(&'static str),
+    /// More positional arguments than the command takes.
+    Unexpected(String),
+}
+
+impl fmt::Display for CliError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CliError::UnknownCommand(command) => write!(f, "unknown command {command:?}"),
+            CliError::UnknownFlag { command, flag } => {
+                write!(f, "{command} does not take {flag:?}")
+            }
+            CliError::MissingValue(flag) => write!(f, "{flag} needs a value"),
+            CliError::BadValue { flag, value } => write!(f, "bad value {value:?} for {flag}"),
+            CliError::Missing(what) => write!(f, "missing {what}"),
+            CliError::Unexpected(arg) => write!(f, "unexpected argument {arg:?}"),
+        }
+    }
+}
+
+impl std::error::Error for CliError {}
+
+/// Parse the arguments after the program name.
+pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Command, CliError> {
+    let mut args = args.into_iter();
+    let Some(command) = args.next() else {
+        return Ok(Command::View(ViewArgs::default()));
+    };
+    match command.as_str() {
+        "view" => parse_view(args).map(Command::View),
+        "render" => parse_render(args).map(Command::Render),
+        "info" => {
+            let mut model = None;
+            for arg in args {
+                if arg.starts_with('-') {
+                    return Err(CliError::UnknownFlag {
+                        command: "info",
+                        flag: arg,
+                    });
+                }
+                if model.is_some() {
+                    return Err(CliError::Unexpected(arg));
+                }
+                model = Some(PathBuf::from(arg));
+            }
+            Ok(Command::Info {
+                model: model.ok_or(CliError::Missing("the model to describe"))?,
+            })
+        }
+        "simplify" => parse_simplify(args).map(Command::Simplify),
+        "reduce" => parse_reduce(args).map(Command::Reduce),
+        "help" | "-h" | "--help" => Ok(Command::Help),
+        _ => Err(CliError::UnknownCommand(command)),
+    }
+}
+
+fn parse_view(mut args: impl Iterator<Item = String>) -> Result<ViewArgs, CliError> {
+    let mut view = ViewArgs::default();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--width" => view.width = value(&arg, &mut args)?,
+            "--height" => view.height = value(&arg, &mut args)?,
+            "--scale" => view.scale = Some(value(&arg, &mut args)?),
+            "--watch" => view.watch = true,
+            "--keep-camera" => view.keep_camera = true,
+            "--max-fps" => view.max_fps = Some(value(&arg, &mut args)?),
+            "--input" => view.input = Some(value(&arg, &mut args)?),
+            "--bookmarks" => view.bookmarks = value(&arg, &mut args)?,
+            "--bookmark-seconds" => view.bookmark_seconds = value(&arg, &mut args)?,
+            "--eye-height" => view.eye_height = Some(value(&arg, &mut args)?),
+            "--export" => view.export = value(&arg, &mut args)?,
+            "--camera-track" => view.camera_track = value(&arg, &mut args)?,
+            "--target-ms" => view.target_ms = Some(value(&arg, &mut args)?),
+            "--profile" => view.profile = true,
+            "--capture-dir" => view.capture_dir = value(&arg, &mut args)?,
+            "--capture-frames" => view.capture_frames = value(&arg, &mut args)?,
+            "--capture-skip" => view.capture_skip = value(&arg, &mut args)?,
+            "--paint-size" => view.paint_size = value(&arg, &mut args)?,
+            "--brush-radius" => view.brush.radius = value(&arg, &mut args)?,
+            "--brush-hardness" => view.brush.hardness = value(&arg, &mut args)?,
+            "--brush-color" => view.brush.color = value(&arg, &mut args)?,
+            "--paint-out" => view.paint_out = value(&arg, &mut args)?,
+            "--stream" => view.streamed.push(value(&arg, &mut args)?),
+            "--stream-budget" => view.stream_budget = value(&arg, &mut args)?,
+            flag if view.scene.parse_flag(flag, &mut args)? => {}
+            flag if flag.starts_with('-') => {
+                return Err(CliError::UnknownFlag {
+                    command: "view",
+                    flag: arg,
+                });
+            }
+            path if is_scene_file(path) => {
+                if view.scene_file.is_some() {
+                    return Err(CliError::Unexpected(arg));
+                }
+                view.scene_file = Some(arg.into());
+            }
+            _ => view.models.push(arg.into()),
+        }
+    }
+    Ok(view)
+}
+
+fn parse_render(mut args: impl Iterator<Item = String>) -> Result<RenderArgs, CliError> {
+    let (mut scene_file, mut models) = (None, Vec::new());
+    let mut output = None;
+    let (mut width, mut height, mut frames) = (DEFAULT_WIDTH, DEFAULT_HEIGHT, 1);
+    let mut camera_track = None;
+    let mut scene = SceneArgs::default();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-o" | "--output" => output = Some(value::<PathBuf>(&arg, &mut args)?),
+            "--width" => width = value(&arg, &mut args)?,
+            "--height" => height = value(&arg, &mut args)?,
+            "--frames" => frames = value(&arg, &mut args)?,
+            "--camera-track" => camera_track = Some(value(&arg, &mut args)?),
+            flag if scene.parse_flag(flag, &mut args)? => {}
+            flag if flag.starts_with('-') => {
+                return Err(CliError::UnknownFlag {
+                    command: "render",
+                    flag: arg,
+                });
+            }
+            path if is_scene_file(path) => {
+                if scene_file.is_some() {
+                    return Err(CliError::Unexpected(arg));
+                }
+                scene_file = Some(arg.into());
+            }
+            _ => models.push(arg.into()),
+        }
+    }
+    Ok(RenderArgs {
+        scene_file,
+        models,
+        output: output.ok_or(CliError::Missing("the output image, -o out.png"))?,
+        width,
+        height,
+        frames,
+        camera_track,
+        scene,
+    })
+}
+
+/// Whether a positional argument names a scene file rather than a model.
+fn is_scene_file(path: &str) -> bool {
+    Path::new(path)
+        .extension()
+        .is_some_and(|extension| extension == "scene")
+}
+
+fn parse_simplify(mut args: impl Iterator<Item = String>) -> Result<SimplifyArgs, CliError> {
+    let (mut model, mut target, mut output) = (None, None, None);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--target" => target = Some(value(&arg, &mut args)?),
+            "-o" | "--output" => output = Some(value::<PathBuf>(&arg, &mut args)?),
+            flag if flag.starts_with('-') => {
+                return Err(CliError::UnknownFlag {
+                    command: "simplify",
+                    flag: arg,
+                });
+            }
+            _ if model.is_some() => return Err(CliError::Unexpected(arg)),
+            _ => model = Some(PathBuf::from(arg)),
+        }
+    }
+    Ok(SimplifyArgs {
+        model: model.ok_or(CliError::Missing("the model to simplify"))?,
+        target: target.ok_or(CliError::Missing("the triangle count, --target N"))?,
+        output: output.ok_or(CliError::Missing("the output model, -o out.obj"))?,
+    })
+}
+
+fn parse_reduce(mut args: impl Iterator<Item = String>) -> Result<ReduceArgs, CliError> {
+    let (mut scene_file, mut output) = (None, None);
+    let (mut width, mut height) = (DEFAULT_WIDTH, DEFAULT_HEIGHT);
+    let mut check_panic = false;
+    let mut scene = SceneArgs::default();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-o" | "--output" => output = Some(value::<PathBuf>(&arg, &mut args)?),
+            "--width" => width = value(&arg, &mut args)?,
+            "--height" => height = value(&arg, &mut args)?,
+            "--check-panic" => check_panic = true,
+            flag if scene.parse_flag(flag, &mut args)? => {}
+            flag if flag.starts_with('-') => {
+                return Err(CliError::UnknownFlag {
+                    command: "reduce",
+                    flag: arg,
+                });
+            }
+            _ if scene_file.is_some() => return Err(CliError::Unexpected(arg)),
+            _ => scene_file = Some(PathBuf::from(arg)),
+        }
+    }
+    let scene_file = scene_file.ok_or(CliError::Missing("the scene file to reduce"))?;
+    if !check_panic {
+        return Err(CliError::Missing(
+            "the check to keep failing, --check-panic",
+        ));
+    }
+    Ok(ReduceArgs {
+        output: output.unwrap_or_else(|| scene_file.with_extension("reduced.scene")),
+        scene_file,
+        width,
+        height,
+        check_panic,
+        scene,
+    })
+}
+
+/// The value after `flag`, which has to be given and make sense for its
+/// type, see [`FlagValue`].
+fn value<T: std::str::FromStr + FlagValue>(
+    flag: &str,
+    args: &mut impl Iterator<Item = String>,
+) -> Result<T, CliError> {
+    let value = args
+        .next()
+        .ok_or_else(|| CliError::MissingValue(flag.to_string()))?;
+    value
+        .parse()
+        .ok()
+        .filter(T::is_valid)
+        .ok_or(CliError::BadValue {
+            flag: flag.to_string(),
+            value,
+        })
+}
+
+/// Types flag values are parsed into, with what counts as a usable one:
+/// numbers above zero, paths that aren't empty and clip planes in front of
+/// the eye with the far one beyond the near one.
+trait FlagValue {
+    fn is_valid(&self) -> bool;
+}
+
+impl FlagValue for u32 {
+    fn is_valid(&self) -> bool {
+        *self > 0
+    }
+}
+
+impl FlagValue for usize {
+    fn is_valid(&self) -> bool {
+        *self > 0
+    }
+}
+
+impl FlagValue for f32 {
+    fn is_valid(&self) -> bool {
+        *self > 0.0 && self.is_finite()
+    }
+}
+
+impl FlagValue for ClipPlanes {
+    fn is_valid(&self) -> bool {
+        match *self {
+            ClipPlanes::Auto => true,
+            ClipPlanes::Fixed(near, far) => near.is_valid() && far.is_finite() && far > near,
+        }
+    }
+}
+
+impl FlagValue for SmallTriangles {
+    fn is_valid(&self) -> bool {
+        match self {
+            SmallTriangles::Cull(size) => size.is_valid(),
+            SmallTriangles::Points => true,
+        }
+    }
+}
+
+impl FlagValue for FilterMode {
+    fn is_valid(&self) -> bool {
+        true
+    }
+}
+
+impl FlagValue for Color {
+    fn is_valid(&self) -> bool {
+        true
+    }
+}
+
+impl FlagValue for PathBuf {
+    fn is_valid(&self) -> bool {
+        !self.as_os_str().is_empty()
+    }
+}
+
+/// What `shapes info` prints about a model.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ModelInfo {
+    pub vertices: usize,
+    pub triangles: usize,
+    /// Corners of the box around every vertex.
+    pub aabb: (Point3<f32>, Point3<f32>),
+    /// Triangles using the same vertex twice.
+    pub degenerate: usize,
+    /// Vertices no triangle uses.
+    pub unused_vertices: usize,
+}
+
+impl ModelInfo {
+    pub fn of(mesh: &dyn Mesh) -> Self {
+        let mut used = vec![false; mesh.verts().len()];
+        let mut triangles = 0;
+        for tri in mesh.primitives().triangles() {
+            for v in [tri.v1, tri.v2, tri.v3] {
+                if let Some(used) = used.get_mut(v) {
+                    *used = true;
+                }
+            }
+            triangles += 1;
+        }
+        Self {
+            vertices: mesh.verts().len(),
+            triangles,
+            aabb: mesh.aabb(),
+            degenerate: mesh.degenerate_triangles(),
+            unused_vertices: used.iter().filter(|used| !**used).count(),
+        }
+    }
+
+    /// Load the OBJ file at `path` and describe it.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, ObjError> {
+        Ok(Self::of(&ObjMesh::from_path(
+            path,
+            ImportOptions::default(),
+        )?))
+    }
+
+    /// Anything about the model worth a second look.
+    pub fn warnings(&self) -> Vec<String> {
+        let mut warnings = Vec::new();
+        if self.triangles == 0 {
+            warnings.push("no triangles".to_string());
+        }
+        if self.degenerate > 0 {
+            warnings.push(format!(
+                "{} degenerate triangles use a vertex twice",
+                self.degenerate
+            ));
+        }
+        if self.unused_vertices > 0 {
+            warnings.push(format!(
+                "{} vertices are not used by any triangle",
+                self.unused_vertices
+            ));
+        }
+        warnings
+    }
+}
+
+impl fmt::Display for ModelInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (min, max) = self.aabb;
+        writeln!(f, "vertices:  {}", self.vertices)?;
+        writeln!(f, "triangles: {}", self.triangles)?;
+        write!(
+            f,
+            "bounds:    ({:.3}, {:.3}, {:.3}) to ({:.3}, {:.3}, {:.3})",
+            min.x, min.y, min.z, max.x, max.y, max.z
+        )?;
+        for warning in self.warnings() {
+            write!(f, "\nwarning:   {warning}")?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,100 @@
+//! Overlap queries between objects' bounding boxes, for checking that
+//! procedurally placed objects don't run into each other.
+
+use nalgebra::{Matrix4, Point3};
+use ordered_float::OrderedFloat;
+
+use crate::mesh::Color;
+use crate::world::World;
+
+/// What [`World::highlight_overlaps`] tints overlapping objects towards.
+pub(crate) const OVERLAP_TINT: Color = Color::rgb(255, 0, 0);
+
+/// How far overlapping objects are tinted, from 0 for not at all to 1 for
+/// flat [`OVERLAP_TINT`].
+pub(crate) const OVERLAP_TINT_AMOUNT: f32 = 0.6;
+
+impl World {
+    /// Indices into [`World::models`] of the pairs of shown objects whose
+    /// world space bounding boxes overlap, lower index first, in order.
+    ///
+    /// Each box is fitted around the eight corners of the object's mesh
+    /// bounding box taken through its world matrix, so rotated objects are
+    /// covered whole. Boxes that only touch count as overlapping, which
+    /// lets flat objects lying against each other be found. Objects are
+    /// sorted along x and only those whose x ranges meet are compared.
+    pub fn overlapping_pairs(&self) -> Vec<(usize, usize)> {
+        let matrices = self.world_matrices();
+        let boxes: Vec<(usize, Point3<f32>, Point3<f32>)> = self
+            .models
+            .iter()
+            .zip(&matrices)
+            .enumerate()
+            .filter(|(_, (model, _))| {
+                model.shown(self.visible_layers) && !model.mesh.verts().is_empty()
+            })
+            .map(|(index, (model, matrix))| {
+                let (min, max) = world_aabb(model.mesh.aabb(), matrix);
+                (index, min, max)
+            })
+            .collect();
+        overlapping_boxes(boxes)
+    }
+
+    /// Whether each object in [`World::models`] is in one of
+    /// [`World::overlapping_pairs`].
+    pub(crate) fn overlapping_objects(&self) -> Vec<bool> {
+        let mut overlapping = vec![false; self.models.len()];
+        for (a, b) in self.overlapping_pairs() {
+            overlapping[a] = true;
+            overlapping[b] = true;
+        }
+        overlapping
+    }
+}
+
+/// Pairs of the indices given with `boxes` whose boxes overlap or touch,
+/// lower index first, in order.
+pub(crate) fn overlapping_boxes(
+    mut boxes: Vec<(usize, Point3<f32>, Point3<f32>)>,
+) -> Vec<(usize, usize)> {
+    boxes.sort_by_key(|(_, min, _)| OrderedFloat(min.x));
+
+    let mut pairs = Vec::new();
+    // Boxes whose x range may still reach the next box along.
+    let mut open: Vec<usize> = Vec::new();
+    for (i, (index, min, max)) in boxes.iter().enumerate() {
+        open.retain(|&j| boxes[j].2.x >= min.x);
+        for &j in &open {
+            let (other, other_min, other_max) = boxes[j];
+            if min.y <= other_max.y
+                && other_min.y <= max.y
+                && min.z <= other_max.z
+                && other_min.z <= max.z
+            {
+                pairs.push((other.min(*index), other.max(*index)));
+            }
+        }
+        open.push(i);
+    }
+    pairs.sort_unstable();
+    pairs
+}
+
+/// The world space box around the model space box `(min, max)` taken
+/// through `matrix`.
+pub(crate) fn world_aabb(
+    (min, max): (Point3<f32>, Point3<f32>),
+    matrix: &Matrix4<f32>,
+) -> (Point3<f32>, Point3<f32>) {
+    let mut corners = (0..8).map(|i| {
+        let corner = Point3::new(
+            if i & 1 == 0 { min.x } else { max.x },
+            if i & 2 == 0 { min.y } else { max.y },
+            if i & 4 == 0 { min.z } else { max.z },
+        );
+        matrix.transform_point(&corner)
+    });
+    let first = corners.next().unwrap_or_else(Point3::origin);
+    corners.fold((first, first), |(lo, hi), p| (lo.inf(&p), hi.sup(&p)))
+}
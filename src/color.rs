@@ -0,0 +1,62 @@
+//! Conversions between 8-bit sRGB and linear light.
+
+use std::sync::OnceLock;
+
+const ENCODE_STEPS: usize = 1024;
+
+fn decode_table() -> &'static [f32; 256] {
+    static TABLE: OnceLock<[f32; 256]> = OnceLock::new();
+    TABLE.get_or_init(|| {
+        let mut table = [0.0; 256];
+        for (i, entry) in table.iter_mut().enumerate() {
+            let c = i as f32 / 255.0;
+            *entry = if c <= 0.04045 {
+                c / 12.92
+            } else {
+                ((c + 0.055) / 1.055).powf(2.4)
+            };
+        }
+        table
+    })
+}
+
+fn encode_table() -> &'static [u8; ENCODE_STEPS] {
+    static TABLE: OnceLock<[u8; ENCODE_STEPS]> = OnceLock::new();
+    TABLE.get_or_init(|| {
+        let mut table = [0; ENCODE_STEPS];
+        for (i, entry) in table.iter_mut().enumerate() {
+            let l = i as f32 / (ENCODE_STEPS - 1) as f32;
+            let c = if l <= 0.0031308 {
+                l * 12.92
+            } else {
+                1.055 * l.powf(1.0 / 2.4) - 0.055
+            };
+            *entry = (c * 255.0).round() as u8;
+        }
+        table
+    })
+}
+
+/// Decode an sRGB channel to linear light in `0.0..=1.0`.
+pub fn srgb_to_linear(c: u8) -> f32 {
+    decode_table()[c as usize]
+}
+
+/// Encode linear light to an sRGB channel in `0.0..=255.0` without rounding,
+/// for callers that quantize it themselves.
+pub fn linear_to_srgb_exact(l: f32) -> f32 {
+    let l = l.clamp(0.0, 1.0);
+    let c = if l <= 0.0031308 {
+        l * 12.92
+    } else {
+        1.055 * l.powf(1.0 / 2.4) - 0.055
+    };
+    c * 255.0
+}
+
+/// Encode linear light back to an sRGB channel. Values outside
+/// `0.0..=1.0` are clamped.
+pub fn linear_to_srgb(l: f32) -> u8 {
+    let i = (l.clamp(0.0, 1.0) * (ENCODE_STEPS - 1) as f32 + 0.5) as usize;
+    encode_table()[i]
+}
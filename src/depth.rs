@@ -0,0 +1,147 @@
+//! Which way depth runs after projection, and the per-pixel depth test
+//! that follows from it.
+
+use nalgebra::Matrix4;
+
+/// How a projection maps the view distances between the clip planes onto
+/// normalized device z. Either way only NDC z in `0.0..=1.0` is drawn.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum DepthConvention {
+    /// OpenGL style: -1 at the near plane and 1 at the far one, so the
+    /// drawn range starts a little beyond the near plane. Floats crowd
+    /// around 1, where most of the scene ends up, so distant surfaces
+    /// close together get the same depth.
+    #[default]
+    Standard,
+    /// 1 at the near plane and 0 at the far one. Floats crowd around 0
+    /// instead, which cancels out the squeeze of the perspective divide
+    /// and keeps distant depths apart.
+    Reversed,
+}
+
+impl DepthConvention {
+    /// A right-handed perspective looking down -z, like
+    /// [`nalgebra::Perspective3`] for [`DepthConvention::Standard`].
+    pub fn perspective(self, aspect: f32, fovy: f32, near: f32, far: f32) -> Matrix4<f32> {
+        let mut matrix = nalgebra::Perspective3::new(aspect, fovy, near, far).to_homogeneous();
+        if self == DepthConvention::Reversed {
+            matrix[(2, 2)] = near / (far - near);
+            matrix[(2, 3)] = near * far / (far - near);
+        }
+        matrix
+    }
+
+    /// The near and far plane distances of a projection built by
+    /// [`DepthConvention::perspective`].
+    pub fn clip_planes(self, projection: &Matrix4<f32>) -> (f32, f32) {
+        let (a, b) = (projection[(2, 2)], projection[(2, 3)]);
+        match self {
+            DepthConvention::Standard => (b / (a - 1.0), b / (a + 1.0)),
+            DepthConvention::Reversed => (b / (1.0 + a), b / a),
+        }
+    }
+
+    /// Where the drawn part of the view starts for a projection between
+    /// `near` and `far`: NDC z 0 for standard depth, at `2nf / (n + f)`,
+    /// and the near plane itself for reversed depth.
+    pub fn drawn_from(self, near: f32, far: f32) -> f32 {
+        match self {
+            DepthConvention::Standard => 2.0 * near * far / (near + far),
+            DepthConvention::Reversed => near,
+        }
+    }
+
+    /// NDC z at the near and far planes.
+    pub fn ndc_range(self) -> (f32, f32) {
+        match self {
+            DepthConvention::Standard => (-1.0, 1.0),
+            DepthConvention::Reversed => (1.0, 0.0),
+        }
+    }
+
+    /// What an empty depth buffer holds: the far end of the drawn range.
+    pub fn clear_value(self) -> f32 {
+        match self {
+            DepthConvention::Standard => 1.0,
+            DepthConvention::Reversed => 0.0,
+        }
+    }
+
+    /// Whether a surface at NDC z `depth` is in front of the `stored` one.
+    /// Ties keep what is already there.
+    pub fn passes(self, depth: f32, stored: f32) -> bool {
+        match self {
+            DepthConvention::Standard => depth < stored,
+            DepthConvention::Reversed => depth > stored,
+        }
+    }
+}
+
+/// Depth offset of a `depth_bias` of 1 on a triangle facing the eye head
+/// on, in NDC z: a few steps of `f32` precision even where depths crowd
+/// around 1.
+pub const DEPTH_BIAS_UNIT: f32 = 1e-6;
+
+/// NDC z across a screen space triangle. Depth after the perspective divide
+/// is affine in screen space, so a plane through the corners gives it
+/// exactly.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct DepthPlane {
+    origin: (f32, f32),
+    depth: f32,
+    dx: f32,
+    dy: f32,
+}
+
+impl DepthPlane {
+    /// The plane through `(x, y, depth)` at each corner. A triangle with no
+    /// area gets the first corner's depth everywhere.
+    pub fn new(corners: [(f32, f32, f32); 3]) -> Self {
+        let [(x1, y1, z1), (x2, y2, z2), (x3, y3, z3)] = corners;
+        let cross = (x2 - x1) * (y3 - y1) - (x3 - x1) * (y2 - y1);
+        let (dx, dy) = if cross == 0.0 {
+            (0.0, 0.0)
+        } else {
+            (
+                ((z2 - z1) * (y3 - y1) - (z3 - z1) * (y2 - y1)) / cross,
+                ((z3 - z1) * (x2 - x1) - (z2 - z1) * (x3 - x1)) / cross,
+            )
+        };
+        Self {
+            origin: (x1, y1),
+            depth: z1,
+            dx,
+            dy,
+        }
+    }
+
+    /// A plane with the same depth everywhere.
+    pub fn flat(depth: f32) -> Self {
+        Self {
+            origin: (0.0, 0.0),
+            depth,
+            dx: 0.0,
+            dy: 0.0,
+        }
+    }
+
+    /// The plane moved `bias` towards the eye when negative and away when
+    /// positive, by that many times its steepest depth change per pixel
+    /// plus [`DEPTH_BIAS_UNIT`], whichever way `convention` runs.
+    pub fn biased(mut self, bias: f32, convention: DepthConvention) -> Self {
+        if bias == 0.0 {
+            return self;
+        }
+        let slope = self.dx.abs().max(self.dy.abs());
+        let offset = bias * (slope + DEPTH_BIAS_UNIT);
+        match convention {
+            DepthConvention::Standard => self.depth += offset,
+            DepthConvention::Reversed => self.depth -= offset,
+        }
+        self
+    }
+
+    pub fn at(&self, x: f32, y: f32) -> f32 {
+        self.depth + self.dx * (x - self.origin.0) + self.dy * (y - self.origin.1)
+    }
+}
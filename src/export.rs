@@ -0,0 +1,142 @@
+//! Writing the scene back out as a Wavefront OBJ file, with every object
+//! baked into place, so a layout arranged in the viewer can be opened
+//! elsewhere or loaded again as one mesh. Single meshes are written with
+//! [`save_obj`](crate::mesh::obj::save_obj) through the same code.
+
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+use nalgebra::Matrix4;
+
+use crate::material::Material;
+use crate::mesh::{Color, Mesh};
+use crate::world::World;
+
+impl World {
+    /// Write every shown object to the OBJ file at `path`: its vertices
+    /// transformed by its world matrix, and its triangles under an
+    /// `o <name>` line of its own. Objects are named after the file they
+    /// were loaded from, or `object`, and their index in
+    /// [`World::models`].
+    pub fn export_obj(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let mut out = BufWriter::new(File::create(path)?);
+        self.write_obj(&mut out, None)?;
+        out.flush()
+    }
+
+    /// Like [`World::export_obj`], but with a material library next to
+    /// the file, named like it with an `.mtl` extension, holding one
+    /// material per color drawn. Faces switch to their color's material
+    /// with `usemtl`.
+    pub fn export_obj_with_materials(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let path = path.as_ref();
+        let mtl_path = path.with_extension("mtl");
+        let mtl_name = mtl_path
+            .file_name()
+            .map_or_else(|| "scene.mtl".into(), |name| name.to_string_lossy());
+        let mut out = BufWriter::new(File::create(path)?);
+        let colors = self.write_obj(&mut out, Some(&mtl_name))?;
+        out.flush()?;
+        write_mtl(&mtl_path, &colors)
+    }
+
+    /// Write the scene as OBJ to `out`, returning the colors its faces
+    /// use when it refers to an `mtllib` for them.
+    fn write_obj(&self, out: &mut impl Write, mtllib: Option<&str>) -> io::Result<Vec<Color>> {
+        let matrices = self.world_matrices();
+        let mut colors: Vec<Color> = Vec::new();
+        if let Some(mtllib) = mtllib {
+            writeln!(out, "mtllib {mtllib}")?;
+        }
+        // OBJ counts vertices from 1 across the whole file.
+        let mut first = 1;
+        for (index, (model, matrix)) in self.models.iter().zip(&matrices).enumerate() {
+            if !model.shown(self.visible_layers) || model.mesh.verts().is_empty() {
+                continue;
+            }
+            let name = model
+                .source
+                .as_ref()
+                .and_then(|source| source.file_stem())
+                .map_or_else(|| "object".into(), |stem| stem.to_string_lossy());
+            let material = model.material.and_then(|handle| {
+                self.materials
+                    .instantiate_override(handle, &model.material_override)
+            });
+            let colors = mtllib.is_some().then_some(&mut colors);
+            let name = format!("{name}_{index}");
+            first = write_object(out, &name, &*model.mesh, matrix, material, first, colors)?;
+        }
+        Ok(colors)
+    }
+}
+
+/// Write `mesh` to `out` as the OBJ object `name`, its vertices taken
+/// through `matrix` and numbered from `first`, returning the number after
+/// its last. With `colors`, faces switch to their color's material with
+/// `usemtl` and colors not in it yet are added.
+pub(crate) fn write_object(
+    out: &mut impl Write,
+    name: &str,
+    mesh: &dyn Mesh,
+    matrix: &Matrix4<f32>,
+    material: Option<Material>,
+    first: usize,
+    mut colors: Option<&mut Vec<Color>>,
+) -> io::Result<usize> {
+    writeln!(out, "o {name}")?;
+    for vertex in mesh.verts() {
+        let p = matrix.transform_point(vertex);
+        writeln!(out, "v {} {} {}", p.x, p.y, p.z)?;
+    }
+    // A mirroring transform turns the winding over with it.
+    let mirrored = matrix.fixed_view::<3, 3>(0, 0).determinant() < 0.0;
+    let mut current = None;
+    for tri in mesh.primitives().triangles() {
+        if let Some(colors) = colors.as_deref_mut() {
+            let color = material.map_or(tri.color, |material| material.color);
+            if current != Some(color) {
+                current = Some(color);
+                if !colors.contains(&color) {
+                    colors.push(color);
+                }
+                writeln!(out, "usemtl {}", material_name(color))?;
+            }
+        }
+        // OBJ winds front faces counter-clockwise, the engine clockwise.
+        let (v2, v3) = if mirrored {
+            (tri.v2, tri.v3)
+        } else {
+            (tri.v3, tri.v2)
+        };
+        writeln!(out, "f {} {} {}", first + tri.v1, first + v2, first + v3)?;
+    }
+    Ok(first + mesh.verts().len())
+}
+
+/// Write a material library at `path` with one material per color.
+pub(crate) fn write_mtl(path: &Path, colors: &[Color]) -> io::Result<()> {
+    let mut out = BufWriter::new(File::create(path)?);
+    for color in colors {
+        let channel = |c: u8| c as f32 / 255.0;
+        writeln!(out, "newmtl {}", material_name(*color))?;
+        writeln!(
+            out,
+            "Kd {:.6} {:.6} {:.6}",
+            channel(color.r),
+            channel(color.g),
+            channel(color.b)
+        )?;
+        writeln!(out, "d {:.6}", channel(color.a))?;
+    }
+    out.flush()
+}
+
+/// The name of the exported material drawing `color`.
+fn material_name(color: Color) -> String {
+    format!(
+        "color_{:02x}{:02x}{:02x}{:02x}",
+        color.r, color.g, color.b, color.a
+    )
+}
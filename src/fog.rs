@@ -0,0 +1,42 @@
+//! Depth fog that fades geometry into a backdrop color with distance.
+
+use crate::mesh::Color;
+
+/// How fog thickens between `start` and `end`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FogMode {
+    /// Fog grows linearly with distance.
+    Linear,
+    /// Fog grows quickly near `start` and eases into `end`. Higher density
+    /// front-loads more of it.
+    Exponential { density: f32 },
+}
+
+/// Fog applied by view space distance. Geometry closer than `start` is
+/// untouched, geometry beyond `end` is exactly `color`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Fog {
+    pub color: Color,
+    pub start: f32,
+    pub end: f32,
+    pub mode: FogMode,
+}
+
+impl Fog {
+    /// How much of the fog color to use at `distance`, from 0.0 to 1.0.
+    pub fn factor(&self, distance: f32) -> f32 {
+        let span = self.end - self.start;
+        if span <= 0.0 {
+            return if distance >= self.end { 1.0 } else { 0.0 };
+        }
+        let t = ((distance - self.start) / span).clamp(0.0, 1.0);
+        match self.mode {
+            FogMode::Linear => t,
+            FogMode::Exponential { density } if density > 0.0 => {
+                // Normalized so the curve still ends at exactly 1.0.
+                ((1.0 - (-density * t).exp()) / (1.0 - (-density).exp())).clamp(0.0, 1.0)
+            }
+            FogMode::Exponential { .. } => t,
+        }
+    }
+}
@@ -0,0 +1,248 @@
+//! Debug overlays drawn in world space: a ground grid, the world axes, face
+//! normals, the light and other cameras' views. They are drawn in flat colors, never lit.
+
+use nalgebra::{Matrix4, Point2, Point3, Vector3, center};
+
+use crate::mesh::Color;
+use crate::pipeline::{Plane, clip_to_screen, extract_frustum_planes};
+use crate::raster::{Primitive, thick_line};
+use crate::world::World;
+
+/// Width in output pixels of gizmo lines.
+const LINE_WIDTH: f32 = 1.0;
+
+/// Length in world units of the lines drawn by [`World::show_normals`].
+pub const NORMAL_LENGTH: f32 = 0.2;
+
+/// Color of face normal lines, picked to stand out against shaded meshes.
+const NORMAL_COLOR: Color = Color::MAGENTA;
+
+/// Half the length in world units of each arm of the light marker.
+const LIGHT_MARKER_SIZE: f32 = 0.15;
+
+/// Length in world units of the light marker's line towards its target.
+const LIGHT_POINTER_LENGTH: f32 = 0.5;
+
+const LIGHT_COLOR: Color = Color::from_hex(0xFFC800);
+
+const FRUSTUM_COLOR: Color = Color::from_hex(0x00C8FF);
+
+/// Layout of the debug ground grid, drawn in the XZ plane at y = 0.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GridSettings {
+    /// Number of cells along each side, centered on the origin.
+    pub cells: u32,
+    /// Size of a cell in world units.
+    pub spacing: f32,
+    /// Lines fade out with distance from the camera and stop here, so the
+    /// grid doesn't alias into noise towards the horizon.
+    pub fade_distance: f32,
+    pub color: Color,
+}
+
+impl Default for GridSettings {
+    fn default() -> Self {
+        Self {
+            cells: 20,
+            spacing: 1.0,
+            fade_distance: 20.0,
+            color: Color {
+                r: 120,
+                g: 120,
+                b: 120,
+                a: 255,
+            },
+        }
+    }
+}
+
+impl World {
+    /// Queue the grid and axis lines as seen through `view_mat`. They go
+    /// first so every object is painted over them.
+    pub(crate) fn draw_debug_gizmos(
+        &self,
+        view_mat: &Matrix4<f32>,
+        proj_mat: &Matrix4<f32>,
+        width: u32,
+        height: u32,
+        out: &mut Vec<Primitive>,
+    ) {
+        let grid = &self.grid;
+        let line_width = LINE_WIDTH * self.ssaa() as f32;
+        let clip_mat = proj_mat * view_mat;
+        let mut line = |a: Point3<f32>, b: Point3<f32>, color: Color| {
+            if let Some((sa, sb)) = project_line(&clip_mat, a, b, width, height) {
+                out.extend(thick_line(sa, sb, line_width, color).map(Primitive::Triangle));
+            }
+        };
+
+        // Split grid lines into one segment per cell so each can fade on
+        // its own.
+        let half = grid.cells as f32 * grid.spacing / 2.0;
+        let ticks = grid.cells as i32;
+        for i in 0..=ticks {
+            let across = -half + i as f32 * grid.spacing;
+            for j in 0..ticks {
+                let from = -half + j as f32 * grid.spacing;
+                let to = from + grid.spacing;
+                for (a, b) in [
+                    (Point3::new(across, 0.0, from), Point3::new(across, 0.0, to)),
+                    (Point3::new(from, 0.0, across), Point3::new(to, 0.0, across)),
+                ] {
+                    let distance = view_mat.transform_point(&center(&a, &b)).coords.norm();
+                    let fade = 1.0 - distance / grid.fade_distance;
+                    if fade <= 0.0 {
+                        continue;
+                    }
+                    let color = Color {
+                        a: (grid.color.a as f32 * fade).round() as u8,
+                        ..grid.color
+                    };
+                    line(a, b, color);
+                }
+            }
+        }
+
+        let axis = half.max(1.0);
+        let axes = [
+            (Point3::new(axis, 0.0, 0.0), (230, 40, 40)),
+            (Point3::new(0.0, axis, 0.0), (40, 200, 40)),
+            (Point3::new(0.0, 0.0, axis), (40, 80, 230)),
+        ];
+        for (end, (r, g, b)) in axes {
+            line(Point3::origin(), end, Color { r, g, b, a: 255 });
+        }
+    }
+}
+
+impl World {
+    /// Queue a line from the world space `centroid` along the unit `normal`.
+    /// `clip_mat` is `proj * view`.
+    pub(crate) fn draw_normal(
+        &self,
+        clip_mat: &Matrix4<f32>,
+        centroid: Point3<f32>,
+        normal: Vector3<f32>,
+        width: u32,
+        height: u32,
+        out: &mut Vec<Primitive>,
+    ) {
+        let tip = centroid + normal * NORMAL_LENGTH;
+        if let Some((a, b)) = project_line(clip_mat, centroid, tip, width, height) {
+            let line_width = LINE_WIDTH * self.ssaa() as f32;
+            out.extend(thick_line(a, b, line_width, NORMAL_COLOR).map(Primitive::Triangle));
+        }
+    }
+}
+
+impl World {
+    /// Queue a small unlit cross at the light's position with a line
+    /// pointing the way it shines. `clip_mat` is `proj * view`.
+    pub(crate) fn draw_light_marker(
+        &self,
+        clip_mat: &Matrix4<f32>,
+        width: u32,
+        height: u32,
+        out: &mut Vec<Primitive>,
+    ) {
+        let line_width = LINE_WIDTH * self.ssaa() as f32;
+        let position = self.light.position;
+        let mut segments: Vec<(Point3<f32>, Point3<f32>)> =
+            [Vector3::x(), Vector3::y(), Vector3::z()]
+                .iter()
+                .map(|axis| {
+                    let arm = axis * LIGHT_MARKER_SIZE;
+                    (position - arm, position + arm)
+                })
+                .collect();
+        if let Some(direction) = (self.light.target - position).try_normalize(f32::EPSILON) {
+            segments.push((position, position + direction * LIGHT_POINTER_LENGTH));
+        }
+        for (a, b) in segments {
+            if let Some((sa, sb)) = project_line(clip_mat, a, b, width, height) {
+                out.extend(thick_line(sa, sb, line_width, LIGHT_COLOR).map(Primitive::Triangle));
+            }
+        }
+    }
+}
+
+impl World {
+    /// Queue the twelve edges of the view of camera `index`, as numbered by
+    /// [`Viewport::camera_index`](crate::viewport::Viewport::camera_index),
+    /// through [`World::proj_mat`]. Nothing is drawn when looking through
+    /// that camera, when it doesn't exist, or when its planes don't close.
+    /// `clip_mat` is `proj * view`.
+    pub(crate) fn draw_frustum(
+        &self,
+        index: usize,
+        view_mat: &Matrix4<f32>,
+        clip_mat: &Matrix4<f32>,
+        width: u32,
+        height: u32,
+        out: &mut Vec<Primitive>,
+    ) {
+        let camera = match index {
+            0 => &self.camera,
+            n => match self.cameras.get(n - 1) {
+                Some(camera) => camera,
+                None => return,
+            },
+        };
+        let camera_view = camera.generate_view_mat();
+        if camera_view == *view_mat {
+            return;
+        }
+        let [left, right, bottom, top, zero, one] =
+            extract_frustum_planes(&(self.proj_mat * camera_view));
+        // Corners numbered with bit 0 for right, 1 for top and 2 for NDC z
+        // of 1.
+        let mut corners = [Point3::origin(); 8];
+        for (i, corner) in corners.iter_mut().enumerate() {
+            let pick = |bit: usize, a: Plane, b: Plane| if i & bit == 0 { a } else { b };
+            match Plane::intersection(
+                &pick(1, left, right),
+                &pick(2, bottom, top),
+                &pick(4, zero, one),
+            ) {
+                Some(point) => *corner = point,
+                None => return,
+            }
+        }
+        let line_width = LINE_WIDTH * self.ssaa() as f32;
+        // Pairs of corners one bit apart.
+        let edges = (0..8).flat_map(|i| [1, 2, 4].map(|bit| (i, i | bit)));
+        for (a, b) in edges.filter(|(a, b)| a != b) {
+            if let Some((sa, sb)) = project_line(clip_mat, corners[a], corners[b], width, height) {
+                out.extend(thick_line(sa, sb, line_width, FRUSTUM_COLOR).map(Primitive::Triangle));
+            }
+        }
+    }
+}
+
+/// Screen space end points of the world space segment `a b` through
+/// `clip_mat` (`proj * view`), clipped against the near plane. `None` when
+/// it is entirely behind it.
+fn project_line(
+    clip_mat: &Matrix4<f32>,
+    a: Point3<f32>,
+    b: Point3<f32>,
+    width: u32,
+    height: u32,
+) -> Option<(Point2<f32>, Point2<f32>)> {
+    let mut ca = clip_mat * a.to_homogeneous();
+    let mut cb = clip_mat * b.to_homogeneous();
+    // Inside the near plane when z >= -w.
+    let (da, db) = (ca.z + ca.w, cb.z + cb.w);
+    if da < 0.0 && db < 0.0 {
+        return None;
+    }
+    if da < 0.0 {
+        ca = ca.lerp(&cb, da / (da - db));
+    } else if db < 0.0 {
+        cb = cb.lerp(&ca, db / (db - da));
+    }
+    Some((
+        clip_to_screen(&ca, width, height),
+        clip_to_screen(&cb, width, height),
+    ))
+}
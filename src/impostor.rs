@@ -0,0 +1,88 @@
+//! Spheres drawn without triangles: each one covers a screen space square
+//! and every pixel in it is ray traced against the sphere analytically.
+
+use std::io;
+use std::path::Path;
+
+use nalgebra::Point3;
+
+use crate::mesh::Color;
+
+/// One sphere in world space.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Sphere {
+    pub center: Point3<f32>,
+    pub radius: f32,
+    pub color: Color,
+}
+
+/// A batch of spheres rendered as impostors, for particle or molecule style
+/// data sets far too big to triangulate.
+#[derive(Debug, Clone, Default)]
+pub struct SphereImpostor {
+    pub spheres: Vec<Sphere>,
+}
+
+impl SphereImpostor {
+    pub fn new(spheres: Vec<Sphere>) -> Self {
+        Self { spheres }
+    }
+
+    /// Parse `x,y,z,radius` lines, optionally followed by `,r,g,b` in
+    /// 0..=255. Blank lines and lines starting with `#` are skipped; spheres
+    /// without a color get `default_color`.
+    pub fn from_csv(bytes: &[u8], default_color: Color) -> io::Result<Self> {
+        let text = String::from_utf8_lossy(bytes);
+        let mut spheres = Vec::new();
+        for (number, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let invalid = |message: String| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("line {}: {message}", number + 1),
+                )
+            };
+            let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+            if fields.len() != 4 && fields.len() != 7 {
+                return Err(invalid(format!(
+                    "expected 4 or 7 fields, found {}",
+                    fields.len()
+                )));
+            }
+            let float = |i: usize| -> io::Result<f32> {
+                fields[i]
+                    .parse()
+                    .map_err(|_| invalid(format!("bad number {:?}", fields[i])))
+            };
+            let channel = |i: usize| -> io::Result<u8> {
+                fields[i]
+                    .parse()
+                    .map_err(|_| invalid(format!("bad color channel {:?}", fields[i])))
+            };
+            let color = if fields.len() == 7 {
+                Color {
+                    r: channel(4)?,
+                    g: channel(5)?,
+                    b: channel(6)?,
+                    a: 255,
+                }
+            } else {
+                default_color
+            };
+            spheres.push(Sphere {
+                center: Point3::new(float(0)?, float(1)?, float(2)?),
+                radius: float(3)?,
+                color,
+            });
+        }
+        Ok(Self { spheres })
+    }
+
+    /// Load a CSV data set from disk, see [`SphereImpostor::from_csv`].
+    pub fn from_path(path: impl AsRef<Path>, default_color: Color) -> io::Result<Self> {
+        Self::from_csv(&std::fs::read(path)?, default_color)
+    }
+}
@@ -0,0 +1,340 @@
+//! Key bindings and mouse settings for the viewer.
+//!
+//! Every action the viewer responds to has a default key, any of which can
+//! be rebound from a plain text file of `name = value` lines:
+//!
+//! ```text
+//! # AZERTY movement
+//! MoveForward = KeyZ
+//! StrafeLeft = KeyQ
+//! mouse_sensitivity = 0.002
+//! ```
+//!
+//! A key that already does something else by default swaps with it: above,
+//! the wireframe toggle moves from `KeyZ` to `KeyW`.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::io;
+use std::path::Path;
+
+macro_rules! actions {
+    ($($action:ident => $key:ident, $doc:literal;)*) => {
+        /// Something the viewer does when its key is pressed or held.
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        pub enum Action {
+            $(#[doc = $doc] $action,)*
+        }
+
+        impl Action {
+            /// Every action, in declaration order.
+            pub const ALL: &[Action] = &[$(Action::$action,)*];
+
+            pub fn name(self) -> &'static str {
+                match self {
+                    $(Action::$action => stringify!($action),)*
+                }
+            }
+
+            fn default_key(self) -> Key {
+                match self {
+                    $(Action::$action => Key::$key,)*
+                }
+            }
+        }
+    };
+}
+
+actions! {
+    MoveForward => KeyW, "Walk towards where the camera looks.";
+    MoveBack => KeyS, "Walk away from where the camera looks.";
+    StrafeLeft => KeyA, "Step sideways to the left.";
+    StrafeRight => KeyD, "Step sideways to the right.";
+    Quit => Escape, "Close the viewer.";
+    ToggleMouse => Tab, "Capture or release the mouse.";
+    CycleSelection => BracketRight, "Select the next object.";
+    Deselect => Backquote, "Clear the selection.";
+    SpawnCube => KeyN, "Drop a cube in front of the camera.";
+    DeleteSelected => Delete, "Remove the selected object.";
+    RotateSelected => KeyR, "Held: the object keys rotate instead of move.";
+    MoveSelectedForward => ArrowUp, "Held: move the selected object away from the camera.";
+    MoveSelectedBack => ArrowDown, "Held: move the selected object towards the camera.";
+    MoveSelectedLeft => ArrowLeft, "Held: move the selected object to the left.";
+    MoveSelectedRight => ArrowRight, "Held: move the selected object to the right.";
+    MoveSelectedUp => PageUp, "Held: move the selected object up.";
+    MoveSelectedDown => PageDown, "Held: move the selected object down.";
+    GrowSelected => NumpadAdd, "Held: scale the selected object up.";
+    ShrinkSelected => NumpadSubtract, "Held: scale the selected object down.";
+    LightForward => KeyI, "Held: move the light away from the camera.";
+    LightBack => KeyK, "Held: move the light towards the camera.";
+    LightLeft => KeyJ, "Held: move the light to the left.";
+    LightRight => KeyL, "Held: move the light to the right.";
+    LightUp => KeyU, "Held: move the light up.";
+    LightDown => KeyO, "Held: move the light down.";
+    DimLight => Comma, "Lower the light's intensity.";
+    BrightenLight => Period, "Raise the light's intensity.";
+    LessAmbient => Semicolon, "Lower the ambient light.";
+    MoreAmbient => Quote, "Raise the ambient light.";
+    Headlamp => Home, "Attach the light to the camera, or leave it where it is.";
+    CycleRenderMode => Digit0, "Switch between shaded, depth, point and ID views.";
+    ToggleToon => KeyT, "Switch toon shading on or off.";
+    ToggleSpotlight => KeyV, "Switch the light between directional and spot.";
+    FrameAll => KeyF, "Frame the whole scene, or orbit the selected object.";
+    ToggleOrbit => KeyC, "Switch the camera between flying and orbiting.";
+    ToggleGizmos => KeyG, "Show or hide the ground grid and axes.";
+    ToggleGamma => F2, "Switch gamma-correct lighting on or off.";
+    ToggleStats => F3, "Show or hide the stats overlay.";
+    ToggleNormals => F4, "Show or hide face normals.";
+    ToggleDither => F5, "Switch ordered dithering on or off.";
+    RecordTrack => F6, "Start or stop recording the camera path.";
+    PlayTrack => F7, "Play the recorded camera path, or stop playing it.";
+    ToggleCapture => F9, "Start capturing frames, or stop and save them.";
+    CycleSsaa => F8, "Step through the supersampling factors.";
+    ToggleProgressive => KeyP, "Switch progressive refinement on or off.";
+    ToggleRasterBackend => KeyB, "Switch between the bounding box and scanline rasterizers.";
+    LowerRenderScale => Minus, "Render fewer pixels.";
+    RaiseRenderScale => Equal, "Render more pixels.";
+    ToggleAdaptiveScale => KeyM, "Let the render scale follow the frame time budget, or stop.";
+    ToggleLayer1 => Digit1, "Show or hide the objects on layer 0.";
+    ToggleLayer2 => Digit2, "Show or hide the objects on layer 1.";
+    ToggleLayer3 => Digit3, "Show or hide the objects on layer 2.";
+    ToggleLayer4 => Digit4, "Show or hide the objects on layer 3.";
+    ToggleLayer5 => Digit5, "Show or hide the objects on layer 4.";
+    ToggleLayer6 => Digit6, "Show or hide the objects on layer 5.";
+    ToggleLayer7 => Digit7, "Show or hide the objects on layer 6.";
+    ToggleLayer8 => Digit8, "Show or hide the objects on layer 7.";
+    ToggleStereo => F10, "Switch red/cyan anaglyph stereo on or off.";
+    NarrowerEyes => F11, "Move the stereo eyes closer together.";
+    WiderEyes => F12, "Move the stereo eyes farther apart.";
+    FocusNearer => Insert, "Turn the stereo eyes in on a nearer point.";
+    FocusFarther => End, "Turn the stereo eyes in on a farther point.";
+    ExportScene => KeyX, "Write the shown objects, as arranged, to an OBJ file.";
+    ToggleOverlaps => KeyH, "Tint objects whose bounding boxes overlap red, or stop.";
+    ToggleWireframe => KeyZ, "Outline every triangle, or stop.";
+    TogglePaint => KeyY, "Paint the selected object with the left button, or stop.";
+    SavePaint => Backslash, "Write the texture being painted to an image file.";
+    Bookmark1 => Numpad1, "Fly to camera bookmark 1; with Ctrl, save it.";
+    Bookmark2 => Numpad2, "Fly to camera bookmark 2; with Ctrl, save it.";
+    Bookmark3 => Numpad3, "Fly to camera bookmark 3; with Ctrl, save it.";
+    Bookmark4 => Numpad4, "Fly to camera bookmark 4; with Ctrl, save it.";
+    Bookmark5 => Numpad5, "Fly to camera bookmark 5; with Ctrl, save it.";
+    Bookmark6 => Numpad6, "Fly to camera bookmark 6; with Ctrl, save it.";
+    Bookmark7 => Numpad7, "Fly to camera bookmark 7; with Ctrl, save it.";
+    Bookmark8 => Numpad8, "Fly to camera bookmark 8; with Ctrl, save it.";
+    Bookmark9 => Numpad9, "Fly to camera bookmark 9; with Ctrl, save it.";
+}
+
+macro_rules! keys {
+    ($($key:ident)*) => {
+        /// A key that can be bound, by where it is on a US layout rather
+        /// than what it types, named like winit's `KeyCode`.
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        pub enum Key {
+            $($key,)*
+        }
+
+        impl Key {
+            /// Every key, in declaration order.
+            pub const ALL: &[Key] = &[$(Key::$key,)*];
+
+            pub fn name(self) -> &'static str {
+                match self {
+                    $(Key::$key => stringify!($key),)*
+                }
+            }
+        }
+
+        #[cfg(feature = "viewer")]
+        impl From<Key> for winit::keyboard::KeyCode {
+            fn from(key: Key) -> Self {
+                match key {
+                    $(Key::$key => winit::keyboard::KeyCode::$key,)*
+                }
+            }
+        }
+    };
+}
+
+keys! {
+    KeyA KeyB KeyC KeyD KeyE KeyF KeyG KeyH KeyI KeyJ KeyK KeyL KeyM
+    KeyN KeyO KeyP KeyQ KeyR KeyS KeyT KeyU KeyV KeyW KeyX KeyY KeyZ
+    Digit0 Digit1 Digit2 Digit3 Digit4 Digit5 Digit6 Digit7 Digit8 Digit9
+    F1 F2 F3 F4 F5 F6 F7 F8 F9 F10 F11 F12
+    ArrowUp ArrowDown ArrowLeft ArrowRight PageUp PageDown Home End
+    Insert Delete Backspace Enter Tab Space Escape
+    ShiftLeft ShiftRight ControlLeft ControlRight AltLeft AltRight
+    Backquote Minus Equal BracketLeft BracketRight Backslash
+    Semicolon Quote Comma Period Slash IntlBackslash
+    Numpad0 Numpad1 Numpad2 Numpad3 Numpad4 Numpad5 Numpad6 Numpad7
+    Numpad8 Numpad9 NumpadAdd NumpadSubtract NumpadMultiply NumpadDivide
+    NumpadDecimal NumpadEnter
+}
+
+/// Why an input config could not be loaded.
+#[derive(Debug)]
+pub enum InputConfigError {
+    Io(io::Error),
+    Parse { line: usize, message: String },
+}
+
+impl fmt::Display for InputConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InputConfigError::Io(err) => write!(f, "{err}"),
+            InputConfigError::Parse { line, message } => write!(f, "line {line}: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for InputConfigError {}
+
+impl From<io::Error> for InputConfigError {
+    fn from(err: io::Error) -> Self {
+        InputConfigError::Io(err)
+    }
+}
+
+/// The key bound to every [`Action`], plus mouse and movement speeds.
+#[derive(Debug, Clone)]
+pub struct InputConfig {
+    bindings: HashMap<Action, Key>,
+    /// Radians the camera turns per pixel of mouse movement.
+    pub mouse_sensitivity: f32,
+    /// World units the camera walks per frame a movement key is held.
+    pub move_speed: f32,
+}
+
+impl Default for InputConfig {
+    fn default() -> Self {
+        Self {
+            bindings: Action::ALL
+                .iter()
+                .map(|&action| (action, action.default_key()))
+                .collect(),
+            mouse_sensitivity: 0.003,
+            move_speed: 0.1,
+        }
+    }
+}
+
+impl InputConfig {
+    /// The key bound to `action`.
+    pub fn key(&self, action: Action) -> Key {
+        self.bindings[&action]
+    }
+
+    /// The winit key code of the key bound to `action`.
+    #[cfg(feature = "viewer")]
+    pub fn key_code(&self, action: Action) -> winit::keyboard::KeyCode {
+        self.key(action).into()
+    }
+
+    /// The action bound to `key`, if any.
+    pub fn action(&self, key: Key) -> Option<Action> {
+        Action::ALL
+            .iter()
+            .copied()
+            .find(|action| self.bindings[action] == key)
+    }
+
+    /// Bind `key` to `action`. No two actions share a key: one already on
+    /// `key` takes over the key `action` had instead, and is returned.
+    pub fn bind(&mut self, action: Action, key: Key) -> Option<Action> {
+        let displaced = self.action(key).filter(|&holder| holder != action);
+        let previous = self.bindings.insert(action, key);
+        if let (Some(holder), Some(previous)) = (displaced, previous) {
+            self.bindings.insert(holder, previous);
+        }
+        displaced
+    }
+
+    /// Parse `name = value` lines on top of the defaults. Names are either
+    /// an [`Action`], bound to a key name like `KeyW` or `ArrowUp`, or one
+    /// of `mouse_sensitivity` and `move_speed`, which must be above zero.
+    /// Blank lines and lines starting with `#` are skipped.
+    ///
+    /// A key given to an action that has it by default is swapped, see
+    /// [`InputConfig::bind`]; two actions given the same key is an error.
+    pub fn parse(text: &str) -> Result<Self, InputConfigError> {
+        let mut config = Self::default();
+        let mut rebound = Vec::new();
+        for (number, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let parse_error = |message: String| InputConfigError::Parse {
+                line: number + 1,
+                message,
+            };
+            let Some((name, value)) = line.split_once('=') else {
+                return Err(parse_error("expected name = value".to_string()));
+            };
+            let (name, value) = (name.trim(), value.trim());
+            match name {
+                "mouse_sensitivity" | "move_speed" => {
+                    let number: f32 = value
+                        .parse()
+                        .map_err(|_| parse_error(format!("bad number {value:?}")))?;
+                    if !(number.is_finite() && number > 0.0) {
+                        return Err(parse_error(format!(
+                            "{name} must be a finite number above zero, not {value}"
+                        )));
+                    }
+                    if name == "mouse_sensitivity" {
+                        config.mouse_sensitivity = number;
+                    } else {
+                        config.move_speed = number;
+                    }
+                }
+                _ => {
+                    let action = Action::ALL
+                        .iter()
+                        .find(|action| action.name().eq_ignore_ascii_case(name))
+                        .ok_or_else(|| {
+                            let names: Vec<&str> = Action::ALL.iter().map(|a| a.name()).collect();
+                            parse_error(format!(
+                                "unknown setting {name:?}, expected mouse_sensitivity, \
+                                 move_speed or one of: {}",
+                                names.join(", ")
+                            ))
+                        })?;
+                    let key = parse_key(value).map_err(parse_error)?;
+                    if let Some(holder) = config.action(key)
+                        && holder != *action
+                        && rebound.contains(&holder)
+                    {
+                        return Err(parse_error(format!(
+                            "{value} is bound to both {} and {}",
+                            holder.name(),
+                            action.name()
+                        )));
+                    }
+                    config.bind(*action, key);
+                    rebound.push(*action);
+                }
+            }
+        }
+        Ok(config)
+    }
+
+    /// Load a config file, see [`InputConfig::parse`].
+    pub fn from_path(path: impl AsRef<Path>) -> Result<Self, InputConfigError> {
+        Self::parse(&std::fs::read_to_string(path)?)
+    }
+}
+
+/// Look up a key by name, ignoring case.
+fn parse_key(name: &str) -> Result<Key, String> {
+    Key::ALL
+        .iter()
+        .copied()
+        .find(|key| key.name().eq_ignore_ascii_case(name))
+        .ok_or_else(|| {
+            let names: Vec<&str> = Key::ALL.iter().map(|key| key.name()).collect();
+            format!(
+                "unknown key {name:?}, expected one of: {}",
+                names.join(", ")
+            )
+        })
+}
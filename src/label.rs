@@ -0,0 +1,148 @@
+//! Text pinned to points in the scene, for object names or coordinates at
+//! picked points. Labels are drawn with the overlay font after the 3D pass
+//! of every view, so they are never hidden behind geometry unless asked to
+//! be.
+
+use nalgebra::{Matrix4, Point3};
+
+use crate::depth::DepthConvention;
+use crate::mesh::Color;
+use crate::overlay::{Canvas, text_size};
+use crate::pipeline::project_vertex;
+use crate::world::ObjectRef;
+
+/// Pixels right of and above its anchor a label's text starts.
+pub const LABEL_OFFSET: i32 = 3;
+
+/// How far right of and above its anchor a leader line takes the text.
+pub const LEADER_LENGTH: i32 = 10;
+
+/// How much nearer than its anchor, as a fraction of the anchor's
+/// distance, a surface has to be to hide a
+/// [`hide_when_occluded`](Label::hide_when_occluded) label, so the surface
+/// it is pinned to doesn't.
+pub const OCCLUSION_TOLERANCE: f32 = 0.01;
+
+/// A line of text anchored at a point in the world.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Label {
+    /// In the world, or in the space of `object` when pinned to one.
+    pub position: Point3<f32>,
+    /// `\n` starts a new line below the first.
+    pub text: String,
+    pub color: Color,
+    /// Draw a line from the anchor out to the text, which sits further off.
+    pub leader: bool,
+    /// Leave the label out when the depth buffer holds something in front
+    /// of its anchor. Without [`World::depth_buffer`] nothing is known to
+    /// be in front, and the label is always drawn.
+    ///
+    /// [`World::depth_buffer`]: crate::World::depth_buffer
+    pub hide_when_occluded: bool,
+    /// Object the label moves with. Labels whose object is gone aren't
+    /// drawn.
+    pub object: Option<ObjectRef>,
+}
+
+impl Label {
+    /// A label with no leader line that shows through everything.
+    pub fn new(position: Point3<f32>, text: impl Into<String>, color: Color) -> Self {
+        Self {
+            position,
+            text: text.into(),
+            color,
+            leader: false,
+            hide_when_occluded: false,
+            object: None,
+        }
+    }
+
+    /// Move with `object`, reading `position` in its space.
+    pub fn pinned_to(mut self, object: impl Into<ObjectRef>) -> Self {
+        self.object = Some(object.into());
+        self
+    }
+}
+
+/// The depth buffer a view was drawn with, `factor` times the view's size
+/// each way.
+pub(crate) struct DepthSamples<'a> {
+    pub values: &'a [f32],
+    pub factor: u32,
+    pub convention: DepthConvention,
+}
+
+/// Draw the `labels` in front of the camera onto a `width * height` frame
+/// seen through `view_mat` and `proj_mat`, returning how many were drawn.
+pub(crate) fn draw_labels(
+    labels: &[Label],
+    view_mat: &Matrix4<f32>,
+    proj_mat: &Matrix4<f32>,
+    depth: Option<DepthSamples>,
+    frame: &mut [u8],
+    width: u32,
+    height: u32,
+) -> usize {
+    let mut canvas = Canvas::new(frame, width, height);
+    let mut drawn = 0;
+    for label in labels {
+        let anchor = view_mat.transform_point(&label.position);
+        let projected = project_vertex(anchor, proj_mat, width, height);
+        if projected.clipped {
+            continue;
+        }
+        let (x, y) = (
+            projected.screen.x.floor() as i32,
+            projected.screen.y.floor() as i32,
+        );
+        if label.hide_when_occluded
+            && let Some(depth) = &depth
+            && occluded(depth, anchor, proj_mat, (x, y), width, height)
+        {
+            continue;
+        }
+        let reach = if label.leader {
+            canvas.draw_line(x, y, x + LEADER_LENGTH, y - LEADER_LENGTH, label.color);
+            LEADER_LENGTH
+        } else {
+            LABEL_OFFSET
+        };
+        let (_, text_height) = text_size(&label.text);
+        canvas.draw_text(
+            x + reach + 1,
+            y - reach - text_height as i32,
+            &label.text,
+            label.color,
+        );
+        drawn += 1;
+    }
+    drawn
+}
+
+/// Whether the depth buffer holds a surface in front of the view space
+/// point `anchor`, which lands on pixel `(x, y)`.
+fn occluded(
+    depth: &DepthSamples,
+    anchor: Point3<f32>,
+    proj_mat: &Matrix4<f32>,
+    (x, y): (i32, i32),
+    width: u32,
+    height: u32,
+) -> bool {
+    if x < 0 || y < 0 || x >= width as i32 || y >= height as i32 {
+        return false;
+    }
+    // The middle sample of the pixel's block when supersampled.
+    let (sx, sy) = (
+        x as u32 * depth.factor + depth.factor / 2,
+        y as u32 * depth.factor + depth.factor / 2,
+    );
+    let Some(&stored) = depth.values.get((sy * width * depth.factor + sx) as usize) else {
+        return false;
+    };
+    // Pull the anchor towards the eye before comparing, so the surface it
+    // sits on doesn't count.
+    let nearer = Point3::from(anchor.coords * (1.0 - OCCLUSION_TOLERANCE));
+    let nearer = proj_mat * nearer.to_homogeneous();
+    depth.convention.passes(stored, nearer.z / nearer.w)
+}
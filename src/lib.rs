@@ -0,0 +1,69 @@
+//! A tiny software rasterizer.
+//!
+//! The library owns everything needed to turn a [`World`] into RGBA bytes;
+//! the `engine` binary is only a winit/pixels front-end on top of it.
+
+pub mod animation;
+pub mod asset;
+pub mod billboard;
+pub mod bookmark;
+pub mod camera;
+pub mod capture;
+pub mod chunked;
+pub mod cli;
+mod collision;
+pub mod color;
+pub mod depth;
+mod export;
+pub mod fog;
+pub mod gizmo;
+pub mod impostor;
+pub mod input;
+pub mod label;
+pub mod light;
+mod loader;
+pub mod lod;
+pub mod material;
+pub mod mesh;
+pub mod output;
+pub mod overlay;
+pub mod pacing;
+pub mod paint;
+pub mod palette;
+pub mod particle;
+pub mod pick;
+pub mod pipeline;
+pub mod planar_shadow;
+#[cfg(feature = "png")]
+pub mod png;
+pub mod profile;
+pub mod progressive;
+pub mod raster;
+pub mod reduce;
+pub mod render_scale;
+pub mod render_texture;
+pub mod scene_file;
+mod shadow;
+pub mod sky;
+mod splitting;
+pub mod stereo;
+pub mod target;
+pub mod texture;
+pub mod track;
+pub mod viewport;
+pub mod watch;
+pub mod world;
+
+pub use animation::Animation;
+pub use billboard::Billboard;
+pub use bookmark::CameraPose;
+pub use camera::{Camera, CameraMode};
+pub use label::Label;
+pub use light::{Ambient, Light, LightKind, ShadingModel};
+pub use material::{Material, MaterialHandle, MaterialLibrary, MaterialOverride};
+pub use particle::ParticleEmitter;
+pub use raster::RasterBackend;
+pub use world::{
+    AddObjectError, ClearMode, ClipPlaneError, CullMode, DuplicateNames, HierarchyError, Object,
+    ObjectRef, Placement, RenderMode, RenderStats, UnsupportedSsaa, World,
+};
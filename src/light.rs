@@ -0,0 +1,332 @@
+use nalgebra::{Point3, Vector3};
+
+use crate::color::{linear_to_srgb, linear_to_srgb_exact, srgb_to_linear};
+use crate::fog::Fog;
+use crate::material::Material;
+use crate::mesh::Color;
+
+/// A single light shining from `position` towards `target`.
+pub struct Light {
+    pub position: Point3<f32>,
+    pub target: Point3<f32>,
+    pub kind: LightKind,
+    pub intensity: f32,
+    /// Width and height of the shadow map in texels; 0 turns shadows off.
+    pub shadow_resolution: u32,
+    /// How much closer to the light, in world units, an occluder must be to
+    /// cast a shadow. Raise it to fight shadow acne, lower it if shadows
+    /// detach from their casters.
+    pub shadow_bias: f32,
+}
+
+/// The shape of the light a [`Light`] casts.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum LightKind {
+    /// Parallel rays along `target - position`, lighting everything.
+    #[default]
+    Directional,
+    /// A cone from `position` around `target - position`. Surfaces within
+    /// `inner_angle` radians of the axis get the full light, which fades
+    /// smoothly to nothing at `outer_angle`. An inner angle wider than the
+    /// outer one is clamped to it. Shadows are still cast along the axis,
+    /// as for a directional light.
+    Spot { inner_angle: f32, outer_angle: f32 },
+}
+
+impl Light {
+    /// Unit direction the light travels when it reaches `point`.
+    pub fn direction_at(&self, point: &Point3<f32>) -> Vector3<f32> {
+        let from = match self.kind {
+            LightKind::Directional => self.target - self.position,
+            LightKind::Spot { .. } => point - self.position,
+        };
+        from.try_normalize(f32::EPSILON)
+            .unwrap_or_else(|| (self.target - self.position).normalize())
+    }
+
+    /// How much of the light reaches `point`, from 0 to 1. Always 1 for a
+    /// directional light.
+    pub fn falloff(&self, point: &Point3<f32>) -> f32 {
+        let LightKind::Spot {
+            inner_angle,
+            outer_angle,
+        } = self.kind
+        else {
+            return 1.0;
+        };
+        let outer = outer_angle.clamp(0.0, std::f32::consts::PI);
+        let inner = inner_angle.clamp(0.0, outer);
+        let Some(axis) = (self.target - self.position).try_normalize(f32::EPSILON) else {
+            return 0.0;
+        };
+        let angle = axis.dot(&self.direction_at(point)).clamp(-1.0, 1.0).acos();
+        if angle <= inner {
+            1.0
+        } else if angle >= outer {
+            0.0
+        } else {
+            let t = (outer - angle) / (outer - inner);
+            t * t * (3.0 - 2.0 * t)
+        }
+    }
+}
+
+/// Light reaching every surface whatever the lights do, standing in for
+/// light bounced around the scene.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Ambient {
+    /// The same fraction of the surface color everywhere.
+    Uniform(f32),
+    /// Sky light from above and ground bounce from below, blended by how
+    /// far the surface faces up. `strength` scales both colors.
+    Hemisphere {
+        sky_color: Color,
+        ground_color: Color,
+        strength: f32,
+    },
+}
+
+impl Default for Ambient {
+    fn default() -> Self {
+        Ambient::Uniform(0.3)
+    }
+}
+
+impl Ambient {
+    /// Red, green and blue ambient light on a surface whose outward unit
+    /// normal has world space y component `up`: 1 facing the sky, -1 facing
+    /// the ground.
+    pub fn light(&self, up: f32) -> [f32; 3] {
+        match *self {
+            Ambient::Uniform(amount) => [amount; 3],
+            Ambient::Hemisphere {
+                sky_color,
+                ground_color,
+                strength,
+            } => {
+                let t = (up.clamp(-1.0, 1.0) + 1.0) / 2.0;
+                let mix = |ground: u8, sky: u8| {
+                    (ground as f32 + (sky as f32 - ground as f32) * t) / 255.0 * strength
+                };
+                [
+                    mix(ground_color.r, sky_color.r),
+                    mix(ground_color.g, sky_color.g),
+                    mix(ground_color.b, sky_color.b),
+                ]
+            }
+        }
+    }
+
+    /// The overall amount, which the key bindings raise and lower.
+    pub fn strength_mut(&mut self) -> &mut f32 {
+        match self {
+            Ambient::Uniform(amount) => amount,
+            Ambient::Hemisphere { strength, .. } => strength,
+        }
+    }
+}
+
+/// How the diffuse term is turned into brightness.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum ShadingModel {
+    /// Smooth Lambertian falloff.
+    #[default]
+    Lambert,
+    /// Diffuse light snapped to `bands` flat levels for a cartoon look. The
+    /// darkest band gets no diffuse light but keeps the ambient, so it is
+    /// only black when ambient is zero.
+    Toon { bands: u8 },
+}
+
+impl ShadingModel {
+    /// Map a diffuse term in 0..=1 through the model.
+    pub fn diffuse(self, diffuse: f32) -> f32 {
+        match self {
+            ShadingModel::Lambert => diffuse,
+            ShadingModel::Toon { bands } if bands <= 1 => 1.0,
+            ShadingModel::Toon { bands } => {
+                let steps = bands as f32;
+                let band = (diffuse * steps).floor().min(steps - 1.0);
+                band / (steps - 1.0)
+            }
+        }
+    }
+}
+
+/// What lighting a triangle takes from the world, borrowed for one frame
+/// so the rasterizer can light each triangle when it gets to it.
+#[derive(Clone, Copy)]
+pub(crate) struct SceneLighting<'a> {
+    pub light: &'a Light,
+    pub ambient: Ambient,
+    pub model: ShadingModel,
+    /// Camera position, for highlights.
+    pub eye: Point3<f32>,
+    pub fog: Option<Fog>,
+    pub gamma: bool,
+    /// Also work out the unrounded color, for dithering.
+    pub dither: bool,
+}
+
+/// One triangle's surface as [`SceneLighting::shade`] lights it.
+#[derive(Clone, Copy)]
+pub(crate) struct Surface {
+    pub material: Material,
+    /// Face normal, pointing into the surface.
+    pub normal: Vector3<f32>,
+    /// The point lit for the whole triangle, in world space.
+    pub point: Point3<f32>,
+    /// View space depth of the point, for fog.
+    pub distance: f32,
+    pub shadowed: bool,
+}
+
+/// The light over one surface, worked out once for a triangle and then
+/// applied to its color, or texel by texel to its texture.
+#[derive(Clone, Copy)]
+pub(crate) struct SurfaceLight {
+    coloring: [f32; 3],
+    fog_color: Color,
+    fog: f32,
+    gamma: bool,
+    dither: bool,
+}
+
+impl SurfaceLight {
+    /// `color` under the light, and its unrounded channels when dithering.
+    pub(crate) fn apply(&self, color: &Color) -> (Color, Option<[f32; 3]>) {
+        let (coloring, fog_color, fog) = (self.coloring, self.fog_color, self.fog);
+        (
+            apply_lighting(color, coloring, fog_color, fog, self.gamma),
+            self.dither
+                .then(|| apply_lighting_exact(color, coloring, fog_color, fog, self.gamma)),
+        )
+    }
+}
+
+impl SceneLighting<'_> {
+    /// The color of `surface`, and its unrounded channels when dithering.
+    pub(crate) fn shade(&self, surface: &Surface) -> (Color, Option<[f32; 3]>) {
+        self.light(surface).apply(&surface.material.color)
+    }
+
+    /// The light over `surface`, to apply to more than its color.
+    pub(crate) fn light(&self, surface: &Surface) -> SurfaceLight {
+        let (coloring, fog_color, fog) = self.coloring(surface);
+        SurfaceLight {
+            coloring,
+            fog_color,
+            fog,
+            gamma: self.gamma,
+            dither: self.dither,
+        }
+    }
+
+    /// Ambient, diffuse and emissive lighting for `surface`, and the fog
+    /// color and amount over it. Shadowed surfaces get no diffuse light.
+    fn coloring(&self, surface: &Surface) -> ([f32; 3], Color, f32) {
+        let Surface {
+            material,
+            normal: norm,
+            point,
+            distance,
+            shadowed,
+        } = surface;
+        let light_dir = self.light.direction_at(point);
+        // `norm` points into the surface; the sky is above its outside.
+        let up = (-norm).try_normalize(f32::EPSILON).map_or(0.0, |out| out.y);
+        let ambient = self.ambient.light(up);
+        let diffuse = if *shadowed {
+            0.0
+        } else {
+            let intensity = self.light.intensity * self.light.falloff(point);
+            let lambert = (light_dir.dot(norm) * intensity).clamp(0.0, 1.0);
+            self.model.diffuse(lambert) * material.diffuse
+        };
+        // Blinn-Phong, once per triangle like the rest of the lighting.
+        let specular = if *shadowed || material.specular <= 0.0 {
+            0.0
+        } else {
+            let to_eye = (self.eye - point).try_normalize(f32::EPSILON);
+            match to_eye.and_then(|to_eye| (to_eye - light_dir).try_normalize(f32::EPSILON)) {
+                Some(half) if light_dir.dot(norm) > 0.0 => {
+                    let intensity = self.light.intensity * self.light.falloff(point);
+                    // `norm` points into the surface.
+                    let facing = (-norm.normalize()).dot(&half).max(0.0);
+                    material.specular * facing.powf(material.shininess.max(1.0)) * intensity
+                }
+                _ => 0.0,
+            }
+        };
+        let coloring = ambient.map(|ambient| ambient + diffuse + material.emissive + specular);
+        let (fog_color, fog) = match &self.fog {
+            Some(fog) => (fog.color, fog.factor(*distance)),
+            None => (material.color, 0.0),
+        };
+        (coloring, fog_color, fog)
+    }
+}
+
+fn _reflected_ray(incident: Vector3<f32>, normal: &Vector3<f32>) -> Vector3<f32> {
+    incident - (normal * (incident.dot(normal))).scale(2.0)
+}
+
+/// Scale `color` by the summed red, green and blue light `coloring` and mix
+/// in `fog` parts of `fog_color`. With `gamma` the math happens in linear
+/// light.
+pub(crate) fn apply_lighting(
+    color: &Color,
+    coloring: [f32; 3],
+    fog_color: Color,
+    fog: f32,
+    gamma: bool,
+) -> Color {
+    if fog >= 1.0 {
+        return Color {
+            a: color.a,
+            ..fog_color
+        };
+    }
+    if !gamma {
+        return Color {
+            a: color.a,
+            ..color.tint(coloring).lerp(fog_color, fog)
+        };
+    }
+    let colormap = |comp: u8, fog_comp: u8, coloring: f32| -> u8 {
+        let lit = (srgb_to_linear(comp) * coloring).min(1.0);
+        linear_to_srgb(srgb_to_linear(fog_comp) * fog + lit * (1.0 - fog))
+    };
+    Color {
+        r: colormap(color.r, fog_color.r, coloring[0]),
+        g: colormap(color.g, fog_color.g, coloring[1]),
+        b: colormap(color.b, fog_color.b, coloring[2]),
+        a: color.a,
+    }
+}
+
+/// [`apply_lighting`] without the final rounding: the lit red, green and
+/// blue channels in `0.0..=255.0`, for dithering.
+pub(crate) fn apply_lighting_exact(
+    color: &Color,
+    coloring: [f32; 3],
+    fog_color: Color,
+    fog: f32,
+    gamma: bool,
+) -> [f32; 3] {
+    let fog = fog.min(1.0);
+    let colormap = |comp: u8, fog_comp: u8, coloring: f32| -> f32 {
+        if gamma {
+            let lit = (srgb_to_linear(comp) * coloring).min(1.0);
+            linear_to_srgb_exact(srgb_to_linear(fog_comp) * fog + lit * (1.0 - fog))
+        } else {
+            let lit = ((comp as f32) * coloring).min(255.0);
+            fog_comp as f32 * fog + lit * (1.0 - fog)
+        }
+    };
+    [
+        colormap(color.r, fog_color.r, coloring[0]),
+        colormap(color.g, fog_color.g, coloring[1]),
+        colormap(color.b, fog_color.b, coloring[2]),
+    ]
+}
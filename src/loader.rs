@@ -0,0 +1,61 @@
+//! Meshes loaded on a thread of their own, for files big enough to stall a
+//! frame. See [`World::load_object_async`](crate::World::load_object_async).
+
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::thread;
+
+use crate::lod::simplified_levels;
+use crate::mesh::ImportOptions;
+use crate::mesh::obj::{ObjError, ObjMesh};
+use crate::mesh::simplify::SimplifiedMesh;
+
+/// What a loader thread hands back.
+pub(crate) struct LoadedMesh {
+    pub mesh: ObjMesh,
+    /// Ever coarser levels of detail, when asked for.
+    pub coarser: Vec<SimplifiedMesh>,
+}
+
+/// An OBJ file being read on a loader thread, for the object at `object`.
+pub(crate) struct PendingLoad {
+    pub path: PathBuf,
+    /// Index in [`World::models`](crate::World::models) of the placeholder.
+    pub object: usize,
+    receiver: Receiver<Result<LoadedMesh, ObjError>>,
+}
+
+impl PendingLoad {
+    /// Start reading `path` with `options` in the background, then
+    /// simplifying it into `lod_levels` coarser meshes.
+    pub fn spawn(path: &Path, object: usize, options: ImportOptions, lod_levels: usize) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        let thread_path = path.to_path_buf();
+        thread::spawn(move || {
+            let mesh = ObjMesh::from_path(&thread_path, options).map(|mesh| LoadedMesh {
+                coarser: simplified_levels(&mesh, lod_levels),
+                mesh,
+            });
+            // Nobody is waiting any more if the world was dropped or the
+            // object removed, which is fine.
+            let _ = sender.send(mesh);
+        });
+        Self {
+            path: path.to_path_buf(),
+            object,
+            receiver,
+        }
+    }
+
+    /// The loaded mesh or why it failed, once the thread is done.
+    pub fn poll(&self) -> Option<Result<LoadedMesh, ObjError>> {
+        match self.receiver.try_recv() {
+            Ok(result) => Some(result),
+            Err(TryRecvError::Empty) => None,
+            Err(TryRecvError::Disconnected) => Some(Err(ObjError::Io(io::Error::other(
+                "loader thread stopped without a mesh",
+            )))),
+        }
+    }
+}
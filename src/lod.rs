@@ -0,0 +1,127 @@
+//! Levels of detail: coarser stand-ins for a mesh, swapped in as its object
+//! moves away from the camera. See [`Object::lods`](crate::Object::lods).
+
+use std::rc::Rc;
+
+use nalgebra::Vector3;
+
+use crate::mesh::capsule::CapsuleMesh;
+use crate::mesh::simplify::SimplifiedMesh;
+use crate::mesh::terrain::{TerrainError, TerrainMesh};
+use crate::mesh::{Color, Mesh, PrimitiveError, simplify};
+
+/// Meshes paired with the farthest view-space distance each is drawn at,
+/// finest and nearest first.
+pub type Lods = Vec<(f32, Rc<dyn Mesh>)>;
+
+/// How far past a threshold, as a fraction of it, an object has to move
+/// before it changes level. Without it an object resting on a threshold
+/// would pop between two meshes every frame.
+pub const LOD_HYSTERESIS: f32 = 0.1;
+
+/// The index in `lods` to draw at `distance` in front of the camera, for an
+/// object last drawn at level `current`. The first level whose distance
+/// covers `distance` wins, and the last is used past them all; but the
+/// current level is kept until the object is [`LOD_HYSTERESIS`] beyond
+/// either of its edges. `lods` must not be empty.
+pub fn select_lod(lods: &[(f32, Rc<dyn Mesh>)], distance: f32, current: usize) -> usize {
+    let last = lods.len() - 1;
+    if current <= last {
+        let near = if current == 0 {
+            f32::NEG_INFINITY
+        } else {
+            lods[current - 1].0 * (1.0 - LOD_HYSTERESIS)
+        };
+        let far = if current == last {
+            f32::INFINITY
+        } else {
+            lods[current].0 * (1.0 + LOD_HYSTERESIS)
+        };
+        if near < distance && distance <= far {
+            return current;
+        }
+    }
+    lods.iter()
+        .position(|(max, _)| distance <= *max)
+        .unwrap_or(last)
+}
+
+/// Spheres of `radius` for each of `distances`, starting at `rings` bands
+/// from pole to equator and halving with each level, down to two. Every
+/// level has twice as many sides as rings per hemisphere.
+pub fn sphere_lods(
+    radius: f32,
+    rings: usize,
+    distances: &[f32],
+    color: Color,
+) -> Result<Lods, PrimitiveError> {
+    distances
+        .iter()
+        .enumerate()
+        .map(|(level, &distance)| {
+            let rings = (rings >> level).max(2);
+            let sphere = CapsuleMesh::new(radius, 0.0, rings, 4 * rings, color)?;
+            Ok((distance, Rc::new(sphere) as Rc<dyn Mesh>))
+        })
+        .collect()
+}
+
+/// Terrains for each of `distances` from the same heightmap as
+/// [`TerrainMesh::from_heightmap`], keeping every second sample along both
+/// axes at each level after the first, until a side is down to two.
+/// Sizes of the form `2^n + 1` keep the edges of every level in place.
+pub fn terrain_lods(
+    width: usize,
+    depth: usize,
+    heights: &[f32],
+    scale: Vector3<f32>,
+    color_fn: impl Fn(f32) -> Color,
+    distances: &[f32],
+) -> Result<Lods, TerrainError> {
+    let mut lods = Lods::with_capacity(distances.len());
+    for (level, &distance) in distances.iter().enumerate() {
+        if level == 0 {
+            let terrain = TerrainMesh::from_heightmap(width, depth, heights, scale, &color_fn)?;
+            lods.push((distance, Rc::new(terrain)));
+            continue;
+        }
+        // The full size level above has checked the sizes.
+        let step = (1usize << level.min(16)).min(width - 1).min(depth - 1);
+        let (w, d) = ((width - 1) / step + 1, (depth - 1) / step + 1);
+        let sampled: Vec<f32> = (0..d)
+            .flat_map(|row| (0..w).map(move |col| heights[row * step * width + col * step]))
+            .collect();
+        let scale = Vector3::new(scale.x * step as f32, scale.y, scale.z * step as f32);
+        let terrain = TerrainMesh::from_heightmap(w, d, &sampled, scale, &color_fn)?;
+        lods.push((distance, Rc::new(terrain)));
+    }
+    Ok(lods)
+}
+
+/// `mesh` itself for the first of `distances`, then [`simplify`]'d copies
+/// for the rest, each with half the triangles of the level before.
+pub fn simplified_lods(mesh: Rc<dyn Mesh>, distances: &[f32]) -> Lods {
+    let Some((&first, rest)) = distances.split_first() else {
+        return Lods::new();
+    };
+    let coarser = simplified_levels(&*mesh, rest.len());
+    let mut lods: Lods = vec![(first, mesh)];
+    lods.extend(
+        rest.iter()
+            .zip(coarser)
+            .map(|(&distance, level)| (distance, Rc::new(level) as Rc<dyn Mesh>)),
+    );
+    lods
+}
+
+/// `count` ever coarser copies of `mesh`, each simplified from the one
+/// before to half its triangles.
+pub(crate) fn simplified_levels(mesh: &dyn Mesh, count: usize) -> Vec<SimplifiedMesh> {
+    let mut levels: Vec<SimplifiedMesh> = Vec::with_capacity(count);
+    for _ in 0..count {
+        let previous = levels.last().map_or(mesh, |level| level as &dyn Mesh);
+        let triangles = previous.primitives().triangles().count();
+        levels.push(simplify(previous, triangles / 2));
+    }
+    levels
+}
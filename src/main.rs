@@ -1,275 +1,1034 @@
-mod mesh;
+use engine::bookmark::{BOOKMARK_SLOTS, BookmarkError, load_bookmarks, save_bookmarks};
+use engine::capture::Capture;
+use engine::chunked::{ChunkCache, TRIANGLES_PER_CHUNK};
+use engine::cli::{
+    self, ClipPlanes, Command, ModelInfo, ReduceArgs, RenderArgs, SceneArgs, SimplifyArgs,
+    SmallTriangles, USAGE, ViewArgs,
+};
+use engine::input::{Action, InputConfig};
+use engine::lod::sphere_lods;
+use engine::mesh::cube::CubeMesh;
+use engine::mesh::obj::{ObjMesh, save_obj};
+use engine::mesh::p_hack::PHackMesh;
+use engine::mesh::quad::QuadMesh;
+use engine::mesh::{Color, ImportOptions, Mesh, simplify};
+use engine::output::{self, ImageFormat};
+use engine::overlay::{Canvas, FpsCounter, text_size};
+use engine::pacing::{FramePacer, SPIN_MARGIN, sleep_until};
+use engine::paint::{Brush, Stroke};
+use engine::palette::IdPalette;
+use engine::particle::{ParticleEmitter, scatter};
+use engine::planar_shadow::PlanarShadowConfig;
+use engine::profile::{ProfileSummary, Profiler, Stage, StageTimes};
+use engine::progressive::Accumulator;
+use engine::reduce::reduce;
+use engine::render_scale::{
+    MAX_RENDER_SCALE, MIN_RENDER_SCALE, resample, scaled_size, step_render_scale,
+};
+use engine::scene_file::SceneFile;
+use engine::sky::Sky;
+use engine::stereo::Stereo;
+use engine::texture::{Texture, TextureHandle};
+use engine::track::CameraTrack;
+use engine::viewport::{Projection, Viewport};
+use engine::watch::FileWatcher;
+use engine::{
+    Ambient, Animation, Billboard, Camera, CameraMode, ClearMode, Label, Light, LightKind,
+    Material, Object, RasterBackend, ShadingModel, World,
+};
+use nalgebra::{Matrix4, Perspective3, Point3, Unit, UnitQuaternion, Vector2, Vector3};
 
-use mesh::p_hack::PHackMesh;
-use mesh::Color;
-use mesh::Mesh as MyMesh;
-use mesh::Triangle;
-use nalgebra::{Matrix4, Perspective3, Point2, Point3, Point4, Vector3, Vector4};
-use ordered_float::OrderedFloat;
+use std::f32::consts::FRAC_PI_2;
+use std::io;
+use std::panic::AssertUnwindSafe;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
 
-use log::error;
+use log::{debug, error, info, warn};
+use pixels::wgpu::PresentMode;
 use pixels::{Error, Pixels, SurfaceTexture};
 use winit::dpi::LogicalSize;
-use winit::event::{Event, WindowEvent};
-use winit::event_loop::EventLoop;
-use winit::keyboard::KeyCode;
-use winit::window::WindowBuilder;
+use winit::event::{Event, MouseButton, WindowEvent};
+use winit::event_loop::{ControlFlow, EventLoop};
+use winit::window::{CursorGrabMode, Window, WindowBuilder};
 use winit_input_helper::WinitInputHelper;
 
-const WIDTH: u32 = 500;
-const HEIGHT: u32 = 500;
+/// Layers of the demo scene, shown or hidden with keys 1 to 8. Everything
+/// else is on layer 0.
+const FLOOR_LAYER: u32 = 1;
+const INSTANCE_LAYER: u32 = 2;
+const LOD_LAYER: u32 = 3;
+const MODEL_LAYER: u32 = 4;
+/// Actions toggling layers 0 to 7 of `World::visible_layers`, on keys 1
+/// to 8.
+const LAYER_ACTIONS: [Action; 8] = [
+    Action::ToggleLayer1,
+    Action::ToggleLayer2,
+    Action::ToggleLayer3,
+    Action::ToggleLayer4,
+    Action::ToggleLayer5,
+    Action::ToggleLayer6,
+    Action::ToggleLayer7,
+    Action::ToggleLayer8,
+];
+/// Actions flying to camera bookmarks 1 to 9, or saving them with Ctrl
+/// held, on the numpad digits since the others toggle layers.
+const BOOKMARK_ACTIONS: [Action; BOOKMARK_SLOTS] = [
+    Action::Bookmark1,
+    Action::Bookmark2,
+    Action::Bookmark3,
+    Action::Bookmark4,
+    Action::Bookmark5,
+    Action::Bookmark6,
+    Action::Bookmark7,
+    Action::Bookmark8,
+    Action::Bookmark9,
+];
+/// Seconds between keyframes while recording a camera track.
+const TRACK_INTERVAL: f32 = 0.25;
+/// Seconds the scene advances in each update, 60 times a second.
+const UPDATE_STEP: f32 = 1.0 / 60.0;
+/// Most seconds of updates run to catch up after a stall, so a slow frame
+/// doesn't leave ever more updates to run the next.
+const MAX_CATCH_UP: f32 = 0.25;
+/// Color of the wireframe, dark enough to show on the ID colors.
+const WIREFRAME_COLOR: Color = Color::rgb(20, 20, 20);
+/// Color of the circle showing where the brush paints.
+const BRUSH_CURSOR_COLOR: Color = Color::YELLOW;
 
-pub struct Object {
-    mesh: Box<dyn MyMesh>,
-    offset_x: f32,
-    offset_y: f32,
-    offset_z: f32,
+/// Walk the camera with the movement keys, or pan the focus when orbiting.
+fn handle_keys(input: &WinitInputHelper, camera: &mut Camera, keys: &InputConfig) -> Matrix4<f32> {
+    let move_speed = keys.move_speed;
+    let held = |action: Action| input.key_held(keys.key_code(action));
+    if let CameraMode::Orbit { .. } = camera.mode {
+        let axis = |plus: Action, minus: Action| {
+            (held(plus) as i32 - held(minus) as i32) as f32 * move_speed
+        };
+        let right = axis(Action::StrafeRight, Action::StrafeLeft);
+        let up = axis(Action::MoveForward, Action::MoveBack);
+        if right != 0.0 || up != 0.0 {
+            camera.pan(right, up);
+        }
+    } else {
+        // Walk level with the ground whatever the camera pitch.
+        let step = if held(Action::StrafeLeft) {
+            -camera.right()
+        } else if held(Action::StrafeRight) {
+            camera.right()
+        } else if held(Action::MoveForward) {
+            camera.forward()
+        } else if held(Action::MoveBack) {
+            -camera.forward()
+        } else {
+            Vector3::zeros()
+        };
+        camera.position.x += step.x * move_speed;
+        camera.position.z += step.z * move_speed;
+    }
+    camera.generate_view_mat()
 }
 
-pub struct Camera {
-    pub position: Point3<f32>,
-    pub target: Point3<f32>,
-    pub up: Vector3<f32>,
-    pub pitch: f32,
-    pub yaw: f32,
+/// Move, rotate or scale the selected object with the `*Selected` keys,
+/// by default the arrow keys, PageUp / PageDown, `R` and numpad `+` / `-`.
+/// Does nothing without a selection.
+fn edit_selected(input: &WinitInputHelper, world: &mut World, keys: &InputConfig) {
+    let Some(index) = world.selected else {
+        return;
+    };
+    let forward = world.camera.forward();
+    let right = world.camera.right();
+    let Some(object) = world.models.get_mut(index) else {
+        return;
+    };
+
+    let held = |action: Action| input.key_held(keys.key_code(action)) as i32 as f32;
+    let sideways = held(Action::MoveSelectedRight) - held(Action::MoveSelectedLeft);
+    let ahead = held(Action::MoveSelectedForward) - held(Action::MoveSelectedBack);
+    let vertical = held(Action::MoveSelectedUp) - held(Action::MoveSelectedDown);
+    let grow = held(Action::GrowSelected) - held(Action::ShrinkSelected);
+    if sideways == 0.0 && ahead == 0.0 && vertical == 0.0 && grow == 0.0 {
+        return;
+    }
+
+    if input.key_held(keys.key_code(Action::RotateSelected)) {
+        let turn_speed = 0.03;
+        let yaw = UnitQuaternion::from_axis_angle(&Vector3::y_axis(), sideways * turn_speed);
+        let pitch =
+            UnitQuaternion::from_axis_angle(&Unit::new_normalize(right), ahead * turn_speed);
+        let roll =
+            UnitQuaternion::from_axis_angle(&Unit::new_normalize(forward), vertical * turn_speed);
+        object.rotation = yaw * pitch * roll * object.rotation;
+    } else {
+        let move_speed = 0.05;
+        // Stay in the ground plane whatever the camera pitch.
+        let flat_forward = Vector3::new(forward.x, 0.0, forward.z)
+            .try_normalize(f32::EPSILON)
+            .unwrap_or_default();
+        let flat_right = Vector3::new(right.x, 0.0, right.z)
+            .try_normalize(f32::EPSILON)
+            .unwrap_or_default();
+        let delta =
+            (flat_right * sideways + flat_forward * ahead + Vector3::y() * vertical) * move_speed;
+        object.offset_x += delta.x;
+        object.offset_y += delta.y;
+        object.offset_z += delta.z;
+    }
+    object.scale = (object.scale * 1.02f32.powf(grow)).max(0.01);
+
+    let (roll, pitch, yaw) = object.rotation.euler_angles();
+    info!(
+        "object {index}: offset ({:.3}, {:.3}, {:.3}) rotation ({roll:.3}, {pitch:.3}, {yaw:.3}) scale {:.3}",
+        object.offset_x, object.offset_y, object.offset_z, object.scale
+    );
 }
 
-pub struct Light {
-    pub position: Point3<f32>,
-    pub target: Point3<f32>,
-    pub intensity: f32,
-    pub ambient: f32,
+/// Step the stereo eye separation and convergence distance while stereo
+/// is on, logging the new values.
+fn edit_stereo(input: &WinitInputHelper, world: &mut World, keys: &InputConfig) {
+    let Some(stereo) = &mut world.stereo else {
+        return;
+    };
+    let pressed = |action: Action| input.key_pressed(keys.key_code(action)) as i32 as f32;
+    let wider = pressed(Action::WiderEyes) - pressed(Action::NarrowerEyes);
+    let farther = pressed(Action::FocusFarther) - pressed(Action::FocusNearer);
+    if wider == 0.0 && farther == 0.0 {
+        return;
+    }
+    stereo.eye_separation *= 1.25f32.powf(wider);
+    stereo.convergence = (stereo.convergence + 0.5 * farther).max(0.0);
+    info!(
+        "eye separation {:.3}, convergence {:.1}",
+        stereo.eye_separation, stereo.convergence
+    );
 }
 
-pub struct World {
-    pub camera: Camera,
-    pub light: Light,
-    pub models: Vec<Object>,
-    pub proj_mat: Matrix4<f32>,
+/// Move the light with its held keys, relative to the camera, and step its
+/// intensity and ambient term. Logs the new values so they can be copied
+/// back into code. A light riding on the camera can't be moved.
+fn edit_light(input: &WinitInputHelper, world: &mut World, keys: &InputConfig, headlamp: bool) {
+    let held = |action: Action| input.key_held(keys.key_code(action)) as i32 as f32;
+    let pressed = |action: Action| input.key_pressed(keys.key_code(action)) as i32 as f32;
+    let sideways = held(Action::LightRight) - held(Action::LightLeft);
+    let ahead = held(Action::LightForward) - held(Action::LightBack);
+    let vertical = held(Action::LightUp) - held(Action::LightDown);
+    let brighter = pressed(Action::BrightenLight) - pressed(Action::DimLight);
+    let ambient = pressed(Action::MoreAmbient) - pressed(Action::LessAmbient);
+    let moving = !headlamp && (sideways != 0.0 || ahead != 0.0 || vertical != 0.0);
+    if !moving && brighter == 0.0 && ambient == 0.0 {
+        return;
+    }
+
+    if moving {
+        let move_speed = 0.05;
+        let forward = world.camera.forward();
+        let right = world.camera.right();
+        let flat_forward = Vector3::new(forward.x, 0.0, forward.z)
+            .try_normalize(f32::EPSILON)
+            .unwrap_or_default();
+        let flat_right = Vector3::new(right.x, 0.0, right.z)
+            .try_normalize(f32::EPSILON)
+            .unwrap_or_default();
+        let delta =
+            (flat_right * sideways + flat_forward * ahead + Vector3::y() * vertical) * move_speed;
+        world.light.position += delta;
+    }
+    let light = &mut world.light;
+    light.intensity = (light.intensity + brighter * 0.05).clamp(0.0, 4.0);
+    let strength = world.ambient.strength_mut();
+    *strength = (*strength + ambient * 0.05).clamp(0.0, 1.0);
+    info!(
+        "light: position ({:.3}, {:.3}, {:.3}) intensity {:.2} ambient {:.2}",
+        light.position.x, light.position.y, light.position.z, light.intensity, strength
+    );
 }
 
-impl Camera {
-    pub fn generate_view_mat(&self) -> Matrix4<f32> {
-        Matrix4::look_at_rh(&self.position, &self.target, &self.up)
+/// Capture the mouse for mouse look, or release it. Locking the cursor in
+/// place isn't supported everywhere, so fall back to confining it to the
+/// window. Returns whether the cursor ended up captured.
+fn grab_cursor(window: &Window, grab: bool) -> bool {
+    let captured = grab
+        && [CursorGrabMode::Locked, CursorGrabMode::Confined]
+            .into_iter()
+            .any(|mode| match window.set_cursor_grab(mode) {
+                Ok(()) => true,
+                Err(err) => {
+                    warn!("could not grab the cursor with {mode:?}: {err}");
+                    false
+                }
+            });
+    if !captured && let Err(err) = window.set_cursor_grab(CursorGrabMode::None) {
+        warn!("could not release the cursor: {err}");
     }
+    window.set_cursor_visible(!captured);
+    captured
 }
 
-impl World {
-    pub fn new(camera: Camera, light: Light, proj_mat: Matrix4<f32>, models: Vec<Object>) -> Self {
-        World {
-            camera,
-            light,
-            models,
-            proj_mat,
+/// Stop capturing, if a capture is running, and write its frames out on a
+/// background thread.
+fn save_capture(
+    capture: &mut Option<Capture>,
+    dir: &Path,
+    skip: usize,
+    saving: &mut Vec<JoinHandle<io::Result<usize>>>,
+) {
+    let Some(frames) = capture.take() else {
+        return;
+    };
+    if frames.dropped() > 0 {
+        warn!("capture dropped its {} oldest frames", frames.dropped());
+    }
+    info!("saving {} frames to {}", frames.len(), dir.display());
+    saving.push(frames.save_in_background(dir.into(), skip, ImageFormat::default()));
+}
+
+/// The demo's perspective for a `width` by `height` window, clipped at
+/// `near` and `far`.
+fn projection(width: u32, height: u32, (near, far): (f32, f32)) -> Matrix4<f32> {
+    Perspective3::new(width as f32 / height.max(1) as f32, 1.0, near, far).to_homogeneous()
+}
+
+/// Draw the frame rate, render counts and camera heading in the top left
+/// corner of a `width` by `height` frame, along with the `render_size`
+/// the scene was drawn at for it and that size's `render_scale`, marked
+/// when it follows the frame time budget. `render_time` is how long the
+/// last frame took to draw and present, `frame_time` how long it was
+/// from the one before it, waits for the frame rate cap included.
+fn draw_stats(
+    frame: &mut [u8],
+    (width, height): (u32, u32),
+    (render_size, render_scale): ((u32, u32), f32),
+    (fps, render_time, frame_time): (f32, Duration, Duration),
+    stages: &StageTimes,
+    world: &World,
+) {
+    let stats = world.stats();
+    let (yaw, pitch) = world.camera.yaw_pitch();
+    let backend = match world.raster_backend {
+        RasterBackend::BoundingBox => "BOX",
+        RasterBackend::Scanline => "SCANLINE",
+    };
+    let auto = if world.target_frame_time().is_some() {
+        " AUTO"
+    } else {
+        ""
+    };
+    let mut text = format!(
+        "FPS {fps:.1}  DRAW {:.1}MS\n\
+         RENDER {:.1}MS  FRAME {:.1}MS\n\
+         OBJECTS {} CULLED {} HIDDEN {}\n\
+         TRIS {}\n\
+         CLIPPED {} BACK {} OFFSCREEN {} DEGENERATE {} SMALL {}\n\
+         DRAWN {} LIT {} POINTS {}\n\
+         PIXELS {} REJECTED {}\n\
+         RASTER {backend}  MODE {}\n\
+         RES {}X{} ({render_scale:.2}{auto})\n\
+         YAW {:.0} PITCH {:.0}",
+        stats.draw_time.as_secs_f32() * 1000.0,
+        render_time.as_secs_f32() * 1000.0,
+        frame_time.as_secs_f32() * 1000.0,
+        stats.objects_drawn,
+        stats.objects_culled,
+        stats.objects_hidden,
+        stats.triangles_submitted,
+        stats.triangles_clipped,
+        stats.triangles_backface_culled,
+        stats.triangles_offscreen,
+        stats.triangles_degenerate,
+        stats.triangles_small,
+        stats.triangles_rasterized,
+        stats.triangles_lit,
+        stats.vertices_drawn,
+        stats.pixels_written,
+        stats.pixels_depth_rejected,
+        world.render_mode.name().to_uppercase(),
+        render_size.0,
+        render_size.1,
+        yaw.to_degrees(),
+        pitch.to_degrees()
+    );
+    if let Some(index) = world.selected
+        && let Some(level) = world.lod_level(index)
+    {
+        let levels = world.models[index].lods.len();
+        text.push_str(&format!("\nLOD {level}/{}", levels - 1));
+    }
+    if world.streamed_bytes() > 0 || world.chunks_pending() > 0 {
+        let approximate = if stats.approximate {
+            " APPROXIMATE"
+        } else {
+            ""
+        };
+        text.push_str(&format!(
+            "\nSTREAMED {:.1}MB{approximate}",
+            world.streamed_bytes() as f64 / (1 << 20) as f64
+        ));
+    }
+    if !stages.is_empty() {
+        for (stage, time) in stages.iter() {
+            let indent = if stage.parent().is_some() { "  " } else { "" };
+            text.push_str(&format!(
+                "\n{indent}{} {:.2}MS",
+                stage.name().to_uppercase(),
+                time.as_secs_f32() * 1000.0
+            ));
         }
     }
+    let (w, h) = text_size(&text);
+    let mut canvas = Canvas::new(frame, width, height);
+    let shade = Color {
+        r: 0,
+        g: 0,
+        b: 0,
+        a: 160,
+    };
+    canvas.fill_rect(2, 2, w + 6, h + 6, shade);
+    canvas.draw_text(5, 5, &text, Color::WHITE);
+}
 
-    pub fn draw(&mut self, view_mat: Matrix4<f32>, frame: &mut [u8]) {
-        frame.fill(255);
-        let mut sorted_models: Vec<(&Object, Matrix4<f32>)> = self
-            .models
-            .iter()
-            .map(|model| -> (&Object, Matrix4<f32>) {
-                (
-                    model,
-                    Matrix4::new(
-                        1.0,
-                        0.0,
-                        0.0,
-                        model.offset_x,
-                        0.0,
-                        1.0,
-                        0.0,
-                        model.offset_y,
-                        0.0,
-                        0.0,
-                        1.0,
-                        model.offset_z,
-                        0.0,
-                        0.0,
-                        0.0,
-                        1.0,
-                    ),
-                )
-            })
-            .collect();
+/// Radius on screen of `brush` over the screen point `(x, y)` of `texture`
+/// on the selected object, from how far the texture coordinates move over
+/// one pixel there. `None` when the point isn't on it.
+fn brush_cursor(
+    world: &World,
+    texture: TextureHandle,
+    brush: &Brush,
+    (x, y): (f32, f32),
+    (width, height): (u32, u32),
+) -> Option<f32> {
+    let uv_at = |x, y| {
+        let hit = world.raycast(&world.screen_ray(x, y, width, height), false)?;
+        if world.selected != Some(hit.object) || world.object_texture(hit.object) != Some(texture) {
+            return None;
+        }
+        world.hit_uv(&hit)
+    };
+    let (here, right) = (uv_at(x, y)?, uv_at(x + 1.0, y)?);
+    let size = world.materials.texture(texture)?;
+    let texels = Vector2::new(
+        (right.x - here.x) * size.width() as f32,
+        (right.y - here.y) * size.height() as f32,
+    )
+    .norm();
+    (texels > 0.0).then(|| brush.radius / texels)
+}
 
-        sorted_models.sort_by_key(|(_, model_mat)| -> OrderedFloat<f32> {
-            object_depth(&self.camera, model_mat)
-        });
+/// How far above the player the minimap looks down from, and how many
+/// units of ground it spans from top to bottom.
+const MINIMAP_HEIGHT: f32 = 30.0;
+const MINIMAP_SPAN: f32 = 16.0;
+/// Texels to a side of the `--monitor` screen's picture.
+const MONITOR_SIZE: (u32, u32) = (160, 120);
 
-        // Iterate over meshes in sorted zbuffer order
-        for (mesh, model_mat) in &sorted_models {
-            let model = &mesh.mesh;
-            let mut screen_verts: Vec<Point2<f32>> = Vec::new();
-            let mut zbuffer: Vec<Vector4<f32>> = Vec::new();
-            let mut transformed_verts: Vec<Vector4<f32>> = Vec::new();
+/// Add a top-down minimap in the top right corner through a camera of its
+/// own, with a marker for the player as the first billboard and the main
+/// camera's view outlined. Both are moved along with the player by
+/// `follow_player`.
+fn add_minimap(world: &mut World) {
+    world.cameras.push(Camera::looking_at(
+        Point3::new(0.0, MINIMAP_HEIGHT, 0.0),
+        Point3::origin(),
+    ));
+    world.viewports.push(Viewport {
+        rect: (0.72, 0.03, 0.25, 0.25),
+        camera_index: world.cameras.len(),
+        projection: Projection::Orthographic {
+            height: MINIMAP_SPAN,
+            near: 1.0,
+            far: 2.0 * MINIMAP_HEIGHT,
+        },
+    });
+    world.billboards.insert(
+        0,
+        Billboard::new(world.camera.position, 0.8, Color::rgb(230, 40, 40)),
+    );
+    world.show_frustum = Some(0);
+    follow_player(world);
+}
 
-            let proj = self.proj_mat * view_mat * model_mat;
+/// Centre the minimap on the main camera and put the marker where it is.
+/// The marker sits right on the eye, so the main view never shows it.
+fn follow_player(world: &mut World) {
+    let player = world.camera.position;
+    world.cameras[0] = Camera::looking_at(player + Vector3::y() * MINIMAP_HEIGHT, player);
+    world.billboards[0].position = player;
+}
 
-            for vertex in model.verts().iter().copied() {
-                let persproj = proj * Point4::new(vertex.x, vertex.y, vertex.z, 1.0);
-                let ndc_x = persproj.x / persproj.w;
-                let ndc_y = persproj.y / persproj.w;
-                let ndc_z = persproj.z / persproj.w;
+/// A screen off to the side of the scene showing it live from above
+/// and behind, through a camera of its own.
+fn add_monitor(world: &mut World) {
+    world.cameras.push(Camera::looking_at(
+        Point3::new(4.0, 4.0, 8.0),
+        Point3::origin(),
+    ));
+    let target = world.add_render_target(
+        world.cameras.len(),
+        Projection::Perspective {
+            fovy: 0.9,
+            near: 0.1,
+            far: 50.0,
+        },
+        MONITOR_SIZE.0,
+        MONITOR_SIZE.1,
+    );
+    let screen = world.materials.insert(
+        "monitor",
+        Material {
+            unlit: true,
+            render_target: Some(target),
+            ..Material::new(Color::WHITE)
+        },
+    );
+    world
+        .add_object(
+            Object::new(QuadMesh::new(2.4, 1.8, Color::WHITE), -3.5, 1.0, 2.0)
+                .with_name("monitor")
+                .with_material(screen),
+        )
+        .expect("quad mesh is valid and the name unused");
+}
 
-                if !(0.0..=1.0).contains(&ndc_z) {
-                    screen_verts.push(Point2::new(f32::NAN, f32::NAN));
-                } else {
-                    let screen_x = (ndc_x + 1.0) * 0.5 * WIDTH as f32;
-                    let screen_y = (1.0 - ndc_y) * 0.5 * HEIGHT as f32;
-                    screen_verts.push(Point2::new(screen_x, screen_y));
-                }
-                zbuffer.push(view_mat * model_mat * Vector4::from(vertex));
-                transformed_verts.push(model_mat * Vector4::from(vertex));
-            }
+/// Scatter `n` small billboards in a spiral over the floor, shading from
+/// red through the rainbow, for stress testing.
+fn spawn_billboards(world: &mut World, n: usize) {
+    let golden_angle = std::f32::consts::PI * (3.0 - 5.0f32.sqrt());
+    for i in 0..n {
+        let t = (i as f32 + 0.5) / n as f32;
+        let angle = i as f32 * golden_angle;
+        let radius = 5.5 * t.sqrt();
+        world.billboards.push(Billboard::new(
+            Point3::new(
+                radius * angle.cos(),
+                -1.5 + (i % 7) as f32 * 0.4,
+                radius * angle.sin(),
+            ),
+            0.15,
+            Color::from_hsv(t * 300.0, 0.8, 1.0),
+        ));
+    }
+}
+
+/// A fountain of droplets rising from the floor in front of the camera,
+/// paling and fading as they fall back.
+fn add_fountain(world: &mut World) {
+    fn spray(n: u32) -> Vector3<f32> {
+        let angle = scatter(n, 0) * std::f32::consts::TAU;
+        let spread = 0.6 * scatter(n, 1);
+        Vector3::new(
+            spread * angle.cos(),
+            4.0 + scatter(n, 2),
+            spread * angle.sin(),
+        )
+    }
+    world.emitters.push(ParticleEmitter::new(
+        Point3::new(2.5, -2.0, 1.0),
+        120.0,
+        spray,
+        1.0,
+        Color::rgb(90, 160, 255),
+        Color {
+            a: 160,
+            ..Color::rgb(230, 240, 255)
+        },
+        0.12,
+    ));
+}
 
-            //Z order each triangle in each mesh
-            let mut z_ordered_tris: Vec<(&Triangle, f32)> = model
-                .tris()
-                .iter()
-                .map(|tri| -> (&Triangle, f32) {
-                    let z = (zbuffer[tri.v1].z + zbuffer[tri.v2].z + zbuffer[tri.v3].z) / 3.0;
-                    (tri, z)
+/// Line up `n` spheres with levels of detail going away from the camera,
+/// to watch them coarsen with distance.
+fn spawn_lod_spheres(world: &mut World, n: usize) {
+    let lods = sphere_lods(0.4, 16, &[6.0, 12.0, 24.0, f32::INFINITY], Color::WHITE)
+        .expect("sphere levels are valid");
+    for i in 0..n {
+        let sphere = Rc::clone(&lods[0].1);
+        world
+            .add_object(
+                Object::instance(sphere, 2.0, -1.5, 4.0 - 3.0 * i as f32)
+                    .with_lods(lods.clone())
+                    .with_layer(LOD_LAYER),
+            )
+            .expect("sphere levels are valid");
+    }
+    info!("spawned {n} spheres with {} levels of detail", lods.len());
+}
+
+/// Cover the floor with an `n` by `n` grid of small cubes that all share one
+/// mesh, for stress testing.
+fn spawn_instances(world: &mut World, n: usize) {
+    let cube: Rc<dyn Mesh> = Rc::new(CubeMesh::new(Color {
+        r: 90,
+        g: 150,
+        b: 80,
+        a: 255,
+    }));
+    let spacing = 12.0 / n.max(1) as f32;
+    for row in 0..n {
+        for col in 0..n {
+            let x = (col as f32 + 0.5) * spacing - 6.0;
+            let z = (row as f32 + 0.5) * spacing - 6.0;
+            world
+                .add_object(Object {
+                    scale: spacing * 0.3,
+                    layer: INSTANCE_LAYER,
+                    ..Object::instance(Rc::clone(&cube), x, -2.0 + spacing * 0.3, z)
                 })
-                .collect();
-            z_ordered_tris.sort_by_key(|tri| -> OrderedFloat<f32> { OrderedFloat(tri.1) });
-
-            // Draw the triangles
-            for (tri, _) in z_ordered_tris {
-                let s1 = screen_verts[tri.v1];
-                let s2 = screen_verts[tri.v2];
-                let s3 = screen_verts[tri.v3];
-                if !s1.x.is_finite() || !s2.x.is_finite() || !s3.x.is_finite() {
-                    continue;
-                }
+                .expect("cube mesh is valid");
+        }
+    }
+    info!("spawned {} instances", n * n);
+}
+
+/// The demo scene for a `width` by `height` frame, set up as `scene` says.
+fn demo_world(width: u32, height: u32, scene: &SceneArgs) -> World {
+    let mut world = World::new(
+        Camera::looking_at(Point3::new(0.0, 0.0, -5.0), Point3::new(0.0, 0.0, 0.0)),
+        Light {
+            position: Point3::new(-1.0, 1.0, -1.0),
+            target: Point3::new(0.0, 0.0, 0.0),
+            kind: LightKind::Directional,
+            intensity: 1.0,
+            shadow_resolution: 1024,
+            shadow_bias: 0.05,
+        },
+        projection(width, height, (0.1, 200.0)),
+        vec![
+            Object::new(PHackMesh::new(), 0.0, 0.0, 0.0)
+                .with_name("spinner")
+                .with_animation(Animation::Spin {
+                    axis: Vector3::y_axis(),
+                    speed: 0.8,
+                }),
+            Object::new(
+                PHackMesh::new_with(
+                    0.75,
+                    Color {
+                        r: 40,
+                        g: 170,
+                        b: 160,
+                        a: 255,
+                    },
+                ),
+                3.0,
+                0.0,
+                3.0,
+            )
+            .with_name("bobber")
+            .with_animation(Animation::Bob {
+                amplitude: 0.5,
+                speed: 2.0,
+            }),
+            Object::new(
+                QuadMesh::new(
+                    3.0,
+                    3.0,
+                    Color {
+                        r: 64,
+                        g: 160,
+                        b: 255,
+                        a: 128,
+                    },
+                ),
+                0.0,
+                0.0,
+                -1.5,
+            ),
+            // A floor to catch the shadows.
+            Object {
+                name: Some("floor".to_string()),
+                rotation: UnitQuaternion::from_axis_angle(&Vector3::x_axis(), FRAC_PI_2),
+                layer: FLOOR_LAYER,
+                ..Object::new(
+                    QuadMesh::grid(
+                        12.0,
+                        12.0,
+                        48,
+                        Color {
+                            r: 200,
+                            g: 200,
+                            b: 190,
+                            a: 255,
+                        },
+                    ),
+                    0.0,
+                    -2.0,
+                    0.0,
+                )
+            },
+            // A path lying right on the floor, kept on top of it by its
+            // depth bias.
+            Object {
+                rotation: UnitQuaternion::from_axis_angle(&Vector3::x_axis(), FRAC_PI_2),
+                layer: FLOOR_LAYER,
+                depth_bias: -1.0,
+                ..Object::new(
+                    QuadMesh::new(1.5, 12.0, Color::rgb(150, 120, 90)),
+                    0.0,
+                    -2.0,
+                    0.0,
+                )
+            },
+        ],
+    );
 
-                let v1 = transformed_verts[tri.v1];
-                let v2 = transformed_verts[tri.v2];
-                let v3 = transformed_verts[tri.v3];
+    // A moon attached to the spinning mesh, carried around as it turns.
+    world
+        .add_object(Object {
+            name: Some("moon".to_string()),
+            scale: 0.3,
+            ..Object::new(
+                CubeMesh::new(Color {
+                    r: 230,
+                    g: 230,
+                    b: 210,
+                    a: 255,
+                }),
+                2.5,
+                0.0,
+                0.0,
+            )
+        })
+        .expect("cube mesh is valid and the name unused");
+    world
+        .attach("moon", Some("spinner".into()))
+        .expect("both are in the scene");
 
-                let norm = (v2.xyz() - v1.xyz())
-                    .normalize()
-                    .cross(&(v3.xyz() - v1.xyz()).normalize());
+    add_minimap(&mut world);
+    if let Some(n) = scene.instances {
+        spawn_instances(&mut world, n);
+    }
+    if let Some(n) = scene.lod_spheres {
+        spawn_lod_spheres(&mut world, n);
+    }
+    if let Some(n) = scene.billboards {
+        spawn_billboards(&mut world, n);
+    }
+    if scene.particles {
+        add_fountain(&mut world);
+    }
+    if scene.monitor {
+        add_monitor(&mut world);
+    }
+    if let Some(target) = scene.simplify {
+        world.import_options = world.import_options.simplified(target);
+    }
+    if scene.auto_lod {
+        world.lod_distances = vec![8.0, 16.0, 32.0, f32::INFINITY];
+    }
+    if scene.sky {
+        world.clear_mode = ClearMode::Sky(Sky::default());
+    }
+    if scene.colorblind_ids {
+        world.id_palette = IdPalette::ColorBlind;
+    }
+    match scene.clip {
+        Some(ClipPlanes::Auto) => world.auto_clip_planes = true,
+        Some(ClipPlanes::Fixed(near, far)) => {
+            if let Err(err) = world.set_clip_planes(near, far) {
+                error!("--clip {near},{far}: {err}");
+                std::process::exit(1);
+            }
+        }
+        None => {}
+    }
+    if let Some(convention) = scene.depth_buffer {
+        world.depth_buffer = true;
+        world.set_depth_convention(convention);
+    }
+    world.front_to_back = !scene.back_to_front;
+    world.splitting_sort = scene.splitting_sort;
+    // Flat shadows on the floor instead of the light's shadow map.
+    if scene.planar_shadows {
+        world.light.shadow_resolution = 0;
+        world.planar_shadows = Some(PlanarShadowConfig {
+            plane_y: -2.0,
+            ..PlanarShadowConfig::default()
+        });
+    }
+    if let Some(filter) = scene.checker_floor {
+        let checker = Texture::checker(12, Color::rgb(255, 255, 255), Color::rgb(150, 150, 150));
+        let texture = world.materials.add_texture(checker.with_filter(filter));
+        let material = world.materials.insert(
+            "checker floor",
+            Material {
+                texture: Some(texture),
+                ..Material::new(Color::rgb(200, 200, 190))
+            },
+        );
+        if let Some(floor) = world.object_mut("floor") {
+            floor.material = Some(material);
+        }
+    }
+    match scene.small_triangles {
+        Some(SmallTriangles::Points) => {
+            world.small_triangle_cull = Some(0.5);
+            world.small_triangle_points = true;
+        }
+        Some(SmallTriangles::Cull(size)) => world.small_triangle_cull = Some(size),
+        None => {}
+    }
+    world.threads = std::thread::available_parallelism().map_or(1, |n| n.get());
+    world.show_light = true;
+    // Pale sky above, brown earth below, so sides facing away from the
+    // light still read as up or down.
+    world.ambient = Ambient::Hemisphere {
+        sky_color: Color::from_hex(0xC8DCFF),
+        ground_color: Color::from_hex(0x6E5A46),
+        strength: 0.35,
+    };
+    world
+}
 
-                if is_front_facing(s1, s2, s3) {
-                    self.draw_triangle(s1, s2, s3, &tri.color, frame, &norm);
+fn main() -> Result<(), Error> {
+    env_logger::init();
+    let command = cli::parse(std::env::args().skip(1)).unwrap_or_else(|err| {
+        eprintln!("{err}\n\n{USAGE}");
+        std::process::exit(2);
+    });
+    match command {
+        Command::View(args) => view(args),
+        Command::Render(args) => {
+            if let Err(err) = render(&args) {
+                error!("{err}");
+                std::process::exit(1);
+            }
+            Ok(())
+        }
+        Command::Info { model } => {
+            match ModelInfo::load(&model) {
+                Ok(info) => println!("{}\n{info}", model.display()),
+                Err(err) => {
+                    error!("{}: {err}", model.display());
+                    std::process::exit(1);
                 }
             }
+            Ok(())
+        }
+        Command::Simplify(args) => {
+            if let Err(err) = simplify_model(&args) {
+                error!("{err}");
+                std::process::exit(1);
+            }
+            Ok(())
+        }
+        Command::Reduce(args) => {
+            if let Err(err) = reduce_scene(&args) {
+                error!("{err}");
+                std::process::exit(1);
+            }
+            Ok(())
+        }
+        Command::Help => {
+            println!("{USAGE}");
+            Ok(())
         }
     }
+}
 
-    fn draw_triangle(
-        &self,
-        t1: Point2<f32>,
-        t2: Point2<f32>,
-        t3: Point2<f32>,
-        color: &Color,
-        frame: &mut [u8],
-        norm: &Vector3<f32>,
-    ) {
-        let light_dir = (self.light.target - self.light.position).normalize();
-        let ambient = self.light.ambient;
-        let diffuse = (light_dir.dot(norm) * self.light.intensity).clamp(0.0, 1.0);
-        let specular = 0.0; //no fancy lighting for now its too laggy
-        let coloring = ambient + diffuse + specular;
-        let colormap = |comp: u8, coloring: f32| -> u8 { ((comp as f32) * coloring) as u8 };
-        let p_color = Color {
-            r: colormap(color.r, coloring),
-            g: colormap(color.g, coloring),
-            b: colormap(color.b, coloring),
-            a: color.a,
-        };
+/// Load the model `args` names, simplify it and write it out.
+fn simplify_model(args: &SimplifyArgs) -> Result<(), String> {
+    let mesh = ObjMesh::from_path(&args.model, ImportOptions::default())
+        .map_err(|err| format!("{}: {err}", args.model.display()))?;
+    let simplified = simplify(&mesh, args.target);
+    save_obj(&simplified, &args.output)
+        .map_err(|err| format!("{}: {err}", args.output.display()))?;
+    println!(
+        "{} triangles in {}, {} in {}",
+        mesh.primitives().triangles().count(),
+        args.model.display(),
+        simplified.tris().len(),
+        args.output.display()
+    );
+    Ok(())
+}
 
-        let (x1, y1) = (t1.x, t1.y);
-        let (x2, y2) = (t2.x, t2.y);
-        let (x3, y3) = (t3.x, t3.y);
-        let min_x = (x1.min(x2).min(x3).max(0.0)) as i32;
-        let max_x = (x1.max(x2).max(x3).min(WIDTH as f32 - 1.0) + 1.0) as i32;
-        let min_y = (y1.min(y2).min(y3).max(0.0)) as i32;
-        let max_y = (y1.max(y2).max(y3).min(HEIGHT as f32 - 1.0) + 1.0) as i32;
+/// Cut the scene file `args` names down to what drawing still panics
+/// with, and write it out.
+fn reduce_scene(args: &ReduceArgs) -> Result<(), String> {
+    let scene = SceneFile::from_path(&args.scene_file)
+        .map_err(|err| format!("{}: {err}", args.scene_file.display()))?;
+    // The first panic is shown; the many while cutting are not.
+    if !draw_panics(&scene, args) {
+        return Err(format!(
+            "drawing {} does not panic, so there is nothing to reduce",
+            args.scene_file.display()
+        ));
+    }
+    let hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(|_| {}));
+    let mut tries = 0;
+    let reduced = reduce(&scene, |cut| {
+        tries += 1;
+        draw_panics(cut, args)
+    });
+    std::panic::set_hook(hook);
+    reduced
+        .save(&args.output)
+        .map_err(|err| format!("{}: {err}", args.output.display()))?;
+    println!(
+        "{} models and {} boxes down to {} and {} in {tries} tries, written to {}",
+        scene.models.len(),
+        scene.boxes.len(),
+        reduced.models.len(),
+        reduced.boxes.len(),
+        args.output.display()
+    );
+    Ok(())
+}
 
-        let edge = |(ax, ay): (f32, f32), (bx, by): (f32, f32), (px, py): (f32, f32)| -> f32 {
-            (py - ay) * (bx - ax) - (px - ax) * (by - ay)
-        };
-        for y in min_y..=max_y {
-            for x in min_x..=max_x {
-                let p = (x as f32 , y as f32);
-                let w0 = edge((x2, y2), (x3, y3), p);
-                let w1 = edge((x3, y3), (x1, y1), p);
-                let w2 = edge((x1, y1), (x2, y2), p);
-
-                if w0 >= 0.0 && w1 >= 0.0 && w2 >= 0.0 {
-                    let index = (y as u32 * WIDTH + x as u32) * 4;
-                    if index as usize + 4 <= frame.len() {
-                        frame[index as usize..index as usize + 4]
-                            .copy_from_slice(&[p_color.r, p_color.g, p_color.b, p_color.a]);
-                    }
-                }
-            }
+/// Whether drawing `scene` once, in the demo scene set up as `args` says,
+/// panics.
+fn draw_panics(scene: &SceneFile, args: &ReduceArgs) -> bool {
+    std::panic::catch_unwind(AssertUnwindSafe(|| {
+        let mut world = demo_world(args.width, args.height, &args.scene);
+        load_scene_file(&mut world, scene, &[], true);
+        while world.loads_pending() > 0 {
+            world.poll_loads();
+            std::thread::sleep(Duration::from_millis(1));
         }
+        follow_player(&mut world);
+        let mut frame = vec![0; (args.width * args.height * 4) as usize];
+        let view_mat = world.camera.generate_view_mat();
+        world.draw(view_mat, &mut frame, args.width, args.height);
+    }))
+    .is_err()
+}
+
+/// Line `models` up behind the demo scene, loading in the background.
+fn load_models(world: &mut World, models: &[PathBuf]) {
+    for (i, path) in models.iter().enumerate() {
+        let offset = Vector3::new(3.0 * i as f32 - 3.0, 1.5, 6.0);
+        let index = world.load_object_async(path, Matrix4::new_translation(&offset));
+        world.models[index].layer = MODEL_LAYER;
     }
 }
 
-/// True if the triangle faces the cam. False, we dont need to draw it.
-fn is_front_facing(p1: Point2<f32>, p2: Point2<f32>, p3: Point2<f32>) -> bool {
-    let cross = (p2.x - p1.x) * (p3.y - p1.y) - (p2.y - p1.y) * (p3.x - p1.x);
-    cross > 0.0
+/// The chunk cache for the model at `path`, kept beside it with the
+/// extension `chunks`. It is built again when it is missing, older than
+/// the model or unreadable.
+fn chunk_cache(path: &Path, options: ImportOptions) -> Result<ChunkCache, String> {
+    let cache_path = path.with_extension("chunks");
+    let modified = |path: &Path| {
+        std::fs::metadata(path)
+            .and_then(|meta| meta.modified())
+            .ok()
+    };
+    if let (Some(cached), Some(model)) = (modified(&cache_path), modified(path))
+        && cached >= model
+        && let Ok(cache) = ChunkCache::open(&cache_path)
+    {
+        return Ok(cache);
+    }
+    let mesh =
+        ObjMesh::from_path(path, options).map_err(|err| format!("{}: {err}", path.display()))?;
+    ChunkCache::build(&mesh, TRIANGLES_PER_CHUNK, &cache_path)
+        .map_err(|err| format!("{}: {err}", cache_path.display()))
 }
 
-/// Handle key press turning and etc... TODO add mouse movement
-fn handle_keys(input: &WinitInputHelper, camera: &mut Camera, move_speed: f32) -> Matrix4<f32> {
-    if input.key_held(KeyCode::KeyA) {
-        let delta: Vector3<f32> = (camera.position - camera.target)
-            .normalize()
-            .cross(&camera.up)
-            * move_speed;
-        camera.position.x += delta.x;
-        camera.position.z += delta.z;
-        camera.target.x += delta.x;
-        camera.target.z += delta.z;
-    } else if input.key_held(KeyCode::KeyD) {
-        let delta: Vector3<f32> = (camera.position - camera.target)
-            .normalize()
-            .cross(&camera.up)
-            * move_speed;
-        camera.position.x -= delta.x;
-        camera.position.z -= delta.z;
-        camera.target.x -= delta.x;
-        camera.target.z -= delta.z;
-    } else if input.key_held(KeyCode::KeyW) {
-        let delta: Vector3<f32> = (camera.position - camera.target).normalize() * move_speed;
-        camera.position.x -= delta.x;
-        camera.position.z -= delta.z;
-        camera.target.x -= delta.x;
-        camera.target.z -= delta.z;
-    } else if input.key_held(KeyCode::KeyS) {
-        let delta: Vector3<f32> = (camera.position - camera.target).normalize() * move_speed;
-        camera.position.x += delta.x;
-        camera.position.z += delta.z;
-        camera.target.x += delta.x;
-        camera.target.z += delta.z;
+/// Add the models at `models` on the model layer, lined up in front of
+/// the ones [`load_models`] places, streamed from their chunk caches
+/// within `budget_mb` megabytes each.
+fn stream_models(world: &mut World, models: &[PathBuf], budget_mb: usize) {
+    for (i, path) in models.iter().enumerate() {
+        let cache = match chunk_cache(path, world.import_options) {
+            Ok(cache) => cache,
+            Err(err) => {
+                error!("{err}");
+                continue;
+            }
+        };
+        info!(
+            "{}: {} triangles in {} chunks",
+            path.display(),
+            cache.triangles(),
+            cache.chunks().len()
+        );
+        let offset = Vector3::new(3.0 * i as f32 - 3.0, 1.5, 2.0);
+        let budget = budget_mb.saturating_mul(1 << 20);
+        for index in world.load_chunked(cache, Matrix4::new_translation(&offset), budget) {
+            world.models[index].layer = MODEL_LAYER;
+        }
     }
-    camera.generate_view_mat()
 }
 
-fn object_depth(camera: &Camera, model_mat: &Matrix4<f32>) -> OrderedFloat<f32> {
-    let view_mat = camera.generate_view_mat();
-    let view_model = view_mat * model_mat;
-    let object_pos = view_model.transform_point(&Point3::origin());
-    OrderedFloat(object_pos.z)
+/// Set the demo scene up as `scene` says, with its models on the model
+/// layer in place of the objects at `previous` that an earlier version of
+/// the file added. The camera moves only with `move_camera`. Returns the
+/// indices of the objects added for the models.
+fn load_scene_file(
+    world: &mut World,
+    scene: &SceneFile,
+    previous: &[usize],
+    move_camera: bool,
+) -> Vec<usize> {
+    if move_camera {
+        scene.place_camera(&mut world.camera);
+    }
+    let indices = scene.replace(world, previous);
+    for &index in &indices {
+        world.models[index].layer = MODEL_LAYER;
+    }
+    indices
 }
 
-fn _reflected_ray(incident: Vector3<f32>, normal: &Vector3<f32>) -> Vector3<f32> {
-    incident - (normal * (incident.dot(normal))).scale(2.0)
+/// Draw the demo scene without a window into the image files `args` asks
+/// for, playing its camera track over the frames if it has one and
+/// otherwise stepping the animations at 30 frames a second.
+fn render(args: &RenderArgs) -> Result<(), String> {
+    output::format_for(&args.output).map_err(|err| format!("{}: {err}", args.output.display()))?;
+    let track = args
+        .camera_track
+        .as_ref()
+        .map(|path| {
+            CameraTrack::from_path(path).map_err(|err| format!("{}: {err}", path.display()))
+        })
+        .transpose()?;
+    let scene_file = args
+        .scene_file
+        .as_ref()
+        .map(|path| SceneFile::from_path(path).map_err(|err| format!("{}: {err}", path.display())))
+        .transpose()?;
+    let mut world = demo_world(args.width, args.height, &args.scene);
+    if let Some(scene_file) = &scene_file {
+        load_scene_file(&mut world, scene_file, &[], true);
+    }
+    load_models(&mut world, &args.models);
+    while world.loads_pending() > 0 {
+        world.poll_loads();
+        std::thread::sleep(Duration::from_millis(1));
+    }
+    let mut frame = vec![0; (args.width * args.height * 4) as usize];
+    for index in 0..args.frames {
+        if let Some(track) = &track {
+            let t = track.duration() * index as f32 / (args.frames - 1).max(1) as f32;
+            track.apply(t, &mut world.camera);
+        } else if index > 0 {
+            world.update(1.0 / 30.0);
+        }
+        follow_player(&mut world);
+        let view_mat = world.camera.generate_view_mat();
+        world.draw(view_mat, &mut frame, args.width, args.height);
+        let path = args.frame_path(index);
+        output::write(&path, &frame, args.width, args.height)
+            .map_err(|err| format!("{}: {err}", path.display()))?;
+    }
+    info!("wrote {} frames to {}", args.frames, args.output.display());
+    Ok(())
 }
 
-fn main() -> Result<(), Error> {
-    env_logger::init();
+/// The interactive window.
+fn view(args: ViewArgs) -> Result<(), Error> {
+    let keys = match &args.input {
+        Some(path) => InputConfig::from_path(path).unwrap_or_else(|err| {
+            error!("{}: {err}", path.display());
+            std::process::exit(1);
+        }),
+        None => InputConfig::default(),
+    };
     let mut input = WinitInputHelper::new();
     let event_loop = EventLoop::new().unwrap();
     let window = {
-        let size = LogicalSize::new(WIDTH as f64, HEIGHT as f64);
+        let size = LogicalSize::new(args.width as f64, args.height as f64);
         WindowBuilder::new()
             .with_title("Renderer")
             .with_inner_size(size)
@@ -278,47 +1037,131 @@ fn main() -> Result<(), Error> {
             .unwrap()
     };
 
-    window
-        .set_cursor_grab(winit::window::CursorGrabMode::Locked)
-        .unwrap();
-    window.set_cursor_visible(false);
+    let mut grabbed = grab_cursor(&window, true);
+    let mut focused = true;
 
+    // The pixels buffer always matches the window; the scene is drawn at
+    // `render_scale` times that and stretched over it when they differ.
+    let window_size = window.inner_size();
+    let (mut width, mut height) = (window_size.width, window_size.height);
     let mut pixels = {
-        let window_size = window.inner_size();
-        let surface_texture = SurfaceTexture::new(window_size.width, window_size.height, &window);
-        Pixels::new(WIDTH, HEIGHT, surface_texture)?
+        let surface_texture = SurfaceTexture::new(width, height, &window);
+        Pixels::new(width, height, surface_texture)?
     };
-
-    let mut world = World::new(
-        Camera {
-            position: Point3::new(0.0, 0.0, -5.0),
-            target: Point3::new(0.0, 0.0, 0.0),
-            up: Vector3::new(0.0, 1.0, 0.0),
-            pitch: 0.0,
-            yaw: 0.0,
-        },
-        Light {
-            position: Point3::new(-1.0, 1.0, -1.0),
-            target: Point3::new(0.0, 0.0, 0.0),
-            intensity: 1.0,
-            ambient: 0.3,
-        },
-        Perspective3::new((WIDTH as f32) / (HEIGHT as f32), 1.0, 0.1, 200.0).to_homogeneous(),
-        vec![
-            Object {
-                mesh: Box::new(PHackMesh::new()),
-                offset_x: 0.0,
-                offset_y: 0.0,
-                offset_z: 0.0,
-            },
-            Object {
-                mesh: Box::new(PHackMesh::new()),
-                offset_x: 3.0,
-                offset_y: 0.0,
-                offset_z: 3.0,
-            },
-        ],
+    // With vsync, presenting a frame already waits for the display, so
+    // --max-fps only has frames wait when it is below the refresh rate.
+    let mut pacer = FramePacer::new(args.max_fps);
+    let vsync = matches!(
+        pixels.present_mode(),
+        PresentMode::AutoVsync | PresentMode::Fifo | PresentMode::FifoRelaxed
     );
+    if vsync && pacer.budget().is_some() {
+        let refresh_hz = window
+            .current_monitor()
+            .and_then(|monitor| monitor.refresh_rate_millihertz())
+            .map(|millihertz| millihertz as f32 / 1000.0);
+        if !pacer.sync_to_display(refresh_hz) {
+            info!("frame rate left to vsync at {refresh_hz:?} Hz");
+        }
+    }
+    let mut render_scale = args
+        .scale
+        .map_or(1.0, |scale| scale.clamp(MIN_RENDER_SCALE, MAX_RENDER_SCALE));
+    let mut scene = Vec::new();
+    // Frame time the adaptive render scale aims for, in milliseconds.
+    // Setting it starts the demo with the scale adapting.
+    let target_ms = args.target_ms;
+    let target_frame_time = Duration::from_secs_f32(target_ms.unwrap_or(16.6) / 1000.0);
+
+    let mut world = demo_world(width, height, &args.scene);
+    // Objects added for the scene file's models, swapped for new ones when
+    // --watch sees the file change.
+    let mut scene_models = Vec::new();
+    if let Some(path) = &args.scene_file {
+        match SceneFile::from_path(path) {
+            Ok(scene_file) => {
+                scene_models = load_scene_file(&mut world, &scene_file, &[], true);
+            }
+            Err(err) => {
+                error!("{}: {err}", path.display());
+                std::process::exit(1);
+            }
+        }
+    }
+    // The models on the command line, lined up behind the scene. They
+    // load on threads of their own, shown as placeholder cubes until they
+    // arrive. With --watch they are read again whenever their file changes,
+    // and the scene file too. Streamed models are cut into chunks, shown as
+    // their bounds until they are read.
+    load_models(&mut world, &args.models);
+    stream_models(&mut world, &args.streamed, args.stream_budget);
+    let mut watcher = args.watch.then(|| {
+        let mut watcher = FileWatcher::default();
+        if let Some(path) = &args.scene_file {
+            watcher.watch(path);
+        }
+        for source in world
+            .models
+            .iter()
+            .filter_map(|model| model.source.as_ref())
+        {
+            watcher.watch(source);
+        }
+        watcher
+    });
+
+    // Time each stage of every frame, for the overlay and a summary logged
+    // on exit.
+    world.profiling_enabled = args.profile;
+    let mut profile = ProfileSummary::default();
+    // The last frame's stages, overlay included.
+    let mut stages = StageTimes::default();
+    if target_ms.is_some() {
+        world.set_target_frame_time(target_frame_time);
+    }
+    let mut progressive = Accumulator::new(64);
+    let mut progressive_enabled = true;
+    let mut show_stats = false;
+    let mut fps = FpsCounter::new(Duration::from_millis(500));
+    let mut last_frame = Instant::now();
+    let (mut render_time, mut frame_time) = (Duration::ZERO, Duration::ZERO);
+    let mut last_stats_log = Instant::now();
+    // Set whenever the next frame could look different from the last one
+    // drawn; an idle window then sleeps until an event arrives. The first
+    // frame always draws.
+    let mut dirty = true;
+    let mut drawn_fingerprint = None;
+    let mut last_update = Instant::now();
+    // Seconds passed that the scene hasn't been stepped through yet.
+    let mut unsimulated = 0.0;
+    let export_path = &args.export;
+    let track_path = &args.camera_track;
+    // The track being recorded or played, with the time into it.
+    let mut recording: Option<(CameraTrack, f32)> = None;
+    let mut playing: Option<(CameraTrack, f32)> = None;
+    let mut last_track: Option<CameraTrack> = None;
+    // Camera bookmarks are read from here at the start and written back
+    // whenever one is saved.
+    let bookmark_path = &args.bookmarks;
+    match load_bookmarks(bookmark_path) {
+        Ok(bookmarks) => world.bookmarks = bookmarks,
+        Err(BookmarkError::Io(err)) if err.kind() == io::ErrorKind::NotFound => {}
+        Err(err) => error!("could not load {}: {err}", bookmark_path.display()),
+    }
+    let bookmark_seconds = args.bookmark_seconds;
+    let capture_dir = &args.capture_dir;
+    let (capture_frames, capture_skip) = (args.capture_frames, args.capture_skip);
+    let mut capture: Option<Capture> = None;
+    let mut saving = Vec::new();
+    let mut headlamp = false;
+    // The texture being painted while in paint mode, and the last one
+    // painted, which is what gets saved.
+    let mut painting: Option<TextureHandle> = None;
+    let mut painted: Option<TextureHandle> = None;
+    let mut stroke = Stroke::new();
+    let mut cursor: Option<(f32, f32)> = None;
+    // Where the brush circle goes: screen position and radius.
+    let mut brush_at: Option<(f32, f32, f32)> = None;
 
     let res = event_loop.run(|event, elwt| {
         let view_mat: Matrix4<f32> = world.camera.generate_view_mat();
@@ -327,45 +1170,688 @@ fn main() -> Result<(), Error> {
             ..
         } = event
         {
-            world.draw(view_mat, pixels.frame_mut());
+            let started = Instant::now();
+            frame_time = started - last_frame;
+            last_frame = started;
+            fps.tick(frame_time);
+            pacer.frame_started(started);
+            drawn_fingerprint = Some(world.fingerprint(&view_mat));
+            dirty = false;
+            // Hold the scale still while capturing, so every frame is the
+            // same size.
+            if capture.is_none() {
+                let adapted = world.adapt_render_scale(render_scale);
+                if adapted != render_scale {
+                    render_scale = adapted;
+                    debug!("render scale adapted to {render_scale:.2}");
+                }
+            }
+            let render_size = scaled_size(width, height, render_scale);
+            let (render_width, render_height) = render_size;
+            let direct = render_size == (width, height);
+            let target = if direct {
+                pixels.frame_mut()
+            } else {
+                scene.resize((render_width * render_height * 4) as usize, 0);
+                &mut scene[..]
+            };
+            if progressive_enabled {
+                progressive.render(&mut world, view_mat, target, render_width, render_height);
+            } else {
+                world.draw(view_mat, target, render_width, render_height);
+            }
+            // Captured before the stats so they never end up in the images.
+            if let Some(frames) = &mut capture
+                && let Err(err) = frames.push(target, render_width, render_height)
+            {
+                warn!("capture stopped: {err}");
+                save_capture(&mut capture, capture_dir, capture_skip, &mut saving);
+            }
+            if !direct {
+                resample(
+                    &scene,
+                    render_width,
+                    render_height,
+                    pixels.frame_mut(),
+                    width,
+                    height,
+                );
+            }
+            let now = Instant::now();
+            if now - last_stats_log >= Duration::from_secs(1) {
+                debug!("{:?}", world.stats());
+                last_stats_log = now;
+            }
+            if let Some((x, y, radius)) = brush_at {
+                // Past a screen's size there is nothing left to see of it.
+                let radius = radius.round().min((width + height) as f32) as u32;
+                Canvas::new(pixels.frame_mut(), width, height).draw_circle(
+                    x as i32,
+                    y as i32,
+                    radius,
+                    BRUSH_CURSOR_COLOR,
+                );
+            }
+            // Drawn last so the scene never covers it.
+            let profiler = Profiler::new(world.profiling_enabled);
+            if show_stats {
+                let _overlay = profiler.scope(Stage::Overlay);
+                draw_stats(
+                    pixels.frame_mut(),
+                    (width, height),
+                    (render_size, render_scale),
+                    (fps.fps(), render_time, frame_time),
+                    &stages,
+                    &world,
+                );
+            }
+            stages = world.stats().stages;
+            stages.add(Stage::Overlay, profiler.times().get(Stage::Overlay));
+            profile.record(&stages);
             if let Err(err) = pixels.render() {
                 error!("failed: {err}");
                 elwt.exit();
                 return;
             }
+            render_time = started.elapsed();
+            // Keep refining the still image while nothing moves.
+            if progressive_enabled && progressive.converging() {
+                dirty = true;
+            }
+        }
+        if let Event::WindowEvent {
+            event: WindowEvent::Focused(now_focused),
+            ..
+        } = event
+        {
+            focused = now_focused;
+            // Coming back to the window redraws in case the compositor
+            // dropped the old contents.
+            dirty |= now_focused;
+        }
+        if let Event::WindowEvent {
+            event: WindowEvent::Occluded(false),
+            ..
+        } = event
+        {
+            dirty = true;
         }
         if input.update(&event) {
-            if input.key_pressed(KeyCode::Escape) || input.close_requested() {
+            if input.key_pressed(keys.key_code(Action::Quit)) || input.close_requested() {
                 elwt.exit();
                 return;
             }
 
-            if let Some(size) = input.window_resized() {
-                if let Err(err) = pixels.resize_surface(size.width, size.height) {
+            // A minimized window reports a zero size; keep the old one.
+            if let Some(size) = input.window_resized()
+                && size.width > 0
+                && size.height > 0
+            {
+                dirty = true;
+                if capture.is_some() {
+                    warn!("window resized, stopping the capture");
+                    save_capture(&mut capture, capture_dir, capture_skip, &mut saving);
+                }
+                (width, height) = (size.width, size.height);
+                if let Err(err) = pixels.resize_surface(width, height) {
+                    error!("failed: {err}");
+                    elwt.exit();
+                    return;
+                }
+                if let Err(err) = pixels.resize_buffer(width, height) {
                     error!("failed: {err}");
                     elwt.exit();
                     return;
                 }
+                world.set_aspect_ratio(width as f32 / height as f32);
+            }
+
+            let scale_steps = input.key_pressed(keys.key_code(Action::RaiseRenderScale)) as i32
+                - input.key_pressed(keys.key_code(Action::LowerRenderScale)) as i32;
+            if scale_steps != 0 && world.target_frame_time().is_some() {
+                world.clear_target_frame_time();
+                info!("adaptive render scale: false");
+            }
+            let new_scale = step_render_scale(render_scale, scale_steps);
+            if scale_steps != 0 && new_scale != render_scale {
+                render_scale = new_scale;
+                dirty = true;
+                if capture.is_some() {
+                    warn!("render scale changed, stopping the capture");
+                    save_capture(&mut capture, capture_dir, capture_skip, &mut saving);
+                }
+                let (w, h) = scaled_size(width, height, render_scale);
+                info!("render scale {render_scale}: {w}x{h}");
             }
 
+            if input.key_pressed(keys.key_code(Action::ToggleCapture)) {
+                if capture.is_some() {
+                    save_capture(&mut capture, capture_dir, capture_skip, &mut saving);
+                } else {
+                    let (w, h) = scaled_size(width, height, render_scale);
+                    capture = Some(Capture::new(w, h, capture_frames));
+                    info!("capturing up to {capture_frames} frames");
+                }
+            }
+            for handle in saving.extract_if(.., |handle| handle.is_finished()) {
+                match handle.join() {
+                    Ok(Ok(written)) => info!("saved {written} frames to {}", capture_dir.display()),
+                    Ok(Err(err)) => error!("could not save the capture: {err}"),
+                    Err(_) => error!("saving the capture panicked"),
+                }
+            }
+
+            if input.key_pressed(keys.key_code(Action::ToggleMouse)) {
+                grabbed = grab_cursor(&window, !grabbed);
+                info!("mouse captured: {grabbed}");
+            }
+
+            if input.key_pressed(keys.key_code(Action::ToggleAdaptiveScale)) {
+                if world.target_frame_time().is_some() {
+                    world.clear_target_frame_time();
+                } else {
+                    world.set_target_frame_time(target_frame_time);
+                }
+                dirty = true;
+                info!(
+                    "adaptive render scale: {}",
+                    world.target_frame_time().is_some()
+                );
+            }
+
+            if input.key_pressed(keys.key_code(Action::TogglePaint)) {
+                if painting.take().is_some() {
+                    stroke.end();
+                    (cursor, brush_at) = (None, None);
+                    info!("painting: false");
+                } else if let Some(object) = world.selected {
+                    match world.paintable_texture(object, args.paint_size) {
+                        Some(texture) => {
+                            painting = Some(texture);
+                            painted = Some(texture);
+                            // The brush follows the cursor, so free it.
+                            if grabbed {
+                                grabbed = grab_cursor(&window, false);
+                            }
+                            info!("painting object {object}");
+                        }
+                        None => warn!("object {object} has no texture coordinates to paint"),
+                    }
+                } else {
+                    warn!("select an object to paint first");
+                }
+            }
+            if let Some(texture) = painting {
+                let pointer = input
+                    .cursor()
+                    .filter(|_| !grabbed)
+                    .and_then(|pos| pixels.window_pos_to_pixel(pos).ok())
+                    .map(|(x, y)| (x as f32 + 0.5, y as f32 + 0.5));
+                let uv = pointer
+                    .filter(|_| input.mouse_held(MouseButton::Left))
+                    .and_then(|(x, y)| world.raycast(&world.screen_ray(x, y, width, height), false))
+                    .filter(|hit| {
+                        world.selected == Some(hit.object)
+                            && world.object_texture(hit.object) == Some(texture)
+                    })
+                    .and_then(|hit| world.hit_uv(&hit));
+                match uv {
+                    Some(uv) => stroke.to(&mut world, texture, uv, &args.brush),
+                    None => stroke.end(),
+                }
+                if pointer != cursor {
+                    cursor = pointer;
+                    brush_at = pointer.and_then(|(x, y)| {
+                        let radius =
+                            brush_cursor(&world, texture, &args.brush, (x, y), (width, height))?;
+                        Some((x, y, radius))
+                    });
+                    dirty = true;
+                }
+            }
+            if input.key_pressed(keys.key_code(Action::SavePaint)) {
+                let paint_out = &args.paint_out;
+                match painted.and_then(|texture| world.materials.texture(texture)) {
+                    Some(texture) => match texture.save(paint_out) {
+                        Ok(()) => info!("texture written to {}", paint_out.display()),
+                        Err(err) => error!("could not write {}: {err}", paint_out.display()),
+                    },
+                    None => warn!("nothing painted to save"),
+                }
+            }
+
+            // A captured cursor is hidden for mouse look, so pick what is
+            // under the middle of the screen; otherwise under the cursor.
+            // In paint mode the left button paints instead.
+            if painting.is_none() && input.mouse_pressed(MouseButton::Left) {
+                let (x, y) = input
+                    .cursor()
+                    .filter(|_| !grabbed)
+                    .and_then(|pos| pixels.window_pos_to_pixel(pos).ok())
+                    .map_or((width as f32 / 2.0, height as f32 / 2.0), |(x, y)| {
+                        (x as f32 + 0.5, y as f32 + 0.5)
+                    });
+                let ray = world.screen_ray(x, y, width, height);
+                match world.raycast(&ray, false) {
+                    Some(hit) => {
+                        let name = world.models[hit.object].name.as_deref();
+                        info!(
+                            "picked object {} ({}) at distance {:.2}",
+                            hit.object,
+                            name.unwrap_or("unnamed"),
+                            hit.distance
+                        );
+                        world.selected = Some(hit.object);
+                        world.labels = vec![Label {
+                            leader: true,
+                            hide_when_occluded: true,
+                            ..Label::new(
+                                ray.origin + ray.dir * hit.distance,
+                                format!("#{} {:.2}", hit.object, hit.distance),
+                                Color::YELLOW,
+                            )
+                        }];
+                    }
+                    None => {
+                        info!("picked nothing");
+                        world.labels.clear();
+                    }
+                }
+            }
+
+            if input.key_pressed(keys.key_code(Action::CycleSelection)) {
+                world.selected = world.next_shown(world.selected);
+            }
+            for (layer, action) in LAYER_ACTIONS.into_iter().enumerate() {
+                if input.key_pressed(keys.key_code(action)) {
+                    world.visible_layers ^= 1 << layer;
+                    let shown = world.visible_layers & (1 << layer) != 0;
+                    info!("layer {layer}: {}", if shown { "shown" } else { "hidden" });
+                }
+            }
+
+            let ctrl = input.held_control();
+            for (slot, action) in BOOKMARK_ACTIONS.into_iter().enumerate() {
+                if !input.key_pressed(keys.key_code(action)) {
+                    continue;
+                }
+                if ctrl {
+                    world.save_bookmark(slot);
+                    match save_bookmarks(bookmark_path, &world.bookmarks) {
+                        Ok(()) => info!("saved bookmark {}", slot + 1),
+                        Err(err) => error!("could not save {}: {err}", bookmark_path.display()),
+                    }
+                } else if world.go_to_bookmark(slot, bookmark_seconds) {
+                    playing = None;
+                    info!("flying to bookmark {}", slot + 1);
+                } else {
+                    info!("no bookmark {}", slot + 1);
+                }
+            }
+
+            if input.key_pressed(keys.key_code(Action::ExportScene)) {
+                match world.export_obj_with_materials(export_path) {
+                    Ok(()) => info!("scene written to {}", export_path.display()),
+                    Err(err) => error!("could not write {}: {err}", export_path.display()),
+                }
+            }
+
+            if input.key_pressed(keys.key_code(Action::Deselect)) {
+                world.selected = None;
+                world.labels.clear();
+            }
+
+            if input.key_pressed(keys.key_code(Action::SpawnCube)) {
+                let spot = world.camera.position + world.camera.forward() * 2.0;
+                let mut cube = Object::new(
+                    CubeMesh::new(Color {
+                        r: 220,
+                        g: 120,
+                        b: 60,
+                        a: 255,
+                    }),
+                    spot.x,
+                    spot.y,
+                    spot.z,
+                );
+                cube.scale = 0.25;
+                match world.add_object(cube) {
+                    Ok(index) => info!("spawned object {index}"),
+                    Err(err) => warn!("couldn't spawn cube: {err}"),
+                }
+            }
+
+            if input.key_pressed(keys.key_code(Action::DeleteSelected))
+                && let Some(index) = world.selected
+                && world.remove_object(index).is_some()
+            {
+                scene_models.retain(|&model| model != index);
+                for model in &mut scene_models {
+                    if *model > index {
+                        *model -= 1;
+                    }
+                }
+                info!("removed object {index}");
+            }
+
+            if input.key_pressed(keys.key_code(Action::CycleRenderMode)) {
+                world.render_mode = world.render_mode.next();
+                info!("render mode: {:?}", world.render_mode);
+            }
+
+            if input.key_pressed(keys.key_code(Action::ToggleToon)) {
+                world.shading_model = match world.shading_model {
+                    ShadingModel::Lambert => ShadingModel::Toon { bands: 3 },
+                    ShadingModel::Toon { .. } => ShadingModel::Lambert,
+                };
+                info!("shading: {:?}", world.shading_model);
+            }
+
+            if input.key_pressed(keys.key_code(Action::ToggleSpotlight)) {
+                world.light.kind = match world.light.kind {
+                    LightKind::Directional => LightKind::Spot {
+                        inner_angle: 0.2,
+                        outer_angle: 0.35,
+                    },
+                    LightKind::Spot { .. } => LightKind::Directional,
+                };
+                info!("light: {:?}", world.light.kind);
+            }
+
+            if input.key_pressed(keys.key_code(Action::ToggleGamma)) {
+                world.gamma_correct = !world.gamma_correct;
+                info!("gamma correction: {}", world.gamma_correct);
+            }
+
+            if input.key_pressed(keys.key_code(Action::ToggleDither)) {
+                world.dither = !world.dither;
+                info!("dithering: {}", world.dither);
+            }
+
+            if input.key_pressed(keys.key_code(Action::ToggleStereo)) {
+                world.stereo = match world.stereo {
+                    Some(_) => None,
+                    None => Some(Stereo::default()),
+                };
+                info!("stereo: {:?}", world.stereo);
+            }
+
+            if input.key_pressed(keys.key_code(Action::FrameAll)) {
+                world.cancel_camera_transition();
+                match world.selected {
+                    Some(index) => {
+                        let focus =
+                            world.world_matrices()[index].transform_point(&Point3::origin());
+                        world.camera.orbit_around(focus);
+                        info!("orbiting object {index}");
+                    }
+                    None => world.frame_all(),
+                }
+            }
+
+            if input.key_pressed(keys.key_code(Action::ToggleOrbit)) {
+                world.cancel_camera_transition();
+                match world.camera.mode {
+                    CameraMode::Fly => {
+                        let focus = world.camera.target();
+                        world.camera.orbit_around(focus);
+                    }
+                    CameraMode::Orbit { .. } => world.camera.fly(),
+                }
+                info!("camera: {:?}", world.camera.mode);
+            }
+
+            if input.key_pressed(keys.key_code(Action::ToggleGizmos)) {
+                world.show_gizmos = !world.show_gizmos;
+            }
+
+            if input.key_pressed(keys.key_code(Action::ToggleStats)) {
+                show_stats = !show_stats;
+                dirty = true;
+            }
+
+            if input.key_pressed(keys.key_code(Action::ToggleNormals)) {
+                world.show_normals = !world.show_normals;
+            }
+
+            if input.key_pressed(keys.key_code(Action::ToggleWireframe)) {
+                world.wireframe = match world.wireframe {
+                    Some(_) => None,
+                    None => Some(WIREFRAME_COLOR),
+                };
+            }
+
+            if input.key_pressed(keys.key_code(Action::ToggleOverlaps)) {
+                world.highlight_overlaps = !world.highlight_overlaps;
+                if world.highlight_overlaps {
+                    info!("overlapping objects: {:?}", world.overlapping_pairs());
+                }
+            }
+
+            if input.key_pressed(keys.key_code(Action::CycleSsaa)) {
+                let next = match world.ssaa() {
+                    1 => 2,
+                    2 => 4,
+                    _ => 1,
+                };
+                world.set_ssaa(next).expect("1, 2 and 4 are supported");
+                info!("supersampling: {next}x");
+            }
+
+            if input.key_pressed(keys.key_code(Action::ToggleProgressive)) {
+                progressive_enabled = !progressive_enabled;
+                progressive.reset();
+                dirty = true;
+                info!("progressive refinement: {progressive_enabled}");
+            }
+
+            if input.key_pressed(keys.key_code(Action::ToggleRasterBackend)) {
+                world.raster_backend = match world.raster_backend {
+                    RasterBackend::BoundingBox => RasterBackend::Scanline,
+                    RasterBackend::Scanline => RasterBackend::BoundingBox,
+                };
+                info!("rasterizer: {:?}", world.raster_backend);
+            }
+
+            if input.key_pressed(keys.key_code(Action::RecordTrack)) {
+                match recording.take() {
+                    Some((mut track, time)) => {
+                        track.record(time, &world.camera);
+                        match track.save(track_path) {
+                            Ok(()) => info!(
+                                "saved {} keyframes to {}",
+                                track.keyframes().len(),
+                                track_path.display()
+                            ),
+                            Err(err) => error!("could not save {}: {err}", track_path.display()),
+                        }
+                        last_track = Some(track);
+                    }
+                    None => {
+                        let mut track = CameraTrack::new();
+                        track.record(0.0, &world.camera);
+                        recording = Some((track, 0.0));
+                        playing = None;
+                        info!("recording camera track");
+                    }
+                }
+            }
+
+            if input.key_pressed(keys.key_code(Action::PlayTrack)) {
+                if playing.take().is_some() {
+                    info!("stopped playback");
+                } else {
+                    let track = match last_track.clone() {
+                        Some(track) => Ok(track),
+                        None => CameraTrack::from_path(track_path),
+                    };
+                    match track {
+                        Ok(track) if !track.is_empty() => {
+                            recording = None;
+                            world.cancel_camera_transition();
+                            world.camera.fly();
+                            info!("playing {:.1}s camera track", track.duration());
+                            playing = Some((track, 0.0));
+                        }
+                        Ok(_) => warn!("{} has no keyframes", track_path.display()),
+                        Err(err) => error!("could not load {}: {err}", track_path.display()),
+                    }
+                }
+            }
+
+            // Without the cursor, mouse and movement keys belong to whatever
+            // window the user is working in. Playback ignores them entirely.
+            let looking = grabbed && focused && playing.is_none();
             let (dx, dy) = input.mouse_diff();
-            let sensitivity = 0.003;
-            world.camera.yaw -= dx * sensitivity;
-            world.camera.pitch -= dy * sensitivity;
-
-            let max_pitch = std::f32::consts::FRAC_PI_2 - 0.01;
-            world.camera.pitch = world.camera.pitch.clamp(-max_pitch, max_pitch);
-
-            let radius = (world.camera.position - world.camera.target).norm();
-            let yaw = world.camera.yaw;
-            let pitch = world.camera.pitch;
-
-            world.camera.target.x = world.camera.position.x + radius * pitch.cos() * yaw.sin();
-            world.camera.target.y = world.camera.position.y + radius * pitch.sin();
-            world.camera.target.z = world.camera.position.z + radius * pitch.cos() * yaw.cos();
-            handle_keys(&input, &mut world.camera, 0.1);
-            window.request_redraw();
+            // Orbiting also follows a right-button drag with the cursor free.
+            let dragging = focused
+                && playing.is_none()
+                && matches!(world.camera.mode, CameraMode::Orbit { .. })
+                && input.mouse_held(MouseButton::Right);
+            // Steering the camera takes it back from a bookmark transition.
+            let (_, scroll) = input.scroll_diff();
+            let moving = [
+                Action::MoveForward,
+                Action::MoveBack,
+                Action::StrafeLeft,
+                Action::StrafeRight,
+            ]
+            .into_iter()
+            .any(|action| input.key_held(keys.key_code(action)));
+            if ((looking || dragging) && (dx != 0.0 || dy != 0.0)
+                || looking && (scroll != 0.0 || moving))
+                && world.cancel_camera_transition()
+            {
+                info!("bookmark transition cancelled");
+            }
+            // Only turn on actual movement so an idle camera stays
+            // bit-for-bit still and progressive refinement can converge.
+            if (looking || dragging) && (dx != 0.0 || dy != 0.0) {
+                let sensitivity = keys.mouse_sensitivity;
+                world.camera.rotate(-dx * sensitivity, -dy * sensitivity);
+            }
+            if looking && scroll != 0.0 {
+                world.camera.dolly(scroll * 0.5);
+            }
+            if looking {
+                handle_keys(&input, &mut world.camera, &keys);
+            }
+            if let Some(eye_height) = args.eye_height
+                && playing.is_none()
+            {
+                world.keep_camera_above_ground(eye_height);
+            }
+            if playing.is_none() {
+                edit_selected(&input, &mut world, &keys);
+            }
+            edit_light(&input, &mut world, &keys, headlamp);
+            edit_stereo(&input, &mut world, &keys);
+            if input.key_pressed(keys.key_code(Action::Headlamp)) {
+                headlamp = !headlamp;
+                // The marker would sit right on the eye.
+                world.show_light = !headlamp;
+                info!("headlamp: {headlamp}");
+            }
+            if headlamp {
+                world.light.position = world.camera.position;
+                world.light.target = world.camera.position + world.camera.forward();
+            }
+
+            // The scene steps at a fixed rate and frames are drawn part way
+            // between the last two steps, however often they come.
+            let now = Instant::now();
+            let dt = (now - last_update).as_secs_f32();
+            unsimulated = (unsimulated + dt).min(MAX_CATCH_UP);
+            while unsimulated >= UPDATE_STEP {
+                world.update(UPDATE_STEP);
+                unsimulated -= UPDATE_STEP;
+            }
+            world.interpolation = unsimulated / UPDATE_STEP;
+            follow_player(&mut world);
+            last_update = now;
+            if let Some(watcher) = &mut watcher {
+                let changed = watcher.changed();
+                if !changed.is_empty() {
+                    info!("reloading {changed:?}");
+                    world.reload_sources(&changed);
+                    if let Some(path) = args
+                        .scene_file
+                        .as_ref()
+                        .filter(|path| changed.contains(path))
+                    {
+                        match SceneFile::from_path(path) {
+                            Ok(scene_file) => {
+                                scene_models = load_scene_file(
+                                    &mut world,
+                                    &scene_file,
+                                    &scene_models,
+                                    !args.keep_camera,
+                                );
+                                for model in &scene_file.models {
+                                    watcher.watch(&model.path);
+                                }
+                            }
+                            Err(err) => {
+                                error!("{}: {err}; keeping the scene as it was", path.display());
+                            }
+                        }
+                    }
+                }
+            }
+            dirty |= world.poll_loads() > 0;
+            dirty |= world.stream_chunks() > 0;
+
+            if let Some((track, time)) = &mut recording {
+                *time += dt;
+                if *time - track.duration() >= TRACK_INTERVAL {
+                    track.record(*time, &world.camera);
+                }
+            }
+            if let Some((track, time)) = &mut playing {
+                *time += dt;
+                track.apply(*time, &mut world.camera);
+                if *time > track.duration() {
+                    info!("playback finished");
+                    playing = None;
+                }
+            }
+
+            // Camera moves, edits and animation all show up in the
+            // fingerprint. Playback and capture want a steady stream of
+            // frames even when the picture holds still.
+            let fingerprint = world.fingerprint(&world.camera.generate_view_mat());
+            dirty |= drawn_fingerprint != Some(fingerprint)
+                || world.is_animated()
+                || playing.is_some()
+                || capture.is_some();
+            // Under a frame rate cap, a frame that isn't due yet waits in
+            // the event loop, so input keeps being handled and makes it
+            // into the frame, and only its last moment is spun away.
+            let due = pacer.next_frame().filter(|_| dirty);
+            let held_back = due.filter(|due| *due > Instant::now() + SPIN_MARGIN);
+            if dirty && held_back.is_none() {
+                if let Some(due) = due {
+                    sleep_until(due);
+                }
+                window.request_redraw();
+            }
+            // Nothing wakes the loop when a background save or load
+            // finishes, or a watched file changes, so check back on them
+            // now and then.
+            let waiting = !saving.is_empty()
+                || world.loads_pending() > 0
+                || world.chunks_pending() > 0
+                || watcher.is_some();
+            let mut wake = waiting.then(|| Instant::now() + Duration::from_millis(100));
+            if let Some(due) = held_back {
+                let due = due - SPIN_MARGIN;
+                wake = Some(wake.map_or(due, |wake| wake.min(due)));
+            }
+            elwt.set_control_flow(wake.map_or(ControlFlow::Wait, ControlFlow::WaitUntil));
         }
     });
+    if profile.frames() > 0 {
+        info!("{profile}");
+    }
     res.map_err(|e| Error::UserDefined(Box::new(e)))
 }
@@ -0,0 +1,194 @@
+//! Named materials shared between objects.
+//!
+//! A material lives once in the world's [`MaterialLibrary`] and objects
+//! refer to it by [`MaterialHandle`], so editing it restyles every object
+//! using it on the next frame.
+
+use std::collections::HashMap;
+
+use crate::mesh::Color;
+use crate::render_texture::RenderTargetHandle;
+use crate::texture::{Texture, TextureHandle};
+
+/// How a surface responds to light.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Material {
+    /// Base color, replacing the mesh's triangle colors.
+    pub color: Color,
+    /// Scale on the diffuse light the surface receives.
+    pub diffuse: f32,
+    /// Light the surface gives off by itself, added to ambient and diffuse.
+    pub emissive: f32,
+    /// Strength of the highlight where light reflects towards the camera.
+    pub specular: f32,
+    /// Exponent of the highlight; higher is smaller and sharper.
+    pub shininess: f32,
+    /// Draw `color` exactly as is, ignoring light, shadow and fog.
+    pub unlit: bool,
+    /// Texture from the [`MaterialLibrary`] over meshes with
+    /// [`crate::mesh::Mesh::uvs`], tinted by `color` and lit texel by
+    /// texel. Texel alpha is ignored; `color`'s is used. Pieces cut up by
+    /// the splitting sort are drawn without it.
+    pub texture: Option<TextureHandle>,
+    /// Show what a [`crate::render_texture::RenderTexture`] of the world
+    /// drew last, in place of `texture`, over meshes with UVs.
+    pub render_target: Option<RenderTargetHandle>,
+}
+
+impl Default for Material {
+    fn default() -> Self {
+        Self {
+            color: Color {
+                r: 180,
+                g: 180,
+                b: 180,
+                a: 255,
+            },
+            diffuse: 1.0,
+            emissive: 0.0,
+            specular: 0.0,
+            shininess: 32.0,
+            unlit: false,
+            texture: None,
+            render_target: None,
+        }
+    }
+}
+
+impl Material {
+    /// The default material in `color`.
+    pub fn new(color: Color) -> Self {
+        Self {
+            color,
+            ..Self::default()
+        }
+    }
+}
+
+/// Per-object changes on top of a library material. Unset fields follow the
+/// library, including later edits to it.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct MaterialOverride {
+    pub color: Option<Color>,
+    pub diffuse: Option<f32>,
+    pub emissive: Option<f32>,
+    pub specular: Option<f32>,
+    pub shininess: Option<f32>,
+    pub unlit: Option<bool>,
+    pub texture: Option<TextureHandle>,
+    pub render_target: Option<RenderTargetHandle>,
+}
+
+impl MaterialOverride {
+    /// `base` with every set field replaced.
+    pub fn apply(&self, base: &Material) -> Material {
+        Material {
+            color: self.color.unwrap_or(base.color),
+            diffuse: self.diffuse.unwrap_or(base.diffuse),
+            emissive: self.emissive.unwrap_or(base.emissive),
+            specular: self.specular.unwrap_or(base.specular),
+            shininess: self.shininess.unwrap_or(base.shininess),
+            unlit: self.unlit.unwrap_or(base.unlit),
+            texture: self.texture.or(base.texture),
+            render_target: self.render_target.or(base.render_target),
+        }
+    }
+}
+
+/// Index of a material in a [`MaterialLibrary`]. Handles stay valid for the
+/// life of the library.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct MaterialHandle(usize);
+
+/// Materials stored once by name, with the textures they use.
+#[derive(Debug, Clone, Default)]
+pub struct MaterialLibrary {
+    materials: Vec<(String, Material)>,
+    by_name: HashMap<String, MaterialHandle>,
+    textures: Vec<Texture>,
+}
+
+impl MaterialLibrary {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Store `material` under `name`. Inserting an existing name replaces
+    /// that material in place and returns its handle.
+    pub fn insert(&mut self, name: &str, material: Material) -> MaterialHandle {
+        if let Some(&handle) = self.by_name.get(name) {
+            self.materials[handle.0].1 = material;
+            return handle;
+        }
+        let handle = MaterialHandle(self.materials.len());
+        self.materials.push((name.to_string(), material));
+        self.by_name.insert(name.to_string(), handle);
+        handle
+    }
+
+    pub fn get(&self, handle: MaterialHandle) -> Option<&Material> {
+        self.materials.get(handle.0).map(|(_, material)| material)
+    }
+
+    /// Edit a material; every object using it picks up the change.
+    pub fn get_mut(&mut self, handle: MaterialHandle) -> Option<&mut Material> {
+        self.materials
+            .get_mut(handle.0)
+            .map(|(_, material)| material)
+    }
+
+    pub fn handle(&self, name: &str) -> Option<MaterialHandle> {
+        self.by_name.get(name).copied()
+    }
+
+    pub fn name(&self, handle: MaterialHandle) -> Option<&str> {
+        self.materials.get(handle.0).map(|(name, _)| name.as_str())
+    }
+
+    /// The library material `handle` with `overrides` applied, the way an
+    /// object with that override sees it.
+    pub fn instantiate_override(
+        &self,
+        handle: MaterialHandle,
+        overrides: &MaterialOverride,
+    ) -> Option<Material> {
+        self.get(handle).map(|base| overrides.apply(base))
+    }
+
+    /// Every material with its handle and name, in insertion order.
+    pub fn iter(&self) -> impl Iterator<Item = (MaterialHandle, &str, &Material)> {
+        self.materials
+            .iter()
+            .enumerate()
+            .map(|(i, (name, material))| (MaterialHandle(i), name.as_str(), material))
+    }
+
+    pub fn len(&self) -> usize {
+        self.materials.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.materials.is_empty()
+    }
+
+    /// Store `texture` for materials to use.
+    pub fn add_texture(&mut self, texture: Texture) -> TextureHandle {
+        self.textures.push(texture);
+        TextureHandle(self.textures.len() - 1)
+    }
+
+    pub fn texture(&self, handle: TextureHandle) -> Option<&Texture> {
+        self.textures.get(handle.0)
+    }
+
+    /// Edit a texture's filter or wrap mode; every material using it picks
+    /// up the change.
+    pub fn texture_mut(&mut self, handle: TextureHandle) -> Option<&mut Texture> {
+        self.textures.get_mut(handle.0)
+    }
+
+    /// Every texture, indexed by handle.
+    pub(crate) fn textures(&self) -> &[Texture] {
+        &self.textures
+    }
+}
@@ -0,0 +1,95 @@
+use std::f32::consts::FRAC_PI_2;
+
+use crate::mesh::lathe::{band, fan, ring};
+use crate::mesh::{Color, Mesh, MeshMut, PrimitiveError, Triangle, compute_smooth_normals};
+use nalgebra::{Point3, Vector3};
+
+/// A cylinder with hemispherical ends, on the y axis and centered on the
+/// origin. Smooth all over.
+#[derive(Debug)]
+pub struct CapsuleMesh {
+    verts: Vec<Point3<f32>>,
+    tris: Vec<Triangle>,
+    normals: Vec<Vector3<f32>>,
+}
+
+impl CapsuleMesh {
+    /// A capsule whose straight middle is `height` long, so it is
+    /// `height + 2 * radius` tall overall. Each hemisphere has `rings`
+    /// bands from its pole to the middle, and every ring `segments` sides.
+    pub fn new(
+        radius: f32,
+        height: f32,
+        rings: usize,
+        segments: usize,
+        color: Color,
+    ) -> Result<Self, PrimitiveError> {
+        PrimitiveError::check_segments(segments)?;
+        if rings == 0 {
+            return Err(PrimitiveError::TooFewRings(rings));
+        }
+        let half = height.max(0.0) / 2.0;
+        let mut verts = Vec::new();
+        let mut tris = Vec::new();
+
+        // Rings from the top down: the upper hemisphere ending at its
+        // equator, then the lower one starting at its own. Without a middle
+        // the two equators are the same ring.
+        let mut profile: Vec<(f32, f32)> = (1..=rings)
+            .map(|k| {
+                let angle = FRAC_PI_2 * k as f32 / rings as f32;
+                (radius * angle.sin(), half + radius * angle.cos())
+            })
+            .collect();
+        let first = if half > 0.0 { 0 } else { 1 };
+        profile.extend((first..rings).map(|k| {
+            let angle = FRAC_PI_2 * k as f32 / rings as f32;
+            (radius * angle.cos(), -half - radius * angle.sin())
+        }));
+
+        let starts: Vec<usize> = profile
+            .iter()
+            .map(|&(r, y)| ring(&mut verts, r, y, segments))
+            .collect();
+        for pair in starts.windows(2) {
+            band(&mut tris, pair[1], pair[0], segments, color);
+        }
+        verts.push(Point3::new(0.0, half + radius, 0.0));
+        fan(&mut tris, verts.len() - 1, starts[0], segments, true, color);
+        verts.push(Point3::new(0.0, -half - radius, 0.0));
+        let last = starts[starts.len() - 1];
+        fan(&mut tris, verts.len() - 1, last, segments, false, color);
+
+        let normals = compute_smooth_normals(&verts, &tris);
+        Ok(Self {
+            verts,
+            tris,
+            normals,
+        })
+    }
+
+    /// One unit normal per vertex, pointing out of the front faces.
+    pub fn normals(&self) -> &[Vector3<f32>] {
+        &self.normals
+    }
+}
+
+impl Mesh for CapsuleMesh {
+    fn tris(&self) -> &Vec<Triangle> {
+        &self.tris
+    }
+
+    fn verts(&self) -> &[Point3<f32>] {
+        &self.verts
+    }
+}
+
+impl MeshMut for CapsuleMesh {
+    fn tris_mut(&mut self) -> &mut Vec<Triangle> {
+        &mut self.tris
+    }
+
+    fn verts_mut(&mut self) -> &mut [Point3<f32>] {
+        &mut self.verts
+    }
+}
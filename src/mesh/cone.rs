@@ -1,178 +1,57 @@
-use crate::mesh::{Mesh, Triangle};
-use nalgebra::Point3;
-use std::f32::consts::PI;
+use crate::mesh::lathe::{fan, ring};
+use crate::mesh::{Color, Mesh, MeshMut, PrimitiveError, Triangle, compute_smooth_normals};
+use nalgebra::{Point3, Vector3};
 
+/// A closed cone on the y axis with its tip up, centered on the origin.
+///
+/// The base has its own vertices so its rim stays sharp, and the tip has
+/// one vertex per side so the side shades smoothly all the way up.
 #[derive(Debug)]
 pub struct ConeMesh {
     verts: Vec<Point3<f32>>,
     tris: Vec<Triangle>,
+    normals: Vec<Vector3<f32>>,
 }
 
 impl ConeMesh {
-    pub fn _new(height: f32, radius: f32) -> Self {
-        let offset = -height / 2.0;
+    /// A cone `height` tall with a base of `radius` and `segments` sides.
+    pub fn new(
+        radius: f32,
+        height: f32,
+        segments: usize,
+        color: Color,
+    ) -> Result<Self, PrimitiveError> {
+        PrimitiveError::check_segments(segments)?;
+        let (bottom, top) = (-height / 2.0, height / 2.0);
+        let mut verts = Vec::with_capacity(3 * segments + 1);
+        let mut tris = Vec::with_capacity(2 * segments);
 
-        let mut verts: Vec<Point3<f32>> = (0..12)
-            .map(|x| -> Point3<f32> {
-                let angle = 2.0 * PI * (x as f32) / 12.0;
-                let x = radius * angle.cos();
-                let z = radius * angle.sin();
-                Point3::new(x, offset, z)
-            })
-            .collect();
+        let side = ring(&mut verts, radius, bottom, segments);
+        let tips = ring(&mut verts, 0.0, top, segments);
+        for i in 0..segments {
+            tris.push(Triangle {
+                v1: side + i,
+                v2: side + (i + 1) % segments,
+                v3: tips + i,
+                color,
+            });
+        }
 
-        verts.push(Point3::new(0.0, height + offset, 0.0));
-        verts.push(Point3::new(0.0, offset, 0.0));
+        let base = ring(&mut verts, radius, bottom, segments);
+        verts.push(Point3::new(0.0, bottom, 0.0));
+        fan(&mut tris, verts.len() - 1, base, segments, false, color);
 
-        Self {
+        let normals = compute_smooth_normals(&verts, &tris);
+        Ok(Self {
             verts,
-            tris: vec![
-                Triangle {
-                    v1: 0,
-                    v2: 1,
-                    v3: 12,
-                    color: RED,
-                },
-                Triangle {
-                    v1: 1,
-                    v2: 2,
-                    v3: 12,
-                    color: ORANGE,
-                },
-                Triangle {
-                    v1: 2,
-                    v2: 3,
-                    v3: 12,
-                    color: YELLOW,
-                },
-                Triangle {
-                    v1: 3,
-                    v2: 4,
-                    v3: 12,
-                    color: GREEN,
-                },
-                Triangle {
-                    v1: 4,
-                    v2: 5,
-                    v3: 12,
-                    color: BLUE,
-                },
-                Triangle {
-                    v1: 5,
-                    v2: 6,
-                    v3: 12,
-                    color: PURPLE,
-                },
-                Triangle {
-                    v1: 6,
-                    v2: 7,
-                    v3: 12,
-                    color: PINK,
-                },
-                Triangle {
-                    v1: 7,
-                    v2: 8,
-                    v3: 12,
-                    color: LIME,
-                },
-                Triangle {
-                    v1: 8,
-                    v2: 9,
-                    v3: 12,
-                    color: GOLD,
-                },
-                Triangle {
-                    v1: 9,
-                    v2: 10,
-                    v3: 12,
-                    color: BEIGE,
-                },
-                Triangle {
-                    v1: 10,
-                    v2: 11,
-                    v3: 12,
-                    color: GRAY,
-                },
-                Triangle {
-                    v1: 11,
-                    v2: 0,
-                    v3: 12,
-                    color: DARKBLUE,
-                },
-                Triangle {
-                    v1: 0,
-                    v2: 13,
-                    v3: 1,
-                    color: SKYBLUE,
-                },
-                Triangle {
-                    v1: 1,
-                    v2: 13,
-                    v3: 2,
-                    color: RED,
-                },
-                Triangle {
-                    v1: 2,
-                    v2: 13,
-                    v3: 3,
-                    color: SKYBLUE,
-                },
-                Triangle {
-                    v1: 3,
-                    v2: 13,
-                    v3: 4,
-                    color: RED,
-                },
-                Triangle {
-                    v1: 4,
-                    v2: 13,
-                    v3: 5,
-                    color: SKYBLUE,
-                },
-                Triangle {
-                    v1: 5,
-                    v2: 13,
-                    v3: 6,
-                    color: RED,
-                },
-                Triangle {
-                    v1: 6,
-                    v2: 13,
-                    v3: 7,
-                    color: SKYBLUE,
-                },
-                Triangle {
-                    v1: 7,
-                    v2: 13,
-                    v3: 8,
-                    color: RED,
-                },
-                Triangle {
-                    v1: 8,
-                    v2: 13,
-                    v3: 9,
-                    color: SKYBLUE,
-                },
-                Triangle {
-                    v1: 9,
-                    v2: 13,
-                    v3: 10,
-                    color: RED,
-                },
-                Triangle {
-                    v1: 10,
-                    v2: 13,
-                    v3: 11,
-                    color: SKYBLUE,
-                },
-                Triangle {
-                    v1: 11,
-                    v2: 13,
-                    v3: 0,
-                    color: RED,
-                },
-            ],
-        }
+            tris,
+            normals,
+        })
+    }
+
+    /// One unit normal per vertex, pointing out of the front faces.
+    pub fn normals(&self) -> &[Vector3<f32>] {
+        &self.normals
     }
 }
 
@@ -185,3 +64,13 @@ impl Mesh for ConeMesh {
         &self.verts
     }
 }
+
+impl MeshMut for ConeMesh {
+    fn tris_mut(&mut self) -> &mut Vec<Triangle> {
+        &mut self.tris
+    }
+
+    fn verts_mut(&mut self) -> &mut [Point3<f32>] {
+        &mut self.verts
+    }
+}
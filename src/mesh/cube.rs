@@ -1,6 +1,7 @@
-use crate::mesh::{Mesh, Triangle};
+use crate::mesh::{Color, Mesh, MeshMut, Triangle};
 use nalgebra::Point3;
 
+/// A cube from -1 to 1 on every axis, in a single color.
 #[derive(Debug)]
 pub struct CubeMesh {
     verts: Vec<Point3<f32>>,
@@ -8,7 +9,7 @@ pub struct CubeMesh {
 }
 
 impl CubeMesh {
-    pub fn _new() -> Self {
+    pub fn new(color: Color) -> Self {
         Self {
             verts: vec![
                 Point3::new(1.0, -1.0, 1.0),
@@ -25,77 +26,88 @@ impl CubeMesh {
                     v1: 0,
                     v2: 1,
                     v3: 2,
-                    color: RED,
+                    color,
                 },
                 Triangle {
                     v1: 0,
                     v2: 2,
                     v3: 3,
-                    color: GREEN,
+                    color,
                 },
                 Triangle {
                     v1: 4,
                     v2: 6,
                     v3: 5,
-                    color: BLUE,
+                    color,
                 },
                 Triangle {
                     v1: 4,
                     v2: 7,
                     v3: 6,
-                    color: ORANGE,
+                    color,
                 },
                 Triangle {
                     v1: 0,
                     v2: 3,
                     v3: 7,
-                    color: PINK,
+                    color,
                 },
                 Triangle {
                     v1: 0,
                     v2: 7,
                     v3: 4,
-                    color: DARKBLUE,
+                    color,
                 },
                 Triangle {
                     v1: 1,
                     v2: 5,
                     v3: 6,
-                    color: LIME,
+                    color,
                 },
                 Triangle {
                     v1: 1,
                     v2: 6,
                     v3: 2,
-                    color: PURPLE,
+                    color,
                 },
                 Triangle {
                     v1: 0,
                     v2: 4,
                     v3: 5,
-                    color: BEIGE,
+                    color,
                 },
                 Triangle {
                     v1: 0,
                     v2: 5,
                     v3: 1,
-                    color: GOLD,
+                    color,
                 },
                 Triangle {
                     v1: 3,
                     v2: 2,
                     v3: 6,
-                    color: GRAY,
+                    color,
                 },
                 Triangle {
                     v1: 3,
                     v2: 6,
                     v3: 7,
-                    color: YELLOW,
+                    color,
                 },
             ],
         }
     }
+
+    /// A box from `min` to `max`, in a single color.
+    pub fn spanning(min: Point3<f32>, max: Point3<f32>, color: Color) -> Self {
+        let mut cube = Self::new(color);
+        let half = (max - min) / 2.0;
+        let centre = min + half;
+        for v in &mut cube.verts {
+            *v = centre + v.coords.component_mul(&half);
+        }
+        cube
+    }
 }
 
 impl Mesh for CubeMesh {
@@ -107,3 +119,13 @@ impl Mesh for CubeMesh {
         &self.verts
     }
 }
+
+impl MeshMut for CubeMesh {
+    fn tris_mut(&mut self) -> &mut Vec<Triangle> {
+        &mut self.tris
+    }
+
+    fn verts_mut(&mut self) -> &mut [Point3<f32>] {
+        &mut self.verts
+    }
+}
@@ -1,70 +1,60 @@
-use crate::mesh::{Mesh, Triangle};
-use nalgebra::Point3;
-use std::f32::consts::PI;
+use crate::mesh::lathe::{band, fan, ring};
+use crate::mesh::{Color, Mesh, MeshMut, PrimitiveError, Triangle, compute_smooth_normals};
+use nalgebra::{Point3, Vector3};
 
+/// A closed cylinder standing on the y axis, centered on the origin.
+///
+/// The caps have their own vertices, so the side shades smoothly while the
+/// rims stay sharp.
 #[derive(Debug)]
 pub struct CylinderMesh {
     verts: Vec<Point3<f32>>,
     tris: Vec<Triangle>,
+    normals: Vec<Vector3<f32>>,
 }
 
 impl CylinderMesh {
-    pub fn _new(height: f32, radius: f32) -> Self {
-        let offset = -height / 2.0;
+    /// A cylinder `height` tall with `segments` sides around.
+    pub fn new(
+        radius: f32,
+        height: f32,
+        segments: usize,
+        color: Color,
+    ) -> Result<Self, PrimitiveError> {
+        PrimitiveError::check_segments(segments)?;
+        let (bottom, top) = (-height / 2.0, height / 2.0);
+        let mut verts = Vec::with_capacity(4 * segments + 2);
+        let mut tris = Vec::with_capacity(4 * segments);
 
-        let mut verts: Vec<Point3<f32>> = Vec::new();
+        let side_bottom = ring(&mut verts, radius, bottom, segments);
+        let side_top = ring(&mut verts, radius, top, segments);
+        band(&mut tris, side_bottom, side_top, segments, color);
 
-        for i in 0..12 {
-            let angle = 2.0 * PI * (i as f32) / 12.0;
-            let x = radius * angle.cos();
-            let z = radius * angle.sin();
-            verts.push(Point3::new(x, offset, z));
-        }
+        let cap_bottom = ring(&mut verts, radius, bottom, segments);
+        let cap_top = ring(&mut verts, radius, top, segments);
+        verts.push(Point3::new(0.0, bottom, 0.0));
+        fan(
+            &mut tris,
+            verts.len() - 1,
+            cap_bottom,
+            segments,
+            false,
+            color,
+        );
+        verts.push(Point3::new(0.0, top, 0.0));
+        fan(&mut tris, verts.len() - 1, cap_top, segments, true, color);
 
-        for i in 0..12 {
-            let angle = 2.0 * PI * (i as f32) / 12.0;
-            let x = radius * angle.cos();
-            let z = radius * angle.sin();
-            verts.push(Point3::new(x, height + offset, z));
-        }
-
-        verts.push(Point3::new(0.0, offset, 0.0));
-        verts.push(Point3::new(0.0, height + offset, 0.0));
-
-        let mut tris: Vec<Triangle> = Vec::new();
-        for i in 0..12 {
-            let next = (i + 1) % 12;
-
-            tris.push(Triangle {
-                v1: i,
-                v2: next,
-                v3: i + 12,
-                color: RED,
-            });
-
-            tris.push(Triangle {
-                v1: i + 12,
-                v2: next,
-                v3: next + 12,
-                color: ORANGE,
-            });
-
-            tris.push(Triangle {
-                v1: next,
-                v2: i,
-                v3: 24,
-                color: BLUE,
-            });
-
-            tris.push(Triangle {
-                v1: i + 12,
-                v2: next + 12,
-                v3: 25,
-                color: GREEN,
-            });
-        }
+        let normals = compute_smooth_normals(&verts, &tris);
+        Ok(Self {
+            verts,
+            tris,
+            normals,
+        })
+    }
 
-        Self { verts, tris }
+    /// One unit normal per vertex, pointing out of the front faces.
+    pub fn normals(&self) -> &[Vector3<f32>] {
+        &self.normals
     }
 }
 
@@ -77,3 +67,13 @@ impl Mesh for CylinderMesh {
         &self.verts
     }
 }
+
+impl MeshMut for CylinderMesh {
+    fn tris_mut(&mut self) -> &mut Vec<Triangle> {
+        &mut self.tris
+    }
+
+    fn verts_mut(&mut self) -> &mut [Point3<f32>] {
+        &mut self.verts
+    }
+}
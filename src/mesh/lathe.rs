@@ -0,0 +1,69 @@
+//! Building blocks for shapes made by spinning a profile around the y axis.
+
+use std::f32::consts::TAU;
+
+use crate::mesh::{Color, Triangle};
+use nalgebra::Point3;
+
+/// Append `segments` vertices evenly around a circle of `radius` at height
+/// `y`, returning the index of the first.
+pub(super) fn ring(verts: &mut Vec<Point3<f32>>, radius: f32, y: f32, segments: usize) -> usize {
+    let start = verts.len();
+    verts.extend((0..segments).map(|i| {
+        let angle = TAU * i as f32 / segments as f32;
+        Point3::new(radius * angle.cos(), y, radius * angle.sin())
+    }));
+    start
+}
+
+/// Join the ring starting at `lower` to the one above it starting at
+/// `upper` with a band of quads facing away from the axis.
+pub(super) fn band(
+    tris: &mut Vec<Triangle>,
+    lower: usize,
+    upper: usize,
+    segments: usize,
+    color: Color,
+) {
+    for i in 0..segments {
+        let next = (i + 1) % segments;
+        tris.push(Triangle {
+            v1: lower + i,
+            v2: lower + next,
+            v3: upper + i,
+            color,
+        });
+        tris.push(Triangle {
+            v1: upper + i,
+            v2: lower + next,
+            v3: upper + next,
+            color,
+        });
+    }
+}
+
+/// Fan the ring starting at `ring` around the vertex `center`. `up` picks
+/// the side the fan faces: +y for a top cap, -y for a bottom one.
+pub(super) fn fan(
+    tris: &mut Vec<Triangle>,
+    center: usize,
+    ring: usize,
+    segments: usize,
+    up: bool,
+    color: Color,
+) {
+    for i in 0..segments {
+        let next = (i + 1) % segments;
+        let (v2, v3) = if up {
+            (ring + i, ring + next)
+        } else {
+            (ring + next, ring + i)
+        };
+        tris.push(Triangle {
+            v1: center,
+            v2,
+            v3,
+            color,
+        });
+    }
+}
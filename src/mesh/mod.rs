@@ -1,12 +1,33 @@
-// pub mod cone;
-// pub mod cube;
-// pub mod cylinder;
+//! Mesh geometry and the built-in shapes.
+
+pub mod capsule;
+pub mod cone;
+pub mod cube;
+pub mod cylinder;
 // pub mod letter_n;
+mod lathe;
+mod normals;
+pub mod obj;
 pub mod p_hack;
+pub mod quad;
+pub mod simplify;
+pub mod subdivide;
+pub mod terrain;
+pub mod torus;
+
+use std::fmt;
+
+use nalgebra::{Point3, Vector2};
 
-use nalgebra::Point3;
+use crate::material::Material;
 
-#[derive(Debug)]
+pub use normals::{compute_smooth_normals, compute_split_normals};
+pub use simplify::simplify;
+pub use subdivide::subdivide;
+
+/// A triangle indexing three vertices of its mesh, with a flat color. Front
+/// faces wind clockwise when seen from outside the mesh.
+#[derive(Debug, Clone, Copy)]
 pub struct Triangle {
     pub v1: usize,
     pub v2: usize,
@@ -14,7 +35,8 @@ pub struct Triangle {
     pub color: Color,
 }
 
-#[derive(Debug)]
+/// An 8-bit RGBA color. Alpha below 255 is blended over the background.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct Color {
     pub r: u8,
     pub g: u8,
@@ -22,7 +44,267 @@ pub struct Color {
     pub a: u8,
 }
 
+/// How a mesh stores its triangles.
+#[derive(Debug, Clone, Copy)]
+pub enum MeshPrimitives<'a> {
+    /// Independent triangles, each with its own color.
+    TriangleList(&'a [Triangle]),
+    /// Triangles in one color, each index after the first two making a
+    /// triangle with the two before it. Every other triangle is walked in
+    /// reverse so they all keep the winding of the first. Separate strips
+    /// are joined by repeating an index, which makes triangles that are
+    /// skipped.
+    TriangleStrip { indices: &'a [usize], color: Color },
+}
+
+impl<'a> MeshPrimitives<'a> {
+    /// Every triangle to draw, in order, with strips walked into
+    /// independent triangles.
+    pub fn triangles(self) -> impl Iterator<Item = Triangle> + 'a {
+        let (list, strip, color) = match self {
+            MeshPrimitives::TriangleList(tris) => (tris, &[][..], None),
+            MeshPrimitives::TriangleStrip { indices, color } => (&[][..], indices, Some(color)),
+        };
+        list.iter()
+            .copied()
+            .chain(strip.windows(3).enumerate().filter_map(move |(i, window)| {
+                let &[a, b, c] = window else { unreachable!() };
+                if a == b || b == c || a == c {
+                    return None;
+                }
+                // Odd triangles run the other way round the strip.
+                let (v1, v2) = if i % 2 == 0 { (a, b) } else { (b, a) };
+                Some(Triangle {
+                    v1,
+                    v2,
+                    v3: c,
+                    color: color?,
+                })
+            }))
+    }
+}
+
+/// Indexed triangle geometry that can be placed in a [`crate::World`].
 pub trait Mesh {
     fn verts(&self) -> &[Point3<f32>];
     fn tris(&self) -> &Vec<Triangle>;
+
+    /// The triangles as the renderer walks them. Meshes stored as strips
+    /// override this and leave [`Mesh::tris`] empty.
+    fn primitives(&self) -> MeshPrimitives<'_> {
+        MeshPrimitives::TriangleList(self.tris())
+    }
+
+    /// Smallest and largest corner of the box around every vertex, in the
+    /// mesh's own space. Both are the origin for a mesh without vertices.
+    fn aabb(&self) -> (Point3<f32>, Point3<f32>) {
+        let mut verts = self.verts().iter();
+        let Some(first) = verts.next() else {
+            return (Point3::origin(), Point3::origin());
+        };
+        verts.fold((*first, *first), |(min, max), v| (min.inf(v), max.sup(v)))
+    }
+
+    /// Texture coordinates of each vertex, indexed like [`Mesh::verts`],
+    /// for meshes that can be textured. See [`crate::texture`] for which
+    /// way `u` and `v` run.
+    fn uvs(&self) -> Option<&[Vector2<f32>]> {
+        None
+    }
+
+    /// Whether the mesh only stands in for geometry that isn't in memory
+    /// yet, like the bounds of a chunk that hasn't been streamed in.
+    /// Picking looks through placeholders.
+    fn placeholder(&self) -> bool {
+        false
+    }
+
+    /// Material of the triangle at `index`, for meshes whose parts respond
+    /// to light differently. Its color is ignored in favor of the
+    /// triangle's own.
+    fn material(&self, _index: usize) -> Option<&Material> {
+        None
+    }
+
+    /// Check that every triangle refers to vertices the mesh has, reporting
+    /// the first one that doesn't. Strip triangles are numbered by their
+    /// position in the strip, counting the ones joining strips.
+    fn validate(&self) -> Result<(), MeshError> {
+        let vertex_count = self.verts().len();
+        let out_of_range = |triangle: usize, indices: &[usize]| {
+            indices
+                .iter()
+                .find(|&&index| index >= vertex_count)
+                .map(|&index| MeshError::IndexOutOfRange {
+                    triangle,
+                    index,
+                    vertex_count,
+                })
+        };
+        let error = match self.primitives() {
+            MeshPrimitives::TriangleList(tris) => tris
+                .iter()
+                .enumerate()
+                .find_map(|(triangle, tri)| out_of_range(triangle, &[tri.v1, tri.v2, tri.v3])),
+            MeshPrimitives::TriangleStrip { indices, .. } => indices
+                .iter()
+                .enumerate()
+                .find_map(|(i, &index)| out_of_range(i.saturating_sub(2), &[index])),
+        };
+        error.map_or(Ok(()), Err)
+    }
+
+    /// Number of listed triangles using the same vertex twice. They cover
+    /// no pixels but still cost a trip through the pipeline. Strips are
+    /// joined by such triangles on purpose, so they never count.
+    fn degenerate_triangles(&self) -> usize {
+        match self.primitives() {
+            MeshPrimitives::TriangleList(tris) => tris
+                .iter()
+                .filter(|tri| tri.v1 == tri.v2 || tri.v2 == tri.v3 || tri.v1 == tri.v3)
+                .count(),
+            MeshPrimitives::TriangleStrip { .. } => 0,
+        }
+    }
+}
+
+/// A [`Mesh`] whose geometry can be changed in place.
+pub trait MeshMut: Mesh {
+    fn tris_mut(&mut self) -> &mut Vec<Triangle>;
+    fn verts_mut(&mut self) -> &mut [Point3<f32>];
+
+    /// Move the mesh so the middle of its bounding box is at the origin.
+    fn center_to_origin(&mut self) {
+        let (min, max) = self.aabb();
+        let center = nalgebra::center(&min, &max).coords;
+        for v in self.verts_mut() {
+            *v -= center;
+        }
+    }
+
+    /// Scale the mesh about the origin so the longest side of its bounding
+    /// box is `target_extent`. A mesh with no extent is left alone.
+    fn normalize_scale(&mut self, target_extent: f32) {
+        let (min, max) = self.aabb();
+        let extent = (max - min).max();
+        if extent <= f32::EPSILON {
+            return;
+        }
+        let factor = target_extent / extent;
+        for v in self.verts_mut() {
+            *v *= factor;
+        }
+    }
+
+    /// Reverse the winding of every triangle, turning the mesh inside out.
+    /// Fixes meshes exported with the opposite front face convention.
+    fn flip_winding(&mut self) {
+        for tri in self.tris_mut() {
+            std::mem::swap(&mut tri.v2, &mut tri.v3);
+        }
+    }
 }
+
+/// Settings shared by the mesh loaders.
+#[derive(Debug, Clone, Copy)]
+pub struct ImportOptions {
+    /// Color of faces that have no material.
+    pub color: Color,
+    /// Center the loaded mesh on the origin and scale it so its longest
+    /// side is this long.
+    pub normalize: Option<f32>,
+    /// Cut meshes with more triangles than this down to it with
+    /// [`simplify`].
+    pub simplify: Option<usize>,
+}
+
+impl Default for ImportOptions {
+    fn default() -> Self {
+        Self {
+            color: Color {
+                r: 180,
+                g: 180,
+                b: 180,
+                a: 255,
+            },
+            normalize: None,
+            simplify: None,
+        }
+    }
+}
+
+impl ImportOptions {
+    pub fn color(mut self, color: Color) -> Self {
+        self.color = color;
+        self
+    }
+
+    /// See [`ImportOptions::normalize`].
+    pub fn normalized(mut self, target_extent: f32) -> Self {
+        self.normalize = Some(target_extent);
+        self
+    }
+
+    /// See [`ImportOptions::simplify`].
+    pub fn simplified(mut self, target_tris: usize) -> Self {
+        self.simplify = Some(target_tris);
+        self
+    }
+}
+
+/// Why a mesh can't be drawn.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MeshError {
+    /// A triangle refers to a vertex past the end of the mesh.
+    IndexOutOfRange {
+        triangle: usize,
+        index: usize,
+        vertex_count: usize,
+    },
+}
+
+impl fmt::Display for MeshError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MeshError::IndexOutOfRange {
+                triangle,
+                index,
+                vertex_count,
+            } => write!(
+                f,
+                "triangle {triangle} refers to vertex {index}, but the mesh has {vertex_count}"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for MeshError {}
+
+/// Why a built-in shape could not be generated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PrimitiveError {
+    /// Round shapes need at least 3 segments around.
+    TooFewSegments(usize),
+    /// A capsule needs at least 1 ring per hemisphere.
+    TooFewRings(usize),
+}
+
+impl PrimitiveError {
+    fn check_segments(segments: usize) -> Result<(), PrimitiveError> {
+        if segments < 3 {
+            return Err(PrimitiveError::TooFewSegments(segments));
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for PrimitiveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PrimitiveError::TooFewSegments(n) => write!(f, "{n} segments, needs at least 3"),
+            PrimitiveError::TooFewRings(n) => write!(f, "{n} rings, needs at least 1"),
+        }
+    }
+}
+
+impl std::error::Error for PrimitiveError {}
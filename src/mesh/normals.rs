@@ -0,0 +1,102 @@
+//! Per-vertex normals worked out from the faces around each vertex.
+
+use std::collections::HashMap;
+
+use nalgebra::{Point3, Vector3};
+
+use crate::mesh::Triangle;
+
+/// Outward unit normal of a clockwise front face and its angles at `v1`,
+/// `v2` and `v3`, or `None` for a zero area triangle.
+fn face(verts: &[Point3<f32>], tri: &Triangle) -> Option<(Vector3<f32>, [f32; 3])> {
+    let (a, b, c) = (verts[tri.v1], verts[tri.v2], verts[tri.v3]);
+    let normal = (c - a).cross(&(b - a)).try_normalize(f32::EPSILON)?;
+    Some((
+        normal,
+        [
+            (b - a).angle(&(c - a)),
+            (a - b).angle(&(c - b)),
+            (a - c).angle(&(b - c)),
+        ],
+    ))
+}
+
+/// One unit normal per vertex: the average of the outward normals of every
+/// triangle using it, each weighted by the triangle's angle at the vertex so
+/// the result doesn't depend on how faces were split into triangles. Zero
+/// area triangles are skipped, and a vertex with no other triangles gets a
+/// zero normal.
+pub fn compute_smooth_normals(verts: &[Point3<f32>], tris: &[Triangle]) -> Vec<Vector3<f32>> {
+    let mut normals = vec![Vector3::zeros(); verts.len()];
+    for tri in tris {
+        let Some((normal, angles)) = face(verts, tri) else {
+            continue;
+        };
+        for (v, angle) in [tri.v1, tri.v2, tri.v3].into_iter().zip(angles) {
+            normals[v] += normal * angle;
+        }
+    }
+    for normal in &mut normals {
+        *normal = normal.try_normalize(f32::EPSILON).unwrap_or_default();
+    }
+    normals
+}
+
+/// Like [`compute_smooth_normals`], but faces meeting at more than
+/// `max_angle` degrees keep a hard edge. Each corner is smoothed only with
+/// the faces around its vertex within that angle of its own face, and a
+/// vertex needing more than one normal is split into copies.
+///
+/// Returns the new vertices, the triangles re-indexed to them and one normal
+/// per new vertex.
+pub fn compute_split_normals(
+    verts: &[Point3<f32>],
+    tris: &[Triangle],
+    max_angle: f32,
+) -> (Vec<Point3<f32>>, Vec<Triangle>, Vec<Vector3<f32>>) {
+    let cos_max = max_angle.to_radians().cos() - 1e-6;
+    let faces: Vec<_> = tris.iter().map(|tri| face(verts, tri)).collect();
+    // Every (face normal, corner angle) around each vertex.
+    let mut around: Vec<Vec<(Vector3<f32>, f32)>> = vec![Vec::new(); verts.len()];
+    for (tri, face) in tris.iter().zip(&faces) {
+        if let Some((normal, angles)) = face {
+            for (v, angle) in [tri.v1, tri.v2, tri.v3].into_iter().zip(angles) {
+                around[v].push((*normal, *angle));
+            }
+        }
+    }
+
+    let mut new_verts = Vec::new();
+    let mut normals = Vec::new();
+    // Copies already made of a vertex, keyed by the exact normal bits so
+    // corners with equal normals share one.
+    let mut copies: HashMap<(usize, [u32; 3]), usize> = HashMap::new();
+    let mut new_tris = Vec::with_capacity(tris.len());
+    for (tri, face) in tris.iter().zip(&faces) {
+        let mut corner = |v: usize| -> usize {
+            let normal = match face {
+                Some((own, _)) => around[v]
+                    .iter()
+                    .filter(|(normal, _)| normal.dot(own) >= cos_max)
+                    .map(|(normal, angle)| normal * *angle)
+                    .sum::<Vector3<f32>>()
+                    .try_normalize(f32::EPSILON)
+                    .unwrap_or(*own),
+                None => Vector3::zeros(),
+            };
+            let key = (v, normal.map(f32::to_bits).into());
+            *copies.entry(key).or_insert_with(|| {
+                new_verts.push(verts[v]);
+                normals.push(normal);
+                new_verts.len() - 1
+            })
+        };
+        new_tris.push(Triangle {
+            v1: corner(tri.v1),
+            v2: corner(tri.v2),
+            v3: corner(tri.v3),
+            color: tri.color,
+        });
+    }
+    (new_verts, new_tris, normals)
+}
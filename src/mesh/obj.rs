@@ -0,0 +1,381 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufWriter, Read, Seek, Write};
+use std::ops::Range;
+use std::path::Path;
+
+use crate::asset::{AssetResolver, DirResolver, NoAssets};
+use crate::export::{write_mtl, write_object};
+use crate::material::Material;
+use crate::mesh::{
+    Color, ImportOptions, Mesh, MeshError, MeshMut, Triangle, compute_smooth_normals, simplify,
+};
+use log::warn;
+use nalgebra::{Matrix4, Point3, Vector3};
+
+/// A mesh loaded from a Wavefront OBJ file.
+///
+/// Polygons are fan triangulated and flipped from OBJ's counter-clockwise
+/// winding to the engine's clockwise one. Faces after a `usemtl` take the
+/// material's `Kd` color and `d` opacity, and its `Ks` and `Ns` highlight.
+/// Vertex normals come from `vn` where faces reference one, and are
+/// otherwise smoothed from the faces around the vertex.
+#[derive(Debug)]
+pub struct ObjMesh {
+    verts: Vec<Point3<f32>>,
+    tris: Vec<Triangle>,
+    normals: Vec<Vector3<f32>>,
+    materials: Vec<(Range<usize>, Material)>,
+}
+
+/// Why an OBJ file could not be loaded.
+#[derive(Debug)]
+pub enum ObjError {
+    Io(io::Error),
+    Parse {
+        line: usize,
+        message: String,
+    },
+    /// The file parsed but the mesh it describes can't be drawn.
+    Mesh(MeshError),
+}
+
+impl fmt::Display for ObjError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ObjError::Io(err) => write!(f, "{err}"),
+            ObjError::Parse { line, message } => write!(f, "line {line}: {message}"),
+            ObjError::Mesh(err) => write!(f, "{err}"),
+        }
+    }
+}
+
+impl std::error::Error for ObjError {}
+
+impl From<io::Error> for ObjError {
+    fn from(err: io::Error) -> Self {
+        ObjError::Io(err)
+    }
+}
+
+impl ObjMesh {
+    /// Parse an OBJ file from memory. `mtllib` references are ignored; use
+    /// [`ObjMesh::from_bytes_with`] to resolve them.
+    pub fn from_bytes(bytes: &[u8], options: ImportOptions) -> Result<Self, ObjError> {
+        Self::from_bytes_with(bytes, options, &NoAssets)
+    }
+
+    /// Parse an OBJ file from any seekable reader.
+    pub fn from_reader(
+        mut reader: impl Read + Seek,
+        options: ImportOptions,
+    ) -> Result<Self, ObjError> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes)?;
+        Self::from_bytes(&bytes, options)
+    }
+
+    /// Load an OBJ file from disk, resolving `mtllib` next to it.
+    pub fn from_path(path: impl AsRef<Path>, options: ImportOptions) -> Result<Self, ObjError> {
+        let path = path.as_ref();
+        let bytes = std::fs::read(path)?;
+        let dir = path.parent().unwrap_or(Path::new("")).to_path_buf();
+        Self::from_bytes_with(&bytes, options, &DirResolver(dir))
+    }
+
+    /// Parse an OBJ file from memory, loading any `mtllib` it references
+    /// through `resolver`. Missing libraries are logged and skipped.
+    pub fn from_bytes_with(
+        bytes: &[u8],
+        options: ImportOptions,
+        resolver: &dyn AssetResolver,
+    ) -> Result<Self, ObjError> {
+        let text = String::from_utf8_lossy(bytes);
+        let mut verts = Vec::new();
+        let mut tris = Vec::new();
+        let mut file_normals: Vec<Vector3<f32>> = Vec::new();
+        let mut vert_normals: HashMap<usize, Vector3<f32>> = HashMap::new();
+        let mut library: HashMap<String, Material> = HashMap::new();
+        let mut materials: Vec<(Range<usize>, Material)> = Vec::new();
+        let mut color = options.color;
+
+        for (number, line) in text.lines().enumerate() {
+            let line_no = number + 1;
+            let parse_error = |message: String| ObjError::Parse {
+                line: line_no,
+                message,
+            };
+            let mut parts = line.split_whitespace();
+            match parts.next() {
+                Some("v") => {
+                    let coords: Vec<f32> = parts
+                        .take(3)
+                        .map(|p| p.parse::<f32>())
+                        .collect::<Result<_, _>>()
+                        .map_err(|err| parse_error(format!("bad vertex: {err}")))?;
+                    if coords.len() != 3 {
+                        return Err(parse_error("vertex needs 3 coordinates".to_string()));
+                    }
+                    verts.push(Point3::new(coords[0], coords[1], coords[2]));
+                }
+                Some("vn") => {
+                    let coords: Vec<f32> = parts
+                        .take(3)
+                        .map(|p| p.parse::<f32>())
+                        .collect::<Result<_, _>>()
+                        .map_err(|err| parse_error(format!("bad normal: {err}")))?;
+                    if coords.len() != 3 {
+                        return Err(parse_error("normal needs 3 coordinates".to_string()));
+                    }
+                    file_normals.push(Vector3::new(coords[0], coords[1], coords[2]));
+                }
+                Some("f") => {
+                    let tokens: Vec<&str> = parts.collect();
+                    let indices: Vec<usize> = tokens
+                        .iter()
+                        .map(|p| resolve_index(p, verts.len()))
+                        .collect::<Result<_, _>>()
+                        .map_err(parse_error)?;
+                    for (token, &index) in tokens.iter().zip(&indices) {
+                        if let Some(normal) =
+                            resolve_normal(token, file_normals.len()).map_err(parse_error)?
+                        {
+                            vert_normals
+                                .entry(index)
+                                .or_insert(file_normals[normal].normalize());
+                        }
+                    }
+                    if indices.len() < 3 {
+                        return Err(parse_error("face needs at least 3 vertices".to_string()));
+                    }
+                    for i in 1..indices.len() - 1 {
+                        tris.push(Triangle {
+                            v1: indices[0],
+                            v2: indices[i + 1],
+                            v3: indices[i],
+                            color,
+                        });
+                    }
+                }
+                Some("mtllib") => {
+                    for name in parts {
+                        match resolver.load(name) {
+                            Ok(mtl) => library.extend(parse_mtl(&mtl)),
+                            Err(err) => warn!("could not load material library {name}: {err}"),
+                        }
+                    }
+                }
+                Some("usemtl") => {
+                    let name = parts.next().unwrap_or_default();
+                    let material = match library.get(name) {
+                        Some(material) => *material,
+                        None => {
+                            warn!("unknown material {name}, using the default color");
+                            Material::new(options.color)
+                        }
+                    };
+                    color = material.color;
+                    if let Some((range, _)) = materials.last_mut() {
+                        range.end = tris.len();
+                    }
+                    materials.push((tris.len()..tris.len(), material));
+                }
+                _ => {}
+            }
+        }
+
+        if let Some((range, _)) = materials.last_mut() {
+            range.end = tris.len();
+        }
+        materials.retain(|(range, _)| !range.is_empty());
+
+        let mut normals = compute_smooth_normals(&verts, &tris);
+        for (index, normal) in vert_normals {
+            normals[index] = normal;
+        }
+
+        let mut mesh = Self {
+            verts,
+            tris,
+            normals,
+            materials,
+        };
+        mesh.validate().map_err(ObjError::Mesh)?;
+        let degenerate = mesh.degenerate_triangles();
+        if degenerate > 0 {
+            warn!("OBJ has {degenerate} degenerate faces");
+        }
+        if let Some(target) = options.simplify
+            && mesh.tris.len() > target
+        {
+            mesh.simplify(target);
+        }
+        if let Some(extent) = options.normalize {
+            mesh.center_to_origin();
+            mesh.normalize_scale(extent);
+        }
+        Ok(mesh)
+    }
+
+    /// Replace the geometry with [`simplify`]'s, keeping each surviving
+    /// triangle's material. Normals are smoothed again from the new faces.
+    fn simplify(&mut self, target_tris: usize) {
+        let simplified = simplify(self, target_tris);
+        let mut materials: Vec<(Range<usize>, Material)> = Vec::new();
+        for (index, &source) in simplified.sources().iter().enumerate() {
+            let Some(material) = self.material(source).copied() else {
+                continue;
+            };
+            match materials.last_mut() {
+                Some((range, last)) if range.end == index && *last == material => range.end += 1,
+                _ => materials.push((index..index + 1, material)),
+            }
+        }
+        self.verts = simplified.verts().to_vec();
+        self.tris = simplified.tris().clone();
+        self.normals = compute_smooth_normals(&self.verts, &self.tris);
+        self.materials = materials;
+    }
+
+    /// One unit normal per vertex, pointing out of the front faces.
+    pub fn normals(&self) -> &[Vector3<f32>] {
+        &self.normals
+    }
+
+    /// The triangle ranges drawn with each `usemtl` material, in file order.
+    /// Triangles before the first `usemtl` have none.
+    pub fn materials(&self) -> &[(Range<usize>, Material)] {
+        &self.materials
+    }
+}
+
+/// Write `mesh` to the OBJ file at `path` as one object named after the
+/// file, with a material library beside it, named like it with an `.mtl`
+/// extension, holding a material for each triangle color.
+pub fn save_obj(mesh: &dyn Mesh, path: impl AsRef<Path>) -> io::Result<()> {
+    let path = path.as_ref();
+    let mtl_path = path.with_extension("mtl");
+    let name = path
+        .file_stem()
+        .map_or_else(|| "mesh".into(), |stem| stem.to_string_lossy());
+    let mtl_name = mtl_path
+        .file_name()
+        .map_or_else(|| "mesh.mtl".into(), |name| name.to_string_lossy());
+    let mut out = BufWriter::new(File::create(path)?);
+    writeln!(out, "mtllib {mtl_name}")?;
+    let mut colors = Vec::new();
+    let identity = Matrix4::identity();
+    write_object(&mut out, &name, mesh, &identity, None, 1, Some(&mut colors))?;
+    out.flush()?;
+    write_mtl(&mtl_path, &colors)
+}
+
+/// Turn a face token (`7`, `7/1`, `7//3`, `-1/...`) into a zero based index.
+fn resolve_index(token: &str, vert_count: usize) -> Result<usize, String> {
+    let raw = token.split('/').next().unwrap_or_default();
+    let index: i64 = raw
+        .parse()
+        .map_err(|_| format!("bad face index {token:?}"))?;
+    let resolved = if index < 0 {
+        vert_count as i64 + index
+    } else {
+        index - 1
+    };
+    if resolved < 0 || resolved >= vert_count as i64 {
+        return Err(format!("face index {index} out of range"));
+    }
+    Ok(resolved as usize)
+}
+
+/// The normal index of a face token (`7//3`, `7/1/-1`), if it has one.
+fn resolve_normal(token: &str, normal_count: usize) -> Result<Option<usize>, String> {
+    let Some(raw) = token.split('/').nth(2).filter(|raw| !raw.is_empty()) else {
+        return Ok(None);
+    };
+    let index: i64 = raw
+        .parse()
+        .map_err(|_| format!("bad normal index {token:?}"))?;
+    let resolved = if index < 0 {
+        normal_count as i64 + index
+    } else {
+        index - 1
+    };
+    if resolved < 0 || resolved >= normal_count as i64 {
+        return Err(format!("normal index {index} out of range"));
+    }
+    Ok(Some(resolved as usize))
+}
+
+/// Read every material in an MTL file. `Kd` becomes the color, `d` (or
+/// `1 - Tr`) its alpha, the mean of `Ks` the specular strength and `Ns` the
+/// shininess, and `illum 0` (color without lighting) makes it unlit. `Ka` is
+/// skipped: the scene has a single ambient term.
+fn parse_mtl(bytes: &[u8]) -> HashMap<String, Material> {
+    let text = String::from_utf8_lossy(bytes);
+    let mut materials = HashMap::new();
+    let mut current: Option<String> = None;
+    let channel = |c: f32| (c.clamp(0.0, 1.0) * 255.0).round() as u8;
+    for line in text.lines() {
+        let mut parts = line.split_whitespace();
+        let keyword = parts.next();
+        if keyword == Some("newmtl") {
+            current = parts.next().map(str::to_string);
+            if let Some(name) = &current {
+                materials.insert(name.clone(), Material::default());
+            }
+            continue;
+        }
+        let Some(material) = current.as_ref().and_then(|name| materials.get_mut(name)) else {
+            continue;
+        };
+        let values: Vec<f32> = parts.filter_map(|p| p.parse().ok()).collect();
+        match (keyword, values.as_slice()) {
+            (Some("Kd"), [r, g, b, ..]) => {
+                material.color = Color {
+                    r: channel(*r),
+                    g: channel(*g),
+                    b: channel(*b),
+                    a: material.color.a,
+                };
+            }
+            (Some("Ks"), [r, g, b, ..]) => material.specular = (r + g + b) / 3.0,
+            (Some("Ns"), [ns, ..]) => material.shininess = *ns,
+            (Some("d"), [d, ..]) => material.color.a = channel(*d),
+            (Some("Tr"), [tr, ..]) => material.color.a = channel(1.0 - tr),
+            (Some("illum"), [illum, ..]) => material.unlit = *illum == 0.0,
+            _ => {}
+        }
+    }
+    materials
+}
+
+impl Mesh for ObjMesh {
+    fn tris(&self) -> &Vec<Triangle> {
+        &self.tris
+    }
+
+    fn verts(&self) -> &[Point3<f32>] {
+        &self.verts
+    }
+
+    fn material(&self, index: usize) -> Option<&Material> {
+        let i = self
+            .materials
+            .partition_point(|(range, _)| range.end <= index);
+        self.materials
+            .get(i)
+            .filter(|(range, _)| range.contains(&index))
+            .map(|(_, material)| material)
+    }
+}
+
+impl MeshMut for ObjMesh {
+    fn tris_mut(&mut self) -> &mut Vec<Triangle> {
+        &mut self.tris
+    }
+
+    fn verts_mut(&mut self) -> &mut [Point3<f32>] {
+        &mut self.verts
+    }
+}
@@ -1,6 +1,14 @@
-use crate::mesh::{Color, Mesh, Triangle};
+use crate::mesh::{Color, Mesh, MeshMut, Triangle};
 use nalgebra::Point3;
 
+const YELLOW: Color = Color::from_hex(0xFFF836);
+
+const PURPLE: Color = Color::from_hex(0xAB36FF);
+
+/// Triangles of the front face; the rest are the sides and back.
+const FACE_TRIS: usize = 12;
+
+/// The purple hack logo, extruded.
 #[derive(Debug)]
 pub struct PHackMesh {
     verts: Vec<Point3<f32>>,
@@ -8,20 +16,35 @@ pub struct PHackMesh {
 }
 
 impl PHackMesh {
+    /// The logo in its own colors: a yellow face on a purple body, 3 units
+    /// across.
     pub fn new() -> Self {
-        const YELLOW: Color = Color {
-            r: 255,
-            g: 248,
-            b: 54,
-            a: 255,
-        };
-        const PURPLE: Color = Color {
-            r: 171,
-            g: 54,
-            b: 255,
-            a: 255,
-        };
-        Self {
+        Self::build(1.0, YELLOW, PURPLE)
+    }
+
+    /// The logo scaled by `scale` about its middle, all in `color`.
+    pub fn new_with(scale: f32, color: Color) -> Self {
+        Self::build(scale, color, color)
+    }
+
+    /// Recolor the front `face` and the rest of the `body`.
+    pub fn with_colors(mut self, face: Color, body: Color) -> Self {
+        for (i, tri) in self.tris.iter_mut().enumerate() {
+            tri.color = if i < FACE_TRIS { face } else { body };
+        }
+        self
+    }
+
+    pub fn triangle_count(&self) -> usize {
+        self.tris.len()
+    }
+
+    pub fn vertex_count(&self) -> usize {
+        self.verts.len()
+    }
+
+    fn build(scale: f32, face: Color, body: Color) -> Self {
+        let mut mesh = Self {
             verts: vec![
                 Point3::new(-0.5, 1.5, -0.5),
                 Point3::new(0.5, 1.5, -0.5),
@@ -51,220 +74,233 @@ impl PHackMesh {
                     v1: 0,
                     v2: 4,
                     v3: 1,
-                    color: YELLOW,
+                    color: face,
                 },
                 Triangle {
                     v1: 4,
                     v2: 0,
                     v3: 2,
-                    color: YELLOW,
+                    color: face,
                 },
                 Triangle {
                     v1: 3,
                     v2: 6,
                     v3: 5,
-                    color: YELLOW,
+                    color: face,
                 },
                 Triangle {
                     v1: 6,
                     v2: 3,
                     v3: 7,
-                    color: YELLOW,
+                    color: face,
                 },
                 Triangle {
                     v1: 9,
                     v2: 2,
                     v3: 8,
-                    color: YELLOW,
+                    color: face,
                 },
                 Triangle {
                     v1: 2,
                     v2: 9,
                     v3: 10,
-                    color: YELLOW,
+                    color: face,
                 },
                 Triangle {
                     v1: 11,
                     v2: 12,
                     v3: 15,
-                    color: YELLOW,
+                    color: face,
                 },
                 Triangle {
                     v1: 15,
                     v2: 13,
                     v3: 11,
-                    color: YELLOW,
+                    color: face,
                 },
                 Triangle {
                     v1: 14,
                     v2: 16,
                     v3: 17,
-                    color: YELLOW,
+                    color: face,
                 },
                 Triangle {
                     v1: 17,
                     v2: 18,
                     v3: 14,
-                    color: YELLOW,
+                    color: face,
                 },
                 Triangle {
                     v1: 20,
                     v2: 19,
                     v3: 13,
-                    color: YELLOW,
+                    color: face,
                 },
                 Triangle {
                     v1: 20,
                     v2: 13,
                     v3: 21,
-                    color: YELLOW,
+                    color: face,
                 },
                 Triangle {
                     v1: 13,
                     v2: 2,
                     v3: 0,
-                    color: PURPLE,
+                    color: body,
                 },
                 Triangle {
                     v1: 0,
                     v2: 11,
                     v3: 13,
-                    color: PURPLE,
+                    color: body,
                 },
                 Triangle {
                     v1: 20,
                     v2: 9,
                     v3: 8,
-                    color: PURPLE,
+                    color: body,
                 },
                 Triangle {
                     v1: 8,
                     v2: 19,
                     v3: 20,
-                    color: PURPLE,
+                    color: body,
                 },
                 Triangle {
                     v1: 18,
                     v2: 7,
                     v3: 4,
-                    color: PURPLE,
+                    color: body,
                 },
                 Triangle {
                     v1: 4,
                     v2: 15,
                     v3: 18,
-                    color: PURPLE,
+                    color: body,
                 },
                 Triangle {
                     v1: 1,
                     v2: 3,
                     v3: 12,
-                    color: PURPLE,
+                    color: body,
                 },
                 Triangle {
                     v1: 14,
                     v2: 12,
                     v3: 3,
-                    color: PURPLE,
+                    color: body,
                 },
                 Triangle {
                     v1: 5,
                     v2: 6,
                     v3: 16,
-                    color: PURPLE,
+                    color: body,
                 },
                 Triangle {
                     v1: 17,
                     v2: 16,
                     v3: 6,
-                    color: PURPLE,
+                    color: body,
                 },
                 Triangle {
                     v1: 2,
                     v2: 10,
                     v3: 13,
-                    color: PURPLE,
+                    color: body,
                 },
                 Triangle {
                     v1: 21,
                     v2: 13,
                     v3: 10,
-                    color: PURPLE,
+                    color: body,
                 },
                 Triangle {
                     v1: 0,
                     v2: 1,
                     v3: 12,
-                    color: PURPLE,
+                    color: body,
                 },
                 Triangle {
                     v1: 12,
                     v2: 11,
                     v3: 0,
-                    color: PURPLE,
+                    color: body,
                 },
                 Triangle {
                     v1: 8,
                     v2: 2,
                     v3: 13,
-                    color: PURPLE,
+                    color: body,
                 },
                 Triangle {
                     v1: 13,
                     v2: 19,
                     v3: 8,
-                    color: PURPLE,
+                    color: body,
                 },
                 Triangle {
                     v1: 3,
                     v2: 5,
                     v3: 16,
-                    color: PURPLE,
+                    color: body,
                 },
                 Triangle {
                     v1: 16,
                     v2: 14,
                     v3: 3,
-                    color: PURPLE,
+                    color: body,
                 },
                 Triangle {
                     v1: 10,
                     v2: 9,
                     v3: 21,
-                    color: PURPLE,
+                    color: body,
                 },
                 Triangle {
                     v1: 20,
                     v2: 21,
                     v3: 9,
-                    color: PURPLE,
+                    color: body,
                 },
                 Triangle {
                     v1: 4,
                     v2: 2,
                     v3: 15,
-                    color: PURPLE,
+                    color: body,
                 },
                 Triangle {
                     v1: 13,
                     v2: 15,
                     v3: 2,
-                    color: PURPLE,
+                    color: body,
                 },
                 Triangle {
                     v1: 6,
                     v2: 7,
                     v3: 17,
-                    color: PURPLE,
+                    color: body,
                 },
                 Triangle {
                     v1: 18,
                     v2: 17,
                     v3: 7,
-                    color: PURPLE,
+                    color: body,
                 },
             ],
+        };
+        for v in &mut mesh.verts {
+            *v *= scale;
         }
+        if let Err(err) = mesh.validate() {
+            debug_assert!(false, "PHackMesh: {err}");
+        }
+        mesh
+    }
+}
+
+impl Default for PHackMesh {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
@@ -277,3 +313,13 @@ impl Mesh for PHackMesh {
         &self.verts
     }
 }
+
+impl MeshMut for PHackMesh {
+    fn tris_mut(&mut self) -> &mut Vec<Triangle> {
+        &mut self.tris
+    }
+
+    fn verts_mut(&mut self) -> &mut [Point3<f32>] {
+        &mut self.verts
+    }
+}
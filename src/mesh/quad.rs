@@ -0,0 +1,116 @@
+use crate::mesh::{Color, Mesh, MeshMut, Triangle};
+use nalgebra::{Point3, Vector2};
+
+/// A flat rectangle in the XY plane, centered on the origin and facing -Z.
+/// Its texture coordinates run over the whole rectangle once, from the top
+/// left corner as seen from the front.
+#[derive(Debug)]
+pub struct QuadMesh {
+    verts: Vec<Point3<f32>>,
+    tris: Vec<Triangle>,
+    uvs: Vec<Vector2<f32>>,
+}
+
+impl QuadMesh {
+    /// The rectangle split into `divisions * divisions` cells, for effects
+    /// like shadows that are worked out once per triangle.
+    pub fn grid(width: f32, height: f32, divisions: usize, color: Color) -> Self {
+        let n = divisions.max(1);
+        let mut verts = Vec::with_capacity((n + 1) * (n + 1));
+        for row in 0..=n {
+            for col in 0..=n {
+                verts.push(Point3::new(
+                    width * (col as f32 / n as f32 - 0.5),
+                    height * (row as f32 / n as f32 - 0.5),
+                    0.0,
+                ));
+            }
+        }
+        let mut tris = Vec::with_capacity(2 * n * n);
+        for row in 0..n {
+            for col in 0..n {
+                let corner = row * (n + 1) + col;
+                let (a, b, c, d) = (corner, corner + 1, corner + n + 2, corner + n + 1);
+                tris.push(Triangle {
+                    v1: a,
+                    v2: b,
+                    v3: c,
+                    color,
+                });
+                tris.push(Triangle {
+                    v1: a,
+                    v2: c,
+                    v3: d,
+                    color,
+                });
+            }
+        }
+        let uvs = uvs(&verts, width, height);
+        Self { verts, tris, uvs }
+    }
+
+    pub fn new(width: f32, height: f32, color: Color) -> Self {
+        let (hw, hh) = (width / 2.0, height / 2.0);
+        let verts = vec![
+            Point3::new(-hw, -hh, 0.0),
+            Point3::new(hw, -hh, 0.0),
+            Point3::new(hw, hh, 0.0),
+            Point3::new(-hw, hh, 0.0),
+        ];
+        Self {
+            uvs: uvs(&verts, width, height),
+            verts,
+            tris: vec![
+                Triangle {
+                    v1: 0,
+                    v2: 1,
+                    v3: 2,
+                    color,
+                },
+                Triangle {
+                    v1: 0,
+                    v2: 2,
+                    v3: 3,
+                    color,
+                },
+            ],
+        }
+    }
+}
+
+/// Where each of `verts` lies on a `width` by `height` rectangle seen from
+/// -Z, from 0 at its top left corner to 1 at its bottom right.
+fn uvs(verts: &[Point3<f32>], width: f32, height: f32) -> Vec<Vector2<f32>> {
+    let (width, height) = (
+        width.abs().max(f32::EPSILON),
+        height.abs().max(f32::EPSILON),
+    );
+    verts
+        .iter()
+        .map(|v| Vector2::new(0.5 - v.x / width, 0.5 - v.y / height))
+        .collect()
+}
+
+impl Mesh for QuadMesh {
+    fn tris(&self) -> &Vec<Triangle> {
+        &self.tris
+    }
+
+    fn verts(&self) -> &[Point3<f32>] {
+        &self.verts
+    }
+
+    fn uvs(&self) -> Option<&[Vector2<f32>]> {
+        Some(&self.uvs)
+    }
+}
+
+impl MeshMut for QuadMesh {
+    fn tris_mut(&mut self) -> &mut Vec<Triangle> {
+        &mut self.tris
+    }
+
+    fn verts_mut(&mut self) -> &mut [Point3<f32>] {
+        &mut self.verts
+    }
+}
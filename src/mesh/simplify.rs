@@ -0,0 +1,478 @@
+//! Cutting a mesh down to fewer triangles by collapsing edges, cheapest
+//! first by the quadric error metric of Garland and Heckbert.
+
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+
+use nalgebra::{Matrix3, Point3, Vector3};
+use ordered_float::OrderedFloat;
+
+use crate::mesh::{Color, Mesh, MeshMut, Triangle};
+
+/// The output of [`simplify`].
+#[derive(Debug)]
+pub struct SimplifiedMesh {
+    verts: Vec<Point3<f32>>,
+    tris: Vec<Triangle>,
+    sources: Vec<usize>,
+}
+
+impl SimplifiedMesh {
+    /// For each triangle, the index among the input mesh's triangles of the
+    /// largest one merged into it, whose color it has. Triangles keep their
+    /// input order.
+    pub fn sources(&self) -> &[usize] {
+        &self.sources
+    }
+}
+
+/// Collapse edges of `mesh` until it has at most `target_tris` triangles,
+/// or no edge is left that can go without tearing or folding it.
+///
+/// Each collapse merges two vertices into one at the point that least
+/// changes the surface around them, measured as the squared distance to
+/// the planes of their original triangles, weighted by area. Vertices on
+/// an open border or on an edge shared by more than two triangles are
+/// never moved, so borders and seams keep their outline. A collapse that
+/// would turn a triangle over, or join two surfaces at a vertex, is
+/// skipped.
+///
+/// The triangles that survive keep the color of the largest input
+/// triangle merged into them. Triangles using a vertex twice are dropped
+/// from the input, so the result has none, and vertices no triangle uses
+/// any more are removed.
+pub fn simplify(mesh: &dyn Mesh, target_tris: usize) -> SimplifiedMesh {
+    let mut collapser = Collapser::new(mesh);
+    collapser.run(target_tris);
+    collapser.finish()
+}
+
+/// A triangle of the mesh being simplified.
+struct Face {
+    v: [usize; 3],
+    color: Color,
+    /// Area and index of the largest input triangle merged into this one,
+    /// whose color it has.
+    dominant: f64,
+    source: usize,
+    alive: bool,
+}
+
+impl Face {
+    fn contains(&self, v: usize) -> bool {
+        self.v.contains(&v)
+    }
+}
+
+/// The sum of squared distances to a set of planes, as the symmetric 4x4
+/// matrix `[a b c d]^T [a b c d]` for each plane, upper triangle only.
+#[derive(Debug, Clone, Copy, Default)]
+struct Quadric([f64; 10]);
+
+impl Quadric {
+    /// The plane through `point` facing along the unit `normal`, weighted.
+    fn plane(normal: Vector3<f64>, point: Vector3<f64>, weight: f64) -> Self {
+        let (a, b, c) = (normal.x, normal.y, normal.z);
+        let d = -normal.dot(&point);
+        Self(
+            [
+                a * a,
+                a * b,
+                a * c,
+                a * d,
+                b * b,
+                b * c,
+                b * d,
+                c * c,
+                c * d,
+                d * d,
+            ]
+            .map(|q| q * weight),
+        )
+    }
+
+    fn add(&self, other: &Quadric) -> Quadric {
+        Quadric(std::array::from_fn(|i| self.0[i] + other.0[i]))
+    }
+
+    fn error(&self, p: &Vector3<f64>) -> f64 {
+        let [aa, ab, ac, ad, bb, bc, bd, cc, cd, dd] = self.0;
+        let (x, y, z) = (p.x, p.y, p.z);
+        aa * x * x
+            + 2.0 * ab * x * y
+            + 2.0 * ac * x * z
+            + 2.0 * ad * x
+            + bb * y * y
+            + 2.0 * bc * y * z
+            + 2.0 * bd * y
+            + cc * z * z
+            + 2.0 * cd * z
+            + dd
+    }
+
+    /// The point of least error, unless the planes leave it free along a
+    /// line or plane.
+    fn optimum(&self) -> Option<Vector3<f64>> {
+        let [aa, ab, ac, ad, bb, bc, bd, cc, cd, _] = self.0;
+        let a = Matrix3::new(aa, ab, ac, ab, bb, bc, ac, bc, cc);
+        // Nearly flat neighbourhoods make a nearly singular matrix, whose
+        // solution is noise far away from the edge.
+        if a.determinant().abs() < 1e-12 * a.norm_squared().powf(1.5).max(f64::MIN_POSITIVE) {
+            return None;
+        }
+        a.try_inverse()
+            .map(|inverse| -(inverse * Vector3::new(ad, bd, cd)))
+    }
+}
+
+/// Which vertex of an edge goes, which stays and where that one ends up.
+struct Collapse {
+    remove: usize,
+    keep: usize,
+    position: Vector3<f64>,
+    cost: f64,
+}
+
+/// A queued collapse: its cost, the vertex to remove, the vertex to keep,
+/// and both vertices' versions when it was worked out.
+type Candidate = (OrderedFloat<f64>, usize, usize, u32, u32);
+
+/// The mesh part way through being simplified.
+struct Collapser {
+    verts: Vec<Vector3<f64>>,
+    faces: Vec<Face>,
+    /// Faces using each vertex. Dead faces are dropped lazily.
+    around: Vec<Vec<usize>>,
+    quadrics: Vec<Quadric>,
+    locked: Vec<bool>,
+    removed: Vec<bool>,
+    /// Bumped whenever a vertex changes, so queued collapses computed
+    /// before can be told apart.
+    version: Vec<u32>,
+    queue: BinaryHeap<Reverse<Candidate>>,
+    live: usize,
+}
+
+impl Collapser {
+    fn new(mesh: &dyn Mesh) -> Self {
+        let verts: Vec<Vector3<f64>> = mesh.verts().iter().map(|p| p.coords.cast()).collect();
+        let faces: Vec<Face> = mesh
+            .primitives()
+            .triangles()
+            .enumerate()
+            .filter(|(_, tri)| tri.v1 != tri.v2 && tri.v2 != tri.v3 && tri.v1 != tri.v3)
+            .map(|(source, tri)| {
+                let [a, b, c] = [tri.v1, tri.v2, tri.v3].map(|v| verts[v]);
+                Face {
+                    v: [tri.v1, tri.v2, tri.v3],
+                    color: tri.color,
+                    dominant: (b - a).cross(&(c - a)).norm() / 2.0,
+                    source,
+                    alive: true,
+                }
+            })
+            .collect();
+
+        let mut around = vec![Vec::new(); verts.len()];
+        let mut quadrics = vec![Quadric::default(); verts.len()];
+        let mut edges: HashMap<(usize, usize), u32> = HashMap::new();
+        for (index, face) in faces.iter().enumerate() {
+            let [a, b, c] = face.v.map(|v| verts[v]);
+            let normal = (b - a).cross(&(c - a));
+            let length = normal.norm();
+            let quadric = if length > 0.0 {
+                Quadric::plane(normal / length, a, length / 2.0)
+            } else {
+                Quadric::default()
+            };
+            for (i, &v) in face.v.iter().enumerate() {
+                around[v].push(index);
+                quadrics[v] = quadrics[v].add(&quadric);
+                *edges.entry(edge(v, face.v[(i + 1) % 3])).or_default() += 1;
+            }
+        }
+        // Borders have one face along them and seams more than two.
+        let mut locked = vec![false; verts.len()];
+        for (&(a, b), &count) in &edges {
+            if count != 2 {
+                locked[a] = true;
+                locked[b] = true;
+            }
+        }
+
+        let live = faces.len();
+        let mut collapser = Self {
+            removed: vec![false; verts.len()],
+            version: vec![0; verts.len()],
+            verts,
+            faces,
+            around,
+            quadrics,
+            locked,
+            queue: BinaryHeap::new(),
+            live,
+        };
+        // Sorted so ties between equal costs don't depend on hash order.
+        let mut edges: Vec<(usize, usize)> = edges.into_keys().collect();
+        edges.sort_unstable();
+        for (a, b) in edges {
+            collapser.push(a, b);
+        }
+        collapser
+    }
+
+    /// The best way to collapse the edge `a b`, if it can be collapsed.
+    fn candidate(&self, a: usize, b: usize) -> Option<Collapse> {
+        let quadric = self.quadrics[a].add(&self.quadrics[b]);
+        let (remove, keep, position) = match (self.locked[a], self.locked[b]) {
+            (true, true) => return None,
+            (true, false) => (b, a, self.verts[a]),
+            (false, true) => (a, b, self.verts[b]),
+            (false, false) => {
+                let (pa, pb) = (self.verts[a], self.verts[b]);
+                let middle = (pa + pb) / 2.0;
+                let reach = (pb - pa).norm();
+                let best = quadric
+                    .optimum()
+                    .filter(|p| (p - middle).norm() <= reach)
+                    .unwrap_or_else(|| {
+                        [pa, pb, middle]
+                            .into_iter()
+                            .min_by_key(|p| OrderedFloat(quadric.error(p)))
+                            .unwrap_or(middle)
+                    });
+                (a, b, best)
+            }
+        };
+        Some(Collapse {
+            remove,
+            keep,
+            cost: quadric.error(&position).max(0.0),
+            position,
+        })
+    }
+
+    fn push(&mut self, a: usize, b: usize) {
+        if let Some(collapse) = self.candidate(a, b) {
+            let (remove, keep) = (collapse.remove, collapse.keep);
+            self.queue.push(Reverse((
+                OrderedFloat(collapse.cost),
+                remove,
+                keep,
+                self.version[remove],
+                self.version[keep],
+            )));
+        }
+    }
+
+    fn run(&mut self, target_tris: usize) {
+        while self.live > target_tris {
+            let Some(Reverse((_, remove, keep, remove_version, keep_version))) = self.queue.pop()
+            else {
+                break;
+            };
+            if self.removed[remove]
+                || self.removed[keep]
+                || self.version[remove] != remove_version
+                || self.version[keep] != keep_version
+            {
+                continue;
+            }
+            if let Some(collapse) = self.candidate(remove, keep) {
+                self.collapse(&collapse);
+            }
+        }
+    }
+
+    fn live_around(&self, v: usize) -> impl Iterator<Item = usize> + '_ {
+        self.around[v]
+            .iter()
+            .copied()
+            .filter(|&f| self.faces[f].alive)
+    }
+
+    fn neighbours(&self, v: usize) -> Vec<usize> {
+        let mut neighbours: Vec<usize> = self
+            .live_around(v)
+            .flat_map(|f| self.faces[f].v)
+            .filter(|&n| n != v)
+            .collect();
+        neighbours.sort_unstable();
+        neighbours.dedup();
+        neighbours
+    }
+
+    /// Merge `remove` into `keep` at the collapse's position, unless that
+    /// would tear or fold the surface.
+    fn collapse(&mut self, collapse: &Collapse) {
+        let Collapse {
+            remove,
+            keep,
+            position,
+            ..
+        } = *collapse;
+        let shared: Vec<usize> = self
+            .live_around(remove)
+            .filter(|&f| self.faces[f].contains(keep))
+            .collect();
+        if shared.is_empty() {
+            return;
+        }
+
+        // The two vertices may only have in common the corners facing
+        // their edge; any other would be pinched into a single edge.
+        let opposite: Vec<usize> = shared
+            .iter()
+            .flat_map(|&f| self.faces[f].v)
+            .filter(|&v| v != remove && v != keep)
+            .collect();
+        let (ours, theirs) = (self.neighbours(remove), self.neighbours(keep));
+        let common: Vec<usize> = ours
+            .iter()
+            .copied()
+            .filter(|v| theirs.binary_search(v).is_ok())
+            .collect();
+        if common.len() != opposite.len() || common.iter().any(|v| !opposite.contains(v)) {
+            return;
+        }
+        // Nor may a closed piece shrink past a tetrahedron, whose faces
+        // would fold onto each other.
+        if ours.len() + theirs.len() - common.len() < 5 {
+            return;
+        }
+
+        // No triangle left may turn over or shrink to nothing.
+        let moved = |v: usize| {
+            if v == remove || v == keep {
+                position
+            } else {
+                self.verts[v]
+            }
+        };
+        let folds = self
+            .live_around(remove)
+            .chain(self.live_around(keep))
+            .filter(|f| !shared.contains(f))
+            .any(|f| {
+                let [a, b, c] = self.faces[f].v;
+                let before =
+                    (self.verts[b] - self.verts[a]).cross(&(self.verts[c] - self.verts[a]));
+                let after = (moved(b) - moved(a)).cross(&(moved(c) - moved(a)));
+                after.dot(&before) <= 0.0 || after.norm_squared() <= f64::MIN_POSITIVE
+            });
+        if folds {
+            return;
+        }
+
+        for &dead in &shared {
+            self.faces[dead].alive = false;
+            self.live -= 1;
+            // Across the edge to the corner opposite, the face that ends
+            // up next to where this one was takes its color if it came
+            // from a larger triangle.
+            let Some(&corner) = self.faces[dead]
+                .v
+                .iter()
+                .find(|&&v| v != remove && v != keep)
+            else {
+                continue;
+            };
+            let heir = self
+                .live_around(remove)
+                .find(|&f| self.faces[f].contains(corner));
+            if let Some(heir) = heir
+                && self.faces[dead].dominant > self.faces[heir].dominant
+            {
+                self.faces[heir].color = self.faces[dead].color;
+                self.faces[heir].dominant = self.faces[dead].dominant;
+                self.faces[heir].source = self.faces[dead].source;
+            }
+        }
+        let moving: Vec<usize> = self.live_around(remove).collect();
+        for f in moving {
+            for v in &mut self.faces[f].v {
+                if *v == remove {
+                    *v = keep;
+                }
+            }
+            self.around[keep].push(f);
+        }
+        let faces = &self.faces;
+        self.around[keep].retain(|&f| faces[f].alive);
+        self.around[keep].sort_unstable();
+        self.around[keep].dedup();
+        self.around[remove].clear();
+
+        self.verts[keep] = position;
+        self.quadrics[keep] = self.quadrics[keep].add(&self.quadrics[remove]);
+        self.removed[remove] = true;
+        self.version[remove] += 1;
+        self.version[keep] += 1;
+        for neighbour in self.neighbours(keep) {
+            self.push(keep, neighbour);
+        }
+    }
+
+    fn finish(self) -> SimplifiedMesh {
+        let mut remap = vec![usize::MAX; self.verts.len()];
+        let mut used = vec![false; self.verts.len()];
+        for face in self.faces.iter().filter(|face| face.alive) {
+            for v in face.v {
+                used[v] = true;
+            }
+        }
+        let mut verts = Vec::new();
+        for (v, position) in self.verts.iter().enumerate() {
+            if used[v] {
+                remap[v] = verts.len();
+                verts.push(Point3::from(position.cast::<f32>()));
+            }
+        }
+        let (tris, sources) = self
+            .faces
+            .iter()
+            .filter(|face| face.alive)
+            .map(|face| {
+                let [v1, v2, v3] = face.v.map(|v| remap[v]);
+                (
+                    Triangle {
+                        v1,
+                        v2,
+                        v3,
+                        color: face.color,
+                    },
+                    face.source,
+                )
+            })
+            .unzip();
+        SimplifiedMesh {
+            verts,
+            tris,
+            sources,
+        }
+    }
+}
+
+fn edge(a: usize, b: usize) -> (usize, usize) {
+    (a.min(b), a.max(b))
+}
+
+impl Mesh for SimplifiedMesh {
+    fn tris(&self) -> &Vec<Triangle> {
+        &self.tris
+    }
+
+    fn verts(&self) -> &[Point3<f32>] {
+        &self.verts
+    }
+}
+
+impl MeshMut for SimplifiedMesh {
+    fn tris_mut(&mut self) -> &mut Vec<Triangle> {
+        &mut self.tris
+    }
+
+    fn verts_mut(&mut self) -> &mut [Point3<f32>] {
+        &mut self.verts
+    }
+}
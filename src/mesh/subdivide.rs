@@ -0,0 +1,244 @@
+//! Splitting every triangle into four, optionally smoothing the result.
+
+use std::collections::HashMap;
+use std::fmt;
+
+use nalgebra::{Point3, Vector3};
+
+use crate::mesh::{Mesh, MeshMut, Triangle};
+
+/// The output of [`subdivide`] or [`Subdivision::apply`].
+#[derive(Debug)]
+pub struct SubdividedMesh {
+    verts: Vec<Point3<f32>>,
+    tris: Vec<Triangle>,
+}
+
+/// Why a mesh was not subdivided.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SubdivideError {
+    /// `levels` would make `triangles` triangles, more than `budget`.
+    OverBudget {
+        levels: u32,
+        triangles: usize,
+        budget: usize,
+    },
+}
+
+impl fmt::Display for SubdivideError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SubdivideError::OverBudget {
+                levels,
+                triangles,
+                budget,
+            } => write!(
+                f,
+                "{levels} levels make {triangles} triangles, over the budget of {budget}"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for SubdivideError {}
+
+/// Settings for subdividing a mesh.
+#[derive(Debug, Clone, Copy)]
+pub struct Subdivision {
+    /// How many times every triangle is split into four.
+    pub levels: u32,
+    /// Move the vertices with Loop's rules after each split, rounding the
+    /// shape off instead of keeping its flat faces.
+    pub smooth: bool,
+    /// Most triangles the result may have.
+    pub max_triangles: usize,
+}
+
+impl Default for Subdivision {
+    fn default() -> Self {
+        Self {
+            levels: 1,
+            smooth: false,
+            max_triangles: 1 << 20,
+        }
+    }
+}
+
+impl Subdivision {
+    pub fn levels(mut self, levels: u32) -> Self {
+        self.levels = levels;
+        self
+    }
+
+    pub fn smooth(mut self, smooth: bool) -> Self {
+        self.smooth = smooth;
+        self
+    }
+
+    pub fn max_triangles(mut self, max_triangles: usize) -> Self {
+        self.max_triangles = max_triangles;
+        self
+    }
+
+    /// Subdivide `mesh`. Each new triangle keeps the color of the triangle
+    /// it was cut from, and edges shared by two triangles share their new
+    /// vertex so closed meshes stay closed.
+    pub fn apply(&self, mesh: &dyn Mesh) -> Result<SubdividedMesh, SubdivideError> {
+        let triangles = 4usize
+            .checked_pow(self.levels)
+            .and_then(|factor| factor.checked_mul(mesh.primitives().triangles().count()))
+            .unwrap_or(usize::MAX);
+        if triangles > self.max_triangles {
+            return Err(SubdivideError::OverBudget {
+                levels: self.levels,
+                triangles,
+                budget: self.max_triangles,
+            });
+        }
+        let mut verts = mesh.verts().to_vec();
+        let mut tris: Vec<Triangle> = mesh.primitives().triangles().collect();
+        for _ in 0..self.levels {
+            (verts, tris) = split(&verts, &tris, self.smooth);
+        }
+        Ok(SubdividedMesh { verts, tris })
+    }
+}
+
+/// Midpoint subdivide `mesh` `levels` times, see [`Subdivision`].
+pub fn subdivide(mesh: &dyn Mesh, levels: u32) -> Result<SubdividedMesh, SubdivideError> {
+    Subdivision::default().levels(levels).apply(mesh)
+}
+
+fn edge(a: usize, b: usize) -> (usize, usize) {
+    (a.min(b), a.max(b))
+}
+
+/// One level: every triangle into four, with new vertices at the edges.
+fn split(
+    verts: &[Point3<f32>],
+    tris: &[Triangle],
+    smooth: bool,
+) -> (Vec<Point3<f32>>, Vec<Triangle>) {
+    // The corners facing each edge, one per triangle using it.
+    let mut opposite: HashMap<(usize, usize), Vec<usize>> = HashMap::new();
+    for tri in tris {
+        for (a, b, c) in [
+            (tri.v1, tri.v2, tri.v3),
+            (tri.v2, tri.v3, tri.v1),
+            (tri.v3, tri.v1, tri.v2),
+        ] {
+            opposite.entry(edge(a, b)).or_default().push(c);
+        }
+    }
+
+    let mut new_verts = if smooth {
+        smoothed_corners(verts, &opposite)
+    } else {
+        verts.to_vec()
+    };
+    let mut midpoints: HashMap<(usize, usize), usize> = HashMap::new();
+    let mut midpoint = |a: usize, b: usize| -> usize {
+        *midpoints.entry(edge(a, b)).or_insert_with(|| {
+            let (pa, pb) = (verts[a].coords, verts[b].coords);
+            let position = match opposite[&edge(a, b)].as_slice() {
+                [c, d] if smooth => {
+                    (pa + pb) * 0.375 + (verts[*c].coords + verts[*d].coords) * 0.125
+                }
+                _ => (pa + pb) * 0.5,
+            };
+            new_verts.push(Point3::from(position));
+            new_verts.len() - 1
+        })
+    };
+
+    let mut new_tris = Vec::with_capacity(tris.len() * 4);
+    for tri in tris {
+        let (a, b, c) = (tri.v1, tri.v2, tri.v3);
+        let (ab, bc, ca) = (midpoint(a, b), midpoint(b, c), midpoint(c, a));
+        for (v1, v2, v3) in [(a, ab, ca), (ab, b, bc), (ca, bc, c), (ab, bc, ca)] {
+            new_tris.push(Triangle {
+                v1,
+                v2,
+                v3,
+                color: tri.color,
+            });
+        }
+    }
+    (new_verts, new_tris)
+}
+
+/// Loop's rule for the existing vertices: a weighted average with their
+/// neighbours, or with just the two neighbours along an open border.
+/// Vertices on edges shared by more than two triangles are left alone.
+fn smoothed_corners(
+    verts: &[Point3<f32>],
+    opposite: &HashMap<(usize, usize), Vec<usize>>,
+) -> Vec<Point3<f32>> {
+    let mut neighbours: Vec<Vec<usize>> = vec![Vec::new(); verts.len()];
+    let mut border: Vec<Vec<usize>> = vec![Vec::new(); verts.len()];
+    let mut pinned = vec![false; verts.len()];
+    for (&(a, b), faces) in opposite {
+        neighbours[a].push(b);
+        neighbours[b].push(a);
+        match faces.len() {
+            1 => {
+                border[a].push(b);
+                border[b].push(a);
+            }
+            2 => {}
+            _ => {
+                pinned[a] = true;
+                pinned[b] = true;
+            }
+        }
+    }
+    verts
+        .iter()
+        .enumerate()
+        .map(|(v, p)| {
+            let sum = |around: &[usize]| -> Vector3<f32> {
+                // Sorted so the result doesn't depend on hash order.
+                let mut around = around.to_vec();
+                around.sort_unstable();
+                around.iter().map(|&n| verts[n].coords).sum()
+            };
+            if pinned[v] || neighbours[v].is_empty() {
+                *p
+            } else if !border[v].is_empty() {
+                if border[v].len() == 2 {
+                    Point3::from(p.coords * 0.75 + sum(&border[v]) * 0.125)
+                } else {
+                    *p
+                }
+            } else {
+                let n = neighbours[v].len() as f32;
+                let beta = if neighbours[v].len() == 3 {
+                    3.0 / 16.0
+                } else {
+                    3.0 / (8.0 * n)
+                };
+                Point3::from(p.coords * (1.0 - n * beta) + sum(&neighbours[v]) * beta)
+            }
+        })
+        .collect()
+}
+
+impl Mesh for SubdividedMesh {
+    fn tris(&self) -> &Vec<Triangle> {
+        &self.tris
+    }
+
+    fn verts(&self) -> &[Point3<f32>] {
+        &self.verts
+    }
+}
+
+impl MeshMut for SubdividedMesh {
+    fn tris_mut(&mut self) -> &mut Vec<Triangle> {
+        &mut self.tris
+    }
+
+    fn verts_mut(&mut self) -> &mut [Point3<f32>] {
+        &mut self.verts
+    }
+}
@@ -0,0 +1,233 @@
+use std::fmt;
+
+use crate::mesh::{Color, Mesh, MeshMut, MeshPrimitives, Triangle};
+use nalgebra::{Point3, Vector3};
+
+/// A heightmap turned into a grid of triangles in the XZ plane, centered on
+/// the origin and facing +Y.
+#[derive(Debug)]
+pub struct TerrainMesh {
+    verts: Vec<Point3<f32>>,
+    tris: Vec<Triangle>,
+    /// Indices and color of a strip terrain, which has no `tris`.
+    strip: Option<(Vec<usize>, Color)>,
+}
+
+/// Why a heightmap could not be turned into a terrain.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TerrainError {
+    /// A terrain needs at least 2 samples along each side.
+    TooSmall { width: usize, depth: usize },
+    /// `heights` must hold exactly `width * depth` samples.
+    HeightCount { expected: usize, found: usize },
+}
+
+impl fmt::Display for TerrainError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TerrainError::TooSmall { width, depth } => {
+                write!(f, "heightmap is {width}x{depth}, needs at least 2x2")
+            }
+            TerrainError::HeightCount { expected, found } => {
+                write!(f, "expected {expected} heights, found {found}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for TerrainError {}
+
+impl TerrainMesh {
+    /// Build a terrain from `width * depth` heights in rows along x. Samples
+    /// are `scale.x` apart in x and `scale.z` in z, and heights are
+    /// multiplied by `scale.y`. Each triangle is colored by `color_fn` of its
+    /// average unscaled height.
+    pub fn from_heightmap(
+        width: usize,
+        depth: usize,
+        heights: &[f32],
+        scale: Vector3<f32>,
+        color_fn: impl Fn(f32) -> Color,
+    ) -> Result<Self, TerrainError> {
+        let verts = grid(width, depth, heights, scale)?;
+        let mut tris = Vec::with_capacity(2 * (width - 1) * (depth - 1));
+        let mut push = |v1: usize, v2: usize, v3: usize| {
+            let height = (heights[v1] + heights[v2] + heights[v3]) / 3.0;
+            tris.push(Triangle {
+                v1,
+                v2,
+                v3,
+                color: color_fn(height),
+            });
+        };
+        for row in 0..depth - 1 {
+            for col in 0..width - 1 {
+                let near_left = row * width + col;
+                let near_right = near_left + 1;
+                let far_left = near_left + width;
+                let far_right = far_left + 1;
+                push(near_left, near_right, far_left);
+                push(near_right, far_right, far_left);
+            }
+        }
+
+        Ok(Self {
+            verts,
+            tris,
+            strip: None,
+        })
+    }
+
+    /// Like [`TerrainMesh::from_heightmap`], but in a single color and
+    /// stored as one triangle strip zigzagging along the rows, which takes
+    /// about a quarter of the memory of separate triangles. Cells are split
+    /// along the other diagonal.
+    pub fn strip_from_heightmap(
+        width: usize,
+        depth: usize,
+        heights: &[f32],
+        scale: Vector3<f32>,
+        color: Color,
+    ) -> Result<Self, TerrainError> {
+        let verts = grid(width, depth, heights, scale)?;
+        let mut indices = Vec::with_capacity(2 * width * (depth - 1) + 2 * (depth - 2));
+        for row in 0..depth - 1 {
+            let near = row * width;
+            let far = near + width;
+            if row > 0 {
+                // Two repeats join the rows and keep the next one starting
+                // on an even triangle, so its winding comes out the same.
+                indices.push(near - 1);
+                indices.push(far);
+            }
+            for col in 0..width {
+                indices.push(far + col);
+                indices.push(near + col);
+            }
+        }
+        Ok(Self {
+            verts,
+            tris: Vec::new(),
+            strip: Some((indices, color)),
+        })
+    }
+}
+
+/// Vertices of a `width * depth` heightmap, centered on the origin.
+fn grid(
+    width: usize,
+    depth: usize,
+    heights: &[f32],
+    scale: Vector3<f32>,
+) -> Result<Vec<Point3<f32>>, TerrainError> {
+    if width < 2 || depth < 2 {
+        return Err(TerrainError::TooSmall { width, depth });
+    }
+    if heights.len() != width * depth {
+        return Err(TerrainError::HeightCount {
+            expected: width * depth,
+            found: heights.len(),
+        });
+    }
+
+    let half_x = (width - 1) as f32 / 2.0;
+    let half_z = (depth - 1) as f32 / 2.0;
+    let mut verts = Vec::with_capacity(width * depth);
+    for row in 0..depth {
+        for col in 0..width {
+            verts.push(Point3::new(
+                (col as f32 - half_x) * scale.x,
+                heights[row * width + col] * scale.y,
+                (row as f32 - half_z) * scale.z,
+            ));
+        }
+    }
+    Ok(verts)
+}
+
+/// Fractal value noise in 0..=1 for a `width * depth` heightmap, in the row
+/// order [`TerrainMesh::from_heightmap`] expects. `frequency` is lattice
+/// cells per sample for the first octave; each further octave doubles it at
+/// half the amplitude. The same `seed` always gives the same terrain.
+pub fn noise_heightmap(
+    width: usize,
+    depth: usize,
+    frequency: f32,
+    octaves: u32,
+    seed: u32,
+) -> Vec<f32> {
+    let octaves = octaves.max(1);
+    let mut heights = Vec::with_capacity(width * depth);
+    for row in 0..depth {
+        for col in 0..width {
+            let (mut sum, mut norm) = (0.0, 0.0);
+            let (mut amplitude, mut freq) = (1.0, frequency);
+            for octave in 0..octaves {
+                sum += amplitude
+                    * value_noise(
+                        col as f32 * freq,
+                        row as f32 * freq,
+                        seed.wrapping_add(octave),
+                    );
+                norm += amplitude;
+                amplitude *= 0.5;
+                freq *= 2.0;
+            }
+            heights.push(sum / norm);
+        }
+    }
+    heights
+}
+
+/// Smoothly interpolated random values on an integer lattice, in 0..=1.
+fn value_noise(x: f32, y: f32, seed: u32) -> f32 {
+    let (x0, y0) = (x.floor(), y.floor());
+    let (tx, ty) = (x - x0, y - y0);
+    let (sx, sy) = (tx * tx * (3.0 - 2.0 * tx), ty * ty * (3.0 - 2.0 * ty));
+    let (ix, iy) = (x0 as i32, y0 as i32);
+    let corner = |dx: i32, dy: i32| lattice(ix.wrapping_add(dx), iy.wrapping_add(dy), seed);
+    let top = corner(0, 0) + (corner(1, 0) - corner(0, 0)) * sx;
+    let bottom = corner(0, 1) + (corner(1, 1) - corner(0, 1)) * sx;
+    top + (bottom - top) * sy
+}
+
+/// A pseudo random value in 0..=1 for a lattice point.
+fn lattice(x: i32, y: i32, seed: u32) -> f32 {
+    let mut h = (x as u32).wrapping_mul(0x8da6_b343)
+        ^ (y as u32).wrapping_mul(0xd816_3841)
+        ^ seed.wrapping_mul(0xcb1a_b31f);
+    h ^= h >> 13;
+    h = h.wrapping_mul(0x5bd1_e995);
+    h ^= h >> 15;
+    h as f32 / u32::MAX as f32
+}
+
+impl Mesh for TerrainMesh {
+    fn tris(&self) -> &Vec<Triangle> {
+        &self.tris
+    }
+
+    fn primitives(&self) -> MeshPrimitives<'_> {
+        match &self.strip {
+            Some((indices, color)) => MeshPrimitives::TriangleStrip {
+                indices,
+                color: *color,
+            },
+            None => MeshPrimitives::TriangleList(&self.tris),
+        }
+    }
+
+    fn verts(&self) -> &[Point3<f32>] {
+        &self.verts
+    }
+}
+
+impl MeshMut for TerrainMesh {
+    fn tris_mut(&mut self) -> &mut Vec<Triangle> {
+        &mut self.tris
+    }
+
+    fn verts_mut(&mut self) -> &mut [Point3<f32>] {
+        &mut self.verts
+    }
+}
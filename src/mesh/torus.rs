@@ -0,0 +1,98 @@
+use std::f32::consts::TAU;
+
+use crate::mesh::{Color, Mesh, MeshMut, PrimitiveError, Triangle};
+use nalgebra::{Point3, Vector3};
+
+/// A ring lying in the XZ plane, centered on the origin.
+///
+/// Seen at an angle, the near side of the ring hides part of the far side,
+/// which makes it a good check on occlusion and culling together.
+#[derive(Debug)]
+pub struct TorusMesh {
+    verts: Vec<Point3<f32>>,
+    tris: Vec<Triangle>,
+    normals: Vec<Vector3<f32>>,
+}
+
+impl TorusMesh {
+    /// A torus whose tube, `minor_radius` thick, circles the y axis at
+    /// `major_radius`. The ring is split into `major_segments` around the
+    /// axis and the tube into `minor_segments` around itself; both seams
+    /// reuse the first vertices, so there are exactly
+    /// `major_segments * minor_segments` vertices and twice that triangles.
+    pub fn new(
+        major_radius: f32,
+        minor_radius: f32,
+        major_segments: usize,
+        minor_segments: usize,
+        color: Color,
+    ) -> Result<Self, PrimitiveError> {
+        PrimitiveError::check_segments(major_segments)?;
+        PrimitiveError::check_segments(minor_segments)?;
+        let count = major_segments * minor_segments;
+        let mut verts = Vec::with_capacity(count);
+        let mut normals = Vec::with_capacity(count);
+        for i in 0..major_segments {
+            let (sin_major, cos_major) = (TAU * i as f32 / major_segments as f32).sin_cos();
+            for j in 0..minor_segments {
+                let (sin_minor, cos_minor) = (TAU * j as f32 / minor_segments as f32).sin_cos();
+                let normal = Vector3::new(cos_minor * cos_major, sin_minor, cos_minor * sin_major);
+                let center = Point3::new(major_radius * cos_major, 0.0, major_radius * sin_major);
+                verts.push(center + normal * minor_radius);
+                normals.push(normal);
+            }
+        }
+
+        let index = |i: usize, j: usize| (i % major_segments) * minor_segments + j % minor_segments;
+        let mut tris = Vec::with_capacity(2 * count);
+        for i in 0..major_segments {
+            for j in 0..minor_segments {
+                let (a, b) = (index(i, j), index(i + 1, j));
+                let (c, d) = (index(i, j + 1), index(i + 1, j + 1));
+                tris.push(Triangle {
+                    v1: a,
+                    v2: b,
+                    v3: c,
+                    color,
+                });
+                tris.push(Triangle {
+                    v1: c,
+                    v2: b,
+                    v3: d,
+                    color,
+                });
+            }
+        }
+
+        Ok(Self {
+            verts,
+            tris,
+            normals,
+        })
+    }
+
+    /// One unit normal per vertex, pointing out of the tube.
+    pub fn normals(&self) -> &[Vector3<f32>] {
+        &self.normals
+    }
+}
+
+impl Mesh for TorusMesh {
+    fn tris(&self) -> &Vec<Triangle> {
+        &self.tris
+    }
+
+    fn verts(&self) -> &[Point3<f32>] {
+        &self.verts
+    }
+}
+
+impl MeshMut for TorusMesh {
+    fn tris_mut(&mut self) -> &mut Vec<Triangle> {
+        &mut self.tris
+    }
+
+    fn verts_mut(&mut self) -> &mut [Point3<f32>] {
+        &mut self.verts
+    }
+}
@@ -0,0 +1,158 @@
+//! Writing frames to image files in whichever format the file name asks
+//! for: PNG, binary PPM or BMP, all encoded here with nothing but `std`.
+//! PNG needs the `png` feature, on by default.
+
+use std::io;
+use std::path::Path;
+
+#[cfg(feature = "png")]
+use crate::png;
+
+/// An image file format frames can be written in. The default is PNG, or
+/// BMP when built without the `png` feature.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ImageFormat {
+    #[cfg(feature = "png")]
+    Png,
+    /// Binary PPM (`P6`), RGB only: alpha is dropped.
+    Ppm,
+    /// Uncompressed 32-bit BMP.
+    Bmp,
+}
+
+impl ImageFormat {
+    /// The format named by the extension of `path`, ignoring case.
+    pub fn from_path(path: impl AsRef<Path>) -> Option<Self> {
+        let extension = path.as_ref().extension()?.to_str()?.to_ascii_lowercase();
+        match extension.as_str() {
+            #[cfg(feature = "png")]
+            "png" => Some(ImageFormat::Png),
+            "ppm" => Some(ImageFormat::Ppm),
+            "bmp" => Some(ImageFormat::Bmp),
+            _ => None,
+        }
+    }
+
+    /// The extension of files in this format, without the dot.
+    pub fn extension(self) -> &'static str {
+        match self {
+            #[cfg(feature = "png")]
+            ImageFormat::Png => "png",
+            ImageFormat::Ppm => "ppm",
+            ImageFormat::Bmp => "bmp",
+        }
+    }
+
+    /// Encode a tightly packed RGBA frame of `width * height` pixels.
+    ///
+    /// # Panics
+    ///
+    /// If `rgba` is not exactly `width * height * 4` bytes.
+    pub fn encode(self, rgba: &[u8], width: u32, height: u32) -> Vec<u8> {
+        match self {
+            #[cfg(feature = "png")]
+            ImageFormat::Png => png::encode(rgba, width, height),
+            ImageFormat::Ppm => encode_ppm(rgba, width, height),
+            ImageFormat::Bmp => encode_bmp(rgba, width, height),
+        }
+    }
+}
+
+impl Default for ImageFormat {
+    fn default() -> Self {
+        #[cfg(feature = "png")]
+        return ImageFormat::Png;
+        #[cfg(not(feature = "png"))]
+        return ImageFormat::Bmp;
+    }
+}
+
+/// The format to write `path` in, by its extension. Unknown extensions are
+/// an [`io::ErrorKind::InvalidInput`] error, and `.png` is an
+/// [`io::ErrorKind::Unsupported`] one when built without the `png`
+/// feature.
+pub fn format_for(path: impl AsRef<Path>) -> io::Result<ImageFormat> {
+    let path = path.as_ref();
+    ImageFormat::from_path(path).ok_or_else(|| {
+        let png = path
+            .extension()
+            .is_some_and(|extension| extension.eq_ignore_ascii_case("png"));
+        if png {
+            io::Error::new(io::ErrorKind::Unsupported, "built without png support")
+        } else if cfg!(feature = "png") {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "unknown image format, expected .png, .ppm or .bmp",
+            )
+        } else {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "unknown image format, expected .ppm or .bmp",
+            )
+        }
+    })
+}
+
+/// Encode `rgba` in the format the extension of `path` names and write it
+/// there, failing as [`format_for`] does for formats it can't write.
+pub fn write(path: impl AsRef<Path>, rgba: &[u8], width: u32, height: u32) -> io::Result<()> {
+    let path = path.as_ref();
+    let format = format_for(path)?;
+    std::fs::write(path, format.encode(rgba, width, height))
+}
+
+/// Binary PPM: a text header, then RGB rows top to bottom.
+pub fn encode_ppm(rgba: &[u8], width: u32, height: u32) -> Vec<u8> {
+    check_size(rgba, width, height);
+    let header = format!("P6\n{width} {height}\n255\n");
+    let mut ppm = Vec::with_capacity(header.len() + rgba.len() / 4 * 3);
+    ppm.extend_from_slice(header.as_bytes());
+    for p in rgba.chunks_exact(4) {
+        ppm.extend_from_slice(&p[..3]);
+    }
+    ppm
+}
+
+/// Bytes in the BMP file and info headers in front of the pixels.
+const BMP_HEADERS: usize = 14 + 40;
+
+/// Uncompressed 32-bit BMP: BGRA rows bottom to top. Rows of 32-bit
+/// pixels always fill whole 4-byte words, so no row needs padding,
+/// whatever the width.
+pub fn encode_bmp(rgba: &[u8], width: u32, height: u32) -> Vec<u8> {
+    check_size(rgba, width, height);
+    let row = width as usize * 4;
+    let size = BMP_HEADERS + rgba.len();
+    let mut bmp = Vec::with_capacity(size);
+    // File header.
+    bmp.extend_from_slice(b"BM");
+    bmp.extend_from_slice(&(size as u32).to_le_bytes());
+    bmp.extend_from_slice(&[0; 4]);
+    bmp.extend_from_slice(&(BMP_HEADERS as u32).to_le_bytes());
+    // BITMAPINFOHEADER: a positive height means bottom-up rows, and
+    // compression 0 plain pixels. 2835 pixels a metre is 72 DPI.
+    bmp.extend_from_slice(&40u32.to_le_bytes());
+    bmp.extend_from_slice(&(width as i32).to_le_bytes());
+    bmp.extend_from_slice(&(height as i32).to_le_bytes());
+    bmp.extend_from_slice(&1u16.to_le_bytes());
+    bmp.extend_from_slice(&32u16.to_le_bytes());
+    bmp.extend_from_slice(&0u32.to_le_bytes());
+    bmp.extend_from_slice(&(rgba.len() as u32).to_le_bytes());
+    bmp.extend_from_slice(&2835i32.to_le_bytes());
+    bmp.extend_from_slice(&2835i32.to_le_bytes());
+    bmp.extend_from_slice(&[0; 8]);
+    for line in rgba.chunks_exact(row.max(1)).rev() {
+        for p in line.chunks_exact(4) {
+            bmp.extend_from_slice(&[p[2], p[1], p[0], p[3]]);
+        }
+    }
+    bmp
+}
+
+fn check_size(rgba: &[u8], width: u32, height: u32) {
+    assert_eq!(
+        rgba.len(),
+        width as usize * height as usize * 4,
+        "frame size mismatch"
+    );
+}
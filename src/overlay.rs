@@ -0,0 +1,257 @@
+//! 2D drawing on top of a rendered frame: text, boxes and the stats
+//! readout. Everything here clips to the frame, so callers can draw at any
+//! position without checking the window size first.
+
+use std::time::Duration;
+
+use crate::mesh::Color;
+use crate::raster::blend;
+
+/// Width of a glyph in pixels.
+pub const GLYPH_WIDTH: u32 = 5;
+/// Height of a glyph in pixels.
+pub const GLYPH_HEIGHT: u32 = 7;
+/// Horizontal distance from one glyph to the next.
+pub const GLYPH_ADVANCE: i32 = GLYPH_WIDTH as i32 + 1;
+/// Vertical distance from one line of text to the next.
+pub const LINE_HEIGHT: i32 = GLYPH_HEIGHT as i32 + 2;
+
+/// 5x7 glyphs for ASCII space through `Z`, one byte per row from the top,
+/// with the leftmost column in bit 4.
+const FONT: [[u8; 7]; 59] = [
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // ' '
+    [0x04, 0x04, 0x04, 0x04, 0x04, 0x00, 0x04], // !
+    [0x0A, 0x0A, 0x0A, 0x00, 0x00, 0x00, 0x00], // "
+    [0x0A, 0x0A, 0x1F, 0x0A, 0x1F, 0x0A, 0x0A], // #
+    [0x04, 0x0F, 0x14, 0x0E, 0x05, 0x1E, 0x04], // $
+    [0x18, 0x19, 0x02, 0x04, 0x08, 0x13, 0x03], // %
+    [0x0C, 0x12, 0x14, 0x08, 0x15, 0x12, 0x0D], // &
+    [0x0C, 0x04, 0x08, 0x00, 0x00, 0x00, 0x00], // '
+    [0x02, 0x04, 0x08, 0x08, 0x08, 0x04, 0x02], // (
+    [0x08, 0x04, 0x02, 0x02, 0x02, 0x04, 0x08], // )
+    [0x00, 0x04, 0x15, 0x0E, 0x15, 0x04, 0x00], // *
+    [0x00, 0x04, 0x04, 0x1F, 0x04, 0x04, 0x00], // +
+    [0x00, 0x00, 0x00, 0x00, 0x0C, 0x04, 0x08], // ,
+    [0x00, 0x00, 0x00, 0x1F, 0x00, 0x00, 0x00], // -
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x0C, 0x0C], // .
+    [0x00, 0x01, 0x02, 0x04, 0x08, 0x10, 0x00], // /
+    [0x0E, 0x11, 0x13, 0x15, 0x19, 0x11, 0x0E], // 0
+    [0x04, 0x0C, 0x04, 0x04, 0x04, 0x04, 0x0E], // 1
+    [0x0E, 0x11, 0x01, 0x02, 0x04, 0x08, 0x1F], // 2
+    [0x1F, 0x02, 0x04, 0x02, 0x01, 0x11, 0x0E], // 3
+    [0x02, 0x06, 0x0A, 0x12, 0x1F, 0x02, 0x02], // 4
+    [0x1F, 0x10, 0x1E, 0x01, 0x01, 0x11, 0x0E], // 5
+    [0x06, 0x08, 0x10, 0x1E, 0x11, 0x11, 0x0E], // 6
+    [0x1F, 0x01, 0x02, 0x04, 0x08, 0x08, 0x08], // 7
+    [0x0E, 0x11, 0x11, 0x0E, 0x11, 0x11, 0x0E], // 8
+    [0x0E, 0x11, 0x11, 0x0F, 0x01, 0x02, 0x0C], // 9
+    [0x00, 0x0C, 0x0C, 0x00, 0x0C, 0x0C, 0x00], // :
+    [0x00, 0x0C, 0x0C, 0x00, 0x0C, 0x04, 0x08], // ;
+    [0x02, 0x04, 0x08, 0x10, 0x08, 0x04, 0x02], // <
+    [0x00, 0x00, 0x1F, 0x00, 0x1F, 0x00, 0x00], // =
+    [0x08, 0x04, 0x02, 0x01, 0x02, 0x04, 0x08], // >
+    [0x0E, 0x11, 0x01, 0x02, 0x04, 0x00, 0x04], // ?
+    [0x0E, 0x11, 0x01, 0x0D, 0x15, 0x15, 0x0E], // @
+    [0x0E, 0x11, 0x11, 0x11, 0x1F, 0x11, 0x11], // A
+    [0x1E, 0x11, 0x11, 0x1E, 0x11, 0x11, 0x1E], // B
+    [0x0E, 0x11, 0x10, 0x10, 0x10, 0x11, 0x0E], // C
+    [0x1C, 0x12, 0x11, 0x11, 0x11, 0x12, 0x1C], // D
+    [0x1F, 0x10, 0x10, 0x1E, 0x10, 0x10, 0x1F], // E
+    [0x1F, 0x10, 0x10, 0x1E, 0x10, 0x10, 0x10], // F
+    [0x0E, 0x11, 0x10, 0x17, 0x11, 0x11, 0x0F], // G
+    [0x11, 0x11, 0x11, 0x1F, 0x11, 0x11, 0x11], // H
+    [0x0E, 0x04, 0x04, 0x04, 0x04, 0x04, 0x0E], // I
+    [0x07, 0x02, 0x02, 0x02, 0x02, 0x12, 0x0C], // J
+    [0x11, 0x12, 0x14, 0x18, 0x14, 0x12, 0x11], // K
+    [0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x1F], // L
+    [0x11, 0x1B, 0x15, 0x15, 0x11, 0x11, 0x11], // M
+    [0x11, 0x11, 0x19, 0x15, 0x13, 0x11, 0x11], // N
+    [0x0E, 0x11, 0x11, 0x11, 0x11, 0x11, 0x0E], // O
+    [0x1E, 0x11, 0x11, 0x1E, 0x10, 0x10, 0x10], // P
+    [0x0E, 0x11, 0x11, 0x11, 0x15, 0x12, 0x0D], // Q
+    [0x1E, 0x11, 0x11, 0x1E, 0x14, 0x12, 0x11], // R
+    [0x0F, 0x10, 0x10, 0x0E, 0x01, 0x01, 0x1E], // S
+    [0x1F, 0x04, 0x04, 0x04, 0x04, 0x04, 0x04], // T
+    [0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x0E], // U
+    [0x11, 0x11, 0x11, 0x11, 0x11, 0x0A, 0x04], // V
+    [0x11, 0x11, 0x11, 0x15, 0x15, 0x15, 0x0A], // W
+    [0x11, 0x11, 0x0A, 0x04, 0x0A, 0x11, 0x11], // X
+    [0x11, 0x11, 0x11, 0x0A, 0x04, 0x04, 0x04], // Y
+    [0x1F, 0x01, 0x02, 0x04, 0x08, 0x10, 0x1F], // Z
+];
+
+/// Rows of the glyph for `c`. Lowercase letters use the uppercase glyphs
+/// and anything the font lacks is drawn as `?`.
+fn glyph(c: char) -> &'static [u8; 7] {
+    let c = c.to_ascii_uppercase();
+    match c {
+        ' '..='Z' => &FONT[c as usize - ' ' as usize],
+        _ => &FONT['?' as usize - ' ' as usize],
+    }
+}
+
+/// A frame to draw 2D overlays into, after the world has been rendered.
+pub struct Canvas<'a> {
+    frame: &'a mut [u8],
+    width: u32,
+    height: u32,
+}
+
+impl<'a> Canvas<'a> {
+    /// Wrap a tightly packed RGBA `frame` of `width * height` pixels.
+    pub fn new(frame: &'a mut [u8], width: u32, height: u32) -> Self {
+        Self {
+            frame,
+            width,
+            height,
+        }
+    }
+
+    /// Blend `color` onto one pixel, ignoring positions off the frame.
+    pub fn put(&mut self, x: i32, y: i32, color: Color) {
+        if x < 0 || y < 0 || x >= self.width as i32 || y >= self.height as i32 {
+            return;
+        }
+        let index = ((y as u32 * self.width + x as u32) * 4) as usize;
+        blend(&mut self.frame[index..index + 4], &color);
+    }
+
+    /// Blend a filled rectangle with its top left corner at `(x, y)`.
+    pub fn fill_rect(&mut self, x: i32, y: i32, width: u32, height: u32, color: Color) {
+        let x0 = x.max(0);
+        let y0 = y.max(0);
+        let x1 = (x + width as i32).min(self.width as i32);
+        let y1 = (y + height as i32).min(self.height as i32);
+        for py in y0..y1 {
+            for px in x0..x1 {
+                self.put(px, py, color);
+            }
+        }
+    }
+
+    /// Blend a one pixel wide line from `(x0, y0)` to `(x1, y1)`, both ends
+    /// included.
+    pub fn draw_line(&mut self, x0: i32, y0: i32, x1: i32, y1: i32, color: Color) {
+        let (dx, dy) = ((x1 - x0).abs(), -(y1 - y0).abs());
+        let (step_x, step_y) = ((x1 - x0).signum(), (y1 - y0).signum());
+        let (mut x, mut y, mut error) = (x0, y0, dx + dy);
+        loop {
+            self.put(x, y, color);
+            if (x, y) == (x1, y1) {
+                break;
+            }
+            if 2 * error >= dy {
+                error += dy;
+                x += step_x;
+            }
+            if 2 * error <= dx {
+                error += dx;
+                y += step_y;
+            }
+        }
+    }
+
+    /// Blend a one pixel wide circle of `radius` around `(cx, cy)`.
+    pub fn draw_circle(&mut self, cx: i32, cy: i32, radius: u32, color: Color) {
+        let radius = radius as i32;
+        let (mut x, mut y, mut error) = (radius, 0, 1 - radius);
+        while x >= y {
+            // One point per octant, skipping the ones that land twice on
+            // the diagonals and axes so the blend stays even.
+            let points = [
+                (x, y),
+                (-x, y),
+                (x, -y),
+                (-x, -y),
+                (y, x),
+                (-y, x),
+                (y, -x),
+                (-y, -x),
+            ];
+            for (i, &(dx, dy)) in points.iter().enumerate() {
+                if !points[..i].contains(&(dx, dy)) {
+                    self.put(cx + dx, cy + dy, color);
+                }
+            }
+            y += 1;
+            if error < 0 {
+                error += 2 * y + 1;
+            } else {
+                x -= 1;
+                error += 2 * (y - x) + 1;
+            }
+        }
+    }
+
+    /// Draw `text` with its top left corner at `(x, y)`. `\n` starts a new
+    /// line under the first one.
+    pub fn draw_text(&mut self, x: i32, y: i32, text: &str, color: Color) {
+        let (mut pen_x, mut pen_y) = (x, y);
+        for c in text.chars() {
+            if c == '\n' {
+                pen_x = x;
+                pen_y += LINE_HEIGHT;
+                continue;
+            }
+            for (row, bits) in glyph(c).iter().enumerate() {
+                for col in 0..GLYPH_WIDTH {
+                    if bits & (0x10 >> col) != 0 {
+                        self.put(pen_x + col as i32, pen_y + row as i32, color);
+                    }
+                }
+            }
+            pen_x += GLYPH_ADVANCE;
+        }
+    }
+}
+
+/// Size in pixels of `text` as [`Canvas::draw_text`] would draw it.
+pub fn text_size(text: &str) -> (u32, u32) {
+    if text.is_empty() {
+        return (0, 0);
+    }
+    let columns = text.split('\n').map(|line| line.chars().count()).max();
+    let lines = text.split('\n').count() as i32;
+    let width = (columns.unwrap_or(0) as i32 * GLYPH_ADVANCE - 1).max(0);
+    let height = (lines - 1) * LINE_HEIGHT + GLYPH_HEIGHT as i32;
+    (width as u32, height as u32)
+}
+
+/// Frames per second averaged over a fixed window, so the readout is stable
+/// enough to read.
+#[derive(Debug, Clone)]
+pub struct FpsCounter {
+    window: Duration,
+    elapsed: Duration,
+    frames: u32,
+    fps: f32,
+}
+
+impl FpsCounter {
+    /// Report a new average every `window`.
+    pub fn new(window: Duration) -> Self {
+        Self {
+            window,
+            elapsed: Duration::ZERO,
+            frames: 0,
+            fps: 0.0,
+        }
+    }
+
+    /// Count a frame that took `frame_time`, returning the current average.
+    pub fn tick(&mut self, frame_time: Duration) -> f32 {
+        self.elapsed += frame_time;
+        self.frames += 1;
+        if self.elapsed >= self.window {
+            self.fps = self.frames as f32 / self.elapsed.as_secs_f32();
+            self.elapsed = Duration::ZERO;
+            self.frames = 0;
+        }
+        self.fps
+    }
+
+    /// The last average, zero until the first window has passed.
+    pub fn fps(&self) -> f32 {
+        self.fps
+    }
+}
@@ -0,0 +1,87 @@
+//! Holding the frame rate down to a cap, so an animated scene doesn't
+//! spin a core drawing frames nobody sees and frame times stay even.
+//!
+//! A [`FramePacer`] only says when the next frame is due; the caller
+//! decides how to wait for it, so input can still be handled in between.
+//! [`sleep_until`] waits for a deadline precisely.
+
+use std::time::{Duration, Instant};
+
+/// How long before a deadline [`sleep_until`] stops sleeping and spins
+/// instead. Sleeps overshoot by up to about this much, spinning doesn't.
+pub const SPIN_MARGIN: Duration = Duration::from_millis(2);
+
+/// Spaces frames at least `1 / max_fps` seconds apart.
+#[derive(Debug, Clone)]
+pub struct FramePacer {
+    budget: Option<Duration>,
+    /// Whether the display's vsync already keeps frames this far apart.
+    vsync: bool,
+    last_frame: Option<Instant>,
+}
+
+impl FramePacer {
+    /// A pacer for at most `max_fps` frames a second, or no limit when
+    /// `None`. Rates that aren't above zero and finite mean no limit too.
+    pub fn new(max_fps: Option<f32>) -> Self {
+        let budget = max_fps
+            .filter(|fps| *fps > 0.0 && fps.is_finite())
+            .map(|fps| Duration::from_secs_f32(1.0 / fps));
+        Self {
+            budget,
+            vsync: false,
+            last_frame: None,
+        }
+    }
+
+    /// The shortest time from one frame to the next, if capped at all.
+    pub fn budget(&self) -> Option<Duration> {
+        self.budget
+    }
+
+    /// Tell the pacer frames are presented in step with a display that
+    /// refreshes `refresh_hz` times a second, or at an unknown rate when
+    /// `None`. Presenting then already waits for the display, so the
+    /// pacer stands aside when its cap is no lower than the refresh rate,
+    /// and when the rate is unknown. Returns whether it still paces.
+    pub fn sync_to_display(&mut self, refresh_hz: Option<f32>) -> bool {
+        self.vsync = match (self.budget, refresh_hz) {
+            (Some(budget), Some(hz)) if hz > 0.0 => budget.as_secs_f32() * hz <= 1.0,
+            _ => true,
+        };
+        self.is_pacing()
+    }
+
+    /// Whether frames have to wait for [`FramePacer::next_frame`].
+    pub fn is_pacing(&self) -> bool {
+        self.budget.is_some() && !self.vsync
+    }
+
+    /// Note that a frame started drawing at `now`.
+    pub fn frame_started(&mut self, now: Instant) {
+        self.last_frame = Some(now);
+    }
+
+    /// When the next frame may start. `None` when it may start straight
+    /// away, because nothing is paced or no frame has been drawn yet; a
+    /// frame coming after a long idle spell gets a time already passed.
+    pub fn next_frame(&self) -> Option<Instant> {
+        if !self.is_pacing() {
+            return None;
+        }
+        Some(self.last_frame? + self.budget?)
+    }
+}
+
+/// Block until `deadline`: sleep while it is more than [`SPIN_MARGIN`]
+/// away, then spin for the rest, so waking is accurate to well under a
+/// millisecond without spinning the whole wait.
+pub fn sleep_until(deadline: Instant) {
+    let now = Instant::now();
+    if deadline > now + SPIN_MARGIN {
+        std::thread::sleep(deadline - now - SPIN_MARGIN);
+    }
+    while Instant::now() < deadline {
+        std::hint::spin_loop();
+    }
+}
@@ -0,0 +1,214 @@
+//! Painting into textures through the meshes that show them.
+//!
+//! A [`Brush`] is stamped into a [`Texture`] at texture coordinates, found
+//! from a picking [`Hit`] with [`World::hit_uv`]. A [`Stroke`] follows the
+//! cursor from frame to frame and fills in the stamps between, so a fast
+//! drag draws a line rather than a trail of dots.
+
+use std::io;
+use std::path::Path;
+
+use nalgebra::Vector2;
+
+use crate::material::Material;
+use crate::mesh::Color;
+use crate::pick::Hit;
+use crate::texture::{Texture, TextureHandle};
+use crate::world::World;
+
+/// Stamps along a stroke, per brush radius. Close enough that the edge of a
+/// line doesn't scallop.
+const STAMPS_PER_RADIUS: f32 = 4.0;
+/// Largest jump in `u` or `v` between two frames that is still drawn as a
+/// line. Past it the cursor most likely crossed a seam into another part
+/// of the texture, and joining the two would paint across everything in
+/// between.
+pub const SEAM_JUMP: f32 = 0.5;
+
+/// What is painted: a round stamp of `color`, solid out to `hardness`
+/// times `radius` and fading to nothing at `radius`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Brush {
+    /// In texels.
+    pub radius: f32,
+    /// `0.0` fades all the way from the centre, `1.0` has a hard edge.
+    pub hardness: f32,
+    pub color: Color,
+}
+
+impl Default for Brush {
+    fn default() -> Self {
+        Self {
+            radius: 4.0,
+            hardness: 0.5,
+            color: Color::RED,
+        }
+    }
+}
+
+impl Brush {
+    /// How much of the brush color lands `distance` texels from the
+    /// centre, from `1.0` down to `0.0`.
+    pub fn coverage(&self, distance: f32) -> f32 {
+        let solid = self.radius * self.hardness.clamp(0.0, 1.0);
+        if distance >= self.radius {
+            0.0
+        } else if distance <= solid {
+            1.0
+        } else {
+            1.0 - (distance - solid) / (self.radius - solid)
+        }
+    }
+}
+
+impl Texture {
+    /// A `width` by `height` texture of `color` to paint on.
+    pub fn blank(width: u32, height: u32, color: Color) -> Self {
+        Self::from_fn(width, height, |_, _| color)
+    }
+
+    /// Blend one stamp of `brush` centred on `(u, v)`. The stamp is not
+    /// wrapped: the part past the texture's edges is dropped.
+    pub fn paint(&mut self, uv: Vector2<f32>, brush: &Brush) {
+        let (width, height) = (self.width() as f32, self.height() as f32);
+        let centre = Vector2::new(uv.x * width, uv.y * height);
+        if !(centre.x.is_finite() && centre.y.is_finite()) || brush.radius <= 0.0 {
+            return;
+        }
+        let x0 = (centre.x - brush.radius).floor().max(0.0) as u32;
+        let y0 = (centre.y - brush.radius).floor().max(0.0) as u32;
+        let x1 = (centre.x + brush.radius).ceil().min(width) as u32;
+        let y1 = (centre.y + brush.radius).ceil().min(height) as u32;
+        for y in y0..y1 {
+            for x in x0..x1 {
+                let texel = Vector2::new(x as f32 + 0.5, y as f32 + 0.5);
+                let coverage = brush.coverage((texel - centre).norm());
+                if coverage > 0.0 {
+                    self.blend_texel(x, y, brush.color, coverage);
+                }
+            }
+        }
+    }
+
+    /// Paint a line of stamps from `from` to `to`, both included.
+    pub fn stroke(&mut self, from: Vector2<f32>, to: Vector2<f32>, brush: &Brush) {
+        let texels = Vector2::new(
+            (to.x - from.x) * self.width() as f32,
+            (to.y - from.y) * self.height() as f32,
+        );
+        let spacing = (brush.radius / STAMPS_PER_RADIUS).max(0.5);
+        let steps = (texels.norm() / spacing).ceil().min(u32::MAX as f32) as u32;
+        for step in 0..=steps {
+            let t = if steps == 0 {
+                1.0
+            } else {
+                step as f32 / steps as f32
+            };
+            self.paint(from.lerp(&to, t), brush);
+        }
+    }
+
+    /// Write the texture to `path`, in the format its extension picks, as
+    /// [`crate::output::write`] does.
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        crate::output::write(path, self.rgba(), self.width(), self.height())
+    }
+}
+
+/// A drag of the brush across whatever is under the cursor.
+#[derive(Debug, Clone, Default)]
+pub struct Stroke {
+    /// Where the last stamp went, and into which texture.
+    last: Option<(TextureHandle, Vector2<f32>)>,
+}
+
+impl Stroke {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Paint at `uv` in `texture`, joined to the last stamp when it went
+    /// into the same texture and not across a seam.
+    pub fn to(
+        &mut self,
+        world: &mut World,
+        texture: TextureHandle,
+        uv: Vector2<f32>,
+        brush: &Brush,
+    ) {
+        let Some(target) = world.materials.texture_mut(texture) else {
+            return;
+        };
+        match self.last {
+            Some((last_texture, last))
+                if last_texture == texture
+                    && (uv.x - last.x).abs() <= SEAM_JUMP
+                    && (uv.y - last.y).abs() <= SEAM_JUMP =>
+            {
+                target.stroke(last, uv, brush);
+            }
+            _ => target.paint(uv, brush),
+        }
+        self.last = Some((texture, uv));
+    }
+
+    /// Lift the brush, so the next stamp starts a new line.
+    pub fn end(&mut self) {
+        self.last = None;
+    }
+}
+
+impl World {
+    /// Texture coordinates at `hit`, if the object's mesh has them.
+    pub fn hit_uv(&self, hit: &Hit) -> Option<Vector2<f32>> {
+        let mesh = &self.models.get(hit.object)?.mesh;
+        let uvs = mesh.uvs().filter(|uvs| uvs.len() == mesh.verts().len())?;
+        let tri = mesh.primitives().triangles().nth(hit.triangle)?;
+        Some(uvs[tri.v1] * (1.0 - hit.u - hit.v) + uvs[tri.v2] * hit.u + uvs[tri.v3] * hit.v)
+    }
+
+    /// The texture `object` shows, if any.
+    pub fn object_texture(&self, object: usize) -> Option<TextureHandle> {
+        let model = self.models.get(object)?;
+        self.materials
+            .instantiate_override(model.material?, &model.material_override)?
+            .texture
+    }
+
+    /// The texture to paint `object` through: the one it shows, or else a
+    /// new white `size` by `size` one set as its material override.
+    /// Objects without a material get one of their own, in the color of
+    /// their first triangle. `None` when the mesh has no texture
+    /// coordinates, so there is nowhere to paint.
+    pub fn paintable_texture(&mut self, object: usize, size: u32) -> Option<TextureHandle> {
+        let model = self.models.get(object)?;
+        model
+            .mesh
+            .uvs()
+            .filter(|uvs| uvs.len() == model.mesh.verts().len())?;
+        if let Some(texture) = self.object_texture(object) {
+            return Some(texture);
+        }
+        let color = model
+            .mesh
+            .primitives()
+            .triangles()
+            .next()
+            .map_or(Color::WHITE, |tri| tri.color);
+        let texture = self
+            .materials
+            .add_texture(Texture::blank(size, size, Color::WHITE));
+        if self.models[object].material.is_none() {
+            // Named apart from any the library already has, which
+            // inserting would replace.
+            let name = (0..)
+                .map(|n| format!("paint {n}"))
+                .find(|name| self.materials.handle(name).is_none())
+                .unwrap_or_default();
+            let material = self.materials.insert(&name, Material::new(color));
+            self.models[object].material = Some(material);
+        }
+        self.models[object].material_override.texture = Some(texture);
+        Some(texture)
+    }
+}
@@ -0,0 +1,196 @@
+//! Named colors and ways to build a [`Color`] other than channel by channel.
+
+use std::fmt;
+use std::str::FromStr;
+
+use crate::mesh::Color;
+
+/// Why a string is not a `#rrggbb` or `#rrggbbaa` color.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ColorParseError(String);
+
+impl fmt::Display for ColorParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "bad color {:?}, expected #rrggbb or #rrggbbaa", self.0)
+    }
+}
+
+impl std::error::Error for ColorParseError {}
+
+impl Color {
+    pub const BLACK: Color = Color::rgb(0, 0, 0);
+    pub const WHITE: Color = Color::rgb(255, 255, 255);
+    pub const GRAY: Color = Color::rgb(128, 128, 128);
+    pub const RED: Color = Color::rgb(255, 0, 0);
+    pub const GREEN: Color = Color::rgb(0, 255, 0);
+    pub const BLUE: Color = Color::rgb(0, 0, 255);
+    pub const YELLOW: Color = Color::rgb(255, 255, 0);
+    pub const CYAN: Color = Color::rgb(0, 255, 255);
+    pub const MAGENTA: Color = Color::rgb(255, 0, 255);
+    /// Fully transparent black.
+    pub const TRANSPARENT: Color = Color {
+        r: 0,
+        g: 0,
+        b: 0,
+        a: 0,
+    };
+
+    /// An opaque color.
+    pub const fn rgb(r: u8, g: u8, b: u8) -> Self {
+        Color { r, g, b, a: 255 }
+    }
+
+    /// A color written as hex, `0xRRGGBB` for opaque colors or `0xRRGGBBAA`
+    /// with alpha. Anything up to `0xFFFFFF` is read as the opaque form, so
+    /// a color with alpha and no red has to go through [`str::parse`]
+    /// instead.
+    pub const fn from_hex(hex: u32) -> Self {
+        let [high, r, g, b] = hex.to_be_bytes();
+        if hex > 0xFF_FFFF {
+            Color {
+                r: high,
+                g: r,
+                b: g,
+                a: b,
+            }
+        } else {
+            Color::rgb(r, g, b)
+        }
+    }
+
+    /// An opaque color from hue in degrees, wrapping around at 360, and
+    /// saturation and value in `0.0..=1.0`, clamped.
+    pub fn from_hsv(h: f32, s: f32, v: f32) -> Self {
+        let (s, v) = (s.clamp(0.0, 1.0), v.clamp(0.0, 1.0));
+        let h = h.rem_euclid(360.0) / 60.0;
+        let chroma = v * s;
+        let x = chroma * (1.0 - (h % 2.0 - 1.0).abs());
+        let (r, g, b) = match h as u32 {
+            0 => (chroma, x, 0.0),
+            1 => (x, chroma, 0.0),
+            2 => (0.0, chroma, x),
+            3 => (0.0, x, chroma),
+            4 => (x, 0.0, chroma),
+            _ => (chroma, 0.0, x),
+        };
+        let m = v - chroma;
+        let channel = |c: f32| ((c + m) * 255.0).round() as u8;
+        Color::rgb(channel(r), channel(g), channel(b))
+    }
+
+    /// The color `t` of the way from `self` to `other`, alpha included.
+    /// `t` is clamped to `0.0..=1.0`.
+    pub fn lerp(self, other: Color, t: f32) -> Self {
+        let t = t.clamp(0.0, 1.0);
+        let mix = |a: u8, b: u8| (a as f32 * (1.0 - t) + b as f32 * t).round() as u8;
+        Color {
+            r: mix(self.r, other.r),
+            g: mix(self.g, other.g),
+            b: mix(self.b, other.b),
+            a: mix(self.a, other.a),
+        }
+    }
+
+    /// Multiply red, green and blue by `factor`, saturating at 255. Alpha is
+    /// kept.
+    pub fn scale(self, factor: f32) -> Self {
+        self.tint([factor; 3])
+    }
+
+    /// [`Color::scale`] with a separate factor for red, green and blue.
+    pub fn tint(self, factors: [f32; 3]) -> Self {
+        let channel = |c: u8, factor: f32| (c as f32 * factor).clamp(0.0, 255.0) as u8;
+        Color {
+            r: channel(self.r, factors[0]),
+            g: channel(self.g, factors[1]),
+            b: channel(self.b, factors[2]),
+            a: self.a,
+        }
+    }
+}
+
+/// Colors told apart by eye for debug views that color things by number,
+/// as [`RenderMode::TriangleId`](crate::RenderMode::TriangleId) does.
+/// Neighbouring numbers get unrelated colors, and none are near black, so
+/// they never pass for shadow or a gap.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum IdPalette {
+    /// Hues all the way around the color wheel, varying in brightness and
+    /// saturation too.
+    #[default]
+    Hues,
+    /// The Okabe-Ito colors, which stay distinct with the common kinds of
+    /// color blindness, each plain and in two lighter tints, so colors
+    /// also differ in lightness.
+    ColorBlind,
+}
+
+/// The Okabe-Ito palette without its black.
+const OKABE_ITO: [Color; 7] = [
+    Color::from_hex(0xE69F00),
+    Color::from_hex(0x56B4E9),
+    Color::from_hex(0x009E73),
+    Color::from_hex(0xF0E442),
+    Color::from_hex(0x0072B2),
+    Color::from_hex(0xD55E00),
+    Color::from_hex(0xCC79A7),
+];
+
+impl IdPalette {
+    /// The opaque color for `id`, the same every time.
+    pub fn color(self, id: u64) -> Color {
+        let hash = scramble(id);
+        // Independent bits of the hash for each choice.
+        let unit = |shift: u32| ((hash >> shift) & 0xFFFF) as f32 / 65535.0;
+        match self {
+            IdPalette::Hues => {
+                Color::from_hsv(unit(0) * 360.0, 0.55 + 0.4 * unit(16), 0.7 + 0.3 * unit(32))
+            }
+            IdPalette::ColorBlind => {
+                let base = OKABE_ITO[(hash % OKABE_ITO.len() as u64) as usize];
+                let tint = (hash >> 32) % 3;
+                base.lerp(Color::WHITE, tint as f32 * 0.3)
+            }
+        }
+    }
+}
+
+/// Spread the bits of `id` over the whole word (splitmix64's finalizer),
+/// so consecutive ids share nothing.
+fn scramble(id: u64) -> u64 {
+    let mut x = id.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    x = (x ^ (x >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    x ^ (x >> 31)
+}
+
+impl FromStr for Color {
+    type Err = ColorParseError;
+
+    /// Parse `#rrggbb` or `#rrggbbaa`, the `#` being optional.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let error = || ColorParseError(s.to_string());
+        let digits = s.strip_prefix('#').unwrap_or(s);
+        if !digits.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return Err(error());
+        }
+        let value = u32::from_str_radix(digits, 16).map_err(|_| error())?;
+        let [r, g, b, a] = match digits.len() {
+            6 => (value << 8 | 0xFF).to_be_bytes(),
+            8 => value.to_be_bytes(),
+            _ => return Err(error()),
+        };
+        Ok(Color { r, g, b, a })
+    }
+}
+
+impl fmt::Display for Color {
+    /// `#rrggbb`, with `aa` added for colors that aren't opaque.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "#{:02x}{:02x}{:02x}", self.r, self.g, self.b)?;
+        if self.a != 255 {
+            write!(f, "{:02x}", self.a)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,183 @@
+//! Particle effects: emitters spawning short-lived camera-facing squares
+//! that fly, fall and fade, stepped by [`World::update`] and painted in
+//! depth order with everything else, like [`Billboard`]s.
+//!
+//! [`World::update`]: crate::World::update
+
+use nalgebra::{Point3, Vector3};
+
+use crate::billboard::Billboard;
+use crate::mesh::Color;
+
+/// Live particles an emitter holds at most by default.
+pub const MAX_PARTICLES: usize = 1024;
+
+/// Fraction of its lifetime at the end of which a particle fades out.
+pub const FADE_OUT: f32 = 0.25;
+
+/// One particle in flight.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Particle {
+    pub position: Point3<f32>,
+    pub velocity: Vector3<f32>,
+    /// Seconds since it was spawned.
+    pub age: f32,
+}
+
+/// Spawns particles at `origin` at a steady rate and moves them until they
+/// die of old age.
+///
+/// Particles live in a pool allocated up front for `max_particles`, so
+/// stepping and drawing them allocates nothing. While the pool is full,
+/// spawning waits.
+#[derive(Debug, Clone)]
+pub struct ParticleEmitter {
+    pub origin: Point3<f32>,
+    /// Particles spawned per second.
+    pub rate: f32,
+    /// Starting velocity of the particle with spawn number `n`, counting
+    /// from zero. Being a function of the number, it gives the same
+    /// particles every run; [`scatter`] helps spread them.
+    pub velocity_fn: fn(u32) -> Vector3<f32>,
+    /// Seconds a particle lives.
+    pub lifetime: f32,
+    /// Color at birth. Particles shade towards `end_color` as they age,
+    /// alpha included, and fade out over the last [`FADE_OUT`] of their
+    /// lives.
+    pub start_color: Color,
+    pub end_color: Color,
+    /// Side of a particle's square, in world units.
+    pub size: f32,
+    /// Acceleration of every particle, in world units per second squared.
+    pub gravity: Vector3<f32>,
+    particles: Vec<Particle>,
+    spawned: u32,
+    /// Particles owed by earlier steps too short to spawn a whole one.
+    backlog: f32,
+}
+
+impl ParticleEmitter {
+    /// An emitter with room for [`MAX_PARTICLES`], falling under a gravity
+    /// of 9.8 down the y axis.
+    pub fn new(
+        origin: Point3<f32>,
+        rate: f32,
+        velocity_fn: fn(u32) -> Vector3<f32>,
+        lifetime: f32,
+        start_color: Color,
+        end_color: Color,
+        size: f32,
+    ) -> Self {
+        Self {
+            origin,
+            rate,
+            velocity_fn,
+            lifetime,
+            start_color,
+            end_color,
+            size,
+            gravity: Vector3::new(0.0, -9.8, 0.0),
+            particles: Vec::with_capacity(MAX_PARTICLES),
+            spawned: 0,
+            backlog: 0.0,
+        }
+    }
+
+    /// Hold at most `max` live particles, reallocating the pool once now.
+    pub fn with_max_particles(mut self, max: usize) -> Self {
+        self.particles = Vec::with_capacity(max);
+        self
+    }
+
+    /// Live particles the emitter holds at most.
+    pub fn max_particles(&self) -> usize {
+        self.particles.capacity()
+    }
+
+    /// Particles alive now, in no particular order.
+    pub fn particles(&self) -> &[Particle] {
+        &self.particles
+    }
+
+    /// Particles spawned since the emitter was made.
+    pub fn spawned(&self) -> u32 {
+        self.spawned
+    }
+
+    /// Age particles by `dt` seconds, dropping the ones that die, move the
+    /// rest and spawn any new ones due.
+    pub fn step(&mut self, dt: f32) {
+        let lifetime = self.lifetime;
+        let gravity = self.gravity;
+        let mut i = 0;
+        while i < self.particles.len() {
+            let particle = &mut self.particles[i];
+            particle.age += dt;
+            if particle.age >= lifetime {
+                self.particles.swap_remove(i);
+                continue;
+            }
+            particle.velocity += gravity * dt;
+            particle.position += particle.velocity * dt;
+            i += 1;
+        }
+
+        self.backlog += self.rate.max(0.0) * dt;
+        while self.backlog >= 1.0 {
+            self.backlog -= 1.0;
+            if self.particles.len() == self.particles.capacity() {
+                // Particles held back by a full pool are not made up for
+                // in a burst later.
+                self.backlog = self.backlog.fract();
+                break;
+            }
+            self.particles.push(Particle {
+                position: self.origin,
+                velocity: (self.velocity_fn)(self.spawned),
+                age: 0.0,
+            });
+            self.spawned = self.spawned.wrapping_add(1);
+        }
+    }
+
+    /// Drop every live particle.
+    pub fn clear(&mut self) {
+        self.particles.clear();
+        self.backlog = 0.0;
+    }
+
+    /// The color of a particle `age` seconds old.
+    pub fn color_at(&self, age: f32) -> Color {
+        let t = if self.lifetime > 0.0 {
+            (age / self.lifetime).clamp(0.0, 1.0)
+        } else {
+            1.0
+        };
+        let color = self.start_color.lerp(self.end_color, t);
+        let fade = ((1.0 - t) / FADE_OUT).min(1.0);
+        Color {
+            a: (color.a as f32 * fade).round() as u8,
+            ..color
+        }
+    }
+
+    /// Every live particle as the billboard it is drawn as.
+    pub fn billboards(&self) -> impl Iterator<Item = Billboard> + '_ {
+        self.particles.iter().map(|particle| {
+            Billboard::new(particle.position, self.size, self.color_at(particle.age))
+        })
+    }
+}
+
+/// A number in `0.0..1.0` that looks random but is the same every time
+/// for the same `n` and `channel`, for spreading out the particles a
+/// [`ParticleEmitter::velocity_fn`] makes.
+pub fn scatter(n: u32, channel: u32) -> f32 {
+    let mut h = n.wrapping_mul(0x9e37_79b9) ^ channel.wrapping_mul(0x85eb_ca6b);
+    h ^= h >> 16;
+    h = h.wrapping_mul(0x7feb_352d);
+    h ^= h >> 15;
+    h = h.wrapping_mul(0x846c_a68b);
+    h ^= h >> 16;
+    (h >> 8) as f32 / (1 << 24) as f32
+}
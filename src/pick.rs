@@ -0,0 +1,185 @@
+//! Ray casting against the world's meshes, for picking objects under the
+//! cursor and keeping the camera out of the ground.
+
+use nalgebra::{Point2, Point3, Vector3};
+
+use crate::camera::CameraMode;
+
+use crate::pipeline::unproject_screen;
+use crate::world::World;
+
+/// A half-line in world space. `dir` is unit length.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Ray {
+    pub origin: Point3<f32>,
+    pub dir: Vector3<f32>,
+}
+
+/// Where a ray first hit a mesh.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Hit {
+    /// Index of the object in [`World::models`].
+    pub object: usize,
+    /// Index of the triangle in the object's mesh.
+    pub triangle: usize,
+    /// Distance from the ray origin, in world units.
+    pub distance: f32,
+    /// Barycentric weights of the triangle's `v2` and `v3` at the hit; `v1`
+    /// gets `1 - u - v`.
+    pub u: f32,
+    pub v: f32,
+    /// The ray passed through a [`Mesh::placeholder`] on the way, so
+    /// geometry not read yet may lie in front of the hit.
+    ///
+    /// [`Mesh::placeholder`]: crate::mesh::Mesh::placeholder
+    pub approximate: bool,
+}
+
+impl Ray {
+    pub fn new(origin: Point3<f32>, dir: Vector3<f32>) -> Self {
+        Self {
+            origin,
+            dir: dir.normalize(),
+        }
+    }
+
+    /// Möller–Trumbore intersection with the triangle `a b c`, returning the
+    /// distance and barycentric `(u, v)` of the hit. With `back_faces` off,
+    /// triangles wound counter-clockwise as seen along the ray are skipped,
+    /// matching what the rasterizer culls.
+    pub fn intersect_triangle(
+        &self,
+        a: Point3<f32>,
+        b: Point3<f32>,
+        c: Point3<f32>,
+        back_faces: bool,
+    ) -> Option<(f32, f32, f32)> {
+        let e1 = b - a;
+        let e2 = c - a;
+        let p = self.dir.cross(&e2);
+        let det = e1.dot(&p);
+        // Front faces wind clockwise, which makes the determinant negative.
+        if det.abs() < f32::EPSILON || (!back_faces && det > 0.0) {
+            return None;
+        }
+        let inv_det = 1.0 / det;
+        let s = self.origin - a;
+        let u = s.dot(&p) * inv_det;
+        if !(0.0..=1.0).contains(&u) {
+            return None;
+        }
+        let q = s.cross(&e1);
+        let v = self.dir.dot(&q) * inv_det;
+        if v < 0.0 || u + v > 1.0 {
+            return None;
+        }
+        let t = e2.dot(&q) * inv_det;
+        (t > 0.0).then_some((t, u, v))
+    }
+}
+
+impl World {
+    /// The ray from the camera through the screen point `(screen_x,
+    /// screen_y)` of a `width * height` frame, in pixels from the top left.
+    pub fn screen_ray(&self, screen_x: f32, screen_y: f32, width: u32, height: u32) -> Ray {
+        let inverse = (self.proj_mat * self.camera.generate_view_mat())
+            .try_inverse()
+            .unwrap_or_default();
+        let screen = Point2::new(screen_x, screen_y);
+        let unproject = |ndc_z| unproject_screen(screen, ndc_z, &inverse, width, height);
+        let (near, far) = self.depth_convention().ndc_range();
+        Ray::new(self.camera.position, unproject(far) - unproject(near))
+    }
+
+    /// The closest triangle hit by `ray` over every object drawn, hidden
+    /// ones left out. Back faces are only considered when `back_faces` is
+    /// set.
+    pub fn raycast(&self, ray: &Ray, back_faces: bool) -> Option<Hit> {
+        self.raycast_objects(ray, back_faces, false)
+    }
+
+    /// Like [`World::raycast`], but hitting hidden objects too.
+    pub fn raycast_including_hidden(&self, ray: &Ray, back_faces: bool) -> Option<Hit> {
+        self.raycast_objects(ray, back_faces, true)
+    }
+
+    fn raycast_objects(&self, ray: &Ray, back_faces: bool, hidden: bool) -> Option<Hit> {
+        let mut closest: Option<Hit> = None;
+        // Nearest placeholder the ray passes through, which can't be hit.
+        let mut nearest_placeholder = f32::INFINITY;
+        let matrices = self.world_matrices();
+        for (object, (model, model_mat)) in self.models.iter().zip(&matrices).enumerate() {
+            if !hidden && !model.shown(self.visible_layers) {
+                continue;
+            }
+            let verts: Vec<Point3<f32>> = model
+                .mesh
+                .verts()
+                .iter()
+                .map(|v| model_mat.transform_point(v))
+                .collect();
+            // A placeholder counts from inside too.
+            let placeholder = model.mesh.placeholder();
+            let back_faces = back_faces || placeholder;
+            for (triangle, tri) in model.mesh.primitives().triangles().enumerate() {
+                let Some((distance, u, v)) =
+                    ray.intersect_triangle(verts[tri.v1], verts[tri.v2], verts[tri.v3], back_faces)
+                else {
+                    continue;
+                };
+                if placeholder {
+                    nearest_placeholder = nearest_placeholder.min(distance);
+                } else if closest.is_none_or(|hit| distance < hit.distance) {
+                    closest = Some(Hit {
+                        object,
+                        triangle,
+                        distance,
+                        u,
+                        v,
+                        approximate: false,
+                    });
+                }
+            }
+        }
+        closest.map(|hit| Hit {
+            approximate: nearest_placeholder < hit.distance,
+            ..hit
+        })
+    }
+
+    /// Height of the highest surface facing up under `(x, z)`, at or
+    /// below `from_y`, if any drawn object has one there.
+    pub fn ground_height(&self, x: f32, z: f32, from_y: f32) -> Option<f32> {
+        let ray = Ray::new(Point3::new(x, from_y, z), -Vector3::y());
+        self.raycast(&ray, false).map(|hit| from_y - hit.distance)
+    }
+
+    /// Lift the camera to `eye_height` over the ground beneath it, if it
+    /// is any lower, moving what it looks at or orbits by as much. Ground
+    /// more than `eye_height` above the camera counts as overhead, and
+    /// with no ground beneath the camera is free to go anywhere. Returns
+    /// whether the camera moved.
+    pub fn keep_camera_above_ground(&mut self, eye_height: f32) -> bool {
+        let position = self.camera.position;
+        let Some(ground) = self.ground_height(position.x, position.z, position.y + eye_height)
+        else {
+            return false;
+        };
+        let lift = ground + eye_height - position.y;
+        if lift <= 0.0 {
+            return false;
+        }
+        self.camera.position.y += lift;
+        if let CameraMode::Orbit { focus, .. } = &mut self.camera.mode {
+            focus.y += lift;
+        }
+        true
+    }
+
+    /// Index of the object drawn under the screen point, if any. Only front
+    /// faces count, so what is picked is what is visible.
+    pub fn pick(&self, screen_x: f32, screen_y: f32, width: u32, height: u32) -> Option<usize> {
+        let ray = self.screen_ray(screen_x, screen_y, width, height);
+        self.raycast(&ray, false).map(|hit| hit.object)
+    }
+}
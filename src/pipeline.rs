@@ -0,0 +1,156 @@
+//! The vertex stage: taking points through a model-view-projection matrix
+//! to the screen, and screen points back into the world. Drawing, picking
+//! and the debug gizmos all map coordinates through here, and culling tests
+//! bounds against the planes of the same matrices.
+
+use nalgebra::{Matrix3, Matrix4, Point2, Point3, Vector3, Vector4};
+
+/// A vertex after the model-view-projection transform.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ProjectedVertex {
+    /// Position in pixels from the top left of the frame. NaN when
+    /// `clipped`.
+    pub screen: Point2<f32>,
+    /// Depth after the perspective divide.
+    pub ndc_z: f32,
+    /// Distance in front of the eye along the view direction: clip space w
+    /// for a perspective projection, negative behind the eye.
+    pub view_z: f32,
+    /// `1 / view_z`, for interpolating across a triangle in perspective.
+    pub inv_w: f32,
+    /// At or behind the eye, or outside the `0..=1` depth range the
+    /// renderer keeps, so `screen` means nothing. Triangles using the
+    /// vertex are dropped.
+    pub clipped: bool,
+}
+
+/// Project the model space point `v` through `mvp` onto a `width * height`
+/// frame.
+pub fn project_vertex(
+    v: Point3<f32>,
+    mvp: &Matrix4<f32>,
+    width: u32,
+    height: u32,
+) -> ProjectedVertex {
+    let clip = mvp * v.to_homogeneous();
+    let ndc_z = clip.z / clip.w;
+    // At or behind the eye the divide mirrors the vertex, so reject it
+    // before trusting any of the NDC values.
+    let clipped = clip.w <= f32::EPSILON || !(0.0..=1.0).contains(&ndc_z);
+    ProjectedVertex {
+        screen: if clipped {
+            Point2::new(f32::NAN, f32::NAN)
+        } else {
+            clip_to_screen(&clip, width, height)
+        },
+        ndc_z,
+        view_z: clip.w,
+        inv_w: 1.0 / clip.w,
+        clipped,
+    }
+}
+
+/// Divide the clip space point `clip` by its w and map it onto a
+/// `width * height` frame. Only meaningful for points in front of the eye.
+pub fn clip_to_screen(clip: &Vector4<f32>, width: u32, height: u32) -> Point2<f32> {
+    Point2::new(
+        (clip.x / clip.w + 1.0) * 0.5 * width as f32,
+        (1.0 - clip.y / clip.w) * 0.5 * height as f32,
+    )
+}
+
+/// The point that [`project_vertex`] would put at `screen` with depth
+/// `ndc_z`, given the inverse of its matrix.
+pub fn unproject_screen(
+    screen: Point2<f32>,
+    ndc_z: f32,
+    inverse_mvp: &Matrix4<f32>,
+    width: u32,
+    height: u32,
+) -> Point3<f32> {
+    let ndc_x = 2.0 * screen.x / width as f32 - 1.0;
+    let ndc_y = 1.0 - 2.0 * screen.y / height as f32;
+    let p = inverse_mvp * Vector4::new(ndc_x, ndc_y, ndc_z, 1.0);
+    Point3::from(p.xyz() / p.w)
+}
+
+/// A plane of the points `p` where `normal.dot(p) + d` is zero. Points on
+/// the side `normal` faces are in front of it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Plane {
+    pub normal: Vector3<f32>,
+    pub d: f32,
+}
+
+impl Plane {
+    /// The plane `a x + b y + c z + d = 0` for the coefficients `v`,
+    /// scaled so its normal has unit length and distances come out in
+    /// world units.
+    fn from_coefficients(v: Vector4<f32>) -> Self {
+        let normal = v.xyz();
+        let length = normal.norm();
+        let scale = if length > 0.0 { 1.0 / length } else { 1.0 };
+        Self {
+            normal: normal * scale,
+            d: v.w * scale,
+        }
+    }
+
+    /// How far `point` is in front of the plane, negative behind it.
+    pub fn signed_distance(&self, point: &Point3<f32>) -> f32 {
+        self.normal.dot(&point.coords) + self.d
+    }
+
+    /// The point where three planes meet, if no two of them are parallel.
+    pub fn intersection(a: &Plane, b: &Plane, c: &Plane) -> Option<Point3<f32>> {
+        let normals = Matrix3::from_rows(&[
+            a.normal.transpose(),
+            b.normal.transpose(),
+            c.normal.transpose(),
+        ]);
+        let inverse = normals.try_inverse()?;
+        Some(Point3::from(inverse * -Vector3::new(a.d, b.d, c.d)))
+    }
+}
+
+/// The planes bounding what the matrix `m` takes into view, facing inwards:
+/// left, right, bottom, top, then where NDC z is 0 and where it is 1.
+///
+/// Those last two bound the NDC z of `0.0..=1.0` the renderer draws, not
+/// nalgebra's `-1.0..=1.0`: they are the near and far planes for
+/// [`DepthConvention::Standard`], where the near one is where drawing
+/// starts a little beyond the near clip plane, and the far and near planes
+/// for [`DepthConvention::Reversed`]. For `proj * view` the planes are in
+/// world space, and for a full model-view-projection matrix in the model's
+/// own space.
+///
+/// [`DepthConvention::Standard`]: crate::depth::DepthConvention::Standard
+/// [`DepthConvention::Reversed`]: crate::depth::DepthConvention::Reversed
+pub fn extract_frustum_planes(m: &Matrix4<f32>) -> [Plane; 6] {
+    let row = |i: usize| m.row(i).transpose();
+    let (x, y, z, w) = (row(0), row(1), row(2), row(3));
+    [w + x, w - x, w + y, w - y, z, w - z].map(Plane::from_coefficients)
+}
+
+/// Whether any of the sphere around `center` reaches inside `planes`.
+/// Spheres near a corner of the frustum may pass without touching it.
+pub fn sphere_intersects_frustum(planes: &[Plane; 6], center: &Point3<f32>, radius: f32) -> bool {
+    planes
+        .iter()
+        .all(|plane| plane.signed_distance(center) >= -radius)
+}
+
+/// Whether any of the axis aligned box from `min` to `max` reaches inside
+/// `planes`. Like [`sphere_intersects_frustum`] it only rejects boxes
+/// wholly behind one plane.
+pub fn aabb_intersects_frustum(planes: &[Plane; 6], min: &Point3<f32>, max: &Point3<f32>) -> bool {
+    planes.iter().all(|plane| {
+        // The corner furthest along the plane's normal.
+        let corner = Point3::new(
+            if plane.normal.x >= 0.0 { max.x } else { min.x },
+            if plane.normal.y >= 0.0 { max.y } else { min.y },
+            if plane.normal.z >= 0.0 { max.z } else { min.z },
+        );
+        plane.signed_distance(&corner) >= 0.0
+    })
+}
@@ -0,0 +1,67 @@
+//! Shadows squashed flat onto a ground plane, a cheap stand-in for shadow
+//! maps when all that needs darkening is the floor.
+//!
+//! Each triangle of an object facing the light is projected along the
+//! light's rays onto the plane and drawn there in one translucent color,
+//! after the ground and before anything standing on it.
+
+use nalgebra::{Matrix4, Vector4};
+
+use crate::light::{Light, LightKind};
+use crate::mesh::Color;
+
+/// How far shadows reach from their caster by default, in world units.
+pub const PLANAR_SHADOW_RADIUS: f32 = 20.0;
+
+/// Height above the plane shadows are drawn at, so a depth-tested floor
+/// doesn't hide them.
+pub const PLANAR_SHADOW_LIFT: f32 = 0.01;
+
+/// Where and how [`World::planar_shadows`](crate::World::planar_shadows)
+/// are drawn.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PlanarShadowConfig {
+    /// Height of the horizontal ground plane. Objects whose bounding box
+    /// doesn't reach above it are the ground: they cast nothing.
+    pub plane_y: f32,
+    /// What shadows are drawn in. Its alpha is how dark they are.
+    pub color: Color,
+    /// Shadow triangles reaching farther than this along the plane from
+    /// their caster's origin are dropped, so a light low over the plane
+    /// doesn't smear shadows across the whole floor.
+    pub radius: f32,
+}
+
+impl Default for PlanarShadowConfig {
+    fn default() -> Self {
+        Self {
+            plane_y: 0.0,
+            color: Color {
+                r: 0,
+                g: 0,
+                b: 0,
+                a: 110,
+            },
+            radius: PLANAR_SHADOW_RADIUS,
+        }
+    }
+}
+
+/// The matrix flattening world space points onto the plane `y = plane_y`
+/// along the rays of `light`, or `None` when the light isn't above the
+/// plane. Points are divided by their `w` afterwards, which is only
+/// positive for points below a spot light.
+///
+/// A spot light shines from its position like a point light; a
+/// directional one along `target - position` from infinitely far away.
+pub fn projection(plane_y: f32, light: &Light) -> Option<Matrix4<f32>> {
+    let plane = Vector4::new(0.0, 1.0, 0.0, -plane_y);
+    let light = match light.kind {
+        LightKind::Directional => (light.position - light.target)
+            .try_normalize(f32::EPSILON)?
+            .push(0.0),
+        LightKind::Spot { .. } => light.position.to_homogeneous(),
+    };
+    let facing = plane.dot(&light);
+    (facing > 0.0).then(|| Matrix4::identity() * facing - light * plane.transpose())
+}
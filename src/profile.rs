@@ -0,0 +1,202 @@
+//! Per-stage frame timing, for finding out where a frame's time goes.
+//! Scopes are timed with [`ScopeTimer`] into a [`Profiler`], which only
+//! reads the clock while it is enabled.
+
+use std::cell::Cell;
+use std::fmt;
+use std::time::{Duration, Instant};
+
+/// A part of the frame timed on its own. Some stages are parts of others;
+/// see [`Stage::parent`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Stage {
+    /// Filling the frame with the background.
+    Clear,
+    /// Taking vertices to the screen and into view space.
+    Vertex,
+    /// Ordering objects and triangles back to front.
+    Sort,
+    /// Turning triangles into pixels: everything below.
+    Rasterize,
+    /// Culling and queueing each triangle, and lighting those too small to
+    /// be more than a point.
+    Lighting,
+    /// Filling the queued primitives' pixels, lighting each triangle at the
+    /// first of them it shows in.
+    Pixels,
+    /// The stats overlay, drawn over the finished frame.
+    Overlay,
+}
+
+impl Stage {
+    /// Every stage, each parent before its children.
+    pub const ALL: [Stage; 7] = [
+        Stage::Clear,
+        Stage::Vertex,
+        Stage::Sort,
+        Stage::Rasterize,
+        Stage::Lighting,
+        Stage::Pixels,
+        Stage::Overlay,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Stage::Clear => "clear",
+            Stage::Vertex => "vertex",
+            Stage::Sort => "sort",
+            Stage::Rasterize => "rasterize",
+            Stage::Lighting => "lighting",
+            Stage::Pixels => "pixels",
+            Stage::Overlay => "overlay",
+        }
+    }
+
+    /// The stage this one is a part of. Its time is counted in both.
+    pub fn parent(self) -> Option<Stage> {
+        match self {
+            Stage::Lighting | Stage::Pixels => Some(Stage::Rasterize),
+            _ => None,
+        }
+    }
+}
+
+/// Time spent in each [`Stage`] of one frame.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct StageTimes([Duration; Stage::ALL.len()]);
+
+impl StageTimes {
+    pub fn get(&self, stage: Stage) -> Duration {
+        self.0[stage as usize]
+    }
+
+    pub fn add(&mut self, stage: Stage, time: Duration) {
+        self.0[stage as usize] += time;
+    }
+
+    /// Every stage with its time, in [`Stage::ALL`] order.
+    pub fn iter(&self) -> impl Iterator<Item = (Stage, Duration)> + '_ {
+        Stage::ALL.iter().map(|&stage| (stage, self.get(stage)))
+    }
+
+    /// Whether nothing was timed.
+    pub fn is_empty(&self) -> bool {
+        self.0.iter().all(Duration::is_zero)
+    }
+}
+
+/// Collects the time of every [`ScopeTimer`] started from it. A disabled
+/// profiler never reads the clock, so leaving the scopes in costs nothing.
+#[derive(Debug, Default)]
+pub struct Profiler {
+    enabled: bool,
+    times: Cell<StageTimes>,
+}
+
+impl Profiler {
+    pub fn new(enabled: bool) -> Self {
+        Self {
+            enabled,
+            times: Cell::default(),
+        }
+    }
+
+    /// Time from now until the returned timer is dropped, counted towards
+    /// `stage`. Scopes can nest, and the same stage can be timed any number
+    /// of times a frame.
+    pub fn scope(&self, stage: Stage) -> ScopeTimer<'_> {
+        ScopeTimer {
+            profiler: self,
+            stage,
+            start: self.enabled.then(Instant::now),
+        }
+    }
+
+    /// Everything timed so far.
+    pub fn times(&self) -> StageTimes {
+        self.times.get()
+    }
+}
+
+/// Adds the time since it was made to a stage of its [`Profiler`] when
+/// dropped.
+#[must_use = "the scope ends when the timer is dropped"]
+pub struct ScopeTimer<'a> {
+    profiler: &'a Profiler,
+    stage: Stage,
+    start: Option<Instant>,
+}
+
+impl Drop for ScopeTimer<'_> {
+    fn drop(&mut self) {
+        if let Some(start) = self.start {
+            let mut times = self.profiler.times.get();
+            times.add(self.stage, start.elapsed());
+            self.profiler.times.set(times);
+        }
+    }
+}
+
+/// Per-stage times of every frame recorded, summed up as a mean and a 95th
+/// percentile.
+#[derive(Debug, Clone, Default)]
+pub struct ProfileSummary {
+    /// Milliseconds per frame, by stage.
+    samples: [Vec<f32>; Stage::ALL.len()],
+}
+
+impl ProfileSummary {
+    /// Add a frame. Frames where nothing was timed are skipped.
+    pub fn record(&mut self, times: &StageTimes) {
+        if times.is_empty() {
+            return;
+        }
+        for (stage, time) in times.iter() {
+            self.samples[stage as usize].push(time.as_secs_f32() * 1000.0);
+        }
+    }
+
+    /// Frames recorded.
+    pub fn frames(&self) -> usize {
+        self.samples[0].len()
+    }
+
+    /// Mean milliseconds per frame in `stage`.
+    pub fn mean(&self, stage: Stage) -> f32 {
+        let samples = &self.samples[stage as usize];
+        samples.iter().sum::<f32>() / samples.len().max(1) as f32
+    }
+
+    /// Milliseconds that 95% of frames spent at most in `stage`.
+    pub fn p95(&self, stage: Stage) -> f32 {
+        let mut samples = self.samples[stage as usize].clone();
+        if samples.is_empty() {
+            return 0.0;
+        }
+        samples.sort_by(f32::total_cmp);
+        let rank = (samples.len() as f32 * 0.95).ceil() as usize;
+        samples[rank.clamp(1, samples.len()) - 1]
+    }
+}
+
+impl fmt::Display for ProfileSummary {
+    /// One line per stage, children indented under their parent.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} frames, mean / p95 ms", self.frames())?;
+        for stage in Stage::ALL {
+            let indent = if stage.parent().is_some() {
+                "    "
+            } else {
+                "  "
+            };
+            write!(
+                f,
+                "\n{indent}{:<10} {:>8.3} {:>8.3}",
+                stage.name(),
+                self.mean(stage),
+                self.p95(stage)
+            )?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,120 @@
+//! Progressive refinement: while nothing changes, keep rendering the same
+//! view with sub-pixel jitter and average the frames into a supersampled,
+//! anti-aliased image.
+
+use nalgebra::Matrix4;
+
+use crate::color::{linear_to_srgb, srgb_to_linear};
+use crate::world::World;
+
+/// Accumulates jittered frames of an unchanging scene in linear light.
+pub struct Accumulator {
+    /// Stop taking samples once this many have been averaged.
+    pub max_samples: u32,
+    sum: Vec<f32>,
+    samples: u32,
+    width: u32,
+    height: u32,
+    fingerprint: Option<u64>,
+}
+
+impl Accumulator {
+    pub fn new(max_samples: u32) -> Self {
+        Self {
+            max_samples,
+            sum: Vec::new(),
+            samples: 0,
+            width: 0,
+            height: 0,
+            fingerprint: None,
+        }
+    }
+
+    /// Number of frames averaged into the current image.
+    pub fn samples(&self) -> u32 {
+        self.samples
+    }
+
+    /// True while more samples would still improve the image.
+    pub fn converging(&self) -> bool {
+        self.samples < self.max_samples
+    }
+
+    /// Throw away the accumulated samples.
+    pub fn reset(&mut self) {
+        self.samples = 0;
+        self.fingerprint = None;
+    }
+
+    /// Render `world` into `frame`. The first frame after any change is a
+    /// plain render; later ones add a jittered sample and write the average.
+    pub fn render(
+        &mut self,
+        world: &mut World,
+        view_mat: Matrix4<f32>,
+        frame: &mut [u8],
+        width: u32,
+        height: u32,
+    ) {
+        let fingerprint = world.fingerprint(&view_mat);
+        if self.fingerprint != Some(fingerprint) || self.width != width || self.height != height {
+            self.reset();
+            self.fingerprint = Some(fingerprint);
+            self.width = width;
+            self.height = height;
+            self.sum.clear();
+            self.sum.resize(frame.len(), 0.0);
+        }
+
+        if !self.converging() {
+            self.resolve(frame);
+            return;
+        }
+
+        let saved = world.projection_jitter;
+        world.projection_jitter = if self.samples == 0 {
+            (0.0, 0.0)
+        } else {
+            (halton(self.samples, 2) - 0.5, halton(self.samples, 3) - 0.5)
+        };
+        world.draw(view_mat, frame, width, height);
+        world.projection_jitter = saved;
+
+        if self.samples == 0 {
+            self.sum.fill(0.0);
+        }
+        for (sum, chunk) in self.sum.chunks_exact_mut(4).zip(frame.chunks_exact(4)) {
+            sum[0] += srgb_to_linear(chunk[0]);
+            sum[1] += srgb_to_linear(chunk[1]);
+            sum[2] += srgb_to_linear(chunk[2]);
+            sum[3] += chunk[3] as f32 / 255.0;
+        }
+        self.samples += 1;
+        if self.samples > 1 {
+            self.resolve(frame);
+        }
+    }
+
+    /// Write the average of the accumulated samples to `frame`.
+    fn resolve(&self, frame: &mut [u8]) {
+        let scale = 1.0 / self.samples.max(1) as f32;
+        for (sum, chunk) in self.sum.chunks_exact(4).zip(frame.chunks_exact_mut(4)) {
+            chunk[0] = linear_to_srgb(sum[0] * scale);
+            chunk[1] = linear_to_srgb(sum[1] * scale);
+            chunk[2] = linear_to_srgb(sum[2] * scale);
+            chunk[3] = (sum[3] * scale * 255.0).round() as u8;
+        }
+    }
+}
+
+/// The `index`th element of the Halton low discrepancy sequence in `base`.
+fn halton(mut index: u32, base: u32) -> f32 {
+    let mut result = 0.0;
+    let mut fraction = 1.0;
+    while index > 0 {
+        fraction /= base as f32;
+        result += fraction * (index % base) as f32;
+        index /= base;
+    }
+    result
+}
@@ -0,0 +1,821 @@
+use std::iter::Sum;
+use std::ops::{AddAssign, Range};
+use std::thread;
+
+use nalgebra::{Point2, Point3, Vector2, Vector3, Vector4};
+
+use crate::depth::{DepthConvention, DepthPlane};
+use crate::light::{Ambient, SceneLighting, ShadingModel, Surface, apply_lighting};
+use crate::mesh::Color;
+use crate::texture::{Texture, TextureHandle};
+
+/// A screen space triangle ready to be filled.
+pub(crate) struct RasterTriangle {
+    pub s1: Point2<f32>,
+    pub s2: Point2<f32>,
+    pub s3: Point2<f32>,
+    pub color: Color,
+    /// Unrounded red, green and blue of `color`. When set, every pixel is
+    /// rounded up or down by [`dither`] instead of using `color` as is.
+    pub dither: Option<[f32; 3]>,
+    /// The surface to light when the first pixel passes the depth test,
+    /// replacing `color` and `dither`, so a triangle that is hidden whole
+    /// is never lit. Its material's alpha must be `color`'s.
+    pub surface: Option<Surface>,
+    /// NDC z across the triangle, to test against the depth buffer. Opaque
+    /// triangles also write it. Without one the triangle is drawn over
+    /// whatever is there, depth buffer or not.
+    pub depth: Option<DepthPlane>,
+    /// Where the triangle lies on its texture. Each pixel then samples it,
+    /// tints it by `color` and lights it like `surface`.
+    pub uv: Option<TriangleUv>,
+}
+
+/// Texture coordinates across a triangle.
+#[derive(Clone, Copy)]
+pub(crate) struct TriangleUv {
+    pub texture: TextureHandle,
+    /// The UV at each corner, `s1` to `s3`, with its view space depth, so
+    /// they are interpolated with perspective and don't swim.
+    pub corners: [(Vector2<f32>, f32); 3],
+}
+
+impl TriangleUv {
+    /// The UV under pixel `(x, y)` of `tri`.
+    fn at(&self, tri: &RasterTriangle, x: u32, y: u32) -> (f32, f32) {
+        let p = Point2::new(x as f32, y as f32);
+        // Each corner's share of the pixel on screen, divided by its depth.
+        let weights = [(tri.s2, tri.s3), (tri.s3, tri.s1), (tri.s1, tri.s2)]
+            .map(|(a, b)| screen_cross(a, b, p).max(0.0));
+        let mut uv = Vector2::zeros();
+        let mut total = 0.0;
+        for (weight, (corner, depth)) in weights.into_iter().zip(self.corners) {
+            let weight = weight / depth.max(f32::EPSILON);
+            uv += corner * weight;
+            total += weight;
+        }
+        if total <= 0.0 {
+            let (corner, _) = self.corners[0];
+            return (corner.x, corner.y);
+        }
+        uv /= total;
+        (uv.x, uv.y)
+    }
+}
+
+/// How a triangle is searched for the pixels it covers. Both decide
+/// coverage with the same edge test, so they fill exactly the same pixels
+/// and only differ in speed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum RasterBackend {
+    /// Test every pixel in the triangle's bounding box.
+    #[default]
+    BoundingBox,
+    /// Walk the edges row by row and only test around the span between
+    /// them, which skips most of the box of a long thin triangle.
+    Scanline,
+}
+
+/// How [`rasterize`] fills triangles.
+#[derive(Clone, Copy, Default)]
+pub(crate) struct RasterSettings<'a> {
+    pub backend: RasterBackend,
+    /// Decide coverage in 28.4 fixed point instead of floats, see
+    /// [`FixedEdges`].
+    pub fixed_point: bool,
+    /// Which way the depth test goes, when there is a depth buffer.
+    pub depth: DepthConvention,
+    /// What lights [`RasterTriangle::surface`]. Triangles with one are
+    /// drawn in their unlit `color` without it.
+    pub lighting: Option<&'a SceneLighting<'a>>,
+    /// Textures by [`TextureHandle`], for [`RasterTriangle::uv`].
+    pub textures: &'a [Texture],
+}
+
+/// A square of `size` by `size` pixels centred on `center`, for drawing
+/// vertices as points.
+pub(crate) struct RasterPoint {
+    pub center: Point2<f32>,
+    pub size: u32,
+    pub color: Color,
+    /// NDC z of the point, when it is depth tested.
+    pub depth: Option<f32>,
+}
+
+/// A sphere impostor in view space with its clamped screen bounds.
+pub(crate) struct RasterSphere {
+    pub center: Point3<f32>,
+    pub radius: f32,
+    pub color: Color,
+    /// Fraction of the light reaching the sphere, see [`crate::Light::falloff`].
+    pub light: f32,
+    pub min: (i32, i32),
+    pub max: (i32, i32),
+}
+
+/// Per-frame state shared by every sphere impostor.
+pub(crate) struct SphereShading {
+    /// Homogeneous view space point under pixel (0, 0), and how it changes
+    /// per pixel step in x and y. Unprojecting is linear, so a pixel's eye
+    /// ray is `ray_origin + x * ray_dx + y * ray_dy`.
+    pub ray_origin: Vector4<f32>,
+    pub ray_dx: Vector4<f32>,
+    pub ray_dy: Vector4<f32>,
+    /// Size of one pixel at unit distance, for the anti-aliased edge.
+    pub pixel_size: f32,
+    /// Direction the light travels, in view space.
+    pub light_dir: Vector3<f32>,
+    pub ambient: Ambient,
+    /// World +y in view space, for hemisphere ambient.
+    pub up: Vector3<f32>,
+    pub intensity: f32,
+    pub model: ShadingModel,
+    pub fog: Option<crate::fog::Fog>,
+    pub gamma: bool,
+    /// Near and far planes when drawing depth instead of shading, see
+    /// [`crate::world::RenderMode::Depth`].
+    pub depth_range: Option<(f32, f32)>,
+    /// The projection's z row, `(m22, m23)`, to find the NDC z of each hit
+    /// for the depth buffer. `None` when there is none.
+    pub depth_projection: Option<(f32, f32)>,
+}
+
+/// Something the rasterizer can fill.
+pub(crate) enum Primitive<'a> {
+    Triangle(RasterTriangle),
+    Sphere(RasterSphere, &'a SphereShading),
+    Point(RasterPoint),
+}
+
+impl Primitive<'_> {
+    /// Whether every pixel this draws is depth tested, written to the
+    /// depth buffer and covers what was there, so drawing it earlier or
+    /// later among other solid primitives leaves the same picture. Spheres
+    /// never are: their edges blend.
+    pub(crate) fn is_solid(&self) -> bool {
+        match self {
+            Primitive::Triangle(tri) => tri.depth.is_some() && tri.color.a == 255,
+            Primitive::Point(point) => point.depth.is_some() && point.color.a == 255,
+            Primitive::Sphere(..) => false,
+        }
+    }
+}
+
+/// What filling some primitives did to the pixels they covered.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) struct PixelCounts {
+    /// Pixels written, overdraw included.
+    pub written: usize,
+    /// Pixels covered but hidden by what the depth buffer already held,
+    /// and so left as they were.
+    pub depth_rejected: usize,
+    /// Triangle surfaces lit on reaching their first visible pixel.
+    pub lit: usize,
+}
+
+impl AddAssign for PixelCounts {
+    fn add_assign(&mut self, other: Self) {
+        self.written += other.written;
+        self.depth_rejected += other.depth_rejected;
+        self.lit += other.lit;
+    }
+}
+
+impl Sum for PixelCounts {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::default(), |mut total, counts| {
+            total += counts;
+            total
+        })
+    }
+}
+
+/// Fill every primitive of `tris`, in order, into `frame`, returning how
+/// many pixel writes that took, overdraw included, and how many covered
+/// pixels the depth test threw away. Primitives with a depth are tested
+/// against `depth`, one value per pixel, when it is given.
+///
+/// With `threads > 1` the frame is split into horizontal bands that are
+/// rasterized concurrently. Every band walks the whole list in the same order
+/// and only touches its own rows, so each pixel sees exactly the same sequence
+/// of writes no matter how many threads there are or how they are scheduled.
+pub(crate) fn rasterize(
+    tris: &[Primitive],
+    frame: &mut [u8],
+    mut depth: Option<&mut [f32]>,
+    width: u32,
+    height: u32,
+    threads: usize,
+    settings: RasterSettings,
+) -> PixelCounts {
+    let threads = threads.clamp(1, height.max(1) as usize);
+    if threads == 1 {
+        return tris
+            .iter()
+            .map(|prim| {
+                draw_primitive_rows(
+                    prim,
+                    frame,
+                    depth.as_deref_mut(),
+                    width,
+                    0..height,
+                    settings,
+                )
+            })
+            .sum();
+    }
+
+    // Each band counts its own pixels; they are summed once all are done.
+    let band_rows = height.div_ceil(threads as u32);
+    let band_len = (band_rows * width * 4) as usize;
+    let mut depth_bands = depth.map(|depth| depth.chunks_mut((band_rows * width) as usize));
+    thread::scope(|scope| {
+        let bands: Vec<_> = frame
+            .chunks_mut(band_len)
+            .enumerate()
+            .map(|(i, band)| {
+                let first = i as u32 * band_rows;
+                let rows = first..(first + band_rows).min(height);
+                let mut depth = depth_bands.as_mut().and_then(Iterator::next);
+                scope.spawn(move || {
+                    tris.iter()
+                        .map(|prim| {
+                            let depth = depth.as_deref_mut();
+                            draw_primitive_rows(prim, band, depth, width, rows.clone(), settings)
+                        })
+                        .sum::<PixelCounts>()
+                })
+            })
+            .collect();
+        bands
+            .into_iter()
+            .map(|band| band.join().expect("raster band panicked"))
+            .sum()
+    })
+}
+
+fn draw_primitive_rows(
+    prim: &Primitive,
+    band: &mut [u8],
+    depth: Option<&mut [f32]>,
+    width: u32,
+    rows: Range<u32>,
+    settings: RasterSettings,
+) -> PixelCounts {
+    match prim {
+        Primitive::Triangle(tri) => draw_triangle_rows(tri, band, depth, width, rows, settings),
+        Primitive::Sphere(sphere, shading) => {
+            draw_sphere_rows(sphere, shading, band, depth, width, rows, settings.depth)
+        }
+        Primitive::Point(point) => draw_point_rows(point, band, depth, width, rows, settings.depth),
+    }
+}
+
+/// Test `value` against pixel `index` of a depth band, storing it if it
+/// passes and `write` is set. Always passes without a band or a value.
+fn depth_test(
+    depth: &mut Option<&mut [f32]>,
+    index: usize,
+    value: Option<f32>,
+    write: bool,
+    convention: DepthConvention,
+) -> bool {
+    let (Some(depth), Some(value)) = (depth, value) else {
+        return true;
+    };
+    let Some(stored) = depth.get_mut(index) else {
+        return true;
+    };
+    if !convention.passes(value, *stored) {
+        return false;
+    }
+    if write {
+        *stored = value;
+    }
+    true
+}
+
+/// Fill the pixels of a point's square that fall inside `rows`.
+fn draw_point_rows(
+    point: &RasterPoint,
+    band: &mut [u8],
+    mut depth: Option<&mut [f32]>,
+    width: u32,
+    rows: Range<u32>,
+    convention: DepthConvention,
+) -> PixelCounts {
+    let Some((min_x, max_x)) = point_span(point.center.x, point.size, 0..width) else {
+        return PixelCounts::default();
+    };
+    let Some((min_y, max_y)) = point_span(point.center.y, point.size, rows.clone()) else {
+        return PixelCounts::default();
+    };
+    let color = &point.color;
+    let mut counts = PixelCounts::default();
+    for y in min_y..=max_y {
+        for x in min_x..=max_x {
+            let index = ((y - rows.start) * width + x) as usize;
+            if !depth_test(&mut depth, index, point.depth, color.a == 255, convention) {
+                counts.depth_rejected += 1;
+                continue;
+            }
+            let pixel = &mut band[index * 4..index * 4 + 4];
+            if color.a == 255 {
+                pixel.copy_from_slice(&[color.r, color.g, color.b, color.a]);
+            } else {
+                blend(pixel, color);
+            }
+            counts.written += 1;
+        }
+    }
+    counts
+}
+
+/// Ray trace a sphere over its screen bounds. Each pixel shoots a ray from
+/// the eye, shades the analytic normal where it hits, and blends the edge by
+/// how much of the pixel footprint the sphere covers.
+fn draw_sphere_rows(
+    sphere: &RasterSphere,
+    shading: &SphereShading,
+    band: &mut [u8],
+    mut depth: Option<&mut [f32]>,
+    width: u32,
+    rows: Range<u32>,
+    convention: DepthConvention,
+) -> PixelCounts {
+    let min_y = sphere.min.1.max(rows.start as i32);
+    let max_y = sphere.max.1.min(rows.end as i32 - 1);
+    let min_x = sphere.min.0.max(0);
+    let max_x = sphere.max.0.min(width as i32 - 1);
+    if min_y > max_y || min_x > max_x {
+        return PixelCounts::default();
+    }
+    let mut counts = PixelCounts::default();
+    let c = sphere.center.coords;
+    let r = sphere.radius;
+    let c_len2 = c.norm_squared();
+    let fog = shading.fog.map(|fog| (fog.color, fog.factor(c.norm())));
+
+    for y in min_y..=max_y {
+        let row = shading.ray_origin + shading.ray_dy * y as f32;
+        for x in min_x..=max_x {
+            let p = row + shading.ray_dx * x as f32;
+            let dir = p.xyz() / p.w;
+            let dir = dir / dir.norm();
+
+            // Closest approach of the ray to the center.
+            let b = dir.dot(&c);
+            if b <= 0.0 {
+                continue;
+            }
+            let miss2 = (c_len2 - b * b).max(0.0);
+            let edge = r + 0.5 * b * shading.pixel_size;
+            if miss2 >= edge * edge {
+                continue;
+            }
+            let miss = miss2.sqrt();
+            let coverage = ((r - miss) / (b * shading.pixel_size) + 0.5).clamp(0.0, 1.0);
+            let t = if miss2 <= r * r {
+                b - (r * r - miss2).sqrt()
+            } else {
+                b
+            };
+            let hit = dir * t;
+            let index = ((y as u32 - rows.start) * width + x as u32) as usize;
+            let hit_depth = shading
+                .depth_projection
+                .map(|(a, b)| (a * hit.z + b) / -hit.z);
+            // Only the solid middle hides what is behind it.
+            let solid = coverage >= 1.0 && sphere.color.a == 255;
+            if !depth_test(&mut depth, index, hit_depth, solid, convention) {
+                counts.depth_rejected += 1;
+                continue;
+            }
+
+            let mut color = if let Some((near, far)) = shading.depth_range {
+                depth_gray(-hit.z, near, far, sphere.color.a)
+            } else {
+                let normal = (hit - c).normalize();
+                let diffuse = shading.model.diffuse(
+                    (-shading.light_dir.dot(&normal) * shading.intensity * sphere.light)
+                        .clamp(0.0, 1.0),
+                );
+                let (fog_color, fog) = fog.unwrap_or((sphere.color, 0.0));
+                let ambient = shading.ambient.light(normal.dot(&shading.up));
+                apply_lighting(
+                    &sphere.color,
+                    ambient.map(|ambient| ambient + diffuse),
+                    fog_color,
+                    fog,
+                    shading.gamma,
+                )
+            };
+            color.a = (color.a as f32 * coverage).round() as u8;
+
+            let pixel = &mut band[index * 4..index * 4 + 4];
+            if color.a == 255 {
+                pixel.copy_from_slice(&[color.r, color.g, color.b, color.a]);
+            } else {
+                blend(pixel, &color);
+            }
+            counts.written += 1;
+        }
+    }
+    counts
+}
+
+/// Gray for a view space `depth`, linear between white at `near` and black
+/// at `far`.
+pub(crate) fn depth_gray(depth: f32, near: f32, far: f32, alpha: u8) -> Color {
+    let t = ((depth - near) / (far - near)).clamp(0.0, 1.0);
+    let v = (255.0 * (1.0 - t)).round() as u8;
+    Color {
+        r: v,
+        g: v,
+        b: v,
+        a: alpha,
+    }
+}
+
+/// Fill the screen space triangle `t1 t2 t3` with a flat color. Colors with
+/// alpha below 255 are blended over what is already in the frame.
+///
+/// `frame` is tightly packed RGBA, `width * height * 4` bytes.
+pub fn draw_triangle(
+    t1: Point2<f32>,
+    t2: Point2<f32>,
+    t3: Point2<f32>,
+    color: &Color,
+    frame: &mut [u8],
+    width: u32,
+    height: u32,
+) {
+    let tri = RasterTriangle {
+        s1: t1,
+        s2: t2,
+        s3: t3,
+        color: *color,
+        dither: None,
+        surface: None,
+        depth: None,
+        uv: None,
+    };
+    draw_triangle_rows(
+        &tri,
+        frame,
+        None,
+        width,
+        0..height,
+        RasterSettings::default(),
+    );
+}
+
+/// 4x4 Bayer matrix, the order in which pixels of a tile round up.
+const BAYER: [[u8; 4]; 4] = [[0, 8, 2, 10], [12, 4, 14, 6], [3, 11, 1, 9], [15, 7, 13, 5]];
+
+/// Round a channel `value` in `0.0..=255.0` up or down depending on the
+/// pixel `x, y`, so that a 4x4 tile averages out to the exact value. The
+/// pattern is fixed per pixel and does not shimmer between frames.
+pub fn dither(value: f32, x: u32, y: u32) -> u8 {
+    let threshold = (BAYER[(y % 4) as usize][(x % 4) as usize] as f32 + 0.5) / 16.0;
+    (value + threshold).floor().clamp(0.0, 255.0) as u8
+}
+
+/// Fill the part of a triangle that falls in `rows`. `band` holds exactly
+/// those rows, so row `rows.start` is at offset 0.
+fn draw_triangle_rows(
+    tri: &RasterTriangle,
+    band: &mut [u8],
+    mut depth: Option<&mut [f32]>,
+    width: u32,
+    rows: Range<u32>,
+    settings: RasterSettings,
+) -> PixelCounts {
+    let (x1, y1) = (tri.s1.x, tri.s1.y);
+    let (x2, y2) = (tri.s2.x, tri.s2.y);
+    let (x3, y3) = (tri.s3.x, tri.s3.y);
+    if ![x1, y1, x2, y2, x3, y3].iter().all(|c| c.is_finite()) {
+        return PixelCounts::default();
+    }
+    let Some((min_x, max_x)) = pixel_span(x1.min(x2).min(x3), x1.max(x2).max(x3), 0..width) else {
+        return PixelCounts::default();
+    };
+    let Some((min_y, max_y)) = pixel_span(y1.min(y2).min(y3), y1.max(y2).max(y3), rows.clone())
+    else {
+        return PixelCounts::default();
+    };
+
+    let opaque = tri.color.a == 255;
+    let edge = |(ax, ay): (f32, f32), (bx, by): (f32, f32), (px, py): (f32, f32)| -> f32 {
+        (py - ay) * (bx - ax) - (px - ax) * (by - ay)
+    };
+    let fixed = settings.fixed_point.then(|| FixedEdges::new(tri)).flatten();
+    // The one coverage test both backends share. Along a row each edge
+    // value only ever grows or only shrinks with x, even in floating point,
+    // so the covered pixels of a row are always one unbroken span.
+    let covers = |x: u32, y: u32| match &fixed {
+        Some(fixed) => fixed.covers(x, y),
+        None => {
+            let p = (x as f32, y as f32);
+            edge((x2, y2), (x3, y3), p) >= 0.0
+                && edge((x3, y3), (x1, y1), p) >= 0.0
+                && edge((x1, y1), (x2, y2), p) >= 0.0
+        }
+    };
+    let mut counts = PixelCounts::default();
+    // Lit by the first pixel through the depth test, once for the whole
+    // triangle.
+    let mut shade = None;
+    let mut fill = |x: u32, y: u32| {
+        let index = (y - rows.start) as usize * width as usize + x as usize;
+        let z = tri.depth.map(|plane| plane.at(x as f32, y as f32));
+        if !depth_test(&mut depth, index, z, opaque, settings.depth) {
+            counts.depth_rejected += 1;
+            return;
+        }
+        let (color, exact, light) =
+            *shade.get_or_insert_with(|| match tri.surface.as_ref().zip(settings.lighting) {
+                Some((surface, lighting)) => {
+                    counts.lit += 1;
+                    let light = lighting.light(surface);
+                    let (color, exact) = light.apply(&surface.material.color);
+                    (color, exact, Some(light))
+                }
+                None => (tri.color, tri.dither, None),
+            });
+        let texture = tri
+            .uv
+            .as_ref()
+            .and_then(|uv| Some((uv, settings.textures.get(uv.texture.0)?)));
+        let (color, exact) = match texture {
+            Some((uv, texture)) => {
+                let (u, v) = uv.at(tri, x, y);
+                let texel = modulate(texture.sample(u, v), tri.color);
+                match light {
+                    Some(light) => light.apply(&texel),
+                    None => (texel, None),
+                }
+            }
+            None => (color, exact),
+        };
+        let index = index * 4;
+        if let Some(pixel) = band.get_mut(index..index + 4) {
+            let color = match exact {
+                Some([r, g, b]) => &Color {
+                    r: dither(r, x, y),
+                    g: dither(g, x, y),
+                    b: dither(b, x, y),
+                    a: color.a,
+                },
+                None => &color,
+            };
+            if opaque {
+                pixel.copy_from_slice(&[color.r, color.g, color.b, color.a]);
+            } else {
+                blend(pixel, color);
+            }
+            counts.written += 1;
+        }
+    };
+
+    match settings.backend {
+        RasterBackend::BoundingBox => {
+            for y in min_y..=max_y {
+                for x in min_x..=max_x {
+                    if covers(x, y) {
+                        fill(x, y);
+                    }
+                }
+            }
+        }
+        RasterBackend::Scanline => {
+            let mut by_y = [(x1, y1), (x2, y2), (x3, y3)];
+            by_y.sort_by(|a, b| a.1.total_cmp(&b.1));
+            let [top, mid, bottom] = by_y;
+            for y in min_y..=max_y {
+                let row = y as f32;
+                // Where the long edge and the short edge beside this row
+                // cross it. Only a guess at the span: the edge test has the
+                // final say, a pixel or so either side.
+                let (long_lo, long_hi) = edge_crossing(top, bottom, row);
+                let (short_lo, short_hi) = if row < mid.1 {
+                    edge_crossing(top, mid, row)
+                } else {
+                    edge_crossing(mid, bottom, row)
+                };
+                let Some((start, end)) = pixel_span(
+                    long_lo.min(short_lo) - 1.0,
+                    long_hi.max(short_hi) + 1.0,
+                    min_x..max_x + 1,
+                ) else {
+                    continue;
+                };
+                let Some(mut x) = (start..=end).find(|&x| covers(x, y)) else {
+                    continue;
+                };
+                while x > min_x && covers(x - 1, y) {
+                    x -= 1;
+                }
+                while x <= max_x && covers(x, y) {
+                    fill(x, y);
+                    x += 1;
+                }
+            }
+        }
+    }
+    counts
+}
+
+/// `texel` tinted by `color`, taking `color`'s alpha.
+fn modulate(texel: Color, color: Color) -> Color {
+    let channel = |t: u8, c: u8| ((t as u32 * c as u32 + 127) / 255) as u8;
+    Color {
+        r: channel(texel.r, color.r),
+        g: channel(texel.g, color.g),
+        b: channel(texel.b, color.b),
+        a: color.a,
+    }
+}
+
+/// Sub-pixel steps per pixel of 28.4 fixed point.
+const SUBPIXELS: f32 = 16.0;
+/// Furthest a vertex may be from the frame's origin, in pixels, for the
+/// fixed point edge test.
+///
+/// Inside it a snapped coordinate is below 2^24 in magnitude, so the
+/// difference of two is below 2^25, each product in an edge function below
+/// 2^50, and the edge function itself below 2^51: comfortably inside an
+/// `i64`. Triangles reaching further out, which only happens for vertices
+/// almost level with the eye, use the float test instead.
+const GUARD_BAND: f32 = (1 << 20) as f32;
+
+/// A triangle snapped to 1/16 of a pixel, with coverage decided in integer
+/// math so the same triangle covers the same pixels on every machine.
+struct FixedEdges {
+    verts: [(i64, i64); 3],
+}
+
+impl FixedEdges {
+    /// Snap `tri`, or `None` if it reaches outside [`GUARD_BAND`].
+    fn new(tri: &RasterTriangle) -> Option<Self> {
+        let snap = |c: f32| (c * SUBPIXELS).round() as i64;
+        let mut verts = [(0, 0); 3];
+        for (vert, s) in verts.iter_mut().zip([tri.s1, tri.s2, tri.s3]) {
+            if s.x.abs() > GUARD_BAND || s.y.abs() > GUARD_BAND {
+                return None;
+            }
+            *vert = (snap(s.x), snap(s.y));
+        }
+        Some(Self { verts })
+    }
+
+    fn covers(&self, x: u32, y: u32) -> bool {
+        const LIMIT: i64 = 1 << 25;
+        let p = (x as i64 * SUBPIXELS as i64, y as i64 * SUBPIXELS as i64);
+        let edge = |(ax, ay): (i64, i64), (bx, by): (i64, i64)| -> i64 {
+            let (dx, dy, px, py) = (bx - ax, by - ay, p.0 - ax, p.1 - ay);
+            debug_assert!(
+                [dx, dy, px, py].iter().all(|d| d.abs() < LIMIT),
+                "fixed point edge outside the guard band"
+            );
+            py * dx - px * dy
+        };
+        let [v1, v2, v3] = self.verts;
+        edge(v2, v3) >= 0 && edge(v3, v1) >= 0 && edge(v1, v2) >= 0
+    }
+}
+
+/// The x range where the edge `a b` crosses row `y`, clamped to the edge's
+/// ends. A flat edge covers its whole length.
+fn edge_crossing(a: (f32, f32), b: (f32, f32), y: f32) -> (f32, f32) {
+    let dy = b.1 - a.1;
+    if dy.abs() <= f32::EPSILON {
+        return (a.0.min(b.0), a.0.max(b.0));
+    }
+    let x = a.0 + ((y - a.1) / dy).clamp(0.0, 1.0) * (b.0 - a.0);
+    (x, x)
+}
+
+/// Whether the bounding box of a screen space triangle touches any pixel of
+/// a `width * height` frame. Triangles failing this can be dropped before
+/// any shading is worked out.
+pub(crate) fn on_screen(
+    s1: Point2<f32>,
+    s2: Point2<f32>,
+    s3: Point2<f32>,
+    width: u32,
+    height: u32,
+) -> bool {
+    let xs = (s1.x.min(s2.x).min(s3.x), s1.x.max(s2.x).max(s3.x));
+    let ys = (s1.y.min(s2.y).min(s3.y), s1.y.max(s2.y).max(s3.y));
+    pixel_span(xs.0, xs.1, 0..width).is_some() && pixel_span(ys.0, ys.1, 0..height).is_some()
+}
+
+/// Whether a point's square touches any pixel of a `width * height` frame.
+pub(crate) fn point_on_screen(center: Point2<f32>, size: u32, width: u32, height: u32) -> bool {
+    point_span(center.x, size, 0..width).is_some()
+        && point_span(center.y, size, 0..height).is_some()
+}
+
+/// First and last pixel, inside `range`, of the `size` pixels centred on
+/// `center` along one axis, or `None` if they miss the range or `center`
+/// isn't finite. Pixel centres sit on whole coordinates, as in the
+/// triangle edge test, and the casts saturate like [`pixel_span`]'s.
+fn point_span(center: f32, size: u32, range: Range<u32>) -> Option<(u32, u32)> {
+    if !center.is_finite() || size == 0 {
+        return None;
+    }
+    let first = (center - (size - 1) as f32 / 2.0).round() as i64;
+    let last = first + size as i64 - 1;
+    let (first, last) = (
+        first.max(range.start as i64),
+        last.min(range.end as i64 - 1),
+    );
+    (first <= last).then_some((first as u32, last as u32))
+}
+
+/// First and last pixel, inside `range`, of the bounding span `lo..=hi` of
+/// a triangle, or `None` if the span misses the range. Float to integer
+/// casts saturate, so coordinates of any finite size are safe.
+fn pixel_span(lo: f32, hi: f32, range: Range<u32>) -> Option<(u32, u32)> {
+    let first = (lo as i64).max(range.start as i64);
+    let last = ((hi + 1.0) as i64).min(range.end as i64 - 1);
+    (first <= last).then_some((first as u32, last as u32))
+}
+
+/// Source-over blend `color` onto an RGBA `pixel`.
+pub(crate) fn blend(pixel: &mut [u8], color: &Color) {
+    let a = color.a as u32;
+    let mix =
+        |src: u8, dst: u8| -> u8 { ((src as u32 * a + dst as u32 * (255 - a) + 127) / 255) as u8 };
+    pixel[0] = mix(color.r, pixel[0]);
+    pixel[1] = mix(color.g, pixel[1]);
+    pixel[2] = mix(color.b, pixel[2]);
+    pixel[3] = (a + (pixel[3] as u32 * (255 - a) + 127) / 255) as u8;
+}
+
+/// Two triangles covering the segment `p q` drawn `width` pixels wide, with
+/// square caps so joined segments leave no gap at the corners.
+pub(crate) fn thick_line(
+    p: Point2<f32>,
+    q: Point2<f32>,
+    width: f32,
+    color: Color,
+) -> [RasterTriangle; 2] {
+    let half = width * 0.5;
+    let along = (q - p).try_normalize(f32::EPSILON).unwrap_or(Vector2::x()) * half;
+    let across = Vector2::new(-along.y, along.x);
+    let (p, q) = (p - along, q + along);
+    let corners = [p + across, q + across, q - across, p - across];
+    let tri = |a: Point2<f32>, b: Point2<f32>, c: Point2<f32>| {
+        // The fill only accepts one winding.
+        let (b, c) = if is_front_facing(a, b, c) {
+            (b, c)
+        } else {
+            (c, b)
+        };
+        RasterTriangle {
+            s1: a,
+            s2: b,
+            s3: c,
+            color,
+            dither: None,
+            surface: None,
+            depth: None,
+            uv: None,
+        }
+    };
+    [
+        tri(corners[0], corners[1], corners[2]),
+        tri(corners[0], corners[2], corners[3]),
+    ]
+}
+
+/// True if the triangle faces the cam. False, we dont need to draw it.
+pub fn is_front_facing(p1: Point2<f32>, p2: Point2<f32>, p3: Point2<f32>) -> bool {
+    screen_cross(p1, p2, p3) > 0.0
+}
+
+/// Twice the signed area of a screen space triangle, positive for front
+/// faces.
+pub fn screen_cross(p1: Point2<f32>, p2: Point2<f32>, p3: Point2<f32>) -> f32 {
+    (p2.x - p1.x) * (p3.y - p1.y) - (p2.y - p1.y) * (p3.x - p1.x)
+}
+
+/// The larger of the width and height of a screen space triangle's
+/// bounding box, in pixels.
+pub fn screen_extent(p1: Point2<f32>, p2: Point2<f32>, p3: Point2<f32>) -> f32 {
+    let width = p1.x.max(p2.x).max(p3.x) - p1.x.min(p2.x).min(p3.x);
+    let height = p1.y.max(p2.y).max(p3.y) - p1.y.min(p2.y).min(p3.y);
+    width.max(height)
+}
+
+/// How thin a screen space triangle is: its height over its longest side,
+/// in pixels. Zero for a degenerate triangle.
+pub fn sliver_width(p1: Point2<f32>, p2: Point2<f32>, p3: Point2<f32>) -> f32 {
+    let longest = (p2 - p1).norm().max((p3 - p2).norm()).max((p1 - p3).norm());
+    if longest == 0.0 {
+        return 0.0;
+    }
+    screen_cross(p1, p2, p3).abs() / longest
+}
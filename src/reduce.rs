@@ -0,0 +1,127 @@
+//! Cutting a scene that shows a bug down to a small one that still does,
+//! for bug reports.
+//!
+//! [`reduce`] takes pieces out of a [`SceneFile`] and keeps each cut only
+//! while a check on the scene, like "drawing it panics", still fails. It
+//! tries taking out models and boxes many at a time and then fewer
+//! (delta debugging), then the light and the background, then swapping
+//! each model left for a [`SceneBox`] around it, over and over until no
+//! single cut keeps the check failing.
+
+use crate::mesh::obj::ObjMesh;
+use crate::mesh::{ImportOptions, Mesh};
+use crate::scene_file::{BOX_COLOR, SceneBox, SceneFile, SceneModel};
+
+/// One of the things [`reduce`] can take out of a scene.
+#[derive(Debug, Clone)]
+enum Part {
+    Model(SceneModel),
+    Box(SceneBox),
+}
+
+/// The smallest scene cut down from `scene` for which `still_fails`
+/// returns true, or `scene` itself when it doesn't fail to begin with.
+/// Only the camera always stays. `still_fails` is called with every cut
+/// tried, so a check that draws the scene should draw it from scratch.
+pub fn reduce(scene: &SceneFile, mut still_fails: impl FnMut(&SceneFile) -> bool) -> SceneFile {
+    let mut scene = scene.clone();
+    if !still_fails(&scene) {
+        return scene;
+    }
+    loop {
+        let before = scene.clone();
+        scene = remove_parts(scene, &mut still_fails);
+        for strip in [
+            |scene: &mut SceneFile| scene.light = None,
+            |scene: &mut SceneFile| scene.clear_color = None,
+            |scene: &mut SceneFile| scene.gradient = None,
+        ] {
+            let mut cut = scene.clone();
+            strip(&mut cut);
+            if cut != scene && still_fails(&cut) {
+                scene = cut;
+            }
+        }
+        let mut i = 0;
+        while i < scene.models.len() {
+            if let Some(proxy) = proxy(&scene.models[i]) {
+                let mut cut = scene.clone();
+                cut.models.remove(i);
+                cut.boxes.push(proxy);
+                if still_fails(&cut) {
+                    scene = cut;
+                    continue;
+                }
+            }
+            i += 1;
+        }
+        if scene == before {
+            return scene;
+        }
+    }
+}
+
+/// `scene` with as many models and boxes taken out as keep it failing,
+/// trying them in halves, then quarters and so on down to one at a time.
+fn remove_parts(scene: SceneFile, still_fails: &mut impl FnMut(&SceneFile) -> bool) -> SceneFile {
+    let with_parts = |parts: &[Part]| {
+        let mut cut = SceneFile {
+            models: Vec::new(),
+            boxes: Vec::new(),
+            ..scene.clone()
+        };
+        for part in parts {
+            match part {
+                Part::Model(model) => cut.models.push(model.clone()),
+                Part::Box(scene_box) => cut.boxes.push(scene_box.clone()),
+            }
+        }
+        cut
+    };
+    let mut parts: Vec<Part> = scene
+        .models
+        .iter()
+        .cloned()
+        .map(Part::Model)
+        .chain(scene.boxes.iter().cloned().map(Part::Box))
+        .collect();
+    let mut pieces = 2;
+    while !parts.is_empty() {
+        let pieces_now = pieces.min(parts.len());
+        let size = parts.len().div_ceil(pieces_now);
+        let kept = (0..parts.len()).step_by(size).find_map(|start| {
+            let rest: Vec<Part> = parts[..start]
+                .iter()
+                .chain(parts.get(start + size..).unwrap_or_default())
+                .cloned()
+                .collect();
+            still_fails(&with_parts(&rest)).then_some(rest)
+        });
+        match kept {
+            Some(rest) => {
+                parts = rest;
+                pieces = (pieces_now - 1).max(2);
+            }
+            None if pieces_now == parts.len() => break,
+            None => pieces = (pieces_now * 2).min(parts.len()),
+        }
+    }
+    with_parts(&parts)
+}
+
+/// A box around `model` where the scene places it, in the color of its
+/// first triangle, or `None` when the model can't be read.
+fn proxy(model: &SceneModel) -> Option<SceneBox> {
+    let mesh = ObjMesh::from_path(&model.path, ImportOptions::default()).ok()?;
+    let (min, max) = mesh.aabb();
+    let color = mesh
+        .primitives()
+        .triangles()
+        .next()
+        .map_or(BOX_COLOR, |tri| tri.color);
+    Some(SceneBox {
+        min: model.position + min.coords * model.scale,
+        max: model.position + max.coords * model.scale,
+        color,
+    })
+}
@@ -0,0 +1,148 @@
+//! Rendering at a different resolution than the window, as a dial between
+//! speed and sharpness: the scene is drawn `render_scale` times the window
+//! size and stretched to fit.
+
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use crate::color::{linear_to_srgb, srgb_to_linear};
+
+pub const MIN_RENDER_SCALE: f32 = 0.25;
+pub const MAX_RENDER_SCALE: f32 = 2.0;
+/// How far one key press moves the scale.
+pub const RENDER_SCALE_STEP: f32 = 0.25;
+
+/// The rendered image size for a `width` by `height` window at `scale`,
+/// clamped to [`MIN_RENDER_SCALE`]`..=`[`MAX_RENDER_SCALE`] and never
+/// smaller than one pixel.
+pub fn scaled_size(width: u32, height: u32, scale: f32) -> (u32, u32) {
+    let scale = scale.clamp(MIN_RENDER_SCALE, MAX_RENDER_SCALE);
+    let scaled = |n: u32| ((n as f32 * scale).round() as u32).max(1);
+    (scaled(width), scaled(height))
+}
+
+/// `scale` moved `steps` key presses up (or down, when negative), snapped
+/// to the step size and kept in range.
+pub fn step_render_scale(scale: f32, steps: i32) -> f32 {
+    let snapped = (scale / RENDER_SCALE_STEP).round() + steps as f32;
+    (snapped * RENDER_SCALE_STEP).clamp(MIN_RENDER_SCALE, MAX_RENDER_SCALE)
+}
+
+/// Stretch the RGBA image `src` over all of `dst`. Every destination pixel
+/// averages, in linear light, the source pixels it covers; when enlarging
+/// that is a single pixel, copied as is.
+pub fn resample(
+    src: &[u8],
+    src_width: u32,
+    src_height: u32,
+    dst: &mut [u8],
+    dst_width: u32,
+    dst_height: u32,
+) {
+    // The source range under destination pixel `i` out of `dst` along an
+    // axis of `src` pixels, always at least one pixel wide.
+    let span = |i: u32, src: u32, dst: u32| {
+        let start = (i as u64 * src as u64 / dst as u64) as u32;
+        let end = ((i as u64 + 1) * src as u64).div_ceil(dst as u64) as u32;
+        start..end.max(start + 1).min(src)
+    };
+    let columns: Vec<_> = (0..dst_width)
+        .map(|x| span(x, src_width, dst_width))
+        .collect();
+    for y in 0..dst_height {
+        let rows = span(y, src_height, dst_height);
+        for (x, columns) in columns.iter().enumerate() {
+            let i = ((y * dst_width + x as u32) * 4) as usize;
+            if rows.len() == 1 && columns.len() == 1 {
+                let j = ((rows.start * src_width + columns.start) * 4) as usize;
+                dst[i..i + 4].copy_from_slice(&src[j..j + 4]);
+                continue;
+            }
+            let mut sum = [0.0f32; 4];
+            for sy in rows.clone() {
+                for sx in columns.clone() {
+                    let j = ((sy * src_width + sx) * 4) as usize;
+                    sum[0] += srgb_to_linear(src[j]);
+                    sum[1] += srgb_to_linear(src[j + 1]);
+                    sum[2] += srgb_to_linear(src[j + 2]);
+                    sum[3] += src[j + 3] as f32;
+                }
+            }
+            let samples = (rows.len() * columns.len()) as f32;
+            dst[i] = linear_to_srgb(sum[0] / samples);
+            dst[i + 1] = linear_to_srgb(sum[1] / samples);
+            dst[i + 2] = linear_to_srgb(sum[2] / samples);
+            dst[i + 3] = (sum[3] / samples).round() as u8;
+        }
+    }
+}
+
+/// Frames averaged before the scale is judged.
+const ADAPTIVE_SAMPLES: usize = 20;
+/// Scales picked by [`AdaptiveScale`] are multiples of this.
+pub const ADAPTIVE_STEP: f32 = 0.05;
+/// Shortest time between two changes of scale.
+const ADAPTIVE_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Picks a render scale that keeps frames near a time budget: lower when
+/// the average of the last few frames runs over it, higher when they come
+/// in well under. Between the two bounds the scale is left alone, so it
+/// settles instead of flipping back and forth every frame.
+#[derive(Debug, Clone)]
+pub struct AdaptiveScale {
+    target: Duration,
+    frame_times: VecDeque<Duration>,
+    last_change: Option<Instant>,
+}
+
+impl AdaptiveScale {
+    pub fn new(target: Duration) -> Self {
+        Self {
+            target,
+            frame_times: VecDeque::with_capacity(ADAPTIVE_SAMPLES),
+            last_change: None,
+        }
+    }
+
+    /// The frame time aimed for.
+    pub fn target(&self) -> Duration {
+        self.target
+    }
+
+    /// Note how long a frame took.
+    pub fn record(&mut self, frame_time: Duration) {
+        if self.frame_times.len() == ADAPTIVE_SAMPLES {
+            self.frame_times.pop_front();
+        }
+        self.frame_times.push_back(frame_time);
+    }
+
+    /// The scale to render the next frame at, given the current `scale`
+    /// and the time `now`. Frames recorded at the old scale are forgotten
+    /// whenever it changes.
+    pub fn adjust(&mut self, scale: f32, now: Instant) -> f32 {
+        if self.frame_times.len() < ADAPTIVE_SAMPLES
+            || self
+                .last_change
+                .is_some_and(|last| now.duration_since(last) < ADAPTIVE_INTERVAL)
+        {
+            return scale;
+        }
+        let average = self.frame_times.iter().sum::<Duration>() / ADAPTIVE_SAMPLES as u32;
+        let load = average.as_secs_f32() / self.target.as_secs_f32().max(f32::EPSILON);
+        if (0.7..=1.05).contains(&load) {
+            return scale;
+        }
+        // Frame time goes with the pixel count, the square of the scale.
+        // Aim a little under budget to leave room for noise.
+        let ideal = scale * (0.85 / load).sqrt();
+        let quantized = ((ideal / ADAPTIVE_STEP + 1e-3).floor() * ADAPTIVE_STEP)
+            .clamp(MIN_RENDER_SCALE, MAX_RENDER_SCALE);
+        if (quantized - scale).abs() < ADAPTIVE_STEP / 2.0 {
+            return scale;
+        }
+        self.frame_times.clear();
+        self.last_change = Some(now);
+        quantized
+    }
+}
@@ -0,0 +1,190 @@
+//! Textures drawn live from another camera, for screens in the world like
+//! a security monitor.
+//!
+//! A [`RenderTexture`] renders the world into a texture of the
+//! [`MaterialLibrary`](crate::MaterialLibrary) at the start of every
+//! [`World::draw`], or every few, and a material showing it through
+//! [`Material::render_target`](crate::Material::render_target) samples
+//! that texture like any other. Not to be confused with
+//! [`RenderTarget`](crate::target::RenderTarget), which finished frames
+//! are copied out to.
+//!
+//! A screen can be in view of its own camera, or of another screen's. Such
+//! screens are drawn over again within each frame, each time showing what
+//! the last time drew, [`World::render_target_depth`] times in all, and
+//! the first time blank: a monitor showing itself shows that many monitors
+//! within it, not an endless tunnel.
+
+use crate::texture::{Texture, TextureHandle};
+use crate::viewport::Projection;
+use crate::world::World;
+
+/// Index of a [`RenderTexture`] in its [`World`], set as a
+/// [`Material::render_target`](crate::Material::render_target). Handles
+/// stay valid for the life of the world.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct RenderTargetHandle(pub(crate) usize);
+
+/// An offscreen frame drawn through one camera into a texture.
+#[derive(Debug, Clone)]
+pub struct RenderTexture {
+    /// The camera looked through, numbered as for
+    /// [`Viewport::camera_index`](crate::viewport::Viewport::camera_index).
+    /// Targets of cameras that don't exist are left as they are.
+    pub camera_index: usize,
+    /// Fitted to the texture's shape, as a viewport's is to its own.
+    pub projection: Projection,
+    /// Draw every this many frames; `1`, the default, draws every frame.
+    pub interval: u32,
+    texture: TextureHandle,
+    /// Frames drawn since the target was last drawn.
+    since: u32,
+    /// What the pass drew, waiting to be swapped into the texture.
+    frame: Vec<u8>,
+    /// The texels the texture had before this frame's passes, to tell
+    /// whether they drew anything different.
+    shown: Vec<u8>,
+}
+
+impl RenderTexture {
+    /// The texture the target draws into.
+    pub fn texture(&self) -> TextureHandle {
+        self.texture
+    }
+}
+
+impl World {
+    /// Add a `width` by `height` target drawn through the camera at
+    /// `camera_index` with `projection`. Its texture, added to
+    /// [`World::materials`], starts out in the clear color.
+    pub fn add_render_target(
+        &mut self,
+        camera_index: usize,
+        projection: Projection,
+        width: u32,
+        height: u32,
+    ) -> RenderTargetHandle {
+        let clear_color = self.clear_color;
+        let texture = self
+            .materials
+            .add_texture(Texture::from_fn(width, height, |_, _| clear_color));
+        self.render_targets.push(RenderTexture {
+            camera_index,
+            projection,
+            interval: 1,
+            texture,
+            since: 0,
+            frame: Vec::new(),
+            shown: Vec::new(),
+        });
+        RenderTargetHandle(self.render_targets.len() - 1)
+    }
+
+    pub fn render_target(&self, handle: RenderTargetHandle) -> Option<&RenderTexture> {
+        self.render_targets.get(handle.0)
+    }
+
+    /// Change a target's camera, projection or interval.
+    pub fn render_target_mut(&mut self, handle: RenderTargetHandle) -> Option<&mut RenderTexture> {
+        self.render_targets.get_mut(handle.0)
+    }
+
+    /// The texture a material showing `handle` samples.
+    pub(crate) fn render_target_texture(
+        &self,
+        handle: RenderTargetHandle,
+    ) -> Option<TextureHandle> {
+        if self.innermost_screens {
+            return None;
+        }
+        self.render_targets
+            .get(handle.0)
+            .map(RenderTexture::texture)
+    }
+
+    /// Draw the targets that are due into their textures, again as many
+    /// times as [`World::render_target_depth`] allows when one of them is
+    /// shown on an object, which another target may see.
+    pub(crate) fn draw_render_targets(&mut self) {
+        let mut due = Vec::new();
+        for (i, target) in self.render_targets.iter_mut().enumerate() {
+            if target.since == 0 {
+                due.push(i);
+            }
+            target.since = (target.since + 1) % target.interval.max(1);
+        }
+        // Each with its view and size, leaving out those without a camera.
+        let due: Vec<_> = due
+            .into_iter()
+            .filter_map(|i| {
+                let target = &self.render_targets[i];
+                let view = match target.camera_index {
+                    0 => self.camera.generate_view_mat(),
+                    n => self.cameras.get(n - 1)?.generate_view_mat(),
+                };
+                let texture = self.materials.texture(target.texture)?;
+                Some((i, view, texture.width(), texture.height()))
+            })
+            .collect();
+        if due.is_empty() {
+            return;
+        }
+        let passes = if self.screens_shown() {
+            self.render_target_depth.max(1)
+        } else {
+            1
+        };
+        let mut depth = Vec::new();
+        for pass in 0..passes {
+            // Screens show nothing on the first pass, and what the pass
+            // before drew on the rest.
+            self.innermost_screens = pass == 0;
+            for &(i, view, width, height) in &due {
+                let projection = self.render_targets[i].projection.matrix(
+                    &self.proj_mat,
+                    width as f32 / height as f32,
+                    self.depth_convention(),
+                );
+                let mut frame = std::mem::take(&mut self.render_targets[i].frame);
+                frame.resize(width as usize * height as usize * 4, 0);
+                self.select_lods(&view);
+                self.render_scene(view, projection, &mut frame, &mut depth, width, height);
+                self.render_targets[i].frame = frame;
+            }
+            // Only swapped in once every target of the pass is drawn, so
+            // targets seeing each other see the same pass.
+            for &(i, ..) in &due {
+                let target = &mut self.render_targets[i];
+                if let Some(texture) = self.materials.texture_mut(target.texture) {
+                    texture.swap_rgba(&mut target.frame);
+                    if pass == 0 {
+                        std::mem::swap(&mut target.frame, &mut target.shown);
+                    }
+                }
+            }
+        }
+        self.innermost_screens = false;
+        for &(i, ..) in &due {
+            let target = &self.render_targets[i];
+            if let Some(texture) = self.materials.texture_mut(target.texture)
+                && texture.rgba() != target.shown.as_slice()
+            {
+                texture.mark_changed();
+            }
+        }
+    }
+
+    /// Whether any object shown has a material with a render target.
+    fn screens_shown(&self) -> bool {
+        self.models.iter().any(|model| {
+            model.shown(self.visible_layers)
+                && model
+                    .material
+                    .and_then(|handle| {
+                        self.materials
+                            .instantiate_override(handle, &model.material_override)
+                    })
+                    .is_some_and(|material| material.render_target.is_some())
+        })
+    }
+}
@@ -0,0 +1,330 @@
+//! Scene files: where the camera and light start and which models to load.
+//!
+//! A scene file is plain text, one item per line. The camera and light
+//! each take a position and the point they face; a model takes its OBJ
+//! file, where to put it and an optional uniform scale; a box takes two
+//! opposite corners and an optional color, and stands in for a model in
+//! scenes cut down by [`crate::reduce`]. The background is a solid `clear`
+//! color or a `gradient` from top to bottom, in hex:
+//!
+//! ```text
+//! # a crate on the floor
+//! camera 0 2 -8  0 0 0
+//! light -1 1 -1  0 0 0
+//! gradient #87ceeb #f0f0e0
+//! model crate.obj 0 0 4
+//! model statue.obj 3 0 4 0.5
+//! box -1 -2 -1  1 -1.5 1 #a0a0a0
+//! ```
+//!
+//! Model paths are read relative to the scene file and can't contain
+//! spaces.
+
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use nalgebra::{Matrix4, Point3};
+
+use crate::camera::Camera;
+use crate::mesh::Color;
+use crate::mesh::cube::CubeMesh;
+use crate::world::{ClearMode, Object, World};
+
+/// Color of boxes that don't give one.
+pub const BOX_COLOR: Color = Color::rgb(160, 160, 160);
+
+/// Why a scene file could not be loaded.
+#[derive(Debug)]
+pub enum SceneFileError {
+    Io(io::Error),
+    Parse { line: usize, message: String },
+}
+
+impl fmt::Display for SceneFileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SceneFileError::Io(err) => write!(f, "{err}"),
+            SceneFileError::Parse { line, message } => write!(f, "line {line}: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for SceneFileError {}
+
+impl fmt::Display for SceneFile {
+    /// The scene as a scene file, with model paths as they are.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let point = |p: &Point3<f32>| format!("{} {} {}", p.x, p.y, p.z);
+        if let Some((position, target)) = &self.camera {
+            writeln!(f, "camera {}  {}", point(position), point(target))?;
+        }
+        if let Some((position, target)) = &self.light {
+            writeln!(f, "light {}  {}", point(position), point(target))?;
+        }
+        if let Some(color) = self.clear_color {
+            writeln!(f, "clear {color}")?;
+        }
+        if let Some((top, bottom)) = self.gradient {
+            writeln!(f, "gradient {top} {bottom}")?;
+        }
+        for model in &self.models {
+            write!(
+                f,
+                "model {} {}",
+                model.path.display(),
+                point(&model.position)
+            )?;
+            if model.scale != 1.0 {
+                write!(f, " {}", model.scale)?;
+            }
+            writeln!(f)?;
+        }
+        for scene_box in &self.boxes {
+            writeln!(
+                f,
+                "box {}  {} {}",
+                point(&scene_box.min),
+                point(&scene_box.max),
+                scene_box.color
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl From<io::Error> for SceneFileError {
+    fn from(err: io::Error) -> Self {
+        SceneFileError::Io(err)
+    }
+}
+
+/// A model a scene file places.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SceneModel {
+    pub path: PathBuf,
+    pub position: Point3<f32>,
+    pub scale: f32,
+}
+
+/// A box a scene file places, from one corner to the opposite one.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SceneBox {
+    pub min: Point3<f32>,
+    pub max: Point3<f32>,
+    pub color: Color,
+}
+
+/// What a scene file holds. The camera and light are left where they are
+/// when the file doesn't mention them.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SceneFile {
+    /// Camera position and the point it looks at.
+    pub camera: Option<(Point3<f32>, Point3<f32>)>,
+    /// Light position and the point it shines at.
+    pub light: Option<(Point3<f32>, Point3<f32>)>,
+    /// Solid background color.
+    pub clear_color: Option<Color>,
+    /// Top and bottom colors of a gradient background, drawn instead of
+    /// the solid color.
+    pub gradient: Option<(Color, Color)>,
+    pub models: Vec<SceneModel>,
+    pub boxes: Vec<SceneBox>,
+}
+
+impl SceneFile {
+    /// Parse a scene file. Blank lines and lines starting with `#` are
+    /// skipped; model paths are kept as written.
+    pub fn parse(text: &str) -> Result<Self, SceneFileError> {
+        let mut scene = Self::default();
+        for (number, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let parse_error = |message: String| SceneFileError::Parse {
+                line: number + 1,
+                message,
+            };
+            let mut words = line.split_whitespace();
+            let kind = words.next().unwrap_or_default();
+            if let "clear" | "gradient" = kind {
+                let colors: Vec<Color> = words
+                    .map(str::parse)
+                    .collect::<Result<_, _>>()
+                    .map_err(|err| parse_error(format!("{err}")))?;
+                match (kind, colors.as_slice()) {
+                    ("clear", &[color]) => scene.clear_color = Some(color),
+                    ("gradient", &[top, bottom]) => scene.gradient = Some((top, bottom)),
+                    _ => {
+                        return Err(parse_error(format!(
+                            "{kind} takes {} colors, found {}",
+                            if kind == "clear" { 1 } else { 2 },
+                            colors.len()
+                        )));
+                    }
+                }
+                continue;
+            }
+            if kind == "box" {
+                let words: Vec<&str> = words.collect();
+                let (numbers, color) = match words.as_slice() {
+                    [numbers @ .., color] if color.starts_with('#') => (
+                        numbers,
+                        color.parse().map_err(|err| parse_error(format!("{err}")))?,
+                    ),
+                    numbers => (numbers, BOX_COLOR),
+                };
+                let values: Vec<f32> = numbers
+                    .iter()
+                    .map(|word| word.parse())
+                    .collect::<Result<_, _>>()
+                    .map_err(|err| parse_error(format!("bad number: {err}")))?;
+                let &[x0, y0, z0, x1, y1, z1] = values.as_slice() else {
+                    return Err(parse_error(format!(
+                        "box takes 6 numbers, found {}",
+                        values.len()
+                    )));
+                };
+                scene.boxes.push(SceneBox {
+                    min: Point3::new(x0.min(x1), y0.min(y1), z0.min(z1)),
+                    max: Point3::new(x0.max(x1), y0.max(y1), z0.max(z1)),
+                    color,
+                });
+                continue;
+            }
+            let path = match kind {
+                "model" => Some(
+                    words
+                        .next()
+                        .ok_or_else(|| parse_error("model needs a file".to_string()))?,
+                ),
+                _ => None,
+            };
+            let values: Vec<f32> = words
+                .map(str::parse)
+                .collect::<Result<_, _>>()
+                .map_err(|err| parse_error(format!("bad number: {err}")))?;
+            match (kind, path, values.as_slice()) {
+                ("camera", _, &[px, py, pz, tx, ty, tz]) => {
+                    scene.camera = Some((Point3::new(px, py, pz), Point3::new(tx, ty, tz)));
+                }
+                ("light", _, &[px, py, pz, tx, ty, tz]) => {
+                    scene.light = Some((Point3::new(px, py, pz), Point3::new(tx, ty, tz)));
+                }
+                ("camera" | "light", _, values) => {
+                    return Err(parse_error(format!(
+                        "{kind} takes 6 numbers, found {}",
+                        values.len()
+                    )));
+                }
+                ("model", Some(path), &[x, y, z]) => scene.models.push(SceneModel {
+                    path: path.into(),
+                    position: Point3::new(x, y, z),
+                    scale: 1.0,
+                }),
+                ("model", Some(path), &[x, y, z, scale]) => {
+                    if !(scale > 0.0 && scale.is_finite()) {
+                        return Err(parse_error(format!("scale {scale} is not positive")));
+                    }
+                    scene.models.push(SceneModel {
+                        path: path.into(),
+                        position: Point3::new(x, y, z),
+                        scale,
+                    });
+                }
+                ("model", _, values) => {
+                    return Err(parse_error(format!(
+                        "model takes 3 or 4 numbers, found {}",
+                        values.len()
+                    )));
+                }
+                _ => return Err(parse_error(format!("unknown item {kind:?}"))),
+            }
+        }
+        Ok(scene)
+    }
+
+    /// Load the scene file at `path`, with model paths made relative to
+    /// the folder it is in.
+    pub fn from_path(path: impl AsRef<Path>) -> Result<Self, SceneFileError> {
+        let path = path.as_ref();
+        let mut scene = Self::parse(&std::fs::read_to_string(path)?)?;
+        let dir = path.parent().unwrap_or(Path::new(""));
+        for model in &mut scene.models {
+            model.path = dir.join(&model.path);
+        }
+        Ok(scene)
+    }
+
+    /// Write the scene to `path`, as [`SceneFile::parse`] reads it back,
+    /// with model paths made relative to the folder it is in where they
+    /// are inside it.
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let path = path.as_ref();
+        let dir = path.parent().unwrap_or(Path::new(""));
+        let mut scene = self.clone();
+        for model in &mut scene.models {
+            if let Ok(relative) = model.path.strip_prefix(dir) {
+                model.path = relative.to_path_buf();
+            }
+        }
+        std::fs::write(path, scene.to_string())
+    }
+
+    /// Move `camera` to where the file puts it, if it does.
+    pub fn place_camera(&self, camera: &mut Camera) {
+        if let Some((position, target)) = self.camera {
+            camera.position = position;
+            camera.look_at(target);
+        }
+    }
+
+    /// Move the light and set the background to what the file says, and
+    /// start loading the file's models into `world` with [`World::load_object_async`].
+    /// Returns the indices of the objects added for the models, in the
+    /// order the file lists them, and then for the boxes.
+    pub fn load_into(&self, world: &mut World) -> Vec<usize> {
+        if let Some((position, target)) = self.light {
+            world.light.position = position;
+            world.light.target = target;
+        }
+        if let Some(color) = self.clear_color {
+            world.clear_color = color;
+            world.clear_mode = ClearMode::Solid;
+        }
+        if let Some((top, bottom)) = self.gradient {
+            world.clear_mode = ClearMode::Gradient { top, bottom };
+        }
+        let mut indices: Vec<usize> = self
+            .models
+            .iter()
+            .map(|model| {
+                let transform = Matrix4::new_translation(&model.position.coords)
+                    * Matrix4::new_scaling(model.scale);
+                world.load_object_async(&model.path, transform)
+            })
+            .collect();
+        for scene_box in &self.boxes {
+            let mesh = CubeMesh::spanning(scene_box.min, scene_box.max, scene_box.color);
+            let index = world
+                .add_object(Object::new(mesh, 0.0, 0.0, 0.0))
+                .expect("box meshes are valid and unnamed");
+            indices.push(index);
+        }
+        indices
+    }
+
+    /// Take the objects at `previous`, as returned by an earlier
+    /// [`SceneFile::load_into`], out of `world` and load this file's in
+    /// their place, for when the file changes. Returns the new objects'
+    /// indices.
+    pub fn replace(&self, world: &mut World, previous: &[usize]) -> Vec<usize> {
+        let mut previous = previous.to_vec();
+        previous.sort_unstable_by(|a, b| b.cmp(a));
+        for index in previous {
+            world.remove_object(index);
+        }
+        self.load_into(world)
+    }
+}
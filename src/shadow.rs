@@ -0,0 +1,153 @@
+//! Shadow maps for the directional light.
+//!
+//! The scene is rasterized depth-only along the light direction with an
+//! orthographic projection fitted around every object. A surface is in
+//! shadow when something else sits closer to the light at its spot in the
+//! map.
+
+use nalgebra::{Matrix4, Point3, Vector3};
+
+use crate::light::Light;
+use crate::world::Object;
+
+/// Depth of the scene as seen from the light.
+pub(crate) struct ShadowMap {
+    /// World space to map space: x and y in texels, z the distance along
+    /// the light direction.
+    to_map: Matrix4<f32>,
+    size: u32,
+    depth: Vec<f32>,
+    bias: f32,
+}
+
+impl ShadowMap {
+    /// Render the depth of the `models` shown on `visible_layers`, placed by
+    /// their world `matrices`, from `light`. Returns `None` when shadows are off or there is nothing to
+    /// cast them.
+    pub fn build(
+        light: &Light,
+        models: &[Object],
+        matrices: &[Matrix4<f32>],
+        visible_layers: u32,
+    ) -> Option<Self> {
+        let size = light.shadow_resolution;
+        if size == 0 {
+            return None;
+        }
+        let dir = (light.target - light.position).try_normalize(f32::EPSILON)?;
+        let up = if dir.y.abs() > 0.99 {
+            Vector3::z()
+        } else {
+            Vector3::y()
+        };
+        let view = Matrix4::look_at_rh(&Point3::origin(), &Point3::from(dir), &up);
+
+        let meshes: Vec<(Vec<Point3<f32>>, &Object)> = models
+            .iter()
+            .zip(matrices)
+            .filter(|(model, _)| model.shown(visible_layers))
+            .map(|(model, model_mat)| {
+                let to_light = view * model_mat;
+                let verts = model
+                    .mesh
+                    .verts()
+                    .iter()
+                    .map(|v| to_light.transform_point(v))
+                    .collect();
+                (verts, model)
+            })
+            .collect();
+
+        // Fit the map around everything, with a little room at the edges.
+        let (mut min, mut max) = (
+            Vector3::repeat(f32::INFINITY),
+            Vector3::repeat(f32::NEG_INFINITY),
+        );
+        for v in meshes.iter().flat_map(|(verts, _)| verts) {
+            min = min.inf(&v.coords);
+            max = max.sup(&v.coords);
+        }
+        if !min.x.is_finite() {
+            return None;
+        }
+        let margin = (max - min).xy().max() * 0.01 + f32::EPSILON;
+        let extent = (max - min).xy().max() + 2.0 * margin;
+        let scale = size as f32 / extent;
+        // Light view space looks down -z, so distance from the light is -z.
+        let to_texels = Matrix4::new(
+            scale,
+            0.0,
+            0.0,
+            (margin - min.x) * scale,
+            0.0,
+            scale,
+            0.0,
+            (margin - min.y) * scale,
+            0.0,
+            0.0,
+            -1.0,
+            0.0,
+            0.0,
+            0.0,
+            0.0,
+            1.0,
+        );
+
+        let mut map = ShadowMap {
+            to_map: to_texels * view,
+            size,
+            depth: vec![f32::INFINITY; (size * size) as usize],
+            bias: light.shadow_bias,
+        };
+        for (verts, model) in &meshes {
+            for tri in model.mesh.primitives().triangles() {
+                let [a, b, c] =
+                    [tri.v1, tri.v2, tri.v3].map(|i| to_texels.transform_point(&verts[i]));
+                map.fill(a, b, c);
+            }
+        }
+        Some(map)
+    }
+
+    /// Keep the nearest depth of the triangle `a b c`, in map space, in
+    /// every texel whose center it covers. Both windings are drawn.
+    fn fill(&mut self, a: Point3<f32>, b: Point3<f32>, c: Point3<f32>) {
+        let area = (b.x - a.x) * (c.y - a.y) - (b.y - a.y) * (c.x - a.x);
+        if area.abs() < f32::EPSILON {
+            return;
+        }
+        let last = self.size as f32 - 1.0;
+        let min_x = a.x.min(b.x).min(c.x).floor().clamp(0.0, last) as u32;
+        let max_x = a.x.max(b.x).max(c.x).ceil().clamp(0.0, last) as u32;
+        let min_y = a.y.min(b.y).min(c.y).floor().clamp(0.0, last) as u32;
+        let max_y = a.y.max(b.y).max(c.y).ceil().clamp(0.0, last) as u32;
+        let edge = |p: Point3<f32>, q: Point3<f32>, x: f32, y: f32| {
+            (q.x - p.x) * (y - p.y) - (q.y - p.y) * (x - p.x)
+        };
+        for y in min_y..=max_y {
+            for x in min_x..=max_x {
+                let (px, py) = (x as f32 + 0.5, y as f32 + 0.5);
+                let wa = edge(b, c, px, py) / area;
+                let wb = edge(c, a, px, py) / area;
+                let wc = edge(a, b, px, py) / area;
+                if wa < 0.0 || wb < 0.0 || wc < 0.0 {
+                    continue;
+                }
+                let z = wa * a.z + wb * b.z + wc * c.z;
+                let texel = &mut self.depth[(y * self.size + x) as usize];
+                *texel = texel.min(z);
+            }
+        }
+    }
+
+    /// True if something is between the light and the world space `point`.
+    /// Points outside the map are lit.
+    pub fn in_shadow(&self, point: &Point3<f32>) -> bool {
+        let p = self.to_map.transform_point(point);
+        if p.x < 0.0 || p.y < 0.0 || p.x >= self.size as f32 || p.y >= self.size as f32 {
+            return false;
+        }
+        let texel = self.depth[(p.y as u32 * self.size + p.x as u32) as usize];
+        p.z > texel + self.bias
+    }
+}
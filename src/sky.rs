@@ -0,0 +1,187 @@
+//! A sky behind the scene that follows the camera: each pixel is shaded by
+//! how far above the horizon its view ray points, with a sun disc where
+//! the ray meets the light.
+
+use nalgebra::{Matrix4, Vector3, Vector4};
+
+use crate::depth::DepthConvention;
+use crate::mesh::Color;
+
+/// Steps the gradient from horizon to zenith is divided into.
+pub const SKY_STEPS: usize = 256;
+
+/// Pixels of a row between exactly worked out sky colors.
+const SKY_SPAN: usize = 8;
+
+/// Colors of [`ClearMode::Sky`](crate::world::ClearMode::Sky).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Sky {
+    /// Color of rays along the horizon and below it.
+    pub horizon: Color,
+    /// Color of rays straight up. Rays in between blend by their
+    /// elevation angle.
+    pub zenith: Color,
+    /// Disc drawn towards the light, if any.
+    pub sun: Option<SunDisc>,
+}
+
+/// The light seen in the sky.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SunDisc {
+    pub color: Color,
+    /// Angle from the disc's centre to its edge, in radians. The edge
+    /// fades out over half as much again.
+    pub angular_radius: f32,
+}
+
+impl Default for Sky {
+    fn default() -> Self {
+        Self {
+            horizon: Color::rgb(205, 220, 235),
+            zenith: Color::rgb(60, 110, 190),
+            sun: Some(SunDisc {
+                color: Color::rgb(255, 245, 220),
+                angular_radius: 0.05,
+            }),
+        }
+    }
+}
+
+/// The sky's gradient for [`fill_sky`], blending the colors with `lerp`.
+pub(crate) fn sky_gradient(
+    sky: &Sky,
+    lerp: impl Fn(Color, Color, f32) -> Color,
+) -> [Color; SKY_STEPS] {
+    let last = (SKY_STEPS - 1) as f32;
+    std::array::from_fn(|i| {
+        let elevation = (i as f32 / last).asin() / std::f32::consts::FRAC_PI_2;
+        lerp(sky.horizon, sky.zenith, elevation)
+    })
+}
+
+/// Fill a `width * height` frame seen through `clip_mat` with the sky.
+/// `gradient` holds the sky color of rays whose upward component, the
+/// sine of their elevation, runs from 0 to 1 in [`SKY_STEPS`] even steps,
+/// and `sun` the unit direction towards the light with its disc.
+pub(crate) fn fill_sky(
+    frame: &mut [u8],
+    width: u32,
+    height: u32,
+    clip_mat: &Matrix4<f32>,
+    convention: DepthConvention,
+    gradient: &[Color; SKY_STEPS],
+    sun: Option<(Vector3<f32>, SunDisc)>,
+) {
+    let row_len = (width * 4) as usize;
+    let Some(inverse) = clip_mat.try_inverse() else {
+        for pixel in frame.chunks_exact_mut(4) {
+            let c = gradient[0];
+            pixel.copy_from_slice(&[c.r, c.g, c.b, c.a]);
+        }
+        return;
+    };
+    // The ray through a point on the screen runs from where it meets the
+    // near plane to where it meets the far one.
+    let (near_z, far_z) = convention.ndc_range();
+    let unproject = |x: f32, y: f32, z: f32| {
+        let p = inverse * Vector4::new(x, y, z, 1.0);
+        p.xyz() / p.w
+    };
+    let ray = |x: f32, y: f32| unproject(x, y, far_z) - unproject(x, y, near_z);
+    let last = (SKY_STEPS - 1) as f32;
+    let rgba = gradient.map(|c| [c.r, c.g, c.b, c.a]);
+    for (y, row) in frame.chunks_exact_mut(row_len.max(1)).enumerate() {
+        // Unnormalized ray directions are linear across a row, so only its
+        // two ends are unprojected.
+        let ndc_y = 1.0 - 2.0 * (y as f32 + 0.5) / height as f32;
+        let left = ray(-1.0 + 1.0 / width as f32, ndc_y);
+        let right = ray(1.0 - 1.0 / width as f32, ndc_y);
+        let step = (right - left) / (width.max(2) - 1) as f32;
+        let sun = sun.map(|(toward, disc)| SunSpans::new(toward, disc, left, step));
+        // Elevation barely curves over a few pixels: it is worked out at
+        // the ends of short spans and interpolated in between, in 16.16
+        // fixed point.
+        let mut start = left;
+        let mut from = fixed_elevation(start, last);
+        for pixels in row.chunks_mut(SKY_SPAN * 4) {
+            let count = pixels.len() / 4;
+            let end = start + step * count as f32;
+            let to = fixed_elevation(end, last);
+            let slope = (to - from) / count as i32;
+            let mut at = from;
+            for pixel in pixels.chunks_exact_mut(4) {
+                // Rays below the horizon get step 0.
+                let index = ((at + 0x8000) >> 16).clamp(0, SKY_STEPS as i32 - 1) as usize;
+                pixel.copy_from_slice(&rgba[index]);
+                at += slope;
+            }
+            if let Some(sun) = &sun {
+                sun.draw(pixels, start, step);
+            }
+            (start, from) = (end, to);
+        }
+    }
+}
+
+/// A ray's gradient step in 16.16 fixed point, before rounding.
+fn fixed_elevation(direction: Vector3<f32>, last: f32) -> i32 {
+    let length = direction.norm();
+    if length > 0.0 {
+        (direction.y / length * last * 65536.0) as i32
+    } else {
+        0
+    }
+}
+
+/// The sun disc as seen from the rays of one row of sky.
+struct SunSpans {
+    toward: Vector3<f32>,
+    disc: SunDisc,
+    /// Cosines of the angles from the sun's centre to the edge of the disc
+    /// and to the end of its fading edge.
+    inner: f32,
+    outer: f32,
+    /// Below this cosine of the angle to the sun at its start, no pixel of
+    /// a span reaches the fading edge.
+    skip_below: f32,
+}
+
+impl SunSpans {
+    fn new(toward: Vector3<f32>, disc: SunDisc, left: Vector3<f32>, step: Vector3<f32>) -> Self {
+        let outer = disc.angular_radius * 1.5;
+        // No ray of the row is shorter than the line's distance from the
+        // eye, so no span turns through more than this.
+        let nearest = left.cross(&step).norm() / step.norm().max(f32::MIN_POSITIVE);
+        let reach = step.norm() * SKY_SPAN as f32 / nearest.max(f32::MIN_POSITIVE);
+        Self {
+            toward,
+            disc,
+            inner: disc.angular_radius.cos(),
+            outer: outer.cos(),
+            skip_below: (outer + reach).min(std::f32::consts::PI).cos(),
+        }
+    }
+
+    /// Blend the pixels of a span seen along `start`, `start + step`, ...
+    /// towards the sun's color where they look at the disc.
+    fn draw(&self, pixels: &mut [u8], start: Vector3<f32>, step: Vector3<f32>) {
+        if start.dot(&self.toward) < self.skip_below * start.norm() {
+            return;
+        }
+        for (i, pixel) in pixels.chunks_exact_mut(4).enumerate() {
+            let direction = start + step * i as f32;
+            let alignment = direction.dot(&self.toward) / direction.norm();
+            if alignment > self.outer {
+                let t = ((alignment - self.outer) / (self.inner - self.outer)).min(1.0);
+                let sky = Color {
+                    r: pixel[0],
+                    g: pixel[1],
+                    b: pixel[2],
+                    a: pixel[3],
+                };
+                let c = sky.lerp(self.disc.color, t);
+                pixel.copy_from_slice(&[c.r, c.g, c.b, c.a]);
+            }
+        }
+    }
+}
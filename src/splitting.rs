@@ -0,0 +1,718 @@
+//! Cutting up triangles that pass through each other, so the painter's
+//! algorithm can draw them in an order that is right from every side, for
+//! [`World::splitting_sort`](crate::world::World::splitting_sort).
+//!
+//! Objects whose bounding boxes overlap are checked for triangles that
+//! cross, and objects with crossing triangles are grouped. Of each
+//! crossing pair, the larger triangle is cut along the other's plane.
+//! Every triangle of the group, whole or cut, then goes into a BSP tree,
+//! which cuts whatever still straddles a plane, and walking the tree from
+//! the eye gives the order to draw the group in.
+//!
+//! Groups are built in world space and built again whenever an object
+//! moves. A mesh that only cuts through itself is built once, in its own
+//! space, and shared by every object drawing it, so only the walk is left
+//! to do each frame.
+
+use std::collections::HashMap;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::rc::Rc;
+
+use log::warn;
+use nalgebra::{Matrix4, Point3, Vector3, Vector4};
+use ordered_float::OrderedFloat;
+
+use crate::collision::{overlapping_boxes, world_aabb};
+use crate::material::Material;
+use crate::mesh::{Color, Mesh, Triangle};
+use crate::world::Object;
+
+/// Distance from a plane within which a point counts as on it.
+const PLANE_EPSILON: f32 = 1e-4;
+
+/// Pieces a group may be cut into before it is given up on and drawn
+/// unsplit, so two dense meshes cutting through each other can't stall
+/// the frame.
+const MAX_FRAGMENTS: usize = 1 << 16;
+
+/// Groups of objects cut up against each other, kept until an object
+/// moves, appears or goes.
+#[derive(Default)]
+pub(crate) struct SplitCache {
+    /// Fingerprint of the meshes and transforms the groups were built for.
+    key: Option<u64>,
+    /// Meshes by address, with their cuts against themselves.
+    meshes: HashMap<usize, SelfCuts>,
+    /// For each object in `models`, the group it is drawn as part of.
+    drawn: Vec<Option<Member>>,
+}
+
+/// A mesh cut against itself in its own space, if it passes through
+/// itself at all.
+struct SelfCuts {
+    /// Held so its address can't be reused.
+    _mesh: Rc<dyn Mesh>,
+    group: Option<Rc<SplitGroup>>,
+}
+
+#[derive(Clone)]
+struct Member {
+    group: Rc<SplitGroup>,
+    /// Which of the group's meshes the object draws.
+    index: usize,
+    /// Whether the group was built in the object's own space rather than
+    /// the world's.
+    own_space: bool,
+}
+
+/// How an object of a split group is drawn in one frame.
+pub(crate) struct SplitDraw<'a> {
+    pub mesh: &'a SplitMesh,
+    /// Objects with the same group are drawn together.
+    pub group: usize,
+    /// Where each of the mesh's triangles comes, back to front, among the
+    /// triangles of the whole group.
+    pub ranks: Vec<u32>,
+}
+
+impl SplitCache {
+    /// Bring the groups up to date with `models`, placed by `matrices`,
+    /// unless nothing shown has changed since the last call.
+    pub(crate) fn update(
+        &mut self,
+        models: &[Object],
+        matrices: &[Matrix4<f32>],
+        visible_layers: u32,
+    ) {
+        let shown: Vec<usize> = (0..models.len())
+            .filter(|&i| models[i].shown(visible_layers) && !models[i].mesh.verts().is_empty())
+            .collect();
+        let mut h = DefaultHasher::new();
+        models.len().hash(&mut h);
+        for &i in &shown {
+            i.hash(&mut h);
+            address(&models[i].mesh).hash(&mut h);
+            for v in matrices[i].iter() {
+                v.to_bits().hash(&mut h);
+            }
+        }
+        let key = h.finish();
+        if self.key == Some(key) {
+            return;
+        }
+        self.key = Some(key);
+
+        let in_use: Vec<usize> = shown.iter().map(|&i| address(&models[i].mesh)).collect();
+        self.meshes.retain(|address, _| in_use.contains(address));
+        for &i in &shown {
+            let mesh = &models[i].mesh;
+            self.meshes
+                .entry(address(mesh))
+                .or_insert_with(|| SelfCuts {
+                    _mesh: Rc::clone(mesh),
+                    group: SplitGroup::build(&[(Rc::clone(mesh), Matrix4::identity())])
+                        .map(Rc::new),
+                });
+        }
+
+        // Objects that cut through each other, joined into groups.
+        let boxes = shown
+            .iter()
+            .map(|&i| {
+                let (min, max) = world_aabb(models[i].mesh.aabb(), &matrices[i]);
+                (i, min, max)
+            })
+            .collect();
+        let mut leader: Vec<usize> = (0..models.len()).collect();
+        fn find(leader: &mut [usize], mut i: usize) -> usize {
+            while leader[i] != i {
+                leader[i] = leader[leader[i]];
+                i = leader[i];
+            }
+            i
+        }
+        for (a, b) in overlapping_boxes(boxes) {
+            let mut tris: Vec<_> = world_triangles(&*models[a].mesh, &matrices[a]).collect();
+            let first_of_b = tris.len();
+            tris.extend(world_triangles(&*models[b].mesh, &matrices[b]));
+            let across = |i: usize, j: usize| (i < first_of_b) != (j < first_of_b);
+            if !cutting_pairs(&tris, across).is_empty() {
+                let (a, b) = (find(&mut leader, a), find(&mut leader, b));
+                leader[a.max(b)] = a.min(b);
+            }
+        }
+
+        self.drawn = vec![None; models.len()];
+        let mut groups: Vec<(usize, Vec<usize>)> = Vec::new();
+        for &i in &shown {
+            let root = find(&mut leader, i);
+            match groups.iter_mut().find(|(r, _)| *r == root) {
+                Some((_, members)) => members.push(i),
+                None => groups.push((root, vec![i])),
+            }
+        }
+        for (_, members) in groups.into_iter().filter(|(_, m)| m.len() > 1) {
+            let meshes: Vec<_> = members
+                .iter()
+                .map(|&i| (Rc::clone(&models[i].mesh), matrices[i]))
+                .collect();
+            match SplitGroup::build(&meshes) {
+                Some(group) => {
+                    let group = Rc::new(group);
+                    for (index, &i) in members.iter().enumerate() {
+                        self.drawn[i] = Some(Member {
+                            group: Rc::clone(&group),
+                            index,
+                            own_space: false,
+                        });
+                    }
+                }
+                None => warn!("objects {members:?} cut into too many pieces to sort"),
+            }
+        }
+        for &i in &shown {
+            if self.drawn[i].is_none()
+                && let Some(SelfCuts {
+                    group: Some(group), ..
+                }) = self.meshes.get(&address(&models[i].mesh))
+            {
+                self.drawn[i] = Some(Member {
+                    group: Rc::clone(group),
+                    index: 0,
+                    own_space: true,
+                });
+            }
+        }
+    }
+
+    /// How each object in `models` is drawn through `view_mat` and
+    /// `proj_mat`, when it is part of a split group, indexed like
+    /// `models`. `matrices` place the objects in the world.
+    pub(crate) fn draws(
+        &self,
+        view_mat: &Matrix4<f32>,
+        proj_mat: &Matrix4<f32>,
+        matrices: &[Matrix4<f32>],
+    ) -> Vec<Option<SplitDraw<'_>>> {
+        let Some(inverse_view) = view_mat.try_inverse() else {
+            return Vec::new();
+        };
+        // A parallel projection, with nothing in its w row, looks from
+        // infinitely far back along +z.
+        let eye = if proj_mat[(3, 2)] == 0.0 {
+            inverse_view * Vector4::z()
+        } else {
+            inverse_view * Vector4::w()
+        };
+        let mut world_groups: Vec<(*const SplitGroup, Vec<Vec<u32>>)> = Vec::new();
+        let mut groups = 0;
+        let mut draws = Vec::with_capacity(self.drawn.len());
+        for (handle, member) in self.drawn.iter().enumerate() {
+            let Some(member) = member else {
+                draws.push(None);
+                continue;
+            };
+            let mesh = &member.group.meshes[member.index];
+            if member.own_space {
+                let Some(inverse) = matrices.get(handle).and_then(|matrix| matrix.try_inverse())
+                else {
+                    draws.push(None);
+                    continue;
+                };
+                let mut ranks = member.group.ranks(&(inverse * eye));
+                draws.push(Some(SplitDraw {
+                    mesh,
+                    group: groups,
+                    ranks: ranks.swap_remove(0),
+                }));
+                groups += 1;
+                continue;
+            }
+            let key = Rc::as_ptr(&member.group);
+            let group = match world_groups.iter().position(|(g, _)| *g == key) {
+                Some(group) => group,
+                None => {
+                    world_groups.push((key, member.group.ranks(&eye)));
+                    world_groups.len() - 1
+                }
+            };
+            draws.push(Some(SplitDraw {
+                mesh,
+                group: usize::MAX - group,
+                ranks: std::mem::take(&mut world_groups[group].1[member.index]),
+            }));
+        }
+        draws
+    }
+}
+
+fn address(mesh: &Rc<dyn Mesh>) -> usize {
+    Rc::as_ptr(mesh) as *const () as usize
+}
+
+fn world_triangles<'a>(
+    mesh: &'a dyn Mesh,
+    matrix: &'a Matrix4<f32>,
+) -> impl Iterator<Item = [Point3<f32>; 3]> + 'a {
+    let verts = mesh.verts();
+    mesh.primitives()
+        .triangles()
+        .map(move |tri| [tri.v1, tri.v2, tri.v3].map(|v| matrix.transform_point(&verts[v])))
+}
+
+/// An object's mesh with its triangles cut into pieces. Each piece has the
+/// color and material of the triangle it was cut from.
+pub(crate) struct SplitMesh {
+    source: Rc<dyn Mesh>,
+    verts: Vec<Point3<f32>>,
+    tris: Vec<Triangle>,
+    /// For each triangle, the index of the one it was cut from among the
+    /// source mesh's.
+    sources: Vec<usize>,
+    /// The corners of each of the source mesh's triangles.
+    corners: Vec<[Point3<f32>; 3]>,
+}
+
+impl SplitMesh {
+    /// The index among the source mesh's triangles of the one triangle
+    /// `index` was cut from.
+    pub(crate) fn source_index(&self, index: usize) -> usize {
+        self.sources.get(index).copied().unwrap_or(index)
+    }
+
+    /// The corners of the triangle triangle `index` was cut from, which
+    /// it is lit as.
+    pub(crate) fn source_corners(&self, index: usize) -> Option<[Point3<f32>; 3]> {
+        self.corners.get(self.source_index(index)).copied()
+    }
+}
+
+impl Mesh for SplitMesh {
+    fn tris(&self) -> &Vec<Triangle> {
+        &self.tris
+    }
+
+    fn verts(&self) -> &[Point3<f32>] {
+        &self.verts
+    }
+
+    fn material(&self, index: usize) -> Option<&Material> {
+        self.source.material(self.source_index(index))
+    }
+}
+
+/// Meshes cut up against each other, with the tree that orders their
+/// triangles.
+struct SplitGroup {
+    meshes: Vec<SplitMesh>,
+    tree: Bsp,
+}
+
+impl SplitGroup {
+    /// Cut up `members`, each placed by its matrix, against each other, or
+    /// `None` when no triangles cross, or when they make too many pieces.
+    fn build(members: &[(Rc<dyn Mesh>, Matrix4<f32>)]) -> Option<SplitGroup> {
+        let mut fragments = Vec::new();
+        for (member, (mesh, matrix)) in members.iter().enumerate() {
+            let verts = mesh.verts();
+            let placed: Vec<Point3<f32>> =
+                verts.iter().map(|v| matrix.transform_point(v)).collect();
+            for (source, tri) in mesh.primitives().triangles().enumerate() {
+                let corner = |v: usize| Corner {
+                    at: placed[v],
+                    model: verts[v],
+                    vertex: Some(v),
+                };
+                let corners = [corner(tri.v1), corner(tri.v2), corner(tri.v3)];
+                fragments.extend(Fragment::new(member, source, tri.color, corners));
+            }
+        }
+        let points: Vec<_> = fragments.iter().map(Fragment::points).collect();
+        let pairs = cutting_pairs(&points, |_, _| true);
+        if pairs.is_empty() {
+            return None;
+        }
+
+        // The larger of each crossing pair is cut along the other's plane,
+        // piece by piece if it has been cut before.
+        let mut pieces: Vec<Vec<Fragment>> = fragments.iter().map(|f| vec![*f]).collect();
+        for (i, j) in pairs {
+            let (cut, along) = if fragments[i].area() >= fragments[j].area() {
+                (i, j)
+            } else {
+                (j, i)
+            };
+            let plane = fragments[along].plane;
+            pieces[cut] = pieces[cut]
+                .iter()
+                .flat_map(|piece| match piece.split(&plane) {
+                    Side::Both(front, back) => [front, back].concat(),
+                    _ => vec![*piece],
+                })
+                .collect();
+        }
+        let (mut tree, fragments) = Bsp::build(pieces.into_iter().flatten().collect())?;
+
+        let mut meshes: Vec<SplitMesh> = members
+            .iter()
+            .map(|(mesh, _)| {
+                let verts = mesh.verts();
+                SplitMesh {
+                    source: Rc::clone(mesh),
+                    verts: verts.to_vec(),
+                    tris: Vec::new(),
+                    sources: Vec::new(),
+                    corners: mesh
+                        .primitives()
+                        .triangles()
+                        .map(|tri| [tri.v1, tri.v2, tri.v3].map(|v| verts[v]))
+                        .collect(),
+                }
+            })
+            .collect();
+        for node in &mut tree.nodes {
+            for entry in &mut node.on {
+                let fragment = &fragments[entry.1];
+                let mesh = &mut meshes[fragment.member];
+                let [v1, v2, v3] = fragment.corners.map(|corner| {
+                    corner.vertex.unwrap_or_else(|| {
+                        mesh.verts.push(corner.model);
+                        mesh.verts.len() - 1
+                    })
+                });
+                *entry = (fragment.member, mesh.tris.len());
+                mesh.tris.push(Triangle {
+                    v1,
+                    v2,
+                    v3,
+                    color: fragment.color,
+                });
+                mesh.sources.push(fragment.source);
+            }
+        }
+        Some(SplitGroup { meshes, tree })
+    }
+
+    /// Where each triangle of each mesh comes, back to front, seen from
+    /// `eye` in the group's space: a point, or a direction with no w for a
+    /// parallel view.
+    fn ranks(&self, eye: &Vector4<f32>) -> Vec<Vec<u32>> {
+        let mut ranks: Vec<Vec<u32>> = self
+            .meshes
+            .iter()
+            .map(|mesh| vec![0; mesh.tris.len()])
+            .collect();
+        let mut next = 0;
+        self.tree.back_to_front(eye, |on| {
+            for &(member, tri) in on {
+                ranks[member][tri] = next;
+                next += 1;
+            }
+        });
+        ranks
+    }
+}
+
+/// A point of a piece, in the group's space and in its object's own.
+#[derive(Debug, Clone, Copy)]
+struct Corner {
+    at: Point3<f32>,
+    model: Point3<f32>,
+    /// The mesh vertex it is, unless made by a cut.
+    vertex: Option<usize>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Plane {
+    normal: Vector3<f32>,
+    offset: f32,
+}
+
+impl Plane {
+    /// The plane of a triangle with an area.
+    fn through([a, b, c]: &[Point3<f32>; 3]) -> Option<Plane> {
+        let normal = (b - a).cross(&(c - a)).try_normalize(1e-12)?;
+        Some(Plane {
+            normal,
+            offset: -normal.dot(&a.coords),
+        })
+    }
+
+    /// Signed distance of `p`, snapped to 0 within [`PLANE_EPSILON`].
+    fn distance(&self, p: &Point3<f32>) -> f32 {
+        let d = self.normal.dot(&p.coords) + self.offset;
+        if d.abs() <= PLANE_EPSILON { 0.0 } else { d }
+    }
+}
+
+/// A triangle of a mesh in a group, or a piece cut from one.
+#[derive(Debug, Clone, Copy)]
+struct Fragment {
+    member: usize,
+    source: usize,
+    color: Color,
+    corners: [Corner; 3],
+    plane: Plane,
+}
+
+/// Where a piece lies against a plane.
+enum Side {
+    Front,
+    Back,
+    On,
+    /// Across it, cut into the pieces in front and behind.
+    Both(Vec<Fragment>, Vec<Fragment>),
+}
+
+impl Fragment {
+    /// A piece with these corners, unless it has no area.
+    fn new(member: usize, source: usize, color: Color, corners: [Corner; 3]) -> Option<Self> {
+        let plane = Plane::through(&corners.map(|c| c.at))?;
+        Some(Self {
+            member,
+            source,
+            color,
+            corners,
+            plane,
+        })
+    }
+
+    fn points(&self) -> [Point3<f32>; 3] {
+        self.corners.map(|corner| corner.at)
+    }
+
+    fn area(&self) -> f32 {
+        let [a, b, c] = self.points();
+        (b - a).cross(&(c - a)).norm()
+    }
+
+    fn split(&self, plane: &Plane) -> Side {
+        let d = self.corners.map(|corner| plane.distance(&corner.at));
+        match (d.iter().any(|&d| d > 0.0), d.iter().any(|&d| d < 0.0)) {
+            (false, false) => Side::On,
+            (true, false) => Side::Front,
+            (false, true) => Side::Back,
+            (true, true) => {
+                let (mut front, mut back) = (Vec::with_capacity(4), Vec::with_capacity(4));
+                for i in 0..3 {
+                    let j = (i + 1) % 3;
+                    let (p, q) = (self.corners[i], self.corners[j]);
+                    if d[i] >= 0.0 {
+                        front.push(p);
+                    }
+                    if d[i] <= 0.0 {
+                        back.push(p);
+                    }
+                    if d[i] * d[j] < 0.0 {
+                        let t = d[i] / (d[i] - d[j]);
+                        let cut = Corner {
+                            at: p.at + (q.at - p.at) * t,
+                            model: p.model + (q.model - p.model) * t,
+                            vertex: None,
+                        };
+                        front.push(cut);
+                        back.push(cut);
+                    }
+                }
+                Side::Both(self.fan(&front), self.fan(&back))
+            }
+        }
+    }
+
+    /// The pieces of a convex polygon cut from this one, in its plane.
+    fn fan(&self, polygon: &[Corner]) -> Vec<Fragment> {
+        (1..polygon.len().saturating_sub(1))
+            .filter_map(|i| {
+                let corners = [polygon[0], polygon[i], polygon[i + 1]];
+                Fragment::new(self.member, self.source, self.color, corners)
+            })
+            .map(|piece| Fragment {
+                plane: self.plane,
+                ..piece
+            })
+            .collect()
+    }
+}
+
+/// Index pairs, lower first and in order, of the triangles in `tris` that
+/// pass through each other and that `wanted` accepts. Triangles that only
+/// touch, like neighbours sharing an edge, don't count.
+fn cutting_pairs(
+    tris: &[[Point3<f32>; 3]],
+    wanted: impl Fn(usize, usize) -> bool,
+) -> Vec<(usize, usize)> {
+    let boxes: Vec<(Point3<f32>, Point3<f32>)> = tris
+        .iter()
+        .map(|[a, b, c]| (a.inf(b).inf(c), a.sup(b).sup(c)))
+        .collect();
+    let mut order: Vec<usize> = (0..tris.len()).collect();
+    order.sort_by_key(|&i| OrderedFloat(boxes[i].0.x));
+    let mut pairs = Vec::new();
+    let mut open: Vec<usize> = Vec::new();
+    for &i in &order {
+        let (min, max) = boxes[i];
+        open.retain(|&j| boxes[j].1.x >= min.x);
+        for &j in &open {
+            let (other_min, other_max) = boxes[j];
+            if min.y <= other_max.y
+                && other_min.y <= max.y
+                && min.z <= other_max.z
+                && other_min.z <= max.z
+                && wanted(i.min(j), i.max(j))
+                && triangles_cut(&tris[i], &tris[j])
+            {
+                pairs.push((i.min(j), i.max(j)));
+            }
+        }
+        open.push(i);
+    }
+    pairs.sort_unstable();
+    pairs
+}
+
+/// Whether `a` and `b` pass through each other: each crosses the other's
+/// plane, and the stretches where they do overlap along the line the
+/// planes meet in.
+fn triangles_cut(a: &[Point3<f32>; 3], b: &[Point3<f32>; 3]) -> bool {
+    let (Some(plane_a), Some(plane_b)) = (Plane::through(a), Plane::through(b)) else {
+        return false;
+    };
+    let (Some(across_a), Some(across_b)) = (crossing(a, &plane_b), crossing(b, &plane_a)) else {
+        return false;
+    };
+    let Some(line) = plane_a.normal.cross(&plane_b.normal).try_normalize(1e-6) else {
+        return false;
+    };
+    let span = |(p, q): (Point3<f32>, Point3<f32>)| {
+        let (s, t) = (line.dot(&p.coords), line.dot(&q.coords));
+        (s.min(t), s.max(t))
+    };
+    let ((a0, a1), (b0, b1)) = (span(across_a), span(across_b));
+    a0.max(b0) + PLANE_EPSILON < a1.min(b1)
+}
+
+/// Where `tri` crosses `plane`, when it has corners on both sides.
+fn crossing(tri: &[Point3<f32>; 3], plane: &Plane) -> Option<(Point3<f32>, Point3<f32>)> {
+    let d = tri.map(|p| plane.distance(&p));
+    if !d.iter().any(|&d| d > 0.0) || !d.iter().any(|&d| d < 0.0) {
+        return None;
+    }
+    let mut points = Vec::with_capacity(2);
+    for i in 0..3 {
+        let j = (i + 1) % 3;
+        if d[i] == 0.0 {
+            points.push(tri[i]);
+        } else if d[i] * d[j] < 0.0 {
+            points.push(tri[i] + (tri[j] - tri[i]) * (d[i] / (d[i] - d[j])));
+        }
+    }
+    Some((points[0], *points.get(1)?))
+}
+
+/// A binary space partitioning tree over the pieces of a group, each node
+/// splitting space along the plane of the pieces lying in it.
+struct Bsp {
+    nodes: Vec<BspNode>,
+}
+
+struct BspNode {
+    plane: Plane,
+    /// The pieces in the plane: first as indices into the fragments the
+    /// tree was built from, then as (mesh, triangle) of a [`SplitGroup`].
+    on: Vec<(usize, usize)>,
+    front: Option<usize>,
+    back: Option<usize>,
+}
+
+impl Bsp {
+    /// A tree over `fragments`, returned with them and the pieces cut
+    /// while building it, or `None` past [`MAX_FRAGMENTS`]. The root is
+    /// the first node.
+    fn build(mut fragments: Vec<Fragment>) -> Option<(Bsp, Vec<Fragment>)> {
+        let mut nodes: Vec<BspNode> = Vec::new();
+        // Lists of pieces still to sort, with the node and side they hang
+        // from; a stack rather than recursion, as a convex mesh makes a
+        // tree as deep as it has triangles.
+        let mut work = vec![(
+            (0..fragments.len()).collect::<Vec<_>>(),
+            None::<(usize, bool)>,
+        )];
+        while let Some((list, parent)) = work.pop() {
+            let Some((&first, rest)) = list.split_first() else {
+                continue;
+            };
+            let plane = fragments[first].plane;
+            let mut on = vec![(0, first)];
+            let (mut front, mut back) = (Vec::new(), Vec::new());
+            for &id in rest {
+                match fragments[id].split(&plane) {
+                    Side::On => on.push((0, id)),
+                    Side::Front => front.push(id),
+                    Side::Back => back.push(id),
+                    Side::Both(ahead, behind) => {
+                        for piece in ahead {
+                            front.push(fragments.len());
+                            fragments.push(piece);
+                        }
+                        for piece in behind {
+                            back.push(fragments.len());
+                            fragments.push(piece);
+                        }
+                    }
+                }
+            }
+            if fragments.len() > MAX_FRAGMENTS {
+                return None;
+            }
+            let node = nodes.len();
+            nodes.push(BspNode {
+                plane,
+                on,
+                front: None,
+                back: None,
+            });
+            match parent {
+                Some((parent, true)) => nodes[parent].front = Some(node),
+                Some((parent, false)) => nodes[parent].back = Some(node),
+                None => {}
+            }
+            if !front.is_empty() {
+                work.push((front, Some((node, true))));
+            }
+            if !back.is_empty() {
+                work.push((back, Some((node, false))));
+            }
+        }
+        Some((Bsp { nodes }, fragments))
+    }
+
+    /// Call `visit` with the pieces of each node, farthest from `eye`
+    /// first.
+    fn back_to_front(&self, eye: &Vector4<f32>, mut visit: impl FnMut(&[(usize, usize)])) {
+        enum Step {
+            Enter(usize),
+            Draw(usize),
+        }
+        let mut steps = Vec::new();
+        if !self.nodes.is_empty() {
+            steps.push(Step::Enter(0));
+        }
+        while let Some(step) = steps.pop() {
+            match step {
+                Step::Enter(n) => {
+                    let node = &self.nodes[n];
+                    let side = node.plane.normal.dot(&eye.xyz()) + node.plane.offset * eye.w;
+                    let (near, far) = if side > 0.0 {
+                        (node.front, node.back)
+                    } else {
+                        (node.back, node.front)
+                    };
+                    steps.extend(near.map(Step::Enter));
+                    steps.push(Step::Draw(n));
+                    steps.extend(far.map(Step::Enter));
+                }
+                Step::Draw(n) => visit(&self.nodes[n].on),
+            }
+        }
+    }
+}
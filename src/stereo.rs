@@ -0,0 +1,64 @@
+//! Red/cyan anaglyph stereo: the scene drawn once from each eye and the
+//! two frames merged channel by channel, for viewing through red/cyan
+//! glasses.
+
+use nalgebra::{Matrix4, Point3, Vector3};
+
+/// Eye separation of [`Stereo::default`], in world units.
+pub const DEFAULT_EYE_SEPARATION: f32 = 0.2;
+/// Convergence distance of [`Stereo::default`], in world units.
+pub const DEFAULT_CONVERGENCE: f32 = 5.0;
+
+/// Where the two eyes of [`World::stereo`](crate::World::stereo) are and
+/// where they look.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Stereo {
+    /// Distance between the eyes, each half of it off the camera along its
+    /// right vector. Wider eyes give stronger depth.
+    pub eye_separation: f32,
+    /// Distance in front of the camera the eyes turn in to look at.
+    /// Things that far away sit in the screen, nearer ones come out of it.
+    /// Zero or less keeps the eyes parallel.
+    pub convergence: f32,
+}
+
+impl Default for Stereo {
+    fn default() -> Self {
+        Self {
+            eye_separation: DEFAULT_EYE_SEPARATION,
+            convergence: DEFAULT_CONVERGENCE,
+        }
+    }
+}
+
+impl Stereo {
+    /// The left and right eyes' view matrices, for a camera with
+    /// `view_mat`.
+    pub fn eye_views(&self, view_mat: &Matrix4<f32>) -> [Matrix4<f32>; 2] {
+        let half = self.eye_separation / 2.0;
+        [-half, half].map(|x| {
+            // In the camera's view space it sits at the origin looking
+            // down -z, with its right along +x.
+            let eye = Point3::new(x, 0.0, 0.0);
+            let target = if self.convergence > 0.0 {
+                Point3::new(0.0, 0.0, -self.convergence)
+            } else {
+                Point3::new(x, 0.0, -1.0)
+            };
+            Matrix4::look_at_rh(&eye, &target, &Vector3::y()) * view_mat
+        })
+    }
+}
+
+/// Merge the frame drawn for the left eye into `frame`, which holds the
+/// right eye's: red comes from the left eye, green and blue from the right.
+///
+/// # Panics
+///
+/// If the frames are not the same size.
+pub fn merge_anaglyph(left: &[u8], frame: &mut [u8]) {
+    assert_eq!(left.len(), frame.len(), "eye frames differ in size");
+    for (left, pixel) in left.chunks_exact(4).zip(frame.chunks_exact_mut(4)) {
+        pixel[0] = left[0];
+    }
+}
@@ -0,0 +1,93 @@
+//! Where [`World::draw_to`](crate::World::draw_to) copies its finished
+//! frame, for embedding the renderer in something that owns the pixels,
+//! like a GUI panel whose rows are padded.
+//!
+//! The renderer itself only draws into tightly packed RGBA. A target that
+//! stores its pixels that way is drawn into directly; any other gets the
+//! finished frame handed over a row at a time.
+
+/// A grid of RGBA pixels to copy frames out to.
+pub trait RenderTarget {
+    fn width(&self) -> u32;
+    fn height(&self) -> u32;
+    /// Bytes from the start of one row to the start of the next.
+    fn stride(&self) -> usize;
+
+    /// Copy a row of `width` tightly packed RGBA pixels into row `y`.
+    fn put_row(&mut self, y: u32, rgba: &[u8]);
+
+    /// Every pixel as one tightly packed RGBA slice, if that is how they
+    /// are stored. Frames are then drawn straight into it, without a copy.
+    fn packed(&mut self) -> Option<&mut [u8]> {
+        None
+    }
+}
+
+/// RGBA rows in a byte buffer, `stride` bytes apart. Bytes between the
+/// end of one row and the start of the next are never written.
+pub struct BufferTarget<'a> {
+    buf: &'a mut [u8],
+    width: u32,
+    height: u32,
+    stride: usize,
+}
+
+impl<'a> BufferTarget<'a> {
+    /// Rows packed right after each other, as [`World::draw`] takes them.
+    ///
+    /// [`World::draw`]: crate::World::draw
+    pub fn new(buf: &'a mut [u8], width: u32, height: u32) -> Self {
+        Self::with_stride(buf, width, height, width as usize * 4)
+    }
+
+    /// Rows starting every `stride` bytes.
+    ///
+    /// # Panics
+    ///
+    /// If `stride` is less than a row of pixels, or `buf` ends before the
+    /// last row does.
+    pub fn with_stride(buf: &'a mut [u8], width: u32, height: u32, stride: usize) -> Self {
+        let row = width as usize * 4;
+        assert!(stride >= row, "stride {stride} is shorter than a row");
+        let needed = if height == 0 {
+            0
+        } else {
+            stride * (height as usize - 1) + row
+        };
+        assert!(
+            buf.len() >= needed,
+            "buffer of {} bytes is too short for {needed}",
+            buf.len()
+        );
+        Self {
+            buf,
+            width,
+            height,
+            stride,
+        }
+    }
+}
+
+impl RenderTarget for BufferTarget<'_> {
+    fn width(&self) -> u32 {
+        self.width
+    }
+
+    fn height(&self) -> u32 {
+        self.height
+    }
+
+    fn stride(&self) -> usize {
+        self.stride
+    }
+
+    fn put_row(&mut self, y: u32, rgba: &[u8]) {
+        let start = y as usize * self.stride;
+        self.buf[start..start + rgba.len()].copy_from_slice(rgba);
+    }
+
+    fn packed(&mut self) -> Option<&mut [u8]> {
+        let len = self.width as usize * self.height as usize * 4;
+        (self.stride == self.width as usize * 4).then(|| &mut self.buf[..len])
+    }
+}
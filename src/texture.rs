@@ -0,0 +1,230 @@
+//! Textures generated in code, for checking UVs and perspective without
+//! image files in the repo.
+//!
+//! Texels are tightly packed RGBA rows, top row first, the same layout as a
+//! rendered frame. `u` runs left to right and `v` top to bottom, each over
+//! `0.0..1.0`; outside it the texture's [`WrapMode`] decides.
+
+use crate::mesh::Color;
+
+/// Texels to a side of the [`Texture::uv_gradient`] texture, one for every
+/// channel value.
+pub const GRADIENT_SIZE: u32 = 256;
+/// Texels to a side of one [`Texture::grid`] cell.
+pub const GRID_CELL: u32 = 32;
+
+/// How a sample between texel centres is colored.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum FilterMode {
+    /// The texel the sample falls in. Blocky when magnified.
+    #[default]
+    Nearest,
+    /// A blend of the four texels whose centres surround the sample.
+    Bilinear,
+}
+
+/// What lies past the edges of a texture.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum WrapMode {
+    /// The texture again, so UV 1.25 samples like 0.25.
+    #[default]
+    Repeat,
+    /// The edge texels, stretched out forever.
+    Clamp,
+    /// The texture flipped every other time, so edges meet their own
+    /// reflection without a seam.
+    Mirror,
+}
+
+impl WrapMode {
+    /// Texel index `i` of a side `size` texels long, brought inside it.
+    pub fn wrap(self, i: i64, size: u32) -> usize {
+        let size = size as i64;
+        let i = match self {
+            WrapMode::Repeat => i.rem_euclid(size),
+            WrapMode::Clamp => i.clamp(0, size - 1),
+            WrapMode::Mirror => {
+                let i = i.rem_euclid(2 * size);
+                if i < size { i } else { 2 * size - 1 - i }
+            }
+        };
+        i as usize
+    }
+}
+
+/// Index of a texture in a [`crate::MaterialLibrary`], set as a
+/// [`crate::Material::texture`]. Handles stay valid for the life of the
+/// library.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TextureHandle(pub(crate) usize);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Texture {
+    width: u32,
+    height: u32,
+    rgba: Vec<u8>,
+    /// Bumped on every change to the texels, so a redraw can tell.
+    generation: u64,
+    pub filter: FilterMode,
+    pub wrap: WrapMode,
+}
+
+impl Texture {
+    /// A `width` by `height` texture colored texel by texel from the top
+    /// left, sampled nearest and repeating. Sides of zero are taken as one.
+    pub fn from_fn(width: u32, height: u32, texel: impl Fn(u32, u32) -> Color) -> Self {
+        let (width, height) = (width.max(1), height.max(1));
+        let mut rgba = Vec::with_capacity(width as usize * height as usize * 4);
+        for y in 0..height {
+            for x in 0..width {
+                let c = texel(x, y);
+                rgba.extend_from_slice(&[c.r, c.g, c.b, c.a]);
+            }
+        }
+        Self {
+            width,
+            height,
+            rgba,
+            generation: 0,
+            filter: FilterMode::default(),
+            wrap: WrapMode::default(),
+        }
+    }
+
+    /// A checkerboard of `size` by `size` squares, one texel each, starting
+    /// with `color_a` in the top left corner.
+    pub fn checker(size: u32, color_a: Color, color_b: Color) -> Self {
+        Self::from_fn(
+            size,
+            size,
+            |x, y| {
+                if (x + y) % 2 == 0 { color_a } else { color_b }
+            },
+        )
+    }
+
+    /// `u` in the red channel and `v` in the green one, so every texel tells
+    /// where it was sampled.
+    pub fn uv_gradient() -> Self {
+        Self::from_fn(GRADIENT_SIZE, GRADIENT_SIZE, |x, y| {
+            Color::rgb(x as u8, y as u8, 0)
+        })
+    }
+
+    /// One [`GRID_CELL`] of `bg` with `line_width` texels of `fg` along its
+    /// top and left edges, which repeat into a grid.
+    pub fn grid(line_width: u32, bg: Color, fg: Color) -> Self {
+        Self::from_fn(GRID_CELL, GRID_CELL, |x, y| {
+            if x < line_width || y < line_width {
+                fg
+            } else {
+                bg
+            }
+        })
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// The packed RGBA texels.
+    pub fn rgba(&self) -> &[u8] {
+        &self.rgba
+    }
+
+    /// How many times the texels have been changed since the texture was
+    /// made.
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    /// Blend `color` over the texel at column `x` and row `y`, with its
+    /// alpha scaled by `coverage`. Positions off the texture are ignored.
+    pub(crate) fn blend_texel(&mut self, x: u32, y: u32, color: Color, coverage: f32) {
+        if x >= self.width || y >= self.height {
+            return;
+        }
+        let a = (color.a as f32 * coverage.clamp(0.0, 1.0)).round() as u8;
+        let i = (y as usize * self.width as usize + x as usize) * 4;
+        crate::raster::blend(&mut self.rgba[i..i + 4], &Color { a, ..color });
+        self.generation += 1;
+    }
+
+    /// Trade the texels for `rgba`, packed the same way, without counting
+    /// it as a change; see [`Texture::mark_changed`].
+    pub(crate) fn swap_rgba(&mut self, rgba: &mut Vec<u8>) {
+        debug_assert_eq!(rgba.len(), self.rgba.len());
+        std::mem::swap(&mut self.rgba, rgba);
+    }
+
+    /// Count the texels as changed, for a redraw to notice.
+    pub(crate) fn mark_changed(&mut self) {
+        self.generation += 1;
+    }
+
+    /// The same texture sampled with `filter`.
+    pub fn with_filter(mut self, filter: FilterMode) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    /// The same texture wrapped with `wrap`.
+    pub fn with_wrap(mut self, wrap: WrapMode) -> Self {
+        self.wrap = wrap;
+        self
+    }
+
+    /// The texel at column `x` and row `y`, brought inside the texture by
+    /// its wrap mode.
+    pub fn texel(&self, x: i64, y: i64) -> Color {
+        let x = self.wrap.wrap(x, self.width);
+        let y = self.wrap.wrap(y, self.height);
+        let i = (y * self.width as usize + x) * 4;
+        let p = &self.rgba[i..i + 4];
+        Color {
+            r: p[0],
+            g: p[1],
+            b: p[2],
+            a: p[3],
+        }
+    }
+
+    /// The color at `(u, v)` under the texture's filter and wrap modes.
+    pub fn sample(&self, u: f32, v: f32) -> Color {
+        let (x, y) = (u * self.width as f32, v * self.height as f32);
+        match self.filter {
+            FilterMode::Nearest => self.texel(x.floor() as i64, y.floor() as i64),
+            FilterMode::Bilinear => {
+                // Texel centres sit half a texel in, so a sample exactly on
+                // one gets that texel alone.
+                let (x, y) = (x - 0.5, y - 0.5);
+                let (x0, y0) = (x.floor(), y.floor());
+                let (fx, fy) = (x - x0, y - y0);
+                let (x0, y0) = (x0 as i64, y0 as i64);
+                let corners = [
+                    (self.texel(x0, y0), (1.0 - fx) * (1.0 - fy)),
+                    (self.texel(x0 + 1, y0), fx * (1.0 - fy)),
+                    (self.texel(x0, y0 + 1), (1.0 - fx) * fy),
+                    (self.texel(x0 + 1, y0 + 1), fx * fy),
+                ];
+                let blend = |channel: fn(&Color) -> u8| {
+                    let sum: f32 = corners
+                        .iter()
+                        .map(|(c, weight)| channel(c) as f32 * weight)
+                        .sum();
+                    sum.round().clamp(0.0, 255.0) as u8
+                };
+                Color {
+                    r: blend(|c| c.r),
+                    g: blend(|c| c.g),
+                    b: blend(|c| c.b),
+                    a: blend(|c| c.a),
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,204 @@
+//! Recorded camera paths for repeatable flythroughs.
+//!
+//! A track saves as plain text, one keyframe per line of seven numbers:
+//!
+//! ```text
+//! # time  position  target
+//! 0.25 0 0 -5 0 0 0
+//! ```
+
+use std::fmt;
+use std::io;
+use std::path::Path;
+
+use crate::camera::Camera;
+use nalgebra::Point3;
+
+/// Why a camera track could not be loaded.
+#[derive(Debug)]
+pub enum TrackError {
+    Io(io::Error),
+    Parse { line: usize, message: String },
+}
+
+impl fmt::Display for TrackError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TrackError::Io(err) => write!(f, "{err}"),
+            TrackError::Parse { line, message } => write!(f, "line {line}: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for TrackError {}
+
+impl From<io::Error> for TrackError {
+    fn from(err: io::Error) -> Self {
+        TrackError::Io(err)
+    }
+}
+
+/// Where the camera was and what it looked at, at one moment.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Keyframe {
+    pub time: f32,
+    pub position: Point3<f32>,
+    pub target: Point3<f32>,
+}
+
+/// Camera keyframes in time order, played back along a Catmull-Rom spline
+/// through them.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CameraTrack {
+    keyframes: Vec<Keyframe>,
+}
+
+impl CameraTrack {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn keyframes(&self) -> &[Keyframe] {
+        &self.keyframes
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keyframes.is_empty()
+    }
+
+    /// Time of the last keyframe, or zero for an empty track.
+    pub fn duration(&self) -> f32 {
+        self.keyframes.last().map_or(0.0, |key| key.time)
+    }
+
+    /// Add a keyframe for `camera` at time `t`. Times must increase; a
+    /// keyframe at or before the last one is ignored.
+    pub fn record(&mut self, t: f32, camera: &Camera) {
+        if self.keyframes.last().is_some_and(|last| t <= last.time) {
+            return;
+        }
+        self.keyframes.push(Keyframe {
+            time: t,
+            position: camera.position,
+            target: camera.target(),
+        });
+    }
+
+    /// Camera position and target at time `t`. Before the first keyframe or
+    /// after the last the track holds that pose rather than extrapolating.
+    ///
+    /// # Panics
+    ///
+    /// If the track is empty.
+    pub fn sample(&self, t: f32) -> (Point3<f32>, Point3<f32>) {
+        let keys = &self.keyframes;
+        assert!(!keys.is_empty(), "sampling an empty camera track");
+        let last = keys.len() - 1;
+        // Index of the keyframe starting the segment containing `t`.
+        let i = keys.partition_point(|key| key.time <= t);
+        if i == 0 {
+            return (keys[0].position, keys[0].target);
+        }
+        if i > last {
+            return (keys[last].position, keys[last].target);
+        }
+        let (k0, k1, k2, k3) = (
+            &keys[i.saturating_sub(2)],
+            &keys[i - 1],
+            &keys[i],
+            &keys[(i + 1).min(last)],
+        );
+        let u = (t - k1.time) / (k2.time - k1.time);
+        (
+            catmull_rom(k0.position, k1.position, k2.position, k3.position, u),
+            catmull_rom(k0.target, k1.target, k2.target, k3.target, u),
+        )
+    }
+
+    /// Move `camera` to the pose at time `t` and face its target.
+    pub fn apply(&self, t: f32, camera: &mut Camera) {
+        let (position, target) = self.sample(t);
+        camera.position = position;
+        camera.look_at(target);
+    }
+
+    /// Parse a saved track, one `time px py pz tx ty tz` keyframe per line.
+    /// Blank lines and lines starting with `#` are skipped.
+    pub fn parse(text: &str) -> Result<Self, TrackError> {
+        let mut track = Self::new();
+        for (number, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let parse_error = |message: String| TrackError::Parse {
+                line: number + 1,
+                message,
+            };
+            let values: Vec<f32> = line
+                .split_whitespace()
+                .map(str::parse)
+                .collect::<Result<_, _>>()
+                .map_err(|err| parse_error(format!("bad number: {err}")))?;
+            let &[time, px, py, pz, tx, ty, tz] = values.as_slice() else {
+                return Err(parse_error(format!(
+                    "expected 7 numbers, found {}",
+                    values.len()
+                )));
+            };
+            if track.keyframes.last().is_some_and(|last| time <= last.time) {
+                return Err(parse_error(format!(
+                    "time {time} is not after the last keyframe"
+                )));
+            }
+            track.keyframes.push(Keyframe {
+                time,
+                position: Point3::new(px, py, pz),
+                target: Point3::new(tx, ty, tz),
+            });
+        }
+        Ok(track)
+    }
+
+    /// Load a track saved by [`CameraTrack::save`].
+    pub fn from_path(path: impl AsRef<Path>) -> Result<Self, TrackError> {
+        Self::parse(&std::fs::read_to_string(path)?)
+    }
+
+    /// Write the track in the format [`CameraTrack::parse`] reads.
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        std::fs::write(path, self.to_string())
+    }
+}
+
+impl fmt::Display for CameraTrack {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "# time  position  target")?;
+        for key in &self.keyframes {
+            let (p, t) = (key.position, key.target);
+            writeln!(
+                f,
+                "{} {} {} {} {} {} {}",
+                key.time, p.x, p.y, p.z, t.x, t.y, t.z
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Uniform Catmull-Rom between `p1` and `p2`, `u` running from 0 to 1.
+fn catmull_rom(
+    p0: Point3<f32>,
+    p1: Point3<f32>,
+    p2: Point3<f32>,
+    p3: Point3<f32>,
+    u: f32,
+) -> Point3<f32> {
+    let (u2, u3) = (u * u, u * u * u);
+    let coords = (p1.coords * 2.0
+        + (p2.coords - p0.coords) * u
+        + (p0.coords * 2.0 - p1.coords * 5.0 + p2.coords * 4.0 - p3.coords) * u2
+        + (p1.coords * 3.0 - p0.coords - p2.coords * 3.0 + p3.coords) * u3)
+        * 0.5;
+    Point3::from(coords)
+}
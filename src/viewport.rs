@@ -0,0 +1,99 @@
+//! Parts of the frame drawn through their own camera and projection, for
+//! split screens and picture-in-picture views like a minimap.
+
+use nalgebra::Matrix4;
+
+use crate::depth::DepthConvention;
+
+/// How a [`Viewport`] projects what its camera sees.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum Projection {
+    /// [`World::proj_mat`](crate::World::proj_mat) as it is, with its clip
+    /// planes and depth convention. It is made for the whole frame, so it
+    /// stretches in a viewport of another shape.
+    #[default]
+    Main,
+    /// A perspective with a vertical field of view of `fovy` radians,
+    /// fitted to the viewport's shape.
+    Perspective { fovy: f32, near: f32, far: f32 },
+    /// Parallel projection showing `height` world units from the bottom of
+    /// the viewport to the top, as wide as its shape makes it. Impostor
+    /// spheres, which are traced through a perspective, are left out.
+    Orthographic { height: f32, near: f32, far: f32 },
+}
+
+impl Projection {
+    /// The projection matrix for a viewport `aspect` times wider than it
+    /// is tall, given the world's `main` projection and `depth`
+    /// convention.
+    ///
+    /// Unlike a perspective, an orthographic projection spreads the whole
+    /// range between its planes over the drawn NDC z of `0.0..=1.0`,
+    /// whichever way depth runs.
+    pub fn matrix(&self, main: &Matrix4<f32>, aspect: f32, depth: DepthConvention) -> Matrix4<f32> {
+        match *self {
+            Projection::Main => *main,
+            Projection::Perspective { fovy, near, far } => {
+                depth.perspective(aspect, fovy, near, far)
+            }
+            Projection::Orthographic { height, near, far } => {
+                let mut matrix = Matrix4::identity();
+                matrix[(0, 0)] = 2.0 / (height * aspect);
+                matrix[(1, 1)] = 2.0 / height;
+                // View z runs from -near to -far in front of the camera.
+                let (at_near, at_far) = match depth {
+                    DepthConvention::Standard => (0.0, 1.0),
+                    DepthConvention::Reversed => (1.0, 0.0),
+                };
+                matrix[(2, 2)] = (at_near - at_far) / (far - near);
+                matrix[(2, 3)] = at_near - near * (at_far - at_near) / (far - near);
+                matrix
+            }
+        }
+    }
+}
+
+/// A rectangle of the frame drawn through one camera.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Viewport {
+    /// Left, top, width and height as fractions of the frame, so a
+    /// viewport keeps its share of the window as that is resized.
+    pub rect: (f32, f32, f32, f32),
+    /// The camera looked through: 0 for
+    /// [`World::camera`](crate::World::camera) and `n` for
+    /// `World::cameras[n - 1]`. Viewports of cameras that don't exist are
+    /// not drawn.
+    pub camera_index: usize,
+    pub projection: Projection,
+}
+
+impl Default for Viewport {
+    /// The whole frame through the main camera and projection.
+    fn default() -> Self {
+        Self {
+            rect: (0.0, 0.0, 1.0, 1.0),
+            camera_index: 0,
+            projection: Projection::Main,
+        }
+    }
+}
+
+impl Viewport {
+    /// Left, top, width and height in pixels of a `width * height` frame,
+    /// clamped to it. Edges are rounded, so viewports sharing an edge
+    /// share it to the pixel.
+    pub fn pixel_rect(&self, width: u32, height: u32) -> (u32, u32, u32, u32) {
+        let (x, y, w, h) = self.rect;
+        let edge = |fraction: f32, size: u32| {
+            (fraction * size as f32).round().clamp(0.0, size as f32) as u32
+        };
+        let (left, right) = (edge(x, width), edge(x + w, width));
+        let (top, bottom) = (edge(y, height), edge(y + h, height));
+        (
+            left,
+            top,
+            right.saturating_sub(left),
+            bottom.saturating_sub(top),
+        )
+    }
+}
@@ -0,0 +1,80 @@
+//! Noticing when files on disk change, by polling their modification times
+//! rather than asking the OS to report changes.
+
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+
+/// How often [`FileWatcher::changed`] looks at the files by default.
+pub const WATCH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// A set of files checked for changes at most once per `interval`.
+#[derive(Debug, Clone)]
+pub struct FileWatcher {
+    pub interval: Duration,
+    last_check: Option<Instant>,
+    /// Each file with its modification time when last checked, `None` if
+    /// it could not be read.
+    files: Vec<(PathBuf, Option<SystemTime>)>,
+}
+
+impl Default for FileWatcher {
+    fn default() -> Self {
+        Self::new(WATCH_INTERVAL)
+    }
+}
+
+impl FileWatcher {
+    pub fn new(interval: Duration) -> Self {
+        Self {
+            interval,
+            last_check: None,
+            files: Vec::new(),
+        }
+    }
+
+    /// Start watching `path`, as it is now. Watching a file twice does
+    /// nothing.
+    pub fn watch(&mut self, path: impl AsRef<Path>) {
+        let path = path.as_ref();
+        if !self.files.iter().any(|(watched, _)| watched == path) {
+            self.files.push((path.to_path_buf(), modified(path)));
+        }
+    }
+
+    pub fn unwatch(&mut self, path: impl AsRef<Path>) {
+        self.files.retain(|(watched, _)| watched != path.as_ref());
+    }
+
+    /// Every watched file.
+    pub fn files(&self) -> impl Iterator<Item = &Path> {
+        self.files.iter().map(|(path, _)| path.as_path())
+    }
+
+    /// Files written, created or deleted since the last check, if
+    /// `interval` has passed since it; otherwise nothing.
+    pub fn changed(&mut self) -> Vec<PathBuf> {
+        let now = Instant::now();
+        if self
+            .last_check
+            .is_some_and(|last| now.duration_since(last) < self.interval)
+        {
+            return Vec::new();
+        }
+        self.last_check = Some(now);
+        let mut changed = Vec::new();
+        for (path, seen) in &mut self.files {
+            let current = modified(path);
+            if current != *seen {
+                *seen = current;
+                changed.push(path.clone());
+            }
+        }
+        changed
+    }
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path)
+        .and_then(|meta| meta.modified())
+        .ok()
+}
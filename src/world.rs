@@ -0,0 +1,3060 @@
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fmt;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::ops::AddAssign;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+use log::{error, warn};
+use nalgebra::{Matrix4, Perspective3, Point2, Point3, UnitQuaternion, Vector2, Vector3, Vector4};
+use ordered_float::OrderedFloat;
+
+use crate::animation::Animation;
+use crate::billboard::Billboard;
+use crate::bookmark::{BOOKMARK_SLOTS, CameraPose, CameraTransition};
+use crate::camera::{Camera, CameraMode};
+use crate::chunked::ChunkStream;
+use crate::collision::{OVERLAP_TINT, OVERLAP_TINT_AMOUNT};
+use crate::color::{linear_to_srgb, srgb_to_linear};
+use crate::depth::{DepthConvention, DepthPlane};
+use crate::fog::{Fog, FogMode};
+use crate::gizmo::GridSettings;
+use crate::impostor::SphereImpostor;
+use crate::label::{DepthSamples, Label, draw_labels};
+use crate::light::{Ambient, Light, LightKind, SceneLighting, ShadingModel, Surface};
+use crate::loader::PendingLoad;
+use crate::lod::{Lods, select_lod};
+use crate::material::{Material, MaterialHandle, MaterialLibrary, MaterialOverride};
+use crate::mesh::cube::CubeMesh;
+use crate::mesh::{Color, ImportOptions, Mesh, MeshError, MeshPrimitives, Triangle};
+use crate::palette::IdPalette;
+use crate::particle::ParticleEmitter;
+use crate::pipeline::{
+    aabb_intersects_frustum, clip_to_screen, extract_frustum_planes, project_vertex,
+};
+use crate::planar_shadow::{self, PLANAR_SHADOW_LIFT, PlanarShadowConfig};
+use crate::profile::{Profiler, Stage, StageTimes};
+use crate::raster::{
+    Primitive, RasterBackend, RasterPoint, RasterSettings, RasterSphere, RasterTriangle,
+    SphereShading, TriangleUv, depth_gray, is_front_facing, on_screen, point_on_screen, rasterize,
+    screen_cross, screen_extent, sliver_width, thick_line,
+};
+use crate::render_scale::AdaptiveScale;
+use crate::render_texture::RenderTexture;
+use crate::shadow::ShadowMap;
+use crate::sky::{Sky, fill_sky, sky_gradient};
+use crate::splitting::SplitCache;
+use crate::stereo::{Stereo, merge_anaglyph};
+use crate::target::RenderTarget;
+use crate::viewport::{Projection, Viewport};
+
+/// Size of the depth buckets, in view space units, used to order the
+/// transparent pass. Triangles whose depths round to the same bucket are
+/// ordered by object handle and then by triangle index instead, so ties are
+/// always broken the same way.
+pub const TRANSPARENT_DEPTH_QUANTUM: f32 = 1.0 / 1024.0;
+
+/// Width in output pixels of the selection outline.
+pub const OUTLINE_WIDTH: f32 = 2.0;
+/// Width in output pixels of the lines of [`World::wireframe`].
+pub const WIREFRAME_WIDTH: f32 = 1.0;
+
+/// [`World::visible_layers`] with every layer shown.
+pub const ALL_LAYERS: u32 = u32::MAX;
+
+/// Color of the see-through cube standing in for a mesh that is still
+/// loading, see [`World::load_object_async`].
+pub const PLACEHOLDER_COLOR: Color = Color {
+    r: 150,
+    g: 150,
+    b: 150,
+    a: 80,
+};
+
+/// Screen space triangles with less than half this many square pixels are
+/// treated as having no area at all: no pixel center can land inside them
+/// reliably, and their edges have no usable direction.
+pub const DEGENERATE_AREA: f32 = 1e-6;
+
+/// Triangles thinner than this many output pixels are skipped while
+/// [`World::skip_slivers`] is set.
+pub const SLIVER_WIDTH: f32 = 0.5;
+
+/// How the frame is cleared before geometry is drawn.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ClearMode {
+    /// Fill with [`World::clear_color`].
+    Solid,
+    /// Lerp from `top` on the first scanline to `bottom` on the last, a cheap
+    /// sky and horizon.
+    Gradient { top: Color, bottom: Color },
+    /// Shade each pixel by where its view ray points, so the horizon stays
+    /// put as the camera looks up and down.
+    Sky(Sky),
+}
+
+/// Counts from the last frame drawn, for profiling and the stats overlay.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RenderStats {
+    /// Objects at least partly inside the view, whose triangles were
+    /// looked at one by one.
+    pub objects_drawn: usize,
+    /// Objects skipped whole because their bounding box is outside the
+    /// view.
+    pub objects_culled: usize,
+    /// Objects not drawn because they or their layer are hidden.
+    pub objects_hidden: usize,
+    /// Triangles in every model, before any culling.
+    pub triangles_submitted: usize,
+    /// Triangles with a corner behind the eye or past the far plane.
+    pub triangles_clipped: usize,
+    /// Triangles skipped before lighting because their screen bounding box
+    /// lies entirely outside the frame.
+    pub triangles_offscreen: usize,
+    /// Triangles facing the side [`World::cull_mode`] skips.
+    pub triangles_backface_culled: usize,
+    /// Triangles skipped for covering no area on screen, or for being
+    /// slivers while [`World::skip_slivers`] is set.
+    pub triangles_degenerate: usize,
+    /// Triangles smaller on screen than [`World::small_triangle_cull`],
+    /// whether dropped or gathered into points.
+    pub triangles_small: usize,
+    /// Triangles that survived clipping and back face culling and were
+    /// handed to the rasterizer.
+    pub triangles_rasterized: usize,
+    /// Impostor spheres that were on screen.
+    pub spheres_rasterized: usize,
+    /// Billboards and particles in front of the camera and on screen.
+    pub billboards_rasterized: usize,
+    /// Triangles of [`World::planar_shadows`] handed to the rasterizer.
+    pub planar_shadow_triangles: usize,
+    /// [`World::labels`] in front of the camera and not hidden.
+    pub labels_drawn: usize,
+    /// Vertices drawn as points by [`RenderMode::Points`].
+    pub vertices_drawn: usize,
+    /// Pixel writes by the rasterizer, counting every layer of overdraw and
+    /// overlays like outlines.
+    pub pixels_written: usize,
+    /// Pixels the rasterizer found hidden by the depth buffer and left
+    /// alone, instead of writing over what was there as it does for pixels
+    /// covered again from in front. [`World::front_to_back`] turns most
+    /// overdraw into these.
+    pub pixels_depth_rejected: usize,
+    /// Triangles lit by the rasterizer. A triangle is lit when the first of
+    /// its pixels passes the depth test, so one hidden behind what is
+    /// already drawn is never lit. With [`World::threads`] above 1, one
+    /// that spans several bands is lit once in each it shows in.
+    pub triangles_lit: usize,
+    /// Wall clock time of the whole [`World::draw`] call.
+    pub draw_time: Duration,
+    /// Where that time went. All zero unless
+    /// [`World::profiling_enabled`] is set.
+    pub stages: StageTimes,
+    /// Some object in view was drawn as a stand-in for geometry not read
+    /// yet, such as a chunk of [`World::load_chunked`], so the counts and
+    /// picture leave out what it stands for.
+    pub approximate: bool,
+}
+
+/// Counts of both frames together, as for the two eyes of a stereo frame.
+impl AddAssign for RenderStats {
+    fn add_assign(&mut self, other: Self) {
+        self.objects_drawn += other.objects_drawn;
+        self.objects_culled += other.objects_culled;
+        self.objects_hidden += other.objects_hidden;
+        self.triangles_submitted += other.triangles_submitted;
+        self.triangles_clipped += other.triangles_clipped;
+        self.triangles_offscreen += other.triangles_offscreen;
+        self.triangles_backface_culled += other.triangles_backface_culled;
+        self.triangles_degenerate += other.triangles_degenerate;
+        self.triangles_small += other.triangles_small;
+        self.triangles_rasterized += other.triangles_rasterized;
+        self.spheres_rasterized += other.spheres_rasterized;
+        self.billboards_rasterized += other.billboards_rasterized;
+        self.planar_shadow_triangles += other.planar_shadow_triangles;
+        self.labels_drawn += other.labels_drawn;
+        self.vertices_drawn += other.vertices_drawn;
+        self.pixels_written += other.pixels_written;
+        self.pixels_depth_rejected += other.pixels_depth_rejected;
+        self.triangles_lit += other.triangles_lit;
+        self.draw_time += other.draw_time;
+        self.approximate |= other.approximate;
+        for (stage, time) in other.stages.iter() {
+            self.stages.add(stage, time);
+        }
+    }
+}
+
+/// What the rasterizer writes for each surface.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum RenderMode {
+    /// Lit, fogged color.
+    #[default]
+    Shaded,
+    /// View distance as gray, white at the near plane and black at the far
+    /// plane, on a black background. Depth is per triangle, the same
+    /// average the painter's sort uses.
+    Depth,
+    /// Only the vertices, as unlit `size` pixel squares in the color of
+    /// the first triangle using each one, or the object's material. Quick
+    /// for previewing huge meshes, and shows point clouds with no faces.
+    Points { size: u32 },
+    /// Each mesh triangle unlit in a color of [`World::id_palette`] for
+    /// its index in the mesh, whatever its material, so duplicated
+    /// triangles, gaps and sorting mistakes stand out.
+    TriangleId,
+    /// Like [`RenderMode::TriangleId`], with one color per object, for its
+    /// index in [`World::models`].
+    ObjectId,
+}
+
+impl RenderMode {
+    /// The mode after this one, wrapping around.
+    pub fn next(self) -> Self {
+        match self {
+            RenderMode::Shaded => RenderMode::Depth,
+            RenderMode::Depth => RenderMode::Points { size: 2 },
+            RenderMode::Points { .. } => RenderMode::TriangleId,
+            RenderMode::TriangleId => RenderMode::ObjectId,
+            RenderMode::ObjectId => RenderMode::Shaded,
+        }
+    }
+
+    /// A short name for the mode, to show on screen.
+    pub fn name(self) -> &'static str {
+        match self {
+            RenderMode::Shaded => "shaded",
+            RenderMode::Depth => "depth",
+            RenderMode::Points { .. } => "points",
+            RenderMode::TriangleId => "triangle id",
+            RenderMode::ObjectId => "object id",
+        }
+    }
+}
+
+/// Which triangles are skipped, by which way they face the camera.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum CullMode {
+    /// Skip back faces. Right for closed meshes wound the engine's way.
+    #[default]
+    Back,
+    /// Skip front faces, for closed meshes wound the other way.
+    Front,
+    /// Draw both sides, lighting whichever side faces the camera.
+    None,
+}
+
+impl CullMode {
+    /// Whether a triangle facing the camera (`front`) or away is drawn.
+    pub fn draws(self, front: bool) -> bool {
+        match self {
+            CullMode::Back => front,
+            CullMode::Front => !front,
+            CullMode::None => true,
+        }
+    }
+}
+
+/// A mesh placed in the world at an offset from the origin. Several objects
+/// can share one mesh, see [`Object::instance`].
+pub struct Object {
+    pub mesh: Rc<dyn Mesh>,
+    /// Name to find the object by, with [`World::object`] or an
+    /// [`ObjectRef`], which stays right as objects come and go. Names are
+    /// unique within a world.
+    pub name: Option<String>,
+    pub offset_x: f32,
+    pub offset_y: f32,
+    pub offset_z: f32,
+    /// Orientation around the object's own origin, applied before the offset.
+    pub rotation: UnitQuaternion<f32>,
+    /// Uniform scale around the object's own origin, applied first.
+    pub scale: f32,
+    /// Motion applied by [`World::update`], if any.
+    pub animation: Option<Animation>,
+    /// Library material to draw with instead of the mesh's own colors.
+    pub material: Option<MaterialHandle>,
+    /// Per-object tweaks on top of `material`.
+    pub material_override: MaterialOverride,
+    /// Index in [`World::models`] of the object this one is attached to.
+    /// Its transform is then relative to the parent's, so it follows the
+    /// parent around. Set it with [`World::set_parent`], which rejects
+    /// cycles.
+    pub parent: Option<usize>,
+    /// Meshes drawn instead of `mesh`, picked each frame by how far the
+    /// object is in front of the camera: the first whose distance covers
+    /// it, or the last past all of them. Empty to always draw `mesh`, which
+    /// is still what shadows and picking use. See [`crate::lod`].
+    pub lods: Lods,
+    /// File `mesh` was loaded from, for [`World::reload_sources`]. Set by
+    /// [`World::load_object_async`].
+    pub source: Option<PathBuf>,
+    /// Draw the object at all. Hidden objects cast no shadows and aren't
+    /// picked, but keep their place in the hierarchy.
+    pub visible: bool,
+    /// Which of the 32 layers of [`World::visible_layers`] the object is
+    /// on, 0 by default. Layers past 31 are never shown.
+    pub layer: u32,
+    /// Where [`World::update`] found the object before moving it, to draw
+    /// it part of the way since, see [`World::interpolation`]. `None` for
+    /// objects added since the last update or snapped with
+    /// [`Object::snap`], which are drawn where they are.
+    pub previous: Option<Placement>,
+    /// Pushes the object's depth towards the eye when negative, or away
+    /// when positive, so a decal lying flat on another surface wins the
+    /// depth test against it. Like hardware polygon offset it is scaled by
+    /// how steeply each triangle's depth changes across the screen, plus
+    /// [`DEPTH_BIAS_UNIT`](crate::depth::DEPTH_BIAS_UNIT) for triangles
+    /// facing the eye head on; -1 is plenty for coplanar surfaces. Without
+    /// the depth buffer, objects at the same depth are painted in order of
+    /// their bias, the most negative last. 0 by default.
+    pub depth_bias: f32,
+}
+
+/// The offset, rotation and scale an object was placed with.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Placement {
+    pub offset: Vector3<f32>,
+    pub rotation: UnitQuaternion<f32>,
+    pub scale: f32,
+}
+
+/// An object of a [`World`], by its index in [`World::models`] or by its
+/// [`Object::name`]. Indices shift as objects are removed; names don't.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ObjectRef {
+    Index(usize),
+    Name(String),
+}
+
+impl From<usize> for ObjectRef {
+    fn from(index: usize) -> Self {
+        ObjectRef::Index(index)
+    }
+}
+
+impl From<&str> for ObjectRef {
+    fn from(name: &str) -> Self {
+        ObjectRef::Name(name.to_string())
+    }
+}
+
+impl From<String> for ObjectRef {
+    fn from(name: String) -> Self {
+        ObjectRef::Name(name)
+    }
+}
+
+impl fmt::Display for ObjectRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ObjectRef::Index(index) => write!(f, "object {index}"),
+            ObjectRef::Name(name) => write!(f, "object {name:?}"),
+        }
+    }
+}
+
+/// Why [`World::add_object`] refused an object.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AddObjectError {
+    /// Its mesh, or one of its levels of detail, is broken.
+    Mesh(MeshError),
+    /// Another object already has its name.
+    DuplicateName(String),
+}
+
+impl fmt::Display for AddObjectError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AddObjectError::Mesh(err) => err.fmt(f),
+            AddObjectError::DuplicateName(name) => {
+                write!(f, "there already is an object named {name:?}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for AddObjectError {}
+
+impl From<MeshError> for AddObjectError {
+    fn from(err: MeshError) -> Self {
+        AddObjectError::Mesh(err)
+    }
+}
+
+/// A supersampling factor [`World::set_ssaa`] doesn't support.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UnsupportedSsaa(pub u32);
+
+impl fmt::Display for UnsupportedSsaa {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "supersampling factor {} isn't one of 1, 2 or 4", self.0)
+    }
+}
+
+impl std::error::Error for UnsupportedSsaa {}
+
+/// Names [`World::index_names`] found on more than one object, each
+/// listed once, in the order they first appear.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DuplicateNames(pub Vec<String>);
+
+impl fmt::Display for DuplicateNames {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "more than one object named")?;
+        for (i, name) in self.0.iter().enumerate() {
+            let separator = if i == 0 { " " } else { ", " };
+            write!(f, "{separator}{name:?}")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for DuplicateNames {}
+
+/// Why [`World::set_parent`] or [`World::attach`] refused a change.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HierarchyError {
+    /// No object has this index.
+    NoSuchObject(usize),
+    /// No object has this name.
+    NoSuchName(String),
+    /// `parent` is `child` or one of its descendants.
+    Cycle { child: usize, parent: usize },
+}
+
+impl fmt::Display for HierarchyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HierarchyError::NoSuchObject(index) => write!(f, "no object {index}"),
+            HierarchyError::NoSuchName(name) => write!(f, "no object named {name:?}"),
+            HierarchyError::Cycle { child, parent } => {
+                write!(f, "object {parent} is attached to object {child}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for HierarchyError {}
+
+/// Why [`World::set_clip_planes`] refused a pair of planes.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ClipPlaneError {
+    /// The near plane has to be in front of the eye.
+    NearNotPositive(f32),
+    /// The far plane has to be a finite distance beyond the near one.
+    FarNotBeyondNear { near: f32, far: f32 },
+}
+
+impl fmt::Display for ClipPlaneError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ClipPlaneError::NearNotPositive(near) => {
+                write!(f, "near plane {near} must be greater than 0")
+            }
+            ClipPlaneError::FarNotBeyondNear { near, far } => {
+                write!(
+                    f,
+                    "far plane {far} must be finite and beyond near plane {near}"
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for ClipPlaneError {}
+
+impl Object {
+    /// Like [`Object::instance`], but failing with the first problem
+    /// [`Mesh::validate`] finds instead of leaving it to surface as an
+    /// index panic deep inside drawing.
+    pub fn try_instance(
+        mesh: Rc<dyn Mesh>,
+        offset_x: f32,
+        offset_y: f32,
+        offset_z: f32,
+    ) -> Result<Self, MeshError> {
+        mesh.validate()?;
+        Ok(Self::instance(mesh, offset_x, offset_y, offset_z))
+    }
+
+    /// An unrotated, unanimated object at the given offset.
+    pub fn new(mesh: impl Mesh + 'static, offset_x: f32, offset_y: f32, offset_z: f32) -> Self {
+        Self::instance(Rc::new(mesh), offset_x, offset_y, offset_z)
+    }
+
+    /// Like [`Object::new`], but drawing a mesh shared with other objects
+    /// instead of owning a copy of it. The mesh isn't checked; see
+    /// [`Object::try_instance`] or [`World::add_object`].
+    pub fn instance(mesh: Rc<dyn Mesh>, offset_x: f32, offset_y: f32, offset_z: f32) -> Self {
+        Object {
+            mesh,
+            name: None,
+            offset_x,
+            offset_y,
+            offset_z,
+            rotation: UnitQuaternion::identity(),
+            scale: 1.0,
+            animation: None,
+            material: None,
+            material_override: MaterialOverride::default(),
+            parent: None,
+            lods: Vec::new(),
+            source: None,
+            visible: true,
+            layer: 0,
+            previous: None,
+            depth_bias: 0.0,
+        }
+    }
+
+    pub fn with_animation(mut self, animation: Animation) -> Self {
+        self.animation = Some(animation);
+        self
+    }
+
+    pub fn with_material(mut self, material: MaterialHandle) -> Self {
+        self.material = Some(material);
+        self
+    }
+
+    pub fn with_lods(mut self, lods: Lods) -> Self {
+        self.lods = lods;
+        self
+    }
+
+    pub fn with_layer(mut self, layer: u32) -> Self {
+        self.layer = layer;
+        self
+    }
+
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    /// Whether the object is drawn while `visible_layers` are shown.
+    pub fn shown(&self, visible_layers: u32) -> bool {
+        self.visible
+            && 1u32
+                .checked_shl(self.layer)
+                .is_some_and(|bit| visible_layers & bit != 0)
+    }
+
+    /// Object to parent transform: scale first, then rotation, then the
+    /// offset. For objects without a parent this is the world transform;
+    /// see [`World::world_matrices`] for the rest.
+    pub fn model_mat(&self) -> Matrix4<f32> {
+        Matrix4::new_translation(&Vector3::new(self.offset_x, self.offset_y, self.offset_z))
+            * self.rotation.to_homogeneous()
+            * Matrix4::new_scaling(self.scale)
+    }
+
+    pub fn placement(&self) -> Placement {
+        Placement {
+            offset: Vector3::new(self.offset_x, self.offset_y, self.offset_z),
+            rotation: self.rotation,
+            scale: self.scale,
+        }
+    }
+
+    /// Draw the object where it is until the next update, rather than
+    /// sliding there from where the last one left it. Call it after
+    /// teleporting the object.
+    pub fn snap(&mut self) {
+        self.previous = None;
+    }
+
+    /// Like [`Object::model_mat`], but `alpha` of the way from `previous`
+    /// to where the object is now: offset and scale straight there, the
+    /// rotation along the shorter arc.
+    pub fn interpolated_mat(&self, alpha: f32) -> Matrix4<f32> {
+        let Some(previous) = self.previous.filter(|_| alpha < 1.0) else {
+            return self.model_mat();
+        };
+        let offset = previous.offset.lerp(&self.placement().offset, alpha);
+        // Opposite orientations have no shorter arc; jump to the new one.
+        let rotation = previous
+            .rotation
+            .try_slerp(&self.rotation, alpha, f32::EPSILON)
+            .unwrap_or(self.rotation);
+        let scale = previous.scale + (self.scale - previous.scale) * alpha;
+        Matrix4::new_translation(&offset) * rotation.to_homogeneous() * Matrix4::new_scaling(scale)
+    }
+}
+
+/// Everything needed to render a frame: the camera, the light, the objects
+/// and the projection matrix.
+pub struct World {
+    pub camera: Camera,
+    /// More cameras for [`World::viewports`] to look through, numbered
+    /// from 1 after `camera`.
+    pub cameras: Vec<Camera>,
+    /// Saved views of `camera`, one per slot, flown back to with
+    /// [`World::go_to_bookmark`].
+    pub bookmarks: Vec<Option<CameraPose>>,
+    /// Rectangles of the frame drawn in order, each through its own camera
+    /// and projection; later ones are drawn over earlier ones. One covering
+    /// the whole frame through `camera` and `proj_mat` by default.
+    pub viewports: Vec<Viewport>,
+    /// How many times a frame draws the render targets while a screen
+    /// showing one is in view, so how many screens deep a screen seen on
+    /// a screen goes. See [`crate::render_texture`]. 3 by default.
+    pub render_target_depth: u32,
+    pub light: Light,
+    pub models: Vec<Object>,
+    /// Materials objects can share by handle.
+    pub materials: MaterialLibrary,
+    /// Sphere impostor batches, drawn without triangles.
+    pub impostors: Vec<SphereImpostor>,
+    /// Camera-facing squares, drawn without meshes.
+    pub billboards: Vec<Billboard>,
+    /// Particle effects, stepped by [`World::update`] and drawn as
+    /// billboards.
+    pub emitters: Vec<ParticleEmitter>,
+    /// Text pinned to points in the scene, drawn over every view after
+    /// the scene.
+    pub labels: Vec<Label>,
+    pub proj_mat: Matrix4<f32>,
+    /// Number of threads used to rasterize a frame. The output is identical
+    /// for every thread count.
+    pub threads: usize,
+    /// How triangles are scanned for pixels. Every backend draws the same
+    /// image, so this only changes speed.
+    pub raster_backend: RasterBackend,
+    /// Decide which pixels a triangle covers with integer math on
+    /// coordinates snapped to 1/16 of a pixel, so the same scene renders
+    /// the same pixels on any machine. Off by default.
+    pub deterministic: bool,
+    /// Light in linear space and encode the result back to sRGB. When off,
+    /// the 8-bit colors are scaled directly.
+    pub gamma_correct: bool,
+    /// Hide banding in smooth shading with an ordered dither on triangles.
+    pub dither: bool,
+    /// Sub-pixel offset, in pixels, applied to the projection for the next
+    /// frames. Used to take jittered samples for anti-aliasing.
+    pub projection_jitter: (f32, f32),
+    /// Fade distant geometry into this fog. The background is cleared to
+    /// the fog color while it is set.
+    pub fog: Option<Fog>,
+    /// Darken the ground under every object with its shadow projected
+    /// flat onto a plane, a much cheaper shadow than the light's shadow
+    /// map. Off by default.
+    pub planar_shadows: Option<PlanarShadowConfig>,
+    /// Draw the scene once from each eye and merge the two into a
+    /// red/cyan anaglyph, at twice the cost of a frame. Off by default.
+    pub stereo: Option<Stereo>,
+    /// Background color for [`ClearMode::Solid`]. White by default.
+    pub clear_color: Color,
+    pub clear_mode: ClearMode,
+    pub render_mode: RenderMode,
+    /// Colors of [`RenderMode::TriangleId`] and [`RenderMode::ObjectId`].
+    pub id_palette: IdPalette,
+    pub shading_model: ShadingModel,
+    /// Light every surface gets on top of `light`'s. A third of the
+    /// surface color by default.
+    pub ambient: Ambient,
+    /// Draw the debug ground grid and world axes under the scene.
+    pub show_gizmos: bool,
+    pub grid: GridSettings,
+    /// Draw a short line along the normal of every drawn triangle.
+    pub show_normals: bool,
+    /// Outline every drawn mesh triangle in this color, one pixel wide,
+    /// just after the triangle itself, so nearer triangles still cover
+    /// it. Works in every render mode but points.
+    pub wireframe: Option<Color>,
+    /// Draw an unlit marker at the light's position, on top of the scene.
+    pub show_light: bool,
+    /// Outline the view of a camera, numbered as in
+    /// [`Viewport::camera_index`], through [`World::proj_mat`] in every
+    /// other view: twelve lines on top of the scene, showing what culling
+    /// keeps.
+    pub show_frustum: Option<usize>,
+    /// Tint objects in one of [`World::overlapping_pairs`] red, checking
+    /// again every frame.
+    pub highlight_overlaps: bool,
+    /// Which faces are skipped. Back faces by default.
+    pub cull_mode: CullMode,
+    /// Skip triangles thinner than [`SLIVER_WIDTH`] on screen, which cover
+    /// a scattering of pixels at most. Off by default.
+    pub skip_slivers: bool,
+    /// Skip triangles whose screen bounding box is smaller than this many
+    /// output pixels both ways, like the far-off triangles of a dense
+    /// model, which cover a pixel at most. Off by default.
+    pub small_triangle_cull: Option<f32>,
+    /// Rather than drop the triangles `small_triangle_cull` skips, gather
+    /// each object's by the pixel they fall in and write every pixel they
+    /// cover at least half of once, in their average color at their
+    /// average position. Costs the lighting of every small triangle. Off
+    /// by default.
+    pub small_triangle_points: bool,
+    /// Test every pixel of a mesh, billboard, point or sphere against a
+    /// per-pixel depth buffer on top of drawing them back to front, so
+    /// surfaces that cut through each other are drawn right. Off by
+    /// default. Its precision depends on [`World::depth_convention`].
+    pub depth_buffer: bool,
+    /// While [`World::depth_buffer`] is on, draw solid geometry nearest
+    /// first, so hidden pixels are rejected by the depth test rather than
+    /// written and painted over, and triangles hidden whole are never lit.
+    /// Blended geometry is drawn back to front after
+    /// it. Only the first of two surfaces at exactly the same depth is
+    /// drawn, and this reverses which one that is: the one that would be
+    /// painted last, rather than first, wins. On by default.
+    pub front_to_back: bool,
+    /// Cut triangles that pass through each other, within an object or
+    /// between objects, and draw them in an order that is right from
+    /// every side, for scenes too slow for [`World::depth_buffer`]. The
+    /// cuts are kept until objects move; while they hold still drawing
+    /// costs barely more than without. Objects cut up are drawn at full
+    /// detail, and their translucent triangles are still sorted by depth.
+    /// Off by default.
+    pub splitting_sort: bool,
+    /// One bit per [`Object::layer`]: objects on layers whose bit is clear
+    /// are hidden. Every layer is shown by default.
+    pub visible_layers: u32,
+    /// Index in `models` of the object drawn with an outline.
+    pub selected: Option<usize>,
+    /// Color of the selection outline.
+    pub outline_color: Color,
+    /// Seconds of animation time, advanced by [`World::update`].
+    pub time: f32,
+    /// How far from where the previous [`World::update`] found objects to
+    /// where the last one left them they are drawn, from 0 to 1. For
+    /// updates at a fixed rate, set it to the part of the next update's
+    /// time step that has passed, so motion stays smooth at any frame
+    /// rate. 1 by default, drawing objects where they are.
+    pub interpolation: f32,
+    /// Time each stage of drawing into [`RenderStats::stages`]. Off by
+    /// default, when the clock is never read.
+    pub profiling_enabled: bool,
+    /// Fit the clip planes around every object's bounding box whenever the
+    /// scene or the camera moves, instead of keeping those in `proj_mat`.
+    pub auto_clip_planes: bool,
+    /// How [`World::load_object_async`] and [`World::reload_sources`] read
+    /// their files.
+    pub import_options: ImportOptions,
+    /// Give every mesh those loads bring levels of detail for these
+    /// distances, made like [`simplified_lods`](crate::lod::simplified_lods)
+    /// on the loader thread. Empty by default, leaving objects'
+    /// [`Object::lods`] alone.
+    pub lod_distances: Vec<f32>,
+    ssaa: u32,
+    ssaa_buffer: Vec<u8>,
+    stats: RenderStats,
+    adaptive_scale: Option<AdaptiveScale>,
+    /// Level of detail each object was last drawn at, by index in `models`.
+    lod_levels: Vec<usize>,
+    /// What the clip planes were last fitted to by `auto_clip_planes`.
+    fitted_clip_planes: Option<u64>,
+    /// How `proj_mat` maps depth, which it has to be built for.
+    depth_convention: DepthConvention,
+    /// NDC z per pixel while `depth_buffer` is set, kept between frames.
+    depth_values: Vec<f32>,
+    /// Meshes still loading for objects drawn as placeholders meanwhile.
+    pending_loads: Vec<PendingLoad>,
+    /// Chunked meshes being streamed in, see [`World::load_chunked`].
+    pub(crate) streams: Vec<ChunkStream>,
+    /// Offscreen frames drawn into textures, see
+    /// [`World::add_render_target`].
+    pub(crate) render_targets: Vec<RenderTexture>,
+    /// Set while drawing the first pass of the render targets, when
+    /// screens show nothing.
+    pub(crate) innermost_screens: bool,
+    /// Index in `models` of each named object, as last known: `models`
+    /// can be edited directly, so a lookup checks the object still has
+    /// the name.
+    names: HashMap<String, usize>,
+    /// Where `camera` is flying to, stepped by [`World::update`].
+    camera_transition: Option<CameraTransition>,
+    /// Objects cut up for `splitting_sort`.
+    splits: SplitCache,
+    /// Frame drawn for a [`RenderTarget`] whose rows are not packed.
+    target_buffer: Vec<u8>,
+    /// The left eye's frame while `stereo` is set.
+    stereo_buffer: Vec<u8>,
+    /// Frame drawn for a viewport smaller than the whole frame.
+    viewport_buffer: Vec<u8>,
+}
+
+impl World {
+    /// A world drawing `models`. Names shared by several of them are
+    /// logged; use [`World::index_names`] to get them back as an error.
+    pub fn new(camera: Camera, light: Light, proj_mat: Matrix4<f32>, models: Vec<Object>) -> Self {
+        let mut world = World {
+            camera,
+            cameras: Vec::new(),
+            bookmarks: vec![None; BOOKMARK_SLOTS],
+            viewports: vec![Viewport::default()],
+            render_target_depth: 3,
+            light,
+            models,
+            materials: MaterialLibrary::new(),
+            impostors: Vec::new(),
+            billboards: Vec::new(),
+            emitters: Vec::new(),
+            labels: Vec::new(),
+            proj_mat,
+            threads: 1,
+            raster_backend: RasterBackend::default(),
+            deterministic: false,
+            gamma_correct: true,
+            dither: false,
+            projection_jitter: (0.0, 0.0),
+            fog: None,
+            planar_shadows: None,
+            stereo: None,
+            clear_color: Color::WHITE,
+            clear_mode: ClearMode::Solid,
+            render_mode: RenderMode::Shaded,
+            id_palette: IdPalette::Hues,
+            shading_model: ShadingModel::Lambert,
+            ambient: Ambient::default(),
+            show_gizmos: false,
+            grid: GridSettings::default(),
+            show_normals: false,
+            wireframe: None,
+            show_light: false,
+            show_frustum: None,
+            highlight_overlaps: false,
+            cull_mode: CullMode::Back,
+            skip_slivers: false,
+            small_triangle_cull: None,
+            small_triangle_points: false,
+            depth_buffer: false,
+            front_to_back: true,
+            splitting_sort: false,
+            visible_layers: ALL_LAYERS,
+            selected: None,
+            outline_color: Color {
+                r: 255,
+                g: 160,
+                b: 0,
+                a: 255,
+            },
+            time: 0.0,
+            interpolation: 1.0,
+            profiling_enabled: false,
+            auto_clip_planes: false,
+            import_options: ImportOptions::default(),
+            lod_distances: Vec::new(),
+            ssaa: 1,
+            ssaa_buffer: Vec::new(),
+            stats: RenderStats::default(),
+            adaptive_scale: None,
+            lod_levels: Vec::new(),
+            fitted_clip_planes: None,
+            depth_convention: DepthConvention::Standard,
+            depth_values: Vec::new(),
+            pending_loads: Vec::new(),
+            streams: Vec::new(),
+            render_targets: Vec::new(),
+            innermost_screens: false,
+            names: HashMap::new(),
+            camera_transition: None,
+            splits: SplitCache::default(),
+            target_buffer: Vec::new(),
+            stereo_buffer: Vec::new(),
+            viewport_buffer: Vec::new(),
+        };
+        if let Err(err) = world.index_names() {
+            warn!("{err}");
+        }
+        world
+    }
+
+    /// Render the world as seen through `view_mat` into `frame`, a tightly
+    /// packed RGBA buffer of `width * height` pixels.
+    ///
+    /// Opaque triangles are painted object by object, back to front.
+    /// Translucent ones (alpha below 255) are deferred to a second pass over
+    /// all objects, sorted by (quantized depth, object handle, triangle
+    /// index) where the object handle is its index in `models`.
+    ///
+    /// Each of [`World::viewports`] is drawn in turn into its part of the
+    /// frame; `view_mat` stands in for `camera`'s view.
+    ///
+    /// Returns counts of what was drawn, also kept for [`World::stats`].
+    pub fn draw(
+        &mut self,
+        view_mat: Matrix4<f32>,
+        frame: &mut [u8],
+        width: u32,
+        height: u32,
+    ) -> RenderStats {
+        let start = Instant::now();
+        if self.auto_clip_planes {
+            self.fit_clip_planes(&view_mat);
+        }
+        if self.splitting_sort {
+            let matrices = self.world_matrices();
+            self.splits
+                .update(&self.models, &matrices, self.visible_layers);
+        }
+        self.draw_render_targets();
+        let rects: Vec<_> = self
+            .viewports
+            .iter()
+            .map(|viewport| viewport.pixel_rect(width, height))
+            .collect();
+        // Whatever no viewport covers shows the background.
+        if !rects.contains(&(0, 0, width, height)) {
+            self.clear(frame, width, height, &view_mat, &self.proj_mat);
+        }
+        let mut stats = RenderStats::default();
+        for (i, (x, y, w, h)) in rects.into_iter().enumerate() {
+            let viewport = self.viewports[i];
+            let view = match viewport.camera_index {
+                0 => view_mat,
+                n => match self.cameras.get(n - 1) {
+                    Some(camera) => camera.generate_view_mat(),
+                    None => continue,
+                },
+            };
+            if w == 0 || h == 0 {
+                continue;
+            }
+            let (jx, jy) = self.projection_jitter;
+            let jitter = Matrix4::new_translation(&Vector3::new(
+                2.0 * jx / w as f32,
+                -2.0 * jy / h as f32,
+                0.0,
+            ));
+            let projection = viewport.projection.matrix(
+                &self.proj_mat,
+                w as f32 / h as f32,
+                self.depth_convention,
+            );
+            let proj_mat = jitter * projection;
+            self.select_lods(&view);
+            if (x, y, w, h) == (0, 0, width, height) {
+                stats += self.render_eyes(view, proj_mat, frame, width, height);
+                continue;
+            }
+            let mut buffer = std::mem::take(&mut self.viewport_buffer);
+            buffer.resize((w * h * 4) as usize, 0);
+            stats += self.render_eyes(view, proj_mat, &mut buffer, w, h);
+            let row = w as usize * 4;
+            for (line, pixels) in buffer.chunks_exact(row).enumerate() {
+                let start = ((y as usize + line) * width as usize + x as usize) * 4;
+                frame[start..start + row].copy_from_slice(pixels);
+            }
+            self.viewport_buffer = buffer;
+        }
+        self.stats = stats;
+        self.stats.draw_time = start.elapsed();
+        if let Some(adaptive) = &mut self.adaptive_scale {
+            adaptive.record(self.stats.draw_time);
+        }
+        self.stats
+    }
+
+    /// One frame seen through `view_mat`, or a stereo pair either side of
+    /// it merged into one.
+    fn render_eyes(
+        &mut self,
+        view_mat: Matrix4<f32>,
+        proj_mat: Matrix4<f32>,
+        frame: &mut [u8],
+        width: u32,
+        height: u32,
+    ) -> RenderStats {
+        let Some(stereo) = self.stereo else {
+            return self.render_view(view_mat, proj_mat, frame, width, height);
+        };
+        let [left, right] = stereo.eye_views(&view_mat);
+        let mut buffer = std::mem::take(&mut self.stereo_buffer);
+        buffer.resize(frame.len(), 0);
+        let mut stats = self.render_view(left, proj_mat, &mut buffer, width, height);
+        stats += self.render_view(right, proj_mat, frame, width, height);
+        merge_anaglyph(&buffer, frame);
+        self.stereo_buffer = buffer;
+        stats
+    }
+
+    /// One frame seen through `view_mat`, supersampled when asked to.
+    fn render_view(
+        &mut self,
+        view_mat: Matrix4<f32>,
+        proj_mat: Matrix4<f32>,
+        frame: &mut [u8],
+        width: u32,
+        height: u32,
+    ) -> RenderStats {
+        let mut depth = std::mem::take(&mut self.depth_values);
+        // A frame too big to supersample in memory is drawn without it.
+        let supersampled = (self.ssaa != 1)
+            .then(|| supersampled_size(width, height, self.ssaa))
+            .flatten();
+        let factor = if supersampled.is_some() { self.ssaa } else { 1 };
+        let mut stats = if let Some((big_width, big_height, len)) = supersampled {
+            let mut buffer = std::mem::take(&mut self.ssaa_buffer);
+            buffer.resize(len, 0);
+            let stats = self.render_scene(
+                view_mat,
+                proj_mat,
+                &mut buffer,
+                &mut depth,
+                big_width,
+                big_height,
+            );
+            downsample(&buffer, frame, width, height, factor);
+            self.ssaa_buffer = buffer;
+            stats
+        } else {
+            self.render_scene(view_mat, proj_mat, frame, &mut depth, width, height)
+        };
+        let samples = self.depth_buffer.then(|| DepthSamples {
+            values: &depth,
+            factor,
+            convention: self.depth_convention,
+        });
+        stats.labels_drawn = draw_labels(
+            &self.placed_labels(),
+            &view_mat,
+            &proj_mat,
+            samples,
+            frame,
+            width,
+            height,
+        );
+        self.depth_values = depth;
+        stats
+    }
+
+    /// Like [`World::draw`], but into any [`RenderTarget`], such as a
+    /// [`BufferTarget`](crate::target::BufferTarget) with padded rows.
+    /// Packed targets are drawn into directly; others get the finished
+    /// frame copied over row by row.
+    pub fn draw_to(
+        &mut self,
+        view_mat: Matrix4<f32>,
+        target: &mut impl RenderTarget,
+    ) -> RenderStats {
+        let (width, height) = (target.width(), target.height());
+        if let Some(frame) = target.packed() {
+            return self.draw(view_mat, frame, width, height);
+        }
+        let mut frame = std::mem::take(&mut self.target_buffer);
+        frame.resize(width as usize * height as usize * 4, 0);
+        let stats = self.draw(view_mat, &mut frame, width, height);
+        for (y, row) in frame.chunks_exact((width as usize * 4).max(1)).enumerate() {
+            target.put_row(y as u32, row);
+        }
+        self.target_buffer = frame;
+        stats
+    }
+
+    /// Pick the level of detail of every object with `lods` for a frame
+    /// seen through `view_mat`.
+    pub(crate) fn select_lods(&mut self, view_mat: &Matrix4<f32>) {
+        self.lod_levels.resize(self.models.len(), 0);
+        if self.models.iter().all(|model| model.lods.is_empty()) {
+            return;
+        }
+        let matrices = self.world_matrices();
+        for ((model, model_mat), level) in
+            self.models.iter().zip(&matrices).zip(&mut self.lod_levels)
+        {
+            if !model.lods.is_empty() {
+                let distance = -object_depth(view_mat, model_mat).0;
+                *level = select_lod(&model.lods, distance, *level);
+            }
+        }
+    }
+
+    /// Index in its `lods` of the mesh the object at `index` was last drawn
+    /// with, or `None` for objects always drawn with their `mesh`.
+    pub fn lod_level(&self, index: usize) -> Option<usize> {
+        let model = self.models.get(index)?;
+        if model.lods.is_empty() {
+            return None;
+        }
+        Some(
+            self.lod_levels
+                .get(index)
+                .copied()
+                .unwrap_or(0)
+                .min(model.lods.len() - 1),
+        )
+    }
+
+    /// Aim for frames that take `target` to draw, by way of
+    /// [`World::adapt_render_scale`]. Only the time spent in
+    /// [`World::draw`] counts, so waiting on the display doesn't.
+    pub fn set_target_frame_time(&mut self, target: Duration) {
+        self.adaptive_scale = Some(AdaptiveScale::new(target));
+    }
+
+    /// Stop adapting the render scale.
+    pub fn clear_target_frame_time(&mut self) {
+        self.adaptive_scale = None;
+    }
+
+    /// The draw time being aimed for, if any.
+    pub fn target_frame_time(&self) -> Option<Duration> {
+        self.adaptive_scale.as_ref().map(AdaptiveScale::target)
+    }
+
+    /// The render scale the next frame should use, moved from `scale`
+    /// towards the target frame time if one is set and recent frames missed
+    /// it. See [`AdaptiveScale`].
+    pub fn adapt_render_scale(&mut self, scale: f32) -> f32 {
+        match &mut self.adaptive_scale {
+            Some(adaptive) => adaptive.adjust(scale, Instant::now()),
+            None => scale,
+        }
+    }
+
+    /// Add `object` to the scene, returning its index in `models`. Meshes
+    /// with out of range vertex indices are refused, as are names another
+    /// object already has, and degenerate triangles logged.
+    pub fn add_object(&mut self, object: Object) -> Result<usize, AddObjectError> {
+        object.mesh.validate()?;
+        for (_, lod) in &object.lods {
+            lod.validate()?;
+        }
+        if let Some(name) = &object.name
+            && self.index_of(name).is_some()
+        {
+            return Err(AddObjectError::DuplicateName(name.clone()));
+        }
+        let degenerate = object.mesh.degenerate_triangles();
+        if degenerate > 0 {
+            warn!("mesh has {degenerate} degenerate triangles");
+        }
+        if let Some(name) = &object.name {
+            self.names.insert(name.clone(), self.models.len());
+        }
+        self.models.push(object);
+        Ok(self.models.len() - 1)
+    }
+
+    /// Index the names of `models` again after editing it directly, such
+    /// as when a scene has been put together from a file, failing with
+    /// every name given to more than one object. Each of those finds the
+    /// first object with it.
+    pub fn index_names(&mut self) -> Result<(), DuplicateNames> {
+        self.names.clear();
+        let mut duplicates = Vec::new();
+        for (index, model) in self.models.iter().enumerate() {
+            let Some(name) = &model.name else {
+                continue;
+            };
+            if self.names.contains_key(name) {
+                if !duplicates.contains(name) {
+                    duplicates.push(name.clone());
+                }
+            } else {
+                self.names.insert(name.clone(), index);
+            }
+        }
+        if duplicates.is_empty() {
+            Ok(())
+        } else {
+            Err(DuplicateNames(duplicates))
+        }
+    }
+
+    /// The object named `name`.
+    pub fn object(&self, name: &str) -> Option<&Object> {
+        self.index_of(name).map(|index| &self.models[index])
+    }
+
+    pub fn object_mut(&mut self, name: &str) -> Option<&mut Object> {
+        self.index_of(name).map(|index| &mut self.models[index])
+    }
+
+    /// Index in `models` of the object `object` refers to, if there is
+    /// one.
+    pub fn resolve(&self, object: &ObjectRef) -> Option<usize> {
+        match object {
+            ObjectRef::Index(index) => (*index < self.models.len()).then_some(*index),
+            ObjectRef::Name(name) => self.index_of(name),
+        }
+    }
+
+    /// Index in `models` of the object named `name`.
+    fn index_of(&self, name: &str) -> Option<usize> {
+        let named = |&index: &usize| {
+            self.models
+                .get(index)
+                .is_some_and(|model| model.name.as_deref() == Some(name))
+        };
+        // Objects pushed or moved in `models` directly aren't indexed.
+        self.names
+            .get(name)
+            .copied()
+            .filter(named)
+            .or_else(|| (0..self.models.len()).find(named))
+    }
+
+    /// Select `object`, returning its index, or leave the selection as it
+    /// is when there's no such object.
+    pub fn select(&mut self, object: impl Into<ObjectRef>) -> Option<usize> {
+        let index = self.resolve(&object.into())?;
+        self.selected = Some(index);
+        Some(index)
+    }
+
+    /// Take the object at `index` out of the scene. Later objects shift down
+    /// by one and the selection follows the object it pointed at, or is
+    /// cleared if that object was removed. Children of the removed object
+    /// are detached to the root, keeping their place in the world.
+    pub fn remove_object(&mut self, index: usize) -> Option<Object> {
+        if index >= self.models.len() {
+            return None;
+        }
+        let matrices = self.world_matrices();
+        for (child, model) in self.models.iter_mut().enumerate() {
+            if model.parent == Some(index) {
+                model.parent = None;
+                set_transform(model, &matrices[child]);
+            }
+        }
+        for model in &mut self.models {
+            if let Some(parent) = model.parent.as_mut()
+                && *parent > index
+            {
+                *parent -= 1;
+            }
+        }
+        self.selected = match self.selected {
+            Some(selected) if selected == index => None,
+            Some(selected) if selected > index => Some(selected - 1),
+            selected => selected,
+        };
+        // Labels pinned to the object by index go with it; those pinned
+        // by name wait for another object of that name.
+        self.labels
+            .retain(|label| label.object != Some(ObjectRef::Index(index)));
+        for label in &mut self.labels {
+            if let Some(ObjectRef::Index(pinned)) = label.object.as_mut()
+                && *pinned > index
+            {
+                *pinned -= 1;
+            }
+        }
+        self.names.retain(|_, named| *named != index);
+        for named in self.names.values_mut() {
+            if *named > index {
+                *named -= 1;
+            }
+        }
+        if index < self.lod_levels.len() {
+            self.lod_levels.remove(index);
+        }
+        // A load for the removed object finishes unnoticed.
+        self.pending_loads.retain(|load| load.object != index);
+        for load in &mut self.pending_loads {
+            if load.object > index {
+                load.object -= 1;
+            }
+        }
+        for stream in &mut self.streams {
+            stream.object_removed(index);
+        }
+        Some(self.models.remove(index))
+    }
+
+    /// Add an object for the OBJ file at `path` without waiting for it to
+    /// load. Until [`World::poll_loads`] finds the mesh read, the object is
+    /// drawn as a see-through cube in [`PLACEHOLDER_COLOR`]. If the file
+    /// can't be loaded the error is logged and the cube stays.
+    ///
+    /// `transform` places the object, built from a translation, rotation
+    /// and uniform scale. Returns the object's index.
+    pub fn load_object_async(&mut self, path: impl AsRef<Path>, transform: Matrix4<f32>) -> usize {
+        let mut placeholder = Object::new(CubeMesh::new(PLACEHOLDER_COLOR), 0.0, 0.0, 0.0);
+        set_transform(&mut placeholder, &transform);
+        placeholder.source = Some(path.as_ref().to_path_buf());
+        self.models.push(placeholder);
+        let index = self.models.len() - 1;
+        self.pending_loads.push(PendingLoad::spawn(
+            path.as_ref(),
+            index,
+            self.import_options,
+            self.lod_distances.len().saturating_sub(1),
+        ));
+        index
+    }
+
+    /// Swap the meshes of every finished [`World::load_object_async`] into
+    /// their objects. Call it once a frame before drawing. Returns how many
+    /// meshes arrived.
+    pub fn poll_loads(&mut self) -> usize {
+        let mut arrived = 0;
+        let mut still_loading = Vec::with_capacity(self.pending_loads.len());
+        for load in std::mem::take(&mut self.pending_loads) {
+            match load.poll() {
+                None => still_loading.push(load),
+                Some(Ok(loaded)) => match loaded.mesh.validate() {
+                    Ok(()) => {
+                        // `models` is public; its object may be gone.
+                        let Some(model) = self.models.get_mut(load.object) else {
+                            continue;
+                        };
+                        model.mesh = Rc::new(loaded.mesh);
+                        if let Some((&first, rest)) = self.lod_distances.split_first() {
+                            model.lods = vec![(first, Rc::clone(&model.mesh))];
+                            model.lods.extend(rest.iter().zip(loaded.coarser).map(
+                                |(&distance, level)| (distance, Rc::new(level) as Rc<dyn Mesh>),
+                            ));
+                        }
+                        arrived += 1;
+                    }
+                    Err(err) => error!("{}: {err}", load.path.display()),
+                },
+                Some(Err(err)) => error!("{}: {err}", load.path.display()),
+            }
+        }
+        self.pending_loads = still_loading;
+        arrived
+    }
+
+    /// Load the mesh of every object whose [`Object::source`] is among
+    /// `changed` again, in the background like
+    /// [`World::load_object_async`]. Objects keep drawing their current
+    /// mesh until [`World::poll_loads`] swaps the new one in, and keep it
+    /// if the file no longer loads. Returns how many loads were started.
+    pub fn reload_sources(&mut self, changed: &[PathBuf]) -> usize {
+        let mut started = 0;
+        for (index, model) in self.models.iter().enumerate() {
+            let Some(source) = model
+                .source
+                .as_ref()
+                .filter(|source| changed.contains(source))
+            else {
+                continue;
+            };
+            // The file has moved on since any load already under way.
+            self.pending_loads.retain(|load| load.object != index);
+            self.pending_loads.push(PendingLoad::spawn(
+                source,
+                index,
+                self.import_options,
+                self.lod_distances.len().saturating_sub(1),
+            ));
+            started += 1;
+        }
+        started
+    }
+
+    /// How many [`World::load_object_async`] meshes have not arrived yet.
+    pub fn loads_pending(&self) -> usize {
+        self.pending_loads.len()
+    }
+
+    /// Index of the next shown object after `after`, wrapping around, or
+    /// the first shown one with `None`. For stepping a selection through
+    /// what is on screen.
+    pub fn next_shown(&self, after: Option<usize>) -> Option<usize> {
+        let len = self.models.len();
+        let start = after.map_or(0, |i| i + 1);
+        (0..len)
+            .map(|i| (start + i) % len)
+            .find(|&i| self.models[i].shown(self.visible_layers))
+    }
+
+    /// Attach `child` to `parent`, or detach it with `None`. Its offset,
+    /// rotation and scale are kept and from then on read relative to the
+    /// parent.
+    pub fn set_parent(
+        &mut self,
+        child: usize,
+        parent: Option<usize>,
+    ) -> Result<(), HierarchyError> {
+        let len = self.models.len();
+        if child >= len {
+            return Err(HierarchyError::NoSuchObject(child));
+        }
+        if let Some(parent) = parent {
+            if parent >= len {
+                return Err(HierarchyError::NoSuchObject(parent));
+            }
+            // Walk up from the new parent; meeting the child means a loop.
+            let mut node = Some(parent);
+            for _ in 0..=len {
+                match node {
+                    Some(n) if n == child => {
+                        return Err(HierarchyError::Cycle { child, parent });
+                    }
+                    Some(n) => node = self.models[n].parent,
+                    None => break,
+                }
+            }
+        }
+        self.models[child].parent = parent;
+        Ok(())
+    }
+
+    /// `labels` with those pinned to an object moved to where it is, and
+    /// those pinned to a missing one left out.
+    fn placed_labels(&self) -> Cow<'_, [Label]> {
+        if self.labels.iter().all(|label| label.object.is_none()) {
+            return Cow::Borrowed(&self.labels);
+        }
+        let matrices = self.world_matrices();
+        let placed = self.labels.iter().filter_map(|label| match &label.object {
+            None => Some(label.clone()),
+            Some(object) => self.resolve(object).map(|index| Label {
+                position: matrices[index].transform_point(&label.position),
+                ..label.clone()
+            }),
+        });
+        Cow::Owned(placed.collect())
+    }
+
+    /// Attach `child` to `parent`, or detach it with `None`, like
+    /// [`World::set_parent`] but with the objects by index or by name.
+    pub fn attach(
+        &mut self,
+        child: impl Into<ObjectRef>,
+        parent: Option<ObjectRef>,
+    ) -> Result<(), HierarchyError> {
+        let resolve = |object: ObjectRef| {
+            self.resolve(&object).ok_or(match object {
+                ObjectRef::Index(index) => HierarchyError::NoSuchObject(index),
+                ObjectRef::Name(name) => HierarchyError::NoSuchName(name),
+            })
+        };
+        let child = resolve(child.into())?;
+        let parent = parent.map(resolve).transpose()?;
+        self.set_parent(child, parent)
+    }
+
+    /// Object to world transform of every object, in `models` order, each
+    /// parent's resolved once. A parent index that is out of range or part of
+    /// a loop, possible only by editing [`Object::parent`] directly, is
+    /// treated as no parent. Objects are placed as they are drawn, part way
+    /// from their previous placement by [`World::interpolation`].
+    pub fn world_matrices(&self) -> Vec<Matrix4<f32>> {
+        let len = self.models.len();
+        let mut resolved: Vec<Option<Matrix4<f32>>> = vec![None; len];
+        let mut chain = Vec::new();
+        for start in 0..len {
+            // Climb to the first resolved ancestor or the root...
+            chain.clear();
+            let mut node = Some(start);
+            let mut base = Matrix4::identity();
+            while let Some(n) = node {
+                if let Some(matrix) = resolved[n] {
+                    base = matrix;
+                    break;
+                }
+                if chain.contains(&n) {
+                    warn!("object {n} is its own ancestor, drawing it unparented");
+                    break;
+                }
+                chain.push(n);
+                node = self.models[n].parent.filter(|&p| p < len);
+            }
+            // ...then resolve back down.
+            for &n in chain.iter().rev() {
+                if resolved[n].is_none() {
+                    base *= self.models[n].interpolated_mat(self.interpolation);
+                    resolved[n] = Some(base);
+                }
+            }
+        }
+        resolved
+            .into_iter()
+            .map(Option::unwrap_or_default)
+            .collect()
+    }
+
+    /// Whether any object has an animation, there are particle emitters
+    /// or the camera is flying to a bookmark, so [`World::update`] changes
+    /// the scene every frame.
+    pub fn is_animated(&self) -> bool {
+        self.models.iter().any(|model| model.animation.is_some())
+            || !self.emitters.is_empty()
+            || self.camera_transition.is_some()
+    }
+
+    /// Advance every animated object and particle emitter, and the camera
+    /// flying to a bookmark, by `dt` seconds, keeping where every object
+    /// was in [`Object::previous`].
+    pub fn update(&mut self, dt: f32) {
+        if let Some(transition) = &mut self.camera_transition {
+            let pose = transition.advance(dt);
+            if transition.is_finished() {
+                self.camera_transition = None;
+            }
+            self.set_camera_pose(&pose);
+        }
+        for model in &mut self.models {
+            model.previous = Some(model.placement());
+            if let Some(animation) = model.animation {
+                animation.step(model, self.time, dt);
+            }
+        }
+        for emitter in &mut self.emitters {
+            emitter.step(dt);
+        }
+        self.time += dt;
+    }
+
+    /// Use a solid background color.
+    pub fn with_clear_color(mut self, color: Color) -> Self {
+        self.clear_color = color;
+        self.clear_mode = ClearMode::Solid;
+        self
+    }
+
+    /// Use a vertical gradient background.
+    pub fn with_gradient(mut self, top: Color, bottom: Color) -> Self {
+        self.clear_mode = ClearMode::Gradient { top, bottom };
+        self
+    }
+
+    /// Use a sky that follows the camera as the background.
+    pub fn with_sky(mut self, sky: Sky) -> Self {
+        self.clear_mode = ClearMode::Sky(sky);
+        self
+    }
+
+    /// Near and far clip plane distances of `proj_mat`, assuming an OpenGL
+    /// style perspective projection like [`nalgebra::Perspective3`].
+    pub fn clip_planes(&self) -> (f32, f32) {
+        self.depth_convention.clip_planes(&self.proj_mat)
+    }
+
+    /// How `proj_mat` maps view distance to depth. Standard by default.
+    pub fn depth_convention(&self) -> DepthConvention {
+        self.depth_convention
+    }
+
+    /// Rebuild `proj_mat` for depth running the `convention` way, keeping
+    /// its field of view, aspect ratio and clip planes. The depth buffer
+    /// tests the other way round to match.
+    pub fn set_depth_convention(&mut self, convention: DepthConvention) {
+        let (near, far) = self.clip_planes();
+        self.depth_convention = convention;
+        self.rebuild_projection(None, near, far);
+    }
+
+    /// Rebuild `proj_mat` for a frame `aspect` times as wide as it is tall,
+    /// keeping everything else.
+    pub fn set_aspect_ratio(&mut self, aspect: f32) {
+        let (near, far) = self.clip_planes();
+        self.rebuild_projection(Some(aspect), near, far);
+    }
+
+    /// Vertical field of view of `proj_mat` in radians.
+    pub fn field_of_view(&self) -> f32 {
+        Perspective3::from_matrix_unchecked(self.proj_mat).fovy()
+    }
+
+    /// Rebuild `proj_mat` with a vertical field of view of `fovy` radians,
+    /// keeping everything else.
+    pub fn set_field_of_view(&mut self, fovy: f32) {
+        let (near, far) = self.clip_planes();
+        let aspect = Perspective3::from_matrix_unchecked(self.proj_mat).aspect();
+        self.proj_mat = self.depth_convention.perspective(aspect, fovy, near, far);
+    }
+
+    /// Replace `proj_mat` with one of the current convention, keeping the
+    /// field of view and, unless given, the aspect ratio.
+    fn rebuild_projection(&mut self, aspect: Option<f32>, near: f32, far: f32) {
+        let current = Perspective3::from_matrix_unchecked(self.proj_mat);
+        let aspect = aspect.unwrap_or_else(|| current.aspect());
+        self.proj_mat = self
+            .depth_convention
+            .perspective(aspect, current.fovy(), near, far);
+    }
+
+    /// Where `camera` is, which way it faces and how wide it sees.
+    pub fn camera_pose(&self) -> CameraPose {
+        CameraPose::of(&self.camera, self.field_of_view())
+    }
+
+    /// Put `camera` at `pose`, flying, and take on its field of view if it
+    /// differs.
+    pub fn set_camera_pose(&mut self, pose: &CameraPose) {
+        self.camera.position = pose.position;
+        self.camera.orientation = pose.orientation;
+        self.camera.mode = CameraMode::Fly;
+        if (pose.fovy - self.field_of_view()).abs() > f32::EPSILON {
+            self.set_field_of_view(pose.fovy);
+        }
+    }
+
+    /// Save the current camera pose in bookmark `slot`, counting from 0.
+    /// Slots past [`World::bookmarks`] are added.
+    pub fn save_bookmark(&mut self, slot: usize) {
+        if self.bookmarks.len() <= slot {
+            self.bookmarks.resize(slot + 1, None);
+        }
+        self.bookmarks[slot] = Some(self.camera_pose());
+    }
+
+    /// Start flying the camera to bookmark `slot` over `duration` seconds,
+    /// eased at both ends, or jump there when `duration` isn't above zero.
+    /// Returns false, leaving the camera be, when the slot is empty.
+    pub fn go_to_bookmark(&mut self, slot: usize, duration: f32) -> bool {
+        let Some(Some(target)) = self.bookmarks.get(slot).copied() else {
+            return false;
+        };
+        if duration > 0.0 {
+            let from = self.camera_pose();
+            self.camera_transition = Some(CameraTransition::new(from, target, duration));
+        } else {
+            self.camera_transition = None;
+            self.set_camera_pose(&target);
+        }
+        true
+    }
+
+    /// Whether the camera is flying to a bookmark.
+    pub fn is_camera_in_transition(&self) -> bool {
+        self.camera_transition.is_some()
+    }
+
+    /// Stop flying to a bookmark, leaving the camera where it got to.
+    /// Returns whether it was flying.
+    pub fn cancel_camera_transition(&mut self) -> bool {
+        self.camera_transition.take().is_some()
+    }
+
+    /// Rebuild `proj_mat` with new near and far clip plane distances,
+    /// keeping its field of view and aspect ratio. Only what lies between
+    /// the planes is drawn, less a sliver in front for standard depth, see
+    /// [`DepthConvention::drawn_from`].
+    pub fn set_clip_planes(&mut self, near: f32, far: f32) -> Result<(), ClipPlaneError> {
+        if near.is_nan() || near <= 0.0 {
+            return Err(ClipPlaneError::NearNotPositive(near));
+        }
+        if !far.is_finite() || far <= near {
+            return Err(ClipPlaneError::FarNotBeyondNear { near, far });
+        }
+        self.rebuild_projection(None, near, far);
+        Ok(())
+    }
+
+    /// Fit the clip planes around the corners of every object's bounding
+    /// box seen through `view_mat`, unless neither they nor the camera
+    /// have moved since the last fit.
+    fn fit_clip_planes(&mut self, view_mat: &Matrix4<f32>) {
+        let matrices = self.world_matrices();
+        let mut h = DefaultHasher::new();
+        hash_floats(&mut h, view_mat.as_slice());
+        for (model, matrix) in self.models.iter().zip(&matrices) {
+            (Rc::as_ptr(&model.mesh) as *const () as usize).hash(&mut h);
+            hash_floats(&mut h, matrix.as_slice());
+            model.shown(self.visible_layers).hash(&mut h);
+        }
+        let key = h.finish();
+        if self.fitted_clip_planes == Some(key) {
+            return;
+        }
+        self.fitted_clip_planes = Some(key);
+
+        let (nearest, farthest) = self
+            .bounding_corners(&matrices)
+            .map(|corner| -view_mat.transform_point(&corner).z)
+            .fold((f32::INFINITY, f32::NEG_INFINITY), |(near, far), depth| {
+                (near.min(depth), far.max(depth))
+            });
+        // Nothing to fit, or all of it behind the camera.
+        if farthest <= 0.0 {
+            return;
+        }
+        let far = farthest * 1.01;
+        // With the eye inside the box, stop at a ratio that keeps some
+        // depth precision.
+        let nearest = nearest.max(far * 1e-4);
+        // Start the drawn range a little in front of the nearest corner.
+        let near = match self.depth_convention {
+            // Solving 2nf / (n + f) for n.
+            DepthConvention::Standard => 0.9 * nearest * far / (2.0 * far - nearest),
+            DepthConvention::Reversed => 0.9 * nearest,
+        };
+        if let Err(err) = self.set_clip_planes(near, far) {
+            warn!("can't fit the clip planes: {err}");
+        }
+    }
+
+    /// The corners of every non-empty object's bounding box, in world
+    /// space.
+    fn bounding_corners<'a>(
+        &'a self,
+        matrices: &'a [Matrix4<f32>],
+    ) -> impl Iterator<Item = Point3<f32>> + 'a {
+        self.models
+            .iter()
+            .zip(matrices)
+            .filter(|(model, _)| model.shown(self.visible_layers) && !model.mesh.verts().is_empty())
+            .flat_map(|(model, matrix)| {
+                let (min, max) = model.mesh.aabb();
+                (0..8).map(move |i| {
+                    let corner = Point3::new(
+                        if i & 1 == 0 { min.x } else { max.x },
+                        if i & 2 == 0 { min.y } else { max.y },
+                        if i & 4 == 0 { min.z } else { max.z },
+                    );
+                    matrix.transform_point(&corner)
+                })
+            })
+    }
+
+    /// Move the camera back along its view direction until every object's
+    /// bounding box fits in view, looking at the middle of them all. An
+    /// orbiting camera keeps orbiting, around that middle. Does nothing but
+    /// warn when there is nothing to frame.
+    pub fn frame_all(&mut self) {
+        let matrices = self.world_matrices();
+        let mut corners = self.bounding_corners(&matrices).peekable();
+        let Some(&first) = corners.peek() else {
+            warn!("nothing to frame");
+            return;
+        };
+        let (min, max) = corners.fold((first, first), |(min, max), p| (min.inf(&p), max.sup(&p)));
+
+        // Fit the sphere around the box into the narrower field of view.
+        let center = nalgebra::center(&min, &max);
+        let radius = ((max - min).norm() / 2.0).max(f32::EPSILON);
+        let tan_half = (1.0 / self.proj_mat[(0, 0)]).min(1.0 / self.proj_mat[(1, 1)]);
+        let distance = radius * (1.0 + tan_half * tan_half).sqrt() / tan_half;
+        let forward = self.camera.forward();
+        self.camera.position = center - forward * distance;
+        if let CameraMode::Orbit { .. } = self.camera.mode {
+            self.camera.mode = CameraMode::Orbit {
+                focus: center,
+                distance,
+            };
+        }
+    }
+
+    /// What the last call to [`World::draw`] rendered.
+    pub fn stats(&self) -> RenderStats {
+        self.stats
+    }
+
+    /// Supersampling factor per axis; 1 means off.
+    pub fn ssaa(&self) -> u32 {
+        self.ssaa
+    }
+
+    /// Render at `factor` times the resolution on each axis, 1, 2 or 4,
+    /// and box filter down to the output. The internal buffer is kept
+    /// between frames and released when supersampling is turned off.
+    pub fn set_ssaa(&mut self, factor: u32) -> Result<(), UnsupportedSsaa> {
+        if !matches!(factor, 1 | 2 | 4) {
+            return Err(UnsupportedSsaa(factor));
+        }
+        self.ssaa = factor;
+        if self.ssaa == 1 {
+            self.ssaa_buffer = Vec::new();
+        }
+        Ok(())
+    }
+
+    pub(crate) fn render_scene(
+        &self,
+        view_mat: Matrix4<f32>,
+        proj_mat: Matrix4<f32>,
+        frame: &mut [u8],
+        depth: &mut Vec<f32>,
+        width: u32,
+        height: u32,
+    ) -> RenderStats {
+        let profiler = Profiler::new(self.profiling_enabled);
+        let lighting = self.scene_lighting();
+        {
+            let _clear = profiler.scope(Stage::Clear);
+            self.clear(frame, width, height, &view_mat, &proj_mat);
+            depth.clear();
+            if self.depth_buffer {
+                let clear = self.depth_convention.clear_value();
+                depth.resize(width as usize * height as usize, clear);
+            } else {
+                depth.shrink_to_fit();
+            }
+        }
+        let mut stats = RenderStats::default();
+        let matrices = self.world_matrices();
+        let mut sorted_models: Vec<(usize, &Object, Matrix4<f32>)> = self
+            .models
+            .iter()
+            .zip(&matrices)
+            .enumerate()
+            .filter(|(_, (model, _))| model.shown(self.visible_layers))
+            .map(|(handle, (model, model_mat))| (handle, model, *model_mat))
+            .collect();
+        stats.objects_hidden = self.models.len() - sorted_models.len();
+
+        {
+            let _sort = profiler.scope(Stage::Sort);
+            sorted_models.sort_by_key(|(_, model, model_mat)| {
+                (
+                    object_depth(&view_mat, model_mat),
+                    OrderedFloat(-model.depth_bias),
+                )
+            });
+        }
+        // Planar shadows lie on the ground, so it is drawn first, then the
+        // shadows, then everything standing on it.
+        let grounded: Vec<bool> = match &self.planar_shadows {
+            Some(config) => self
+                .models
+                .iter()
+                .zip(&matrices)
+                .map(|(model, matrix)| on_ground(model, matrix, config.plane_y))
+                .collect(),
+            None => Vec::new(),
+        };
+        if !grounded.is_empty() {
+            sorted_models.sort_by_key(|(handle, _, _)| !grounded[*handle]);
+        }
+        // Objects cut up against each other are drawn together, where the
+        // farthest of them comes, in the order of their group.
+        let splits = if self.splitting_sort {
+            self.splits.draws(&view_mat, &proj_mat, &matrices)
+        } else {
+            Vec::new()
+        };
+        let group_of = |handle: usize| {
+            splits
+                .get(handle)
+                .and_then(Option::as_ref)
+                .map(|split| split.group)
+        };
+        if splits.iter().any(Option::is_some) {
+            let mut gathered = Vec::with_capacity(sorted_models.len());
+            let mut taken = vec![false; sorted_models.len()];
+            for i in 0..sorted_models.len() {
+                if taken[i] {
+                    continue;
+                }
+                let group = group_of(sorted_models[i].0);
+                for j in i..sorted_models.len() {
+                    if j == i
+                        || !taken[j] && group.is_some() && group_of(sorted_models[j].0) == group
+                    {
+                        taken[j] = true;
+                        gathered.push(sorted_models[j]);
+                    }
+                }
+            }
+            sorted_models = gathered;
+        }
+
+        let depth_range = (self.render_mode == RenderMode::Depth).then(|| self.clip_planes());
+        let inv_proj = proj_mat.try_inverse().unwrap_or_else(Matrix4::identity);
+        let shading = SphereShading {
+            ray_origin: inv_proj * Vector4::new(-1.0, 1.0, 0.0, 1.0),
+            ray_dx: inv_proj.column(0) * (2.0 / width as f32),
+            ray_dy: inv_proj.column(1) * (-2.0 / height as f32),
+            pixel_size: 2.0 * inv_proj[(1, 1)] / height as f32,
+            light_dir: view_mat
+                .transform_vector(&(self.light.target - self.light.position))
+                .normalize(),
+            ambient: self.ambient,
+            up: view_mat.transform_vector(&Vector3::y()),
+            intensity: self.light.intensity,
+            model: self.shading_model,
+            fog: self.fog,
+            gamma: self.gamma_correct,
+            depth_range,
+            depth_projection: self
+                .depth_buffer
+                .then(|| (proj_mat[(2, 2)], proj_mat[(2, 3)])),
+        };
+        // Spheres are traced along rays from the eye, which a parallel
+        // projection, with nothing in its w row, doesn't have.
+        let spheres = if proj_mat[(3, 2)] == 0.0 {
+            Vec::new()
+        } else {
+            self.project_spheres(&view_mat, &proj_mat, width, height)
+        };
+        stats.spheres_rasterized = spheres.len();
+        let billboards = self.project_billboards(&view_mat, &proj_mat, width, height);
+        stats.billboards_rasterized = billboards.len();
+        // Spheres and billboards are slotted in between the objects by
+        // depth, each billboard's two triangles staying together.
+        let mut sprites: Vec<(f32, Primitive)> = spheres
+            .into_iter()
+            .map(|(z, sphere)| (z, Primitive::Sphere(sphere, &shading)))
+            .chain(
+                billboards
+                    .into_iter()
+                    .flat_map(|(z, tris)| tris.map(|tri| (z, Primitive::Triangle(tri)))),
+            )
+            .collect();
+        sprites.sort_by_key(|(z, _)| OrderedFloat(*z));
+        let mut sprites = sprites.into_iter().peekable();
+
+        let mut opaque: Vec<Primitive> = Vec::new();
+        if self.show_gizmos {
+            self.draw_debug_gizmos(&view_mat, &proj_mat, width, height, &mut opaque);
+        }
+        // Gizmos lie under everything else, whatever order that is drawn in.
+        let underlay = opaque.len();
+        let mut transparent: Vec<((i64, usize, usize), RasterTriangle)> = Vec::new();
+        let mut outline: Vec<Primitive> = Vec::new();
+        let mut normals: Vec<Primitive> = Vec::new();
+        let clip_mat = proj_mat * view_mat;
+        let overlapping = if self.highlight_overlaps {
+            self.overlapping_objects()
+        } else {
+            Vec::new()
+        };
+        let shadows = ShadowMap::build(&self.light, &self.models, &matrices, self.visible_layers);
+        let mut planar_shadows = self.planar_shadows.map(|config| {
+            self.project_planar_shadows(
+                &config,
+                &sorted_models,
+                &grounded,
+                &clip_mat,
+                width,
+                height,
+            )
+        });
+        stats.planar_shadow_triangles = planar_shadows.as_ref().map_or(0, Vec::len);
+
+        // Per-vertex scratch, reused across objects so instances of a shared
+        // mesh don't allocate per draw.
+        let mut vertices: Vec<TransformedVertex> = Vec::new();
+        let mut z_ordered_tris: Vec<(usize, Triangle, f32)> = Vec::new();
+        let mut small_clusters: HashMap<(i32, i32), SmallCluster> = HashMap::new();
+        // Opaque triangles of the split group being drawn, with their place
+        // in its order.
+        let mut split_queue: Vec<(u32, Primitive)> = Vec::new();
+        let mut queued_group = None;
+
+        // Iterate over meshes in sorted zbuffer order
+        for (handle, mesh, model_mat) in &sorted_models {
+            let split = splits.get(*handle).and_then(Option::as_ref);
+            if split.map(|split| split.group) != queued_group {
+                flush_split_queue(&mut split_queue, &mut opaque);
+                queued_group = split.map(|split| split.group);
+            }
+            if !grounded.get(*handle).copied().unwrap_or(false) {
+                if let Some(shadows) = planar_shadows.take() {
+                    opaque.extend(shadows.into_iter().map(Primitive::Triangle));
+                }
+                // Spheres and billboards farther away than this object go
+                // first.
+                let depth = object_depth(&view_mat, model_mat).0;
+                while let Some((_, sprite)) = sprites.next_if(|(z, _)| *z < depth) {
+                    opaque.push(sprite);
+                }
+            }
+
+            let model = match split {
+                Some(split) => split.mesh,
+                None => self.drawn_mesh(*handle, mesh),
+            };
+            debug_assert!(model.validate().is_ok(), "drawing an invalid mesh");
+            stats.triangles_submitted += model.primitives().triangles().count();
+            let model_view = view_mat * model_mat;
+            let proj = proj_mat * model_view;
+            if outside_view(model, &proj) {
+                stats.objects_culled += 1;
+                continue;
+            }
+            stats.approximate |= model.placeholder();
+            stats.objects_drawn += 1;
+
+            let material = mesh
+                .material
+                .and_then(|handle| {
+                    self.materials
+                        .instantiate_override(handle, &mesh.material_override)
+                })
+                .map(|material| Material {
+                    // A screen shows its target, or what the material
+                    // shows without it while screens show nothing.
+                    texture: material
+                        .render_target
+                        .and_then(|target| self.render_target_texture(target))
+                        .or(material.texture),
+                    ..material
+                });
+            // Two products per vertex: to the screen and into view space
+            // for sorting. World positions wait until a triangle using the
+            // vertex is lit.
+            {
+                let _vertex = profiler.scope(Stage::Vertex);
+                vertices.clear();
+                vertices.extend(model.verts().iter().map(|vertex| {
+                    let projected = project_vertex(*vertex, &proj, width, height);
+                    TransformedVertex {
+                        screen: projected.screen,
+                        ndc_z: projected.ndc_z,
+                        view_z: model_view.transform_point(vertex).z,
+                        world: None,
+                    }
+                }));
+            }
+
+            if let RenderMode::Points { size } = self.render_mode {
+                let color = material.map(|material| material.color);
+                stats.vertices_drawn += queue_points(
+                    model,
+                    &vertices,
+                    color,
+                    size,
+                    self.depth_buffer,
+                    (width, height),
+                    &mut opaque,
+                );
+                continue;
+            }
+
+            //Z order each triangle in each mesh
+            let sort_timer = profiler.scope(Stage::Sort);
+            z_ordered_tris.clear();
+            z_ordered_tris.extend(model.primitives().triangles().enumerate().map(
+                |(index, tri)| -> (usize, Triangle, f32) {
+                    let z = (vertices[tri.v1].view_z
+                        + vertices[tri.v2].view_z
+                        + vertices[tri.v3].view_z)
+                        / 3.0;
+                    (index, tri, z)
+                },
+            ));
+            z_ordered_tris.sort_by_key(|tri| -> OrderedFloat<f32> { OrderedFloat(tri.2) });
+            drop(sort_timer);
+
+            // Queue the triangles
+            let raster_timer = profiler.scope(Stage::Rasterize);
+            let lighting_timer = profiler.scope(Stage::Lighting);
+            for &(index, tri, z) in &z_ordered_tris {
+                let s1 = vertices[tri.v1].screen;
+                let s2 = vertices[tri.v2].screen;
+                let s3 = vertices[tri.v3].screen;
+                if !s1.x.is_finite() || !s2.x.is_finite() || !s3.x.is_finite() {
+                    stats.triangles_clipped += 1;
+                    continue;
+                }
+                if !on_screen(s1, s2, s3, width, height) {
+                    stats.triangles_offscreen += 1;
+                    continue;
+                }
+                // Collinear or coincident corners: nothing to fill, and no
+                // normal to light with.
+                let cross = screen_cross(s1, s2, s3);
+                if cross.abs() < 2.0 * DEGENERATE_AREA
+                    || self.skip_slivers
+                        && sliver_width(s1, s2, s3) < SLIVER_WIDTH * self.ssaa as f32
+                {
+                    stats.triangles_degenerate += 1;
+                    continue;
+                }
+                let small = self.small_triangle_cull.is_some_and(|threshold| {
+                    screen_extent(s1, s2, s3) < threshold * self.ssaa as f32
+                });
+                if small && !self.small_triangle_points {
+                    stats.triangles_small += 1;
+                    continue;
+                }
+
+                let front = cross > 0.0;
+                if self.cull_mode.draws(front) {
+                    let depth_plane = self.depth_buffer.then(|| {
+                        let corner = |s: Point2<f32>, v: usize| (s.x, s.y, vertices[v].ndc_z);
+                        DepthPlane::new([
+                            corner(s1, tri.v1),
+                            corner(s2, tri.v2),
+                            corner(s3, tri.v3),
+                        ])
+                        .biased(mesh.depth_bias, self.depth_convention)
+                    });
+                    // Pieces of a cut triangle are lit like the whole of
+                    // it, so the cuts don't show.
+                    let source = split.and_then(|split| split.mesh.source_corners(index));
+                    let [v1, v2, v3] = match source {
+                        Some(corners) => corners.map(|corner| model_mat.transform_point(&corner)),
+                        None => [tri.v1, tri.v2, tri.v3].map(|i| {
+                            *vertices[i].world.get_or_insert_with(|| {
+                                Point3::from((model_mat * model.verts()[i].to_homogeneous()).xyz())
+                            })
+                        }),
+                    };
+                    // Projection keeps collinear points collinear, but a
+                    // zero length edge would still light the triangle NaN.
+                    let edges = (v2 - v1)
+                        .try_normalize(0.0)
+                        .zip((v3 - v1).try_normalize(0.0));
+                    let Some((edge1, edge2)) = edges else {
+                        stats.triangles_degenerate += 1;
+                        continue;
+                    };
+                    let mut norm = edge1.cross(&edge2);
+
+                    // Back faces are seen from the other side: light that
+                    // side and wind them the way the fill expects.
+                    let (s2, s3) = if front {
+                        (s2, s3)
+                    } else {
+                        norm = -norm;
+                        (s3, s2)
+                    };
+                    let material = material.unwrap_or_else(|| match model.material(index) {
+                        Some(material) => Material {
+                            color: tri.color,
+                            ..*material
+                        },
+                        None => Material::new(tri.color),
+                    });
+                    let id = match self.render_mode {
+                        // Pieces of a cut triangle keep its color.
+                        RenderMode::TriangleId => {
+                            Some(split.map_or(index, |split| split.mesh.source_index(index)))
+                        }
+                        RenderMode::ObjectId => Some(*handle),
+                        _ => None,
+                    };
+                    let material = if let Some(id) = id {
+                        Material {
+                            unlit: true,
+                            ..Material::new(self.id_palette.color(id as u64))
+                        }
+                    } else if overlapping.get(*handle) == Some(&true) {
+                        Material {
+                            // Transparent objects stay as see-through.
+                            color: Color {
+                                a: material.color.a,
+                                ..self.lerp_color(material.color, OVERLAP_TINT, OVERLAP_TINT_AMOUNT)
+                            },
+                            ..material
+                        }
+                    } else {
+                        material
+                    };
+                    let centroid = Point3::from((v1.coords + v2.coords + v3.coords) / 3.0);
+                    let z = match source {
+                        Some(_) => view_mat.transform_point(&centroid).z,
+                        None => z,
+                    };
+                    if self.show_normals {
+                        // `norm` points into the surface; show the outside.
+                        self.draw_normal(&clip_mat, centroid, -norm, width, height, &mut normals);
+                    }
+                    let (color, surface) = match depth_range {
+                        Some((near, far)) => (depth_gray(-z, near, far, material.color.a), None),
+                        None if material.unlit => (material.color, None),
+                        None => {
+                            // One shadow lookup per triangle, at its centroid.
+                            let shadowed = shadows
+                                .as_ref()
+                                .is_some_and(|shadows| shadows.in_shadow(&centroid));
+                            let surface = Surface {
+                                material,
+                                normal: norm,
+                                point: centroid,
+                                distance: -z,
+                                shadowed,
+                            };
+                            (material.color, Some(surface))
+                        }
+                    };
+                    if small {
+                        let color = surface.map_or(color, |surface| lighting.shade(&surface).0);
+                        stats.triangles_small += 1;
+                        let ndc_z = [tri.v1, tri.v2, tri.v3]
+                            .map(|v| vertices[v].ndc_z)
+                            .iter()
+                            .sum::<f32>()
+                            / 3.0;
+                        let center = Point2::from((s1.coords + s2.coords + s3.coords) / 3.0);
+                        // Pixel centres sit on whole coordinates.
+                        let pixel = (center.x.round() as i32, center.y.round() as i32);
+                        small_clusters.entry(pixel).or_default().add(
+                            center,
+                            cross.abs() / 2.0,
+                            color,
+                            ndc_z,
+                        );
+                        continue;
+                    }
+                    // The material's texture, unless the colors are replaced
+                    // by a debug view or the triangle was cut up.
+                    let uvs = model.uvs().filter(|uvs| uvs.len() == model.verts().len());
+                    let uv = material
+                        .texture
+                        .zip(uvs)
+                        .filter(|_| depth_range.is_none() && id.is_none() && split.is_none())
+                        .map(|(texture, uvs)| {
+                            // Corners in the order the fill walks them.
+                            let corners = if front {
+                                [tri.v1, tri.v2, tri.v3]
+                            } else {
+                                [tri.v1, tri.v3, tri.v2]
+                            };
+                            TriangleUv {
+                                texture,
+                                corners: corners.map(|v| (uvs[v], -vertices[v].view_z)),
+                            }
+                        });
+                    let raster_tri = RasterTriangle {
+                        s1,
+                        s2,
+                        s3,
+                        color,
+                        dither: None,
+                        surface,
+                        depth: depth_plane,
+                        uv,
+                    };
+                    stats.triangles_rasterized += 1;
+                    let wire = self.wireframe.into_iter().flat_map(|color| {
+                        let width = WIREFRAME_WIDTH * self.ssaa as f32;
+                        // Pulled towards the eye by the line's width, so it
+                        // wins over its own triangle whichever is drawn
+                        // first but stays hidden behind nearer surfaces.
+                        let depth =
+                            depth_plane.map(|plane| plane.biased(-width, self.depth_convention));
+                        [(s1, s2), (s2, s3), (s3, s1)]
+                            .into_iter()
+                            .flat_map(move |(p, q)| thick_line(p, q, width, color))
+                            .map(move |line| RasterTriangle { depth, ..line })
+                    });
+                    if material.color.a < 255 {
+                        let depth = (z / TRANSPARENT_DEPTH_QUANTUM).round() as i64;
+                        transparent.push(((depth, *handle, index), raster_tri));
+                        transparent.extend(wire.map(|line| ((depth, *handle, index), line)));
+                    } else if let Some(split) = split {
+                        let rank = split.ranks[index];
+                        split_queue.push((rank, Primitive::Triangle(raster_tri)));
+                        split_queue.extend(wire.map(|line| (rank, Primitive::Triangle(line))));
+                    } else {
+                        opaque.push(Primitive::Triangle(raster_tri));
+                        opaque.extend(wire.map(Primitive::Triangle));
+                    }
+                } else {
+                    stats.triangles_backface_culled += 1;
+                }
+            }
+            // By pixel, so the frame doesn't depend on the map's order.
+            let mut clusters: Vec<_> = small_clusters.drain().collect();
+            clusters.sort_by_key(|(pixel, _)| *pixel);
+            opaque.extend(
+                clusters
+                    .into_iter()
+                    .filter_map(|(_, cluster)| cluster.point(self.depth_buffer))
+                    .map(Primitive::Point),
+            );
+            drop(lighting_timer);
+            drop(raster_timer);
+
+            if self.selected == Some(*handle) {
+                let line_width = OUTLINE_WIDTH * self.ssaa as f32;
+                for (a, b) in silhouette_edges(model.primitives(), &vertices, self.cull_mode) {
+                    outline.extend(
+                        thick_line(
+                            vertices[a].screen,
+                            vertices[b].screen,
+                            line_width,
+                            self.outline_color,
+                        )
+                        .map(Primitive::Triangle),
+                    );
+                }
+            }
+        }
+
+        flush_split_queue(&mut split_queue, &mut opaque);
+        opaque.extend(
+            planar_shadows
+                .into_iter()
+                .flatten()
+                .map(Primitive::Triangle),
+        );
+        opaque.extend(sprites.map(|(_, sprite)| sprite));
+        if self.depth_buffer && self.front_to_back {
+            // With a depth buffer the order of solid primitives only
+            // matters where two are at exactly the same depth, so they go
+            // nearest first: whatever they hide then fails the depth test
+            // instead of being written and painted over. The rest keep
+            // their back to front order and come after, to blend over what
+            // they are in front of.
+            let _sort = profiler.scope(Stage::Sort);
+            let (mut solid, blended): (Vec<_>, Vec<_>) = opaque
+                .split_off(underlay)
+                .into_iter()
+                .partition(Primitive::is_solid);
+            solid.reverse();
+            opaque.extend(solid);
+            opaque.extend(blended);
+        }
+
+        {
+            let _sort = profiler.scope(Stage::Sort);
+            transparent.sort_by_key(|(key, _)| *key);
+        }
+        opaque.extend(
+            transparent
+                .into_iter()
+                .map(|(_, tri)| Primitive::Triangle(tri)),
+        );
+        opaque.extend(normals);
+        if self.show_light {
+            self.draw_light_marker(&clip_mat, width, height, &mut opaque);
+        }
+        if let Some(index) = self.show_frustum {
+            self.draw_frustum(index, &view_mat, &clip_mat, width, height, &mut opaque);
+        }
+        opaque.extend(outline);
+        let raster_timer = profiler.scope(Stage::Rasterize);
+        let pixels_timer = profiler.scope(Stage::Pixels);
+        let depth = self.depth_buffer.then_some(&mut depth[..]);
+        let pixels = rasterize(
+            &opaque,
+            frame,
+            depth,
+            width,
+            height,
+            self.threads,
+            RasterSettings {
+                backend: self.raster_backend,
+                fixed_point: self.deterministic,
+                depth: self.depth_convention,
+                lighting: Some(&lighting),
+                textures: self.materials.textures(),
+            },
+        );
+        stats.pixels_written = pixels.written;
+        stats.pixels_depth_rejected = pixels.depth_rejected;
+        stats.triangles_lit = pixels.lit;
+        drop(pixels_timer);
+        drop(raster_timer);
+        stats.stages = profiler.times();
+        stats
+    }
+
+    /// The mesh `object`, at index `handle` in `models`, is drawn with at
+    /// its current level of detail.
+    fn drawn_mesh<'a>(&self, handle: usize, object: &'a Object) -> &'a dyn Mesh {
+        match object.lods.as_slice() {
+            [] => &*object.mesh,
+            lods => {
+                let level = self.lod_levels.get(handle).copied().unwrap_or(0);
+                &*lods[level.min(lods.len() - 1)].1
+            }
+        }
+    }
+
+    /// The shadows of every object in `models` but the ground, flattened
+    /// onto the plane of `config` along the light. Only triangles facing
+    /// the light are projected: behind a silhouette they would cover the
+    /// same spot twice and darken it twice over.
+    fn project_planar_shadows(
+        &self,
+        config: &PlanarShadowConfig,
+        models: &[(usize, &Object, Matrix4<f32>)],
+        grounded: &[bool],
+        clip_mat: &Matrix4<f32>,
+        width: u32,
+        height: u32,
+    ) -> Vec<RasterTriangle> {
+        let Some(flatten) = planar_shadow::projection(config.plane_y, &self.light) else {
+            return Vec::new();
+        };
+        let mut triangles = Vec::new();
+        let mut corners = Vec::new();
+        for (handle, object, model_mat) in models {
+            if grounded[*handle] {
+                continue;
+            }
+            let mesh = self.drawn_mesh(*handle, object);
+            let origin = model_mat.transform_point(&Point3::origin());
+            corners.clear();
+            corners.extend(mesh.verts().iter().map(|vertex| {
+                let world = model_mat * vertex.to_homogeneous();
+                let flat = flatten * world;
+                // At or above a spot light nothing falls to the plane.
+                if flat.w <= 0.0 {
+                    return None;
+                }
+                let mut point = Point3::from(flat.xyz() / flat.w);
+                if (point.x - origin.x).hypot(point.z - origin.z) > config.radius {
+                    return None;
+                }
+                point.y += PLANAR_SHADOW_LIFT;
+                let projected = project_vertex(point, clip_mat, width, height);
+                Some((Point3::from(world.xyz()), projected))
+            }));
+            for tri in mesh.primitives().triangles() {
+                let [Some(a), Some(b), Some(c)] = [tri.v1, tri.v2, tri.v3].map(|i| corners[i])
+                else {
+                    continue;
+                };
+                if [a, b, c].iter().all(|(world, _)| world.y < config.plane_y)
+                    || [a, b, c].iter().any(|(_, projected)| projected.clipped)
+                {
+                    continue;
+                }
+                // The same winding the lighting calls a normal into the
+                // surface, so this is positive facing the light.
+                let inward = (b.0 - a.0).cross(&(c.0 - a.0));
+                if inward.dot(&self.light.direction_at(&a.0)) <= 0.0 {
+                    continue;
+                }
+                let [s1, s2, s3] = [a, b, c].map(|(_, projected)| projected.screen);
+                let cross = screen_cross(s1, s2, s3);
+                if cross.abs() < 2.0 * DEGENERATE_AREA || !on_screen(s1, s2, s3, width, height) {
+                    continue;
+                }
+                let depth = self.depth_buffer.then(|| {
+                    DepthPlane::new([a, b, c].map(|(_, projected)| {
+                        (projected.screen.x, projected.screen.y, projected.ndc_z)
+                    }))
+                });
+                // Seen from above or below, the flat triangle is drawn.
+                let (s2, s3) = if cross > 0.0 { (s2, s3) } else { (s3, s2) };
+                triangles.push(RasterTriangle {
+                    s1,
+                    s2,
+                    s3,
+                    color: config.color,
+                    dither: None,
+                    surface: None,
+                    depth,
+                    uv: None,
+                });
+            }
+        }
+        triangles
+    }
+
+    /// The two triangles of every billboard and particle wholly in front of
+    /// the camera and at least partly on screen, paired with its view space
+    /// depth.
+    fn project_billboards(
+        &self,
+        view_mat: &Matrix4<f32>,
+        proj_mat: &Matrix4<f32>,
+        width: u32,
+        height: u32,
+    ) -> Vec<(f32, [RasterTriangle; 2])> {
+        let depth_range = (self.render_mode == RenderMode::Depth).then(|| self.clip_planes());
+        let mut projected = Vec::new();
+        let particles = self.emitters.iter().flat_map(ParticleEmitter::billboards);
+        for billboard in self.billboards.iter().copied().chain(particles) {
+            let center = view_mat.transform_point(&billboard.position);
+            // The camera looks down -z in view space, so a square in the xy
+            // plane there faces it whichever way it turns.
+            let half = billboard.size / 2.0;
+            let corner = |x: f32, y: f32| {
+                project_vertex(center + Vector3::new(x, y, 0.0), proj_mat, width, height)
+            };
+            let corners = [
+                corner(-half, -half),
+                corner(half, -half),
+                corner(half, half),
+                corner(-half, half),
+            ];
+            if corners.iter().any(|corner| corner.clipped) {
+                continue;
+            }
+            let [bottom_left, bottom_right, top_right, top_left] = corners.map(|c| c.screen);
+            if !on_screen(bottom_left, bottom_right, top_right, width, height)
+                && !on_screen(bottom_left, top_right, top_left, width, height)
+            {
+                continue;
+            }
+            let color = match (depth_range, &self.fog) {
+                (Some((near, far)), _) => depth_gray(-center.z, near, far, billboard.color.a),
+                (None, Some(fog)) => Color {
+                    a: billboard.color.a,
+                    ..billboard.color.lerp(fog.color, fog.factor(-center.z))
+                },
+                (None, None) => billboard.color,
+            };
+            // The square faces the camera, so its depth is the same all over.
+            let depth = self
+                .depth_buffer
+                .then(|| DepthPlane::flat(corners[0].ndc_z));
+            let tri = |s1, s2, s3| RasterTriangle {
+                s1,
+                s2,
+                s3,
+                color,
+                dither: None,
+                surface: None,
+                depth,
+                uv: None,
+            };
+            projected.push((
+                center.z,
+                [
+                    tri(bottom_left, top_right, bottom_right),
+                    tri(bottom_left, top_left, top_right),
+                ],
+            ));
+        }
+        projected
+    }
+
+    /// Every impostor sphere in front of the camera, in view space with its
+    /// screen bounds, paired with its view space depth.
+    fn project_spheres(
+        &self,
+        view_mat: &Matrix4<f32>,
+        proj_mat: &Matrix4<f32>,
+        width: u32,
+        height: u32,
+    ) -> Vec<(f32, RasterSphere)> {
+        let mut projected = Vec::new();
+        for sphere in self.impostors.iter().flat_map(|batch| &batch.spheres) {
+            let center = view_mat.transform_point(&sphere.center);
+            let r = sphere.radius;
+            let clip = proj_mat * center.to_homogeneous();
+            if clip.w <= 0.0 || !(0.0..=1.0).contains(&(clip.z / clip.w)) {
+                continue;
+            }
+
+            // Bound the sphere by projecting the corners of its box.
+            let (mut min_x, mut min_y) = (f32::INFINITY, f32::INFINITY);
+            let (mut max_x, mut max_y) = (f32::NEG_INFINITY, f32::NEG_INFINITY);
+            let mut behind = false;
+            for corner in 0..8 {
+                let offset = Vector3::new(
+                    if corner & 1 == 0 { -r } else { r },
+                    if corner & 2 == 0 { -r } else { r },
+                    if corner & 4 == 0 { -r } else { r },
+                );
+                let clip = proj_mat * (center + offset).to_homogeneous();
+                if clip.w <= 0.0 {
+                    behind = true;
+                    break;
+                }
+                let screen = clip_to_screen(&clip, width, height);
+                min_x = min_x.min(screen.x);
+                max_x = max_x.max(screen.x);
+                min_y = min_y.min(screen.y);
+                max_y = max_y.max(screen.y);
+            }
+            if behind || max_x < 0.0 || max_y < 0.0 || min_x > width as f32 || min_y > height as f32
+            {
+                continue;
+            }
+
+            // Half a pixel of slack for the anti-aliased edge.
+            let clamp = |v: f32, hi: u32| (v.clamp(-1.0, hi as f32)) as i32;
+            projected.push((
+                center.z,
+                RasterSphere {
+                    center,
+                    radius: r,
+                    color: sphere.color,
+                    light: self.light.falloff(&sphere.center),
+                    min: (
+                        clamp((min_x - 0.5).floor(), width),
+                        clamp((min_y - 0.5).floor(), height),
+                    ),
+                    max: (
+                        clamp((max_x + 0.5).ceil(), width),
+                        clamp((max_y + 0.5).ceil(), height),
+                    ),
+                },
+            ));
+        }
+        projected
+    }
+
+    /// Fill `frame`, seen through `view_mat` and `proj_mat`, with the
+    /// background: the fog color when there is fog, otherwise according to
+    /// `clear_mode`.
+    fn clear(
+        &self,
+        frame: &mut [u8],
+        width: u32,
+        height: u32,
+        view_mat: &Matrix4<f32>,
+        proj_mat: &Matrix4<f32>,
+    ) {
+        let row_len = (width * 4) as usize;
+        let fill_row = |row: &mut [u8], c: Color| {
+            for pixel in row.chunks_exact_mut(4) {
+                pixel.copy_from_slice(&[c.r, c.g, c.b, c.a]);
+            }
+        };
+        let solid = match (&self.fog, self.clear_mode) {
+            _ if self.render_mode == RenderMode::Depth => Color {
+                r: 0,
+                g: 0,
+                b: 0,
+                a: 255,
+            },
+            (Some(fog), _) => fog.color,
+            (None, ClearMode::Solid) => self.clear_color,
+            (None, ClearMode::Gradient { top, bottom }) => {
+                for (y, row) in frame.chunks_exact_mut(row_len).enumerate() {
+                    let t = y as f32 / (height.max(2) - 1) as f32;
+                    fill_row(row, self.lerp_color(top, bottom, t));
+                }
+                return;
+            }
+            (None, ClearMode::Sky(sky)) => {
+                let gradient = sky_gradient(&sky, |a, b, t| self.lerp_color(a, b, t));
+                let eye = view_mat
+                    .try_inverse()
+                    .map_or_else(Point3::origin, |inverse| {
+                        inverse.transform_point(&Point3::origin())
+                    });
+                let sun = sky.sun.map(|disc| (-self.light.direction_at(&eye), disc));
+                fill_sky(
+                    frame,
+                    width,
+                    height,
+                    &(proj_mat * view_mat),
+                    self.depth_convention,
+                    &gradient,
+                    sun,
+                );
+                return;
+            }
+        };
+        if frame.len() >= row_len && row_len > 0 {
+            let (first, rest) = frame.split_at_mut(row_len);
+            fill_row(first, solid);
+            for row in rest.chunks_exact_mut(row_len) {
+                row.copy_from_slice(first);
+            }
+        }
+    }
+
+    /// Mix two colors, in linear light when gamma correction is on.
+    fn lerp_color(&self, a: Color, b: Color, t: f32) -> Color {
+        if t <= 0.0 {
+            return a;
+        } else if t >= 1.0 {
+            return b;
+        }
+        let mix = |x: u8, y: u8| -> u8 {
+            if self.gamma_correct {
+                linear_to_srgb(srgb_to_linear(x) * (1.0 - t) + srgb_to_linear(y) * t)
+            } else {
+                (x as f32 * (1.0 - t) + y as f32 * t).round() as u8
+            }
+        };
+        Color {
+            r: mix(a.r, b.r),
+            g: mix(a.g, b.g),
+            b: mix(a.b, b.b),
+            a: (a.a as f32 * (1.0 - t) + b.a as f32 * t).round() as u8,
+        }
+    }
+
+    /// A hash of everything that affects the rendered image through `view_mat`,
+    /// used to notice when nothing has changed between frames. Mesh contents
+    /// are assumed immutable; only their number and placement count.
+    pub fn fingerprint(&self, view_mat: &Matrix4<f32>) -> u64 {
+        let mut h = DefaultHasher::new();
+        hash_floats(&mut h, view_mat.as_slice());
+        hash_floats(&mut h, self.proj_mat.as_slice());
+        for camera in &self.cameras {
+            hash_floats(&mut h, camera.generate_view_mat().as_slice());
+        }
+        for viewport in &self.viewports {
+            let (left, top, width, height) = viewport.rect;
+            hash_floats(&mut h, &[left, top, width, height]);
+            viewport.camera_index.hash(&mut h);
+            hash_projection(&mut h, viewport.projection);
+        }
+        for target in &self.render_targets {
+            (target.camera_index, target.interval, target.texture()).hash(&mut h);
+            hash_projection(&mut h, target.projection);
+        }
+        self.render_target_depth.hash(&mut h);
+        hash_floats(&mut h, self.light.position.coords.as_slice());
+        hash_floats(&mut h, self.light.target.coords.as_slice());
+        hash_floats(&mut h, &[self.light.intensity]);
+        match self.ambient {
+            Ambient::Uniform(amount) => hash_floats(&mut h, &[amount]),
+            Ambient::Hemisphere {
+                sky_color,
+                ground_color,
+                strength,
+            } => {
+                sky_color.hash(&mut h);
+                ground_color.hash(&mut h);
+                hash_floats(&mut h, &[strength]);
+            }
+        }
+        hash_floats(&mut h, &[self.light.shadow_bias]);
+        if let LightKind::Spot {
+            inner_angle,
+            outer_angle,
+        } = self.light.kind
+        {
+            hash_floats(&mut h, &[inner_angle, outer_angle]);
+        }
+        self.light.shadow_resolution.hash(&mut h);
+        self.visible_layers.hash(&mut h);
+        hash_floats(&mut h, &[self.interpolation]);
+        for model in &self.models {
+            hash_floats(&mut h, &[model.offset_x, model.offset_y, model.offset_z]);
+            if let Some(previous) = model.previous
+                && self.interpolation < 1.0
+            {
+                hash_floats(&mut h, previous.offset.as_slice());
+                hash_floats(&mut h, previous.rotation.coords.as_slice());
+                hash_floats(&mut h, &[previous.scale]);
+            }
+            (model.visible, model.layer).hash(&mut h);
+            hash_floats(&mut h, model.rotation.coords.as_slice());
+            hash_floats(&mut h, &[model.scale, model.depth_bias]);
+            model.parent.hash(&mut h);
+            if let Some(handle) = model.material
+                && let Some(material) = self
+                    .materials
+                    .instantiate_override(handle, &model.material_override)
+            {
+                material.color.hash(&mut h);
+                material.unlit.hash(&mut h);
+                material.texture.hash(&mut h);
+                material.render_target.hash(&mut h);
+                hash_floats(
+                    &mut h,
+                    &[
+                        material.diffuse,
+                        material.emissive,
+                        material.specular,
+                        material.shininess,
+                    ],
+                );
+            }
+        }
+        self.models.len().hash(&mut h);
+        for texture in self.materials.textures() {
+            (texture.filter, texture.wrap, texture.generation()).hash(&mut h);
+        }
+        for sphere in self.impostors.iter().flat_map(|batch| &batch.spheres) {
+            hash_floats(&mut h, sphere.center.coords.as_slice());
+            hash_floats(&mut h, &[sphere.radius]);
+            sphere.color.hash(&mut h);
+        }
+        let particles = self.emitters.iter().flat_map(ParticleEmitter::billboards);
+        for billboard in self.billboards.iter().copied().chain(particles) {
+            hash_floats(&mut h, billboard.position.coords.as_slice());
+            hash_floats(&mut h, &[billboard.size]);
+            billboard.color.hash(&mut h);
+        }
+        for label in &self.labels {
+            hash_floats(&mut h, label.position.coords.as_slice());
+            label.text.hash(&mut h);
+            label.color.hash(&mut h);
+            (label.leader, label.hide_when_occluded).hash(&mut h);
+            label.object.hash(&mut h);
+        }
+        self.gamma_correct.hash(&mut h);
+        self.dither.hash(&mut h);
+        self.deterministic.hash(&mut h);
+        if self.show_gizmos {
+            self.grid.cells.hash(&mut h);
+            hash_floats(&mut h, &[self.grid.spacing, self.grid.fade_distance]);
+            self.grid.color.hash(&mut h);
+        }
+        self.render_mode.hash(&mut h);
+        self.id_palette.hash(&mut h);
+        self.shading_model.hash(&mut h);
+        self.show_normals.hash(&mut h);
+        self.wireframe.hash(&mut h);
+        self.show_light.hash(&mut h);
+        self.show_frustum.hash(&mut h);
+        self.highlight_overlaps.hash(&mut h);
+        if self.show_frustum == Some(0) {
+            hash_floats(&mut h, self.camera.generate_view_mat().as_slice());
+        }
+        self.cull_mode.hash(&mut h);
+        self.skip_slivers.hash(&mut h);
+        self.small_triangle_cull.map(f32::to_bits).hash(&mut h);
+        self.small_triangle_points.hash(&mut h);
+        self.depth_buffer.hash(&mut h);
+        self.front_to_back.hash(&mut h);
+        self.splitting_sort.hash(&mut h);
+        self.selected.hash(&mut h);
+        self.outline_color.hash(&mut h);
+        self.ssaa.hash(&mut h);
+        self.clear_color.hash(&mut h);
+        match self.clear_mode {
+            ClearMode::Solid => {}
+            ClearMode::Gradient { top, bottom } => {
+                top.hash(&mut h);
+                bottom.hash(&mut h);
+            }
+            ClearMode::Sky(sky) => {
+                (sky.horizon, sky.zenith).hash(&mut h);
+                if let Some(sun) = sky.sun {
+                    sun.color.hash(&mut h);
+                    hash_floats(&mut h, &[sun.angular_radius]);
+                }
+            }
+        }
+        if let Some(config) = &self.planar_shadows {
+            hash_floats(&mut h, &[config.plane_y, config.radius]);
+            config.color.hash(&mut h);
+        }
+        if let Some(stereo) = &self.stereo {
+            hash_floats(&mut h, &[stereo.eye_separation, stereo.convergence]);
+        }
+        if let Some(fog) = &self.fog {
+            hash_floats(&mut h, &[fog.start, fog.end]);
+            fog.color.hash(&mut h);
+            match fog.mode {
+                FogMode::Linear => 0.0f32.to_bits().hash(&mut h),
+                FogMode::Exponential { density } => density.to_bits().hash(&mut h),
+            }
+        }
+        h.finish()
+    }
+
+    /// Lighting as it stands, for the rasterizer to light triangles with.
+    fn scene_lighting(&self) -> SceneLighting<'_> {
+        SceneLighting {
+            light: &self.light,
+            ambient: self.ambient,
+            model: self.shading_model,
+            eye: self.camera.position,
+            fog: self.fog,
+            gamma: self.gamma_correct,
+            dither: self.dither,
+        }
+    }
+}
+
+/// One mesh vertex carried through the vertex stage for the object being
+/// drawn.
+#[derive(Debug, Clone, Copy)]
+struct TransformedVertex {
+    /// Pixel position, NaN when the vertex is clipped.
+    screen: Point2<f32>,
+    /// Depth after the perspective divide, for the depth buffer.
+    ndc_z: f32,
+    /// View space depth, negative in front of the camera.
+    view_z: f32,
+    /// World space position, worked out the first time a triangle using the
+    /// vertex survives culling.
+    world: Option<Point3<f32>>,
+}
+
+/// Queue a `size` pixel square at every vertex in front of the camera and
+/// on screen, farthest first, returning how many. Each takes `color` if
+/// set, or else the color of the first triangle using it, and is tested
+/// against the depth buffer if `depth_tested`.
+/// Small triangles gathered into one pixel by
+/// [`World::small_triangle_points`].
+#[derive(Default)]
+struct SmallCluster {
+    center: Vector2<f32>,
+    /// Screen area of the triangles, in pixels.
+    area: f32,
+    rgba: [u32; 4],
+    ndc_z: f32,
+    count: u32,
+}
+
+impl SmallCluster {
+    fn add(&mut self, center: Point2<f32>, area: f32, color: Color, ndc_z: f32) {
+        self.center += center.coords;
+        self.area += area;
+        for (sum, channel) in self
+            .rgba
+            .iter_mut()
+            .zip([color.r, color.g, color.b, color.a])
+        {
+            *sum += channel as u32;
+        }
+        self.ndc_z += ndc_z;
+        self.count += 1;
+    }
+
+    /// The pixel standing in for the triangles, if they cover at least
+    /// half of it: about when rasterizing them would have reached its
+    /// centre.
+    fn point(&self, depth_tested: bool) -> Option<RasterPoint> {
+        if self.area < 0.5 || self.count == 0 {
+            return None;
+        }
+        let n = self.count;
+        let [r, g, b, a] = self.rgba.map(|sum| ((sum + n / 2) / n) as u8);
+        Some(RasterPoint {
+            center: Point2::from(self.center / n as f32),
+            size: 1,
+            color: Color { r, g, b, a },
+            depth: depth_tested.then_some(self.ndc_z / n as f32),
+        })
+    }
+}
+
+fn queue_points(
+    model: &dyn Mesh,
+    vertices: &[TransformedVertex],
+    color: Option<Color>,
+    size: u32,
+    depth_tested: bool,
+    (width, height): (u32, u32),
+    out: &mut Vec<Primitive>,
+) -> usize {
+    let mut colors = vec![color; vertices.len()];
+    if color.is_none() {
+        for tri in model.primitives().triangles() {
+            for v in [tri.v1, tri.v2, tri.v3] {
+                colors[v].get_or_insert(tri.color);
+            }
+        }
+    }
+    // Clipped vertices sit at NaN and fail the screen test.
+    let mut visible: Vec<usize> = (0..vertices.len())
+        .filter(|&i| point_on_screen(vertices[i].screen, size, width, height))
+        .collect();
+    visible.sort_by_key(|&i| OrderedFloat(vertices[i].view_z));
+    out.extend(visible.iter().map(|&i| {
+        Primitive::Point(RasterPoint {
+            center: vertices[i].screen,
+            size,
+            color: colors[i].unwrap_or(Color::GRAY),
+            depth: depth_tested.then_some(vertices[i].ndc_z),
+        })
+    }));
+    visible.len()
+}
+
+/// Whether `model`, placed by `matrix`, lies wholly on or below the plane
+/// `y = plane_y`, so it is ground for planar shadows rather than a caster.
+fn on_ground(model: &Object, matrix: &Matrix4<f32>, plane_y: f32) -> bool {
+    let (min, max) = model.mesh.aabb();
+    (0..8).all(|i| {
+        let pick = |bit: usize, lo: f32, hi: f32| if i & bit == 0 { lo } else { hi };
+        let corner = Point3::new(
+            pick(1, min.x, max.x),
+            pick(2, min.y, max.y),
+            pick(4, min.z, max.z),
+        );
+        matrix.transform_point(&corner).y <= plane_y + PLANAR_SHADOW_LIFT
+    })
+}
+
+/// Whether `mesh`'s bounding box, taken through the model-view-projection
+/// `proj`, is wholly beyond one side of the view, so none of it can reach
+/// the frame.
+fn outside_view(mesh: &dyn Mesh, proj: &Matrix4<f32>) -> bool {
+    let (min, max) = mesh.aabb();
+    !aabb_intersects_frustum(&extract_frustum_planes(proj), &min, &max)
+}
+
+/// Edges of `tris` on the outline of the mesh as seen on screen: those with
+/// exactly one front facing triangle, which covers both the silhouette of a
+/// closed mesh and the open border of a flat one. With [`CullMode::Front`]
+/// the back faces are the ones counted. Edges touching a clipped vertex are
+/// left out.
+fn silhouette_edges(
+    primitives: MeshPrimitives,
+    verts: &[TransformedVertex],
+    cull_mode: CullMode,
+) -> Vec<(usize, usize)> {
+    let mut front_faces: HashMap<(usize, usize), u32> = HashMap::new();
+    for tri in primitives.triangles() {
+        let (s1, s2, s3) = (
+            verts[tri.v1].screen,
+            verts[tri.v2].screen,
+            verts[tri.v3].screen,
+        );
+        if !s1.x.is_finite() || !s2.x.is_finite() || !s3.x.is_finite() {
+            continue;
+        }
+        let front = (is_front_facing(s1, s2, s3) != (cull_mode == CullMode::Front)) as u32;
+        for (a, b) in [(tri.v1, tri.v2), (tri.v2, tri.v3), (tri.v3, tri.v1)] {
+            *front_faces.entry((a.min(b), a.max(b))).or_default() += front;
+        }
+    }
+    let mut edges: Vec<(usize, usize)> = front_faces
+        .into_iter()
+        .filter(|&(_, count)| count == 1)
+        .map(|(edge, _)| edge)
+        .collect();
+    // HashMap order varies between runs; keep the frame deterministic.
+    edges.sort_unstable();
+    edges
+}
+
+/// Move the queued triangles of a split group to `opaque`, in their
+/// group's order.
+fn flush_split_queue<'a>(queue: &mut Vec<(u32, Primitive<'a>)>, opaque: &mut Vec<Primitive<'a>>) {
+    queue.sort_by_key(|(rank, _)| *rank);
+    opaque.extend(queue.drain(..).map(|(_, primitive)| primitive));
+}
+
+fn hash_floats(hasher: &mut impl Hasher, values: &[f32]) {
+    for v in values {
+        v.to_bits().hash(hasher);
+    }
+}
+
+/// Width, height and RGBA byte length of a `width * height` frame
+/// supersampled `factor` times per axis, if they fit.
+fn supersampled_size(width: u32, height: u32, factor: u32) -> Option<(u32, u32, usize)> {
+    let big_width = width.checked_mul(factor)?;
+    let big_height = height.checked_mul(factor)?;
+    let len = (big_width as usize)
+        .checked_mul(big_height as usize)?
+        .checked_mul(4)?;
+    Some((big_width, big_height, len))
+}
+
+/// Box filter `src`, rendered at `factor` times the resolution of `dst`, into
+/// `dst`. Color is averaged in linear light so edges don't come out dark.
+fn downsample(src: &[u8], dst: &mut [u8], width: u32, height: u32, factor: u32) {
+    let src_width = width as usize * factor as usize;
+    let samples = (factor * factor) as f32;
+    for y in 0..height {
+        for x in 0..width {
+            let mut sum = [0.0f32; 4];
+            for sy in y * factor..(y + 1) * factor {
+                for sx in x * factor..(x + 1) * factor {
+                    let i = (sy as usize * src_width + sx as usize) * 4;
+                    sum[0] += srgb_to_linear(src[i]);
+                    sum[1] += srgb_to_linear(src[i + 1]);
+                    sum[2] += srgb_to_linear(src[i + 2]);
+                    sum[3] += src[i + 3] as f32;
+                }
+            }
+            let i = (y as usize * width as usize + x as usize) * 4;
+            dst[i] = linear_to_srgb(sum[0] / samples);
+            dst[i + 1] = linear_to_srgb(sum[1] / samples);
+            dst[i + 2] = linear_to_srgb(sum[2] / samples);
+            dst[i + 3] = (sum[3] / samples).round() as u8;
+        }
+    }
+}
+
+/// Make `model`'s own offset, rotation and scale produce `matrix`, which
+/// must be built from those three with a uniform scale.
+fn hash_projection(h: &mut impl Hasher, projection: Projection) {
+    match projection {
+        Projection::Main => 0u8.hash(h),
+        Projection::Perspective { fovy, near, far } => {
+            1u8.hash(h);
+            hash_floats(h, &[fovy, near, far]);
+        }
+        Projection::Orthographic { height, near, far } => {
+            2u8.hash(h);
+            hash_floats(h, &[height, near, far]);
+        }
+    }
+}
+
+pub(crate) fn set_transform(model: &mut Object, matrix: &Matrix4<f32>) {
+    let scale = matrix.column(0).xyz().norm();
+    let translation = matrix.column(3).xyz();
+    model.offset_x = translation.x;
+    model.offset_y = translation.y;
+    model.offset_z = translation.z;
+    model.scale = scale;
+    if scale > 0.0 {
+        let rotation = matrix.fixed_view::<3, 3>(0, 0) / scale;
+        model.rotation = UnitQuaternion::from_matrix(&rotation.into_owned());
+    }
+}
+
+fn object_depth(view_mat: &Matrix4<f32>, model_mat: &Matrix4<f32>) -> OrderedFloat<f32> {
+    let view_model = view_mat * model_mat;
+    let object_pos = view_model.transform_point(&Point3::origin());
+    OrderedFloat(object_pos.z)
+}
@@ -0,0 +1,60 @@
+mod common;
+
+use common::{HEIGHT, WIDTH, object, pixel, render, world};
+
+use engine::Ambient;
+use engine::mesh::Color;
+use engine::mesh::cube::CubeMesh;
+use nalgebra::Point3;
+
+const SKY: Color = Color::rgb(120, 180, 240);
+const GROUND: Color = Color::rgb(90, 60, 30);
+
+fn hemisphere(strength: f32) -> Ambient {
+    Ambient::Hemisphere {
+        sky_color: SKY,
+        ground_color: GROUND,
+        strength,
+    }
+}
+
+fn close(found: [u8; 4], expected: Color) -> bool {
+    let expected = [expected.r, expected.g, expected.b, expected.a];
+    found.iter().zip(expected).all(|(&a, b)| a.abs_diff(b) <= 1)
+}
+
+#[test]
+fn uniform_ambient_is_the_same_everywhere() {
+    let ambient = Ambient::Uniform(0.25);
+    assert_eq!(ambient.light(1.0), [0.25; 3]);
+    assert_eq!(ambient.light(-1.0), [0.25; 3]);
+}
+
+#[test]
+fn hemisphere_blends_sky_and_ground_by_normal() {
+    let ambient = hemisphere(1.0);
+    let channels = |c: Color| [c.r, c.g, c.b].map(|c| c as f32 / 255.0);
+    assert_eq!(ambient.light(1.0), channels(SKY));
+    assert_eq!(ambient.light(-1.0), channels(GROUND));
+    let side = ambient.light(0.0);
+    assert!((side[0] - (120.0 + 90.0) / 2.0 / 255.0).abs() < 1e-6);
+    assert_eq!(hemisphere(0.5).light(1.0), channels(SKY).map(|c| c * 0.5));
+}
+
+#[test]
+fn tops_get_sky_and_bottoms_get_ground() {
+    let mut world = world(vec![object(CubeMesh::new(Color::WHITE), 0.0, 0.0, 0.0)]);
+    world.light.intensity = 0.0;
+    world.gamma_correct = false;
+    world.ambient = hemisphere(1.0);
+
+    world.camera.position = Point3::new(0.0, 5.0, -0.1);
+    world.camera.look_at(Point3::origin());
+    let top = pixel(&render(&mut world), WIDTH / 2, HEIGHT / 2);
+    assert!(close(top, SKY), "{top:?}");
+
+    world.camera.position = Point3::new(0.0, -5.0, -0.1);
+    world.camera.look_at(Point3::origin());
+    let bottom = pixel(&render(&mut world), WIDTH / 2, HEIGHT / 2);
+    assert!(close(bottom, GROUND), "{bottom:?}");
+}
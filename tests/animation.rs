@@ -0,0 +1,66 @@
+mod common;
+
+use std::f32::consts::PI;
+
+use common::{object, render, world};
+
+use engine::Animation;
+use engine::mesh::p_hack::PHackMesh;
+use nalgebra::{Point3, Vector3};
+
+#[test]
+fn bob_returns_to_its_start_after_a_period() {
+    let bob = object(PHackMesh::new(), 0.0, 1.0, 0.0).with_animation(Animation::Bob {
+        amplitude: 0.5,
+        speed: PI,
+    });
+    let mut world = world(vec![bob]);
+
+    world.update(0.5);
+    assert!((world.models[0].offset_y - 1.5).abs() < 1e-5);
+    for _ in 0..3 {
+        world.update(0.5);
+    }
+    assert!((world.models[0].offset_y - 1.0).abs() < 1e-5);
+    assert!((world.time - 2.0).abs() < 1e-6);
+}
+
+#[test]
+fn orbit_follows_the_world_clock() {
+    let orbit = object(PHackMesh::new(), 0.0, 0.0, 0.0).with_animation(Animation::Orbit {
+        center: Point3::new(1.0, 2.0, 3.0),
+        radius: 2.0,
+        speed: PI,
+    });
+    let mut world = world(vec![orbit]);
+
+    world.update(0.5);
+    let model = &world.models[0];
+    assert!((model.offset_x - 1.0).abs() < 1e-5);
+    assert_eq!(model.offset_y, 2.0);
+    assert!((model.offset_z - 5.0).abs() < 1e-5);
+}
+
+#[test]
+fn spin_rotates_the_rendered_mesh() {
+    let spin = object(PHackMesh::new(), 0.0, 0.0, 0.0).with_animation(Animation::Spin {
+        axis: Vector3::y_axis(),
+        speed: 1.0,
+    });
+    let mut world = world(vec![spin]);
+    assert!(world.is_animated());
+    let before = render(&mut world);
+
+    world.update(0.5);
+    assert!((world.models[0].rotation.angle() - 0.5).abs() < 1e-5);
+    assert!(render(&mut world) != before);
+}
+
+#[test]
+fn static_objects_do_not_move() {
+    let mut world = world(vec![object(PHackMesh::new(), 1.0, 2.0, 3.0)]);
+    assert!(!world.is_animated());
+    let before = render(&mut world);
+    world.update(1.0);
+    assert!(render(&mut world) == before);
+}
@@ -0,0 +1,129 @@
+mod common;
+
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+use common::world;
+use engine::World;
+use engine::world::PLACEHOLDER_COLOR;
+use nalgebra::{Matrix4, Vector3};
+
+/// An OBJ file of `faces` triangles in the temp directory, unique to this
+/// test run and `name`.
+fn obj_file(name: &str, faces: usize) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("shapes-load-{}-{name}.obj", std::process::id()));
+    let mut text = String::new();
+    for i in 0..faces {
+        text.push_str(&format!("v {i} 0 0\nv {i} 1 0\nv {i} 0 1\n"));
+        text.push_str(&format!("f {} {} {}\n", 3 * i + 1, 3 * i + 2, 3 * i + 3));
+    }
+    std::fs::write(&path, text).unwrap();
+    path
+}
+
+/// Poll until every load is in, or give up after a few seconds.
+fn finish_loads(world: &mut World) -> usize {
+    let start = Instant::now();
+    let mut arrived = 0;
+    while world.loads_pending() > 0 && start.elapsed() < Duration::from_secs(10) {
+        arrived += world.poll_loads();
+        std::thread::sleep(Duration::from_millis(1));
+    }
+    assert_eq!(world.loads_pending(), 0, "loads timed out");
+    arrived
+}
+
+fn is_placeholder(world: &World, index: usize) -> bool {
+    let mesh = &world.models[index].mesh;
+    mesh.verts().len() == 8
+        && mesh
+            .primitives()
+            .triangles()
+            .all(|tri| tri.color == PLACEHOLDER_COLOR)
+}
+
+#[test]
+fn placeholders_stand_in_until_the_mesh_arrives() {
+    let path = obj_file("one", 1);
+    let mut world = world(vec![]);
+    let transform =
+        Matrix4::new_translation(&Vector3::new(1.0, 2.0, 3.0)) * Matrix4::new_scaling(2.0);
+    let index = world.load_object_async(&path, transform);
+    assert_eq!(index, 0);
+    assert!(is_placeholder(&world, 0));
+    let model = &world.models[0];
+    assert_eq!(
+        (model.offset_x, model.offset_y, model.offset_z),
+        (1.0, 2.0, 3.0)
+    );
+    assert!((model.scale - 2.0).abs() < 1e-6);
+
+    assert_eq!(finish_loads(&mut world), 1);
+    assert_eq!(world.models[0].mesh.verts().len(), 3);
+    // Placement is kept.
+    assert_eq!(world.models[0].offset_y, 2.0);
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn failed_loads_keep_the_placeholder() {
+    let mut world = world(vec![]);
+    world.load_object_async("/nonexistent/shapes/missing.obj", Matrix4::identity());
+    assert_eq!(finish_loads(&mut world), 0);
+    assert!(is_placeholder(&world, 0));
+}
+
+#[test]
+fn loads_run_side_by_side() {
+    let paths: Vec<PathBuf> = (1..=4)
+        .map(|faces| obj_file(&format!("many{faces}"), faces * 50))
+        .collect();
+    let mut world = world(vec![]);
+    for path in &paths {
+        world.load_object_async(path, Matrix4::identity());
+    }
+    assert_eq!(world.loads_pending(), 4);
+    assert_eq!(finish_loads(&mut world), 4);
+    for (i, model) in world.models.iter().enumerate() {
+        assert_eq!(model.mesh.primitives().triangles().count(), (i + 1) * 50);
+    }
+    for path in paths {
+        std::fs::remove_file(path).unwrap();
+    }
+}
+
+#[test]
+fn loads_follow_their_object_when_another_is_removed() {
+    let (first, second) = (obj_file("first", 1), obj_file("second", 20));
+    let mut world = world(vec![]);
+    world.load_object_async(&first, Matrix4::identity());
+    world.load_object_async(&second, Matrix4::identity());
+    world.remove_object(0);
+    assert_eq!(world.loads_pending(), 1);
+    assert_eq!(finish_loads(&mut world), 1);
+    assert_eq!(world.models[0].mesh.primitives().triangles().count(), 20);
+    std::fs::remove_file(first).unwrap();
+    std::fs::remove_file(second).unwrap();
+}
+
+#[test]
+fn loads_for_objects_removed_by_hand_are_dropped() {
+    let path = obj_file("by-hand", 1);
+    let mut world = world(vec![]);
+    world.load_object_async(&path, Matrix4::identity());
+    world.models.clear();
+    assert_eq!(finish_loads(&mut world), 0);
+    assert!(world.models.is_empty());
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn worlds_can_be_dropped_mid_load() {
+    let path = obj_file("dropped", 20_000);
+    let mut world = world(vec![]);
+    world.load_object_async(&path, Matrix4::identity());
+    drop(world);
+    // Long enough for the loader to finish and find nobody listening.
+    std::thread::sleep(Duration::from_millis(300));
+    std::fs::remove_file(path).unwrap();
+}
@@ -0,0 +1,93 @@
+mod common;
+
+use std::f32::consts::FRAC_PI_2;
+
+use common::{HEIGHT, WHITE, WIDTH, object, pixel, render, world};
+
+use engine::mesh::p_hack::PHackMesh;
+use engine::mesh::quad::QuadMesh;
+use engine::mesh::{Color, Mesh, Triangle};
+use nalgebra::{Point3, UnitQuaternion, Vector3};
+
+const GREEN: Color = Color {
+    r: 30,
+    g: 180,
+    b: 60,
+    a: 255,
+};
+
+/// A floor below the camera stretching far behind it.
+fn floor() -> engine::Object {
+    let mut floor = object(QuadMesh::grid(40.0, 40.0, 20, GREEN), 0.0, -1.0, 0.0);
+    floor.rotation = UnitQuaternion::from_axis_angle(&Vector3::x_axis(), FRAC_PI_2);
+    floor
+}
+
+#[test]
+fn geometry_behind_the_camera_never_reaches_the_sky() {
+    let mut world = world(vec![floor()]);
+    let frame = render(&mut world);
+    // Everything on the floor projects below the horizon; a mirrored vertex
+    // from behind the camera would smear across the top half.
+    for y in 0..HEIGHT / 2 {
+        for x in 0..WIDTH {
+            assert_eq!(pixel(&frame, x, y), WHITE, "({x}, {y})");
+        }
+    }
+    assert!((HEIGHT / 2 + 1..HEIGHT).any(|y| pixel(&frame, WIDTH / 2, y) != WHITE));
+}
+
+#[test]
+fn vertices_on_the_eye_plane_are_rejected() {
+    // One corner exactly level with the camera, where w is zero.
+    let mut quad = object(QuadMesh::new(2.0, 2.0, GREEN), 0.0, 0.0, -4.0);
+    quad.rotation = UnitQuaternion::from_axis_angle(&Vector3::y_axis(), FRAC_PI_2);
+    let mut world = world(vec![quad]);
+    let frame = render(&mut world);
+    assert!(frame.chunks(4).all(|p| p == WHITE));
+    assert_eq!(world.stats().triangles_rasterized, 0);
+}
+
+/// A copy of a mesh keeping only some of its triangles.
+struct Filtered {
+    verts: Vec<Point3<f32>>,
+    tris: Vec<Triangle>,
+}
+
+impl Mesh for Filtered {
+    fn verts(&self) -> &[Point3<f32>] {
+        &self.verts
+    }
+
+    fn tris(&self) -> &Vec<Triangle> {
+        &self.tris
+    }
+}
+
+#[test]
+fn camera_inside_a_mesh_draws_only_what_is_in_front() {
+    let mesh = PHackMesh::new();
+    let inside = Point3::new(0.1, 0.2, -0.3);
+    let look = |world: &mut engine::World| {
+        world.camera.position = inside;
+        world.camera.look_at(inside + Vector3::z());
+    };
+    let mut world = world(vec![object(PHackMesh::new(), 0.0, 0.0, 0.0)]);
+    look(&mut world);
+    let frame = render(&mut world);
+
+    // Only triangles wholly past the near plane can be drawn.
+    let near = inside.z + 0.1;
+    let in_front = Filtered {
+        verts: mesh.verts().to_vec(),
+        tris: mesh
+            .tris()
+            .iter()
+            .filter(|t| [t.v1, t.v2, t.v3].iter().all(|&v| mesh.verts()[v].z > near))
+            .copied()
+            .collect(),
+    };
+    let mut expected = common::world(vec![object(in_front, 0.0, 0.0, 0.0)]);
+    look(&mut expected);
+    assert!(frame == render(&mut expected));
+}
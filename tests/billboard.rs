@@ -0,0 +1,88 @@
+mod common;
+
+use common::{HEIGHT, WHITE, WIDTH, object, pixel, render, world};
+use engine::Billboard;
+use engine::mesh::Color;
+use engine::mesh::quad::QuadMesh;
+use nalgebra::Point3;
+
+const RED: Color = Color::rgb(220, 30, 30);
+const BLUE: Color = Color::rgb(30, 30, 220);
+
+#[test]
+fn billboards_show_their_color_unlit() {
+    let mut world = world(vec![]);
+    world
+        .billboards
+        .push(Billboard::new(Point3::origin(), 1.0, RED));
+    let frame = render(&mut world);
+    assert_eq!(pixel(&frame, WIDTH / 2, HEIGHT / 2), [220, 30, 30, 255]);
+    assert_eq!(pixel(&frame, 0, 0), WHITE);
+    assert_eq!(world.stats().billboards_rasterized, 1);
+}
+
+#[test]
+fn billboards_face_the_camera_from_any_side() {
+    let mut world = world(vec![]);
+    world
+        .billboards
+        .push(Billboard::new(Point3::origin(), 1.0, RED));
+    for eye in [
+        Point3::new(5.0, 0.0, 0.0),
+        Point3::new(0.0, 4.0, 3.0),
+        Point3::new(-3.0, -2.0, 4.0),
+    ] {
+        world.camera.position = eye;
+        world.camera.look_at(Point3::origin());
+        let frame = render(&mut world);
+        assert_eq!(pixel(&frame, WIDTH / 2, HEIGHT / 2), [220, 30, 30, 255]);
+    }
+}
+
+#[test]
+fn billboards_sort_with_meshes() {
+    let wall = || object(QuadMesh::new(2.0, 2.0, BLUE), 0.0, 0.0, 0.0);
+    let blue_or_red = |world: &mut engine::World| {
+        let p = pixel(&render(world), WIDTH / 2, HEIGHT / 2);
+        assert!(p != WHITE);
+        p[0] > p[2]
+    };
+
+    let mut world = world(vec![wall()]);
+    world
+        .billboards
+        .push(Billboard::new(Point3::new(0.0, 0.0, -1.0), 0.5, RED));
+    assert!(blue_or_red(&mut world), "billboard in front of the wall");
+
+    world.billboards[0].position.z = 1.0;
+    assert!(!blue_or_red(&mut world), "billboard behind the wall");
+}
+
+#[test]
+fn billboards_behind_the_camera_are_culled() {
+    let mut world = world(vec![]);
+    world
+        .billboards
+        .push(Billboard::new(Point3::new(0.0, 0.0, -8.0), 1.0, RED));
+    // Straddling the eye.
+    world
+        .billboards
+        .push(Billboard::new(Point3::new(0.0, 0.0, -5.0), 1.0, RED));
+    let frame = render(&mut world);
+    assert!(frame.chunks(4).all(|p| p == WHITE));
+    assert_eq!(world.stats().billboards_rasterized, 0);
+}
+
+#[test]
+fn a_field_of_billboards_draws_in_one_frame() {
+    let mut world = world(vec![]);
+    for i in 0..500 {
+        let (x, y) = ((i % 25) as f32 * 0.2 - 2.4, (i / 25) as f32 * 0.2 - 2.0);
+        world
+            .billboards
+            .push(Billboard::new(Point3::new(x, y, (i % 3) as f32), 0.1, RED));
+    }
+    render(&mut world);
+    assert_eq!(world.stats().billboards_rasterized, 500);
+    assert_eq!(world.stats().objects_drawn, 0);
+}
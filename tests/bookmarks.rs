@@ -0,0 +1,110 @@
+mod common;
+
+use common::world;
+
+use engine::bookmark::{
+    BookmarkError, CameraPose, CameraTransition, ease_in_out, format_bookmarks, parse_bookmarks,
+};
+use nalgebra::{Point3, UnitQuaternion, Vector3};
+
+fn pose(x: f32, yaw: f32, fovy: f32) -> CameraPose {
+    CameraPose {
+        position: Point3::new(x, 1.0, -5.0),
+        orientation: UnitQuaternion::from_axis_angle(&Vector3::y_axis(), yaw),
+        fovy,
+    }
+}
+
+#[test]
+fn easing_starts_and_ends_slowly() {
+    assert_eq!(ease_in_out(0.0), 0.0);
+    assert_eq!(ease_in_out(0.5), 0.5);
+    assert_eq!(ease_in_out(1.0), 1.0);
+    assert!(ease_in_out(0.1) < 0.1);
+    assert!(ease_in_out(0.9) > 0.9);
+}
+
+#[test]
+fn transitions_blend_position_orientation_and_fov() {
+    let (from, to) = (pose(0.0, 0.0, 1.0), pose(4.0, 1.0, 0.5));
+    let mut transition = CameraTransition::new(from, to, 2.0);
+    let halfway = transition.advance(1.0);
+    assert!((halfway.position.x - 2.0).abs() < 1e-5);
+    assert!((halfway.fovy - 0.75).abs() < 1e-5);
+    assert!((halfway.orientation.angle() - 0.5).abs() < 1e-5);
+    assert!(!transition.is_finished());
+    assert_eq!(transition.advance(5.0), to);
+    assert!(transition.is_finished());
+
+    // Facing the other way has no shorter arc; no NaNs either way.
+    let behind = pose(0.0, std::f32::consts::PI, 1.0);
+    let turned = from.lerp(&behind, 0.5);
+    assert!(turned.orientation.coords.iter().all(|c| c.is_finite()));
+}
+
+#[test]
+fn worlds_fly_to_bookmarks_and_can_be_stopped() {
+    let mut world = world(vec![]);
+    let start = world.camera_pose();
+    world.save_bookmark(2);
+    assert!(!world.go_to_bookmark(0, 1.0));
+
+    world.camera.position = Point3::new(10.0, 0.0, 0.0);
+    world.set_field_of_view(0.5);
+    assert!(world.go_to_bookmark(2, 1.0));
+    assert!(world.is_animated());
+    world.update(0.5);
+    let midway = world.camera.position.x;
+    assert!(midway > 0.0 && midway < 10.0);
+    for _ in 0..10 {
+        world.update(0.1);
+    }
+    assert!(!world.is_camera_in_transition());
+    assert!((world.camera.position - start.position).norm() < 1e-5);
+    assert!((world.field_of_view() - start.fovy).abs() < 1e-5);
+
+    world.camera.position = Point3::new(10.0, 0.0, 0.0);
+    world.go_to_bookmark(2, 1.0);
+    world.update(0.25);
+    assert!(world.cancel_camera_transition());
+    let stopped = world.camera.position;
+    world.update(0.25);
+    assert_eq!(world.camera.position, stopped);
+
+    // No time at all jumps straight there.
+    world.go_to_bookmark(2, 0.0);
+    assert!(!world.is_camera_in_transition());
+    assert!((world.camera.position - start.position).norm() < 1e-5);
+}
+
+#[test]
+fn bookmarks_round_trip_through_text() {
+    let mut bookmarks = vec![None; 9];
+    bookmarks[0] = Some(pose(1.5, 0.3, 0.9));
+    bookmarks[8] = Some(pose(-2.0, -1.2, 1.1));
+    let parsed = parse_bookmarks(&format_bookmarks(&bookmarks)).unwrap();
+    assert_eq!(parsed.len(), 9);
+    for (parsed, saved) in parsed.iter().zip(&bookmarks) {
+        match (parsed, saved) {
+            (Some(parsed), Some(saved)) => {
+                assert_eq!(parsed.position, saved.position);
+                assert!(parsed.orientation.angle_to(&saved.orientation) < 1e-5);
+                assert_eq!(parsed.fovy, saved.fovy);
+            }
+            (None, None) => {}
+            _ => panic!("slots differ"),
+        }
+    }
+}
+
+#[test]
+fn bad_bookmark_lines_are_reported() {
+    let err = |text: &str| match parse_bookmarks(text) {
+        Err(BookmarkError::Parse { line, .. }) => line,
+        other => panic!("expected a parse error, got {other:?}"),
+    };
+    assert_eq!(err("# ok\n1 0 0 0 0 0 0 1\n"), 2);
+    assert_eq!(err("10 0 0 0 0 0 0 1 1\n"), 1);
+    assert_eq!(err("1 0 0 0 0 0 0 0 1\n"), 1);
+    assert_eq!(err("\n1 x 0 0 0 0 0 1 1\n"), 2);
+}
@@ -0,0 +1,80 @@
+use engine::Camera;
+use engine::track::{CameraTrack, TrackError};
+use nalgebra::Point3;
+
+fn track(points: &[(f32, f32)]) -> CameraTrack {
+    let mut track = CameraTrack::new();
+    for &(t, x) in points {
+        let camera = Camera::looking_at(Point3::new(x, 0.0, -5.0), Point3::new(x, 0.0, 0.0));
+        track.record(t, &camera);
+    }
+    track
+}
+
+#[test]
+fn passes_through_every_keyframe() {
+    let track = track(&[(0.0, 0.0), (0.25, 1.0), (0.5, 3.0), (0.75, 2.0)]);
+    for key in track.keyframes() {
+        let (position, target) = track.sample(key.time);
+        assert!((position - key.position).norm() < 1e-5);
+        assert!((target - key.target).norm() < 1e-4);
+    }
+}
+
+#[test]
+fn interpolates_smoothly_between_keyframes() {
+    // Evenly spaced keyframes along a line stay on it at constant speed.
+    let track = track(&[(0.0, 0.0), (1.0, 1.0), (2.0, 2.0), (3.0, 3.0)]);
+    let (position, _) = track.sample(1.5);
+    assert!((position.x - 1.5).abs() < 1e-5);
+    // No jump arriving at a keyframe.
+    let track = self::track(&[(0.0, 0.0), (1.0, 1.0), (2.0, 0.0)]);
+    let (position, _) = track.sample(1.0 - 1e-3);
+    assert!((position.x - 1.0).abs() < 1e-2);
+}
+
+#[test]
+fn holds_the_ends() {
+    let track = track(&[(0.0, 0.0), (0.25, 1.0), (0.5, 4.0)]);
+    assert_eq!(track.sample(-1.0).0, Point3::new(0.0, 0.0, -5.0));
+    assert_eq!(track.sample(0.5).0, Point3::new(4.0, 0.0, -5.0));
+    assert_eq!(track.sample(100.0).0, Point3::new(4.0, 0.0, -5.0));
+    assert_eq!(track.duration(), 0.5);
+}
+
+#[test]
+fn ignores_keyframes_out_of_order() {
+    let track = track(&[(0.0, 0.0), (0.5, 1.0), (0.5, 2.0), (0.25, 3.0)]);
+    assert_eq!(track.keyframes().len(), 2);
+}
+
+#[test]
+fn playback_faces_the_target() {
+    let track = track(&[(0.0, 0.0), (1.0, 2.0)]);
+    let mut camera = Camera::looking_at(Point3::new(9.0, 9.0, 9.0), Point3::origin());
+    track.apply(1.0, &mut camera);
+    assert_eq!(camera.position, Point3::new(2.0, 0.0, -5.0));
+    assert!((camera.forward() - nalgebra::Vector3::z()).norm() < 1e-5);
+}
+
+#[test]
+fn saves_and_loads() {
+    let track = track(&[(0.0, 0.0), (0.25, 1.5), (0.5, -3.25)]);
+    let path = std::env::temp_dir().join(format!("shapes-track-{}.txt", std::process::id()));
+    track.save(&path).unwrap();
+    let loaded = CameraTrack::from_path(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(loaded, track);
+}
+
+#[test]
+fn bad_files_report_the_line() {
+    let error = |text: &str| match CameraTrack::parse(text) {
+        Err(TrackError::Parse { line, .. }) => line,
+        other => panic!("expected a parse error, got {other:?}"),
+    };
+    assert_eq!(error("# header\n0 0 0 0 0 0 0\n1 2 3"), 3);
+    assert_eq!(error("0 0 0 0 0 0 x"), 1);
+    assert_eq!(error("1 0 0 0 0 0 0\n0.5 0 0 0 0 0 0"), 2);
+    assert!(CameraTrack::parse("").unwrap().is_empty());
+}
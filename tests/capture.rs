@@ -0,0 +1,147 @@
+use engine::capture::{Capture, CaptureError};
+use engine::output::{ImageFormat, encode_ppm};
+#[cfg(feature = "png")]
+use engine::png;
+
+/// Read back the chunks of a PNG, checking the signature.
+#[cfg(feature = "png")]
+fn chunks(png: &[u8]) -> Vec<([u8; 4], Vec<u8>)> {
+    assert_eq!(&png[..8], b"\x89PNG\r\n\x1a\n");
+    let mut chunks = Vec::new();
+    let mut rest = &png[8..];
+    while !rest.is_empty() {
+        let len = u32::from_be_bytes(rest[..4].try_into().unwrap()) as usize;
+        let kind: [u8; 4] = rest[4..8].try_into().unwrap();
+        chunks.push((kind, rest[8..8 + len].to_vec()));
+        rest = &rest[12 + len..];
+    }
+    chunks
+}
+
+/// Undo the stored deflate blocks of a zlib stream.
+#[cfg(feature = "png")]
+fn inflate_stored(zlib: &[u8]) -> Vec<u8> {
+    let mut out = Vec::new();
+    let mut at = 2;
+    loop {
+        let last = zlib[at] & 1 == 1;
+        assert_eq!(zlib[at] >> 1, 0, "not a stored block");
+        let len = u16::from_le_bytes([zlib[at + 1], zlib[at + 2]]) as usize;
+        let nlen = u16::from_le_bytes([zlib[at + 3], zlib[at + 4]]);
+        assert_eq!(!nlen as usize, len);
+        out.extend_from_slice(&zlib[at + 5..at + 5 + len]);
+        at += 5 + len;
+        if last {
+            break;
+        }
+    }
+    assert_eq!(zlib.len(), at + 4);
+    out
+}
+
+fn gradient(width: u32, height: u32, seed: u8) -> Vec<u8> {
+    (0..width * height * 4)
+        .map(|i| (i as u8).wrapping_mul(31).wrapping_add(seed))
+        .collect()
+}
+
+#[cfg(feature = "png")]
+#[test]
+fn png_round_trips() {
+    // Tall enough to need several deflate blocks.
+    let (width, height) = (100, 300);
+    let frame = gradient(width, height, 0);
+    let png = png::encode(&frame, width, height);
+    // The well-known CRC of an empty IEND chunk.
+    assert_eq!(&png[png.len() - 4..], &[0xAE, 0x42, 0x60, 0x82]);
+    let chunks = chunks(&png);
+    let kinds: Vec<&[u8; 4]> = chunks.iter().map(|(kind, _)| kind).collect();
+    assert_eq!(kinds, [b"IHDR", b"IDAT", b"IEND"]);
+
+    let header = &chunks[0].1;
+    assert_eq!(&header[..4], &width.to_be_bytes());
+    assert_eq!(&header[4..8], &height.to_be_bytes());
+    assert_eq!(&header[8..], &[8, 6, 0, 0, 0]);
+
+    let raw = inflate_stored(&chunks[1].1);
+    let row = width as usize * 4;
+    assert_eq!(raw.len(), (row + 1) * height as usize);
+    for (y, line) in raw.chunks(row + 1).enumerate() {
+        assert_eq!(line[0], 0);
+        assert_eq!(&line[1..], &frame[y * row..(y + 1) * row]);
+    }
+}
+
+#[test]
+fn capture_keeps_the_newest_frames() {
+    let mut capture = Capture::new(2, 2, 3);
+    for seed in 0..5 {
+        capture.push(&gradient(2, 2, seed), 2, 2).unwrap();
+    }
+    assert_eq!(capture.len(), 3);
+    assert_eq!(capture.dropped(), 2);
+}
+
+#[test]
+fn capture_refuses_resized_frames() {
+    let mut capture = Capture::new(4, 4, 10);
+    capture.push(&gradient(4, 4, 0), 4, 4).unwrap();
+    assert_eq!(
+        capture.push(&gradient(4, 5, 0), 4, 5),
+        Err(CaptureError::SizeChanged {
+            expected: (4, 4),
+            found: (4, 5)
+        })
+    );
+    assert_eq!(capture.len(), 1);
+}
+
+#[cfg(feature = "png")]
+#[test]
+fn writes_every_nth_frame() {
+    let mut capture = Capture::new(3, 2, 10);
+    for seed in 0..7 {
+        capture.push(&gradient(3, 2, seed), 3, 2).unwrap();
+    }
+    let dir = std::env::temp_dir().join(format!("shapes-capture-{}", std::process::id()));
+    let written = capture
+        .save_in_background(dir.clone(), 3, ImageFormat::Png)
+        .join()
+        .unwrap();
+    let mut files: Vec<String> = std::fs::read_dir(&dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+        .collect();
+    files.sort();
+    let third = std::fs::read(dir.join("frame_00002.png")).unwrap();
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    assert_eq!(written.unwrap(), 3);
+    assert_eq!(
+        files,
+        ["frame_00000.png", "frame_00001.png", "frame_00002.png"]
+    );
+    let raw = inflate_stored(&chunks(&third)[1].1);
+    let frame = gradient(3, 2, 6);
+    assert_eq!(&raw[1..13], &frame[..12]);
+}
+
+#[test]
+fn sequences_take_the_format_they_are_given() {
+    let mut capture = Capture::new(2, 2, 2);
+    capture.push(&gradient(2, 2, 0), 2, 2).unwrap();
+    capture.push(&gradient(2, 2, 1), 2, 2).unwrap();
+    let dir = std::env::temp_dir().join(format!("shapes-capture-ppm-{}", std::process::id()));
+    let written = capture.write_sequence(&dir, 1, ImageFormat::Ppm);
+    let second = std::fs::read(dir.join("frame_00001.ppm"));
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    assert_eq!(written.unwrap(), 2);
+    assert_eq!(second.unwrap(), encode_ppm(&gradient(2, 2, 1), 2, 2));
+}
+
+#[cfg(not(feature = "png"))]
+#[test]
+fn captures_fall_back_to_bmp_without_png_support() {
+    assert_eq!(ImageFormat::default(), ImageFormat::Bmp);
+}
@@ -0,0 +1,175 @@
+mod common;
+
+use std::path::PathBuf;
+use std::time::Duration;
+
+use common::{HEIGHT, WIDTH, object, render, world};
+use engine::chunked::{ChunkCache, MAGIC};
+use engine::mesh::cube::CubeMesh;
+use engine::mesh::quad::QuadMesh;
+use engine::mesh::{Color, Mesh};
+use engine::{Camera, World};
+use nalgebra::{Matrix4, Point3};
+
+const GREEN: Color = Color::rgb(40, 160, 60);
+
+/// An 8 by 8 square of 3200 triangles facing the camera, bigger than the
+/// view at the default distance.
+fn scan() -> QuadMesh {
+    QuadMesh::grid(8.0, 8.0, 40, GREEN)
+}
+
+fn cache_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("shapes-{name}-{}.chunks", std::process::id()))
+}
+
+/// Stream until every read asked for has arrived and nothing more is
+/// asked for.
+fn settle(world: &mut World) {
+    for _ in 0..5000 {
+        let arrived = world.stream_chunks();
+        if arrived == 0 && world.chunks_pending() == 0 {
+            return;
+        }
+        std::thread::sleep(Duration::from_millis(1));
+    }
+    panic!("chunks never settled");
+}
+
+/// Indices of the chunk objects drawn as their bounds.
+fn placeholders(world: &World, chunks: &[usize]) -> Vec<usize> {
+    chunks
+        .iter()
+        .copied()
+        .filter(|&i| world.models[i].mesh.placeholder())
+        .collect()
+}
+
+#[test]
+fn caches_keep_every_triangle_in_small_chunks() {
+    let path = cache_path("round-trip");
+    let mesh = scan();
+    let built = ChunkCache::build(&mesh, 100, &path).unwrap();
+    let opened = ChunkCache::open(&path).unwrap();
+    assert_eq!(opened, built);
+    assert_eq!(opened.triangles(), 3200);
+    assert!(opened.chunks().len() >= 32);
+
+    for (index, info) in opened.chunks().iter().enumerate() {
+        assert!(info.triangles <= 100);
+        let chunk = opened.read_chunk(index).unwrap();
+        assert_eq!(chunk.tris().len(), info.triangles);
+        assert_eq!(chunk.verts().len(), info.vertices);
+        assert!(chunk.validate().is_ok());
+        assert!(chunk.tris().iter().all(|tri| tri.color == GREEN));
+        assert_eq!(chunk.aabb(), (info.min, info.max));
+    }
+    // Chunks are neighbourhoods: together no bigger than the whole.
+    let area: f32 = opened
+        .chunks()
+        .iter()
+        .map(|info| (info.max.x - info.min.x) * (info.max.y - info.min.y))
+        .sum();
+    assert!(area < 64.0 * 1.5, "{area}");
+    assert!(opened.read_chunk(opened.chunks().len()).is_err());
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn other_files_are_not_taken_for_caches() {
+    let path = cache_path("bad");
+    std::fs::write(&path, b"v 0 0 0\n").unwrap();
+    assert!(ChunkCache::open(&path).is_err());
+    // Cut off in the middle of the table.
+    let mut truncated = MAGIC.to_vec();
+    truncated.extend_from_slice(&3u32.to_le_bytes());
+    truncated.extend_from_slice(&[0; 20]);
+    std::fs::write(&path, truncated).unwrap();
+    assert!(ChunkCache::open(&path).is_err());
+    std::fs::remove_file(&path).unwrap();
+    assert!(ChunkCache::open(&path).is_err());
+}
+
+#[test]
+fn chunks_in_view_stream_in_and_the_rest_stay_bounds() {
+    let path = cache_path("view");
+    let cache = ChunkCache::build(&scan(), 100, &path).unwrap();
+    let total: usize = cache.chunks().iter().map(|info| info.bytes()).sum();
+    let mut world = world(Vec::new());
+    let chunks = world.load_chunked(cache, Matrix4::identity(), usize::MAX);
+    assert_eq!(placeholders(&world, &chunks).len(), chunks.len());
+
+    settle(&mut world);
+    let loaded = chunks.len() - placeholders(&world, &chunks).len();
+    assert!(loaded > 0 && loaded < chunks.len(), "{loaded}");
+    assert!(world.streamed_bytes() < total);
+    render(&mut world);
+    assert!(!world.stats().approximate);
+    let hit = world
+        .raycast(&world.screen_ray(32.0, 32.0, WIDTH, HEIGHT), false)
+        .unwrap();
+    assert!(chunks.contains(&hit.object) && !hit.approximate);
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn the_budget_holds_while_orbiting() {
+    let path = cache_path("orbit");
+    let cache = ChunkCache::build(&scan(), 100, &path).unwrap();
+    let largest = cache
+        .chunks()
+        .iter()
+        .map(|info| info.bytes())
+        .max()
+        .unwrap();
+    let budget = 6 * largest;
+    let mut world = world(Vec::new());
+    let chunks = world.load_chunked(cache, Matrix4::identity(), budget);
+
+    for step in 0..16 {
+        // Circling close over the square, looking down at it.
+        let angle = step as f32 * std::f32::consts::TAU / 16.0;
+        let focus = Point3::new(2.5 * angle.cos(), 2.5 * angle.sin(), 0.0);
+        world.camera = Camera::looking_at(focus + nalgebra::Vector3::new(0.0, 0.0, -1.5), focus);
+        settle(&mut world);
+        assert!(world.streamed_bytes() <= budget, "step {step}");
+        let resident = chunks.len() - placeholders(&world, &chunks).len();
+        assert!((1..=6).contains(&resident), "step {step}: {resident}");
+        // What is straight ahead is among what was read.
+        let hit = world
+            .raycast(&world.screen_ray(32.0, 32.0, WIDTH, HEIGHT), false)
+            .unwrap();
+        assert!(chunks.contains(&hit.object), "step {step}");
+    }
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn what_is_not_read_yet_is_flagged() {
+    let path = cache_path("approximate");
+    let cache = ChunkCache::build(&scan(), 100, &path).unwrap();
+    let mut world = world(vec![object(CubeMesh::new(GREEN), 0.0, 0.0, 4.0)]);
+    let chunks = world.load_chunked(cache, Matrix4::identity(), usize::MAX);
+
+    // The square is all bounds, so picking looks through it to the cube.
+    let hit = world
+        .raycast(&world.screen_ray(32.0, 32.0, WIDTH, HEIGHT), false)
+        .unwrap();
+    assert_eq!(hit.object, 0);
+    assert!(hit.approximate);
+    render(&mut world);
+    assert!(world.stats().approximate);
+
+    settle(&mut world);
+    let hit = world
+        .raycast(&world.screen_ray(32.0, 32.0, WIDTH, HEIGHT), false)
+        .unwrap();
+    assert!(chunks.contains(&hit.object));
+
+    // Removing a chunk's object gives its memory back.
+    let before = world.streamed_bytes();
+    world.remove_object(hit.object);
+    assert!(world.streamed_bytes() < before);
+    settle(&mut world);
+    std::fs::remove_file(&path).unwrap();
+}
@@ -0,0 +1,45 @@
+mod common;
+
+use common::{HEIGHT, WIDTH, pixel, render, world};
+use engine::mesh::Color;
+
+const BLUE: Color = Color {
+    r: 30,
+    g: 60,
+    b: 200,
+    a: 255,
+};
+const ORANGE: Color = Color {
+    r: 250,
+    g: 140,
+    b: 20,
+    a: 255,
+};
+
+fn rgba(c: Color) -> [u8; 4] {
+    [c.r, c.g, c.b, c.a]
+}
+
+#[test]
+fn solid_clear_writes_every_channel() {
+    let mut world = world(vec![]).with_clear_color(BLUE);
+    let frame = render(&mut world);
+    assert!(frame.chunks(4).all(|p| p == rgba(BLUE)));
+}
+
+#[test]
+fn gradient_runs_from_top_to_bottom() {
+    let mut world = world(vec![]).with_gradient(BLUE, ORANGE);
+    let frame = render(&mut world);
+    for x in [0, WIDTH / 2, WIDTH - 1] {
+        assert_eq!(pixel(&frame, x, 0), rgba(BLUE));
+        assert_eq!(pixel(&frame, x, HEIGHT - 1), rgba(ORANGE));
+    }
+    let middle = pixel(&frame, 0, HEIGHT / 2);
+    assert!(middle[0] > BLUE.r && middle[0] < ORANGE.r);
+    assert!(middle[2] < BLUE.b && middle[2] > ORANGE.b);
+    // Every scanline is a single color.
+    for y in 0..HEIGHT {
+        assert_eq!(pixel(&frame, 0, y), pixel(&frame, WIDTH - 1, y));
+    }
+}
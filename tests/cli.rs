@@ -0,0 +1,427 @@
+use std::path::PathBuf;
+
+use engine::cli::{
+    CliError, ClipPlanes, Command, ModelInfo, ReduceArgs, RenderArgs, SceneArgs, SimplifyArgs,
+    SmallTriangles, ViewArgs, parse,
+};
+use engine::depth::DepthConvention;
+use engine::mesh::cube::CubeMesh;
+use engine::mesh::obj::ObjMesh;
+use engine::mesh::{Color, ImportOptions};
+use engine::paint::Brush;
+use engine::texture::FilterMode;
+use nalgebra::Point3;
+
+fn args(line: &str) -> Vec<String> {
+    line.split_whitespace().map(String::from).collect()
+}
+
+fn render_args(line: &str) -> RenderArgs {
+    match parse(args(line)) {
+        Ok(Command::Render(render)) => render,
+        other => panic!("{line}: {other:?}"),
+    }
+}
+
+#[test]
+fn no_arguments_open_the_window() {
+    assert_eq!(parse(args("")), Ok(Command::View(ViewArgs::default())));
+}
+
+#[test]
+fn view_takes_models_size_and_scale() {
+    let Ok(Command::View(view)) = parse(args(
+        "view a.obj --width 320 b.obj --height 200 --scale 0.5 --watch --max-fps 60",
+    )) else {
+        panic!("not a view");
+    };
+    assert_eq!(
+        view.models,
+        [PathBuf::from("a.obj"), PathBuf::from("b.obj")]
+    );
+    assert_eq!((view.width, view.height, view.scale), (320, 200, Some(0.5)));
+    assert!(view.watch);
+    assert_eq!(view.max_fps, Some(60.0));
+    assert!(matches!(
+        parse(args("view --max-fps 0")),
+        Err(CliError::BadValue { .. })
+    ));
+}
+
+#[test]
+fn view_takes_input_bookmarks_and_scene_flags() {
+    let Ok(Command::View(view)) = parse(args(
+        "view --input azerty.txt --bookmarks shots.txt --bookmark-seconds 2.5 --eye-height 1.5 --export room.obj --clip 0.5,900 \
+         --reversed-depth",
+    )) else {
+        panic!("not a view");
+    };
+    assert_eq!(view.input, Some(PathBuf::from("azerty.txt")));
+    assert_eq!(view.bookmarks, PathBuf::from("shots.txt"));
+    assert_eq!(view.bookmark_seconds, 2.5);
+    assert_eq!(view.eye_height, Some(1.5));
+    assert_eq!(view.export, PathBuf::from("room.obj"));
+    assert_eq!(
+        view.scene,
+        SceneArgs {
+            clip: Some(ClipPlanes::Fixed(0.5, 900.0)),
+            depth_buffer: Some(DepthConvention::Reversed),
+            ..SceneArgs::default()
+        }
+    );
+    assert_eq!(
+        ViewArgs::default().bookmarks,
+        PathBuf::from("bookmarks.txt")
+    );
+    assert_eq!(ViewArgs::default().export, PathBuf::from("scene.obj"));
+}
+
+#[test]
+fn view_takes_track_budget_and_capture_flags() {
+    let Ok(Command::View(view)) = parse(args(
+        "view --camera-track fly.track --target-ms 8 --profile --capture-dir shots \
+         --capture-frames 120 --capture-skip 2",
+    )) else {
+        panic!("not a view");
+    };
+    assert_eq!(view.camera_track, PathBuf::from("fly.track"));
+    assert_eq!(view.target_ms, Some(8.0));
+    assert!(view.profile);
+    assert_eq!(view.capture_dir, PathBuf::from("shots"));
+    assert_eq!((view.capture_frames, view.capture_skip), (120, 2));
+
+    let defaults = ViewArgs::default();
+    assert_eq!(defaults.camera_track, PathBuf::from("camera.track"));
+    assert_eq!(defaults.capture_dir, PathBuf::from("capture"));
+    assert_eq!((defaults.capture_frames, defaults.capture_skip), (600, 1));
+    assert!(matches!(
+        parse(args("view --capture-skip 0")),
+        Err(CliError::BadValue { .. })
+    ));
+}
+
+#[test]
+fn view_takes_paint_flags() {
+    let Ok(Command::View(view)) = parse(args(
+        "view --paint-size 512 --brush-radius 2.5 --brush-hardness 0.9 \
+         --brush-color #204060 --paint-out skin.png",
+    )) else {
+        panic!("not a view");
+    };
+    assert_eq!(view.paint_size, 512);
+    assert_eq!(
+        view.brush,
+        Brush {
+            radius: 2.5,
+            hardness: 0.9,
+            color: Color::rgb(0x20, 0x40, 0x60),
+        }
+    );
+    assert_eq!(view.paint_out, PathBuf::from("skin.png"));
+
+    let defaults = ViewArgs::default();
+    assert_eq!(defaults.paint_size, 256);
+    assert_eq!(defaults.brush, Brush::default());
+    assert_eq!(defaults.paint_out, PathBuf::from("paint.png"));
+    for bad in ["--brush-radius 0", "--paint-size 0", "--brush-color blue"] {
+        assert!(
+            matches!(
+                parse(args(&format!("view {bad}"))),
+                Err(CliError::BadValue { .. })
+            ),
+            "{bad}"
+        );
+    }
+}
+
+#[test]
+fn view_takes_streamed_models_and_their_budget() {
+    let Ok(Command::View(view)) = parse(args(
+        "view a.obj --stream scan.obj --stream city.obj --stream-budget 64",
+    )) else {
+        panic!("not a view");
+    };
+    assert_eq!(view.models, vec![PathBuf::from("a.obj")]);
+    assert_eq!(
+        view.streamed,
+        vec![PathBuf::from("scan.obj"), PathBuf::from("city.obj")]
+    );
+    assert_eq!(view.stream_budget, 64);
+
+    let defaults = ViewArgs::default();
+    assert!(defaults.streamed.is_empty());
+    assert_eq!(defaults.stream_budget, 256);
+    for bad in ["--stream-budget 0", "--stream-budget lots"] {
+        assert!(
+            matches!(
+                parse(args(&format!("view {bad}"))),
+                Err(CliError::BadValue { .. })
+            ),
+            "{bad}"
+        );
+    }
+}
+
+#[test]
+fn a_scene_file_is_told_apart_from_models() {
+    let Ok(Command::View(view)) = parse(args("view a.obj room.scene b.obj")) else {
+        panic!("not a view");
+    };
+    assert_eq!(view.scene_file, Some(PathBuf::from("room.scene")));
+    assert_eq!(
+        view.models,
+        [PathBuf::from("a.obj"), PathBuf::from("b.obj")]
+    );
+    assert!(!view.keep_camera);
+    let Ok(Command::View(view)) = parse(args("view room.scene --watch --keep-camera")) else {
+        panic!("not a view");
+    };
+    assert!(view.watch && view.keep_camera);
+    let render = render_args("render room.scene -o out.png");
+    assert_eq!(render.scene_file, Some(PathBuf::from("room.scene")));
+    assert!(render.models.is_empty());
+    assert!(matches!(
+        parse(args("render a.scene b.scene -o out.png")),
+        Err(CliError::Unexpected(arg)) if arg == "b.scene"
+    ));
+}
+
+#[test]
+fn render_takes_scene_flags() {
+    let render = render_args("render -o out.png --clip auto --splitting-sort --simplify 5000");
+    assert_eq!(
+        render.scene,
+        SceneArgs {
+            clip: Some(ClipPlanes::Auto),
+            splitting_sort: true,
+            simplify: Some(5000),
+            ..SceneArgs::default()
+        }
+    );
+    assert_eq!(
+        render_args("render -o out.png --instances 30 --lod-spheres 8 --billboards 500").scene,
+        SceneArgs {
+            instances: Some(30),
+            lod_spheres: Some(8),
+            billboards: Some(500),
+            ..SceneArgs::default()
+        }
+    );
+    assert_eq!(
+        render_args("render -o out.png --checker-floor bilinear")
+            .scene
+            .checker_floor,
+        Some(FilterMode::Bilinear)
+    );
+    assert!(render_args("render -o out.png --monitor").scene.monitor);
+    assert!(matches!(
+        parse(args("render -o out.png --checker-floor trilinear")),
+        Err(CliError::BadValue { .. })
+    ));
+    assert_eq!(
+        render_args("render -o out.png --depth-buffer")
+            .scene
+            .depth_buffer,
+        Some(DepthConvention::Standard)
+    );
+    assert!(
+        render_args("render -o out.png --back-to-front")
+            .scene
+            .back_to_front
+    );
+    assert!(
+        render_args("render -o out.png --planar-shadows")
+            .scene
+            .planar_shadows
+    );
+    assert_eq!(
+        render_args("render -o out.png --small-triangles 0.5")
+            .scene
+            .small_triangles,
+        Some(SmallTriangles::Cull(0.5))
+    );
+    assert_eq!(
+        render_args("render -o out.png --small-triangles points")
+            .scene
+            .small_triangles,
+        Some(SmallTriangles::Points)
+    );
+    assert!(render_args("render -o out.png --particles").scene.particles);
+    assert!(render_args("render -o out.png --sky").scene.sky);
+    assert!(render_args("render -o out.png --auto-lod").scene.auto_lod);
+    assert!(
+        render_args("render -o out.png --colorblind-ids")
+            .scene
+            .colorblind_ids
+    );
+    for size in ["0", "-1", "dots"] {
+        assert!(
+            matches!(
+                parse(args(&format!("render -o out.png --small-triangles {size}"))),
+                Err(CliError::BadValue { .. })
+            ),
+            "{size}"
+        );
+    }
+    for clip in ["0.5", "0,10", "10,1", "near,far", "1,inf"] {
+        assert!(
+            matches!(
+                parse(args(&format!("render -o out.png --clip {clip}"))),
+                Err(CliError::BadValue { .. })
+            ),
+            "{clip}"
+        );
+    }
+    assert!(matches!(
+        parse(args("render -o out.png --input keys.txt")),
+        Err(CliError::UnknownFlag { .. })
+    ));
+}
+
+#[test]
+fn render_needs_an_output() {
+    let render = render_args("render ship.obj -o out.png --frames 3 --camera-track fly.track");
+    assert_eq!(render.models, [PathBuf::from("ship.obj")]);
+    assert_eq!(render.output, PathBuf::from("out.png"));
+    assert_eq!(render.frames, 3);
+    assert_eq!(render.camera_track, Some(PathBuf::from("fly.track")));
+    assert_eq!(
+        parse(args("render ship.obj")),
+        Err(CliError::Missing("the output image, -o out.png"))
+    );
+}
+
+#[test]
+fn simplify_needs_a_target_and_an_output() {
+    assert_eq!(
+        parse(args("simplify in.obj --target 10000 -o out.obj")),
+        Ok(Command::Simplify(SimplifyArgs {
+            model: PathBuf::from("in.obj"),
+            target: 10000,
+            output: PathBuf::from("out.obj"),
+        }))
+    );
+    assert_eq!(
+        parse(args("simplify in.obj -o out.obj")),
+        Err(CliError::Missing("the triangle count, --target N"))
+    );
+    assert_eq!(
+        parse(args("simplify in.obj --target 500")),
+        Err(CliError::Missing("the output model, -o out.obj"))
+    );
+    assert_eq!(
+        parse(args("simplify --target 500 -o out.obj")),
+        Err(CliError::Missing("the model to simplify"))
+    );
+    assert!(matches!(
+        parse(args("simplify in.obj --target many -o out.obj")),
+        Err(CliError::BadValue { .. })
+    ));
+}
+
+#[test]
+fn reduce_needs_a_scene_and_a_check() {
+    assert_eq!(
+        parse(args("reduce bugs/repro.scene --check-panic")),
+        Ok(Command::Reduce(ReduceArgs {
+            scene_file: PathBuf::from("bugs/repro.scene"),
+            output: PathBuf::from("bugs/repro.reduced.scene"),
+            width: 500,
+            height: 500,
+            check_panic: true,
+            scene: SceneArgs::default(),
+        }))
+    );
+    let Ok(Command::Reduce(reduce)) = parse(args(
+        "reduce repro.scene --check-panic -o small.scene --width 64 --sky",
+    )) else {
+        panic!("not a reduce");
+    };
+    assert_eq!(reduce.output, PathBuf::from("small.scene"));
+    assert_eq!(reduce.width, 64);
+    assert!(reduce.scene.sky);
+
+    assert_eq!(
+        parse(args("reduce repro.scene")),
+        Err(CliError::Missing(
+            "the check to keep failing, --check-panic"
+        ))
+    );
+    assert_eq!(
+        parse(args("reduce --check-panic")),
+        Err(CliError::Missing("the scene file to reduce"))
+    );
+    assert_eq!(
+        parse(args("reduce a.scene b.scene --check-panic")),
+        Err(CliError::Unexpected("b.scene".to_string()))
+    );
+}
+
+#[test]
+fn frames_are_numbered_only_when_there_are_several() {
+    let one = render_args("render -o shots/out.png");
+    assert_eq!(one.frame_path(0), PathBuf::from("shots/out.png"));
+    let many = render_args("render -o shots/out.png --frames 12");
+    assert_eq!(many.frame_path(7), PathBuf::from("shots/out-0007.png"));
+}
+
+#[test]
+fn bad_command_lines_say_what_is_wrong() {
+    assert_eq!(
+        parse(args("paint")),
+        Err(CliError::UnknownCommand("paint".into()))
+    );
+    assert_eq!(
+        parse(args("view --fullscreen")),
+        Err(CliError::UnknownFlag {
+            command: "view",
+            flag: "--fullscreen".into()
+        })
+    );
+    assert_eq!(
+        parse(args("render -o out.png --width")),
+        Err(CliError::MissingValue("--width".into()))
+    );
+    for bad in [
+        "render -o x.png --frames 0",
+        "view --scale -1",
+        "view --width wide",
+    ] {
+        assert!(
+            matches!(parse(args(bad)), Err(CliError::BadValue { .. })),
+            "{bad}"
+        );
+    }
+    assert_eq!(
+        parse(args("info")),
+        Err(CliError::Missing("the model to describe"))
+    );
+    assert_eq!(
+        parse(args("info a.obj b.obj")),
+        Err(CliError::Unexpected("b.obj".into()))
+    );
+    assert_eq!(parse(args("--help")), Ok(Command::Help));
+}
+
+#[test]
+fn model_info_counts_and_warns() {
+    let cube = ModelInfo::of(&CubeMesh::new(Color::WHITE));
+    assert_eq!((cube.vertices, cube.triangles), (8, 12));
+    assert_eq!(
+        cube.aabb,
+        (Point3::new(-1.0, -1.0, -1.0), Point3::new(1.0, 1.0, 1.0))
+    );
+    assert!(cube.warnings().is_empty());
+
+    let obj = "v 0 0 0\nv 1 0 0\nv 0 1 0\nv 5 5 5\nf 1 2 3\nf 1 1 2\n";
+    let info =
+        ModelInfo::of(&ObjMesh::from_bytes(obj.as_bytes(), ImportOptions::default()).unwrap());
+    assert_eq!((info.degenerate, info.unused_vertices), (1, 1));
+    assert_eq!(info.warnings().len(), 2);
+    assert!(info.to_string().contains("triangles: 2"));
+}
+
+#[test]
+fn model_info_reports_missing_files() {
+    assert!(ModelInfo::load("/nonexistent/shapes/model.obj").is_err());
+}
@@ -0,0 +1,104 @@
+mod common;
+
+use common::{HEIGHT, WHITE, WIDTH, object, pixel, render, world};
+use engine::ClipPlaneError;
+use engine::mesh::Color;
+use engine::mesh::quad::QuadMesh;
+use engine::{Object, World};
+
+const RED: Color = Color::rgb(220, 30, 30);
+
+/// A red square `size` across facing the camera, `distance` in front of it.
+fn square_at(distance: f32, size: f32) -> Object {
+    object(QuadMesh::new(size, size, RED), 0.0, 0.0, distance - 5.0)
+}
+
+fn middle_drawn(world: &mut World) -> bool {
+    pixel(&render(world), WIDTH / 2, HEIGHT / 2) != WHITE
+}
+
+#[test]
+fn invalid_planes_are_refused() {
+    let mut world = world(vec![]);
+    let before = world.proj_mat;
+    assert_eq!(
+        world.set_clip_planes(0.0, 10.0),
+        Err(ClipPlaneError::NearNotPositive(0.0))
+    );
+    assert!(world.set_clip_planes(f32::NAN, 10.0).is_err());
+    assert_eq!(
+        world.set_clip_planes(2.0, 2.0),
+        Err(ClipPlaneError::FarNotBeyondNear {
+            near: 2.0,
+            far: 2.0
+        })
+    );
+    let err = world.set_clip_planes(1.0, f32::INFINITY).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "far plane inf must be finite and beyond near plane 1"
+    );
+    assert_eq!(world.proj_mat, before);
+}
+
+#[test]
+fn new_planes_keep_the_field_of_view() {
+    let mut world = world(vec![]);
+    let before = world.proj_mat;
+    world.set_clip_planes(0.5, 40.0).unwrap();
+    let (near, far) = world.clip_planes();
+    assert!((near - 0.5).abs() < 1e-4);
+    assert!((far - 40.0).abs() < 1e-2);
+    assert_eq!(world.proj_mat[(0, 0)], before[(0, 0)]);
+    assert_eq!(world.proj_mat[(1, 1)], before[(1, 1)]);
+}
+
+#[test]
+fn wide_ranges_draw_near_and_far() {
+    let mut world = world(vec![square_at(5000.0, 4000.0)]);
+    assert!(!middle_drawn(&mut world), "past the default far plane");
+    world.set_clip_planes(0.01, 10000.0).unwrap();
+    assert!(middle_drawn(&mut world));
+
+    let mut world = world_with(square_at(0.1, 0.1));
+    world.set_clip_planes(0.01, 10000.0).unwrap();
+    assert!(middle_drawn(&mut world));
+}
+
+#[test]
+fn narrow_ranges_draw_only_between_the_planes() {
+    for (distance, drawn) in [(0.5, false), (1.6, true), (1.95, true), (2.5, false)] {
+        let mut world = world_with(square_at(distance, 2.0));
+        world.set_clip_planes(1.0, 2.0).unwrap();
+        assert_eq!(middle_drawn(&mut world), drawn, "at {distance}");
+    }
+}
+
+#[test]
+fn auto_planes_fit_the_scene() {
+    let mut world = world_with(square_at(500.0, 400.0));
+    world.auto_clip_planes = true;
+    assert!(middle_drawn(&mut world));
+    let (near, far) = world.clip_planes();
+    assert!((500.0..520.0).contains(&far), "{far}");
+    assert!(near > 0.1 && near < 500.0, "{near}");
+
+    // Moving things refits the planes.
+    world.models[0].offset_z = -4.0;
+    assert!(middle_drawn(&mut world));
+    let (near, far) = world.clip_planes();
+    assert!(far < 2.0 && near < 1.0, "{near} {far}");
+}
+
+#[test]
+fn auto_planes_leave_empty_scenes_alone() {
+    let mut world = world(vec![]);
+    let before = world.clip_planes();
+    world.auto_clip_planes = true;
+    render(&mut world);
+    assert_eq!(world.clip_planes(), before);
+}
+
+fn world_with(object: Object) -> World {
+    world(vec![object])
+}
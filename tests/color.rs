@@ -0,0 +1,22 @@
+use engine::color::{linear_to_srgb, srgb_to_linear};
+
+#[test]
+fn white_and_black_survive_the_round_trip() {
+    assert_eq!(srgb_to_linear(0), 0.0);
+    assert_eq!(srgb_to_linear(255), 1.0);
+    assert_eq!(linear_to_srgb(srgb_to_linear(0)), 0);
+    assert_eq!(linear_to_srgb(srgb_to_linear(255)), 255);
+}
+
+#[test]
+fn encoding_clamps_out_of_range_light() {
+    assert_eq!(linear_to_srgb(-1.0), 0);
+    assert_eq!(linear_to_srgb(1.7), 255);
+}
+
+#[test]
+fn mid_gray_is_brighter_than_linear() {
+    // Half the light is well above half the code values in sRGB.
+    assert!(linear_to_srgb(0.5) > 180);
+    assert!(srgb_to_linear(128) < 0.25);
+}
@@ -0,0 +1,46 @@
+#![allow(dead_code)]
+
+use engine::mesh::Mesh;
+use engine::{Camera, Light, LightKind, Object, World};
+use nalgebra::{Perspective3, Point3};
+
+pub const WIDTH: u32 = 64;
+pub const HEIGHT: u32 = 64;
+pub const WHITE: [u8; 4] = [255, 255, 255, 255];
+
+/// The demo camera and light looking at `models` from z = -5, rasterizing
+/// in fixed point so expected pixels hold on every machine.
+pub fn world(models: Vec<Object>) -> World {
+    let mut world = World::new(
+        Camera::looking_at(Point3::new(0.0, 0.0, -5.0), Point3::new(0.0, 0.0, 0.0)),
+        Light {
+            position: Point3::new(-1.0, 1.0, -1.0),
+            target: Point3::new(0.0, 0.0, 0.0),
+            kind: LightKind::Directional,
+            intensity: 1.0,
+            shadow_resolution: 0,
+            shadow_bias: 0.05,
+        },
+        Perspective3::new((WIDTH as f32) / (HEIGHT as f32), 1.0, 0.1, 200.0).to_homogeneous(),
+        models,
+    );
+    world.deterministic = true;
+    world
+}
+
+pub fn object(mesh: impl Mesh + 'static, x: f32, y: f32, z: f32) -> Object {
+    Object::new(mesh, x, y, z)
+}
+
+/// Render `world` from its own camera into a fresh `WIDTH * HEIGHT` frame.
+pub fn render(world: &mut World) -> Vec<u8> {
+    let mut frame = vec![0; (WIDTH * HEIGHT * 4) as usize];
+    let view = world.camera.generate_view_mat();
+    world.draw(view, &mut frame, WIDTH, HEIGHT);
+    frame
+}
+
+pub fn pixel(frame: &[u8], x: u32, y: u32) -> [u8; 4] {
+    let i = ((y * WIDTH + x) * 4) as usize;
+    [frame[i], frame[i + 1], frame[i + 2], frame[i + 3]]
+}
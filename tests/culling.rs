@@ -0,0 +1,78 @@
+mod common;
+
+use common::{HEIGHT, WHITE, WIDTH, object, pixel, render, world};
+
+use engine::CullMode;
+use engine::mesh::obj::ObjMesh;
+use engine::mesh::quad::QuadMesh;
+use engine::mesh::{Color, ImportOptions, Mesh, MeshMut};
+use nalgebra::{UnitQuaternion, Vector3};
+
+const CUBE_OBJ: &[u8] = b"\
+v -1 -1 -1
+v 1 -1 -1
+v 1 1 -1
+v -1 1 -1
+v -1 -1 1
+v 1 -1 1
+v 1 1 1
+v -1 1 1
+f 1 4 3 2
+f 5 6 7 8
+f 1 2 6 5
+f 4 8 7 3
+f 1 5 8 4
+f 2 3 7 6
+";
+
+const GRAY: Color = Color {
+    r: 180,
+    g: 180,
+    b: 180,
+    a: 255,
+};
+
+fn cube() -> ObjMesh {
+    ObjMesh::from_bytes(CUBE_OBJ, ImportOptions::default()).unwrap()
+}
+
+#[test]
+fn flipped_mesh_renders_the_same_with_front_culling() {
+    let mut original = world(vec![object(cube(), 0.3, 0.2, 0.0)]);
+    let expected = render(&mut original);
+
+    let mut flipped = cube();
+    flipped.flip_winding();
+    let mut world = world(vec![object(flipped, 0.3, 0.2, 0.0)]);
+    world.cull_mode = CullMode::Front;
+    assert!(render(&mut world) == expected);
+
+    // Culling the usual way now shows the inside of the cube instead.
+    world.cull_mode = CullMode::Back;
+    assert!(render(&mut world) != expected);
+}
+
+#[test]
+fn flipping_twice_restores_the_mesh() {
+    let mut mesh = cube();
+    mesh.flip_winding();
+    mesh.flip_winding();
+    let (a, b) = (mesh.tris()[0], cube().tris()[0]);
+    assert_eq!((a.v1, a.v2, a.v3), (b.v1, b.v2, b.v3));
+}
+
+#[test]
+fn no_culling_lights_both_sides_of_a_quad() {
+    // Seen from behind, the quad is a back face.
+    let mut quad = object(QuadMesh::new(2.0, 2.0, GRAY), 0.0, 0.0, 0.0);
+    quad.rotation = UnitQuaternion::from_axis_angle(&Vector3::y_axis(), 3.0);
+    let mut world = world(vec![quad]);
+    assert_eq!(pixel(&render(&mut world), WIDTH / 2, HEIGHT / 2), WHITE);
+
+    world.cull_mode = CullMode::None;
+    let back = pixel(&render(&mut world), WIDTH / 2, HEIGHT / 2);
+    assert_ne!(back, WHITE);
+    // The light is on the camera's side, so the visible side is lit past
+    // ambient.
+    assert!(back[0] > (180.0 * 0.3) as u8 + 20);
+}
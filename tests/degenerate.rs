@@ -0,0 +1,106 @@
+mod common;
+
+use common::{WHITE, render, world};
+
+use engine::mesh::{Color, Mesh, Triangle};
+use engine::{CullMode, Object, World};
+use nalgebra::Point3;
+
+const RED: Color = Color::rgb(220, 30, 30);
+
+/// Loose triangles, each with its own three vertices.
+struct Soup {
+    verts: Vec<Point3<f32>>,
+    tris: Vec<Triangle>,
+}
+
+impl Mesh for Soup {
+    fn verts(&self) -> &[Point3<f32>] {
+        &self.verts
+    }
+
+    fn tris(&self) -> &Vec<Triangle> {
+        &self.tris
+    }
+}
+
+fn soup(corners: &[[(f32, f32); 3]]) -> Soup {
+    let verts = corners
+        .iter()
+        .flatten()
+        .map(|&(x, y)| Point3::new(x, y, 0.0))
+        .collect();
+    let tris = (0..corners.len())
+        .map(|i| Triangle {
+            v1: 3 * i,
+            v2: 3 * i + 1,
+            v3: 3 * i + 2,
+            color: RED,
+        })
+        .collect();
+    Soup { verts, tris }
+}
+
+const GOOD: [(f32, f32); 3] = [(-1.0, -1.0), (1.0, -1.0), (0.0, 1.0)];
+const COLLINEAR: [(f32, f32); 3] = [(-1.0, -1.0), (0.0, 0.0), (1.0, 1.0)];
+const COINCIDENT: [(f32, f32); 3] = [(0.5, 0.5), (0.5, 0.5), (0.5, 0.5)];
+const REPEATED_CORNER: [(f32, f32); 3] = [(0.0, 0.0), (0.0, 0.0), (1.0, 0.3)];
+/// About a tenth of a pixel tall.
+const SLIVER: [(f32, f32); 3] = [(-1.0, 0.0), (1.0, 0.0), (0.0, 0.01)];
+
+fn world_of(corners: &[[(f32, f32); 3]]) -> World {
+    let mut world = world(vec![Object::new(soup(corners), 0.0, 0.0, 0.0)]);
+    world.cull_mode = CullMode::None;
+    world
+}
+
+#[test]
+fn zero_area_triangles_are_skipped() {
+    let mut world = world_of(&[COLLINEAR, COINCIDENT, REPEATED_CORNER]);
+    let frame = render(&mut world);
+    assert!(frame.chunks(4).all(|p| p == WHITE));
+    let stats = world.stats();
+    assert_eq!(stats.triangles_degenerate, 3);
+    assert_eq!(stats.triangles_rasterized, 0);
+    assert_eq!(stats.pixels_written, 0);
+}
+
+#[test]
+fn degenerate_triangles_leave_the_rest_untouched() {
+    let clean = render(&mut world_of(&[GOOD]));
+    for degenerate in [COLLINEAR, COINCIDENT, REPEATED_CORNER] {
+        let mut world = world_of(&[degenerate, GOOD, degenerate]);
+        assert!(render(&mut world) == clean);
+        assert_eq!(world.stats().triangles_rasterized, 1);
+    }
+}
+
+#[test]
+fn degenerate_triangles_stay_skipped_in_every_mode() {
+    let mut world = world_of(&[COLLINEAR, COINCIDENT]);
+    world.show_normals = true;
+    world.selected = Some(0);
+    world.set_ssaa(2).unwrap();
+    let frame = render(&mut world);
+    assert!(frame.chunks(4).all(|p| p == WHITE));
+    assert_eq!(world.stats().triangles_degenerate, 2);
+}
+
+#[test]
+fn slivers_are_skipped_only_when_asked() {
+    let mut world = world_of(&[SLIVER]);
+    render(&mut world);
+    assert_eq!(world.stats().triangles_rasterized, 1);
+    assert_eq!(world.stats().triangles_degenerate, 0);
+
+    world.skip_slivers = true;
+    render(&mut world);
+    assert_eq!(world.stats().triangles_rasterized, 0);
+    assert_eq!(world.stats().triangles_degenerate, 1);
+
+    // Ordinary triangles are no slivers.
+    let mut world = world_of(&[GOOD]);
+    world.skip_slivers = true;
+    render(&mut world);
+    assert_eq!(world.stats().triangles_rasterized, 1);
+}
@@ -0,0 +1,97 @@
+mod common;
+
+use std::f32::consts::FRAC_PI_2;
+
+use common::{HEIGHT, WIDTH, object, pixel, render, world};
+use engine::depth::DepthConvention;
+use engine::mesh::Color;
+use engine::mesh::quad::QuadMesh;
+use engine::{Camera, Object, World};
+use nalgebra::{Point3, UnitQuaternion, Vector3};
+
+/// A green ground and a red road strip lying exactly on it, the road first
+/// so it is drawn before the ground unless something says otherwise.
+fn road_on_ground(bias: f32) -> World {
+    let flat = UnitQuaternion::from_axis_angle(&Vector3::x_axis(), FRAC_PI_2);
+    let road = Object {
+        rotation: flat,
+        depth_bias: bias,
+        ..object(
+            QuadMesh::new(1.0, 8.0, Color::rgb(200, 60, 60)),
+            0.0,
+            -1.0,
+            0.0,
+        )
+    };
+    let ground = Object {
+        rotation: flat,
+        ..object(
+            QuadMesh::new(8.0, 8.0, Color::rgb(60, 160, 60)),
+            0.0,
+            -1.0,
+            0.0,
+        )
+    };
+    world(vec![road, ground]).with_clear_color(Color::BLACK)
+}
+
+/// Of the drawn pixels down the middle of the frame, where the road runs
+/// away from the camera, how many show the road rather than the ground.
+fn road_pixels(frame: &[u8]) -> (usize, usize) {
+    let drawn: Vec<[u8; 4]> = (0..HEIGHT)
+        .map(|y| pixel(frame, WIDTH / 2, y))
+        .filter(|color| color[..3] != [0, 0, 0])
+        .collect();
+    let road = drawn.iter().filter(|color| color[0] > color[1]).count();
+    (road, drawn.len())
+}
+
+fn cameras() -> impl Iterator<Item = Camera> {
+    [(3.0, -6.0), (1.0, -7.0), (6.0, -2.0), (0.5, -4.0)]
+        .into_iter()
+        .map(|(y, z)| Camera::looking_at(Point3::new(0.0, y, z), Point3::new(0.0, -1.0, 0.0)))
+}
+
+#[test]
+fn negative_bias_keeps_a_decal_on_top() {
+    for convention in [DepthConvention::Standard, DepthConvention::Reversed] {
+        let mut world = road_on_ground(-1.0);
+        world.depth_buffer = true;
+        world.set_depth_convention(convention);
+        for camera in cameras() {
+            world.camera = camera;
+            let (road, drawn) = road_pixels(&render(&mut world));
+            assert!(drawn > 10);
+            assert_eq!(road, drawn, "{convention:?} from {}", world.camera.position);
+        }
+    }
+}
+
+#[test]
+fn positive_bias_sinks_it() {
+    let mut world = road_on_ground(1.0);
+    world.depth_buffer = true;
+    for camera in cameras() {
+        world.camera = camera;
+        let (road, drawn) = road_pixels(&render(&mut world));
+        assert!(drawn > 10);
+        assert_eq!(road, 0, "from {}", world.camera.position);
+    }
+}
+
+#[test]
+fn bias_breaks_painter_ties() {
+    // Without a depth buffer the ground, at the same depth and listed
+    // after the road, is painted over it.
+    let mut world = road_on_ground(0.0);
+    let (road, _) = road_pixels(&render(&mut world));
+    assert_eq!(road, 0);
+
+    let mut world = road_on_ground(-1.0);
+    for camera in cameras() {
+        world.camera = camera;
+        let (road, drawn) = road_pixels(&render(&mut world));
+        assert!(drawn > 10);
+        assert_eq!(road, drawn, "from {}", world.camera.position);
+    }
+}
@@ -0,0 +1,128 @@
+mod common;
+
+use std::f32::consts::FRAC_PI_4;
+
+use common::{HEIGHT, WIDTH, object, pixel, render, world};
+use engine::depth::DepthConvention;
+use engine::impostor::{Sphere, SphereImpostor};
+use engine::mesh::Color;
+use engine::mesh::quad::QuadMesh;
+use engine::pipeline::project_vertex;
+use engine::{Billboard, Object, World};
+use nalgebra::{Point3, UnitQuaternion, Vector3};
+
+const RED: Color = Color::rgb(220, 30, 30);
+const BLUE: Color = Color::rgb(30, 30, 220);
+
+fn is_red(frame: &[u8], x: u32, y: u32) -> bool {
+    let p = pixel(frame, x, y);
+    p[0] > p[2]
+}
+
+/// A red square facing the camera at the origin, cut through by a blue one
+/// turned 45 degrees about y, in front of it on one side and behind it on
+/// the other.
+fn crossed_squares() -> World {
+    let turned = Object {
+        rotation: UnitQuaternion::from_axis_angle(&Vector3::y_axis(), FRAC_PI_4),
+        ..object(QuadMesh::new(2.0, 2.0, BLUE), 0.0, 0.0, 0.0)
+    };
+    let mut world = world(vec![
+        object(QuadMesh::new(2.0, 2.0, RED), 0.0, 0.0, 0.0),
+        turned,
+    ]);
+    world.cull_mode = engine::CullMode::None;
+    world
+}
+
+/// How many pixels of the frame show the red square of two nearly
+/// touching ones 150 units away, the blue one 0.01 behind it. Both lean
+/// back a little, like a distant floor, so their depth varies across
+/// the screen.
+fn red_pixels_far_away(convention: DepthConvention) -> usize {
+    let lean = UnitQuaternion::from_axis_angle(&Vector3::x_axis(), 0.1);
+    let square = |color, z| Object {
+        rotation: lean,
+        ..object(QuadMesh::new(200.0, 200.0, color), 0.0, 0.0, z)
+    };
+    let mut world = world(vec![square(RED, 145.0), square(BLUE, 145.01)]);
+    world.cull_mode = engine::CullMode::None;
+    world.depth_buffer = true;
+    // Back to front, so depths rounded to the same show the far square.
+    world.front_to_back = false;
+    world.set_depth_convention(convention);
+    let frame = render(&mut world);
+    (0..HEIGHT)
+        .flat_map(|y| (0..WIDTH).map(move |x| (x, y)))
+        .filter(|&(x, y)| is_red(&frame, x, y))
+        .count()
+}
+
+#[test]
+fn reversed_projections_keep_the_planes() {
+    let mut world = world(vec![]);
+    world.set_clip_planes(0.5, 40.0).unwrap();
+    let before = world.proj_mat;
+    assert_eq!(world.depth_convention(), DepthConvention::Standard);
+    world.set_depth_convention(DepthConvention::Reversed);
+    let (near, far) = world.clip_planes();
+    assert!((near - 0.5).abs() < 1e-4 && (far - 40.0).abs() < 1e-2);
+    assert_eq!(world.proj_mat[(0, 0)], before[(0, 0)]);
+    assert_eq!(world.proj_mat[(1, 1)], before[(1, 1)]);
+
+    // The near plane lands on 1 and the far one on 0.
+    let at =
+        |distance: f32| project_vertex(Point3::new(0.0, 0.0, -distance), &world.proj_mat, 2, 2);
+    assert!((at(0.5).ndc_z - 1.0).abs() < 1e-5);
+    assert!(at(40.0).ndc_z.abs() < 1e-5);
+    assert!(at(0.4).clipped && at(41.0).clipped && !at(0.6).clipped);
+
+    world.set_depth_convention(DepthConvention::Standard);
+    let (near, far) = world.clip_planes();
+    assert!((near - 0.5).abs() < 1e-4 && (far - 40.0).abs() < 1e-2);
+}
+
+#[test]
+fn surfaces_that_cross_are_split_by_the_depth_buffer() {
+    let mut world = crossed_squares();
+    let (left, right) = (WIDTH / 2 - 8, WIDTH / 2 + 8);
+    for convention in [DepthConvention::Standard, DepthConvention::Reversed] {
+        world.depth_buffer = true;
+        world.set_depth_convention(convention);
+        let frame = render(&mut world);
+        for y in [HEIGHT / 2 - 8, HEIGHT / 2, HEIGHT / 2 + 8] {
+            assert!(
+                is_red(&frame, left, y) != is_red(&frame, right, y),
+                "{convention:?}"
+            );
+            assert_eq!(is_red(&frame, left, y), is_red(&frame, left, HEIGHT / 2));
+        }
+    }
+}
+
+#[test]
+fn reversed_depth_keeps_distant_surfaces_apart() {
+    let all = (WIDTH * HEIGHT) as usize;
+    assert_eq!(red_pixels_far_away(DepthConvention::Reversed), all);
+    // Standard depth rounds the two to the same values, or past each
+    // other, in places, which then show the square behind.
+    assert!(red_pixels_far_away(DepthConvention::Standard) < all);
+}
+
+#[test]
+fn sprites_are_depth_tested_too() {
+    // The sphere's centre is behind the billboard, so it is drawn first,
+    // but its near side is in front.
+    let mut world = world(vec![]);
+    world
+        .billboards
+        .push(Billboard::new(Point3::origin(), 0.5, BLUE));
+    world.impostors.push(SphereImpostor::new(vec![Sphere {
+        center: Point3::new(0.0, 0.0, 3.0),
+        radius: 3.5,
+        color: RED,
+    }]));
+    assert!(!is_red(&render(&mut world), WIDTH / 2, HEIGHT / 2));
+    world.depth_buffer = true;
+    assert!(is_red(&render(&mut world), WIDTH / 2, HEIGHT / 2));
+}
@@ -0,0 +1,78 @@
+mod common;
+
+use common::{HEIGHT, WIDTH, object, pixel, render, world};
+
+use engine::RenderMode;
+use engine::impostor::{Sphere, SphereImpostor};
+use engine::mesh::Color;
+use engine::mesh::quad::QuadMesh;
+use nalgebra::{Perspective3, Point3};
+
+const RED: Color = Color {
+    r: 255,
+    g: 0,
+    b: 0,
+    a: 255,
+};
+
+#[test]
+fn clip_planes_come_from_the_projection() {
+    let mut world = world(vec![]);
+    world.proj_mat = Perspective3::new(1.0, 1.0, 0.5, 40.0).to_homogeneous();
+    let (near, far) = world.clip_planes();
+    assert!((near - 0.5).abs() < 1e-4);
+    assert!((far - 40.0).abs() < 1e-2);
+}
+
+#[test]
+fn nearer_surfaces_are_brighter_over_black() {
+    let mut world = world(vec![
+        object(QuadMesh::new(2.0, 2.0, RED), 1.5, 0.0, 0.0),
+        object(QuadMesh::new(2.0, 2.0, RED), -3.0, 0.0, 5.0),
+    ]);
+    world.proj_mat = Perspective3::new(1.0, 1.0, 1.0, 20.0).to_homogeneous();
+    world.render_mode = RenderMode::Depth;
+    let frame = render(&mut world);
+
+    assert_eq!(pixel(&frame, 0, 0), [0, 0, 0, 255]);
+    // +x is on the screen's left.
+    let near = pixel(&frame, WIDTH / 4, HEIGHT / 2);
+    let far = pixel(&frame, 3 * WIDTH / 4, HEIGHT / 2);
+    assert_eq!(near[0], near[1]);
+    assert_eq!(near[1], near[2]);
+    assert!(near[0] > far[0] && far[0] > 0);
+    // Five units of a 19 unit range apart.
+    let expected = (255.0 * 5.0 / 19.0) as i32;
+    assert!((near[0] as i32 - far[0] as i32 - expected).abs() <= 2);
+}
+
+#[test]
+fn spheres_show_per_pixel_depth() {
+    let mut world = world(vec![]);
+    world.proj_mat = Perspective3::new(1.0, 1.0, 1.0, 20.0).to_homogeneous();
+    world.impostors.push(SphereImpostor::new(vec![Sphere {
+        center: Point3::new(0.0, 0.0, 0.0),
+        radius: 1.5,
+        color: RED,
+    }]));
+    world.render_mode = RenderMode::Depth;
+    let frame = render(&mut world);
+    let middle = pixel(&frame, WIDTH / 2, HEIGHT / 2)[0];
+    let edge = pixel(&frame, WIDTH / 2 + 12, HEIGHT / 2)[0];
+    assert!(middle > edge && edge > 0);
+}
+
+#[test]
+fn next_cycles_back_to_shaded() {
+    assert_eq!(RenderMode::Shaded.next(), RenderMode::Depth);
+    assert_eq!(
+        RenderMode::Shaded.next().next(),
+        RenderMode::Points { size: 2 }
+    );
+    assert_eq!(
+        RenderMode::Shaded.next().next().next(),
+        RenderMode::TriangleId
+    );
+    assert_eq!(RenderMode::TriangleId.next(), RenderMode::ObjectId);
+    assert_eq!(RenderMode::ObjectId.next(), RenderMode::Shaded);
+}
@@ -0,0 +1,46 @@
+mod common;
+
+use common::{HEIGHT, WHITE, WIDTH, object, pixel, render, world};
+
+use engine::mesh::Color;
+use engine::mesh::p_hack::PHackMesh;
+use engine::mesh::quad::QuadMesh;
+
+#[test]
+fn fixed_point_covers_nearly_what_floats_do() {
+    let mut world = world(vec![object(PHackMesh::new(), 0.0, 0.0, 0.0)]);
+    world.deterministic = true;
+    let fixed = render(&mut world);
+    world.deterministic = false;
+    let float = render(&mut world);
+
+    let covered = fixed.chunks_exact(4).filter(|&p| p != WHITE).count();
+    let differing = fixed
+        .chunks_exact(4)
+        .zip(float.chunks_exact(4))
+        .filter(|(a, b)| a != b)
+        .count();
+    assert!(covered > 100);
+    assert!(differing * 20 < covered, "{differing} of {covered} differ");
+}
+
+#[test]
+fn sub_pixel_jitter_snaps_to_the_same_image() {
+    let mut world = world(vec![object(PHackMesh::new(), 0.0, 0.0, 0.0)]);
+    let before = render(&mut world);
+    // Far below 1/16 of a pixel on screen.
+    world.models[0].offset_x = 1e-5;
+    world.models[0].offset_y = -1e-5;
+    assert!(render(&mut world) == before);
+}
+
+#[test]
+fn triangles_past_the_guard_band_still_draw() {
+    // So close to the eye that its corners project millions of pixels out.
+    let quad = QuadMesh::new(4e5, 4e5, Color::rgb(0, 0, 200));
+    let mut world = world(vec![object(quad, 0.0, 0.0, -4.8)]);
+    world.deterministic = true;
+    let frame = render(&mut world);
+    assert_ne!(pixel(&frame, WIDTH / 2, HEIGHT / 2), WHITE);
+    assert_ne!(pixel(&frame, 0, 0), WHITE);
+}
@@ -0,0 +1,61 @@
+mod common;
+
+use std::collections::HashSet;
+
+use common::{object, render, world};
+
+use engine::mesh::Color;
+use engine::mesh::quad::QuadMesh;
+use engine::raster::dither;
+
+const GRAY: Color = Color {
+    r: 100,
+    g: 100,
+    b: 100,
+    a: 255,
+};
+
+#[test]
+fn dither_stays_within_one_step() {
+    for value in [0.0, 0.4, 87.7, 128.5, 254.9, 255.0] {
+        for y in 0..4 {
+            for x in 0..4 {
+                let d = dither(value, x, y) as f32;
+                assert!((d - value).abs() <= 1.0, "{value} -> {d}");
+            }
+        }
+    }
+    // Whole values are left alone.
+    assert!((0..16).all(|i| dither(42.0, i % 4, i / 4) == 42));
+}
+
+#[test]
+fn dither_averages_to_the_exact_value() {
+    let mean = (0..16)
+        .map(|i| dither(10.25, i % 4, i / 4) as f32)
+        .sum::<f32>()
+        / 16.0;
+    assert!((mean - 10.25).abs() < 1.0 / 16.0);
+}
+
+#[test]
+fn dithered_quad_mixes_neighbouring_shades() {
+    let mut world = world(vec![object(QuadMesh::new(20.0, 20.0, GRAY), 0.0, 0.0, 0.0)]);
+    world.gamma_correct = false;
+    let reds = |frame: &[u8]| -> Vec<u8> { frame.chunks(4).map(|p| p[0]).collect() };
+
+    let plain = reds(&render(&mut world));
+    world.dither = true;
+    let dithered = reds(&render(&mut world));
+    assert_eq!(render(&mut world), render(&mut world));
+
+    assert_eq!(plain.iter().collect::<HashSet<_>>().len(), 1);
+    let shades: HashSet<_> = dithered.iter().collect();
+    assert_eq!(shades.len(), 2);
+    assert!(dithered.iter().all(|&r| r == plain[0] || r == plain[0] + 1));
+    // 100 * (0.3 ambient + cos to the light) is not a whole number; the
+    // dithered mean is closer to it than the truncated value.
+    let exact = 100.0 * (0.3 + 1.0 / 3f32.sqrt());
+    let mean = dithered.iter().map(|&r| r as f32).sum::<f32>() / dithered.len() as f32;
+    assert!((mean - exact).abs() < (plain[0] as f32 - exact).abs());
+}
@@ -0,0 +1,127 @@
+mod common;
+
+use common::{HEIGHT, WIDTH, object, pixel, render, world};
+use engine::mesh::Color;
+use engine::mesh::quad::QuadMesh;
+use engine::{CullMode, RenderStats, World};
+
+const LAYERS: usize = 8;
+
+/// `LAYERS` squares stacked one behind the other, each filling the
+/// frame, colored from red at the front to blue at the back.
+fn stacked_squares() -> World {
+    let models = (0..LAYERS)
+        .map(|layer| {
+            let t = (layer * 255 / (LAYERS - 1)) as u8;
+            let color = Color::rgb(255 - t, 40, t);
+            object(
+                QuadMesh::new(20.0, 20.0, color),
+                0.0,
+                0.0,
+                layer as f32 * 0.5,
+            )
+        })
+        .collect();
+    let mut world = world(models);
+    world.cull_mode = CullMode::None;
+    world.depth_buffer = true;
+    world
+}
+
+fn render_with(world: &mut World, front_to_back: bool) -> (Vec<u8>, RenderStats) {
+    world.front_to_back = front_to_back;
+    let frame = render(world);
+    (frame, world.stats())
+}
+
+#[test]
+fn opaque_overdraw_is_rejected_before_shading() {
+    let mut world = stacked_squares();
+    let (painted, back_to_front) = render_with(&mut world, false);
+    let (frame, front_to_back) = render_with(&mut world, true);
+    assert_eq!(frame, painted);
+
+    let area = (WIDTH * HEIGHT) as usize;
+    // Back to front every square is painted over the one behind it. Only
+    // pixels on a square's diagonal, covered by both its triangles, are
+    // rejected the second time.
+    assert!(back_to_front.pixels_written >= LAYERS * area);
+    assert!(back_to_front.pixels_depth_rejected <= LAYERS * 2 * WIDTH as usize);
+    // Nearest first only the front square is written.
+    assert_eq!(front_to_back.pixels_written, area);
+    assert!(front_to_back.pixels_depth_rejected >= (LAYERS - 1) * area);
+}
+
+#[test]
+fn hidden_triangles_are_never_lit() {
+    let mut world = stacked_squares();
+    let (painted, back_to_front) = render_with(&mut world, false);
+    let (frame, front_to_back) = render_with(&mut world, true);
+    assert_eq!(frame, painted);
+    assert_eq!(back_to_front.triangles_lit, 2 * LAYERS);
+    // Only the front square's two triangles get past the depth test.
+    assert_eq!(front_to_back.triangles_lit, 2);
+    assert_eq!(front_to_back.triangles_rasterized, 2 * LAYERS);
+}
+
+#[test]
+fn translucent_surfaces_still_blend_over_solid_ones() {
+    let mut world = stacked_squares();
+    let (solid, _) = render_with(&mut world, true);
+    world.models.push(object(
+        QuadMesh::new(
+            20.0,
+            20.0,
+            Color {
+                a: 128,
+                ..Color::rgb(0, 255, 0)
+            },
+        ),
+        0.0,
+        0.0,
+        -0.5,
+    ));
+    let (frame, _) = render_with(&mut world, true);
+    let (before, after) = (pixel(&solid, 20, 40), pixel(&frame, 20, 40));
+    assert!(after[1] > before[1] + 60, "{before:?} {after:?}");
+    assert!(after[0] > 60, "the red square shows through: {after:?}");
+}
+
+#[test]
+fn wireframe_stays_on_top_of_its_own_triangles() {
+    let mut world = stacked_squares();
+    world.wireframe = Some(Color::rgb(255, 255, 255));
+    let (painted, _) = render_with(&mut world, false);
+    let (frame, _) = render_with(&mut world, true);
+    let white = |frame: &[u8]| {
+        (0..HEIGHT)
+            .flat_map(|y| (0..WIDTH).map(move |x| (x, y)))
+            .filter(|&(x, y)| pixel(frame, x, y)[..3] == [255, 255, 255])
+            .count()
+    };
+    // The front square's diagonal crosses the frame.
+    assert!(white(&painted) >= WIDTH as usize);
+    assert_eq!(white(&frame), white(&painted));
+}
+
+#[test]
+fn equal_depths_go_to_the_surface_painted_last() {
+    // Two squares in exactly the same place: only the first drawn of them
+    // passes the depth test. Painted back to front that is the first
+    // listed; drawn nearest first, the order flips and so does the winner.
+    let square = |color| object(QuadMesh::new(20.0, 20.0, color), 0.0, 0.0, 1.0);
+    let mut world = world(vec![
+        square(Color::rgb(255, 0, 0)),
+        square(Color::rgb(0, 0, 255)),
+    ]);
+    world.cull_mode = CullMode::None;
+    world.depth_buffer = true;
+    let (painted, _) = render_with(&mut world, false);
+    let (frame, _) = render_with(&mut world, true);
+    let red = |frame: &[u8]| {
+        let [r, _, b, _] = pixel(frame, 20, 40);
+        r > b
+    };
+    assert!(red(&painted));
+    assert!(!red(&frame));
+}
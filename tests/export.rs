@@ -0,0 +1,137 @@
+mod common;
+
+use std::f32::consts::FRAC_PI_2;
+use std::path::PathBuf;
+
+use common::{object, world};
+use engine::mesh::cube::CubeMesh;
+use engine::mesh::obj::ObjMesh;
+use engine::mesh::quad::QuadMesh;
+use engine::mesh::{Color, ImportOptions, Mesh};
+use engine::{Object, World};
+use nalgebra::{Point3, UnitQuaternion, Vector3};
+
+fn temp_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("shapes-export-{}-{name}.obj", std::process::id()))
+}
+
+/// Something like the demo: a floor, a turned and scaled cube with a
+/// smaller one attached to it, a mirrored cube and a hidden one.
+fn scene() -> World {
+    let floor = Object {
+        rotation: UnitQuaternion::from_axis_angle(&Vector3::x_axis(), FRAC_PI_2),
+        ..object(
+            QuadMesh::new(8.0, 8.0, Color::rgb(90, 90, 90)),
+            0.0,
+            -2.0,
+            0.0,
+        )
+    };
+    let cube = Object {
+        rotation: UnitQuaternion::from_euler_angles(0.3, 0.7, 0.1),
+        scale: 1.5,
+        ..object(CubeMesh::new(Color::rgb(200, 40, 40)), 1.0, 0.0, 2.0)
+    };
+    let moon = Object {
+        scale: 0.25,
+        ..object(CubeMesh::new(Color::rgb(40, 40, 200)), 0.0, 2.0, 0.0)
+    };
+    let mirrored = Object {
+        scale: -1.0,
+        ..object(CubeMesh::new(Color::rgb(40, 200, 40)), -3.0, 0.0, 0.0)
+    };
+    let hidden = Object {
+        visible: false,
+        ..object(CubeMesh::new(Color::WHITE), 0.0, 5.0, 0.0)
+    };
+    let mut world = world(vec![floor, cube, moon, mirrored, hidden]);
+    world.set_parent(2, Some(1)).unwrap();
+    world
+}
+
+#[test]
+fn exported_scene_loads_back_in_place() {
+    let world = scene();
+    let path = temp_path("round-trip");
+    world.export_obj(&path).unwrap();
+    let text = std::fs::read_to_string(&path).unwrap();
+    let mesh = ObjMesh::from_path(&path, ImportOptions::default()).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    let shown = &world.models[..4];
+    let vertex_count: usize = shown.iter().map(|model| model.mesh.verts().len()).sum();
+    let triangle_count: usize = shown.iter().map(|model| model.mesh.tris().len()).sum();
+    assert_eq!(mesh.verts().len(), vertex_count);
+    assert_eq!(mesh.tris().len(), triangle_count);
+    assert_eq!(
+        text.lines().filter(|line| line.starts_with("o ")).count(),
+        4
+    );
+    assert!(text.contains("o object_2\n"));
+
+    let matrices = world.world_matrices();
+    let mut first = 0;
+    for (model, matrix) in shown.iter().zip(&matrices) {
+        for (i, vertex) in model.mesh.verts().iter().enumerate().step_by(3) {
+            let expected = matrix.transform_point(vertex);
+            let loaded = mesh.verts()[first + i];
+            assert!((loaded - expected).norm() < 1e-5, "{loaded} != {expected}");
+        }
+        first += model.mesh.verts().len();
+    }
+}
+
+#[test]
+fn faces_keep_facing_even_when_mirrored() {
+    let world = scene();
+    let path = temp_path("facing");
+    world.export_obj(&path).unwrap();
+    let mesh = ObjMesh::from_path(&path, ImportOptions::default()).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    // Every face of a cube, front side out, points away from its centre.
+    let matrices = world.world_matrices();
+    let cubes = [1, 2, 3];
+    let mut tri_start = world.models[0].mesh.tris().len();
+    for index in cubes {
+        let model = &world.models[index];
+        let center = matrices[index].transform_point(&Point3::origin());
+        for tri in &mesh.tris()[tri_start..tri_start + model.mesh.tris().len()] {
+            let [a, b, c] = [tri.v1, tri.v2, tri.v3].map(|v| mesh.verts()[v]);
+            // The engine winds front faces clockwise seen from outside,
+            // so this normal points inwards.
+            let inward = (b - a).cross(&(c - a));
+            let centroid = (a.coords + b.coords + c.coords) / 3.0;
+            assert!(
+                inward.dot(&(centroid - center.coords)) < 0.0,
+                "object {index} has a face turned around"
+            );
+        }
+        tri_start += model.mesh.tris().len();
+    }
+}
+
+#[test]
+fn materials_carry_face_colors() {
+    let world = scene();
+    let path = temp_path("materials");
+    world.export_obj_with_materials(&path).unwrap();
+    let mtl_path = path.with_extension("mtl");
+    let mtl = std::fs::read_to_string(&mtl_path).unwrap();
+    let mesh = ObjMesh::from_path(&path, ImportOptions::default()).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    std::fs::remove_file(&mtl_path).unwrap();
+
+    assert_eq!(
+        mtl.lines()
+            .filter(|line| line.starts_with("newmtl"))
+            .count(),
+        4
+    );
+    let colors: Vec<Color> = world.models[..4]
+        .iter()
+        .flat_map(|model| model.mesh.tris().iter().map(|tri| tri.color))
+        .collect();
+    let loaded: Vec<Color> = mesh.tris().iter().map(|tri| tri.color).collect();
+    assert_eq!(loaded, colors);
+}
@@ -0,0 +1,65 @@
+mod common;
+
+use common::{object, pixel, render, world};
+use engine::fog::{Fog, FogMode};
+use engine::mesh::Color;
+use engine::mesh::p_hack::PHackMesh;
+
+const SKY: Color = Color {
+    r: 120,
+    g: 150,
+    b: 200,
+    a: 255,
+};
+
+fn fog(start: f32, end: f32, mode: FogMode) -> Fog {
+    Fog {
+        color: SKY,
+        start,
+        end,
+        mode,
+    }
+}
+
+#[test]
+fn factor_clamps_outside_the_range() {
+    for mode in [FogMode::Linear, FogMode::Exponential { density: 3.0 }] {
+        let fog = fog(10.0, 20.0, mode);
+        assert_eq!(fog.factor(0.0), 0.0);
+        assert_eq!(fog.factor(10.0), 0.0);
+        assert_eq!(fog.factor(20.0), 1.0);
+        assert_eq!(fog.factor(1e6), 1.0);
+    }
+}
+
+#[test]
+fn exponential_fog_thickens_faster_than_linear() {
+    let linear = fog(0.0, 10.0, FogMode::Linear);
+    let exp = fog(0.0, 10.0, FogMode::Exponential { density: 3.0 });
+    assert_eq!(linear.factor(5.0), 0.5);
+    assert!(exp.factor(5.0) > 0.5);
+    let mut last = 0.0;
+    for d in 0..=10 {
+        let f = exp.factor(d as f32);
+        assert!(f >= last);
+        last = f;
+    }
+}
+
+#[test]
+fn geometry_past_the_end_matches_the_background() {
+    let mut world = world(vec![object(PHackMesh::new(), 0.0, 0.0, 0.0)]);
+    world.fog = Some(fog(0.0, 1.0, FogMode::Linear));
+    let frame = render(&mut world);
+    assert!(frame.chunks(4).all(|p| p == [SKY.r, SKY.g, SKY.b, SKY.a]));
+}
+
+#[test]
+fn distant_fog_leaves_geometry_untouched() {
+    let mut world = world(vec![object(PHackMesh::new(), 0.0, 0.0, 0.0)]);
+    let clear = render(&mut world);
+    world.fog = Some(fog(100.0, 200.0, FogMode::Linear));
+    let fogged = render(&mut world);
+    assert_eq!(pixel(&fogged, 32, 32), pixel(&clear, 32, 32));
+    assert_eq!(pixel(&fogged, 0, 0), [SKY.r, SKY.g, SKY.b, SKY.a]);
+}
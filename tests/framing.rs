@@ -0,0 +1,111 @@
+mod common;
+
+use common::{object, world};
+
+use engine::mesh::cube::CubeMesh;
+use engine::mesh::quad::QuadMesh;
+use engine::mesh::{Color, Mesh, MeshMut};
+use nalgebra::Point3;
+
+const GRAY: Color = Color {
+    r: 128,
+    g: 128,
+    b: 128,
+    a: 255,
+};
+
+#[test]
+fn cube_aabb() {
+    let (min, max) = CubeMesh::new(GRAY).aabb();
+    assert_eq!(min, Point3::new(-1.0, -1.0, -1.0));
+    assert_eq!(max, Point3::new(1.0, 1.0, 1.0));
+    let (min, max) = QuadMesh::new(4.0, 2.0, GRAY).aabb();
+    assert_eq!(
+        (min, max),
+        (Point3::new(-2.0, -1.0, 0.0), Point3::new(2.0, 1.0, 0.0))
+    );
+}
+
+fn assert_in_view(world: &engine::World, min: Point3<f32>, max: Point3<f32>) {
+    let clip = world.proj_mat * world.camera.generate_view_mat();
+    for i in 0..8 {
+        let corner = Point3::new(
+            if i & 1 == 0 { min.x } else { max.x },
+            if i & 2 == 0 { min.y } else { max.y },
+            if i & 4 == 0 { min.z } else { max.z },
+        );
+        let p = clip * corner.to_homogeneous();
+        let ndc = p.xyz() / p.w;
+        assert!(p.w > 0.0, "{corner} is behind the camera");
+        assert!(
+            ndc.x.abs() <= 1.0 && ndc.y.abs() <= 1.0,
+            "{corner} -> {ndc}"
+        );
+        assert!((0.0..=1.0).contains(&ndc.z), "{corner} -> {ndc}");
+    }
+}
+
+#[test]
+fn frame_all_fits_a_far_cube() {
+    // Half a unit cube's size, far off to the side of the camera.
+    let mut cube = object(CubeMesh::new(GRAY), 40.0, -3.0, 60.0);
+    cube.scale = 0.5;
+    let mut world = world(vec![cube]);
+    let forward = world.camera.forward();
+    world.frame_all();
+    assert!((world.camera.forward() - forward).norm() < 1e-5);
+    assert_in_view(
+        &world,
+        Point3::new(39.5, -3.5, 59.5),
+        Point3::new(40.5, -2.5, 60.5),
+    );
+}
+
+#[test]
+fn frame_all_fits_every_object() {
+    let mut world = world(vec![
+        object(CubeMesh::new(GRAY), -10.0, 0.0, 0.0),
+        object(CubeMesh::new(GRAY), 10.0, 5.0, 20.0),
+    ]);
+    world.frame_all();
+    assert_in_view(
+        &world,
+        Point3::new(-11.0, -1.0, -1.0),
+        Point3::new(-9.0, 1.0, 1.0),
+    );
+    assert_in_view(
+        &world,
+        Point3::new(9.0, 4.0, 19.0),
+        Point3::new(11.0, 6.0, 21.0),
+    );
+}
+
+#[test]
+fn nothing_to_frame_leaves_the_camera() {
+    let mut world = world(vec![]);
+    let before = world.camera.position;
+    world.frame_all();
+    assert_eq!(world.camera.position, before);
+}
+
+#[test]
+fn center_and_normalize() {
+    let mut quad = QuadMesh::new(4.0, 2.0, GRAY);
+    for v in quad.verts_mut() {
+        v.x += 10.0;
+        v.z += 3.0;
+    }
+    quad.center_to_origin();
+    quad.normalize_scale(2.0);
+    assert_eq!(
+        quad.aabb(),
+        (Point3::new(-1.0, -0.5, 0.0), Point3::new(1.0, 0.5, 0.0))
+    );
+}
+
+#[test]
+fn degenerate_meshes_are_not_scaled() {
+    let mut quad = QuadMesh::new(0.0, 0.0, GRAY);
+    quad.normalize_scale(2.0);
+    assert!(quad.verts().iter().all(|v| *v == Point3::origin()));
+}
@@ -0,0 +1,174 @@
+mod common;
+
+use common::{render, world};
+use engine::Camera;
+use engine::depth::DepthConvention;
+use engine::pipeline::{
+    Plane, aabb_intersects_frustum, extract_frustum_planes, sphere_intersects_frustum,
+};
+use engine::viewport::Projection;
+use nalgebra::{Matrix4, Point3};
+
+const NAMES: [&str; 6] = ["left", "right", "bottom", "top", "near", "far"];
+
+/// The planes of `m` in the order of [`NAMES`]: reversed depth puts the far
+/// plane at NDC z 0.
+fn planes(m: &Matrix4<f32>, convention: DepthConvention) -> [Plane; 6] {
+    let mut planes = extract_frustum_planes(m);
+    if convention == DepthConvention::Reversed {
+        planes.swap(4, 5);
+    }
+    planes
+}
+
+/// Check that each point is in front of every plane but the one it is
+/// paired with, which it is behind.
+fn assert_sides(planes: &[Plane; 6], cases: &[(Point3<f32>, Option<usize>)]) {
+    for (point, outside) in cases {
+        for (i, plane) in planes.iter().enumerate() {
+            let distance = plane.signed_distance(point);
+            if *outside == Some(i) {
+                assert!(distance < 0.0, "{point} should be behind {}", NAMES[i]);
+            } else {
+                assert!(distance > 0.0, "{point} should be in front of {}", NAMES[i]);
+            }
+        }
+    }
+}
+
+/// A perspective looking down -z from the origin with a 1 radian field of
+/// view, between 1 and 10 units away.
+fn perspective(convention: DepthConvention) -> [Plane; 6] {
+    planes(&convention.perspective(1.0, 1.0, 1.0, 10.0), convention)
+}
+
+#[test]
+fn perspective_planes_bound_the_view() {
+    // tan(0.5) is about 0.546, so the sides lean out that much per unit.
+    let cases = [
+        (Point3::new(0.0, 0.0, -5.0), None),
+        (Point3::new(2.5, 2.5, -5.0), None),
+        (Point3::new(-3.0, 0.0, -5.0), Some(0)),
+        (Point3::new(3.0, 0.0, -5.0), Some(1)),
+        (Point3::new(0.0, -3.0, -5.0), Some(2)),
+        (Point3::new(0.0, 3.0, -5.0), Some(3)),
+        (Point3::new(0.0, 0.0, -0.5), Some(4)),
+        (Point3::new(0.0, 0.0, -11.0), Some(5)),
+    ];
+    for convention in [DepthConvention::Standard, DepthConvention::Reversed] {
+        assert_sides(&perspective(convention), &cases);
+    }
+}
+
+#[test]
+fn near_plane_follows_the_drawn_depth_range() {
+    // Standard depth only draws NDC z from 0, which starts at 2nf / (n + f)
+    // rather than at the near clip plane.
+    let starts = DepthConvention::Standard.drawn_from(1.0, 10.0);
+    assert!((starts - 20.0 / 11.0).abs() < 1e-5);
+    let standard = perspective(DepthConvention::Standard);
+    let reversed = perspective(DepthConvention::Reversed);
+    let between = Point3::new(0.0, 0.0, -1.5);
+    assert!(standard[4].signed_distance(&between) < 0.0);
+    assert!(reversed[4].signed_distance(&between) > 0.0);
+    for planes in [standard, reversed] {
+        assert!(planes[4].signed_distance(&Point3::new(0.0, 0.0, -2.0)) > 0.0);
+        assert!((planes[5].signed_distance(&Point3::new(0.0, 0.0, -9.0)) - 1.0).abs() < 1e-3);
+    }
+}
+
+#[test]
+fn orthographic_planes_bound_the_box() {
+    let projection = Projection::Orthographic {
+        height: 4.0,
+        near: 1.0,
+        far: 10.0,
+    };
+    for convention in [DepthConvention::Standard, DepthConvention::Reversed] {
+        let matrix = projection.matrix(&Matrix4::identity(), 1.0, convention);
+        let planes = planes(&matrix, convention);
+        assert_sides(
+            &planes,
+            &[
+                (Point3::new(0.0, 0.0, -5.0), None),
+                (Point3::new(1.9, -1.9, -9.9), None),
+                (Point3::new(-2.5, 0.0, -5.0), Some(0)),
+                (Point3::new(2.5, 0.0, -5.0), Some(1)),
+                (Point3::new(0.0, -2.5, -5.0), Some(2)),
+                (Point3::new(0.0, 2.5, -5.0), Some(3)),
+                (Point3::new(0.0, 0.0, -0.5), Some(4)),
+                (Point3::new(0.0, 0.0, -10.5), Some(5)),
+            ],
+        );
+        // Distances come out in world units.
+        let distance = planes[1].signed_distance(&Point3::new(2.5, 0.0, -5.0));
+        assert!((distance + 0.5).abs() < 1e-5, "{distance}");
+    }
+}
+
+#[test]
+fn planes_of_proj_view_are_in_world_space() {
+    let world = world(vec![]);
+    let clip = world.proj_mat * world.camera.generate_view_mat();
+    let planes = planes(&clip, DepthConvention::Standard);
+    assert_sides(
+        &planes,
+        &[
+            (Point3::origin(), None),
+            (Point3::new(0.0, 0.0, -4.9), Some(4)),
+            (Point3::new(0.0, 0.0, 300.0), Some(5)),
+        ],
+    );
+}
+
+#[test]
+fn spheres_and_boxes_are_rejected_only_when_wholly_outside() {
+    let planes = perspective(DepthConvention::Standard);
+    let beyond = Point3::new(0.0, 0.0, -11.5);
+    assert!(!sphere_intersects_frustum(&planes, &beyond, 1.0));
+    assert!(sphere_intersects_frustum(&planes, &beyond, 2.0));
+    assert!(sphere_intersects_frustum(
+        &planes,
+        &Point3::new(0.0, 0.0, -5.0),
+        0.1
+    ));
+
+    let min = Point3::new(-1.0, -1.0, -12.0);
+    assert!(!aabb_intersects_frustum(
+        &planes,
+        &min,
+        &Point3::new(1.0, 1.0, -10.5)
+    ));
+    assert!(aabb_intersects_frustum(
+        &planes,
+        &min,
+        &Point3::new(1.0, 1.0, -9.0)
+    ));
+    // A box around the whole view is kept though no corner is inside.
+    assert!(aabb_intersects_frustum(
+        &planes,
+        &Point3::new(-100.0, -100.0, -100.0),
+        &Point3::new(100.0, 100.0, 100.0)
+    ));
+}
+
+#[test]
+fn another_cameras_frustum_is_drawn_in_the_main_view() {
+    let mut world = world(vec![]);
+    let plain = render(&mut world);
+    world.cameras.push(Camera::looking_at(
+        Point3::origin(),
+        Point3::new(0.0, 0.0, 5.0),
+    ));
+
+    world.show_frustum = Some(1);
+    let frame = render(&mut world);
+    assert!(frame.chunks(4).any(|p| p == [0, 200, 255, 255]));
+
+    // Looking through the camera itself, or one that doesn't exist, shows
+    // nothing.
+    world.show_frustum = Some(0);
+    assert_eq!(render(&mut world), plain);
+    world.show_frustum = Some(2);
+    assert_eq!(render(&mut world), plain);
+}
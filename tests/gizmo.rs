@@ -0,0 +1,80 @@
+mod common;
+
+use common::{HEIGHT, WHITE, WIDTH, pixel, render, world};
+
+use nalgebra::Point3;
+
+#[test]
+fn gizmos_are_off_by_default() {
+    let mut world = world(vec![]);
+    assert!(render(&mut world).iter().all(|&b| b == 255));
+}
+
+#[test]
+fn grid_stays_below_the_horizon() {
+    // Standing inside the grid looking along it, half of the lines are
+    // behind the camera. Clipped properly, nothing lands above the horizon.
+    let mut world = world(vec![]);
+    world.show_gizmos = true;
+    world.camera.position = Point3::new(0.3, 1.0, 0.4);
+    world.camera.look_at(Point3::new(0.3, 1.0, 5.0));
+    let frame = render(&mut world);
+
+    for y in 0..HEIGHT / 2 - 1 {
+        for x in 0..WIDTH {
+            assert_eq!(pixel(&frame, x, y), WHITE, "pixel {x}, {y}");
+        }
+    }
+    assert!((HEIGHT / 2 + 1..HEIGHT).any(|y| pixel(&frame, WIDTH / 2, y) != WHITE));
+}
+
+#[test]
+fn axes_are_colored() {
+    let mut world = world(vec![]);
+    world.show_gizmos = true;
+    world.camera.position = Point3::new(-3.0, 4.0, -5.0);
+    world.camera.look_at(Point3::origin());
+    let frame = render(&mut world);
+    let reddish = frame
+        .chunks(4)
+        .any(|p| p[0] > 150 && p[1] < 100 && p[2] < 100);
+    let greenish = frame
+        .chunks(4)
+        .any(|p| p[1] > 150 && p[0] < 100 && p[2] < 100);
+    let blueish = frame
+        .chunks(4)
+        .any(|p| p[2] > 150 && p[0] < 100 && p[1] < 150);
+    assert!(reddish && greenish && blueish);
+}
+
+#[test]
+fn grid_fades_out_with_distance() {
+    let mut world = world(vec![]);
+    world.show_gizmos = true;
+    world.camera.position = Point3::new(0.0, 2.0, -5.0);
+    world.camera.look_at(Point3::origin());
+    let near = render(&mut world);
+    world.grid.fade_distance = 0.5;
+    let faded = render(&mut world);
+    let marked = |frame: &[u8]| frame.chunks(4).filter(|p| *p != WHITE).count();
+    assert!(marked(&faded) < marked(&near));
+}
+
+#[test]
+fn light_marker_follows_the_light() {
+    let mut world = world(vec![]);
+    world.light.position = Point3::new(0.0, 0.0, 0.0);
+    world.light.target = Point3::new(0.0, -1.0, 0.0);
+    assert!(render(&mut world).chunks(4).all(|p| p == WHITE));
+
+    world.show_light = true;
+    let frame = render(&mut world);
+    assert_ne!(pixel(&frame, WIDTH / 2, HEIGHT / 2), WHITE);
+    // The pointer runs down the screen from the light.
+    assert_ne!(pixel(&frame, WIDTH / 2, HEIGHT / 2 + 4), WHITE);
+    assert_eq!(pixel(&frame, WIDTH / 2, HEIGHT / 2 - 4), WHITE);
+
+    world.light.position = Point3::new(-1.0, 0.0, 0.0);
+    let moved = render(&mut world);
+    assert_eq!(pixel(&moved, WIDTH / 2, HEIGHT / 2), WHITE);
+}
@@ -0,0 +1,88 @@
+mod common;
+
+use std::f32::consts::FRAC_PI_2;
+
+use common::{object, world};
+use engine::mesh::Color;
+use engine::mesh::cube::CubeMesh;
+use engine::mesh::quad::QuadMesh;
+use engine::{CameraMode, Object, World};
+use nalgebra::{Point3, UnitQuaternion, Vector3};
+
+/// A 10 by 10 floor at y = -2, facing up, with a box on it at x = 3.
+fn terrain() -> World {
+    let floor = Object {
+        rotation: UnitQuaternion::from_axis_angle(&Vector3::x_axis(), FRAC_PI_2),
+        ..object(QuadMesh::new(10.0, 10.0, Color::WHITE), 0.0, -2.0, 0.0)
+    };
+    let crate_ = Object {
+        scale: 0.5,
+        ..object(CubeMesh::new(Color::WHITE), 3.0, -1.5, 0.0)
+    };
+    world(vec![floor, crate_])
+}
+
+fn close(a: f32, b: f32) -> bool {
+    (a - b).abs() < 1e-4
+}
+
+#[test]
+fn ground_is_the_highest_surface_below() {
+    let world = terrain();
+    assert!(close(world.ground_height(0.0, 0.0, 5.0).unwrap(), -2.0));
+    assert!(close(world.ground_height(3.0, 0.0, 5.0).unwrap(), -1.0));
+    // Looking up from under the box's top finds the floor.
+    assert!(close(world.ground_height(3.0, 0.0, -1.5).unwrap(), -2.0));
+    assert_eq!(world.ground_height(20.0, 0.0, 5.0), None);
+    assert_eq!(world.ground_height(0.0, 0.0, -3.0), None);
+}
+
+#[test]
+fn cameras_below_eye_height_are_lifted() {
+    let mut world = terrain();
+    world.camera.position = Point3::new(0.0, -2.5, 0.0);
+    let forward = world.camera.forward();
+    assert!(world.keep_camera_above_ground(1.0));
+    assert!(close(world.camera.position.y, -1.0));
+    // Flying, the camera keeps looking the same way from up there.
+    assert_eq!(world.camera.forward(), forward);
+
+    assert!(!world.keep_camera_above_ground(1.0));
+    world.camera.position.y = 4.0;
+    assert!(!world.keep_camera_above_ground(1.0));
+    assert_eq!(world.camera.position.y, 4.0);
+}
+
+#[test]
+fn orbit_focus_moves_with_the_camera() {
+    let mut world = terrain();
+    world.camera.position = Point3::new(0.0, -1.8, -3.0);
+    world.camera.mode = CameraMode::Orbit {
+        focus: Point3::new(0.0, -1.8, 0.0),
+        distance: 3.0,
+    };
+    assert!(world.keep_camera_above_ground(0.5));
+    assert!(close(world.camera.position.y, -1.5));
+    let CameraMode::Orbit { focus, .. } = world.camera.mode else {
+        panic!("still orbiting");
+    };
+    assert!(close(focus.y, -1.5));
+}
+
+#[test]
+fn overhead_and_missing_ground_leave_the_camera_alone() {
+    let mut world = terrain();
+    // Inside the box, whose top is more than the eye height overhead: the
+    // floor is the ground.
+    world.camera.position = Point3::new(3.0, -1.9, 0.0);
+    assert!(world.keep_camera_above_ground(0.5));
+    assert!(close(world.camera.position.y, -1.5));
+
+    world.camera.position = Point3::new(30.0, -10.0, 0.0);
+    assert!(!world.keep_camera_above_ground(1.0));
+    assert_eq!(world.camera.position, Point3::new(30.0, -10.0, 0.0));
+
+    world.models[0].visible = false;
+    world.camera.position = Point3::new(0.0, -2.5, 0.0);
+    assert!(!world.keep_camera_above_ground(1.0));
+}
@@ -0,0 +1,106 @@
+mod common;
+
+use std::f32::consts::FRAC_PI_2;
+
+use common::{object, render, world};
+
+use engine::HierarchyError;
+use engine::mesh::Color;
+use engine::mesh::cube::CubeMesh;
+use nalgebra::{Point3, UnitQuaternion, Vector3};
+
+const RED: Color = Color {
+    r: 255,
+    g: 0,
+    b: 0,
+    a: 255,
+};
+
+fn position(world: &engine::World, index: usize) -> Point3<f32> {
+    world.world_matrices()[index].transform_point(&Point3::origin())
+}
+
+fn sun_and_planet() -> engine::World {
+    let mut world = world(vec![
+        object(CubeMesh::new(RED), 0.0, 0.0, 5.0),
+        object(CubeMesh::new(RED), 2.0, 0.0, 0.0),
+    ]);
+    world.set_parent(1, Some(0)).unwrap();
+    world
+}
+
+#[test]
+fn children_follow_their_parent() {
+    let mut world = sun_and_planet();
+    assert!((position(&world, 1) - Point3::new(2.0, 0.0, 5.0)).norm() < 1e-5);
+
+    world.models[0].rotation = UnitQuaternion::from_axis_angle(&Vector3::y_axis(), FRAC_PI_2);
+    world.models[0].scale = 2.0;
+    // +x turns to -z and doubles.
+    assert!((position(&world, 1) - Point3::new(0.0, 0.0, 1.0)).norm() < 1e-5);
+}
+
+#[test]
+fn deep_chains_compose() {
+    let mut world = world(
+        (0..5)
+            .map(|_| object(CubeMesh::new(RED), 1.0, 0.0, 0.0))
+            .collect(),
+    );
+    for i in 1..5 {
+        world.set_parent(i, Some(i - 1)).unwrap();
+    }
+    assert!((position(&world, 4) - Point3::new(5.0, 0.0, 0.0)).norm() < 1e-5);
+}
+
+#[test]
+fn cycles_are_rejected() {
+    let mut world = sun_and_planet();
+    assert_eq!(
+        world.set_parent(0, Some(1)),
+        Err(HierarchyError::Cycle {
+            child: 0,
+            parent: 1
+        })
+    );
+    assert_eq!(
+        world.set_parent(1, Some(1)),
+        Err(HierarchyError::Cycle {
+            child: 1,
+            parent: 1
+        })
+    );
+    assert_eq!(
+        world.set_parent(1, Some(7)),
+        Err(HierarchyError::NoSuchObject(7))
+    );
+    assert_eq!(world.models[0].parent, None);
+    assert_eq!(world.models[1].parent, Some(0));
+}
+
+#[test]
+fn loops_made_by_hand_still_render() {
+    let mut world = sun_and_planet();
+    world.models[0].parent = Some(1);
+    assert_eq!(world.world_matrices().len(), 2);
+    render(&mut world);
+}
+
+#[test]
+fn removing_a_parent_detaches_children_in_place() {
+    let mut world = sun_and_planet();
+    world
+        .add_object(object(CubeMesh::new(RED), 0.0, 1.0, 0.0))
+        .unwrap();
+    world.set_parent(2, Some(1)).unwrap();
+    world.models[0].rotation = UnitQuaternion::from_axis_angle(&Vector3::y_axis(), FRAC_PI_2);
+    let before = position(&world, 1);
+    let grandchild = position(&world, 2);
+
+    world.remove_object(0).unwrap();
+    assert_eq!(world.models[0].parent, None);
+    assert!((position(&world, 0) - before).norm() < 1e-5);
+    // The grandchild's parent shifted down with it.
+    assert_eq!(world.models[1].parent, Some(0));
+    assert!((position(&world, 1) - grandchild).norm() < 1e-5);
+}
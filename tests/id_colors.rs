@@ -0,0 +1,110 @@
+mod common;
+
+use std::collections::HashSet;
+
+use common::{HEIGHT, WHITE, WIDTH, object, pixel, render, world};
+
+use engine::RenderMode;
+use engine::mesh::Color;
+use engine::mesh::cube::CubeMesh;
+use engine::mesh::quad::QuadMesh;
+use engine::palette::IdPalette;
+
+const RED: Color = Color::rgb(255, 0, 0);
+
+fn rgba(color: Color) -> [u8; 4] {
+    [color.r, color.g, color.b, color.a]
+}
+
+/// Every color in `frame`.
+fn colors(frame: &[u8]) -> HashSet<[u8; 4]> {
+    frame
+        .chunks_exact(4)
+        .map(|p| [p[0], p[1], p[2], p[3]])
+        .collect()
+}
+
+#[test]
+fn id_colors_are_stable_bright_and_spread_out() {
+    for palette in [IdPalette::Hues, IdPalette::ColorBlind] {
+        assert_eq!(palette.color(7), palette.color(7));
+        let all: Vec<Color> = (0..1000).map(|id| palette.color(id)).collect();
+        for color in &all {
+            assert_eq!(color.a, 255);
+            let brightest = color.r.max(color.g).max(color.b);
+            assert!(brightest >= 150, "{palette:?} {color} is too dark");
+        }
+        let neighbours_differ = all.windows(2).filter(|pair| pair[0] != pair[1]).count();
+        assert!(neighbours_differ > 900, "{palette:?}");
+    }
+    let hues: HashSet<_> = (0..1000).map(|id| IdPalette::Hues.color(id)).collect();
+    assert!(hues.len() > 900);
+    // Seven colors in three tints.
+    let color_blind: HashSet<_> = (0..1000)
+        .map(|id| IdPalette::ColorBlind.color(id))
+        .collect();
+    assert_eq!(color_blind.len(), 21);
+    assert!(color_blind.contains(&Color::from_hex(0x0072B2)));
+}
+
+#[test]
+fn triangles_are_drawn_unlit_in_their_id_colors() {
+    let mut world = world(vec![object(QuadMesh::new(2.0, 2.0, RED), 0.0, 0.0, 0.0)]);
+    world.render_mode = RenderMode::TriangleId;
+    let frame = render(&mut world);
+    let expected: HashSet<_> = [
+        WHITE,
+        rgba(world.id_palette.color(0)),
+        rgba(world.id_palette.color(1)),
+    ]
+    .into();
+    assert_eq!(colors(&frame), expected);
+
+    world.id_palette = IdPalette::ColorBlind;
+    let frame = render(&mut world);
+    assert!(colors(&frame).contains(&rgba(IdPalette::ColorBlind.color(1))));
+}
+
+#[test]
+fn objects_are_drawn_in_one_color_each() {
+    let mut world = world(vec![
+        object(CubeMesh::new(RED), 1.2, 0.0, 0.0),
+        object(CubeMesh::new(RED), -1.2, 0.0, 0.0),
+    ]);
+    world.render_mode = RenderMode::ObjectId;
+    let frame = render(&mut world);
+    // +x is on the screen's left.
+    let left = pixel(&frame, WIDTH / 4, HEIGHT / 2);
+    let right = pixel(&frame, 3 * WIDTH / 4, HEIGHT / 2);
+    assert_eq!(left, rgba(world.id_palette.color(0)));
+    assert_eq!(right, rgba(world.id_palette.color(1)));
+    assert_eq!(colors(&frame).len(), 3);
+}
+
+#[test]
+fn the_wireframe_outlines_triangles_in_any_mode() {
+    let mut world = world(vec![object(QuadMesh::new(2.0, 2.0, RED), 0.0, 0.0, 0.0)]);
+    let edge = Color::rgb(0, 0, 0);
+    world.render_mode = RenderMode::TriangleId;
+    let plain = render(&mut world);
+    world.wireframe = Some(edge);
+    let wired = render(&mut world);
+    let drawn = colors(&wired);
+    assert!(drawn.contains(&rgba(edge)));
+    // The ID colors still fill the inside.
+    assert!(drawn.contains(&rgba(world.id_palette.color(0))));
+    assert!(drawn.contains(&rgba(world.id_palette.color(1))));
+    // Only pixels along the edges changed.
+    let changed = plain
+        .chunks_exact(4)
+        .zip(wired.chunks_exact(4))
+        .filter(|(a, b)| a != b)
+        .count();
+    assert!(
+        changed > 0 && changed < (WIDTH * HEIGHT / 4) as usize,
+        "{changed}"
+    );
+
+    world.render_mode = RenderMode::Shaded;
+    assert!(colors(&render(&mut world)).contains(&rgba(edge)));
+}
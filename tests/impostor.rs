@@ -0,0 +1,103 @@
+mod common;
+
+use common::{HEIGHT, WHITE, WIDTH, object, pixel, render, world};
+use engine::impostor::{Sphere, SphereImpostor};
+use engine::mesh::Color;
+use engine::mesh::quad::QuadMesh;
+use nalgebra::Point3;
+
+const RED: Color = Color {
+    r: 220,
+    g: 30,
+    b: 30,
+    a: 255,
+};
+const GREEN: Color = Color {
+    r: 30,
+    g: 200,
+    b: 30,
+    a: 255,
+};
+
+fn sphere(z: f32, radius: f32) -> SphereImpostor {
+    SphereImpostor::new(vec![Sphere {
+        center: Point3::new(0.0, 0.0, z),
+        radius,
+        color: RED,
+    }])
+}
+
+#[test]
+fn sphere_covers_a_disc_with_soft_edges() {
+    let mut world = world(vec![]);
+    world.impostors.push(sphere(0.0, 1.0));
+    let frame = render(&mut world);
+
+    let center = pixel(&frame, WIDTH / 2, HEIGHT / 2);
+    assert!(center[0] > center[1] && center[0] > center[2]);
+    assert_eq!(pixel(&frame, 0, 0), WHITE);
+    assert_eq!(pixel(&frame, WIDTH / 2, 0), WHITE);
+
+    // Somewhere along the silhouette a pixel is only partly covered.
+    let partial = (0..WIDTH)
+        .map(|x| pixel(&frame, x, HEIGHT / 2))
+        .any(|p| p != WHITE && p[1] > center[1] + 20);
+    assert!(partial);
+}
+
+#[test]
+fn lit_side_is_brighter_than_shadow_side() {
+    let mut world = world(vec![]);
+    world.impostors.push(sphere(0.0, 1.5));
+    let frame = render(&mut world);
+    // The light sits at world -x, up and towards the camera. Seen from the
+    // camera, world -x is the right side of the screen.
+    let lit = pixel(&frame, WIDTH / 2 + 6, HEIGHT / 2 - 6);
+    let dark = pixel(&frame, WIDTH / 2 - 6, HEIGHT / 2 + 6);
+    let brightness = |p: [u8; 4]| p[0] as u32 + p[1] as u32 + p[2] as u32;
+    assert!(brightness(lit) > brightness(dark));
+}
+
+#[test]
+fn spheres_and_meshes_occlude_by_depth() {
+    let quad = || object(QuadMesh::new(4.0, 4.0, GREEN), 0.0, 0.0, -1.0);
+
+    // Quad in front of the sphere.
+    let mut world = world(vec![quad()]);
+    world.impostors.push(sphere(1.0, 1.0));
+    let front = pixel(&render(&mut world), WIDTH / 2, HEIGHT / 2);
+    assert!(front[1] > front[0]);
+
+    // Sphere in front of the quad.
+    world.impostors = vec![sphere(-2.5, 0.5)];
+    let back = pixel(&render(&mut world), WIDTH / 2, HEIGHT / 2);
+    assert!(back[0] > back[1]);
+}
+
+#[test]
+fn spheres_behind_the_camera_are_skipped() {
+    let mut world = world(vec![]);
+    world.impostors.push(sphere(-10.0, 1.0));
+    let frame = render(&mut world);
+    assert!(frame.chunks(4).all(|p| p == WHITE));
+}
+
+#[test]
+fn csv_with_and_without_colors() {
+    let csv = b"# x,y,z,radius[,r,g,b]\n0,0,0,1\n\n1.5, 2, -3, 0.25, 10, 20, 30\n";
+    let batch = SphereImpostor::from_csv(csv, RED).unwrap();
+    assert_eq!(batch.spheres.len(), 2);
+    assert_eq!(batch.spheres[0].color, RED);
+    assert_eq!(batch.spheres[1].center, Point3::new(1.5, 2.0, -3.0));
+    assert_eq!(batch.spheres[1].radius, 0.25);
+    assert_eq!(
+        (batch.spheres[1].color.r, batch.spheres[1].color.b),
+        (10, 30)
+    );
+}
+
+#[test]
+fn csv_errors_name_the_line() {
+    let err = SphereImpostor::from_csv(b"0,0,0,1\n0,0,zero,1\n", RED).unwrap_err();
+    assert_eq!(err.to_string(), "line 2: bad number \"zero\"");
+}
@@ -0,0 +1,106 @@
+use engine::input::{Action, InputConfig, InputConfigError, Key};
+
+#[test]
+fn defaults_match_the_readme() {
+    let config = InputConfig::default();
+    assert_eq!(config.key(Action::MoveForward), Key::KeyW);
+    assert_eq!(config.key(Action::StrafeLeft), Key::KeyA);
+    assert_eq!(config.key(Action::ToggleMouse), Key::Tab);
+    assert_eq!(config.mouse_sensitivity, 0.003);
+    assert_eq!(config.move_speed, 0.1);
+}
+
+#[test]
+fn rebinds_keys_and_speeds() {
+    let config = InputConfig::parse(
+        "# AZERTY\n\
+         MoveForward = KeyZ\n\
+         strafeleft = keyq\n\
+         \n\
+         mouse_sensitivity = 0.002\n\
+         move_speed=0.5\n",
+    )
+    .unwrap();
+    assert_eq!(config.key(Action::MoveForward), Key::KeyZ);
+    assert_eq!(config.key(Action::StrafeLeft), Key::KeyQ);
+    assert_eq!(config.key(Action::MoveBack), Key::KeyS);
+    assert_eq!(config.mouse_sensitivity, 0.002);
+    assert_eq!(config.move_speed, 0.5);
+}
+
+#[test]
+fn default_keys_are_all_different() {
+    let config = InputConfig::default();
+    for &action in Action::ALL {
+        assert_eq!(config.action(config.key(action)), Some(action));
+    }
+}
+
+#[test]
+fn azerty_example_swaps_with_the_default_holder() {
+    // From the module docs: KeyZ is ToggleWireframe's by default.
+    let config = InputConfig::parse(
+        "# AZERTY movement\n\
+         MoveForward = KeyZ\n\
+         StrafeLeft = KeyQ\n\
+         mouse_sensitivity = 0.002\n",
+    )
+    .unwrap();
+    assert_eq!(config.key(Action::MoveForward), Key::KeyZ);
+    assert_eq!(config.key(Action::ToggleWireframe), Key::KeyW);
+    assert_eq!(config.key(Action::StrafeLeft), Key::KeyQ);
+    assert_eq!(config.action(Key::KeyA), None);
+}
+
+#[test]
+fn one_key_for_two_actions_names_both() {
+    let (line, message) = parse_error("MoveForward = KeyZ\nToggleWireframe = KeyZ");
+    assert_eq!(line, 2);
+    assert!(message.contains("MoveForward"), "{message}");
+    assert!(message.contains("ToggleWireframe"), "{message}");
+}
+
+#[test]
+fn speeds_must_be_above_zero() {
+    for value in ["NaN", "inf", "-inf", "0", "-0.1"] {
+        let (line, message) =
+            parse_error(&format!("move_speed = 0.2\nmouse_sensitivity = {value}"));
+        assert_eq!(line, 2, "{value}");
+        assert!(message.contains("mouse_sensitivity"), "{message}");
+        assert_eq!(
+            parse_error(&format!("move_speed = {value}")).0,
+            1,
+            "{value}"
+        );
+    }
+}
+
+fn parse_error(text: &str) -> (usize, String) {
+    match InputConfig::parse(text) {
+        Err(InputConfigError::Parse { line, message }) => (line, message),
+        other => panic!("expected a parse error, got {other:?}"),
+    }
+}
+
+#[test]
+fn unknown_key_lists_the_valid_keys() {
+    let (line, message) = parse_error("Quit = Escape\nMoveForward = KeyWW");
+    assert_eq!(line, 2);
+    assert!(message.contains("KeyWW"), "{message}");
+    assert!(message.contains("KeyA"), "{message}");
+    assert!(message.contains("ArrowUp"), "{message}");
+}
+
+#[test]
+fn unknown_action_lists_the_valid_actions() {
+    let (line, message) = parse_error("Jump = Space");
+    assert_eq!(line, 1);
+    assert!(message.contains("MoveForward"), "{message}");
+    assert!(message.contains("mouse_sensitivity"), "{message}");
+}
+
+#[test]
+fn bad_lines_are_reported() {
+    assert_eq!(parse_error("\n\nmove_speed = fast").0, 3);
+    assert_eq!(parse_error("MoveForward KeyW").0, 1);
+}
@@ -0,0 +1,75 @@
+mod common;
+
+use std::rc::Rc;
+
+use common::{object, render, world};
+
+use engine::Object;
+use engine::mesh::Color;
+use engine::mesh::Mesh;
+use engine::mesh::cube::CubeMesh;
+use nalgebra::UnitQuaternion;
+
+const GREEN: Color = Color {
+    r: 40,
+    g: 200,
+    b: 40,
+    a: 255,
+};
+
+#[test]
+fn instances_share_one_mesh() {
+    let cube: Rc<dyn Mesh> = Rc::new(CubeMesh::new(GREEN));
+    let objects: Vec<Object> = (0..200)
+        .map(|i| Object::instance(Rc::clone(&cube), (i % 20) as f32, (i / 20) as f32, 30.0))
+        .collect();
+    assert_eq!(Rc::strong_count(&cube), 201);
+    let world = world(objects);
+    assert!(world.models.iter().all(|o| Rc::ptr_eq(&o.mesh, &cube)));
+}
+
+#[test]
+fn instances_render_like_separate_meshes() {
+    let cube: Rc<dyn Mesh> = Rc::new(CubeMesh::new(GREEN));
+    let offsets = [(-1.5, 0.0, 2.0), (1.5, 0.0, 2.0), (0.0, 1.0, 6.0)];
+    let mut shared = world(
+        offsets
+            .iter()
+            .map(|&(x, y, z)| Object::instance(Rc::clone(&cube), x, y, z))
+            .collect(),
+    );
+    let mut owned = world(
+        offsets
+            .iter()
+            .map(|&(x, y, z)| object(CubeMesh::new(GREEN), x, y, z))
+            .collect(),
+    );
+    assert_eq!(render(&mut shared), render(&mut owned));
+    assert_eq!(shared.stats().triangles_submitted, 36);
+}
+
+#[test]
+fn turned_instances_are_lit_from_their_own_vertices() {
+    // Each instance faces the light differently, so shading one with
+    // another's world positions would show.
+    let cube: Rc<dyn Mesh> = Rc::new(CubeMesh::new(GREEN));
+    let turns = [(-1.5, 0.4), (0.0, 1.1), (1.5, 2.3)];
+    let place = |object: Object, angle: f32| Object {
+        rotation: UnitQuaternion::from_euler_angles(angle, angle * 0.7, 0.0),
+        scale: 0.6,
+        ..object
+    };
+    let mut shared = world(
+        turns
+            .iter()
+            .map(|&(x, angle)| place(Object::instance(Rc::clone(&cube), x, 0.0, 0.0), angle))
+            .collect(),
+    );
+    let mut owned = world(
+        turns
+            .iter()
+            .map(|&(x, angle)| place(object(CubeMesh::new(GREEN), x, 0.0, 0.0), angle))
+            .collect(),
+    );
+    assert_eq!(render(&mut shared), render(&mut owned));
+}
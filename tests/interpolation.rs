@@ -0,0 +1,105 @@
+mod common;
+
+use std::f32::consts::FRAC_PI_2;
+
+use common::{object, render, world};
+use engine::mesh::Color;
+use engine::mesh::cube::CubeMesh;
+use engine::{Animation, Object, World};
+use nalgebra::{Point3, UnitQuaternion, Vector3};
+
+fn cube_world() -> World {
+    world(vec![object(CubeMesh::new(Color::WHITE), 0.0, 0.0, 0.0)])
+}
+
+fn origin_of(world: &World) -> Point3<f32> {
+    world.world_matrices()[0].transform_point(&Point3::origin())
+}
+
+#[test]
+fn new_objects_are_drawn_where_they_are() {
+    let mut world = cube_world();
+    world.models[0].offset_x = 2.0;
+    world.interpolation = 0.25;
+    assert!(world.models[0].previous.is_none());
+    assert_eq!(world.world_matrices()[0], world.models[0].model_mat());
+}
+
+#[test]
+fn offsets_and_scale_blend_from_the_previous_update() {
+    let mut world = cube_world();
+    world.update(1.0 / 60.0);
+    world.models[0].offset_x = 2.0;
+    world.models[0].scale = 3.0;
+
+    world.interpolation = 0.0;
+    assert_eq!(origin_of(&world), Point3::origin());
+    world.interpolation = 0.5;
+    assert_eq!(origin_of(&world), Point3::new(1.0, 0.0, 0.0));
+    let right = world.world_matrices()[0].transform_vector(&Vector3::x());
+    assert!((right.norm() - 2.0).abs() < 1e-6);
+    world.interpolation = 1.0;
+    assert_eq!(world.world_matrices()[0], world.models[0].model_mat());
+}
+
+#[test]
+fn rotations_turn_along_the_shorter_arc() {
+    let mut world = cube_world();
+    world.update(1.0 / 60.0);
+    world.models[0].rotation = UnitQuaternion::from_axis_angle(&Vector3::y_axis(), FRAC_PI_2);
+    world.interpolation = 0.5;
+    let halfway = UnitQuaternion::from_axis_angle(&Vector3::y_axis(), FRAC_PI_2 / 2.0);
+    let expected = halfway.to_homogeneous();
+    assert!((world.world_matrices()[0] - expected).abs().max() < 1e-6);
+}
+
+#[test]
+fn children_follow_their_interpolated_parent() {
+    let mut world = cube_world();
+    let child = world
+        .add_object(Object::new(CubeMesh::new(Color::WHITE), 0.0, 1.0, 0.0))
+        .unwrap();
+    world.set_parent(child, Some(0)).unwrap();
+    world.update(1.0 / 60.0);
+    world.models[0].offset_z = 4.0;
+    world.interpolation = 0.25;
+    let at = world.world_matrices()[child].transform_point(&Point3::origin());
+    assert_eq!(at, Point3::new(0.0, 1.0, 1.0));
+}
+
+#[test]
+fn snapped_objects_skip_the_blend() {
+    let mut world = cube_world();
+    world.update(1.0 / 60.0);
+    world.models[0].offset_y = -3.0;
+    world.models[0].snap();
+    world.interpolation = 0.5;
+    assert_eq!(origin_of(&world), Point3::new(0.0, -3.0, 0.0));
+}
+
+#[test]
+fn frames_between_updates_show_the_blend() {
+    let spinning =
+        object(CubeMesh::new(Color::WHITE), 0.0, 0.0, 0.0).with_animation(Animation::Spin {
+            axis: Vector3::y_axis(),
+            speed: 3.0,
+        });
+    let mut world = world(vec![spinning]);
+    world.update(0.1);
+    world.interpolation = 0.0;
+    let before = render(&mut world);
+    world.interpolation = 1.0;
+    let after = render(&mut world);
+    assert_ne!(before, after);
+
+    // The same cube placed by hand where the update found it.
+    let mut still = world_at(world.models[0].previous.unwrap().rotation);
+    assert_eq!(render(&mut still), before);
+}
+
+fn world_at(rotation: UnitQuaternion<f32>) -> World {
+    world(vec![Object {
+        rotation,
+        ..object(CubeMesh::new(Color::WHITE), 0.0, 0.0, 0.0)
+    }])
+}
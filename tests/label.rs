@@ -0,0 +1,117 @@
+mod common;
+
+use common::{HEIGHT, WIDTH, object, pixel, render, world};
+use engine::Label;
+use engine::mesh::Color;
+use engine::mesh::cube::CubeMesh;
+use engine::overlay::GLYPH_HEIGHT;
+use nalgebra::Point3;
+
+const YELLOW: [u8; 4] = [255, 255, 0, 255];
+
+/// Where in `frame` pixels differ from `plain`, as `(x, y)`.
+fn changed(frame: &[u8], plain: &[u8]) -> Vec<(u32, u32)> {
+    let mut changed = Vec::new();
+    for y in 0..HEIGHT {
+        for x in 0..WIDTH {
+            if pixel(frame, x, y) != pixel(plain, x, y) {
+                changed.push((x, y));
+            }
+        }
+    }
+    changed
+}
+
+fn label(x: f32, y: f32, z: f32) -> Label {
+    Label::new(Point3::new(x, y, z), "I", Color::YELLOW)
+}
+
+#[test]
+fn text_sits_above_right_of_its_anchor() {
+    let mut world = world(Vec::new());
+    let plain = render(&mut world);
+    world.labels.push(label(0.0, 0.0, 0.0));
+    let frame = render(&mut world);
+    assert_eq!(world.stats().labels_drawn, 1);
+    let changed = changed(&frame, &plain);
+    assert!(!changed.is_empty());
+    let center = (WIDTH / 2, HEIGHT / 2);
+    for &(x, y) in &changed {
+        assert!(x > center.0 && y < center.1, "({x}, {y})");
+        assert!(center.1 - y <= GLYPH_HEIGHT + 4, "({x}, {y})");
+        assert_eq!(pixel(&frame, x, y), YELLOW);
+    }
+}
+
+#[test]
+fn leader_line_starts_at_the_anchor() {
+    let mut world = world(Vec::new());
+    world.labels.push(Label {
+        leader: true,
+        ..label(0.0, 0.0, 0.0)
+    });
+    let frame = render(&mut world);
+    assert_eq!(pixel(&frame, WIDTH / 2, HEIGHT / 2), YELLOW);
+    assert_eq!(pixel(&frame, WIDTH / 2 + 4, HEIGHT / 2 - 4), YELLOW);
+}
+
+#[test]
+fn labels_behind_the_camera_are_skipped() {
+    let mut world = world(Vec::new());
+    let plain = render(&mut world);
+    world.labels.push(label(0.0, 0.0, -8.0));
+    assert_eq!(render(&mut world), plain);
+    assert_eq!(world.stats().labels_drawn, 0);
+}
+
+#[test]
+fn half_off_screen_labels_clip_without_wrapping() {
+    let mut world = world(Vec::new());
+    let plain = render(&mut world);
+    // The camera looks down +z, so +x is on the left of the screen. One
+    // label is anchored near the right edge and runs off it; a long one is
+    // anchored off the left edge and runs back on.
+    for (x, text, near_edge) in [
+        (-1.9, "MMMMMMMMMM", WIDTH - 6..WIDTH),
+        (3.0, "MMMMMMMMMMMMMMMMMMMM", 0..6),
+    ] {
+        world.labels = vec![Label::new(Point3::new(x, 0.0, 0.0), text, Color::YELLOW)];
+        let frame = render(&mut world);
+        assert_eq!(world.stats().labels_drawn, 1);
+        let changed = changed(&frame, &plain);
+        assert!(changed.iter().any(|(x, _)| near_edge.contains(x)));
+        // Nothing spills onto the rows below the text.
+        assert!(
+            changed
+                .iter()
+                .all(|&(_, y)| (HEIGHT / 2 - GLYPH_HEIGHT - 4..HEIGHT / 2).contains(&y)),
+            "{changed:?}"
+        );
+    }
+}
+
+#[test]
+fn occluded_labels_hide_only_with_a_depth_buffer() {
+    let cube = object(CubeMesh::new(Color::rgb(40, 200, 40)), 0.0, 0.0, 0.0);
+    let mut world = world(vec![cube]);
+    world.labels.push(Label {
+        hide_when_occluded: true,
+        ..label(0.0, 0.0, 3.0)
+    });
+    render(&mut world);
+    assert_eq!(world.stats().labels_drawn, 1);
+
+    world.depth_buffer = true;
+    render(&mut world);
+    assert_eq!(world.stats().labels_drawn, 0);
+
+    // Pinned to the face of the cube it is on, it still shows.
+    world.labels[0].position = Point3::new(0.0, 0.0, -1.0);
+    render(&mut world);
+    assert_eq!(world.stats().labels_drawn, 1);
+
+    world.set_ssaa(2).unwrap();
+    world.labels[0].position = Point3::new(0.0, 0.0, 3.0);
+    render(&mut world);
+    assert_eq!(world.stats().labels_drawn, 0);
+}
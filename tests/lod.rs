@@ -0,0 +1,133 @@
+mod common;
+
+use std::rc::Rc;
+
+use common::{HEIGHT, WIDTH, object, pixel, render, world};
+use engine::lod::{Lods, select_lod, sphere_lods, terrain_lods};
+use engine::mesh::cube::CubeMesh;
+use engine::mesh::quad::QuadMesh;
+use engine::mesh::terrain::noise_heightmap;
+use engine::mesh::{Color, MeshMut};
+use nalgebra::Vector3;
+
+const RED: Color = Color::rgb(220, 30, 30);
+const BLUE: Color = Color::rgb(30, 30, 220);
+
+/// A red quad up to 6 units away and a blue one past that.
+fn red_then_blue() -> Lods {
+    vec![
+        (6.0, Rc::new(QuadMesh::new(2.0, 2.0, RED))),
+        (f32::INFINITY, Rc::new(QuadMesh::new(2.0, 2.0, BLUE))),
+    ]
+}
+
+fn is_red(world: &mut engine::World) -> bool {
+    let p = pixel(&render(world), WIDTH / 2, HEIGHT / 2);
+    p[0] > p[2]
+}
+
+#[test]
+fn the_first_level_covering_the_distance_wins() {
+    let lods = red_then_blue();
+    assert_eq!(select_lod(&lods, 1.0, 0), 0);
+    assert_eq!(select_lod(&lods, 6.0, 0), 0);
+    assert_eq!(select_lod(&lods, 100.0, 0), 1);
+    // An out of range level starts over.
+    assert_eq!(select_lod(&lods, 1.0, 7), 0);
+}
+
+#[test]
+fn levels_hold_within_ten_percent_of_the_threshold() {
+    let lods = red_then_blue();
+    assert_eq!(select_lod(&lods, 6.5, 0), 0);
+    assert_eq!(select_lod(&lods, 6.7, 0), 1);
+    assert_eq!(select_lod(&lods, 5.5, 1), 1);
+    assert_eq!(select_lod(&lods, 5.3, 1), 0);
+}
+
+#[test]
+fn objects_draw_the_level_for_their_distance() {
+    let mut world = world(vec![
+        object(QuadMesh::new(2.0, 2.0, RED), 0.0, 0.0, 0.0).with_lods(red_then_blue()),
+    ]);
+    // The camera is 5 units away.
+    assert!(is_red(&mut world));
+    assert_eq!(world.lod_level(0), Some(0));
+
+    for (z, red) in [(1.5, true), (2.0, false), (0.5, false), (0.0, true)] {
+        world.models[0].offset_z = z;
+        assert_eq!(is_red(&mut world), red, "at z = {z}");
+        assert_eq!(world.lod_level(0), Some(if red { 0 } else { 1 }));
+    }
+}
+
+#[test]
+fn objects_without_levels_draw_their_mesh() {
+    let mut world = world(vec![object(QuadMesh::new(2.0, 2.0, RED), 0.0, 0.0, 40.0)]);
+    assert!(is_red(&mut world));
+    assert_eq!(world.lod_level(0), None);
+    assert_eq!(world.lod_level(3), None);
+}
+
+#[test]
+fn levels_follow_their_object_when_another_is_removed() {
+    let far = object(QuadMesh::new(2.0, 2.0, RED), 0.0, 0.0, 20.0).with_lods(red_then_blue());
+    let mut world = world(vec![object(CubeMesh::new(RED), 5.0, 0.0, 0.0), far]);
+    render(&mut world);
+    assert_eq!(world.lod_level(1), Some(1));
+    world.remove_object(0);
+    assert_eq!(world.lod_level(0), Some(1));
+}
+
+#[test]
+fn worlds_refuse_invalid_levels() {
+    let mut broken = CubeMesh::new(RED);
+    broken.tris_mut()[0].v1 = 99;
+    let lods: Lods = vec![(f32::INFINITY, Rc::new(broken))];
+    let mut world = world(vec![]);
+    let err = world.add_object(object(CubeMesh::new(RED), 0.0, 0.0, 0.0).with_lods(lods));
+    assert!(err.is_err());
+    assert!(world.models.is_empty());
+}
+
+#[test]
+fn sphere_levels_get_coarser() {
+    let lods = sphere_lods(1.0, 16, &[5.0, 10.0, 20.0, f32::INFINITY], RED).unwrap();
+    let counts: Vec<usize> = lods
+        .iter()
+        .map(|(_, mesh)| mesh.primitives().triangles().count())
+        .collect();
+    assert!(
+        counts.windows(2).all(|pair| pair[0] > pair[1]),
+        "{counts:?}"
+    );
+    assert_eq!(lods[1].0, 10.0);
+    for (_, mesh) in &lods {
+        assert!(
+            mesh.verts()
+                .iter()
+                .all(|v| (v.coords.norm() - 1.0).abs() < 1e-4)
+        );
+    }
+}
+
+#[test]
+fn terrain_levels_keep_every_other_sample() {
+    let heights = noise_heightmap(17, 9, 0.2, 2, 7);
+    let lods = terrain_lods(
+        17,
+        9,
+        &heights,
+        Vector3::new(0.5, 2.0, 0.5),
+        |_| BLUE,
+        &[10.0, 20.0, 40.0, 80.0],
+    )
+    .unwrap();
+    let sizes: Vec<usize> = lods.iter().map(|(_, mesh)| mesh.verts().len()).collect();
+    assert_eq!(sizes, [17 * 9, 9 * 5, 5 * 3, 3 * 2]);
+    // Corners stay where they are.
+    for (_, mesh) in &lods {
+        assert_eq!(mesh.verts()[0], lods[0].1.verts()[0]);
+    }
+    assert!(terrain_lods(1, 4, &[0.0; 4], Vector3::repeat(1.0), |_| BLUE, &[1.0]).is_err());
+}
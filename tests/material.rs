@@ -0,0 +1,159 @@
+mod common;
+
+use common::{HEIGHT, WIDTH, object, pixel, render, world};
+
+use engine::mesh::Color;
+use engine::mesh::quad::QuadMesh;
+use engine::{Material, MaterialLibrary, MaterialOverride};
+
+const RED: Color = Color {
+    r: 255,
+    g: 0,
+    b: 0,
+    a: 255,
+};
+const BLUE: Color = Color {
+    r: 0,
+    g: 0,
+    b: 255,
+    a: 255,
+};
+
+fn quad() -> QuadMesh {
+    QuadMesh::new(1.0, 1.0, RED)
+}
+
+#[test]
+fn inserting_a_name_again_keeps_its_handle() {
+    let mut library = MaterialLibrary::new();
+    let paint = library.insert("paint", Material::new(RED));
+    assert_eq!(library.insert("paint", Material::new(BLUE)), paint);
+    assert_eq!(library.len(), 1);
+    assert_eq!(library.get(paint).unwrap().color, BLUE);
+    assert_eq!(library.handle("paint"), Some(paint));
+    assert_eq!(library.name(paint), Some("paint"));
+}
+
+#[test]
+fn overrides_fall_back_to_later_library_edits() {
+    let mut library = MaterialLibrary::new();
+    let paint = library.insert("paint", Material::new(RED));
+    let glow = MaterialOverride {
+        emissive: Some(0.5),
+        ..MaterialOverride::default()
+    };
+
+    library.get_mut(paint).unwrap().color = BLUE;
+    let instance = library.instantiate_override(paint, &glow).unwrap();
+    assert_eq!(instance.color, BLUE);
+    assert_eq!(instance.emissive, 0.5);
+    assert_eq!(instance.diffuse, 1.0);
+}
+
+#[test]
+fn editing_a_material_restyles_every_user() {
+    let mut world = world(vec![]);
+    let paint = world.materials.insert("paint", Material::new(BLUE));
+    world.models = vec![
+        object(quad(), 1.0, 0.0, 0.0).with_material(paint),
+        object(quad(), -1.0, 0.0, 0.0).with_material(paint),
+    ];
+    let (left, right) = ((WIDTH / 4, HEIGHT / 2), (3 * WIDTH / 4, HEIGHT / 2));
+
+    let frame = render(&mut world);
+    assert_eq!(pixel(&frame, left.0, left.1)[0], 0);
+    assert_eq!(pixel(&frame, right.0, right.1)[0], 0);
+
+    world.materials.get_mut(paint).unwrap().color = RED;
+    let frame = render(&mut world);
+    assert!(pixel(&frame, left.0, left.1)[0] > 0);
+    assert!(pixel(&frame, right.0, right.1)[0] > 0);
+    assert_eq!(pixel(&frame, left.0, left.1)[2], 0);
+}
+
+#[test]
+fn objects_without_a_material_keep_mesh_colors() {
+    let mut world = world(vec![object(quad(), 0.0, 0.0, 0.0)]);
+    world.materials.insert("paint", Material::new(BLUE));
+    let frame = render(&mut world);
+    assert_eq!(pixel(&frame, WIDTH / 2, HEIGHT / 2)[2], 0);
+}
+
+#[test]
+fn specular_highlight_brightens_surfaces_facing_the_reflection() {
+    let dull = Color {
+        r: 100,
+        g: 100,
+        b: 100,
+        a: 255,
+    };
+    let mut world = world(vec![object(QuadMesh::new(1.0, 1.0, dull), 0.0, 0.0, 0.0)]);
+    world.gamma_correct = false;
+    // Light from the camera, so the highlight faces straight back at it.
+    world.light.position = world.camera.position;
+    let matte = world.materials.insert("matte", Material::new(dull));
+    let shiny = world.materials.insert(
+        "shiny",
+        Material {
+            specular: 0.5,
+            shininess: 8.0,
+            ..Material::new(dull)
+        },
+    );
+
+    world.models[0].material = Some(matte);
+    let matte = pixel(&render(&mut world), WIDTH / 2, HEIGHT / 2);
+    world.models[0].material = Some(shiny);
+    let shiny = pixel(&render(&mut world), WIDTH / 2, HEIGHT / 2);
+    assert_eq!(shiny[0], matte[0] + 50);
+}
+
+#[test]
+fn unlit_surfaces_keep_their_exact_color() {
+    let green = Color {
+        r: 0,
+        g: 255,
+        b: 0,
+        a: 255,
+    };
+    let mut world = world(vec![object(QuadMesh::new(1.0, 1.0, green), 0.0, 0.0, 0.0)]);
+    // Lit from behind, so the side facing the camera only gets ambient.
+    world.light.position = nalgebra::Point3::new(0.0, 0.0, 5.0);
+    let lit = pixel(&render(&mut world), WIDTH / 2, HEIGHT / 2);
+    assert!(lit[1] < 255);
+
+    let glow = world.materials.insert(
+        "glow",
+        Material {
+            unlit: true,
+            ..Material::new(green)
+        },
+    );
+    world.models[0].material = Some(glow);
+    assert_eq!(
+        pixel(&render(&mut world), WIDTH / 2, HEIGHT / 2),
+        [0, 255, 0, 255]
+    );
+
+    // Unlit ignores fog as well as light.
+    world.fog = Some(engine::fog::Fog {
+        color: RED,
+        start: 0.0,
+        end: 1.0,
+        mode: engine::fog::FogMode::Linear,
+    });
+    assert_eq!(
+        pixel(&render(&mut world), WIDTH / 2, HEIGHT / 2),
+        [0, 255, 0, 255]
+    );
+
+    let lit_again = MaterialOverride {
+        unlit: Some(false),
+        ..MaterialOverride::default()
+    };
+    world.models[0].material_override = lit_again;
+    assert_ne!(
+        pixel(&render(&mut world), WIDTH / 2, HEIGHT / 2),
+        [0, 255, 0, 255]
+    );
+}
@@ -0,0 +1,75 @@
+mod common;
+
+use std::rc::Rc;
+
+use common::{object, world};
+
+use engine::Object;
+use engine::mesh::cube::CubeMesh;
+use engine::mesh::obj::ObjMesh;
+use engine::mesh::{Color, ImportOptions, Mesh, MeshError, MeshMut};
+
+const RED: Color = Color {
+    r: 255,
+    g: 0,
+    b: 0,
+    a: 255,
+};
+
+fn broken_cube() -> CubeMesh {
+    let mut cube = CubeMesh::new(RED);
+    cube.tris_mut()[5].v3 = 42;
+    cube
+}
+
+#[test]
+fn valid_meshes_pass() {
+    assert_eq!(CubeMesh::new(RED).validate(), Ok(()));
+    assert_eq!(CubeMesh::new(RED).degenerate_triangles(), 0);
+}
+
+#[test]
+fn errors_name_the_triangle_and_index() {
+    let err = broken_cube().validate().unwrap_err();
+    assert_eq!(
+        err,
+        MeshError::IndexOutOfRange {
+            triangle: 5,
+            index: 42,
+            vertex_count: 8,
+        }
+    );
+    assert_eq!(
+        err.to_string(),
+        "triangle 5 refers to vertex 42, but the mesh has 8"
+    );
+}
+
+#[test]
+fn degenerate_triangles_are_counted_but_valid() {
+    let mut cube = CubeMesh::new(RED);
+    let tri = &mut cube.tris_mut()[0];
+    tri.v2 = tri.v1;
+    assert_eq!(cube.validate(), Ok(()));
+    assert_eq!(cube.degenerate_triangles(), 1);
+}
+
+#[test]
+fn worlds_refuse_invalid_meshes() {
+    let mut world = world(vec![]);
+    let bad = Object {
+        mesh: Rc::new(broken_cube()),
+        ..object(CubeMesh::new(RED), 0.0, 0.0, 0.0)
+    };
+    let err = world.add_object(bad).unwrap_err();
+    assert!(err.to_string().contains("triangle 5"));
+    assert!(world.models.is_empty());
+}
+
+#[test]
+fn obj_files_with_degenerate_faces_still_load() {
+    let obj = b"v 0 0 0\nv 1 0 0\nv 0 1 0\nf 1 2 3\nf 1 1 2\n";
+    let mesh = ObjMesh::from_bytes(obj, ImportOptions::default()).unwrap();
+    assert_eq!(mesh.tris().len(), 2);
+    assert_eq!(mesh.degenerate_triangles(), 1);
+}
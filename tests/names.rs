@@ -0,0 +1,129 @@
+mod common;
+
+use common::{object, render, world};
+
+use engine::mesh::Color;
+use engine::mesh::cube::CubeMesh;
+use engine::{AddObjectError, DuplicateNames, HierarchyError, Label, Object, ObjectRef};
+use nalgebra::Point3;
+
+const RED: Color = Color::rgb(255, 0, 0);
+
+fn cube(name: &str, x: f32) -> Object {
+    object(CubeMesh::new(RED), x, 0.0, 0.0).with_name(name)
+}
+
+#[test]
+fn names_follow_objects_as_indices_shift() {
+    let mut world = world(vec![]);
+    assert_eq!(world.add_object(cube("a", -2.0)), Ok(0));
+    assert_eq!(world.add_object(cube("b", 0.0)), Ok(1));
+    assert_eq!(world.add_object(cube("c", 2.0)), Ok(2));
+    assert_eq!(world.object("b").map(|b| b.offset_x), Some(0.0));
+    assert!(world.object("d").is_none());
+
+    world.remove_object(0);
+    assert!(world.object("a").is_none());
+    assert_eq!(world.resolve(&"b".into()), Some(0));
+    assert_eq!(world.resolve(&"c".into()), Some(1));
+    assert_eq!(world.resolve(&ObjectRef::Index(1)), Some(1));
+    assert_eq!(world.resolve(&ObjectRef::Index(2)), None);
+
+    world.object_mut("c").unwrap().offset_y = 3.0;
+    assert_eq!(world.models[1].offset_y, 3.0);
+}
+
+#[test]
+fn names_are_unique() {
+    let mut world = world(vec![]);
+    world.add_object(cube("a", 0.0)).unwrap();
+    assert_eq!(
+        world.add_object(cube("a", 1.0)),
+        Err(AddObjectError::DuplicateName("a".into()))
+    );
+    assert_eq!(world.models.len(), 1);
+    // The name is free again once its object is gone.
+    world.remove_object(0);
+    assert_eq!(world.add_object(cube("a", 1.0)), Ok(0));
+}
+
+#[test]
+fn duplicates_in_a_built_scene_are_listed() {
+    let mut world = world(vec![
+        cube("rock", 0.0),
+        cube("tree", 1.0),
+        cube("rock", 2.0),
+        cube("tree", 3.0),
+        cube("rock", 4.0),
+        cube("bush", 5.0),
+    ]);
+    let err = world.index_names().unwrap_err();
+    assert_eq!(err, DuplicateNames(vec!["rock".into(), "tree".into()]));
+    assert_eq!(
+        err.to_string(),
+        "more than one object named \"rock\", \"tree\""
+    );
+    // The first of each is the one found.
+    assert_eq!(world.object("rock").map(|rock| rock.offset_x), Some(0.0));
+
+    world.models.truncate(2);
+    world.models.push(cube("bush", 7.0));
+    assert_eq!(world.index_names(), Ok(()));
+    // Found even before being indexed.
+    world.models.push(cube("fern", 8.0));
+    assert_eq!(world.resolve(&"fern".into()), Some(3));
+}
+
+#[test]
+fn selection_and_parents_take_names() {
+    let mut world = world(vec![cube("sun", 0.0), cube("planet", 2.0)]);
+    assert_eq!(world.select("planet"), Some(1));
+    assert_eq!(world.selected, Some(1));
+    assert_eq!(world.select("pluto"), None);
+    assert_eq!(world.selected, Some(1));
+    assert_eq!(world.select(0), Some(0));
+
+    world.attach("planet", Some("sun".into())).unwrap();
+    assert_eq!(world.models[1].parent, Some(0));
+    assert_eq!(
+        world.attach("planet", Some("pluto".into())),
+        Err(HierarchyError::NoSuchName("pluto".into()))
+    );
+    assert_eq!(world.attach(5, None), Err(HierarchyError::NoSuchObject(5)));
+    assert!(matches!(
+        world.attach("sun", Some("planet".into())),
+        Err(HierarchyError::Cycle { .. })
+    ));
+    world.attach("planet", None).unwrap();
+    assert_eq!(world.models[1].parent, None);
+}
+
+#[test]
+fn pinned_labels_move_with_their_object() {
+    let mut world = world(vec![cube("a", 0.0), cube("b", 1.0)]);
+    let tag =
+        |object: ObjectRef| Label::new(Point3::origin(), "X", Color::YELLOW).pinned_to(object);
+    world.labels = vec![tag("b".into()), tag(ObjectRef::Index(1))];
+    render(&mut world);
+    assert_eq!(world.stats().labels_drawn, 2);
+
+    // Behind the camera, the labels go out of view with their object.
+    world.models[1].offset_z = -10.0;
+    render(&mut world);
+    assert_eq!(world.stats().labels_drawn, 0);
+    world.models[1].offset_z = 0.0;
+
+    // Removing an earlier object keeps both on "b"; removing "b" drops
+    // the one pinned by index and hides the one pinned by name.
+    world.remove_object(0);
+    assert_eq!(world.labels[1].object, Some(ObjectRef::Index(0)));
+    render(&mut world);
+    assert_eq!(world.stats().labels_drawn, 2);
+    world.remove_object(0);
+    assert_eq!(world.labels.len(), 1);
+    render(&mut world);
+    assert_eq!(world.stats().labels_drawn, 0);
+    world.add_object(cube("b", 0.0)).unwrap();
+    render(&mut world);
+    assert_eq!(world.stats().labels_drawn, 1);
+}
@@ -0,0 +1,45 @@
+mod common;
+
+use common::{object, pixel, render, world};
+
+use engine::CullMode;
+use engine::mesh::Color;
+use engine::mesh::quad::QuadMesh;
+use nalgebra::{Point3, UnitQuaternion, Vector3};
+
+const GRAY: Color = Color {
+    r: 180,
+    g: 180,
+    b: 180,
+    a: 255,
+};
+
+fn magenta(frame: &[u8]) -> usize {
+    frame.chunks(4).filter(|p| *p == [255, 0, 255, 255]).count()
+}
+
+#[test]
+fn normals_are_drawn_when_enabled() {
+    // From an angle, so the normals don't point straight at the camera.
+    let mut world = world(vec![object(QuadMesh::new(2.0, 2.0, GRAY), 0.0, 0.0, 0.0)]);
+    world.camera.position = Point3::new(3.0, 3.0, -5.0);
+    world.camera.look_at(Point3::origin());
+    assert_eq!(magenta(&render(&mut world)), 0);
+
+    world.show_normals = true;
+    let frame = render(&mut world);
+    assert!(magenta(&frame) > 0);
+    assert_eq!(pixel(&frame, 0, 0), [255, 255, 255, 255]);
+}
+
+#[test]
+fn culled_faces_have_no_normals() {
+    let mut quad = object(QuadMesh::new(2.0, 2.0, GRAY), 0.0, 0.0, 0.0);
+    quad.rotation = UnitQuaternion::from_axis_angle(&Vector3::y_axis(), 3.0);
+    let mut world = world(vec![quad]);
+    world.show_normals = true;
+    assert_eq!(magenta(&render(&mut world)), 0);
+
+    world.cull_mode = CullMode::None;
+    assert!(magenta(&render(&mut world)) > 0);
+}
@@ -0,0 +1,156 @@
+mod common;
+
+use std::io::{self, Cursor};
+
+use common::{HEIGHT, WHITE, WIDTH, object, pixel, render, world};
+
+use engine::asset::MemoryResolver;
+use engine::mesh::obj::ObjMesh;
+use engine::mesh::{Color, ImportOptions, Mesh};
+
+const CUBE_OBJ: &[u8] = b"\
+mtllib cube.mtl
+v -1 -1 -1
+v 1 -1 -1
+v 1 1 -1
+v -1 1 -1
+v -1 -1 1
+v 1 -1 1
+v 1 1 1
+v -1 1 1
+usemtl red
+f 1 4 3 2
+f 5 6 7 8
+f 1 2 6 5
+usemtl blue
+f 4 8 7 3
+f 1 5 8 4
+f 2/1 3/2 7/3 6/4
+";
+
+const CUBE_MTL: &[u8] = b"\
+newmtl red
+Ka 0.1 0 0
+Kd 1 0 0
+Ks 0.5 0.5 0.5
+Ns 64
+newmtl blue
+Kd 0 0 1
+d 0.5
+illum 0
+";
+
+const GRAY: Color = Color {
+    r: 180,
+    g: 180,
+    b: 180,
+    a: 255,
+};
+
+#[test]
+fn quads_are_triangulated() {
+    let mesh = ObjMesh::from_bytes(CUBE_OBJ, ImportOptions::default()).unwrap();
+    assert_eq!(mesh.verts().len(), 8);
+    assert_eq!(mesh.tris().len(), 12);
+    assert!(mesh.tris().iter().all(|t| t.color == GRAY));
+}
+
+#[test]
+fn materials_come_from_the_resolver() {
+    let resolver = MemoryResolver::new().with("cube.mtl", CUBE_MTL);
+    let mesh = ObjMesh::from_bytes_with(CUBE_OBJ, ImportOptions::default(), &resolver).unwrap();
+    assert_eq!(mesh.tris()[0].color.r, 255);
+    assert_eq!(mesh.tris()[11].color.b, 255);
+}
+
+#[test]
+fn closures_resolve_materials() {
+    let resolver = |path: &str| -> io::Result<Vec<u8>> {
+        assert_eq!(path, "cube.mtl");
+        Ok(CUBE_MTL.to_vec())
+    };
+    let mesh = ObjMesh::from_bytes_with(CUBE_OBJ, ImportOptions::default(), &resolver).unwrap();
+    assert_eq!(mesh.tris()[6].color.b, 255);
+}
+
+#[test]
+fn reader_matches_bytes() {
+    let mesh = ObjMesh::from_reader(Cursor::new(CUBE_OBJ), ImportOptions::default()).unwrap();
+    assert_eq!(mesh.tris().len(), 12);
+}
+
+#[test]
+fn negative_indices_are_relative() {
+    let obj = b"v 0 0 0\nv 1 0 0\nv 0 1 0\nf -3 -2 -1\n";
+    let mesh = ObjMesh::from_bytes(obj, ImportOptions::default()).unwrap();
+    let tri = mesh.tris()[0];
+    assert_eq!((tri.v1, tri.v2, tri.v3), (0, 2, 1));
+}
+
+#[test]
+fn out_of_range_index_reports_the_line() {
+    let obj = b"v 0 0 0\nv 1 0 0\nf 1 2 3\n";
+    let err = ObjMesh::from_bytes(obj, ImportOptions::default()).unwrap_err();
+    assert_eq!(err.to_string(), "line 3: face index 3 out of range");
+}
+
+#[test]
+fn counter_clockwise_faces_render_front_facing() {
+    // A single face looking at the camera, counter-clockwise as OBJ expects.
+    let obj = b"v 1 -1 0\nv 1 1 0\nv -1 1 0\nv -1 -1 0\nf 4 3 2 1\n";
+    let mesh = ObjMesh::from_bytes(obj, ImportOptions::default()).unwrap();
+    let mut world = world(vec![object(mesh, 0.0, 0.0, 0.0)]);
+    let frame = render(&mut world);
+    assert_ne!(pixel(&frame, WIDTH / 2, HEIGHT / 2), WHITE);
+}
+
+#[test]
+fn mtl_properties_attach_to_triangle_ranges() {
+    let resolver = MemoryResolver::new().with("cube.mtl", CUBE_MTL);
+    let mesh = ObjMesh::from_bytes_with(CUBE_OBJ, ImportOptions::default(), &resolver).unwrap();
+    let ranges = mesh.materials();
+    assert_eq!(ranges.len(), 2);
+    let (red_tris, red) = &ranges[0];
+    let (blue_tris, blue) = &ranges[1];
+    assert_eq!((red_tris.clone(), blue_tris.clone()), (0..6, 6..12));
+    assert_eq!((red.specular, red.shininess), (0.5, 64.0));
+    assert_eq!(red.color.a, 255);
+    assert_eq!(blue.color.a, 128);
+    assert!(blue.unlit && !red.unlit);
+    assert_eq!(mesh.tris()[6].color.a, 128);
+    assert_eq!(mesh.material(3), Some(red));
+    assert_eq!(mesh.material(11), Some(blue));
+}
+
+#[test]
+fn unknown_materials_fall_back_to_gray() {
+    let resolver = MemoryResolver::new().with("cube.mtl", b"newmtl red\nKd 1 0 0\n");
+    let mesh = ObjMesh::from_bytes_with(CUBE_OBJ, ImportOptions::default(), &resolver).unwrap();
+    assert_eq!(mesh.tris()[0].color.r, 255);
+    assert_eq!(mesh.tris()[6].color, GRAY);
+    assert_eq!(mesh.tris()[11].color, GRAY);
+}
+
+#[test]
+fn two_material_cube_renders_both_colors() {
+    let mtl = b"newmtl red\nKd 1 0 0\nnewmtl blue\nKd 0 0 1\n";
+    let resolver = MemoryResolver::new().with("cube.mtl", mtl);
+    let mesh = ObjMesh::from_bytes_with(CUBE_OBJ, ImportOptions::default(), &resolver).unwrap();
+    let mut world = world(vec![object(mesh, 0.0, 0.0, 0.0)]);
+    // From above, so the red front and the blue top are both in view.
+    world.camera.position.y = 3.0;
+    let frame = render(&mut world);
+    let pixels: Vec<&[u8]> = frame.chunks(4).collect();
+    assert!(pixels.iter().any(|p| p[0] > 100 && p[2] == 0));
+    assert!(pixels.iter().any(|p| p[2] > 100 && p[0] == 0));
+}
+
+#[test]
+fn normalized_import_fits_the_extent() {
+    let obj = b"v 100 200 300\nv 140 200 300\nv 100 210 300\nf 1 2 3\n";
+    let mesh = ObjMesh::from_bytes(obj, ImportOptions::default().normalized(2.0)).unwrap();
+    let (min, max) = mesh.aabb();
+    assert!((max.x - min.x - 2.0).abs() < 1e-5);
+    assert!((max.y - min.y - 0.5).abs() < 1e-5);
+    assert!((nalgebra::center(&min, &max) - nalgebra::Point3::origin()).norm() < 1e-5);
+}
@@ -0,0 +1,135 @@
+mod common;
+
+use common::{object, world};
+
+use engine::camera::MIN_ORBIT_DISTANCE;
+use engine::mesh::Color;
+use engine::mesh::cube::CubeMesh;
+use engine::{Camera, CameraMode};
+use nalgebra::{Point3, Vector3};
+
+const GRAY: Color = Color {
+    r: 128,
+    g: 128,
+    b: 128,
+    a: 255,
+};
+
+fn orbiting(focus: Point3<f32>) -> Camera {
+    let mut camera = world(Vec::new()).camera;
+    camera.orbit_around(focus);
+    camera
+}
+
+fn distance(camera: &Camera) -> f32 {
+    match camera.mode {
+        CameraMode::Orbit { focus, distance } => {
+            assert!(((camera.position - focus).norm() - distance).abs() < 1e-4);
+            assert_eq!(camera.target(), focus);
+            distance
+        }
+        CameraMode::Fly => panic!("not orbiting"),
+    }
+}
+
+#[test]
+fn entering_orbit_keeps_the_view() {
+    let camera = orbiting(Point3::origin());
+    assert!((camera.position - Point3::new(0.0, 0.0, -5.0)).norm() < 1e-5);
+    assert!((distance(&camera) - 5.0).abs() < 1e-5);
+}
+
+#[test]
+fn rotating_stays_on_the_sphere() {
+    let focus = Point3::new(1.0, 2.0, 3.0);
+    let mut camera = orbiting(focus);
+    let start = distance(&camera);
+    for _ in 0..50 {
+        camera.rotate(0.3, 0.1);
+        assert!((distance(&camera) - start).abs() < 1e-4);
+    }
+}
+
+#[test]
+fn pitch_stops_short_of_the_poles() {
+    let mut camera = orbiting(Point3::origin());
+    for pitch in [100.0, -100.0] {
+        camera.rotate(0.0, pitch);
+        assert!(camera.yaw_pitch().1.abs() < std::f32::consts::FRAC_PI_2);
+        assert!(camera.forward().cross(&Vector3::y()).norm() > 1e-3);
+        let view = camera.generate_view_mat();
+        assert!(view.iter().all(|v| v.is_finite()));
+    }
+}
+
+#[test]
+fn dolly_is_clamped() {
+    let mut camera = orbiting(Point3::origin());
+    camera.dolly(2.0);
+    assert!((distance(&camera) - 3.0).abs() < 1e-5);
+    camera.dolly(100.0);
+    assert_eq!(distance(&camera), MIN_ORBIT_DISTANCE);
+    camera.dolly(-4.0);
+    assert!((distance(&camera) - MIN_ORBIT_DISTANCE - 4.0).abs() < 1e-5);
+}
+
+#[test]
+fn pan_moves_the_focus_in_the_view_plane() {
+    let mut camera = orbiting(Point3::origin());
+    let forward = camera.forward();
+    camera.pan(1.0, 2.0);
+    let CameraMode::Orbit { focus, .. } = camera.mode else {
+        panic!("not orbiting");
+    };
+    assert!((focus.coords.norm() - 5f32.sqrt()).abs() < 1e-5);
+    assert!(focus.coords.dot(&forward).abs() < 1e-5);
+    assert!((focus.y - 2.0).abs() < 1e-5);
+    assert_eq!(camera.forward(), forward);
+}
+
+#[test]
+fn flying_ignores_orbit_controls() {
+    let mut camera = world(Vec::new()).camera;
+    let position = camera.position;
+    camera.dolly(1.0);
+    camera.pan(1.0, 1.0);
+    assert_eq!(camera.position, position);
+    camera.orbit_around(Point3::origin());
+    camera.fly();
+    camera.rotate(0.5, 0.0);
+    assert_eq!(camera.position, position);
+}
+
+#[test]
+fn frame_all_moves_the_focus() {
+    let mut world = world(vec![object(CubeMesh::new(GRAY), 10.0, 0.0, 0.0)]);
+    world.camera.orbit_around(Point3::origin());
+    world.frame_all();
+    let CameraMode::Orbit { focus, .. } = world.camera.mode else {
+        panic!("not orbiting");
+    };
+    assert!((focus - Point3::new(10.0, 0.0, 0.0)).norm() < 1e-4);
+    distance(&world.camera);
+}
+
+#[test]
+fn pitching_back_and_forth_does_not_drift() {
+    let mut camera = Camera::looking_at(Point3::new(1.0, 2.0, -5.0), Point3::new(0.0, 0.5, 0.0));
+    let start = camera.forward();
+    let step = 10f32.to_radians();
+    for _ in 0..1000 {
+        camera.rotate(0.0, step);
+        camera.rotate(0.0, -step);
+    }
+    assert!((camera.forward() - start).norm() < 1e-4);
+    assert!((camera.up().y) > 0.0);
+}
+
+#[test]
+fn yaw_pitch_matches_the_view() {
+    let camera = Camera::looking_at(Point3::origin(), Point3::new(1.0, 1.0, 0.0));
+    let (yaw, pitch) = camera.yaw_pitch();
+    assert!((yaw - std::f32::consts::FRAC_PI_2).abs() < 1e-5);
+    assert!((pitch - std::f32::consts::FRAC_PI_4).abs() < 1e-5);
+    assert!(camera.right().y.abs() < 1e-6);
+}
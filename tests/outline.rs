@@ -0,0 +1,56 @@
+mod common;
+
+use common::{HEIGHT, WIDTH, object, pixel, render, world};
+
+use engine::mesh::Color;
+use engine::mesh::p_hack::PHackMesh;
+use engine::mesh::quad::QuadMesh;
+
+const GREEN: Color = Color {
+    r: 0,
+    g: 255,
+    b: 0,
+    a: 255,
+};
+
+fn outline_pixels(frame: &[u8], color: Color) -> usize {
+    frame
+        .chunks(4)
+        .filter(|p| *p == [color.r, color.g, color.b, 255])
+        .count()
+}
+
+#[test]
+fn selection_outlines_only_the_border() {
+    let mut world = world(vec![object(QuadMesh::new(2.0, 2.0, GREEN), 0.0, 0.0, 0.0)]);
+    let plain = render(&mut world);
+
+    world.selected = Some(0);
+    let outlined = render(&mut world);
+    let outline = world.outline_color;
+    assert!(outline_pixels(&outlined, outline) > 0);
+    // The diagonal shared by the quad's two triangles is not an outline.
+    assert_eq!(
+        pixel(&outlined, WIDTH / 2, HEIGHT / 2),
+        pixel(&plain, WIDTH / 2, HEIGHT / 2)
+    );
+
+    world.selected = None;
+    assert!(render(&mut world) == plain);
+}
+
+#[test]
+fn outline_is_drawn_over_nearer_objects() {
+    // With no depth buffer the outline is the last thing drawn, so it shows
+    // through anything in front of the selected object.
+    let cover = QuadMesh::new(4.0, 4.0, GREEN);
+    let mut world = world(vec![
+        object(PHackMesh::new(), 0.0, 0.0, 0.0),
+        object(cover, 0.0, 0.0, -2.0),
+    ]);
+    let outline = world.outline_color;
+    assert_eq!(outline_pixels(&render(&mut world), outline), 0);
+
+    world.selected = Some(0);
+    assert!(outline_pixels(&render(&mut world), outline) > 0);
+}
@@ -0,0 +1,137 @@
+use engine::output::{ImageFormat, encode_bmp, encode_ppm, format_for, write};
+
+/// A `width` by `height` frame whose pixel at (x, y) is (x, y, x + y, 200).
+fn frame(width: u32, height: u32) -> Vec<u8> {
+    (0..height)
+        .flat_map(|y| (0..width).flat_map(move |x| [x as u8, y as u8, (x + y) as u8, 200]))
+        .collect()
+}
+
+fn u32_at(bytes: &[u8], at: usize) -> u32 {
+    u32::from_le_bytes(bytes[at..at + 4].try_into().unwrap())
+}
+
+fn i32_at(bytes: &[u8], at: usize) -> i32 {
+    i32::from_le_bytes(bytes[at..at + 4].try_into().unwrap())
+}
+
+fn u16_at(bytes: &[u8], at: usize) -> u16 {
+    u16::from_le_bytes(bytes[at..at + 2].try_into().unwrap())
+}
+
+/// Width, height and the offset of the pixels in a binary PPM.
+fn ppm_header(ppm: &[u8]) -> (u32, u32, usize) {
+    let mut fields = Vec::new();
+    let mut start = 0;
+    for (i, &b) in ppm.iter().enumerate() {
+        if b.is_ascii_whitespace() {
+            fields.push(std::str::from_utf8(&ppm[start..i]).unwrap().to_string());
+            start = i + 1;
+            if fields.len() == 4 {
+                break;
+            }
+        }
+    }
+    assert_eq!(fields[0], "P6");
+    assert_eq!(fields[3], "255");
+    (
+        fields[1].parse().unwrap(),
+        fields[2].parse().unwrap(),
+        start,
+    )
+}
+
+#[test]
+fn ppm_headers_and_pixels_read_back() {
+    for (width, height) in [(5, 3), (4, 4), (1, 7)] {
+        let ppm = encode_ppm(&frame(width, height), width, height);
+        let (w, h, start) = ppm_header(&ppm);
+        assert_eq!((w, h), (width, height));
+        let pixels = &ppm[start..];
+        assert_eq!(pixels.len(), (width * height * 3) as usize);
+        let at = |x: u32, y: u32| {
+            let i = ((y * width + x) * 3) as usize;
+            &pixels[i..i + 3]
+        };
+        assert_eq!(at(0, 0), [0, 0, 0]);
+        assert_eq!(
+            at(width - 1, height - 1),
+            [
+                (width - 1) as u8,
+                (height - 1) as u8,
+                (width + height - 2) as u8
+            ]
+        );
+    }
+}
+
+#[test]
+fn bmp_headers_and_pixels_read_back() {
+    // Widths that aren't a multiple of 4 too.
+    for (width, height) in [(5, 3), (4, 4), (3, 2), (1, 1)] {
+        let bmp = encode_bmp(&frame(width, height), width, height);
+        assert_eq!(&bmp[..2], b"BM");
+        assert_eq!(u32_at(&bmp, 2) as usize, bmp.len());
+        let offset = u32_at(&bmp, 10) as usize;
+        assert_eq!(u32_at(&bmp, 14), 40);
+        assert_eq!(i32_at(&bmp, 18), width as i32);
+        assert_eq!(i32_at(&bmp, 22), height as i32);
+        assert_eq!(u16_at(&bmp, 28), 32);
+        assert_eq!(u32_at(&bmp, 30), 0);
+        assert_eq!(bmp.len() - offset, (width * height * 4) as usize);
+
+        // Rows run bottom up, BGRA.
+        let row = width as usize * 4;
+        let at = |x: u32, y: u32| {
+            let i = offset + (height - 1 - y) as usize * row + x as usize * 4;
+            &bmp[i..i + 4]
+        };
+        assert_eq!(at(0, 0), [0, 0, 0, 200]);
+        assert_eq!(
+            at(width - 1, 0),
+            [(width - 1) as u8, 0, (width - 1) as u8, 200]
+        );
+        assert_eq!(
+            at(0, height - 1),
+            [(height - 1) as u8, (height - 1) as u8, 0, 200]
+        );
+    }
+}
+
+#[test]
+fn formats_follow_the_extension() {
+    #[cfg(feature = "png")]
+    assert_eq!(ImageFormat::from_path("a/shot.png"), Some(ImageFormat::Png));
+    assert_eq!(ImageFormat::from_path("shot.PPM"), Some(ImageFormat::Ppm));
+    assert_eq!(ImageFormat::from_path("shot.bmp"), Some(ImageFormat::Bmp));
+    assert_eq!(ImageFormat::from_path("shot.jpg"), None);
+    assert_eq!(ImageFormat::from_path("shot"), None);
+
+    let dir = std::env::temp_dir();
+    let id = std::process::id();
+    let rgba = frame(3, 2);
+    let mut formats = vec![("ppm", &b"P6"[..]), ("bmp", b"BM")];
+    if cfg!(feature = "png") {
+        formats.push(("png", b"\x89PNG"));
+    }
+    for (name, magic) in formats {
+        let path = dir.join(format!("shapes-output-{id}.{name}"));
+        write(&path, &rgba, 3, 2).unwrap();
+        assert!(std::fs::read(&path).unwrap().starts_with(magic), "{name}");
+        std::fs::remove_file(path).unwrap();
+    }
+    let err = write(dir.join(format!("shapes-output-{id}.gif")), &rgba, 3, 2).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+}
+
+#[test]
+fn png_needs_the_png_feature() {
+    let result = format_for("shot.png");
+    if cfg!(feature = "png") {
+        assert!(result.is_ok());
+    } else {
+        let err = result.unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::Unsupported);
+        assert_eq!(err.to_string(), "built without png support");
+    }
+}
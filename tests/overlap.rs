@@ -0,0 +1,110 @@
+mod common;
+
+use common::{HEIGHT, WIDTH, object, pixel, render, world};
+use engine::Object;
+use engine::mesh::Color;
+use engine::mesh::cube::CubeMesh;
+use engine::mesh::quad::QuadMesh;
+use nalgebra::{UnitQuaternion, Vector3};
+
+const GRAY: Color = Color::rgb(128, 128, 128);
+
+/// A unit cube, two wide, at `x`.
+fn cube(x: f32) -> Object {
+    object(CubeMesh::new(GRAY), x, 0.0, 0.0)
+}
+
+#[test]
+fn finds_overlapping_and_touching_boxes() {
+    let world = world(vec![cube(0.0), cube(1.5), cube(5.0), cube(7.0)]);
+    // The first two overlap and the last two touch face to face.
+    assert_eq!(world.overlapping_pairs(), vec![(0, 1), (2, 3)]);
+}
+
+#[test]
+fn flat_objects_touching_count() {
+    // Two quads in the same plane sharing an edge, and one lying flat on
+    // top of a cube.
+    let mut floor = object(QuadMesh::new(2.0, 2.0, GRAY), 10.0, 1.0, 0.0);
+    floor.rotation =
+        UnitQuaternion::from_axis_angle(&Vector3::x_axis(), std::f32::consts::FRAC_PI_2);
+    let world = world(vec![
+        object(QuadMesh::new(2.0, 2.0, GRAY), -10.0, 0.0, 0.0),
+        object(QuadMesh::new(2.0, 2.0, GRAY), -8.0, 0.0, 0.0),
+        object(CubeMesh::new(GRAY), 10.0, 0.0, 0.0),
+        floor,
+    ]);
+    assert_eq!(world.overlapping_pairs(), vec![(0, 1), (2, 3)]);
+}
+
+#[test]
+fn rotation_grows_the_box() {
+    let mut turned = cube(2.3);
+    let world_apart = world(vec![cube(0.0), cube(2.3)]);
+    assert!(world_apart.overlapping_pairs().is_empty());
+    // Turned 45 degrees about y, the cube reaches sqrt(2) out along x.
+    turned.rotation =
+        UnitQuaternion::from_axis_angle(&Vector3::y_axis(), std::f32::consts::FRAC_PI_4);
+    let world = world(vec![cube(0.0), turned]);
+    assert_eq!(world.overlapping_pairs(), vec![(0, 1)]);
+}
+
+#[test]
+fn hidden_objects_are_left_out() {
+    let mut world = world(vec![cube(0.0), cube(1.0), cube(0.5)]);
+    world.models[1].visible = false;
+    assert_eq!(world.overlapping_pairs(), vec![(0, 2)]);
+}
+
+#[test]
+fn sweep_agrees_with_testing_every_pair() {
+    // A deterministic scatter of cubes of varying size.
+    let mut models = Vec::new();
+    for i in 0..300u32 {
+        let at = |k: u32| ((i * 7919 + k * 104729) % 1000) as f32 / 10.0;
+        let mut cube = object(CubeMesh::new(GRAY), at(1), at(2) / 4.0, at(3) / 4.0);
+        cube.scale = 0.5 + (i % 5) as f32 * 0.5;
+        models.push(cube);
+    }
+    let world = world(models);
+    let mut expected = Vec::new();
+    for a in 0..world.models.len() {
+        for b in a + 1..world.models.len() {
+            let reach = |m: &Object| {
+                let c = Vector3::new(m.offset_x, m.offset_y, m.offset_z);
+                (c.add_scalar(-m.scale), c.add_scalar(m.scale))
+            };
+            let ((a_min, a_max), (b_min, b_max)) =
+                (reach(&world.models[a]), reach(&world.models[b]));
+            if (0..3).all(|k| a_min[k] <= b_max[k] && b_min[k] <= a_max[k]) {
+                expected.push((a, b));
+            }
+        }
+    }
+    assert!(!expected.is_empty());
+    assert_eq!(world.overlapping_pairs(), expected);
+}
+
+#[test]
+fn highlight_tints_only_overlapping_objects() {
+    let mut world = world(vec![cube(0.5), cube(-1.0), cube(20.0)]);
+    world.light.intensity = 0.0;
+    let plain = render(&mut world);
+    world.highlight_overlaps = true;
+    let tinted = render(&mut world);
+    let (before, after) = (
+        pixel(&plain, WIDTH / 2, HEIGHT / 2),
+        pixel(&tinted, WIDTH / 2, HEIGHT / 2),
+    );
+    assert!(
+        after[0] > before[0] && after[1] < before[1] && after[2] < before[2],
+        "{after:?}"
+    );
+    assert_eq!(after[3], 255);
+
+    // Pulled apart, nothing is tinted.
+    world.models[1].offset_x = -5.0;
+    let apart = render(&mut world);
+    world.highlight_overlaps = false;
+    assert_eq!(apart, render(&mut world));
+}
@@ -0,0 +1,94 @@
+mod common;
+
+use std::time::Duration;
+
+use common::{HEIGHT, WHITE, WIDTH, pixel};
+
+use engine::mesh::Color;
+use engine::overlay::{Canvas, FpsCounter, GLYPH_HEIGHT, text_size};
+
+const BLACK: Color = Color {
+    r: 0,
+    g: 0,
+    b: 0,
+    a: 255,
+};
+
+#[test]
+fn text_is_drawn_at_its_position() {
+    let mut frame = vec![255; (WIDTH * HEIGHT * 4) as usize];
+    Canvas::new(&mut frame, WIDTH, HEIGHT).draw_text(10, 10, "I", BLACK);
+
+    // The I has a full bar across its top row.
+    assert_eq!(pixel(&frame, 11, 10), [0, 0, 0, 255]);
+    assert_eq!(pixel(&frame, 12, 13), [0, 0, 0, 255]);
+    assert_eq!(pixel(&frame, 10, 13), WHITE);
+    assert_eq!(pixel(&frame, 20, 10), WHITE);
+}
+
+#[test]
+fn text_clips_to_small_frames() {
+    let (width, height) = (8, 4);
+    let mut frame = vec![255; (width * height * 4) as usize];
+    let mut canvas = Canvas::new(&mut frame, width, height);
+    canvas.draw_text(-3, -2, "FPS 60.0\nTRIS 12", BLACK);
+    canvas.draw_text(6, 2, "WIDE", BLACK);
+    canvas.fill_rect(-10, -10, 100, 100, BLACK);
+    assert!(frame.chunks(4).all(|p| p == [0, 0, 0, 255]));
+}
+
+#[test]
+fn lines_join_their_ends_and_clip() {
+    let mut frame = vec![255; (WIDTH * HEIGHT * 4) as usize];
+    let mut canvas = Canvas::new(&mut frame, WIDTH, HEIGHT);
+    canvas.draw_line(5, 20, 15, 10, BLACK);
+    canvas.draw_line(-10, 40, WIDTH as i32 + 10, 40, BLACK);
+    for i in 0..=10 {
+        assert_eq!(pixel(&frame, 5 + i, 20 - i), [0, 0, 0, 255]);
+    }
+    assert_eq!(pixel(&frame, 16, 9), WHITE);
+    assert!((0..WIDTH).all(|x| pixel(&frame, x, 40) == [0, 0, 0, 255]));
+    assert!((0..WIDTH).all(|x| pixel(&frame, x, 41) == WHITE));
+}
+
+#[test]
+fn circles_pass_through_their_radius_once() {
+    let mut frame = vec![255; (WIDTH * HEIGHT * 4) as usize];
+    let half = Color { a: 128, ..BLACK };
+    Canvas::new(&mut frame, WIDTH, HEIGHT).draw_circle(30, 30, 10, half);
+    let once = [127, 127, 127, 255];
+    for (x, y) in [(40, 30), (20, 30), (30, 40), (30, 20)] {
+        assert_eq!(pixel(&frame, x, y), once, "({x}, {y})");
+    }
+    assert_eq!(pixel(&frame, 30, 30), WHITE);
+    assert_eq!(pixel(&frame, 41, 30), WHITE);
+    let drawn = frame.chunks(4).filter(|p| *p != WHITE).count();
+    assert!((56..=64).contains(&drawn), "{drawn}");
+}
+
+#[test]
+fn lowercase_and_unknown_characters_fall_back() {
+    let mut upper = vec![255; (WIDTH * HEIGHT * 4) as usize];
+    let mut lower = upper.clone();
+    Canvas::new(&mut upper, WIDTH, HEIGHT).draw_text(0, 0, "FPS?", BLACK);
+    Canvas::new(&mut lower, WIDTH, HEIGHT).draw_text(0, 0, "fps~", BLACK);
+    assert!(upper == lower);
+}
+
+#[test]
+fn text_size_counts_lines_and_columns() {
+    assert_eq!(text_size(""), (0, 0));
+    assert_eq!(text_size("A"), (5, GLYPH_HEIGHT));
+    assert_eq!(text_size("AB\nC"), (11, 16));
+}
+
+#[test]
+fn fps_is_averaged_over_the_window() {
+    let mut fps = FpsCounter::new(Duration::from_millis(500));
+    for _ in 0..9 {
+        fps.tick(Duration::from_millis(50));
+    }
+    assert_eq!(fps.fps(), 0.0);
+    let average = fps.tick(Duration::from_millis(50));
+    assert!((average - 20.0).abs() < 1e-3);
+}
@@ -0,0 +1,58 @@
+use engine::mesh::p_hack::PHackMesh;
+use engine::mesh::{Color, Mesh, MeshMut};
+
+const TEAL: Color = Color {
+    r: 40,
+    g: 170,
+    b: 160,
+    a: 255,
+};
+const WHITE: Color = Color {
+    r: 255,
+    g: 255,
+    b: 255,
+    a: 255,
+};
+
+#[test]
+fn counts_match_the_geometry() {
+    let mesh = PHackMesh::new();
+    assert_eq!(mesh.vertex_count(), mesh.verts().len());
+    assert_eq!(mesh.triangle_count(), mesh.tris().len());
+    assert_eq!(mesh.validate(), Ok(()));
+}
+
+#[test]
+fn new_with_scales_and_colors() {
+    let plain = PHackMesh::new();
+    let mesh = PHackMesh::new_with(2.0, TEAL);
+    assert!(mesh.tris().iter().all(|tri| tri.color == TEAL));
+    for (a, b) in plain.verts().iter().zip(mesh.verts()) {
+        assert_eq!(a * 2.0, *b);
+    }
+}
+
+#[test]
+fn face_and_body_color_separately() {
+    let mesh = PHackMesh::new().with_colors(WHITE, TEAL);
+    let colors: Vec<Color> = mesh.tris().iter().map(|tri| tri.color).collect();
+    let face = colors.iter().take_while(|&&c| c == WHITE).count();
+    assert!(face > 0);
+    assert!(colors[face..].iter().all(|&c| c == TEAL));
+    // Default colors put the split in the same place.
+    let plain = PHackMesh::new();
+    assert_ne!(plain.tris()[face - 1].color, plain.tris()[face].color);
+}
+
+#[test]
+fn bad_indices_are_caught_when_placed() {
+    let mut mesh = PHackMesh::new();
+    mesh.tris_mut()[3].v2 = 99;
+    let err = engine::Object::try_instance(std::rc::Rc::new(mesh), 0.0, 0.0, 0.0)
+        .err()
+        .expect("a mesh with a bad index");
+    assert!(
+        err.to_string().contains("triangle 3 refers to vertex 99"),
+        "{err}"
+    );
+}
@@ -0,0 +1,62 @@
+use std::time::{Duration, Instant};
+
+use engine::pacing::{FramePacer, sleep_until};
+
+#[test]
+fn uncapped_frames_never_wait() {
+    let mut pacer = FramePacer::new(None);
+    assert_eq!(pacer.budget(), None);
+    pacer.frame_started(Instant::now());
+    assert_eq!(pacer.next_frame(), None);
+    for bad in [0.0, -30.0, f32::INFINITY, f32::NAN] {
+        assert!(!FramePacer::new(Some(bad)).is_pacing(), "{bad}");
+    }
+}
+
+#[test]
+fn capped_frames_are_due_a_budget_after_the_last() {
+    let mut pacer = FramePacer::new(Some(50.0));
+    assert_eq!(pacer.budget(), Some(Duration::from_millis(20)));
+    // The first frame is due straight away.
+    assert_eq!(pacer.next_frame(), None);
+    let start = Instant::now();
+    pacer.frame_started(start);
+    assert_eq!(pacer.next_frame(), Some(start + Duration::from_millis(20)));
+    // After an idle spell the frame is already due.
+    let earlier = start - Duration::from_secs(1);
+    pacer.frame_started(earlier);
+    assert!(pacer.next_frame().unwrap() < start);
+}
+
+#[test]
+fn vsync_paces_caps_at_or_above_the_refresh_rate() {
+    let mut pacer = FramePacer::new(Some(60.0));
+    assert!(!pacer.sync_to_display(Some(60.0)));
+    assert!(!pacer.sync_to_display(Some(59.94)));
+    pacer.frame_started(Instant::now());
+    assert_eq!(pacer.next_frame(), None);
+    // Below the refresh rate vsync doesn't hold frames back enough.
+    assert!(pacer.sync_to_display(Some(144.0)));
+    assert!(pacer.next_frame().is_some());
+    // An unknown rate is trusted to be the display's.
+    assert!(!pacer.sync_to_display(None));
+    assert!(FramePacer::new(Some(30.0)).sync_to_display(Some(60.0)));
+}
+
+#[test]
+fn sleeping_wakes_close_to_the_deadline() {
+    for wait in [1, 5, 12] {
+        let deadline = Instant::now() + Duration::from_millis(wait);
+        sleep_until(deadline);
+        let now = Instant::now();
+        assert!(now >= deadline, "{wait} ms: woke early");
+        // Loose enough for a busy test machine; unloaded it is well
+        // under half a millisecond.
+        let late = now - deadline;
+        assert!(late < Duration::from_millis(5), "{wait} ms: {late:?} late");
+    }
+    // A deadline already passed returns at once.
+    let start = Instant::now();
+    sleep_until(start - Duration::from_millis(5));
+    assert!(start.elapsed() < Duration::from_millis(1));
+}
@@ -0,0 +1,196 @@
+mod common;
+
+use common::{HEIGHT, WIDTH, object, world};
+use engine::mesh::Color;
+use engine::mesh::cube::CubeMesh;
+use engine::mesh::quad::QuadMesh;
+use engine::paint::{Brush, Stroke};
+use engine::texture::Texture;
+use engine::{Material, World};
+use nalgebra::Vector2;
+
+const GRAY: Color = Color::rgb(90, 90, 90);
+
+fn brush(radius: f32, hardness: f32) -> Brush {
+    Brush {
+        radius,
+        hardness,
+        color: Color::BLACK,
+    }
+}
+
+#[test]
+fn brushes_fade_from_their_hard_core() {
+    let soft = brush(4.0, 0.5);
+    assert_eq!(soft.coverage(0.0), 1.0);
+    assert_eq!(soft.coverage(2.0), 1.0);
+    assert_eq!(soft.coverage(3.0), 0.5);
+    assert_eq!(soft.coverage(4.0), 0.0);
+    assert_eq!(soft.coverage(9.0), 0.0);
+    let hard = brush(4.0, 1.0);
+    assert_eq!(hard.coverage(3.99), 1.0);
+    assert_eq!(hard.coverage(4.0), 0.0);
+}
+
+#[test]
+fn stamps_land_around_their_uv_and_clip_at_the_edges() {
+    let mut texture = Texture::blank(16, 16, Color::WHITE);
+    texture.paint(Vector2::new(0.5, 0.25), &brush(2.0, 1.0));
+    assert_eq!(texture.texel(8, 4), Color::BLACK);
+    assert_eq!(texture.texel(7, 3), Color::BLACK);
+    assert_eq!(texture.texel(10, 4), Color::WHITE);
+    assert_eq!(texture.texel(8, 8), Color::WHITE);
+
+    // Half off the corner, and wholly off the texture.
+    let generation = texture.generation();
+    texture.paint(Vector2::new(0.0, 0.0), &brush(2.0, 1.0));
+    assert_eq!(texture.texel(0, 0), Color::BLACK);
+    assert_eq!(texture.texel(15, 15), Color::WHITE);
+    assert!(texture.generation() > generation);
+    let generation = texture.generation();
+    texture.paint(Vector2::new(3.0, -2.0), &brush(2.0, 1.0));
+    texture.paint(Vector2::new(f32::NAN, 0.5), &brush(2.0, 1.0));
+    assert_eq!(texture.generation(), generation);
+}
+
+#[test]
+fn soft_edges_blend_with_what_is_there() {
+    let mut texture = Texture::blank(16, 16, Color::WHITE);
+    texture.paint(Vector2::new(0.5, 0.5), &brush(6.0, 0.0));
+    let centre = texture.texel(8, 8).r;
+    let edge = texture.texel(12, 8).r;
+    assert!(centre < edge && edge < 255, "{centre} {edge}");
+}
+
+#[test]
+fn strokes_leave_no_gaps_between_far_stamps() {
+    let mut texture = Texture::blank(64, 8, Color::WHITE);
+    texture.stroke(
+        Vector2::new(0.05, 0.5),
+        Vector2::new(0.95, 0.5),
+        &brush(1.5, 1.0),
+    );
+    for x in 4..60 {
+        assert_eq!(texture.texel(x, 4), Color::BLACK, "texel {x}");
+    }
+    assert_eq!(texture.texel(32, 0), Color::WHITE);
+}
+
+/// A 4 by 4 quad filling the middle of the view, selected, with a material
+/// and no texture.
+fn quad_world() -> World {
+    let mut world = world(Vec::new());
+    let material = world.materials.insert("board", Material::new(GRAY));
+    world
+        .add_object(object(QuadMesh::new(4.0, 4.0, GRAY), 0.0, 0.0, 0.0).with_material(material))
+        .unwrap();
+    world.selected = Some(0);
+    world
+}
+
+#[test]
+fn hits_map_to_the_meshes_uvs() {
+    let world = quad_world();
+    let uv_at = |x, y| {
+        let hit = world
+            .raycast(&world.screen_ray(x, y, WIDTH, HEIGHT), false)
+            .unwrap();
+        world.hit_uv(&hit).unwrap()
+    };
+    let centre = uv_at(32.0, 32.0);
+    assert!((centre - Vector2::new(0.5, 0.5)).norm() < 0.02, "{centre}");
+    // The top left of the screen is the top left of the texture.
+    let corner = uv_at(24.0, 24.0);
+    assert!(corner.x < centre.x && corner.y < centre.y, "{corner}");
+
+    let cube = common::world(vec![object(CubeMesh::new(GRAY), 0.0, 0.0, 0.0)]);
+    let hit = cube
+        .raycast(&cube.screen_ray(32.0, 32.0, WIDTH, HEIGHT), false)
+        .unwrap();
+    assert_eq!(cube.hit_uv(&hit), None);
+}
+
+#[test]
+fn painting_an_object_gives_it_a_blank_texture_once() {
+    let mut world = quad_world();
+    let texture = world.paintable_texture(0, 32).unwrap();
+    assert_eq!(world.object_texture(0), Some(texture));
+    let blank = world.materials.texture(texture).unwrap();
+    assert_eq!((blank.width(), blank.height()), (32, 32));
+    assert_eq!(blank.texel(5, 5), Color::WHITE);
+    // The library material is left alone for other objects using it.
+    let board = world.materials.handle("board").unwrap();
+    assert_eq!(world.materials.get(board).unwrap().texture, None);
+    assert_eq!(world.paintable_texture(0, 64), Some(texture));
+
+    // Without a material it gets one in its own color.
+    world
+        .add_object(object(QuadMesh::new(1.0, 1.0, GRAY), 0.0, 0.0, 3.0))
+        .unwrap();
+    let other = world.paintable_texture(1, 8).unwrap();
+    assert_ne!(other, texture);
+    let material = world.models[1].material.unwrap();
+    assert_eq!(world.materials.get(material).unwrap().color, GRAY);
+
+    // Nowhere to paint without UVs.
+    world
+        .add_object(object(CubeMesh::new(GRAY), 0.0, 0.0, 6.0))
+        .unwrap();
+    assert_eq!(world.paintable_texture(2, 8), None);
+    assert_eq!(world.paintable_texture(9, 8), None);
+}
+
+#[test]
+fn strokes_join_frames_but_not_across_seams() {
+    let mut world = quad_world();
+    let texture = world.paintable_texture(0, 64).unwrap();
+    let dot = brush(1.0, 1.0);
+    let mut stroke = Stroke::new();
+    stroke.to(&mut world, texture, Vector2::new(0.1, 0.5), &dot);
+    stroke.to(&mut world, texture, Vector2::new(0.4, 0.5), &dot);
+    let painted = world.materials.texture(texture).unwrap();
+    assert_eq!(painted.texel(16, 32), Color::BLACK);
+
+    // A jump across most of the texture is taken for a seam.
+    stroke.to(&mut world, texture, Vector2::new(0.95, 0.5), &dot);
+    let painted = world.materials.texture(texture).unwrap();
+    assert_eq!(painted.texel(60, 32), Color::BLACK);
+    assert_eq!(painted.texel(45, 32), Color::WHITE);
+
+    // Lifting the brush starts a new line.
+    stroke.end();
+    stroke.to(&mut world, texture, Vector2::new(0.95, 0.1), &dot);
+    let painted = world.materials.texture(texture).unwrap();
+    assert_eq!(painted.texel(60, 20), Color::WHITE);
+}
+
+#[test]
+fn painting_shows_in_the_next_frame() {
+    let mut world = quad_world();
+    let texture = world.paintable_texture(0, 16).unwrap();
+    let view = world.camera.generate_view_mat();
+    let before = world.fingerprint(&view);
+    Stroke::new().to(
+        &mut world,
+        texture,
+        Vector2::new(0.5, 0.5),
+        &brush(3.0, 1.0),
+    );
+    assert_ne!(world.fingerprint(&view), before);
+
+    let frame = common::render(&mut world);
+    assert_eq!(common::pixel(&frame, 32, 32)[..3], [0, 0, 0]);
+    assert_ne!(common::pixel(&frame, 26, 26)[..3], [0, 0, 0]);
+}
+
+#[test]
+fn painted_textures_save_as_images() {
+    let mut texture = Texture::blank(4, 2, Color::WHITE);
+    texture.paint(Vector2::new(0.1, 0.2), &brush(0.8, 1.0));
+    let path = std::env::temp_dir().join(format!("shapes-paint-{}.bmp", std::process::id()));
+    texture.save(&path).unwrap();
+    let bytes = std::fs::read(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(&bytes[..2], b"BM");
+    assert!(texture.save(path.with_extension("gif")).is_err());
+}
@@ -0,0 +1,91 @@
+use engine::mesh::Color;
+
+#[test]
+fn hex_reads_both_forms() {
+    assert_eq!(Color::from_hex(0xFF8800), Color::rgb(255, 136, 0));
+    assert_eq!(
+        Color::from_hex(0x11223344),
+        Color {
+            r: 0x11,
+            g: 0x22,
+            b: 0x33,
+            a: 0x44,
+        }
+    );
+    assert_eq!(Color::from_hex(0), Color::BLACK);
+}
+
+#[test]
+fn strings_round_trip() {
+    for text in ["#ff8800", "#11223344", "#000000"] {
+        let color: Color = text.parse().unwrap();
+        assert_eq!(color.to_string(), text);
+    }
+    assert_eq!("00ff00".parse(), Ok(Color::GREEN));
+    assert_eq!("#FFFFFF".parse(), Ok(Color::WHITE));
+}
+
+#[test]
+fn bad_strings_are_errors() {
+    for text in ["", "#", "#fff", "#ff88001", "#gg0000", "#+f0000"] {
+        let err = text.parse::<Color>().unwrap_err();
+        assert!(err.to_string().contains("#rrggbb"), "{text}: {err}");
+    }
+}
+
+#[test]
+fn hsv_primaries_and_wrap_around() {
+    assert_eq!(Color::from_hsv(0.0, 1.0, 1.0), Color::RED);
+    assert_eq!(Color::from_hsv(120.0, 1.0, 1.0), Color::GREEN);
+    assert_eq!(Color::from_hsv(240.0, 1.0, 1.0), Color::BLUE);
+    assert_eq!(Color::from_hsv(360.0, 1.0, 1.0), Color::RED);
+    assert_eq!(Color::from_hsv(-120.0, 1.0, 1.0), Color::BLUE);
+    assert_eq!(Color::from_hsv(420.0, 1.0, 1.0), Color::YELLOW);
+    assert_eq!(Color::from_hsv(359.9, 1.0, 1.0), Color::RED);
+}
+
+#[test]
+fn hsv_clamps_saturation_and_value() {
+    assert_eq!(Color::from_hsv(200.0, 0.0, 1.0), Color::WHITE);
+    assert_eq!(Color::from_hsv(200.0, -1.0, 2.0), Color::WHITE);
+    assert_eq!(Color::from_hsv(200.0, 1.0, 0.0), Color::BLACK);
+}
+
+#[test]
+fn lerp_ends_and_middle() {
+    assert_eq!(Color::BLACK.lerp(Color::WHITE, 0.0), Color::BLACK);
+    assert_eq!(Color::BLACK.lerp(Color::WHITE, 1.0), Color::WHITE);
+    assert_eq!(
+        Color::BLACK.lerp(Color::WHITE, 0.5),
+        Color::rgb(128, 128, 128)
+    );
+    assert_eq!(Color::BLACK.lerp(Color::WHITE, 7.0), Color::WHITE);
+    assert_eq!(Color::RED.lerp(Color::TRANSPARENT, 1.0).a, 0);
+}
+
+#[test]
+fn scale_saturates_and_keeps_alpha() {
+    let color = Color {
+        r: 200,
+        g: 100,
+        b: 10,
+        a: 50,
+    };
+    assert_eq!(
+        color.scale(2.0),
+        Color {
+            r: 255,
+            g: 200,
+            b: 20,
+            a: 50,
+        }
+    );
+    assert_eq!(color.scale(0.5).r, 100);
+    assert_eq!(
+        color.scale(-1.0),
+        Color {
+            a: 50,
+            ..Color::BLACK
+        }
+    );
+}
@@ -0,0 +1,118 @@
+mod common;
+
+use common::{HEIGHT, WIDTH, object, pixel, render, world};
+use engine::mesh::Color;
+use engine::mesh::cube::CubeMesh;
+use engine::particle::{FADE_OUT, ParticleEmitter, scatter};
+use nalgebra::{Point3, Vector3};
+
+const RED: Color = Color::rgb(255, 0, 0);
+
+fn still(_: u32) -> Vector3<f32> {
+    Vector3::zeros()
+}
+
+fn rising(n: u32) -> Vector3<f32> {
+    Vector3::new(0.0, 1.0 + n as f32, 0.0)
+}
+
+/// An emitter of big motionless red squares at `origin`, eight a second.
+fn puffs(origin: Point3<f32>) -> ParticleEmitter {
+    let mut emitter = ParticleEmitter::new(origin, 8.0, still, 2.0, RED, RED, 1.5);
+    emitter.gravity = Vector3::zeros();
+    emitter
+}
+
+#[test]
+fn spawns_at_its_rate_and_expires_with_age() {
+    let mut emitter = puffs(Point3::origin());
+    // Steps of an eighth of a second keep the clock exact.
+    for _ in 0..4 {
+        emitter.step(0.125);
+    }
+    assert_eq!(emitter.particles().len(), 4);
+    // After two seconds the oldest particles die as fast as new ones come.
+    for _ in 0..36 {
+        emitter.step(0.125);
+    }
+    assert_eq!(emitter.spawned(), 40);
+    assert_eq!(emitter.particles().len(), 16);
+    assert!(emitter.particles().iter().all(|p| p.age < 2.0));
+}
+
+#[test]
+fn particles_fly_and_fall() {
+    let mut emitter = ParticleEmitter::new(Point3::origin(), 1.0, rising, 10.0, RED, RED, 0.1);
+    emitter.step(1.0);
+    let [particle] = emitter.particles() else {
+        panic!("one particle after a second");
+    };
+    assert_eq!(particle.velocity, Vector3::new(0.0, 1.0, 0.0));
+    emitter.step(0.5);
+    let particle = emitter.particles()[0];
+    assert!((particle.velocity.y - (1.0 - 9.8 * 0.5)).abs() < 1e-5);
+    assert!((particle.position.y - particle.velocity.y * 0.5).abs() < 1e-5);
+}
+
+#[test]
+fn pool_caps_live_particles() {
+    let mut emitter = puffs(Point3::origin()).with_max_particles(16);
+    emitter.rate = 1000.0;
+    let pool = emitter.particles().as_ptr();
+    for _ in 0..10 {
+        emitter.step(0.1);
+    }
+    assert_eq!(emitter.particles().len(), 16);
+    assert_eq!(emitter.max_particles(), 16);
+    // The pool was never grown.
+    assert_eq!(emitter.particles().as_ptr(), pool);
+}
+
+#[test]
+fn color_shades_with_age_and_fades_at_death() {
+    let emitter = ParticleEmitter::new(
+        Point3::origin(),
+        1.0,
+        still,
+        4.0,
+        Color::rgb(0, 0, 200),
+        Color::rgb(200, 0, 0),
+        1.0,
+    );
+    assert_eq!(emitter.color_at(0.0), Color::rgb(0, 0, 200));
+    assert_eq!(emitter.color_at(2.0), Color::rgb(100, 0, 100));
+    assert_eq!(emitter.color_at(4.0 * (1.0 - FADE_OUT)).a, 255);
+    let dying = emitter.color_at(4.0 * (1.0 - FADE_OUT / 2.0));
+    assert!((127..=128).contains(&dying.a), "{dying:?}");
+    assert_eq!(emitter.color_at(4.0).a, 0);
+}
+
+#[test]
+fn particles_sort_with_meshes() {
+    let cube = object(CubeMesh::new(Color::rgb(40, 200, 40)), 0.0, 0.0, 0.0);
+    let mut world = world(vec![cube]);
+    let center = (WIDTH / 2, HEIGHT / 2);
+    let plain = render(&mut world);
+
+    world.emitters.push(puffs(Point3::new(0.0, 0.0, 3.0)));
+    world.update(0.125);
+    assert_eq!(render(&mut world), plain, "behind the cube");
+    assert_eq!(world.stats().billboards_rasterized, 1);
+
+    world.emitters[0] = puffs(Point3::new(0.0, 0.0, -2.0));
+    world.update(0.125);
+    assert_eq!(
+        pixel(&render(&mut world), center.0, center.1),
+        [255, 0, 0, 255]
+    );
+}
+
+#[test]
+fn scatter_is_repeatable_and_in_range() {
+    let values: Vec<f32> = (0..1000).map(|n| scatter(n, 3)).collect();
+    assert!(values.iter().all(|v| (0.0..1.0).contains(v)));
+    assert_eq!(values, (0..1000).map(|n| scatter(n, 3)).collect::<Vec<_>>());
+    assert_ne!(scatter(7, 0), scatter(7, 1));
+    let mean = values.iter().sum::<f32>() / values.len() as f32;
+    assert!((mean - 0.5).abs() < 0.05, "{mean}");
+}
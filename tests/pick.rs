@@ -0,0 +1,106 @@
+mod common;
+
+use std::f32::consts::FRAC_PI_4;
+
+use common::{HEIGHT, WIDTH, object, world};
+
+use engine::mesh::obj::ObjMesh;
+use engine::mesh::quad::QuadMesh;
+use engine::mesh::{Color, ImportOptions};
+use engine::pick::Ray;
+use nalgebra::{Point3, UnitQuaternion, Vector3};
+
+const CUBE_OBJ: &[u8] = b"\
+v -1 -1 -1
+v 1 -1 -1
+v 1 1 -1
+v -1 1 -1
+v -1 -1 1
+v 1 -1 1
+v 1 1 1
+v -1 1 1
+f 1 4 3 2
+f 5 6 7 8
+f 1 2 6 5
+f 4 8 7 3
+f 1 5 8 4
+f 2 3 7 6
+";
+
+fn cube() -> ObjMesh {
+    ObjMesh::from_bytes(CUBE_OBJ, ImportOptions::default()).unwrap()
+}
+
+fn quad() -> QuadMesh {
+    QuadMesh::new(
+        2.0,
+        2.0,
+        Color {
+            r: 0,
+            g: 0,
+            b: 0,
+            a: 255,
+        },
+    )
+}
+
+#[test]
+fn axis_rays_hit_the_cube_faces() {
+    let world = world(vec![object(cube(), 0.0, 0.0, 0.0)]);
+    for dir in [Vector3::x(), -Vector3::x(), Vector3::z(), -Vector3::y()] {
+        let ray = Ray::new(Point3::origin() - dir * 4.0, dir);
+        let hit = world.raycast(&ray, false).unwrap();
+        assert_eq!(hit.object, 0);
+        assert!((hit.distance - 3.0).abs() < 1e-5);
+    }
+}
+
+#[test]
+fn rays_beside_the_cube_miss() {
+    let world = world(vec![object(cube(), 0.0, 0.0, 0.0)]);
+    let ray = Ray::new(Point3::new(1.5, 0.0, -4.0), Vector3::z());
+    assert_eq!(world.raycast(&ray, true), None);
+}
+
+#[test]
+fn object_offsets_move_the_hit() {
+    let world = world(vec![object(cube(), 0.0, 0.0, 2.0)]);
+    let ray = Ray::new(Point3::new(0.0, 0.0, -4.0), Vector3::z());
+    assert!((world.raycast(&ray, false).unwrap().distance - 5.0).abs() < 1e-5);
+}
+
+#[test]
+fn back_faces_are_optional() {
+    let world = world(vec![object(quad(), 0.0, 0.0, 0.0)]);
+    let front = Ray::new(Point3::new(0.0, 0.0, -3.0), Vector3::z());
+    let back = Ray::new(Point3::new(0.0, 0.0, 3.0), -Vector3::z());
+    assert!(world.raycast(&front, false).is_some());
+    assert_eq!(world.raycast(&back, false), None);
+    assert!((world.raycast(&back, true).unwrap().distance - 3.0).abs() < 1e-5);
+}
+
+#[test]
+fn pick_returns_the_closest_object_on_screen() {
+    let world = world(vec![
+        object(cube(), 0.0, 0.0, 4.0),
+        object(cube(), 0.0, 0.0, 0.0),
+        object(cube(), 10.0, 0.0, 0.0),
+    ]);
+    assert_eq!(
+        world.pick(WIDTH as f32 / 2.0, HEIGHT as f32 / 2.0, WIDTH, HEIGHT),
+        Some(1)
+    );
+    assert_eq!(world.pick(0.0, 0.0, WIDTH, HEIGHT), None);
+}
+
+#[test]
+fn scale_and_rotation_move_the_hit() {
+    let mut scaled = object(cube(), 0.0, 0.0, 0.0);
+    scaled.scale = 2.0;
+    scaled.rotation = UnitQuaternion::from_axis_angle(&Vector3::y_axis(), FRAC_PI_4);
+    let world = world(vec![scaled]);
+    let ray = Ray::new(Point3::new(0.0, 0.0, -4.0), Vector3::z());
+    // The cube's vertical edge now points at the ray, 2 * sqrt(2) out.
+    let hit = world.raycast(&ray, false).unwrap();
+    assert!((hit.distance - (4.0 - 2.0 * 2f32.sqrt())).abs() < 1e-4);
+}
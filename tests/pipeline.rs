@@ -0,0 +1,91 @@
+use std::f32::consts::FRAC_PI_2;
+
+use engine::pipeline::{project_vertex, unproject_screen};
+use nalgebra::{Matrix4, Perspective3, Point2, Point3, Vector3};
+
+const SIZE: u32 = 100;
+
+/// A 90 degree square frustum from z = -1 to z = -10 with the eye at the
+/// origin. x and y project to `x / -z` and `y / -z`, w is `-z`, and clip
+/// z is `-11/9 z - 20/9`.
+fn mvp() -> Matrix4<f32> {
+    Perspective3::new(1.0, FRAC_PI_2, 1.0, 10.0).to_homogeneous()
+}
+
+fn close(a: f32, b: f32) -> bool {
+    (a - b).abs() < 1e-4
+}
+
+#[test]
+fn points_on_the_axis_land_in_the_middle() {
+    let v = project_vertex(Point3::new(0.0, 0.0, -5.0), &mvp(), SIZE, SIZE);
+    assert!(!v.clipped);
+    assert_eq!(v.screen, Point2::new(50.0, 50.0));
+    // clip z = -11/9 * -5 - 20/9 = 35/9, over w = 5.
+    assert!(close(v.ndc_z, 7.0 / 9.0), "{}", v.ndc_z);
+    assert!(close(v.view_z, 5.0));
+    assert!(close(v.inv_w, 0.2));
+}
+
+#[test]
+fn frustum_corners_land_on_frame_corners() {
+    let top_right = project_vertex(Point3::new(5.0, 5.0, -5.0), &mvp(), SIZE, SIZE);
+    assert_eq!(top_right.screen, Point2::new(100.0, 0.0));
+    let bottom_left = project_vertex(Point3::new(-2.0, -2.0, -2.0), &mvp(), SIZE, SIZE);
+    assert_eq!(bottom_left.screen, Point2::new(0.0, 100.0));
+    // Halfway to the edge at the same depth is halfway across.
+    let quarter = project_vertex(Point3::new(2.5, -2.5, -5.0), &mvp(), SIZE, SIZE);
+    assert_eq!(quarter.screen, Point2::new(75.0, 75.0));
+}
+
+#[test]
+fn the_near_plane_is_outside_the_kept_depth_range() {
+    // NDC z runs from -1 at the near plane to 1 at the far one, and only
+    // 0..=1 is drawn.
+    let near = project_vertex(Point3::new(0.0, 0.0, -1.0), &mvp(), SIZE, SIZE);
+    assert!(close(near.ndc_z, -1.0));
+    assert!(near.clipped);
+    assert!(near.screen.x.is_nan());
+
+    // NDC z is 0 where -11/9 z - 20/9 = 0 scaled by w: z = -20/11.
+    let threshold = project_vertex(Point3::new(0.0, 0.0, -20.0 / 11.0), &mvp(), SIZE, SIZE);
+    assert!(close(threshold.ndc_z, 0.0));
+
+    let far = project_vertex(Point3::new(0.0, 0.0, -10.0), &mvp(), SIZE, SIZE);
+    assert!(close(far.ndc_z, 1.0));
+    assert!(!far.clipped);
+}
+
+#[test]
+fn points_behind_the_camera_are_clipped() {
+    let v = project_vertex(Point3::new(1.0, 1.0, 5.0), &mvp(), SIZE, SIZE);
+    assert!(v.clipped);
+    assert!(close(v.view_z, -5.0));
+    assert!(v.screen.x.is_nan() && v.screen.y.is_nan());
+
+    let at_eye = project_vertex(Point3::origin(), &mvp(), SIZE, SIZE);
+    assert!(at_eye.clipped);
+}
+
+#[test]
+fn unprojecting_undoes_projecting() {
+    let moved = mvp() * Matrix4::new_translation(&Vector3::new(0.5, -1.0, -3.0));
+    let inverse = moved.try_inverse().unwrap();
+    let point = Point3::new(1.5, 0.5, -3.0);
+    let v = project_vertex(point, &moved, SIZE, SIZE);
+    let back = unproject_screen(v.screen, v.ndc_z, &inverse, SIZE, SIZE);
+    assert!((back - point).norm() < 1e-3, "{back}");
+
+    // The middle of the screen on the near plane is straight ahead.
+    let centre = unproject_screen(
+        Point2::new(50.0, 50.0),
+        -1.0,
+        &mvp().try_inverse().unwrap(),
+        SIZE,
+        SIZE,
+    );
+    assert!(
+        (centre - Point3::new(0.0, 0.0, -1.0)).norm() < 1e-4,
+        "{centre}"
+    );
+}
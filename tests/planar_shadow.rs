@@ -0,0 +1,157 @@
+mod common;
+
+use std::f32::consts::FRAC_PI_2;
+
+use common::{HEIGHT, WIDTH, object, pixel, render, world};
+use engine::mesh::Color;
+use engine::mesh::cube::CubeMesh;
+use engine::mesh::quad::QuadMesh;
+use engine::pipeline::project_vertex;
+use engine::planar_shadow::{self, PlanarShadowConfig};
+use engine::{Camera, Light, LightKind, Object, World};
+use nalgebra::{Point3, UnitQuaternion, Vector3};
+
+const FLOOR_Y: f32 = -1.0;
+
+/// A half-unit cube floating over a white floor at `FLOOR_Y`, seen from
+/// above and in front, lit along `light_from -> origin`.
+fn scene(light_from: Point3<f32>) -> World {
+    let floor = Object {
+        rotation: UnitQuaternion::from_axis_angle(&Vector3::x_axis(), FRAC_PI_2),
+        ..object(QuadMesh::new(8.0, 8.0, Color::WHITE), 0.0, FLOOR_Y, 0.0)
+    };
+    let cube = Object {
+        scale: 0.5,
+        ..object(CubeMesh::new(Color::WHITE), 0.0, 0.0, 0.0)
+    };
+    let mut world = world(vec![floor, cube]);
+    world.camera = Camera::looking_at(Point3::new(0.0, 4.0, -6.0), Point3::new(1.0, -1.0, 0.0));
+    world.light.position = light_from;
+    world
+}
+
+fn shadows() -> Option<PlanarShadowConfig> {
+    Some(PlanarShadowConfig {
+        plane_y: FLOOR_Y,
+        ..PlanarShadowConfig::default()
+    })
+}
+
+/// The frame pixel `point` lands on.
+fn pixel_at(world: &World, frame: &[u8], point: Point3<f32>) -> [u8; 4] {
+    let clip = world.proj_mat * world.camera.generate_view_mat();
+    let screen = project_vertex(point, &clip, WIDTH, HEIGHT).screen;
+    assert!(screen.x >= 0.0 && screen.y >= 0.0, "{point} is off screen");
+    assert!(
+        screen.x < WIDTH as f32 && screen.y < HEIGHT as f32,
+        "{point} is off screen"
+    );
+    pixel(frame, screen.x as u32, screen.y as u32)
+}
+
+fn brightness(color: [u8; 4]) -> u32 {
+    color[..3].iter().map(|&c| c as u32).sum()
+}
+
+#[test]
+fn projection_flattens_points_onto_the_plane_along_the_light() {
+    let light = Light {
+        position: Point3::new(-1.0, 1.0, 0.0),
+        target: Point3::origin(),
+        kind: LightKind::Directional,
+        intensity: 1.0,
+        shadow_resolution: 0,
+        shadow_bias: 0.05,
+    };
+    let flatten = planar_shadow::projection(-2.0, &light).expect("light is above the plane");
+    let flat = flatten * Point3::new(0.5, 1.0, 3.0).to_homogeneous();
+    let flat = flat.xyz() / flat.w;
+    assert!(
+        (flat - Vector3::new(3.5, -2.0, 3.0)).norm() < 1e-5,
+        "{flat}"
+    );
+
+    let spot = Light {
+        position: Point3::new(0.0, 4.0, 0.0),
+        kind: LightKind::Spot {
+            inner_angle: 0.5,
+            outer_angle: 1.0,
+        },
+        ..light
+    };
+    let flatten = planar_shadow::projection(0.0, &spot).expect("light is above the plane");
+    let flat = flatten * Point3::new(1.0, 2.0, 0.0).to_homogeneous();
+    let flat = flat.xyz() / flat.w;
+    assert!((flat - Vector3::new(2.0, 0.0, 0.0)).norm() < 1e-5, "{flat}");
+
+    let low = Light {
+        position: Point3::new(0.0, -3.0, 0.0),
+        ..spot
+    };
+    assert!(planar_shadow::projection(0.0, &low).is_none());
+}
+
+#[test]
+fn objects_darken_the_floor_along_the_light() {
+    // Light comes down at 45 degrees from -x, so the cube's shadow falls a
+    // unit to its right.
+    let mut world = scene(Point3::new(-1.0, 1.0, 0.0));
+    let under = Point3::new(1.0, FLOOR_Y, 0.0);
+    let open = Point3::new(-2.0, FLOOR_Y, 1.0);
+    let lit = render(&mut world);
+    assert_eq!(world.stats().planar_shadow_triangles, 0);
+
+    world.planar_shadows = shadows();
+    let shadowed = render(&mut world);
+    assert!(world.stats().planar_shadow_triangles > 0);
+    assert!(
+        brightness(pixel_at(&world, &shadowed, under)) < brightness(pixel_at(&world, &lit, under))
+    );
+    assert_eq!(
+        pixel_at(&world, &shadowed, open),
+        pixel_at(&world, &lit, open)
+    );
+    // Nothing gets brighter anywhere.
+    for (s, l) in shadowed.chunks_exact(4).zip(lit.chunks_exact(4)) {
+        assert!(s[..3].iter().zip(&l[..3]).all(|(s, l)| s <= l));
+    }
+}
+
+#[test]
+fn ground_casts_no_shadow() {
+    let mut world = scene(Point3::new(-1.0, 1.0, 0.0));
+    // A tile lying on the floor and one sunk under it.
+    for y in [FLOOR_Y, FLOOR_Y - 0.5] {
+        world.models.push(Object {
+            rotation: UnitQuaternion::from_axis_angle(&Vector3::x_axis(), FRAC_PI_2),
+            ..object(QuadMesh::new(1.0, 1.0, Color::WHITE), -2.0, y, 0.0)
+        });
+    }
+    world.models.remove(1);
+    world.planar_shadows = shadows();
+    render(&mut world);
+    assert_eq!(world.stats().planar_shadow_triangles, 0);
+}
+
+#[test]
+fn radius_stops_long_shadows() {
+    // Light low from -x stretches the shadow past the edge of the floor.
+    let mut world = scene(Point3::new(-4.0, 1.0, 0.0));
+    let far = Point3::new(3.0, FLOOR_Y, 0.0);
+    world.planar_shadows = shadows();
+    let long = render(&mut world);
+    world.planar_shadows = Some(PlanarShadowConfig {
+        radius: 2.0,
+        ..shadows().unwrap()
+    });
+    let cut = render(&mut world);
+    assert!(brightness(pixel_at(&world, &long, far)) < brightness(pixel_at(&world, &cut, far)));
+}
+
+#[test]
+fn light_under_the_plane_casts_nothing() {
+    let mut world = scene(Point3::new(-1.0, -3.0, 0.0));
+    world.planar_shadows = shadows();
+    render(&mut world);
+    assert_eq!(world.stats().planar_shadow_triangles, 0);
+}
@@ -0,0 +1,76 @@
+mod common;
+
+use common::{HEIGHT, WHITE, WIDTH, object, pixel, render, world};
+use engine::RenderMode;
+use engine::mesh::Color;
+use engine::mesh::cube::CubeMesh;
+use engine::mesh::quad::QuadMesh;
+
+const RED: Color = Color::rgb(220, 30, 30);
+
+fn colored(frame: &[u8]) -> usize {
+    frame.chunks(4).filter(|p| *p != WHITE).count()
+}
+
+#[test]
+fn every_vertex_becomes_a_square() {
+    let mut world = world(vec![object(CubeMesh::new(RED), 0.0, 0.0, 0.0)]);
+    world.render_mode = RenderMode::Points { size: 1 };
+    let frame = render(&mut world);
+    assert_eq!(world.stats().vertices_drawn, 8);
+    assert_eq!(world.stats().triangles_rasterized, 0);
+    // Back corners line up behind the front ones from straight on.
+    let single = colored(&frame);
+    assert!((4..=8).contains(&single), "{single}");
+    assert!(
+        frame
+            .chunks(4)
+            .all(|p| p == WHITE || p == [220, 30, 30, 255])
+    );
+
+    world.render_mode = RenderMode::Points { size: 3 };
+    let frame = render(&mut world);
+    assert_eq!(colored(&frame), single * 9);
+}
+
+#[test]
+fn squares_are_centred_on_the_vertex() {
+    // Seen head on, each of a quad's corners gets a whole 3x3 block.
+    let mut world = world(vec![object(QuadMesh::new(2.0, 2.0, RED), 0.0, 0.0, 0.0)]);
+    world.render_mode = RenderMode::Points { size: 3 };
+    let frame = render(&mut world);
+    assert_eq!(world.stats().vertices_drawn, 4);
+    let red: Vec<(u32, u32)> = (0..HEIGHT)
+        .flat_map(|y| (0..WIDTH).map(move |x| (x, y)))
+        .filter(|&(x, y)| pixel(&frame, x, y) != WHITE)
+        .collect();
+    assert_eq!(red.len(), 4 * 9);
+    for &(x, y) in &red {
+        let neighbours = red
+            .iter()
+            .filter(|&&(ox, oy)| ox.abs_diff(x) <= 2 && oy.abs_diff(y) <= 2)
+            .count();
+        assert!(neighbours >= 4, "stray pixel at {x},{y}");
+    }
+}
+
+#[test]
+fn points_off_the_frame_or_behind_are_skipped() {
+    let mut world = world(vec![
+        object(CubeMesh::new(RED), 0.0, 0.0, -5.0),
+        object(CubeMesh::new(RED), 30.0, 0.0, 0.0),
+    ]);
+    world.render_mode = RenderMode::Points { size: 4 };
+    let frame = render(&mut world);
+    assert_eq!(world.stats().vertices_drawn, 0);
+    assert!(frame.chunks(4).all(|p| p == WHITE));
+}
+
+#[test]
+fn huge_points_are_clamped_to_the_frame() {
+    let mut world = world(vec![object(CubeMesh::new(RED), 0.0, 0.0, 0.0)]);
+    world.render_mode = RenderMode::Points { size: 1000 };
+    let frame = render(&mut world);
+    assert!(frame.chunks(4).all(|p| p == [220, 30, 30, 255]));
+    assert_eq!(world.stats().pixels_written, 8 * (WIDTH * HEIGHT) as usize);
+}
@@ -0,0 +1,167 @@
+mod common;
+
+use std::collections::HashMap;
+
+use common::{HEIGHT, WHITE, WIDTH, object, pixel, render, world};
+
+use engine::mesh::capsule::CapsuleMesh;
+use engine::mesh::cone::ConeMesh;
+use engine::mesh::cylinder::CylinderMesh;
+use engine::mesh::torus::TorusMesh;
+use engine::mesh::{Color, Mesh, PrimitiveError};
+use nalgebra::{Point3, Vector3};
+
+const GRAY: Color = Color {
+    r: 128,
+    g: 128,
+    b: 128,
+    a: 255,
+};
+
+/// Every edge, by vertex position, is used once in each direction: the
+/// surface is closed and consistently wound. Front faces point away from
+/// the origin, which is inside all of these shapes.
+fn assert_closed(mesh: &dyn Mesh) {
+    let key = |p: &Point3<f32>| {
+        let q = |v: f32| (v * 1e4).round() as i64;
+        (q(p.x), q(p.y), q(p.z))
+    };
+    let verts = mesh.verts();
+    let mut edges: HashMap<_, i32> = HashMap::new();
+    for tri in mesh.tris() {
+        let [a, b, c] = [tri.v1, tri.v2, tri.v3].map(|v| verts[v]);
+        let inward = (b - a).cross(&(c - a));
+        let centroid = Point3::from((a.coords + b.coords + c.coords) / 3.0);
+        assert!(inward.dot(&centroid.coords) < 0.0, "{tri:?} faces inwards");
+        for (from, to) in [(a, b), (b, c), (c, a)] {
+            *edges.entry((key(&from), key(&to))).or_default() += 1;
+        }
+    }
+    for (&(from, to), &count) in &edges {
+        assert_eq!(count, 1, "edge {from:?} -> {to:?} used {count} times");
+        assert_eq!(
+            edges.get(&(to, from)),
+            Some(&1),
+            "open edge {from:?} -> {to:?}"
+        );
+    }
+}
+
+#[test]
+fn shapes_are_closed() {
+    for segments in [3, 4, 16] {
+        assert_closed(&CylinderMesh::new(1.0, 2.0, segments, GRAY).unwrap());
+        assert_closed(&ConeMesh::new(1.0, 2.0, segments, GRAY).unwrap());
+        for rings in [1, 2, 5] {
+            assert_closed(&CapsuleMesh::new(0.5, 1.0, rings, segments, GRAY).unwrap());
+            assert_closed(&CapsuleMesh::new(0.5, 0.0, rings, segments, GRAY).unwrap());
+        }
+    }
+}
+
+#[test]
+fn too_few_segments_or_rings_is_an_error() {
+    assert_eq!(
+        CylinderMesh::new(1.0, 1.0, 2, GRAY).unwrap_err(),
+        PrimitiveError::TooFewSegments(2)
+    );
+    assert!(ConeMesh::new(1.0, 1.0, 0, GRAY).is_err());
+    assert!(CapsuleMesh::new(1.0, 1.0, 2, 2, GRAY).is_err());
+    assert_eq!(
+        CapsuleMesh::new(1.0, 1.0, 0, 8, GRAY).unwrap_err(),
+        PrimitiveError::TooFewRings(0)
+    );
+}
+
+#[test]
+fn shapes_fill_their_dimensions() {
+    let (min, max) = CylinderMesh::new(0.5, 3.0, 8, GRAY).unwrap().aabb();
+    assert!((min.y + 1.5).abs() < 1e-6 && (max.y - 1.5).abs() < 1e-6);
+    assert!((max.x - 0.5).abs() < 1e-6);
+    let (min, max) = CapsuleMesh::new(0.5, 1.0, 4, 8, GRAY).unwrap().aabb();
+    assert!((min.y + 1.0).abs() < 1e-6 && (max.y - 1.0).abs() < 1e-6);
+}
+
+#[test]
+fn sides_are_smooth_and_caps_hard() {
+    let cylinder = CylinderMesh::new(1.0, 2.0, 12, GRAY).unwrap();
+    let mut side = 0;
+    let mut cap = 0;
+    for (v, n) in cylinder.verts().iter().zip(cylinder.normals()) {
+        let radial = Vector3::new(v.x, 0.0, v.z);
+        if radial.norm() < 1e-6 {
+            assert!((n - Vector3::y() * v.y.signum()).norm() < 1e-5);
+        } else if n.y.abs() < 1e-5 {
+            // On the side, the normal points straight out from the axis.
+            assert!((n - radial.normalize()).norm() < 1e-5);
+            side += 1;
+        } else {
+            assert!((n - Vector3::y() * v.y.signum()).norm() < 1e-5);
+            cap += 1;
+        }
+    }
+    assert_eq!((side, cap), (24, 24));
+
+    let cone = ConeMesh::new(1.0, 1.0, 12, GRAY).unwrap();
+    // The slope is 45 degrees, so side normals lean out and up equally;
+    // the base points straight down.
+    for (v, n) in cone.verts().iter().zip(cone.normals()) {
+        if n.y < 0.0 {
+            assert!((n + Vector3::y()).norm() < 1e-5);
+        } else {
+            assert!((n.y - n.xz().norm()).abs() < 0.05, "{v} {n}");
+        }
+    }
+
+    let capsule = CapsuleMesh::new(1.0, 2.0, 4, 12, GRAY).unwrap();
+    for (v, n) in capsule.verts().iter().zip(capsule.normals()) {
+        // Out from the nearest point on the middle segment, give or take
+        // the blend where the ends meet the middle.
+        let axis = Point3::new(0.0, v.y.clamp(-1.0, 1.0), 0.0);
+        assert!((n - (v - axis).normalize()).norm() < 0.2, "{v} {n}");
+    }
+}
+
+#[test]
+fn torus_shares_its_seams() {
+    let torus = TorusMesh::new(2.0, 0.5, 24, 12, GRAY).unwrap();
+    assert_eq!(torus.verts().len(), 24 * 12);
+    assert_eq!(torus.tris().len(), 2 * 24 * 12);
+    assert!(TorusMesh::new(2.0, 0.5, 2, 12, GRAY).is_err());
+    assert!(TorusMesh::new(2.0, 0.5, 12, 2, GRAY).is_err());
+
+    // Closed by index alone: every edge runs once each way.
+    let mut edges: HashMap<(usize, usize), i32> = HashMap::new();
+    for tri in torus.tris() {
+        for edge in [(tri.v1, tri.v2), (tri.v2, tri.v3), (tri.v3, tri.v1)] {
+            *edges.entry(edge).or_default() += 1;
+        }
+    }
+    for (&(from, to), &count) in &edges {
+        assert_eq!(count, 1);
+        assert_eq!(edges.get(&(to, from)), Some(&1));
+    }
+
+    // Front faces point out of the tube, like the normals.
+    let verts = torus.verts();
+    for tri in torus.tris() {
+        let [a, b, c] = [tri.v1, tri.v2, tri.v3].map(|v| verts[v]);
+        let outward = -(b - a).cross(&(c - a));
+        let normal = torus.normals()[tri.v1] + torus.normals()[tri.v2] + torus.normals()[tri.v3];
+        assert!(outward.dot(&normal) > 0.0);
+    }
+}
+
+#[test]
+fn torus_occludes_itself() {
+    let torus = TorusMesh::new(1.0, 0.35, 32, 16, GRAY).unwrap();
+    let mut world = world(vec![object(torus, 0.0, 0.0, 0.0)]);
+    world.camera.position = Point3::new(0.0, 2.0, -4.0);
+    world.camera.look_at(Point3::origin());
+    let frame = render(&mut world);
+    // Looking down into the ring: the hole shows the background, with the
+    // far side of the ring above it and the near side below.
+    assert_eq!(pixel(&frame, WIDTH / 2, HEIGHT / 2), WHITE);
+    assert_ne!(pixel(&frame, WIDTH / 2, HEIGHT / 2 - 5), WHITE);
+    assert_ne!(pixel(&frame, WIDTH / 2, HEIGHT / 2 + 8), WHITE);
+}
@@ -0,0 +1,89 @@
+mod common;
+
+use std::time::Duration;
+
+use common::{object, render, world};
+use engine::mesh::Color;
+use engine::mesh::cube::CubeMesh;
+use engine::profile::{ProfileSummary, Profiler, Stage, StageTimes};
+
+fn ms(n: u64) -> Duration {
+    Duration::from_millis(n)
+}
+
+#[test]
+fn disabled_profilers_time_nothing() {
+    let profiler = Profiler::new(false);
+    {
+        let _scope = profiler.scope(Stage::Sort);
+        std::thread::sleep(ms(2));
+    }
+    assert!(profiler.times().is_empty());
+}
+
+#[test]
+fn scopes_nest_and_add_up() {
+    let profiler = Profiler::new(true);
+    {
+        let _outer = profiler.scope(Stage::Rasterize);
+        for _ in 0..2 {
+            let _inner = profiler.scope(Stage::Lighting);
+            std::thread::sleep(ms(2));
+        }
+        let _inner = profiler.scope(Stage::Pixels);
+        std::thread::sleep(ms(2));
+    }
+    let times = profiler.times();
+    assert!(times.get(Stage::Lighting) >= ms(4));
+    assert!(times.get(Stage::Pixels) >= ms(2));
+    assert!(times.get(Stage::Rasterize) >= times.get(Stage::Lighting) + times.get(Stage::Pixels));
+    assert_eq!(times.get(Stage::Clear), Duration::ZERO);
+    assert_eq!(Stage::Pixels.parent(), Some(Stage::Rasterize));
+}
+
+#[test]
+fn worlds_profile_only_when_asked() {
+    let mut world = world(vec![object(
+        CubeMesh::new(Color::rgb(200, 40, 40)),
+        0.0,
+        0.0,
+        0.0,
+    )]);
+    render(&mut world);
+    assert!(world.stats().stages.is_empty());
+
+    world.profiling_enabled = true;
+    render(&mut world);
+    let stats = world.stats();
+    let stages = stats.stages;
+    for stage in [
+        Stage::Vertex,
+        Stage::Rasterize,
+        Stage::Lighting,
+        Stage::Pixels,
+    ] {
+        assert!(stages.get(stage) > Duration::ZERO, "{stage:?}");
+    }
+    assert!(stages.get(Stage::Rasterize) >= stages.get(Stage::Pixels));
+    assert!(stages.get(Stage::Rasterize) <= stats.draw_time);
+    assert_eq!(stages.get(Stage::Overlay), Duration::ZERO);
+}
+
+#[test]
+fn summaries_report_mean_and_p95() {
+    let mut summary = ProfileSummary::default();
+    summary.record(&StageTimes::default());
+    assert_eq!(summary.frames(), 0);
+    for n in 1..=20 {
+        let mut times = StageTimes::default();
+        times.add(Stage::Pixels, ms(n));
+        summary.record(&times);
+    }
+    assert_eq!(summary.frames(), 20);
+    assert!((summary.mean(Stage::Pixels) - 10.5).abs() < 1e-3);
+    assert!((summary.p95(Stage::Pixels) - 19.0).abs() < 1e-3);
+    assert_eq!(summary.mean(Stage::Clear), 0.0);
+    let text = summary.to_string();
+    assert!(text.starts_with("20 frames"), "{text}");
+    assert!(text.contains("    pixels"), "{text}");
+}
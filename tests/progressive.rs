@@ -0,0 +1,59 @@
+mod common;
+
+use common::{HEIGHT, WIDTH, object, render, world};
+use engine::mesh::p_hack::PHackMesh;
+use engine::progressive::Accumulator;
+
+#[test]
+fn first_sample_matches_a_plain_render() {
+    let mut world = world(vec![object(PHackMesh::new(), 0.0, 0.0, 0.0)]);
+    let plain = render(&mut world);
+
+    let mut acc = Accumulator::new(16);
+    let mut frame = vec![0; plain.len()];
+    let view = world.camera.generate_view_mat();
+    acc.render(&mut world, view, &mut frame, WIDTH, HEIGHT);
+    assert_eq!(acc.samples(), 1);
+    assert!(frame == plain);
+}
+
+#[test]
+fn idle_frames_converge_to_softer_edges() {
+    let mut world = world(vec![object(PHackMesh::new(), 0.0, 0.0, 0.0)]);
+    let plain = render(&mut world);
+
+    let mut acc = Accumulator::new(16);
+    let mut frame = vec![0; plain.len()];
+    let view = world.camera.generate_view_mat();
+    for _ in 0..20 {
+        acc.render(&mut world, view, &mut frame, WIDTH, HEIGHT);
+    }
+    assert_eq!(acc.samples(), 16);
+    assert!(!acc.converging());
+    assert_eq!(world.projection_jitter, (0.0, 0.0));
+
+    // Edge pixels pick up in-between values that a single sample never has.
+    let distinct = |f: &[u8]| {
+        let mut colors: Vec<&[u8]> = f.chunks(4).collect();
+        colors.sort();
+        colors.dedup();
+        colors.len()
+    };
+    assert!(distinct(&frame) > distinct(&plain));
+}
+
+#[test]
+fn moving_anything_restarts_accumulation() {
+    let mut world = world(vec![object(PHackMesh::new(), 0.0, 0.0, 0.0)]);
+    let mut acc = Accumulator::new(16);
+    let mut frame = vec![0; (WIDTH * HEIGHT * 4) as usize];
+    let view = world.camera.generate_view_mat();
+    for _ in 0..5 {
+        acc.render(&mut world, view, &mut frame, WIDTH, HEIGHT);
+    }
+    assert_eq!(acc.samples(), 5);
+
+    world.models[0].offset_x += 0.1;
+    acc.render(&mut world, view, &mut frame, WIDTH, HEIGHT);
+    assert_eq!(acc.samples(), 1);
+}
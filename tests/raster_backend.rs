@@ -0,0 +1,111 @@
+mod common;
+
+use common::{HEIGHT, WIDTH, render, world};
+
+use engine::mesh::{Color, Mesh, Triangle};
+use engine::{CullMode, Object, RasterBackend};
+use nalgebra::{Point3, Vector3};
+
+/// Loose triangles in no particular arrangement.
+struct Soup {
+    verts: Vec<Point3<f32>>,
+    tris: Vec<Triangle>,
+}
+
+impl Mesh for Soup {
+    fn verts(&self) -> &[Point3<f32>] {
+        &self.verts
+    }
+
+    fn tris(&self) -> &Vec<Triangle> {
+        &self.tris
+    }
+}
+
+/// `count` random triangles, every third one a long sliver. Colors are
+/// translucent so a pixel filled twice shows.
+fn soup(count: usize, seed: u32) -> Soup {
+    let mut state = seed;
+    let mut next = move || {
+        state = state.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+        (state >> 8) as f32 / (1 << 24) as f32
+    };
+    let mut verts = Vec::new();
+    let mut tris = Vec::new();
+    for i in 0..count {
+        let a = Point3::new(next() * 6.0 - 3.0, next() * 6.0 - 3.0, next() * 2.0 - 1.0);
+        let (b, c) = if i % 3 == 0 {
+            let far = Point3::new(next() * 6.0 - 3.0, next() * 6.0 - 3.0, a.z);
+            (far, far + (a - far) * 0.02 + Vector3::new(0.03, 0.0, 0.0))
+        } else {
+            (
+                a + Vector3::new(next() * 2.0 - 1.0, next() * 2.0 - 1.0, 0.0),
+                a + Vector3::new(next() * 2.0 - 1.0, next() * 2.0 - 1.0, 0.0),
+            )
+        };
+        let start = verts.len();
+        verts.extend([a, b, c]);
+        tris.push(Triangle {
+            v1: start,
+            v2: start + 1,
+            v3: start + 2,
+            color: Color {
+                r: (next() * 255.0) as u8,
+                g: (next() * 255.0) as u8,
+                b: (next() * 255.0) as u8,
+                a: if i % 2 == 0 { 255 } else { 100 },
+            },
+        });
+    }
+    Soup { verts, tris }
+}
+
+#[test]
+fn backends_fill_identical_pixels() {
+    for (seed, deterministic) in (0..4).zip([false, true, false, true]) {
+        let mut world = world(vec![Object::new(soup(100, seed), 0.0, 0.0, 0.0)]);
+        world.cull_mode = CullMode::None;
+        world.gamma_correct = false;
+        world.deterministic = deterministic;
+
+        world.raster_backend = RasterBackend::BoundingBox;
+        let boxed = render(&mut world);
+        world.raster_backend = RasterBackend::Scanline;
+        let scanned = render(&mut world);
+        assert!(boxed.chunks_exact(4).any(|p| p != [255; 4]));
+        let diff = boxed.iter().zip(&scanned).position(|(a, b)| a != b);
+        assert_eq!(diff, None, "seed {seed} differs at byte {diff:?}");
+
+        // Bands split rows between threads; the walk must respect them.
+        world.threads = 3;
+        assert!(render(&mut world) == boxed, "seed {seed} with threads");
+    }
+}
+
+#[test]
+fn scanline_matches_at_frame_edges() {
+    // Big triangles hanging off every side of the frame.
+    let mut soup = soup(0, 0);
+    for (i, &(x, y)) in [(-4.0, 0.0), (4.0, 0.0), (0.0, -4.0), (0.0, 4.0)]
+        .iter()
+        .enumerate()
+    {
+        soup.verts.extend([
+            Point3::new(x - 1.5, y - 3.0, 0.0),
+            Point3::new(x + 1.5, y + 0.2, 0.0),
+            Point3::new(-x * 0.2, -y * 0.2, 0.0),
+        ]);
+        soup.tris.push(Triangle {
+            v1: i * 3,
+            v2: i * 3 + 1,
+            v3: i * 3 + 2,
+            color: Color::rgb(40 * i as u8, 90, 200),
+        });
+    }
+    let mut world = world(vec![Object::new(soup, 0.0, 0.0, 0.0)]);
+    world.cull_mode = CullMode::None;
+    let boxed = render(&mut world);
+    world.raster_backend = RasterBackend::Scanline;
+    assert!(render(&mut world) == boxed);
+    assert_eq!(boxed.len(), (WIDTH * HEIGHT * 4) as usize);
+}
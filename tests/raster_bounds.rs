@@ -0,0 +1,93 @@
+mod common;
+
+use common::{object, render, world};
+
+use engine::mesh::Color;
+use engine::mesh::quad::QuadMesh;
+use engine::raster::{draw_triangle, is_front_facing};
+use nalgebra::Point2;
+
+const W: u32 = 16;
+const H: u32 = 12;
+const GUARD: usize = 64;
+const RED: Color = Color {
+    r: 255,
+    g: 0,
+    b: 0,
+    a: 255,
+};
+
+/// Draw one triangle, both windings, into a frame with guard bytes on
+/// either side, and return the frame.
+fn draw(points: [(f32, f32); 3]) -> Vec<u8> {
+    let mut buffer = vec![7u8; GUARD + (W * H * 4) as usize + GUARD];
+    let [a, b, c] = points.map(|(x, y)| Point2::new(x, y));
+    let frame = &mut buffer[GUARD..GUARD + (W * H * 4) as usize];
+    draw_triangle(a, b, c, &RED, frame, W, H);
+    draw_triangle(a, c, b, &RED, frame, W, H);
+    assert!(buffer[..GUARD].iter().all(|&b| b == 7));
+    assert!(buffer[buffer.len() - GUARD..].iter().all(|&b| b == 7));
+    buffer[GUARD..buffer.len() - GUARD].to_vec()
+}
+
+#[test]
+fn huge_coordinates_stay_in_the_frame() {
+    let cases = [
+        [(-1e6, 1e6), (1e6, 1e6), (0.0, -1e6)],
+        [(-1e30, -1e30), (1e30, -1e30), (0.0, 1e30)],
+        [(-0.4, -0.4), (W as f32 + 0.4, -0.4), (-0.4, H as f32 + 0.4)],
+        [(-5.0, -0.9), (-0.1, -0.9), (-3.0, -0.2)],
+        [(W as f32 + 3.0, 2.0), (1e9, 2.0), (W as f32 + 5.0, 1e9)],
+        [(f32::MAX, f32::MIN), (f32::MIN, f32::MIN), (0.0, f32::MAX)],
+        [
+            (-f32::MIN_POSITIVE, -0.0),
+            (1e-40, 1e-40),
+            (f32::EPSILON, 0.0),
+        ],
+    ];
+    for points in cases {
+        draw(points);
+    }
+    // A triangle covering everything paints every pixel.
+    let frame = draw([(-1e6, -1e6), (1e6, -1e6), (0.0, 1e6)]);
+    assert!(frame.chunks(4).all(|p| p == [255, 0, 0, 255]));
+}
+
+#[test]
+fn non_finite_coordinates_draw_nothing() {
+    let cases = [
+        [(f32::NAN, 0.0), (10.0, 0.0), (0.0, 10.0)],
+        [(0.0, 0.0), (f32::INFINITY, 0.0), (0.0, 10.0)],
+        [(0.0, 0.0), (10.0, 0.0), (f32::NEG_INFINITY, f32::NAN)],
+    ];
+    for points in cases {
+        let frame = draw(points);
+        assert!(frame.iter().all(|&b| b == 7), "{points:?}");
+    }
+}
+
+#[test]
+fn winding_test_handles_extremes() {
+    let p = |x: f32, y: f32| Point2::new(x, y);
+    assert!(!is_front_facing(p(f32::NAN, 0.0), p(1.0, 0.0), p(0.0, 1.0)));
+    assert_ne!(
+        is_front_facing(p(-1e6, 1e6), p(1e6, 1e6), p(0.0, -1e6)),
+        is_front_facing(p(-1e6, 1e6), p(0.0, -1e6), p(1e6, 1e6))
+    );
+}
+
+#[test]
+fn geometry_grazing_the_near_plane_renders() {
+    // A huge floor reaching right up to the camera projects vertices to
+    // screen coordinates far outside the frame.
+    let mut floor = object(QuadMesh::grid(400.0, 400.0, 8, RED), 0.0, -0.5, 0.0);
+    floor.rotation = nalgebra::UnitQuaternion::from_axis_angle(
+        &nalgebra::Vector3::x_axis(),
+        std::f32::consts::FRAC_PI_2,
+    );
+    let mut world = world(vec![floor]);
+    world.camera.position.z = -4.95;
+    render(&mut world);
+    world.threads = 3;
+    render(&mut world);
+}
@@ -0,0 +1,108 @@
+use std::path::PathBuf;
+
+use engine::mesh::Color;
+use engine::reduce::reduce;
+use engine::scene_file::{SceneBox, SceneFile};
+use nalgebra::Point3;
+
+/// Six models, a light and a gradient, with OBJ files in a folder of
+/// their own.
+fn scene(name: &str) -> (SceneFile, PathBuf) {
+    let dir = std::env::temp_dir().join(format!("shapes-reduce-{name}-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("tri.obj"), "v 0 0 0\nv 1 0 0\nv 0 2 0\nf 1 2 3\n").unwrap();
+    let mut text =
+        String::from("camera 0 2 -8 0 0 0\nlight -1 1 -1 0 0 0\ngradient #87ceeb #f0f0e0\n");
+    for i in 0..6 {
+        text.push_str(&format!("model tri.obj {i} 0 4\n"));
+    }
+    text.push_str("model missing.obj 9 0 4\n");
+    let path = dir.join("repro.scene");
+    std::fs::write(&path, text).unwrap();
+    (SceneFile::from_path(&path).unwrap(), dir)
+}
+
+/// Whether anything, model or box, sits at `x`.
+fn has(scene: &SceneFile, x: f32) -> bool {
+    scene.models.iter().any(|model| model.position.x == x)
+        || scene.boxes.iter().any(|b| b.min.x == x)
+}
+
+#[test]
+fn only_what_the_failure_needs_is_kept() {
+    let (original, dir) = scene("needs");
+    let mut tries = 0;
+    // Fails with the models at 1 and 4 and the light, whatever else is
+    // there.
+    let reduced = reduce(&original, |cut| {
+        tries += 1;
+        has(cut, 1.0) && has(cut, 4.0) && cut.light.is_some()
+    });
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    assert_eq!(reduced.camera, original.camera);
+    assert!(reduced.light.is_some());
+    assert_eq!(reduced.gradient, None);
+    assert!(reduced.models.is_empty());
+    let corners: Vec<_> = reduced.boxes.iter().map(|b| (b.min, b.max)).collect();
+    assert_eq!(
+        corners,
+        [
+            (Point3::new(1.0, 0.0, 4.0), Point3::new(2.0, 2.0, 4.0)),
+            (Point3::new(4.0, 0.0, 4.0), Point3::new(5.0, 2.0, 4.0)),
+        ]
+    );
+    // Fewer than trying every subset of eight parts.
+    assert!(tries < 60, "{tries}");
+}
+
+#[test]
+fn models_that_cannot_be_read_are_kept_as_they_are() {
+    let (original, dir) = scene("missing");
+    let reduced = reduce(&original, |cut| has(cut, 9.0));
+    std::fs::remove_dir_all(&dir).unwrap();
+    assert_eq!(reduced.models.len(), 1);
+    assert_eq!(reduced.models[0].position.x, 9.0);
+    assert!(reduced.boxes.is_empty());
+    assert_eq!((reduced.light, reduced.gradient), (None, None));
+}
+
+#[test]
+fn a_failure_that_needs_the_model_itself_keeps_it() {
+    let (original, dir) = scene("model");
+    let reduced = reduce(&original, |cut| {
+        cut.models.iter().any(|model| model.position.x == 3.0)
+    });
+    std::fs::remove_dir_all(&dir).unwrap();
+    assert_eq!(reduced.models.len(), 1);
+    assert!(reduced.boxes.is_empty());
+}
+
+#[test]
+fn scenes_that_pass_are_left_alone() {
+    let (original, dir) = scene("passing");
+    std::fs::remove_dir_all(&dir).unwrap();
+    let mut tries = 0;
+    let reduced = reduce(&original, |_| {
+        tries += 1;
+        false
+    });
+    assert_eq!(reduced, original);
+    assert_eq!(tries, 1);
+
+    // Boxes go too, and a failure needing nothing leaves only the camera.
+    let mut boxed = original.clone();
+    boxed.boxes.push(SceneBox {
+        min: Point3::origin(),
+        max: Point3::new(1.0, 1.0, 1.0),
+        color: Color::RED,
+    });
+    let empty = reduce(&boxed, |_| true);
+    assert_eq!(
+        empty,
+        SceneFile {
+            camera: original.camera,
+            ..SceneFile::default()
+        }
+    );
+}
@@ -0,0 +1,89 @@
+mod common;
+
+use common::{HEIGHT, WHITE, WIDTH, object, pixel, render, world};
+use engine::UnsupportedSsaa;
+use engine::mesh::p_hack::PHackMesh;
+use engine::mesh::quad::QuadMesh;
+use engine::mesh::{Color, Mesh};
+
+#[test]
+fn empty_world_is_white() {
+    let mut world = world(vec![]);
+    let frame = render(&mut world);
+    assert!(frame.iter().all(|&b| b == 255));
+}
+
+#[test]
+fn mesh_covers_center_but_not_corner() {
+    let mut world = world(vec![object(PHackMesh::new(), 0.0, 0.0, 0.0)]);
+    let frame = render(&mut world);
+
+    assert_ne!(pixel(&frame, WIDTH / 2, HEIGHT / 2), WHITE);
+    assert_eq!(pixel(&frame, 0, 0), WHITE);
+}
+
+#[test]
+fn supersampling_softens_edges_and_keeps_flat_areas() {
+    let mut world = world(vec![object(PHackMesh::new(), 0.0, 0.0, 0.0)]);
+    let plain = render(&mut world);
+
+    world.set_ssaa(4).unwrap();
+    assert_eq!(world.ssaa(), 4);
+    let smooth = render(&mut world);
+
+    // Background and solid interiors are unchanged, only edges blend.
+    assert_eq!(pixel(&smooth, 0, 0), WHITE);
+    assert_eq!(
+        pixel(&smooth, WIDTH / 2, HEIGHT / 2),
+        pixel(&plain, WIDTH / 2, HEIGHT / 2)
+    );
+    assert!(smooth != plain);
+
+    world.set_ssaa(1).unwrap();
+    assert!(render(&mut world) == plain);
+}
+
+#[test]
+fn unsupported_supersampling_factors_are_refused() {
+    let mut world = world(vec![object(PHackMesh::new(), 0.0, 0.0, 0.0)]);
+    world.set_ssaa(2).unwrap();
+    for factor in [0, 3, 8, u32::MAX] {
+        assert_eq!(world.set_ssaa(factor), Err(UnsupportedSsaa(factor)));
+        assert_eq!(world.ssaa(), 2);
+    }
+}
+
+#[test]
+fn stats_count_culled_triangles() {
+    let mut world = world(vec![object(PHackMesh::new(), 0.0, 0.0, 0.0)]);
+    render(&mut world);
+    let stats = world.stats();
+    assert_eq!(stats.triangles_submitted, PHackMesh::new().tris().len());
+    assert!(stats.triangles_rasterized > 0);
+    assert!(stats.triangles_rasterized < stats.triangles_submitted);
+}
+
+#[test]
+fn offscreen_objects_and_triangles_are_rejected_before_drawing() {
+    let mut world = world(vec![object(PHackMesh::new(), 40.0, 0.0, 0.0)]);
+    let frame = render(&mut world);
+    let stats = world.stats();
+    assert_eq!(stats.objects_culled, 1);
+    assert_eq!(stats.objects_drawn, 0);
+    assert_eq!(stats.triangles_rasterized, 0);
+    assert!(frame.chunks_exact(4).all(|p| p == WHITE));
+
+    // A floor much wider than the view: the cells past the edges are
+    // dropped one by one.
+    let floor = QuadMesh::grid(30.0, 30.0, 10, Color::rgb(0, 120, 0));
+    world.models[0] = object(floor, 0.0, 0.0, 0.0);
+    render(&mut world);
+    let stats = world.stats();
+    assert_eq!(stats.objects_drawn, 1);
+    assert!(stats.triangles_offscreen > 0);
+    assert!(stats.triangles_rasterized > 0);
+    assert_eq!(
+        stats.triangles_offscreen + stats.triangles_rasterized,
+        stats.triangles_submitted
+    );
+}
@@ -0,0 +1,143 @@
+mod common;
+
+use std::time::{Duration, Instant};
+
+use common::world;
+
+use engine::render_scale::{
+    ADAPTIVE_STEP, AdaptiveScale, MAX_RENDER_SCALE, MIN_RENDER_SCALE, resample, scaled_size,
+    step_render_scale,
+};
+
+const BUDGET: Duration = Duration::from_millis(16);
+
+/// Record `count` frames of `ms` milliseconds.
+fn frames(adaptive: &mut AdaptiveScale, count: usize, ms: u64) {
+    for _ in 0..count {
+        adaptive.record(Duration::from_millis(ms));
+    }
+}
+
+fn on_step(scale: f32) -> bool {
+    let steps = scale / ADAPTIVE_STEP;
+    (steps - steps.round()).abs() < 1e-3
+}
+
+#[test]
+fn scaled_size_follows_the_window() {
+    assert_eq!(scaled_size(1000, 600, 1.0), (1000, 600));
+    assert_eq!(scaled_size(1000, 600, 0.5), (500, 300));
+    assert_eq!(scaled_size(1000, 600, 0.75), (750, 450));
+    // Out of range scales are clamped, and tiny windows keep a pixel.
+    assert_eq!(scaled_size(100, 100, 8.0), (200, 200));
+    assert_eq!(scaled_size(1, 1, 0.0), (1, 1));
+}
+
+#[test]
+fn steps_snap_and_stop_at_the_ends() {
+    assert_eq!(step_render_scale(1.0, 1), 1.25);
+    assert_eq!(step_render_scale(1.0, -2), 0.5);
+    assert_eq!(step_render_scale(0.6, 0), 0.5);
+    assert_eq!(step_render_scale(MIN_RENDER_SCALE, -1), MIN_RENDER_SCALE);
+    assert_eq!(step_render_scale(MAX_RENDER_SCALE, 1), MAX_RENDER_SCALE);
+}
+
+#[test]
+fn enlarging_repeats_pixels() {
+    let src = [10, 20, 30, 255, 200, 100, 0, 128];
+    let mut dst = vec![0; 4 * 4 * 2];
+    resample(&src, 2, 1, &mut dst, 4, 2);
+    for row in dst.chunks(16) {
+        assert_eq!(&row[..8], &[10, 20, 30, 255, 10, 20, 30, 255]);
+        assert_eq!(&row[8..], &[200, 100, 0, 128, 200, 100, 0, 128]);
+    }
+}
+
+#[test]
+fn shrinking_averages_in_linear_light() {
+    // Black and white halves average to the sRGB value of half the light,
+    // not to 128.
+    let src = [0, 0, 0, 255, 255, 255, 255, 255];
+    let mut dst = [0; 4];
+    resample(&src, 2, 1, &mut dst, 1, 1);
+    assert!((186..=189).contains(&dst[0]), "{}", dst[0]);
+    assert_eq!(dst[3], 255);
+}
+
+#[test]
+fn uneven_scales_cover_the_whole_image() {
+    let src: Vec<u8> = (0..3 * 3).flat_map(|_| [50, 60, 70, 255]).collect();
+    let mut dst = vec![0; 2 * 2 * 4];
+    resample(&src, 3, 3, &mut dst, 2, 2);
+    assert!(dst.chunks(4).all(|pixel| pixel == [50, 60, 70, 255]));
+
+    let mut dst = vec![0; 5 * 5 * 4];
+    resample(&src, 3, 3, &mut dst, 5, 5);
+    assert!(dst.chunks(4).all(|pixel| pixel == [50, 60, 70, 255]));
+}
+
+#[test]
+fn slow_frames_lower_the_scale() {
+    let mut adaptive = AdaptiveScale::new(BUDGET);
+    let now = Instant::now();
+    frames(&mut adaptive, 20, 32);
+    let scale = adaptive.adjust(1.0, now);
+    // Half the pixels would about halve the time.
+    assert!((0.6..=0.7).contains(&scale), "{scale}");
+    assert!(on_step(scale));
+}
+
+#[test]
+fn fast_frames_raise_the_scale() {
+    let mut adaptive = AdaptiveScale::new(BUDGET);
+    frames(&mut adaptive, 20, 4);
+    let scale = adaptive.adjust(0.5, Instant::now());
+    assert!(scale > 0.5 && scale <= MAX_RENDER_SCALE, "{scale}");
+    assert!(on_step(scale));
+}
+
+#[test]
+fn frames_near_the_budget_keep_the_scale() {
+    let mut adaptive = AdaptiveScale::new(BUDGET);
+    frames(&mut adaptive, 20, 14);
+    assert_eq!(adaptive.adjust(0.8, Instant::now()), 0.8);
+}
+
+#[test]
+fn changes_wait_for_fresh_frames_and_half_a_second() {
+    let mut adaptive = AdaptiveScale::new(BUDGET);
+    let start = Instant::now();
+    frames(&mut adaptive, 19, 40);
+    assert_eq!(adaptive.adjust(1.0, start), 1.0);
+    frames(&mut adaptive, 1, 40);
+    let lowered = adaptive.adjust(1.0, start);
+    assert!(lowered < 1.0);
+
+    // The old frames were measured at the old scale.
+    assert_eq!(adaptive.adjust(lowered, start), lowered);
+    frames(&mut adaptive, 20, 40);
+    assert_eq!(
+        adaptive.adjust(lowered, start + Duration::from_millis(100)),
+        lowered
+    );
+    assert!(adaptive.adjust(lowered, start + Duration::from_millis(600)) < lowered);
+}
+
+#[test]
+fn the_scale_stays_in_range() {
+    let mut adaptive = AdaptiveScale::new(BUDGET);
+    frames(&mut adaptive, 20, 1000);
+    assert_eq!(adaptive.adjust(0.3, Instant::now()), MIN_RENDER_SCALE);
+}
+
+#[test]
+fn worlds_adapt_only_with_a_target() {
+    let mut world = world(vec![]);
+    assert_eq!(world.target_frame_time(), None);
+    assert_eq!(world.adapt_render_scale(0.75), 0.75);
+
+    world.set_target_frame_time(BUDGET);
+    assert_eq!(world.target_frame_time(), Some(BUDGET));
+    world.clear_target_frame_time();
+    assert_eq!(world.target_frame_time(), None);
+}
@@ -0,0 +1,77 @@
+mod common;
+
+use std::time::Duration;
+
+use common::{WHITE, object, render, world};
+
+use engine::mesh::Color;
+use engine::mesh::cube::CubeMesh;
+use engine::{Object, World};
+use nalgebra::Point3;
+
+fn cube_world() -> World {
+    world(vec![object(CubeMesh::new(Color::RED), 0.0, 0.0, 0.0)])
+}
+
+#[test]
+fn cube_seen_along_an_axis_shows_one_face() {
+    let mut world = cube_world();
+    let frame = render(&mut world);
+    let stats = world.draw(world.camera.generate_view_mat(), &mut frame.clone(), 64, 64);
+    assert_eq!(stats, world.stats());
+    assert_eq!(stats.objects_drawn, 1);
+    assert_eq!(stats.objects_culled, 0);
+    assert_eq!(stats.triangles_submitted, 12);
+    assert_eq!(stats.triangles_clipped, 0);
+    assert_eq!(stats.triangles_offscreen, 0);
+    assert_eq!(stats.triangles_backface_culled, 10);
+    assert_eq!(stats.triangles_rasterized, 2);
+
+    // Pixels on the diagonal both triangles share are written twice.
+    let covered = frame.chunks_exact(4).filter(|&p| p != WHITE).count();
+    assert!(stats.pixels_written >= covered);
+    assert!(stats.pixels_written < covered + 64);
+    assert!(stats.draw_time > Duration::ZERO);
+}
+
+#[test]
+fn cube_seen_from_a_corner_shows_three_faces() {
+    let mut world = cube_world();
+    world.camera.position = Point3::new(4.0, 4.0, -4.0);
+    world.camera.look_at(Point3::origin());
+    render(&mut world);
+    let stats = world.stats();
+    assert_eq!(stats.triangles_submitted, 12);
+    assert_eq!(stats.triangles_backface_culled, 6);
+    assert_eq!(stats.triangles_rasterized, 6);
+}
+
+#[test]
+fn camera_inside_a_cube_clips_the_faces_behind_it() {
+    let mut world = world(vec![Object {
+        scale: 10.0,
+        ..object(CubeMesh::new(Color::RED), 0.0, 0.0, 0.0)
+    }]);
+    render(&mut world);
+    let stats = world.stats();
+    // Every triangle of the near face and the four sides has a corner
+    // behind the eye; only the far face, seen from inside, is left.
+    assert_eq!(stats.triangles_clipped, 10);
+    assert_eq!(stats.triangles_backface_culled, 2);
+    assert_eq!(stats.triangles_rasterized, 0);
+}
+
+#[test]
+fn objects_out_of_view_are_culled_whole() {
+    let mut world = world(vec![
+        object(CubeMesh::new(Color::RED), 0.0, 0.0, 0.0),
+        object(CubeMesh::new(Color::RED), 0.0, 0.0, -20.0),
+        object(CubeMesh::new(Color::RED), 0.0, 30.0, 0.0),
+    ]);
+    render(&mut world);
+    let stats = world.stats();
+    assert_eq!(stats.objects_drawn, 1);
+    assert_eq!(stats.objects_culled, 2);
+    assert_eq!(stats.triangles_submitted, 36);
+    assert_eq!(stats.triangles_rasterized, 2);
+}
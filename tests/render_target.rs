@@ -0,0 +1,106 @@
+mod common;
+
+use common::{HEIGHT, WIDTH, object, render, world};
+use engine::mesh::Color;
+use engine::mesh::cube::CubeMesh;
+use engine::target::{BufferTarget, RenderTarget};
+
+const PAD: u8 = 0xAB;
+
+fn cube_world() -> engine::World {
+    world(vec![object(
+        CubeMesh::new(Color::rgb(220, 30, 30)),
+        0.0,
+        0.0,
+        0.0,
+    )])
+}
+
+/// Pixels stored blue first, the way some windowing toolkits want them.
+struct Bgra {
+    pixels: Vec<[u8; 4]>,
+}
+
+impl RenderTarget for Bgra {
+    fn width(&self) -> u32 {
+        WIDTH
+    }
+
+    fn height(&self) -> u32 {
+        HEIGHT
+    }
+
+    fn stride(&self) -> usize {
+        WIDTH as usize * 4
+    }
+
+    fn put_row(&mut self, y: u32, rgba: &[u8]) {
+        let start = (y * WIDTH) as usize;
+        for (pixel, p) in self.pixels[start..].iter_mut().zip(rgba.chunks_exact(4)) {
+            *pixel = [p[2], p[1], p[0], p[3]];
+        }
+    }
+}
+
+#[test]
+fn padded_rows_keep_their_padding() {
+    let expected = render(&mut cube_world());
+    let stride = WIDTH as usize * 4 + 24;
+    let mut buf = vec![PAD; stride * HEIGHT as usize];
+    let mut world = cube_world();
+    let view = world.camera.generate_view_mat();
+    let stats = world.draw_to(
+        view,
+        &mut BufferTarget::with_stride(&mut buf, WIDTH, HEIGHT, stride),
+    );
+    assert!(stats.triangles_rasterized > 0);
+
+    let row = WIDTH as usize * 4;
+    for (y, line) in buf.chunks(stride).enumerate() {
+        assert_eq!(&line[..row], &expected[y * row..(y + 1) * row], "row {y}");
+        assert!(line[row..].iter().all(|&b| b == PAD), "padding of row {y}");
+    }
+}
+
+#[test]
+fn packed_buffers_draw_like_plain_frames() {
+    let expected = render(&mut cube_world());
+    let mut buf = vec![0; (WIDTH * HEIGHT * 4) as usize];
+    let mut world = cube_world();
+    let view = world.camera.generate_view_mat();
+    let mut target = BufferTarget::new(&mut buf, WIDTH, HEIGHT);
+    assert!(target.packed().is_some());
+    world.draw_to(view, &mut target);
+    assert_eq!(buf, expected);
+}
+
+#[test]
+fn unpacked_targets_get_the_frame_a_row_at_a_time() {
+    let expected = render(&mut cube_world());
+    let mut target = Bgra {
+        pixels: vec![[0; 4]; (WIDTH * HEIGHT) as usize],
+    };
+    let mut world = cube_world();
+    let view = world.camera.generate_view_mat();
+    world.draw_to(view, &mut target);
+    for (p, e) in target.pixels.iter().zip(expected.chunks(4)) {
+        assert_eq!(*p, [e[2], e[1], e[0], e[3]]);
+    }
+}
+
+#[test]
+#[should_panic(expected = "shorter than a row")]
+fn strides_shorter_than_a_row_are_refused() {
+    let mut buf = vec![0; 64];
+    BufferTarget::with_stride(&mut buf, 4, 4, 8);
+}
+
+#[test]
+fn the_last_row_needs_no_padding() {
+    // 3 rows of 2 pixels, 12 bytes apart, end 8 bytes into the third.
+    let mut buf = vec![PAD; 32];
+    let mut target = BufferTarget::with_stride(&mut buf, 2, 3, 12);
+    target.put_row(2, &[4, 5, 6, 255, 1, 2, 3, 255]);
+    assert_eq!(&buf[24..32], &[4, 5, 6, 255, 1, 2, 3, 255]);
+    assert!(buf[20..24].iter().all(|&b| b == PAD));
+}
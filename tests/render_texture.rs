@@ -0,0 +1,151 @@
+mod common;
+
+use common::{object, pixel, render, world};
+use engine::mesh::Color;
+use engine::mesh::cube::CubeMesh;
+use engine::mesh::quad::QuadMesh;
+use engine::render_texture::RenderTargetHandle;
+use engine::viewport::Projection;
+use engine::{Camera, Material, World};
+use nalgebra::Point3;
+
+const RED: Color = Color::rgb(255, 0, 0);
+const BLUE: Color = Color::rgb(0, 0, 255);
+
+/// Half the height the main camera sees at the origin, so a quad this
+/// wide fills the middle half of the frame.
+const HALF_VIEW: f32 = 2.7315;
+
+/// A red cube well out of the main camera's view, and a second camera
+/// looking at it from close by.
+fn watched() -> World {
+    let mut world = world(Vec::new());
+    let glow = world.materials.insert(
+        "glow",
+        Material {
+            unlit: true,
+            ..Material::new(RED)
+        },
+    );
+    world
+        .add_object(object(CubeMesh::new(RED), 30.0, 0.0, 0.0).with_material(glow))
+        .unwrap();
+    world.cameras.push(Camera::looking_at(
+        Point3::new(30.0, 0.0, -3.0),
+        Point3::new(30.0, 0.0, 0.0),
+    ));
+    world
+}
+
+/// A screen at the origin facing the main camera, showing `target`.
+fn add_screen(world: &mut World, target: RenderTargetHandle) {
+    let screen = world.materials.insert(
+        "screen",
+        Material {
+            unlit: true,
+            render_target: Some(target),
+            ..Material::new(Color::WHITE)
+        },
+    );
+    world
+        .add_object(
+            object(
+                QuadMesh::new(HALF_VIEW, HALF_VIEW, Color::WHITE),
+                0.0,
+                0.0,
+                0.0,
+            )
+            .with_material(screen),
+        )
+        .unwrap();
+}
+
+#[test]
+fn screens_show_what_their_camera_sees() {
+    let mut world = watched();
+    let target = world.add_render_target(1, Projection::Main, 32, 32);
+    let texture = world.render_target(target).unwrap().texture();
+    assert_eq!(
+        world.materials.texture(texture).unwrap().texel(16, 16),
+        Color::WHITE
+    );
+    add_screen(&mut world, target);
+
+    let frame = render(&mut world);
+    assert_eq!(pixel(&frame, 32, 32), [255, 0, 0, 255]);
+    // The cube fills the middle of the target, not its corners.
+    let drawn = world.materials.texture(texture).unwrap();
+    assert_eq!(drawn.texel(16, 16), RED);
+    assert_eq!(drawn.texel(1, 1), Color::WHITE);
+    // Round the screen's edge is the main camera's own background.
+    assert_eq!(pixel(&frame, 4, 32), [255, 255, 255, 255]);
+
+    // Without a camera the target is left as it was.
+    world.render_target_mut(target).unwrap().camera_index = 5;
+    world.models[0].visible = false;
+    let frame = render(&mut world);
+    assert_eq!(pixel(&frame, 32, 32), [255, 0, 0, 255]);
+}
+
+/// A frame of a screen filling the middle half of the view and showing
+/// that same view, drawn `depth` times over.
+fn self_view(depth: u32) -> Vec<u8> {
+    let mut world = world(Vec::new()).with_clear_color(BLUE);
+    world.render_target_depth = depth;
+    let target = world.add_render_target(0, Projection::Main, common::WIDTH, common::HEIGHT);
+    add_screen(&mut world, target);
+    render(&mut world)
+}
+
+#[test]
+fn a_screen_seeing_itself_goes_only_so_deep() {
+    // Each screen within shows the one before at half the size, so the
+    // innermost, blank one reaches 16 / 2^depth pixels from the middle.
+    let shallow = self_view(1);
+    assert_eq!(pixel(&shallow, 36, 32), [255, 255, 255, 255]);
+    assert_eq!(pixel(&shallow, 44, 32), [0, 0, 255, 255]);
+
+    let deeper = self_view(2);
+    assert_eq!(pixel(&deeper, 34, 32), [255, 255, 255, 255]);
+    assert_eq!(pixel(&deeper, 38, 32), [0, 0, 255, 255]);
+    // Past the screen it is the background either way.
+    assert_eq!(pixel(&deeper, 4, 32), [0, 0, 255, 255]);
+}
+
+#[test]
+fn targets_can_be_drawn_every_few_frames() {
+    let mut world = watched();
+    let target = world.add_render_target(1, Projection::Main, 32, 32);
+    world.render_target_mut(target).unwrap().interval = 2;
+    add_screen(&mut world, target);
+    let texture = world.render_target(target).unwrap().texture();
+    let centre = |world: &World| world.materials.texture(texture).unwrap().texel(16, 16);
+
+    render(&mut world);
+    assert_eq!(centre(&world), RED);
+    world.models[0].visible = false;
+    render(&mut world);
+    assert_eq!(centre(&world), RED);
+    render(&mut world);
+    assert_eq!(centre(&world), Color::WHITE);
+}
+
+#[test]
+fn unchanged_targets_leave_the_frame_unchanged() {
+    let mut world = watched();
+    let target = world.add_render_target(1, Projection::Main, 32, 32);
+    add_screen(&mut world, target);
+    let texture = world.render_target(target).unwrap().texture();
+    let generation = |world: &World| world.materials.texture(texture).unwrap().generation();
+    let view = world.camera.generate_view_mat();
+    render(&mut world);
+    let (settled, drawn) = (world.fingerprint(&view), generation(&world));
+    render(&mut world);
+    assert_eq!(world.fingerprint(&view), settled);
+    assert_eq!(generation(&world), drawn);
+
+    // A change only the target sees changes its texture.
+    world.models[0].visible = false;
+    render(&mut world);
+    assert!(generation(&world) > drawn);
+}
@@ -0,0 +1,58 @@
+mod common;
+
+use common::{HEIGHT, WHITE, WIDTH, object, pixel, render, world};
+
+use engine::mesh::Color;
+use engine::mesh::cube::CubeMesh;
+use engine::mesh::p_hack::PHackMesh;
+
+const RED: Color = Color {
+    r: 255,
+    g: 0,
+    b: 0,
+    a: 255,
+};
+
+#[test]
+fn added_objects_are_drawn() {
+    let mut world = world(vec![]);
+    assert_eq!(
+        world.add_object(object(CubeMesh::new(RED), 0.0, 0.0, 0.0)),
+        Ok(0)
+    );
+    assert_eq!(
+        world.add_object(object(PHackMesh::new(), 5.0, 0.0, 0.0)),
+        Ok(1)
+    );
+    let frame = render(&mut world);
+    assert_ne!(pixel(&frame, WIDTH / 2, HEIGHT / 2), WHITE);
+}
+
+#[test]
+fn removing_keeps_the_selection_on_its_object() {
+    let mut world = world(vec![
+        object(CubeMesh::new(RED), 0.0, 0.0, 0.0),
+        object(CubeMesh::new(RED), 1.0, 0.0, 0.0),
+        object(CubeMesh::new(RED), 2.0, 0.0, 0.0),
+    ]);
+    world.selected = Some(2);
+
+    let removed = world.remove_object(0).unwrap();
+    assert_eq!(removed.offset_x, 0.0);
+    assert_eq!(world.selected, Some(1));
+    assert_eq!(world.models[1].offset_x, 2.0);
+
+    assert!(world.remove_object(5).is_none());
+    assert_eq!(world.selected, Some(1));
+
+    world.remove_object(1);
+    assert_eq!(world.selected, None);
+    assert_eq!(world.models.len(), 1);
+}
+
+#[test]
+fn removing_the_last_object_empties_the_frame() {
+    let mut world = world(vec![object(CubeMesh::new(RED), 0.0, 0.0, 0.0)]);
+    world.remove_object(0);
+    assert!(render(&mut world).iter().all(|&b| b == 255));
+}
@@ -0,0 +1,211 @@
+mod common;
+
+use std::path::{Path, PathBuf};
+
+use common::world;
+use engine::mesh::Color;
+use engine::scene_file::{BOX_COLOR, SceneBox, SceneFile, SceneFileError, SceneModel};
+use engine::world::ClearMode;
+use nalgebra::{Matrix4, Point3};
+
+const ROOM: &str = "\
+# a crate on the floor
+camera 0 2 -8  0 0 0
+
+light 3 4 -2  0 1 0
+model crate.obj 0 0 4
+model statue.obj 3 0 4 0.5
+";
+
+#[test]
+fn reads_the_camera_light_and_models() {
+    let scene = SceneFile::parse(ROOM).unwrap();
+    assert_eq!(
+        scene.camera,
+        Some((Point3::new(0.0, 2.0, -8.0), Point3::origin()))
+    );
+    assert_eq!(
+        scene.light,
+        Some((Point3::new(3.0, 4.0, -2.0), Point3::new(0.0, 1.0, 0.0)))
+    );
+    assert_eq!(
+        scene.models,
+        [
+            SceneModel {
+                path: PathBuf::from("crate.obj"),
+                position: Point3::new(0.0, 0.0, 4.0),
+                scale: 1.0,
+            },
+            SceneModel {
+                path: PathBuf::from("statue.obj"),
+                position: Point3::new(3.0, 0.0, 4.0),
+                scale: 0.5,
+            },
+        ]
+    );
+    assert_eq!(SceneFile::parse("").unwrap(), SceneFile::default());
+}
+
+#[test]
+fn bad_files_report_the_line() {
+    for (text, bad_line) in [
+        ("camera 0 0 0\n", 1),
+        ("# fine\nlight 0 0 0 0 0 x\n", 2),
+        ("\nmodel\n", 2),
+        ("model a.obj 0 0\n", 1),
+        ("model a.obj 0 0 0 -1\n", 1),
+        ("camera 0 0 -5 0 0 0\nsun 1 1 1\n", 2),
+        ("clear #ff0000 #00ff00\n", 1),
+        ("\ngradient #zz0000 #000000\n", 2),
+        ("box 0 0 0 1 1\n", 1),
+        ("box 0 0 0 1 1 1 #12345\n", 1),
+    ] {
+        match SceneFile::parse(text) {
+            Err(SceneFileError::Parse { line, .. }) => assert_eq!(line, bad_line, "{text:?}"),
+            other => panic!("{text:?} parsed as {other:?}"),
+        }
+    }
+}
+
+#[test]
+fn model_paths_are_relative_to_the_scene_file() {
+    let dir = std::env::temp_dir().join(format!("shapes-scene-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("room.scene");
+    std::fs::write(&path, ROOM).unwrap();
+    let scene = SceneFile::from_path(&path).unwrap();
+    std::fs::remove_dir_all(&dir).unwrap();
+    assert_eq!(scene.models[0].path, dir.join("crate.obj"));
+    assert!(matches!(
+        SceneFile::from_path(Path::new("no-such.scene")),
+        Err(SceneFileError::Io(_))
+    ));
+}
+
+#[test]
+fn loading_places_the_camera_light_and_models() {
+    let scene = SceneFile::parse(ROOM).unwrap();
+    let mut world = world(Vec::new());
+    scene.place_camera(&mut world.camera);
+    let indices = scene.load_into(&mut world);
+
+    assert_eq!(world.camera.position, Point3::new(0.0, 2.0, -8.0));
+    let facing = (world.camera.target() - world.camera.position).normalize();
+    let wanted = (Point3::origin() - world.camera.position).normalize();
+    assert!((facing - wanted).norm() < 1e-4, "{facing:?}");
+    assert_eq!(world.light.position, Point3::new(3.0, 4.0, -2.0));
+    assert_eq!(indices, [0, 1]);
+    assert_eq!(world.models[1].offset_z, 4.0);
+    assert_eq!(world.models[1].scale, 0.5);
+    assert_eq!(
+        world.models[0].source.as_deref(),
+        Some(Path::new("crate.obj"))
+    );
+
+    // A file without a camera or light leaves them where they were.
+    let models = SceneFile::parse("model crate.obj 0 0 0").unwrap();
+    models.place_camera(&mut world.camera);
+    models.load_into(&mut world);
+    assert_eq!(world.camera.position, Point3::new(0.0, 2.0, -8.0));
+    assert_eq!(world.light.position, Point3::new(3.0, 4.0, -2.0));
+}
+
+#[test]
+fn replacing_swaps_only_the_scene_files_objects() {
+    let mut world = world(Vec::new());
+    let before = SceneFile::parse("model a.obj 0 0 0\nmodel b.obj 1 0 0")
+        .unwrap()
+        .load_into(&mut world);
+    let other = world.load_object_async("c.obj", Matrix4::identity());
+    assert_eq!((before.as_slice(), other), ([0, 1].as_slice(), 2));
+
+    let after = SceneFile::parse("model d.obj 0 0 5")
+        .unwrap()
+        .replace(&mut world, &before);
+    assert_eq!(after, [1]);
+    let sources: Vec<_> = world
+        .models
+        .iter()
+        .map(|model| model.source.as_deref().unwrap())
+        .collect();
+    assert_eq!(sources, [Path::new("c.obj"), Path::new("d.obj")]);
+    assert_eq!(world.models[1].offset_z, 5.0);
+}
+
+#[test]
+fn the_background_is_a_clear_color_or_a_gradient() {
+    let mut world = world(Vec::new());
+    let clear = SceneFile::parse("clear #202830").unwrap();
+    assert_eq!(clear.clear_color, Some(Color::rgb(0x20, 0x28, 0x30)));
+    clear.load_into(&mut world);
+    assert_eq!(world.clear_color, Color::rgb(0x20, 0x28, 0x30));
+    assert_eq!(world.clear_mode, ClearMode::Solid);
+
+    let (top, bottom) = (Color::rgb(0x87, 0xce, 0xeb), Color::rgb(0xf0, 0xf0, 0xe0));
+    SceneFile::parse("# sky\ngradient #87ceeb #f0f0e0")
+        .unwrap()
+        .load_into(&mut world);
+    assert_eq!(world.clear_mode, ClearMode::Gradient { top, bottom });
+
+    // A file that doesn't mention the background leaves it be.
+    SceneFile::parse("model a.obj 0 0 0")
+        .unwrap()
+        .load_into(&mut world);
+    assert_eq!(world.clear_mode, ClearMode::Gradient { top, bottom });
+}
+
+#[test]
+fn boxes_span_their_corners() {
+    let scene = SceneFile::parse("box 1 0 2  -1 3 0\nbox 0 0 0 1 1 1 #ff000080").unwrap();
+    assert_eq!(
+        scene.boxes,
+        [
+            SceneBox {
+                min: Point3::new(-1.0, 0.0, 0.0),
+                max: Point3::new(1.0, 3.0, 2.0),
+                color: BOX_COLOR,
+            },
+            SceneBox {
+                min: Point3::origin(),
+                max: Point3::new(1.0, 1.0, 1.0),
+                color: Color {
+                    a: 0x80,
+                    ..Color::RED
+                },
+            },
+        ]
+    );
+
+    // Boxes are added as they are, after the models.
+    let mut world = world(Vec::new());
+    let indices = SceneFile::parse("box 1 0 2 -1 3 0\nmodel a.obj 0 0 0")
+        .unwrap()
+        .load_into(&mut world);
+    assert_eq!(indices, [0, 1]);
+    assert_eq!(
+        world.models[1].mesh.aabb(),
+        (Point3::new(-1.0, 0.0, 0.0), Point3::new(1.0, 3.0, 2.0))
+    );
+}
+
+#[test]
+fn saved_scenes_read_back_the_same() {
+    let dir = std::env::temp_dir().join(format!("shapes-scene-save-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("room.scene");
+    std::fs::write(
+        &path,
+        format!("{ROOM}clear #202830\ngradient #87ceeb #f0f0e0\nbox 0 0.25 0 1 1 1 #336699\n"),
+    )
+    .unwrap();
+    let scene = SceneFile::from_path(&path).unwrap();
+
+    let copy = dir.join("copy.scene");
+    scene.save(&copy).unwrap();
+    let text = std::fs::read_to_string(&copy).unwrap();
+    assert!(text.contains("model crate.obj 0 0 4\n"), "{text}");
+    assert!(text.contains("model statue.obj 3 0 4 0.5\n"), "{text}");
+    assert_eq!(SceneFile::from_path(&copy).unwrap(), scene);
+    assert_eq!(SceneFile::parse(&scene.to_string()).unwrap(), scene);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,71 @@
+mod common;
+
+use common::{HEIGHT, object, pixel, render, world};
+
+use engine::World;
+use engine::mesh::Color;
+use engine::mesh::quad::QuadMesh;
+use nalgebra::Point3;
+
+const GRAY: Color = Color {
+    r: 200,
+    g: 200,
+    b: 200,
+    a: 255,
+};
+
+/// A small quad in front of a finely split wall, lit from the camera's side and a little
+/// to the left of it in world space, so the shadow falls to the wall's +x.
+fn occluded_wall() -> World {
+    let mut world = world(vec![
+        object(QuadMesh::grid(10.0, 10.0, 20, GRAY), 0.0, 0.0, 2.0),
+        object(QuadMesh::new(1.0, 1.0, GRAY), 0.0, 0.0, 0.0),
+    ]);
+    world.light.position = Point3::new(-4.0, 0.0, -10.0);
+    world.light.target = Point3::origin();
+    world.light.shadow_resolution = 256;
+    world
+}
+
+// Seen from the camera, world +x is on the left. These hit the wall at
+// x = +1 and x = -1, either side of the occluder.
+const SHADOW_SIDE: u32 = 24;
+const LIT_SIDE: u32 = 40;
+
+fn brightness(frame: &[u8], x: u32) -> u32 {
+    pixel(frame, x, HEIGHT / 2)[..3]
+        .iter()
+        .map(|&c| c as u32)
+        .sum()
+}
+
+#[test]
+fn occluder_casts_a_shadow() {
+    let mut world = occluded_wall();
+    let frame = render(&mut world);
+    assert!(brightness(&frame, SHADOW_SIDE) < brightness(&frame, LIT_SIDE));
+}
+
+#[test]
+fn zero_resolution_turns_shadows_off() {
+    let mut world = occluded_wall();
+    world.light.shadow_resolution = 0;
+    let frame = render(&mut world);
+    assert_eq!(
+        brightness(&frame, SHADOW_SIDE),
+        brightness(&frame, LIT_SIDE)
+    );
+}
+
+#[test]
+fn lone_surfaces_do_not_shadow_themselves() {
+    let mut lit = world(vec![object(
+        QuadMesh::grid(10.0, 10.0, 20, GRAY),
+        0.0,
+        0.0,
+        2.0,
+    )]);
+    let plain = render(&mut lit);
+    lit.light.shadow_resolution = 256;
+    assert!(render(&mut lit) == plain);
+}
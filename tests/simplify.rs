@@ -0,0 +1,168 @@
+mod common;
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::rc::Rc;
+
+use engine::lod::simplified_lods;
+use engine::mesh::capsule::CapsuleMesh;
+use engine::mesh::cube::CubeMesh;
+use engine::mesh::obj::{ObjMesh, save_obj};
+use engine::mesh::quad::QuadMesh;
+use engine::mesh::{Color, ImportOptions, Mesh, MeshMut, Triangle, simplify, subdivide};
+use nalgebra::Point3;
+
+const RED: Color = Color::rgb(220, 30, 30);
+const BLUE: Color = Color::rgb(30, 30, 220);
+
+fn temp_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("shapes-simplify-{}-{name}.obj", std::process::id()))
+}
+
+/// A unit sphere of 3968 triangles, red above y = 0 and blue below.
+fn sphere() -> CapsuleMesh {
+    let mut sphere = CapsuleMesh::new(1.0, 0.0, 16, 64, RED).unwrap();
+    let verts = sphere.verts().to_vec();
+    for tri in sphere.tris_mut() {
+        let y = verts[tri.v1].y + verts[tri.v2].y + verts[tri.v3].y;
+        if y < 0.0 {
+            tri.color = BLUE;
+        }
+    }
+    sphere
+}
+
+/// How many triangles use each undirected edge.
+fn edge_uses(mesh: &dyn Mesh) -> HashMap<(usize, usize), usize> {
+    let mut uses = HashMap::new();
+    for tri in mesh.tris() {
+        for (a, b) in [(tri.v1, tri.v2), (tri.v2, tri.v3), (tri.v3, tri.v1)] {
+            *uses.entry((a.min(b), a.max(b))).or_insert(0) += 1;
+        }
+    }
+    uses
+}
+
+fn centroid(mesh: &dyn Mesh, tri: &Triangle) -> Point3<f32> {
+    let v = mesh.verts();
+    Point3::from((v[tri.v1].coords + v[tri.v2].coords + v[tri.v3].coords) / 3.0)
+}
+
+#[test]
+fn closed_mesh_reaches_the_target_and_keeps_its_shape() {
+    let sphere = sphere();
+    assert_eq!(sphere.tris().len(), 3968);
+    let simplified = simplify(&sphere, 300);
+    assert!(simplified.tris().len() <= 300);
+    assert!(simplified.tris().len() > 250);
+    assert!(simplified.validate().is_ok());
+    assert_eq!(simplified.degenerate_triangles(), 0);
+    // Still closed, with every vertex in use.
+    assert!(edge_uses(&simplified).values().all(|&n| n == 2));
+    assert_eq!(
+        simplified.verts().len(),
+        simplified.tris().len() / 2 + 2,
+        "a closed sphere has V = F / 2 + 2"
+    );
+    for v in simplified.verts() {
+        let radius = v.coords.norm();
+        assert!((0.9..1.02).contains(&radius), "{radius}");
+    }
+}
+
+#[test]
+fn triangles_keep_their_dominant_parents_color() {
+    let sphere = sphere();
+    let simplified = simplify(&sphere, 200);
+    for tri in simplified.tris() {
+        assert!(tri.color == RED || tri.color == BLUE);
+        // Only triangles straddling the equator can have come from either
+        // side.
+        let y = centroid(&simplified, tri).y;
+        if y > 0.3 {
+            assert_eq!(tri.color, RED);
+        } else if y < -0.3 {
+            assert_eq!(tri.color, BLUE);
+        }
+    }
+    assert_eq!(simplified.sources().len(), simplified.tris().len());
+}
+
+#[test]
+fn open_borders_stay_in_place() {
+    // A flat 2x2 square cut into 8x8 cells.
+    let grid = subdivide(&QuadMesh::new(2.0, 2.0, RED), 3).unwrap();
+    let border: Vec<Point3<f32>> = grid
+        .verts()
+        .iter()
+        .copied()
+        .filter(|v| v.x.abs() == 1.0 || v.y.abs() == 1.0)
+        .collect();
+    assert_eq!(border.len(), 32);
+    let simplified = simplify(&grid, 2);
+    assert_eq!(simplified.degenerate_triangles(), 0);
+    assert_eq!(simplified.aabb(), grid.aabb());
+    for v in &border {
+        assert!(simplified.verts().contains(v), "border vertex {v} moved");
+    }
+    // Every inner vertex could go: a 32 sided polygon is 30 triangles.
+    assert_eq!(simplified.verts().len(), 32);
+    assert_eq!(simplified.tris().len(), 30);
+}
+
+#[test]
+fn nothing_is_collapsed_below_the_count_already_there() {
+    let mut cube = CubeMesh::new(RED);
+    let simplified = simplify(&cube, 100);
+    assert_eq!(simplified.tris().len(), 12);
+    assert_eq!(simplified.verts().len(), 8);
+    // Triangles using a vertex twice are dropped on the way.
+    cube.tris_mut()[0].v2 = cube.tris()[0].v1;
+    let simplified = simplify(&cube, 100);
+    assert_eq!(simplified.tris().len(), 11);
+    assert_eq!(simplified.degenerate_triangles(), 0);
+}
+
+#[test]
+fn a_tetrahedron_is_as_far_as_it_goes() {
+    let simplified = simplify(&CubeMesh::new(RED), 0);
+    assert_eq!(simplified.tris().len(), 4);
+    assert!(edge_uses(&simplified).values().all(|&n| n == 2));
+}
+
+#[test]
+fn saved_meshes_load_back_and_simplify_on_load() {
+    let path = temp_path("sphere");
+    save_obj(&sphere(), &path).unwrap();
+    let loaded = ObjMesh::from_path(&path, ImportOptions::default()).unwrap();
+    assert_eq!(loaded.tris().len(), 3968);
+    assert!(loaded.tris().iter().any(|tri| tri.color == RED));
+    assert!(loaded.tris().iter().any(|tri| tri.color == BLUE));
+
+    let light = ObjMesh::from_path(&path, ImportOptions::default().simplified(500)).unwrap();
+    assert!(light.tris().len() <= 500);
+    assert_eq!(light.normals().len(), light.verts().len());
+    // Each surviving triangle still has the material of its color.
+    for (index, tri) in light.tris().iter().enumerate() {
+        assert_eq!(light.material(index).map(|m| m.color), Some(tri.color));
+    }
+    std::fs::remove_file(&path).unwrap();
+    std::fs::remove_file(path.with_extension("mtl")).unwrap();
+}
+
+#[test]
+fn lod_chains_halve_each_level() {
+    let mesh: Rc<dyn Mesh> = Rc::new(sphere());
+    let lods = simplified_lods(Rc::clone(&mesh), &[5.0, 10.0, 20.0, f32::INFINITY]);
+    assert_eq!(lods.len(), 4);
+    assert!(Rc::ptr_eq(&lods[0].1, &mesh));
+    let counts: Vec<usize> = lods.iter().map(|(_, lod)| lod.tris().len()).collect();
+    for pair in counts.windows(2) {
+        assert!(pair[1] <= pair[0] / 2, "{counts:?}");
+    }
+    assert_eq!(
+        lods.iter().map(|(d, _)| *d).collect::<Vec<_>>(),
+        [5.0, 10.0, 20.0, f32::INFINITY]
+    );
+    assert!(lods.iter().all(|(_, lod)| lod.validate().is_ok()));
+}
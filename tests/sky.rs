@@ -0,0 +1,85 @@
+mod common;
+
+use common::{HEIGHT, WIDTH, pixel, render, world};
+use engine::RenderMode;
+use engine::fog::{Fog, FogMode};
+use engine::mesh::Color;
+use engine::sky::Sky;
+use nalgebra::Point3;
+
+fn rgba(c: Color) -> [u8; 4] {
+    [c.r, c.g, c.b, c.a]
+}
+
+/// The default sky without its sun.
+fn plain_sky() -> Sky {
+    Sky {
+        sun: None,
+        ..Sky::default()
+    }
+}
+
+#[test]
+fn level_camera_sees_zenith_above_and_horizon_below() {
+    let sky = plain_sky();
+    let mut world = world(vec![]).with_sky(sky);
+    let frame = render(&mut world);
+    let top = pixel(&frame, WIDTH / 2, 0);
+    // Bluer towards the zenith.
+    assert!(top[0] < sky.horizon.r && top[2] < sky.horizon.b, "{top:?}");
+    for y in HEIGHT / 2..HEIGHT {
+        assert_eq!(pixel(&frame, WIDTH / 2, y), rgba(sky.horizon), "row {y}");
+    }
+    // Higher rows are never further from the zenith than lower ones.
+    for y in 1..HEIGHT / 2 {
+        assert!(pixel(&frame, WIDTH / 2, y - 1)[0] <= pixel(&frame, WIDTH / 2, y)[0]);
+    }
+}
+
+#[test]
+fn looking_up_turns_the_view_to_the_zenith() {
+    let sky = plain_sky();
+    let mut level = world(vec![]).with_sky(sky);
+    let mut up = world(vec![]).with_sky(sky);
+    up.camera.look_at(Point3::new(0.0, 100.0, -5.0));
+    let center = (WIDTH / 2, HEIGHT / 2);
+    let looking_level = pixel(&render(&mut level), center.0, center.1);
+    let looking_up = pixel(&render(&mut up), center.0, center.1);
+    assert_eq!(looking_level, rgba(sky.horizon));
+    let zenith = rgba(sky.zenith);
+    for channel in 0..3 {
+        assert!(
+            (looking_up[channel] as i32 - zenith[channel] as i32).abs() <= 2,
+            "{looking_up:?}"
+        );
+    }
+}
+
+#[test]
+fn sun_disc_sits_towards_the_light() {
+    let sky = Sky::default();
+    let sun = sky.sun.unwrap();
+    let mut world = world(vec![]).with_sky(sky);
+    // Light shining from straight ahead of the camera, back towards it.
+    world.light.position = Point3::new(0.0, 0.0, 10.0);
+    world.light.target = Point3::origin();
+    let frame = render(&mut world);
+    assert_eq!(pixel(&frame, WIDTH / 2, HEIGHT / 2), rgba(sun.color));
+    assert_eq!(pixel(&frame, 0, HEIGHT / 2), rgba(sky.horizon));
+}
+
+#[test]
+fn fog_and_depth_view_cover_the_sky() {
+    let fog = Color::rgb(90, 90, 90);
+    let mut world = world(vec![]).with_sky(Sky::default());
+    world.fog = Some(Fog {
+        color: fog,
+        start: 1.0,
+        end: 10.0,
+        mode: FogMode::Linear,
+    });
+    assert!(render(&mut world).chunks(4).all(|p| p == rgba(fog)));
+    world.fog = None;
+    world.render_mode = RenderMode::Depth;
+    assert!(render(&mut world).chunks(4).all(|p| p == [0, 0, 0, 255]));
+}
@@ -0,0 +1,77 @@
+mod common;
+
+use common::{HEIGHT, WIDTH, object, render, world};
+use engine::mesh::Color;
+use engine::mesh::cube::CubeMesh;
+use engine::mesh::quad::QuadMesh;
+use engine::{Object, World};
+
+/// A 4 by 4 wall split into 80 by 80 cells, far enough away that every
+/// one of them is a fraction of a pixel.
+fn dense_wall() -> World {
+    let wall = QuadMesh::grid(4.0, 4.0, 80, Color::rgb(30, 140, 90));
+    world(vec![object(wall, 0.0, 0.0, 20.0)])
+}
+
+fn differing_pixels(a: &[u8], b: &[u8]) -> usize {
+    a.chunks_exact(4)
+        .zip(b.chunks_exact(4))
+        .filter(|(a, b)| a != b)
+        .count()
+}
+
+fn covered_pixels(frame: &[u8]) -> usize {
+    frame.chunks_exact(4).filter(|p| p[..3] != [255; 3]).count()
+}
+
+#[test]
+fn off_by_default() {
+    let mut world = dense_wall();
+    render(&mut world);
+    assert_eq!(world.stats().triangles_small, 0);
+    assert_eq!(world.stats().triangles_rasterized, 80 * 80 * 2);
+}
+
+#[test]
+fn tiny_triangles_are_skipped() {
+    let mut world = dense_wall();
+    world.small_triangle_cull = Some(0.5);
+    let frame = render(&mut world);
+    let stats = world.stats();
+    assert_eq!(stats.triangles_small, 80 * 80 * 2);
+    assert_eq!(stats.triangles_rasterized, 0);
+    assert_eq!(covered_pixels(&frame), 0);
+}
+
+#[test]
+fn gathered_points_stand_in_for_them() {
+    let mut world = dense_wall();
+    let full = render(&mut world);
+    let rasterized = world.stats().pixels_written;
+    world.small_triangle_cull = Some(0.5);
+    world.small_triangle_points = true;
+    let gathered = render(&mut world);
+    let stats = world.stats();
+    assert_eq!(stats.triangles_small, 80 * 80 * 2);
+    assert_eq!(stats.triangles_rasterized, 0);
+    assert!(stats.pixels_written <= covered_pixels(&full));
+    assert!(stats.pixels_written < rasterized);
+    // The wall's pixels are all still covered, in nearly the same colors.
+    assert_eq!(covered_pixels(&gathered), covered_pixels(&full));
+    let total = (WIDTH * HEIGHT) as usize;
+    assert!(differing_pixels(&gathered, &full) < total / 20);
+}
+
+#[test]
+fn big_triangles_are_untouched() {
+    let cube = || object(CubeMesh::new(Color::rgb(200, 80, 40)), 0.0, 0.0, 0.0);
+    let mut world = world(vec![Object {
+        scale: 0.5,
+        ..cube()
+    }]);
+    let plain = render(&mut world);
+    world.small_triangle_cull = Some(0.5);
+    world.small_triangle_points = true;
+    assert_eq!(render(&mut world), plain);
+    assert_eq!(world.stats().triangles_small, 0);
+}
@@ -0,0 +1,85 @@
+use engine::mesh::cube::CubeMesh;
+use engine::mesh::obj::ObjMesh;
+use engine::mesh::terrain::TerrainMesh;
+use engine::mesh::{
+    Color, ImportOptions, Mesh, Triangle, compute_smooth_normals, compute_split_normals,
+};
+use nalgebra::{Point3, Vector3};
+
+const GRAY: Color = Color {
+    r: 128,
+    g: 128,
+    b: 128,
+    a: 255,
+};
+
+#[test]
+fn cube_corners_point_along_the_diagonal() {
+    let cube = CubeMesh::new(GRAY);
+    let normals = compute_smooth_normals(cube.verts(), cube.tris());
+    for (v, n) in cube.verts().iter().zip(&normals) {
+        let diagonal = v.coords.normalize();
+        assert!((n - diagonal).norm() < 1e-5, "{v} -> {n}");
+    }
+}
+
+#[test]
+fn flat_plane_normals_are_exactly_up() {
+    let plane =
+        TerrainMesh::from_heightmap(6, 6, &[0.0; 36], Vector3::new(1.0, 1.0, 1.0), |_| GRAY)
+            .unwrap();
+    let normals = compute_smooth_normals(plane.verts(), plane.tris());
+    assert!(normals.iter().all(|n| *n == Vector3::y()));
+}
+
+#[test]
+fn zero_area_triangles_are_skipped() {
+    let verts = [
+        Point3::new(0.0, 0.0, 0.0),
+        Point3::new(0.0, 1.0, 0.0),
+        Point3::new(1.0, 0.0, 0.0),
+        Point3::new(2.0, 0.0, 0.0),
+    ];
+    let tri = |v1, v2, v3| Triangle {
+        v1,
+        v2,
+        v3,
+        color: GRAY,
+    };
+    // A proper face, plus a sliver along the x axis touching vertex 3 only.
+    let normals = compute_smooth_normals(&verts, &[tri(0, 1, 2), tri(0, 2, 3)]);
+    assert_eq!(normals[0], Vector3::z());
+    assert_eq!(normals[3], Vector3::zeros());
+    assert!(normals.iter().all(|n| n.iter().all(|c| c.is_finite())));
+}
+
+#[test]
+fn hard_edges_split_vertices() {
+    let cube = CubeMesh::new(GRAY);
+    let (verts, tris, normals) = compute_split_normals(cube.verts(), cube.tris(), 30.0);
+    // Three copies of each corner, one per face.
+    assert_eq!(verts.len(), 24);
+    assert_eq!(tris.len(), 12);
+    let axes = [Vector3::x(), Vector3::y(), Vector3::z()];
+    for n in &normals {
+        assert!(
+            axes.iter().any(|axis| (n.abs() - axis).norm() < 1e-5),
+            "{n}"
+        );
+    }
+
+    // Wide enough to smooth the whole cube.
+    let (verts, _, _) = compute_split_normals(cube.verts(), cube.tris(), 100.0);
+    assert_eq!(verts.len(), 8);
+}
+
+#[test]
+fn obj_normals_come_from_the_file_or_the_faces() {
+    let obj = b"v 0 0 0\nv 0 1 0\nv 1 0 0\nvn 0 2 0\nf 1//1 3//1 2//1\n";
+    let mesh = ObjMesh::from_bytes(obj, ImportOptions::default()).unwrap();
+    assert_eq!(mesh.normals(), &[Vector3::y(); 3]);
+
+    let obj = b"v 0 0 0\nv 0 1 0\nv 1 0 0\nf 1 3 2\n";
+    let mesh = ObjMesh::from_bytes(obj, ImportOptions::default()).unwrap();
+    assert_eq!(mesh.normals(), &[Vector3::z(); 3]);
+}
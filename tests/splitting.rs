@@ -0,0 +1,135 @@
+mod common;
+
+use common::{HEIGHT, object, render, world};
+
+use engine::mesh::quad::QuadMesh;
+use engine::mesh::{Color, Mesh, Triangle};
+use engine::{Camera, CullMode, Object, World};
+use nalgebra::{Point3, UnitQuaternion, Vector3};
+
+const RED: Color = Color::rgb(255, 0, 0);
+const BLUE: Color = Color::rgb(0, 0, 255);
+
+/// Two squares standing upright through each other in an X, seen from
+/// above, turned `angle` radians from facing the camera either way.
+fn crossed(angle: f32) -> [Object; 2] {
+    [(RED, angle), (BLUE, -angle)].map(|(color, angle)| {
+        let mut square = object(QuadMesh::new(2.0, 2.0, color), 0.0, 0.0, 0.0);
+        square.rotation = UnitQuaternion::from_axis_angle(&Vector3::y_axis(), angle);
+        square
+    })
+}
+
+fn scene(models: Vec<Object>) -> World {
+    let mut world = world(models);
+    world.cull_mode = CullMode::None;
+    world
+}
+
+/// Pixels that differ between drawing with `splitting_sort` and drawing
+/// with the depth buffer, which gets crossing surfaces right.
+fn wrong_pixels(world: &mut World) -> usize {
+    world.splitting_sort = true;
+    world.depth_buffer = false;
+    let split = render(world);
+    world.splitting_sort = false;
+    world.depth_buffer = true;
+    let exact = render(world);
+    world.depth_buffer = false;
+    split
+        .chunks_exact(4)
+        .zip(exact.chunks_exact(4))
+        .filter(|(a, b)| a != b)
+        .count()
+}
+
+/// The camera at distance 5, `yaw` radians round the vertical axis and a
+/// little above.
+fn orbit(world: &mut World, yaw: f32) {
+    let eye = Point3::new(5.0 * yaw.sin(), 1.5, -5.0 * yaw.cos());
+    world.camera = Camera::looking_at(eye, Point3::origin());
+}
+
+/// Only the line where the surfaces meet may come out differently.
+const SEAM: usize = HEIGHT as usize;
+
+#[test]
+fn crossed_squares_are_right_from_every_side() {
+    let mut world = scene(crossed(0.6).into());
+    let mut worst_unsplit = 0;
+    for step in 0..12 {
+        orbit(&mut world, step as f32 * 0.5 + 0.1);
+        assert!(wrong_pixels(&mut world) <= SEAM, "step {step}");
+
+        world.depth_buffer = true;
+        let exact = render(&mut world);
+        world.depth_buffer = false;
+        let unsplit = render(&mut world);
+        let wrong = unsplit
+            .chunks_exact(4)
+            .zip(exact.chunks_exact(4))
+            .filter(|(a, b)| a != b)
+            .count();
+        worst_unsplit = worst_unsplit.max(wrong);
+    }
+    // Without it, one square covers the other whole.
+    assert!(worst_unsplit > 2 * SEAM);
+}
+
+/// Both squares of [`crossed`] in one mesh.
+struct Crossed {
+    verts: Vec<Point3<f32>>,
+    tris: Vec<Triangle>,
+}
+
+impl Crossed {
+    fn new(angle: f32) -> Self {
+        let (mut verts, mut tris) = (Vec::new(), Vec::new());
+        for square in crossed(angle) {
+            let first = verts.len();
+            let turn = square.rotation;
+            verts.extend(square.mesh.verts().iter().map(|v| turn * v));
+            tris.extend(square.mesh.tris().iter().map(|tri| Triangle {
+                v1: first + tri.v1,
+                v2: first + tri.v2,
+                v3: first + tri.v3,
+                ..*tri
+            }));
+        }
+        Self { verts, tris }
+    }
+}
+
+impl Mesh for Crossed {
+    fn verts(&self) -> &[Point3<f32>] {
+        &self.verts
+    }
+
+    fn tris(&self) -> &Vec<Triangle> {
+        &self.tris
+    }
+}
+
+#[test]
+fn a_mesh_cutting_through_itself_is_split_too() {
+    let mut model = object(Crossed::new(0.6), 0.5, 0.0, 0.0);
+    model.rotation = UnitQuaternion::from_axis_angle(&Vector3::y_axis(), 0.3);
+    let mut world = scene(vec![model]);
+    for step in 0..6 {
+        orbit(&mut world, step as f32 + 0.2);
+        assert!(wrong_pixels(&mut world) <= SEAM, "step {step}");
+    }
+}
+
+#[test]
+fn moving_an_object_cuts_it_again() {
+    let mut world = scene(crossed(0.6).into());
+    orbit(&mut world, 0.1);
+    assert!(wrong_pixels(&mut world) <= SEAM);
+    world.models[1].offset_x = 0.5;
+    world.models[1].offset_z = 0.3;
+    assert!(wrong_pixels(&mut world) <= SEAM);
+    // Pulled apart they don't cross at all.
+    world.models[1].offset_z = 3.0;
+    assert!(wrong_pixels(&mut world) <= SEAM);
+}
@@ -0,0 +1,73 @@
+mod common;
+
+use common::{HEIGHT, WIDTH, object, pixel, render, world};
+
+use engine::LightKind;
+use engine::mesh::Color;
+use engine::mesh::quad::QuadMesh;
+use nalgebra::Point3;
+
+const GRAY: Color = Color {
+    r: 200,
+    g: 200,
+    b: 200,
+    a: 255,
+};
+
+/// A wall filling the view, lit head on by a spotlight next to the camera.
+fn spot_world(inner_angle: f32, outer_angle: f32) -> engine::World {
+    let mut world = world(vec![object(
+        QuadMesh::grid(20.0, 20.0, 40, GRAY),
+        0.0,
+        0.0,
+        0.0,
+    )]);
+    world.light.position = Point3::new(0.0, 0.0, -5.0);
+    world.light.target = Point3::origin();
+    world.light.kind = LightKind::Spot {
+        inner_angle,
+        outer_angle,
+    };
+    world
+}
+
+#[test]
+fn spotlight_lights_a_bounded_circle() {
+    let mut world = spot_world(0.1, 0.15);
+    let frame = render(&mut world);
+    let center = pixel(&frame, WIDTH / 2, HEIGHT / 2);
+    let corner = pixel(&frame, 2, 2);
+    let edge = pixel(&frame, WIDTH - 3, HEIGHT / 2);
+    assert!(center[0] > corner[0] + 60, "{center:?} vs {corner:?}");
+    // Outside the cone only the ambient light is left.
+    assert_eq!(corner, edge);
+
+    world.light.kind = LightKind::Directional;
+    let frame = render(&mut world);
+    assert_eq!(
+        pixel(&frame, 2, 2)[0],
+        pixel(&frame, WIDTH / 2, HEIGHT / 2)[0]
+    );
+}
+
+#[test]
+fn spotlight_fades_between_the_cones() {
+    let mut world = spot_world(0.05, 0.3);
+    let frame = render(&mut world);
+    let row: Vec<u8> = (WIDTH / 2..WIDTH)
+        .map(|x| pixel(&frame, x, HEIGHT / 2)[0])
+        .collect();
+    // Beyond the inner cone each cell of the wall is darker than the last.
+    assert!(row[6..].windows(2).all(|w| w[0] >= w[1]), "{row:?}");
+    let distinct = row.iter().collect::<std::collections::HashSet<_>>().len();
+    assert!(distinct > 3, "{row:?}");
+}
+
+#[test]
+fn inverted_cone_angles_are_clamped() {
+    let mut world = spot_world(0.3, 0.1);
+    let frame = render(&mut world);
+    assert!(frame.chunks(4).all(|p| p[3] == 255));
+    let sharp = render(&mut spot_world(0.1, 0.1));
+    assert_eq!(frame, sharp);
+}
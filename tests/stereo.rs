@@ -0,0 +1,102 @@
+mod common;
+
+use common::{HEIGHT, WIDTH, object, render, world};
+use engine::World;
+use engine::mesh::Color;
+use engine::mesh::cube::CubeMesh;
+use engine::stereo::{Stereo, merge_anaglyph};
+use nalgebra::{Matrix4, Point3};
+
+fn scene() -> World {
+    world(vec![
+        object(CubeMesh::new(Color::rgb(200, 120, 40)), -0.5, 0.0, 1.0),
+        object(CubeMesh::new(Color::rgb(40, 180, 220)), 1.0, 0.5, 3.0),
+    ])
+}
+
+fn draw(world: &mut World, view: Matrix4<f32>) -> Vec<u8> {
+    let mut frame = vec![0; (WIDTH * HEIGHT * 4) as usize];
+    world.draw(view, &mut frame, WIDTH, HEIGHT);
+    frame
+}
+
+#[test]
+fn eyes_sit_either_side_of_the_camera_and_look_at_the_focus() {
+    let stereo = Stereo {
+        eye_separation: 0.4,
+        convergence: 3.0,
+    };
+    let [left, right] = stereo.eye_views(&Matrix4::identity());
+    let eye = |view: Matrix4<f32>| {
+        view.try_inverse()
+            .unwrap()
+            .transform_point(&Point3::origin())
+    };
+    assert!((eye(left) - Point3::new(-0.2, 0.0, 0.0)).norm() < 1e-6);
+    assert!((eye(right) - Point3::new(0.2, 0.0, 0.0)).norm() < 1e-6);
+    // The focus is straight ahead of both eyes.
+    for view in [left, right] {
+        let focus = view.transform_point(&Point3::new(0.0, 0.0, -3.0));
+        assert!(focus.x.abs() < 1e-6 && focus.y.abs() < 1e-6, "{focus}");
+    }
+
+    let parallel = Stereo {
+        convergence: 0.0,
+        ..stereo
+    };
+    let [left, _] = parallel.eye_views(&Matrix4::identity());
+    let ahead = left.transform_point(&Point3::new(-0.2, 0.0, -10.0));
+    assert!(ahead.x.abs() < 1e-6, "{ahead}");
+}
+
+#[test]
+fn red_comes_from_the_left_eye_and_cyan_from_the_right() {
+    let mut world = scene();
+    let stereo = Stereo::default();
+    let view = world.camera.generate_view_mat();
+    let [left_view, right_view] = stereo.eye_views(&view);
+    let left = draw(&mut world, left_view);
+    let right = draw(&mut world, right_view);
+    assert_ne!(left, right);
+
+    world.stereo = Some(stereo);
+    let frame = render(&mut world);
+    for ((pixel, l), r) in frame
+        .chunks_exact(4)
+        .zip(left.chunks_exact(4))
+        .zip(right.chunks_exact(4))
+    {
+        assert_eq!(pixel, [l[0], r[1], r[2], r[3]]);
+    }
+}
+
+#[test]
+fn eyes_together_draw_the_plain_frame() {
+    let mut world = scene();
+    let plain = render(&mut world);
+    world.stereo = Some(Stereo {
+        eye_separation: 0.0,
+        convergence: 0.0,
+    });
+    assert_eq!(render(&mut world), plain);
+}
+
+#[test]
+fn stats_count_both_eyes() {
+    let mut world = scene();
+    render(&mut world);
+    let mono = world.stats();
+    world.stereo = Some(Stereo::default());
+    render(&mut world);
+    let stereo = world.stats();
+    assert_eq!(stereo.triangles_submitted, 2 * mono.triangles_submitted);
+    assert_eq!(stereo.objects_drawn, 2 * mono.objects_drawn);
+}
+
+#[test]
+fn merging_keeps_the_right_eye_but_for_red() {
+    let left = [10, 20, 30, 40, 50, 60, 70, 80];
+    let mut frame = [1, 2, 3, 4, 5, 6, 7, 8];
+    merge_anaglyph(&left, &mut frame);
+    assert_eq!(frame, [10, 2, 3, 4, 50, 6, 7, 8]);
+}
@@ -0,0 +1,46 @@
+use engine::mesh::{Color, MeshPrimitives};
+
+const BLUE: Color = Color {
+    r: 0,
+    g: 0,
+    b: 255,
+    a: 255,
+};
+
+fn corners(primitives: MeshPrimitives) -> Vec<[usize; 3]> {
+    primitives
+        .triangles()
+        .map(|tri| [tri.v1, tri.v2, tri.v3])
+        .collect()
+}
+
+#[test]
+fn strips_alternate_winding() {
+    let strip = MeshPrimitives::TriangleStrip {
+        indices: &[0, 1, 2, 3, 4],
+        color: BLUE,
+    };
+    // Every triangle keeps the first one's winding, so none get culled.
+    assert_eq!(corners(strip), [[0, 1, 2], [2, 1, 3], [2, 3, 4]]);
+    assert!(strip.triangles().all(|tri| tri.color == BLUE));
+}
+
+#[test]
+fn repeated_indices_join_strips_without_drawing() {
+    let strip = MeshPrimitives::TriangleStrip {
+        indices: &[0, 1, 2, 3, 3, 4, 4, 5, 6, 7],
+        color: BLUE,
+    };
+    assert_eq!(corners(strip), [[0, 1, 2], [2, 1, 3], [4, 5, 6], [6, 5, 7]]);
+}
+
+#[test]
+fn short_strips_have_no_triangles() {
+    for indices in [&[][..], &[0], &[0, 1]] {
+        let strip = MeshPrimitives::TriangleStrip {
+            indices,
+            color: BLUE,
+        };
+        assert_eq!(strip.triangles().count(), 0);
+    }
+}
@@ -0,0 +1,114 @@
+mod common;
+
+use std::collections::HashMap;
+
+use common::{object, render, world};
+
+use engine::mesh::cube::CubeMesh;
+use engine::mesh::quad::QuadMesh;
+use engine::mesh::subdivide::{SubdivideError, Subdivision};
+use engine::mesh::{Color, Mesh, subdivide};
+
+const ORANGE: Color = Color {
+    r: 240,
+    g: 140,
+    b: 20,
+    a: 255,
+};
+
+/// How many triangles use each undirected edge.
+fn edge_uses(mesh: &dyn Mesh) -> HashMap<(usize, usize), usize> {
+    let mut uses = HashMap::new();
+    for tri in mesh.tris() {
+        for (a, b) in [(tri.v1, tri.v2), (tri.v2, tri.v3), (tri.v3, tri.v1)] {
+            *uses.entry((a.min(b), a.max(b))).or_insert(0) += 1;
+        }
+    }
+    uses
+}
+
+#[test]
+fn each_level_splits_triangles_in_four_and_stays_closed() {
+    let cube = CubeMesh::new(ORANGE);
+    let once = subdivide(&cube, 1).unwrap();
+    // 8 corners plus one new vertex per edge.
+    assert_eq!(once.verts().len(), 8 + 18);
+    assert_eq!(once.tris().len(), 48);
+    assert!(edge_uses(&once).values().all(|&n| n == 2));
+
+    let twice = subdivide(&cube, 2).unwrap();
+    assert_eq!(twice.tris().len(), 192);
+    assert!(edge_uses(&twice).values().all(|&n| n == 2));
+    assert!(twice.tris().iter().all(|t| t.color == ORANGE));
+}
+
+#[test]
+fn midpoint_subdivision_keeps_the_shape() {
+    let cube = CubeMesh::new(ORANGE);
+    let mut plain = world(vec![object(CubeMesh::new(ORANGE), 0.0, 0.0, 0.0)]);
+    let mut split = world(vec![object(subdivide(&cube, 2).unwrap(), 0.0, 0.0, 0.0)]);
+    render(&mut plain);
+    let frame = render(&mut split);
+    assert_eq!(split.stats().triangles_submitted, 192);
+    let covered = |frame: &[u8]| {
+        frame
+            .chunks(4)
+            .filter(|p| p[0] != 255 || p[2] != 255)
+            .count()
+    };
+    assert_eq!(covered(&frame), covered(&render(&mut plain)));
+}
+
+#[test]
+fn loop_smoothing_rounds_the_cube() {
+    let cube = CubeMesh::new(ORANGE);
+    let spread = |mesh: &dyn Mesh| {
+        let radii: Vec<f32> = mesh.verts().iter().map(|v| v.coords.norm()).collect();
+        let max = radii.iter().cloned().fold(f32::MIN, f32::max);
+        let min = radii.iter().cloned().fold(f32::MAX, f32::min);
+        max / min
+    };
+    let smooth = Subdivision::default()
+        .levels(3)
+        .smooth(true)
+        .apply(&cube)
+        .unwrap();
+    assert!(spread(&smooth) < 1.3, "{}", spread(&smooth));
+    assert!(spread(&subdivide(&cube, 3).unwrap()) > 1.7);
+}
+
+#[test]
+fn open_borders_stay_in_place_when_smoothing() {
+    let quad = QuadMesh::new(2.0, 2.0, ORANGE);
+    let smooth = Subdivision::default()
+        .levels(2)
+        .smooth(true)
+        .apply(&quad)
+        .unwrap();
+    assert!(smooth.verts().iter().all(|v| v.z == 0.0));
+    assert!(
+        smooth
+            .verts()
+            .iter()
+            .all(|v| v.x.abs() <= 1.0 && v.y.abs() <= 1.0)
+    );
+}
+
+#[test]
+fn levels_over_budget_are_rejected() {
+    let cube = CubeMesh::new(ORANGE);
+    let err = Subdivision::default()
+        .levels(3)
+        .max_triangles(500)
+        .apply(&cube)
+        .unwrap_err();
+    assert_eq!(
+        err,
+        SubdivideError::OverBudget {
+            levels: 3,
+            triangles: 768,
+            budget: 500
+        }
+    );
+    assert!(subdivide(&cube, 60).is_err());
+}
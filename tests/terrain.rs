@@ -0,0 +1,119 @@
+mod common;
+
+use common::{HEIGHT, WHITE, WIDTH, object, pixel, render, world};
+
+use engine::mesh::terrain::{TerrainError, TerrainMesh, noise_heightmap};
+use engine::mesh::{Color, Mesh, MeshPrimitives};
+use nalgebra::{Point3, Vector3};
+
+const GREEN: Color = Color {
+    r: 0,
+    g: 200,
+    b: 0,
+    a: 255,
+};
+
+fn flat(width: usize, depth: usize) -> Result<TerrainMesh, TerrainError> {
+    TerrainMesh::from_heightmap(
+        width,
+        depth,
+        &vec![0.0; width * depth],
+        Vector3::new(1.0, 1.0, 1.0),
+        |_| GREEN,
+    )
+}
+
+#[test]
+fn degenerate_sizes_are_errors() {
+    assert_eq!(
+        flat(1, 5).unwrap_err(),
+        TerrainError::TooSmall { width: 1, depth: 5 }
+    );
+    assert!(flat(5, 0).is_err());
+    let err = TerrainMesh::from_heightmap(3, 3, &[0.0; 8], Vector3::repeat(1.0), |_| GREEN);
+    assert_eq!(err.unwrap_err().to_string(), "expected 9 heights, found 8");
+}
+
+#[test]
+fn grid_is_split_into_two_triangles_per_cell() {
+    let mesh = flat(4, 3).unwrap();
+    assert_eq!(mesh.verts().len(), 12);
+    assert_eq!(mesh.tris().len(), 2 * 3 * 2);
+}
+
+#[test]
+fn terrain_faces_up() {
+    let mut world = world(vec![object(flat(8, 8).unwrap(), 0.0, -1.0, 0.0)]);
+    world.camera.position = Point3::new(0.0, 5.0, -0.1);
+    world.camera.look_at(Point3::origin());
+    assert_ne!(pixel(&render(&mut world), WIDTH / 2, HEIGHT / 2), WHITE);
+
+    world.camera.position = Point3::new(0.0, -5.0, -0.1);
+    world.camera.look_at(Point3::origin());
+    assert_eq!(pixel(&render(&mut world), WIDTH / 2, HEIGHT / 2), WHITE);
+}
+
+#[test]
+fn color_follows_altitude() {
+    let heights = [0.0, 0.0, 1.0, 1.0];
+    let mesh = TerrainMesh::from_heightmap(2, 2, &heights, Vector3::repeat(1.0), |h| Color {
+        r: (h * 255.0) as u8,
+        g: 0,
+        b: 0,
+        a: 255,
+    })
+    .unwrap();
+    assert_eq!(mesh.tris()[0].color.r, 85);
+    assert_eq!(mesh.tris()[1].color.r, 170);
+}
+
+#[test]
+fn noise_is_deterministic_and_in_range() {
+    let a = noise_heightmap(32, 16, 0.1, 4, 3);
+    assert_eq!(a.len(), 32 * 16);
+    assert!(a.iter().all(|h| (0.0..=1.0).contains(h)));
+    assert_eq!(a, noise_heightmap(32, 16, 0.1, 4, 3));
+    assert_ne!(a, noise_heightmap(32, 16, 0.1, 4, 4));
+}
+
+#[test]
+fn strip_terrain_matches_the_list_and_is_smaller() {
+    let heights = noise_heightmap(256, 256, 0.05, 3, 1);
+    let scale = Vector3::repeat(1.0);
+    let list = TerrainMesh::from_heightmap(256, 256, &heights, scale, |_| GREEN).unwrap();
+    let strip = TerrainMesh::strip_from_heightmap(256, 256, &heights, scale, GREEN).unwrap();
+    assert_eq!(strip.verts().len(), list.verts().len());
+    assert_eq!(strip.validate(), Ok(()));
+    assert_eq!(
+        strip.primitives().triangles().count(),
+        list.primitives().triangles().count()
+    );
+
+    let MeshPrimitives::TriangleStrip { indices, .. } = strip.primitives() else {
+        panic!("expected a strip");
+    };
+    let strip_bytes = size_of_val(indices);
+    let list_bytes = size_of_val(list.tris().as_slice());
+    assert!(
+        strip_bytes * 3 < list_bytes,
+        "strip {strip_bytes} bytes, list {list_bytes}"
+    );
+}
+
+#[test]
+fn strip_terrain_faces_up() {
+    let heights = vec![0.0; 36];
+    let mesh =
+        TerrainMesh::strip_from_heightmap(6, 6, &heights, Vector3::repeat(1.0), GREEN).unwrap();
+    let verts = mesh.verts();
+    for tri in mesh.primitives().triangles() {
+        // Clockwise from above, so the winding normal points down.
+        let normal = (verts[tri.v2] - verts[tri.v1]).cross(&(verts[tri.v3] - verts[tri.v1]));
+        assert!(normal.y < 0.0, "{tri:?} faces down");
+    }
+
+    let mut world = world(vec![object(mesh, 0.0, -1.0, 0.0)]);
+    world.camera.position = Point3::new(0.0, 5.0, -0.1);
+    world.camera.look_at(Point3::origin());
+    assert_ne!(pixel(&render(&mut world), WIDTH / 2, HEIGHT / 2), WHITE);
+}
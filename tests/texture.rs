@@ -0,0 +1,249 @@
+mod common;
+
+use std::f32::consts::FRAC_PI_3;
+
+use common::{HEIGHT, WIDTH, object, pixel, render, world};
+use engine::mesh::Color;
+use engine::mesh::quad::QuadMesh;
+use engine::texture::{FilterMode, GRADIENT_SIZE, GRID_CELL, Texture, WrapMode};
+use engine::{Material, Object, World};
+use nalgebra::{UnitQuaternion, Vector3};
+
+const RED: Color = Color::rgb(220, 30, 30);
+const BLUE: Color = Color::rgb(30, 30, 220);
+
+#[test]
+fn checkers_alternate_every_texel() {
+    let checker = Texture::checker(8, RED, BLUE);
+    assert_eq!((checker.width(), checker.height()), (8, 8));
+    assert_eq!(checker.rgba().len(), 8 * 8 * 4);
+    // Texel centres.
+    assert_eq!(checker.sample(0.5 / 8.0, 0.5 / 8.0), RED);
+    assert_eq!(checker.sample(1.5 / 8.0, 0.5 / 8.0), BLUE);
+    assert_eq!(checker.sample(1.5 / 8.0, 1.5 / 8.0), RED);
+    assert_eq!(checker.sample(7.5 / 8.0, 0.5 / 8.0), BLUE);
+    // Edges belong to the texel they start.
+    assert_eq!(checker.sample(0.125, 0.0), BLUE);
+}
+
+#[test]
+fn samples_repeat_outside_the_unit_square() {
+    let checker = Texture::checker(8, RED, BLUE);
+    for (u, v) in [(0.3, 0.7), (0.05, 0.95), (0.6, 0.1)] {
+        let inside = checker.sample(u, v);
+        assert_eq!(checker.sample(u + 1.0, v), inside);
+        assert_eq!(checker.sample(u, v - 1.0), inside);
+        assert_eq!(checker.sample(u - 3.0, v + 2.0), inside);
+    }
+    assert_eq!(checker.texel(-1, 0), checker.texel(7, 0));
+}
+
+#[test]
+fn gradients_hold_their_uvs() {
+    let gradient = Texture::uv_gradient();
+    assert_eq!(gradient.width(), GRADIENT_SIZE);
+    assert_eq!(gradient.sample(0.0, 0.0), Color::rgb(0, 0, 0));
+    assert_eq!(gradient.sample(0.5, 0.25), Color::rgb(128, 64, 0));
+    assert_eq!(gradient.sample(0.999, 0.999), Color::rgb(255, 255, 0));
+    assert_eq!(gradient.texel(10, 200), Color::rgb(10, 200, 0));
+}
+
+#[test]
+fn grids_draw_lines_along_cell_edges() {
+    let grid = Texture::grid(2, RED, BLUE);
+    assert_eq!(grid.width(), GRID_CELL);
+    assert_eq!(grid.texel(0, 15), BLUE);
+    assert_eq!(grid.texel(1, 15), BLUE);
+    assert_eq!(grid.texel(15, 1), BLUE);
+    assert_eq!(grid.texel(2, 2), RED);
+    assert_eq!(grid.texel(31, 31), RED);
+    // The next cell's line follows straight after.
+    assert_eq!(grid.texel(32, 15), BLUE);
+    assert_eq!(Texture::grid(0, RED, BLUE).texel(0, 0), RED);
+}
+
+#[test]
+fn empty_textures_get_one_texel() {
+    let texture = Texture::checker(0, RED, BLUE);
+    assert_eq!((texture.width(), texture.height()), (1, 1));
+    assert_eq!(texture.sample(0.4, 12.0), RED);
+}
+
+#[test]
+fn bilinear_samples_at_texel_centres_are_exact() {
+    for texture in [
+        Texture::checker(7, RED, BLUE),
+        Texture::uv_gradient(),
+        Texture::grid(3, RED, BLUE),
+    ] {
+        let (w, h) = (texture.width(), texture.height());
+        let bilinear = texture.clone().with_filter(FilterMode::Bilinear);
+        for (x, y) in [(0, 0), (1, 0), (3, 2), (w - 1, h - 1), (w / 2, h - 1)] {
+            let (u, v) = ((x as f32 + 0.5) / w as f32, (y as f32 + 0.5) / h as f32);
+            assert_eq!(bilinear.sample(u, v), texture.texel(x as i64, y as i64));
+        }
+    }
+}
+
+#[test]
+fn bilinear_samples_blend_between_centres() {
+    let gradient = Texture::uv_gradient().with_filter(FilterMode::Bilinear);
+    let size = GRADIENT_SIZE as f32;
+    // Halfway between the centres of texels 10 and 11, a quarter of the
+    // way from row 20 to 21.
+    assert_eq!(
+        gradient.sample(11.0 / size, 20.75 / size),
+        Color::rgb(11, 20, 0)
+    );
+    let checker = Texture::checker(2, Color::rgb(0, 0, 0), Color::rgb(200, 100, 0))
+        .with_filter(FilterMode::Bilinear);
+    assert_eq!(checker.sample(0.5, 0.25), Color::rgb(100, 50, 0));
+}
+
+#[test]
+fn wrap_modes_handle_negative_uvs() {
+    let gradient = Texture::uv_gradient();
+    let at = |wrap, u: f32| gradient.clone().with_wrap(wrap).sample(u, 0.0).r;
+    let texel = 1.0 / GRADIENT_SIZE as f32;
+    assert_eq!(at(WrapMode::Repeat, -0.5 * texel), 255);
+    assert_eq!(at(WrapMode::Clamp, -0.5 * texel), 0);
+    assert_eq!(at(WrapMode::Clamp, -3.0), 0);
+    assert_eq!(at(WrapMode::Clamp, 7.0), 255);
+    assert_eq!(at(WrapMode::Mirror, -0.5 * texel), 0);
+    assert_eq!(at(WrapMode::Mirror, -2.5 * texel), 2);
+    assert_eq!(at(WrapMode::Mirror, 1.0 + 0.5 * texel), 255);
+    assert_eq!(at(WrapMode::Mirror, 2.0 + 3.5 * texel), 3);
+}
+
+#[test]
+fn clamped_bilinear_edges_keep_the_edge_texel() {
+    let checker = Texture::checker(4, RED, BLUE)
+        .with_filter(FilterMode::Bilinear)
+        .with_wrap(WrapMode::Clamp);
+    assert_eq!(checker.sample(0.0, 0.0), RED);
+    assert_eq!(checker.sample(-1.0, -1.0), RED);
+    // Repeating blends the corner with the opposite edges instead.
+    let repeat = checker.clone().with_wrap(WrapMode::Repeat);
+    assert_ne!(repeat.sample(0.0, 0.0), RED);
+}
+
+const WHITE: Color = Color::rgb(255, 255, 255);
+
+/// A 4 by 4 quad facing the camera, under a white material showing
+/// `texture`, `unlit` or not.
+fn textured_quad(texture: Texture, unlit: bool) -> (World, Object) {
+    let mut world = world(Vec::new());
+    let texture = world.materials.add_texture(texture);
+    let material = world.materials.insert(
+        "textured",
+        Material {
+            unlit,
+            texture: Some(texture),
+            ..Material::new(WHITE)
+        },
+    );
+    let quad = object(QuadMesh::new(4.0, 4.0, BLUE), 0.0, 0.0, 0.0).with_material(material);
+    (world, quad)
+}
+
+fn draw(mut world: World, quad: Object) -> Vec<u8> {
+    world.add_object(quad).unwrap();
+    render(&mut world)
+}
+
+fn rgba(color: Color) -> [u8; 4] {
+    [color.r, color.g, color.b, color.a]
+}
+
+#[test]
+fn quads_show_their_texture_the_right_way_round() {
+    let (world, quad) = textured_quad(Texture::checker(2, RED, BLUE), true);
+    let frame = draw(world, quad);
+    let (left, right) = (WIDTH / 2 - 10, WIDTH / 2 + 10);
+    let (top, bottom) = (HEIGHT / 2 - 10, HEIGHT / 2 + 10);
+    assert_eq!(pixel(&frame, left, top), rgba(RED));
+    assert_eq!(pixel(&frame, right, top), rgba(BLUE));
+    assert_eq!(pixel(&frame, left, bottom), rgba(BLUE));
+    assert_eq!(pixel(&frame, right, bottom), rgba(RED));
+}
+
+#[test]
+fn textures_are_tinted_and_lit_like_the_material_color() {
+    let green = Color::rgb(40, 200, 90);
+    let (world, quad) = textured_quad(Texture::checker(1, green, green), false);
+    let textured = draw(world, quad);
+
+    let mut plain = world_with_quad_in(Material::new(green));
+    assert_eq!(textured, render(&mut plain));
+}
+
+fn world_with_quad_in(material: Material) -> World {
+    let mut world = world(Vec::new());
+    let material = world.materials.insert("plain", material);
+    world
+        .add_object(object(QuadMesh::new(4.0, 4.0, BLUE), 0.0, 0.0, 0.0).with_material(material))
+        .unwrap();
+    world
+}
+
+#[test]
+fn filter_modes_reach_the_frame() {
+    // Red on the left half of the texture, blue on the right.
+    let halves = Texture::from_fn(2, 1, |x, _| if x == 0 { RED } else { BLUE });
+    let middle = |filter| {
+        let (world, quad) = textured_quad(halves.clone().with_filter(filter), true);
+        pixel(&draw(world, quad), WIDTH / 2, HEIGHT / 2)
+    };
+    let nearest = middle(FilterMode::Nearest);
+    assert!(nearest == rgba(RED) || nearest == rgba(BLUE), "{nearest:?}");
+    let bilinear = middle(FilterMode::Bilinear);
+    assert!(
+        bilinear[0].abs_diff(125) < 20 && bilinear[2].abs_diff(125) < 20,
+        "{bilinear:?}"
+    );
+}
+
+#[test]
+fn wrap_modes_reach_the_frame() {
+    let halves = Texture::from_fn(2, 1, |x, _| if x == 0 { RED } else { BLUE })
+        .with_filter(FilterMode::Bilinear);
+    // The leftmost column of the quad, a few texels' worth from its edge.
+    let left_edge = |wrap| {
+        let (world, quad) = textured_quad(halves.clone().with_wrap(wrap), true);
+        let frame = draw(world, quad);
+        let background = pixel(&frame, 0, HEIGHT / 2);
+        let x = (0..WIDTH)
+            .find(|&x| pixel(&frame, x, HEIGHT / 2) != background)
+            .expect("the quad is on screen");
+        pixel(&frame, x, HEIGHT / 2)
+    };
+    assert_eq!(left_edge(WrapMode::Clamp), rgba(RED));
+    // Repeating, the left edge blends into the right half.
+    assert!(left_edge(WrapMode::Repeat)[2] > 30 + 40);
+}
+
+#[test]
+fn texture_coordinates_follow_perspective() {
+    // Red over blue, meeting across the middle of the quad.
+    let halves = Texture::from_fn(1, 2, |_, y| if y == 0 { RED } else { BLUE });
+    let (world, mut quad) = textured_quad(halves, true);
+    // Tip the top away from the camera. The middle of the quad still
+    // projects to the middle of the frame, but the far half looks smaller,
+    // so interpolating UVs on screen would put the seam below it.
+    quad.rotation = UnitQuaternion::from_axis_angle(&Vector3::x_axis(), FRAC_PI_3);
+    let frame = draw(world, quad);
+    assert_eq!(pixel(&frame, WIDTH / 2, HEIGHT / 2 - 2), rgba(RED));
+    assert_eq!(pixel(&frame, WIDTH / 2, HEIGHT / 2 + 2), rgba(BLUE));
+}
+
+#[test]
+fn debug_views_leave_textures_out() {
+    let (mut world, quad) = textured_quad(Texture::checker(2, RED, BLUE), true);
+    world.render_mode = engine::RenderMode::ObjectId;
+    let frame = draw(world, quad);
+    let (left, right) = (WIDTH / 2 - 10, WIDTH / 2 + 10);
+    assert_eq!(
+        pixel(&frame, left, HEIGHT / 2 - 10),
+        pixel(&frame, right, HEIGHT / 2 - 10)
+    );
+}
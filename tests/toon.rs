@@ -0,0 +1,50 @@
+mod common;
+
+use std::collections::HashSet;
+
+use common::{render, world};
+
+use engine::ShadingModel;
+use engine::impostor::{Sphere, SphereImpostor};
+use engine::mesh::Color;
+use nalgebra::Point3;
+
+#[test]
+fn toon_bands_snap_diffuse() {
+    let toon = ShadingModel::Toon { bands: 3 };
+    assert_eq!(toon.diffuse(0.0), 0.0);
+    assert_eq!(toon.diffuse(0.2), 0.0);
+    assert_eq!(toon.diffuse(0.5), 0.5);
+    assert_eq!(toon.diffuse(0.9), 1.0);
+    assert_eq!(toon.diffuse(1.0), 1.0);
+    assert_eq!(ShadingModel::Lambert.diffuse(0.3), 0.3);
+    assert_eq!(ShadingModel::Toon { bands: 1 }.diffuse(0.1), 1.0);
+}
+
+#[test]
+fn toon_sphere_has_few_flat_shades_and_no_black() {
+    let mut world = world(vec![]);
+    world.impostors.push(SphereImpostor::new(vec![Sphere {
+        center: Point3::origin(),
+        radius: 2.0,
+        color: Color {
+            r: 200,
+            g: 200,
+            b: 200,
+            a: 255,
+        },
+    }]));
+    let distinct = |frame: &[u8]| -> HashSet<[u8; 4]> {
+        frame
+            .chunks(4)
+            .filter(|p| p[3] == 255 && *p != [255, 255, 255, 255])
+            .map(|p| [p[0], p[1], p[2], p[3]])
+            .collect()
+    };
+    let smooth = distinct(&render(&mut world));
+
+    world.shading_model = ShadingModel::Toon { bands: 3 };
+    let toon = distinct(&render(&mut world));
+    assert!(toon.len() < smooth.len());
+    assert!(toon.iter().all(|p| p[0] > 0));
+}
@@ -0,0 +1,144 @@
+use engine::mesh::{Color, Mesh, Triangle};
+use engine::{Camera, Light, LightKind, Object, World};
+use nalgebra::{Perspective3, Point3};
+
+const WIDTH: u32 = 64;
+const HEIGHT: u32 = 64;
+
+/// A camera facing quad in the z=0 plane.
+struct Quad {
+    verts: Vec<Point3<f32>>,
+    tris: Vec<Triangle>,
+}
+
+impl Quad {
+    fn new(half: f32, [r, g, b, a]: [u8; 4]) -> Self {
+        Self {
+            verts: vec![
+                Point3::new(-half, -half, 0.0),
+                Point3::new(half, -half, 0.0),
+                Point3::new(half, half, 0.0),
+                Point3::new(-half, half, 0.0),
+            ],
+            tris: vec![
+                Triangle {
+                    v1: 0,
+                    v2: 1,
+                    v3: 2,
+                    color: Color { r, g, b, a },
+                },
+                Triangle {
+                    v1: 0,
+                    v2: 2,
+                    v3: 3,
+                    color: Color { r, g, b, a },
+                },
+            ],
+        }
+    }
+}
+
+impl Mesh for Quad {
+    fn verts(&self) -> &[Point3<f32>] {
+        &self.verts
+    }
+
+    fn tris(&self) -> &Vec<Triangle> {
+        &self.tris
+    }
+}
+
+fn quad_stack() -> World {
+    const COLORS: [[u8; 4]; 4] = [
+        [255, 0, 0, 128],
+        [0, 255, 0, 128],
+        [0, 0, 255, 128],
+        [255, 255, 0, 64],
+    ];
+    let models = (0..20)
+        .map(|i| {
+            Object::new(
+                Quad::new(0.5 + 0.05 * i as f32, COLORS[i % COLORS.len()]),
+                0.0,
+                0.0,
+                0.0,
+            )
+        })
+        .collect();
+    World::new(
+        Camera::looking_at(Point3::new(0.0, 0.0, -3.0), Point3::new(0.0, 0.0, 0.0)),
+        Light {
+            position: Point3::new(0.0, 0.0, -1.0),
+            target: Point3::new(0.0, 0.0, 0.0),
+            kind: LightKind::Directional,
+            intensity: 1.0,
+            shadow_resolution: 0,
+            shadow_bias: 0.05,
+        },
+        Perspective3::new((WIDTH as f32) / (HEIGHT as f32), 1.0, 0.1, 200.0).to_homogeneous(),
+        models,
+    )
+}
+
+fn render(world: &mut World) -> Vec<u8> {
+    let mut frame = vec![0; (WIDTH * HEIGHT * 4) as usize];
+    let view = world.camera.generate_view_mat();
+    world.draw(view, &mut frame, WIDTH, HEIGHT);
+    frame
+}
+
+#[test]
+fn coincident_transparent_quads_are_deterministic() {
+    let mut world = quad_stack();
+    let reference = render(&mut world);
+    assert_ne!(reference, vec![255; reference.len()]);
+
+    for threads in 1..=8 {
+        world.threads = threads;
+        for _ in 0..100 {
+            assert!(render(&mut world) == reference, "threads = {threads}");
+        }
+    }
+}
+
+#[test]
+fn translucent_quad_shows_mesh_behind_it() {
+    use engine::mesh::p_hack::PHackMesh;
+    use engine::mesh::quad::QuadMesh;
+
+    let quad = || {
+        Object::new(
+            QuadMesh::new(
+                3.0,
+                3.0,
+                Color {
+                    r: 64,
+                    g: 160,
+                    b: 255,
+                    a: 128,
+                },
+            ),
+            0.0,
+            0.0,
+            -1.5,
+        )
+    };
+    let p_hack = || Object::new(PHackMesh::new(), 0.0, 0.0, 0.0);
+    let center = |frame: &[u8]| -> Vec<u8> {
+        let i = (((HEIGHT / 2) * WIDTH + WIDTH / 2) * 4) as usize;
+        frame[i..i + 4].to_vec()
+    };
+
+    let mut world = quad_stack();
+    world.models = vec![p_hack()];
+    let mesh_only = render(&mut world);
+    world.models = vec![quad()];
+    let quad_only = render(&mut world);
+    // Insert the quad first so only the deferred pass can put it on top.
+    world.models = vec![quad(), p_hack()];
+    let both = render(&mut world);
+
+    assert_ne!(center(&both), center(&mesh_only));
+    assert_ne!(center(&both), center(&quad_only));
+    assert_eq!(center(&both)[3], 255);
+}
@@ -0,0 +1,81 @@
+mod common;
+
+use std::rc::Rc;
+
+use common::{object, render, world};
+
+use engine::mesh::Color;
+use engine::mesh::Mesh;
+use engine::mesh::cube::CubeMesh;
+use engine::mesh::quad::QuadMesh;
+use engine::{CullMode, Object, World};
+use nalgebra::{UnitQuaternion, Vector3};
+
+/// FNV-1a hash of a frame, stable across platforms and Rust versions.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &byte| {
+        (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+/// One cube drawn three times, turned and sized differently, beside a
+/// square facing the camera and one facing away, with the first cube
+/// selected so its outline is drawn too.
+fn scene() -> World {
+    let cube: Rc<dyn Mesh> = Rc::new(CubeMesh::new(Color::rgb(200, 120, 40)));
+    let turned = |axis: Vector3<f32>, angle: f32| {
+        UnitQuaternion::from_axis_angle(&nalgebra::Unit::new_normalize(axis), angle)
+    };
+    let instances = [
+        (
+            -1.6,
+            0.4,
+            1.0,
+            turned(Vector3::new(1.0, 1.0, 0.0), 0.7),
+            1.0,
+        ),
+        (
+            1.4,
+            -0.5,
+            2.0,
+            turned(Vector3::new(0.0, 1.0, 0.3), 2.1),
+            0.8,
+        ),
+        (0.2, 1.2, 4.0, turned(Vector3::new(0.4, 0.2, 1.0), 4.0), 1.5),
+    ];
+    let mut models: Vec<Object> = instances
+        .into_iter()
+        .map(|(x, y, z, rotation, scale)| Object {
+            rotation,
+            scale,
+            ..Object::instance(Rc::clone(&cube), x, y, z)
+        })
+        .collect();
+    let square = |color, angle| Object {
+        rotation: turned(Vector3::y(), angle),
+        ..object(QuadMesh::new(1.5, 1.5, color), 0.0, -1.2, 0.5)
+    };
+    models.push(square(Color::rgb(40, 160, 220), 0.3));
+    models.push(square(Color::rgb(220, 40, 160), std::f32::consts::PI + 0.3));
+    let mut world = world(models);
+    world.selected = Some(0);
+    world
+}
+
+/// The scene as drawn before each vertex was transformed just once, with
+/// back faces culled, front faces culled and nothing culled.
+const EXPECTED: [(CullMode, u64); 3] = [
+    (CullMode::Back, 0x489b_278f_db4d_f177),
+    (CullMode::Front, 0x4287_2a67_8a4c_7cfc),
+    (CullMode::None, 0xe7a6_725f_fb44_7833),
+];
+
+#[test]
+fn vertex_transforms_leave_the_frame_unchanged() {
+    let mut world = scene();
+    for (cull_mode, expected) in EXPECTED {
+        world.cull_mode = cull_mode;
+        let hash = fnv1a(&render(&mut world));
+        assert_eq!(hash, expected, "{cull_mode:?}");
+    }
+}
@@ -0,0 +1,149 @@
+mod common;
+
+use common::{HEIGHT, WHITE, WIDTH, object, pixel, render, world};
+use engine::depth::DepthConvention;
+use engine::mesh::Color;
+use engine::mesh::cube::CubeMesh;
+use engine::viewport::{Projection, Viewport};
+use engine::{Camera, World};
+use nalgebra::{Matrix4, Point3};
+
+fn scene() -> World {
+    world(vec![
+        object(CubeMesh::new(Color::rgb(200, 120, 40)), 0.0, 0.0, 0.0),
+        object(CubeMesh::new(Color::rgb(40, 180, 220)), 2.0, 0.0, 3.0),
+    ])
+}
+
+fn draw(world: &mut World, width: u32, height: u32) -> Vec<u8> {
+    let mut frame = vec![0; (width * height * 4) as usize];
+    let view = world.camera.generate_view_mat();
+    world.draw(view, &mut frame, width, height);
+    frame
+}
+
+const TOP_DOWN: Projection = Projection::Orthographic {
+    height: 8.0,
+    near: 1.0,
+    far: 40.0,
+};
+
+fn top_down(world: &mut World) {
+    world.cameras.push(Camera::looking_at(
+        Point3::new(0.0, 20.0, 0.0),
+        Point3::origin(),
+    ));
+}
+
+#[test]
+fn rects_scale_with_the_frame() {
+    let viewport = Viewport {
+        rect: (0.5, 0.0, 0.5, 0.5),
+        ..Viewport::default()
+    };
+    assert_eq!(viewport.pixel_rect(64, 64), (32, 0, 32, 32));
+    assert_eq!(viewport.pixel_rect(128, 100), (64, 0, 64, 50));
+
+    let overhanging = Viewport {
+        rect: (0.75, -0.25, 0.5, 0.5),
+        ..Viewport::default()
+    };
+    assert_eq!(overhanging.pixel_rect(64, 64), (48, 0, 16, 16));
+}
+
+#[test]
+fn orthographic_depth_spans_the_drawn_range() {
+    for (convention, near, far) in [
+        (DepthConvention::Standard, 0.0, 1.0),
+        (DepthConvention::Reversed, 1.0, 0.0),
+    ] {
+        let matrix = TOP_DOWN.matrix(&Matrix4::identity(), 2.0, convention);
+        let at = |distance: f32| matrix.transform_point(&Point3::new(1.0, 2.0, -distance));
+        assert!((at(1.0).z - near).abs() < 1e-6, "{convention:?}");
+        assert!((at(40.0).z - far).abs() < 1e-6, "{convention:?}");
+        // 8 units tall, twice as wide.
+        assert!((at(1.0).x - 0.125).abs() < 1e-6 && (at(1.0).y - 0.5).abs() < 1e-6);
+    }
+}
+
+#[test]
+fn split_screen_halves_match_frames_of_their_own() {
+    let mut world = scene();
+    top_down(&mut world);
+    let perspective = Projection::Perspective {
+        fovy: 1.0,
+        near: 0.1,
+        far: 200.0,
+    };
+    world.viewports = vec![Viewport {
+        camera_index: 1,
+        projection: TOP_DOWN,
+        ..Viewport::default()
+    }];
+    let above = draw(&mut world, WIDTH / 2, HEIGHT);
+    world.viewports[0].camera_index = 0;
+    world.viewports[0].projection = perspective;
+    let ahead = draw(&mut world, WIDTH / 2, HEIGHT);
+    assert_ne!(above, ahead);
+
+    world.viewports = vec![
+        Viewport {
+            rect: (0.0, 0.0, 0.5, 1.0),
+            camera_index: 0,
+            projection: perspective,
+        },
+        Viewport {
+            rect: (0.5, 0.0, 0.5, 1.0),
+            camera_index: 1,
+            projection: TOP_DOWN,
+        },
+    ];
+    let split = draw(&mut world, WIDTH, HEIGHT);
+    let half = (WIDTH / 2 * 4) as usize;
+    for (y, row) in split.chunks_exact((WIDTH * 4) as usize).enumerate() {
+        let own = y * half..(y + 1) * half;
+        assert_eq!(&row[..half], &ahead[own.clone()], "row {y}");
+        assert_eq!(&row[half..], &above[own], "row {y}");
+    }
+}
+
+#[test]
+fn minimap_covers_only_its_corner() {
+    let mut world = scene();
+    let plain = render(&mut world);
+    top_down(&mut world);
+    world.viewports.push(Viewport {
+        rect: (0.75, 0.0, 0.25, 0.25),
+        camera_index: 1,
+        projection: TOP_DOWN,
+    });
+    let with_map = render(&mut world);
+    let mut map_changed = false;
+    for y in 0..HEIGHT {
+        for x in 0..WIDTH {
+            if x >= 48 && y < 16 {
+                map_changed |= pixel(&with_map, x, y) != pixel(&plain, x, y);
+            } else {
+                assert_eq!(pixel(&with_map, x, y), pixel(&plain, x, y), "({x}, {y})");
+            }
+        }
+    }
+    assert!(map_changed);
+    assert!(
+        world.stats().objects_drawn > 2,
+        "both viewports are counted"
+    );
+}
+
+#[test]
+fn viewports_of_missing_cameras_are_skipped() {
+    let mut world = scene();
+    world.viewports = vec![Viewport {
+        rect: (0.0, 0.0, 0.5, 0.5),
+        camera_index: 3,
+        projection: TOP_DOWN,
+    }];
+    let frame = render(&mut world);
+    assert!(frame.chunks_exact(4).all(|p| p == WHITE));
+    assert_eq!(world.stats().objects_drawn, 0);
+}
@@ -0,0 +1,138 @@
+mod common;
+
+use common::{HEIGHT, WHITE, WIDTH, object, pixel, render, world};
+use engine::World;
+use engine::mesh::Color;
+use engine::mesh::quad::QuadMesh;
+use engine::world::ALL_LAYERS;
+use nalgebra::Point3;
+
+const RED: Color = Color::rgb(220, 30, 30);
+const BLUE: Color = Color::rgb(30, 30, 220);
+const GRAY: Color = Color::rgb(200, 200, 200);
+
+/// A red square in front of a blue one, on layers 0 and 1.
+fn two_squares() -> World {
+    world(vec![
+        object(QuadMesh::new(2.0, 2.0, RED), 0.0, 0.0, 0.0),
+        object(QuadMesh::new(4.0, 4.0, BLUE), 0.0, 0.0, 1.0).with_layer(1),
+    ])
+}
+
+fn centre(world: &mut World) -> [u8; 4] {
+    pixel(&render(world), WIDTH / 2, HEIGHT / 2)
+}
+
+fn is_red(p: [u8; 4]) -> bool {
+    p[0] > p[2]
+}
+
+#[test]
+fn invisible_objects_are_not_drawn() {
+    let mut world = two_squares();
+    assert!(is_red(centre(&mut world)));
+    assert_eq!(world.stats().objects_hidden, 0);
+
+    world.models[0].visible = false;
+    assert!(!is_red(centre(&mut world)));
+    assert_eq!(world.stats().objects_hidden, 1);
+    assert_eq!(world.stats().objects_drawn, 1);
+
+    world.models[1].visible = false;
+    assert_eq!(centre(&mut world), WHITE);
+}
+
+#[test]
+fn layers_hide_whole_groups() {
+    let mut world = two_squares();
+    world
+        .models
+        .push(object(QuadMesh::new(0.5, 0.5, RED), 1.5, 0.0, 0.5).with_layer(1));
+    assert_eq!(world.visible_layers, ALL_LAYERS);
+
+    world.visible_layers &= !(1 << 1);
+    let frame = render(&mut world);
+    assert_eq!(world.stats().objects_hidden, 2);
+    assert_eq!(pixel(&frame, 4, HEIGHT / 2), WHITE);
+    assert!(is_red(pixel(&frame, WIDTH / 2, HEIGHT / 2)));
+
+    world.visible_layers = 1 << 1;
+    assert!(!is_red(centre(&mut world)));
+    assert_eq!(world.stats().objects_hidden, 1);
+}
+
+#[test]
+fn layers_past_the_mask_are_never_shown() {
+    let square = object(QuadMesh::new(2.0, 2.0, RED), 0.0, 0.0, 0.0);
+    assert!(square.shown(1));
+    let square = square.with_layer(31);
+    assert!(square.shown(ALL_LAYERS) && !square.shown(1));
+    assert!(!square.with_layer(32).shown(ALL_LAYERS));
+}
+
+#[test]
+fn hidden_objects_cast_no_shadows() {
+    let brightness = |world: &mut World| -> u32 {
+        pixel(&render(world), 24, HEIGHT / 2)[..3]
+            .iter()
+            .map(|&c| c as u32)
+            .sum()
+    };
+    let mut world = world(vec![
+        object(QuadMesh::grid(10.0, 10.0, 20, GRAY), 0.0, 0.0, 2.0),
+        object(QuadMesh::new(1.0, 1.0, GRAY), 0.0, 0.0, 0.0).with_layer(2),
+    ]);
+    world.light.position = Point3::new(-4.0, 0.0, -10.0);
+    world.light.target = Point3::origin();
+    world.light.shadow_resolution = 256;
+    let shadowed = brightness(&mut world);
+    world.visible_layers = !(1 << 2);
+    assert!(brightness(&mut world) > shadowed);
+}
+
+#[test]
+fn picking_skips_hidden_objects_unless_asked() {
+    let mut world = two_squares();
+    let (x, y) = (WIDTH as f32 / 2.0, HEIGHT as f32 / 2.0);
+    assert_eq!(world.pick(x, y, WIDTH, HEIGHT), Some(0));
+
+    world.models[0].visible = false;
+    assert_eq!(world.pick(x, y, WIDTH, HEIGHT), Some(1));
+    let ray = world.screen_ray(x, y, WIDTH, HEIGHT);
+    assert_eq!(
+        world
+            .raycast_including_hidden(&ray, false)
+            .map(|hit| hit.object),
+        Some(0)
+    );
+
+    world.visible_layers = 0;
+    assert_eq!(world.pick(x, y, WIDTH, HEIGHT), None);
+}
+
+#[test]
+fn selection_steps_over_hidden_objects() {
+    let mut world = two_squares();
+    world
+        .models
+        .push(object(QuadMesh::new(1.0, 1.0, RED), 0.0, 0.0, 0.0));
+    world.models[1].visible = false;
+    assert_eq!(world.next_shown(None), Some(0));
+    assert_eq!(world.next_shown(Some(0)), Some(2));
+    assert_eq!(world.next_shown(Some(2)), Some(0));
+    world.visible_layers = 0;
+    assert_eq!(world.next_shown(Some(0)), None);
+}
+
+#[test]
+fn hiding_changes_the_fingerprint() {
+    let mut world = two_squares();
+    let view = world.camera.generate_view_mat();
+    let shown = world.fingerprint(&view);
+    world.models[0].visible = false;
+    let hidden = world.fingerprint(&view);
+    assert_ne!(shown, hidden);
+    world.models[0].visible = true;
+    world.visible_layers = 1;
+    assert_ne!(world.fingerprint(&view), shown);
+}
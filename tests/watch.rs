@@ -0,0 +1,124 @@
+mod common;
+
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+
+use common::world;
+use engine::World;
+use engine::watch::FileWatcher;
+use nalgebra::Matrix4;
+
+fn temp_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("shapes-watch-{}-{name}", std::process::id()))
+}
+
+/// Write `text` to `path` and date it `age` seconds after the epoch, so
+/// changes show even on file systems with coarse timestamps.
+fn write_at(path: &Path, text: &str, age: u64) {
+    std::fs::write(path, text).unwrap();
+    File::options()
+        .write(true)
+        .open(path)
+        .unwrap()
+        .set_modified(SystemTime::UNIX_EPOCH + Duration::from_secs(age))
+        .unwrap();
+}
+
+/// An OBJ file of `faces` separate triangles.
+fn obj(faces: usize) -> String {
+    (0..faces)
+        .map(|i| {
+            format!(
+                "v {i} 0 0\nv {i} 1 0\nv {i} 0 1\nf {} {} {}\n",
+                3 * i + 1,
+                3 * i + 2,
+                3 * i + 3
+            )
+        })
+        .collect()
+}
+
+fn finish_loads(world: &mut World) {
+    let start = Instant::now();
+    while world.loads_pending() > 0 && start.elapsed() < Duration::from_secs(10) {
+        world.poll_loads();
+        std::thread::sleep(Duration::from_millis(1));
+    }
+    assert_eq!(world.loads_pending(), 0, "loads timed out");
+}
+
+fn triangles(world: &World) -> usize {
+    world.models[0].mesh.primitives().triangles().count()
+}
+
+#[test]
+fn watchers_report_files_that_change() {
+    let (a, b) = (temp_path("a.txt"), temp_path("b.txt"));
+    write_at(&a, "a", 1000);
+    write_at(&b, "b", 1000);
+    let mut watcher = FileWatcher::new(Duration::ZERO);
+    watcher.watch(&a);
+    watcher.watch(&b);
+    watcher.watch(&a);
+    assert_eq!(watcher.files().count(), 2);
+    assert!(watcher.changed().is_empty());
+
+    write_at(&b, "b again", 2000);
+    assert_eq!(watcher.changed(), vec![b.clone()]);
+    assert!(watcher.changed().is_empty());
+
+    // Deleting counts, and so does coming back.
+    std::fs::remove_file(&a).unwrap();
+    assert_eq!(watcher.changed(), vec![a.clone()]);
+    write_at(&a, "a", 1000);
+    assert_eq!(watcher.changed(), vec![a.clone()]);
+
+    watcher.unwatch(&b);
+    write_at(&b, "b", 3000);
+    assert!(watcher.changed().is_empty());
+    std::fs::remove_file(a).unwrap();
+    std::fs::remove_file(b).unwrap();
+}
+
+#[test]
+fn watchers_check_at_most_once_an_interval() {
+    let path = temp_path("slow.txt");
+    write_at(&path, "x", 1000);
+    let mut watcher = FileWatcher::new(Duration::from_secs(3600));
+    watcher.watch(&path);
+    assert!(watcher.changed().is_empty());
+    write_at(&path, "y", 2000);
+    assert!(watcher.changed().is_empty());
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn changed_models_are_swapped_in_place() {
+    let path = temp_path("model.obj");
+    write_at(&path, &obj(1), 1000);
+    let mut world = world(vec![]);
+    world.load_object_async(&path, Matrix4::new_translation(&nalgebra::Vector3::x()));
+    finish_loads(&mut world);
+    assert_eq!(triangles(&world), 1);
+    assert_eq!(world.models[0].source.as_deref(), Some(path.as_path()));
+    world.models[0].scale = 3.0;
+
+    write_at(&path, &obj(5), 2000);
+    assert_eq!(world.reload_sources(std::slice::from_ref(&path)), 1);
+    finish_loads(&mut world);
+    assert_eq!(triangles(&world), 5);
+    assert_eq!(
+        (world.models[0].offset_x, world.models[0].scale),
+        (1.0, 3.0)
+    );
+
+    // A broken file keeps the last good mesh.
+    write_at(&path, "v 0 0 0\nf 1 2 3\n", 3000);
+    world.reload_sources(std::slice::from_ref(&path));
+    finish_loads(&mut world);
+    assert_eq!(triangles(&world), 5);
+
+    assert_eq!(world.reload_sources(&[temp_path("other.obj")]), 0);
+    std::fs::remove_file(path).unwrap();
+}